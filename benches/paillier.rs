@@ -0,0 +1,87 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use tofn::crypto_tools::paillier::{
+    keygen_unsafe,
+    zk::{
+        range::{Statement, StatementWc, Witness},
+        ZkSetup,
+    },
+    Plaintext,
+};
+use tofn::{collections::TypedUsize, crypto_tools::k256_serde::SecretScalar};
+
+pub fn paillier(c: &mut Criterion) {
+    let mut rng = chacha_rng();
+    let (ek, dk) = keygen_unsafe(&mut rng).unwrap();
+    let msg = SecretScalar::random(&mut rng);
+    let plaintext = Plaintext::from_scalar(msg.as_ref());
+    let (ciphertext, randomness) = ek.encrypt(&plaintext);
+
+    let mut g = c.benchmark_group("paillier");
+    g.sample_size(10);
+
+    g.bench_function("keygen (unsafe primes)", |b| {
+        b.iter(|| keygen_unsafe(&mut rng).unwrap())
+    });
+    g.bench_function("encrypt", |b| b.iter(|| ek.encrypt(black_box(&plaintext))));
+    g.bench_function("encrypt with randomness", |b| {
+        b.iter(|| ek.encrypt_with_randomness(black_box(&plaintext), black_box(&randomness)))
+    });
+    g.bench_function("decrypt", |b| b.iter(|| dk.decrypt(black_box(&ciphertext))));
+    g.bench_function("homomorphic mul", |b| {
+        b.iter(|| ek.mul(black_box(&ciphertext), black_box(&plaintext)))
+    });
+    g.finish();
+}
+
+pub fn range_proof(c: &mut Criterion) {
+    let mut rng = chacha_rng();
+    let (ek, _) = keygen_unsafe(&mut rng).unwrap();
+    let (zkp, _) = ZkSetup::new_unsafe(&mut rng, b"bench").unwrap();
+
+    let msg = SecretScalar::random(&mut rng);
+    let (ciphertext, randomness) = ek.encrypt(&Plaintext::from_scalar(msg.as_ref()));
+    let stmt = Statement {
+        prover_id: TypedUsize::from_usize(0),
+        verifier_id: TypedUsize::from_usize(1),
+        ciphertext: &ciphertext,
+        ek: &ek,
+    };
+    let wit = Witness {
+        msg: msg.as_ref(),
+        randomness: &randomness,
+    };
+    let msg_g = k256::ProjectivePoint::GENERATOR * msg.as_ref();
+    let stmt_wc = StatementWc {
+        stmt: stmt.clone(),
+        msg_g: &msg_g,
+        g: &k256::ProjectivePoint::GENERATOR,
+    };
+
+    let proof = zkp.range_proof(&stmt, &wit);
+    let proof_wc = zkp.range_proof_wc(&stmt_wc, &wit).unwrap();
+
+    let mut g = c.benchmark_group("range-proof");
+    g.sample_size(10);
+
+    g.bench_function("prove", |b| b.iter(|| zkp.range_proof(&stmt, &wit)));
+    g.bench_function("verify", |b| {
+        b.iter(|| assert!(zkp.verify_range_proof(&stmt, &proof)))
+    });
+    g.bench_function("prove wc", |b| {
+        b.iter(|| zkp.range_proof_wc(&stmt_wc, &wit).unwrap())
+    });
+    g.bench_function("verify wc", |b| {
+        b.iter(|| assert!(zkp.verify_range_proof_wc(&stmt_wc, &proof_wc)))
+    });
+    g.finish();
+}
+
+criterion_group!(benches, paillier, range_proof);
+criterion_main!(benches);
+
+// initialize a deterministic rng to conserve random bits
+fn chacha_rng() -> impl CryptoRng + RngCore {
+    ChaCha20Rng::from_seed([42; 32])
+}
@@ -0,0 +1,169 @@
+//! End-to-end keygen and sign over a lossless simulated network.
+//! Party keygen data uses unsafe primes, so these benches measure the protocols themselves.
+
+use std::convert::{TryFrom, TryInto};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tofn::{
+    collections::{Subset, TypedUsize, VecMap},
+    gg20::{
+        keygen::{
+            create_party_keypair_and_zksetup_unsafe, new_keygen, KeygenPartyId,
+            KeygenPartyShareCounts, KeygenShareId, PartyKeygenData, SecretKeyShare,
+            SecretRecoveryKey,
+        },
+        sign::{new_sign, MessageDigest, SignParties},
+    },
+    sdk::api::{PartyShareCounts, Protocol},
+    test_utils::network_sim::{simulate, NetworkConfig},
+};
+
+/// (share count, threshold): one share per party, `threshold + 1` parties sign
+const SIZES: [(usize, usize); 3] = [(3, 1), (5, 2), (7, 4)];
+
+const SESSION_NONCE: &[u8] = b"bench";
+
+pub fn party_keygen_data(c: &mut Criterion) {
+    let mut g = c.benchmark_group("gg20-party-keygen-data");
+    g.sample_size(10);
+    g.bench_function("unsafe primes", |b| {
+        b.iter(|| {
+            create_party_keypair_and_zksetup_unsafe(
+                TypedUsize::from_usize(0),
+                &secret_recovery_key(0),
+                SESSION_NONCE,
+            )
+            .unwrap()
+        })
+    });
+    g.finish();
+}
+
+pub fn keygen(c: &mut Criterion) {
+    let mut g = c.benchmark_group("gg20-keygen");
+    g.sample_size(10);
+    for (share_count, threshold) in SIZES {
+        let party_share_counts = party_share_counts(share_count);
+        let party_keygen_data = all_party_keygen_data(&party_share_counts);
+        g.bench_with_input(
+            BenchmarkId::from_parameter(format!("n={} t={}", share_count, threshold)),
+            &threshold,
+            |b, &threshold| {
+                b.iter(|| run_keygen(&party_share_counts, threshold, &party_keygen_data))
+            },
+        );
+    }
+    g.finish();
+}
+
+pub fn sign(c: &mut Criterion) {
+    let mut g = c.benchmark_group("gg20-sign");
+    g.sample_size(10);
+    let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+    for (share_count, threshold) in SIZES {
+        let party_share_counts = party_share_counts(share_count);
+        let party_keygen_data = all_party_keygen_data(&party_share_counts);
+        let key_shares = run_keygen(&party_share_counts, threshold, &party_keygen_data);
+
+        let mut sign_parties = SignParties::with_max_size(share_count);
+        for i in 0..=threshold {
+            sign_parties.add(TypedUsize::from_usize(i)).unwrap();
+        }
+
+        g.bench_with_input(
+            BenchmarkId::from_parameter(format!("n={} t={}", share_count, threshold)),
+            &sign_parties,
+            |b, sign_parties| b.iter(|| run_sign(&key_shares, sign_parties, &msg_to_sign)),
+        );
+    }
+    g.finish();
+}
+
+criterion_group!(benches, party_keygen_data, keygen, sign);
+criterion_main!(benches);
+
+fn run_keygen(
+    party_share_counts: &KeygenPartyShareCounts,
+    threshold: usize,
+    party_keygen_data: &VecMap<KeygenPartyId, PartyKeygenData>,
+) -> VecMap<KeygenShareId, SecretKeyShare> {
+    let parties = party_keygen_data
+        .iter()
+        .map(|(party_id, party_keygen_data)| {
+            new_keygen(
+                party_share_counts.clone(),
+                threshold,
+                party_id,
+                0,
+                party_keygen_data,
+                #[cfg(feature = "malicious")]
+                tofn::gg20::keygen::malicious::Behaviour::Honest,
+            )
+            .unwrap()
+        })
+        .collect();
+    outputs(parties)
+}
+
+fn run_sign(
+    key_shares: &VecMap<KeygenShareId, SecretKeyShare>,
+    sign_parties: &Subset<KeygenPartyId>,
+    msg_to_sign: &MessageDigest,
+) {
+    // one share per party: keygen share ids coincide with party ids
+    let parties = sign_parties
+        .iter()
+        .map(|party_id| {
+            let key_share = key_shares
+                .get(TypedUsize::from_usize(party_id.as_usize()))
+                .unwrap();
+            new_sign(
+                key_share.group(),
+                key_share.share(),
+                sign_parties,
+                msg_to_sign,
+                #[cfg(feature = "malicious")]
+                tofn::gg20::sign::malicious::Behaviour::Honest,
+            )
+            .unwrap()
+        })
+        .collect();
+    outputs(parties);
+}
+
+fn outputs<F, K, P>(
+    parties: VecMap<K, Protocol<F, K, P>>,
+) -> VecMap<K, F> {
+    simulate(parties, &NetworkConfig::default())
+        .unwrap()
+        .outputs
+        .map(|output| output.unwrap_or_else(|_| panic!("honest execution failed")))
+}
+
+fn party_share_counts(share_count: usize) -> KeygenPartyShareCounts {
+    PartyShareCounts::from_vec(vec![1; share_count]).unwrap()
+}
+
+fn all_party_keygen_data(
+    party_share_counts: &KeygenPartyShareCounts,
+) -> VecMap<KeygenPartyId, PartyKeygenData> {
+    party_share_counts
+        .iter()
+        .map(|(party_id, _)| {
+            create_party_keypair_and_zksetup_unsafe(
+                party_id,
+                &secret_recovery_key(party_id.as_usize()),
+                SESSION_NONCE,
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+/// return the all-zero array with the first bytes set to the bytes of `index`
+fn secret_recovery_key(index: usize) -> SecretRecoveryKey {
+    let index_bytes = index.to_be_bytes();
+    let mut result = [0; 64];
+    result[..index_bytes.len()].copy_from_slice(&index_bytes);
+    result[..].try_into().unwrap()
+}
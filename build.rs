@@ -0,0 +1,47 @@
+fn main() {
+    #[cfg(feature = "grpc-server")]
+    grpc_server();
+}
+
+/// Generate the server side of tofnd's `GG20` service for the messages in `sdk::grpc_types`.
+#[cfg(feature = "grpc-server")]
+fn grpc_server() {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    let method = |name: &str, route_name: &str, input: &str, output: &str| {
+        Method::builder()
+            .name(name)
+            .route_name(route_name)
+            .input_type(format!("tofn::sdk::grpc_types::{}", input))
+            .output_type(format!("tofn::sdk::grpc_types::{}", output))
+            .codec_path("tonic::codec::ProstCodec")
+    };
+    let service = Service::builder()
+        .name("GG20")
+        .package("tofnd")
+        .method(method("recover", "Recover", "RecoverRequest", "RecoverResponse").build())
+        .method(
+            method("keygen", "Keygen", "MessageIn", "MessageOut")
+                .client_streaming()
+                .server_streaming()
+                .build(),
+        )
+        .method(
+            method("sign", "Sign", "MessageIn", "MessageOut")
+                .client_streaming()
+                .server_streaming()
+                .build(),
+        )
+        .method(
+            method(
+                "key_presence",
+                "KeyPresence",
+                "KeyPresenceRequest",
+                "KeyPresenceResponse",
+            )
+            .build(),
+        )
+        .build();
+
+    Builder::new().build_client(false).compile(&[service]);
+}
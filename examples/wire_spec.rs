@@ -0,0 +1,7 @@
+//! Print the formats of all tofn wire messages as JSON.
+//!
+//! `cargo run --example wire_spec --features wire-spec > wire-spec.json`
+fn main() {
+    let spec = tofn::wire_spec::wire_spec().expect("wire spec");
+    println!("{}", spec.to_json().expect("wire spec json"));
+}
@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use once_cell::sync::Lazy;
+use tofn::fuzzing::{gg20_keygen_msg_in, Fixture};
+
+static FIXTURE: Lazy<Fixture> = Lazy::new(Fixture::new);
+
+fuzz_target!(|data: &[u8]| {
+    gg20_keygen_msg_in(&FIXTURE, data);
+});
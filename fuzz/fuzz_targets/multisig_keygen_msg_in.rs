@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tofn::fuzzing::multisig_keygen_msg_in;
+
+fuzz_target!(|data: &[u8]| {
+    multisig_keygen_msg_in(data);
+});
@@ -0,0 +1,841 @@
+//! Helpers for threshold-signing Bitcoin transactions.
+//!
+//! [legacy_sighash] and [segwit_v0_sighash] compute the digest to pass to `new_sign`
+//! for an input spent via a legacy or a segwit v0 (BIP143) script.
+//! [to_der_with_sighash_type] encodes the resulting signature the way Bitcoin consensus requires.
+//!
+//!
+//! [TaprootSpendInfo] commits a tree of [TapLeaf] scripts to the threshold group key as the
+//! taproot internal key, and provides the output key and the control block of each leaf
+//! that a wallet needs to build a script-path spend.
+//! [taproot_key_path_sighash] and [taproot_script_path_sighash] compute BIP341 sighashes.
+//! Taproot spends require BIP340 Schnorr signatures,
+//! which the ECDSA protocols in this crate cannot produce:
+//! a taproot sighash must be signed by a Schnorr signer holding the (tweaked) group key.
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use k256::{
+    ecdsa::VerifyingKey,
+    elliptic_curve::{sec1::ToEncodedPoint, PrimeField},
+    FieldBytes, ProjectivePoint, Scalar,
+};
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use crate::{
+    crypto_tools::message_digest::MessageDigest,
+    sdk::api::{BytesVec, Signature, TofnFatal, TofnResult},
+};
+
+pub const SIGHASH_ALL: u32 = 0x01;
+pub const SIGHASH_NONE: u32 = 0x02;
+pub const SIGHASH_SINGLE: u32 = 0x03;
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+/// Taproot only: sign as `SIGHASH_ALL` without appending a sighash type byte to the signature
+pub const SIGHASH_DEFAULT: u32 = 0x00;
+
+/// Leaf version of BIP342 tapscript
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+/// Maximum depth of a taproot script tree, from BIP341
+const TAPROOT_CONTROL_MAX_NODE_COUNT: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutPoint {
+    /// Transaction id in internal byte order (the reverse of the usual hex display)
+    pub txid: [u8; 32],
+    pub vout: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxIn {
+    pub previous_output: OutPoint,
+    pub sequence: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOut {
+    /// Value in satoshis
+    pub value: u64,
+    pub script_pubkey: BytesVec,
+}
+
+/// An unsigned transaction: input scripts and witnesses are not needed to compute sighashes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub version: i32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+}
+
+/// Sighash of input `input_index` of `tx` for a legacy (pre-segwit) spend.
+///
+/// `script_code` is the script being executed: the previous output's `scriptPubKey`,
+/// or the redeem script for P2SH, with any `OP_CODESEPARATOR` handling already applied.
+///
+/// Return `TofnFatal` for `SIGHASH_SINGLE` without a corresponding output:
+/// Bitcoin defines that sighash as the constant `1`, and signing it would authorize any transaction.
+pub fn legacy_sighash(
+    tx: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    sighash_type: u32,
+) -> TofnResult<MessageDigest> {
+    check_input_index(tx, input_index)?;
+    let base_type = sighash_type & 0x1f;
+    let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+
+    if base_type == SIGHASH_SINGLE && input_index >= tx.outputs.len() {
+        error!(
+            "SIGHASH_SINGLE input {} has no corresponding output among {}",
+            input_index,
+            tx.outputs.len()
+        );
+        return Err(TofnFatal);
+    }
+
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(&tx.version.to_le_bytes());
+
+    let inputs: Vec<(usize, &TxIn)> = if anyone_can_pay {
+        alloc::vec![(input_index, &tx.inputs[input_index])]
+    } else {
+        tx.inputs.iter().enumerate().collect()
+    };
+    write_compact_size(&mut preimage, inputs.len());
+    for (i, input) in inputs {
+        write_outpoint(&mut preimage, &input.previous_output);
+        if i == input_index {
+            write_bytes(&mut preimage, script_code);
+        } else {
+            write_bytes(&mut preimage, &[]);
+        }
+        let sequence =
+            if i != input_index && (base_type == SIGHASH_NONE || base_type == SIGHASH_SINGLE) {
+                0
+            } else {
+                input.sequence
+            };
+        preimage.extend_from_slice(&sequence.to_le_bytes());
+    }
+
+    match base_type {
+        SIGHASH_NONE => write_compact_size(&mut preimage, 0),
+        SIGHASH_SINGLE => {
+            write_compact_size(&mut preimage, input_index + 1);
+            for _ in 0..input_index {
+                // blank outputs: value -1, empty script
+                preimage.extend_from_slice(&u64::MAX.to_le_bytes());
+                write_bytes(&mut preimage, &[]);
+            }
+            write_output(&mut preimage, &tx.outputs[input_index]);
+        }
+        _ => {
+            write_compact_size(&mut preimage, tx.outputs.len());
+            for output in tx.outputs.iter() {
+                write_output(&mut preimage, output);
+            }
+        }
+    }
+
+    preimage.extend_from_slice(&tx.lock_time.to_le_bytes());
+    preimage.extend_from_slice(&sighash_type.to_le_bytes());
+    digest(double_sha256(&preimage))
+}
+
+/// Sighash of input `input_index` of `tx` for a segwit v0 spend as specified in BIP143.
+///
+/// `script_code` is as defined in BIP143; for P2WPKH it is `OP_DUP OP_HASH160 <pubkey hash> OP_EQUALVERIFY OP_CHECKSIG`.
+/// `value` is the value in satoshis of the output being spent.
+pub fn segwit_v0_sighash(
+    tx: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    value: u64,
+    sighash_type: u32,
+) -> TofnResult<MessageDigest> {
+    check_input_index(tx, input_index)?;
+    let base_type = sighash_type & 0x1f;
+    let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+    let all_outputs = base_type != SIGHASH_SINGLE && base_type != SIGHASH_NONE;
+
+    let hash_prevouts = if anyone_can_pay {
+        [0; 32]
+    } else {
+        let mut bytes = Vec::new();
+        for input in tx.inputs.iter() {
+            write_outpoint(&mut bytes, &input.previous_output);
+        }
+        double_sha256(&bytes)
+    };
+
+    let hash_sequence = if anyone_can_pay || !all_outputs {
+        [0; 32]
+    } else {
+        let bytes: Vec<u8> = tx
+            .inputs
+            .iter()
+            .flat_map(|input| input.sequence.to_le_bytes())
+            .collect();
+        double_sha256(&bytes)
+    };
+
+    let hash_outputs = if all_outputs {
+        let mut bytes = Vec::new();
+        for output in tx.outputs.iter() {
+            write_output(&mut bytes, output);
+        }
+        double_sha256(&bytes)
+    } else if base_type == SIGHASH_SINGLE && input_index < tx.outputs.len() {
+        let mut bytes = Vec::new();
+        write_output(&mut bytes, &tx.outputs[input_index]);
+        double_sha256(&bytes)
+    } else {
+        [0; 32]
+    };
+
+    let input = &tx.inputs[input_index];
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(&tx.version.to_le_bytes());
+    preimage.extend_from_slice(&hash_prevouts);
+    preimage.extend_from_slice(&hash_sequence);
+    write_outpoint(&mut preimage, &input.previous_output);
+    write_bytes(&mut preimage, script_code);
+    preimage.extend_from_slice(&value.to_le_bytes());
+    preimage.extend_from_slice(&input.sequence.to_le_bytes());
+    preimage.extend_from_slice(&hash_outputs);
+    preimage.extend_from_slice(&tx.lock_time.to_le_bytes());
+    preimage.extend_from_slice(&sighash_type.to_le_bytes());
+    digest(double_sha256(&preimage))
+}
+
+/// Encode `signature` for a Bitcoin input script or witness:
+/// strict DER with low `s` (BIP66, BIP146) followed by the sighash type byte.
+pub fn to_der_with_sighash_type(signature: &Signature, sighash_type: u32) -> BytesVec {
+    let signature = signature.normalize_s().unwrap_or(*signature);
+    let mut bytes = signature.to_der().as_bytes().to_vec();
+    bytes.push(sighash_type as u8);
+    bytes
+}
+
+/// A script at a leaf of a taproot script tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapLeaf {
+    /// Leaf version with the lowest bit clear, usually [TAPSCRIPT_LEAF_VERSION]
+    pub leaf_version: u8,
+    pub script: BytesVec,
+}
+
+impl TapLeaf {
+    pub fn tapscript(script: BytesVec) -> Self {
+        Self {
+            leaf_version: TAPSCRIPT_LEAF_VERSION,
+            script,
+        }
+    }
+
+    pub fn leaf_hash(&self) -> [u8; 32] {
+        let mut bytes = alloc::vec![self.leaf_version];
+        write_bytes(&mut bytes, &self.script);
+        tagged_hash(b"TapLeaf", &bytes)
+    }
+}
+
+/// The threshold group key committed to a taproot script tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaprootSpendInfo {
+    internal_key: [u8; 32],
+    output_key: [u8; 32],
+    output_key_parity: u8,
+    merkle_root: Option<[u8; 32]>,
+    leaves: Vec<(TapLeaf, Vec<[u8; 32]>)>,
+}
+
+impl TaprootSpendInfo {
+    /// Use the x-only form of `group_key` as the internal key of a taproot output
+    /// committing to `leaves` in a balanced script tree.
+    /// With no leaves the output can only be spent via the key path.
+    ///
+    /// Return `TofnFatal` if the tree is too deep
+    /// or in the negligible event that the tweak is invalid.
+    pub fn new(group_key: &VerifyingKey, leaves: Vec<TapLeaf>) -> TofnResult<Self> {
+        // lift the group key to the point with even y that has the same x-only encoding
+        let group_key = ProjectivePoint::from(*group_key.as_affine());
+        let encoded = group_key.to_affine().to_encoded_point(true);
+        let internal_point = if encoded.as_bytes()[0] == 0x03 {
+            -group_key
+        } else {
+            group_key
+        };
+        let mut internal_key = [0; 32];
+        internal_key.copy_from_slice(&encoded.as_bytes()[1..]);
+
+        // build the tree bottom-up, pairing adjacent nodes at each level
+        let mut paths: Vec<Vec<[u8; 32]>> = leaves.iter().map(|_| Vec::new()).collect();
+        let mut nodes: Vec<([u8; 32], Vec<usize>)> = leaves
+            .iter()
+            .enumerate()
+            .map(|(i, leaf)| (leaf.leaf_hash(), alloc::vec![i]))
+            .collect();
+        while nodes.len() > 1 {
+            let mut parents = Vec::with_capacity((nodes.len() + 1) / 2);
+            let mut level = nodes.into_iter();
+            while let Some((left_hash, mut left_leaves)) = level.next() {
+                match level.next() {
+                    Some((right_hash, right_leaves)) => {
+                        for &i in left_leaves.iter() {
+                            paths[i].push(right_hash);
+                        }
+                        for &i in right_leaves.iter() {
+                            paths[i].push(left_hash);
+                        }
+                        left_leaves.extend(right_leaves);
+                        parents.push((tap_branch_hash(&left_hash, &right_hash), left_leaves));
+                    }
+                    None => parents.push((left_hash, left_leaves)),
+                }
+            }
+            nodes = parents;
+        }
+        let merkle_root = nodes.pop().map(|(root, _)| root);
+
+        if paths
+            .iter()
+            .any(|path| path.len() > TAPROOT_CONTROL_MAX_NODE_COUNT)
+        {
+            error!(
+                "taproot script tree of {} leaves exceeds max depth {}",
+                leaves.len(),
+                TAPROOT_CONTROL_MAX_NODE_COUNT
+            );
+            return Err(TofnFatal);
+        }
+
+        // tweak the internal key by the merkle root
+        let mut tweak_preimage = internal_key.to_vec();
+        if let Some(merkle_root) = merkle_root {
+            tweak_preimage.extend_from_slice(&merkle_root);
+        }
+        let tweak = tagged_hash(b"TapTweak", &tweak_preimage);
+        let tweak = Option::<Scalar>::from(Scalar::from_repr(FieldBytes::clone_from_slice(&tweak)))
+            .ok_or_else(|| {
+                error!("taproot tweak exceeds the curve order");
+                TofnFatal
+            })?;
+        let output_point = internal_point + ProjectivePoint::GENERATOR * tweak;
+        if output_point == ProjectivePoint::IDENTITY {
+            error!("taproot output key is the point at infinity");
+            return Err(TofnFatal);
+        }
+        let encoded = output_point.to_affine().to_encoded_point(true);
+        let mut output_key = [0; 32];
+        output_key.copy_from_slice(&encoded.as_bytes()[1..]);
+
+        Ok(Self {
+            internal_key,
+            output_key,
+            output_key_parity: encoded.as_bytes()[0] & 1,
+            merkle_root,
+            leaves: leaves.into_iter().zip(paths.into_iter()).collect(),
+        })
+    }
+
+    /// x-only internal key: the group key
+    pub fn internal_key(&self) -> &[u8; 32] {
+        &self.internal_key
+    }
+
+    /// x-only output key: the internal key tweaked by the merkle root
+    pub fn output_key(&self) -> &[u8; 32] {
+        &self.output_key
+    }
+
+    /// `1` if the output key has odd y, else `0`
+    pub fn output_key_parity(&self) -> u8 {
+        self.output_key_parity
+    }
+
+    pub fn merkle_root(&self) -> Option<&[u8; 32]> {
+        self.merkle_root.as_ref()
+    }
+
+    pub fn leaves(&self) -> impl Iterator<Item = &TapLeaf> {
+        self.leaves.iter().map(|(leaf, _)| leaf)
+    }
+
+    /// `OP_1 <output key>`: the segwit v1 `scriptPubKey` of the taproot output
+    pub fn script_pubkey(&self) -> BytesVec {
+        let mut bytes = alloc::vec![0x51, 0x20];
+        bytes.extend_from_slice(&self.output_key);
+        bytes
+    }
+
+    /// Control block for spending via the script of leaf `leaf_index`,
+    /// to be placed last in the witness after the script itself.
+    pub fn control_block(&self, leaf_index: usize) -> TofnResult<BytesVec> {
+        let (leaf, path) = self.leaves.get(leaf_index).ok_or_else(|| {
+            error!(
+                "leaf index {} out of bounds for {} leaves",
+                leaf_index,
+                self.leaves.len()
+            );
+            TofnFatal
+        })?;
+        let mut bytes = Vec::with_capacity(33 + 32 * path.len());
+        bytes.push((leaf.leaf_version & 0xfe) | self.output_key_parity);
+        bytes.extend_from_slice(&self.internal_key);
+        for node in path {
+            bytes.extend_from_slice(node);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Sighash of input `input_index` of `tx` for a taproot key-path spend as specified in BIP341.
+///
+/// `prevouts` are the outputs spent by all inputs of `tx`, in order.
+pub fn taproot_key_path_sighash(
+    tx: &Transaction,
+    input_index: usize,
+    prevouts: &[TxOut],
+    sighash_type: u32,
+) -> TofnResult<MessageDigest> {
+    taproot_sighash(tx, input_index, prevouts, None, sighash_type)
+}
+
+/// Sighash of input `input_index` of `tx` for a taproot script-path spend of `leaf`
+/// as specified in BIP341 and BIP342, for scripts without `OP_CODESEPARATOR`.
+///
+/// `prevouts` are the outputs spent by all inputs of `tx`, in order.
+pub fn taproot_script_path_sighash(
+    tx: &Transaction,
+    input_index: usize,
+    prevouts: &[TxOut],
+    leaf: &TapLeaf,
+    sighash_type: u32,
+) -> TofnResult<MessageDigest> {
+    taproot_sighash(
+        tx,
+        input_index,
+        prevouts,
+        Some(leaf.leaf_hash()),
+        sighash_type,
+    )
+}
+
+fn taproot_sighash(
+    tx: &Transaction,
+    input_index: usize,
+    prevouts: &[TxOut],
+    leaf_hash: Option<[u8; 32]>,
+    sighash_type: u32,
+) -> TofnResult<MessageDigest> {
+    check_input_index(tx, input_index)?;
+    if !matches!(sighash_type, 0x00..=0x03 | 0x81..=0x83) {
+        error!("invalid taproot sighash type {:#x}", sighash_type);
+        return Err(TofnFatal);
+    }
+    if prevouts.len() != tx.inputs.len() {
+        error!("{} prevouts for {} inputs", prevouts.len(), tx.inputs.len());
+        return Err(TofnFatal);
+    }
+    let base_type = sighash_type & 0x03;
+    let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+
+    if base_type == SIGHASH_SINGLE && input_index >= tx.outputs.len() {
+        error!(
+            "SIGHASH_SINGLE input {} has no corresponding output among {}",
+            input_index,
+            tx.outputs.len()
+        );
+        return Err(TofnFatal);
+    }
+
+    // epoch 0
+    let mut preimage = alloc::vec![0x00, sighash_type as u8];
+    preimage.extend_from_slice(&tx.version.to_le_bytes());
+    preimage.extend_from_slice(&tx.lock_time.to_le_bytes());
+
+    if !anyone_can_pay {
+        let mut outpoints = Vec::new();
+        let mut amounts = Vec::new();
+        let mut script_pubkeys = Vec::new();
+        let mut sequences = Vec::new();
+        for (input, prevout) in tx.inputs.iter().zip(prevouts.iter()) {
+            write_outpoint(&mut outpoints, &input.previous_output);
+            amounts.extend_from_slice(&prevout.value.to_le_bytes());
+            write_bytes(&mut script_pubkeys, &prevout.script_pubkey);
+            sequences.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        preimage.extend_from_slice(&Sha256::digest(&outpoints));
+        preimage.extend_from_slice(&Sha256::digest(&amounts));
+        preimage.extend_from_slice(&Sha256::digest(&script_pubkeys));
+        preimage.extend_from_slice(&Sha256::digest(&sequences));
+    }
+
+    if base_type != SIGHASH_NONE && base_type != SIGHASH_SINGLE {
+        let mut outputs = Vec::new();
+        for output in tx.outputs.iter() {
+            write_output(&mut outputs, output);
+        }
+        preimage.extend_from_slice(&Sha256::digest(&outputs));
+    }
+
+    // spend type: extension flag 1 for script path, no annex
+    preimage.push(if leaf_hash.is_some() { 0x02 } else { 0x00 });
+
+    if anyone_can_pay {
+        let input = &tx.inputs[input_index];
+        write_outpoint(&mut preimage, &input.previous_output);
+        write_output(&mut preimage, &prevouts[input_index]);
+        preimage.extend_from_slice(&input.sequence.to_le_bytes());
+    } else {
+        preimage.extend_from_slice(&(input_index as u32).to_le_bytes());
+    }
+
+    if base_type == SIGHASH_SINGLE {
+        let mut output = Vec::new();
+        write_output(&mut output, &tx.outputs[input_index]);
+        preimage.extend_from_slice(&Sha256::digest(&output));
+    }
+
+    if let Some(leaf_hash) = leaf_hash {
+        preimage.extend_from_slice(&leaf_hash);
+        // key version 0, no OP_CODESEPARATOR executed
+        preimage.push(0x00);
+        preimage.extend_from_slice(&u32::MAX.to_le_bytes());
+    }
+
+    digest(tagged_hash(b"TapSighash", &preimage))
+}
+
+fn check_input_index(tx: &Transaction, input_index: usize) -> TofnResult<()> {
+    if input_index >= tx.inputs.len() {
+        error!(
+            "input index {} out of bounds for {} inputs",
+            input_index,
+            tx.inputs.len()
+        );
+        return Err(TofnFatal);
+    }
+    Ok(())
+}
+
+fn digest(hash: [u8; 32]) -> TofnResult<MessageDigest> {
+    MessageDigest::try_from(&hash[..]).map_err(|_| TofnFatal)
+}
+
+fn double_sha256(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(bytes)).into()
+}
+
+/// BIP340 tagged hash
+fn tagged_hash(tag: &[u8], bytes: &[u8]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag);
+    Sha256::new()
+        .chain_update(&tag_hash)
+        .chain_update(&tag_hash)
+        .chain_update(bytes)
+        .finalize()
+        .into()
+}
+
+fn tap_branch_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut bytes = first.to_vec();
+    bytes.extend_from_slice(second);
+    tagged_hash(b"TapBranch", &bytes)
+}
+
+fn write_compact_size(bytes: &mut Vec<u8>, n: usize) {
+    match n {
+        0..=0xfc => bytes.push(n as u8),
+        0xfd..=0xffff => {
+            bytes.push(0xfd);
+            bytes.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            bytes.push(0xfe);
+            bytes.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        _ => {
+            bytes.push(0xff);
+            bytes.extend_from_slice(&(n as u64).to_le_bytes());
+        }
+    }
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, data: &[u8]) {
+    write_compact_size(bytes, data.len());
+    bytes.extend_from_slice(data);
+}
+
+fn write_outpoint(bytes: &mut Vec<u8>, outpoint: &OutPoint) {
+    bytes.extend_from_slice(&outpoint.txid);
+    bytes.extend_from_slice(&outpoint.vout.to_le_bytes());
+}
+
+fn write_output(bytes: &mut Vec<u8>, output: &TxOut) {
+    bytes.extend_from_slice(&output.value.to_le_bytes());
+    write_bytes(bytes, &output.script_pubkey);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn txid(hex: &str) -> [u8; 32] {
+        let mut txid = [0; 32];
+        txid.copy_from_slice(&hex::decode(hex).unwrap());
+        txid
+    }
+
+    fn p2pkh(pubkey_hash: &str) -> BytesVec {
+        hex::decode(alloc::format!("76a914{}88ac", pubkey_hash)).unwrap()
+    }
+
+    /// Mainnet transaction 452c629d67e41baec3ac6f04fe744b4b9617f8f859c63b3002f8684e7a4fee03
+    #[test]
+    fn legacy_p2pkh() {
+        let tx = Transaction {
+            version: 1,
+            inputs: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: txid("813f79011acb80925dfe69b3def355fe914bd1d96a3f5f71bf8303c6a989c7d1"),
+                    vout: 0,
+                },
+                sequence: 0xfffffffe,
+            }],
+            outputs: vec![
+                TxOut {
+                    value: 32454049,
+                    script_pubkey: p2pkh("bc3b654dca7e56b04dca18f2566cdaf02e8d9ada"),
+                },
+                TxOut {
+                    value: 10011545,
+                    script_pubkey: p2pkh("1c4bc762dd5423e332166702cb75f40df79fea12"),
+                },
+            ],
+            lock_time: 410393,
+        };
+        let script_code = p2pkh("a802fc56c704ce87c42d7c92eb75e7896bdc41ae");
+
+        let sighash = legacy_sighash(&tx, 0, &script_code, SIGHASH_ALL).unwrap();
+        assert_eq!(
+            hex::encode(sighash),
+            "27e0c5994dec7824e56dec6b2fcb342eb7cdb0d0957c2fce9882f715e85d81a6"
+        );
+
+        assert!(legacy_sighash(&tx, 1, &script_code, SIGHASH_ALL).is_err());
+        assert!(legacy_sighash(&tx, 0, &script_code, SIGHASH_SINGLE).is_ok());
+
+        let mut no_outputs = tx.clone();
+        no_outputs.outputs.clear();
+        assert!(legacy_sighash(&no_outputs, 0, &script_code, SIGHASH_SINGLE).is_err());
+        assert!(legacy_sighash(&no_outputs, 0, &script_code, SIGHASH_NONE).is_ok());
+    }
+
+    /// Native P2WPKH example from BIP143
+    #[test]
+    fn segwit_v0_p2wpkh() {
+        let tx = Transaction {
+            version: 1,
+            inputs: vec![
+                TxIn {
+                    previous_output: OutPoint {
+                        txid: txid(
+                            "fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f",
+                        ),
+                        vout: 0,
+                    },
+                    sequence: 0xffffffee,
+                },
+                TxIn {
+                    previous_output: OutPoint {
+                        txid: txid(
+                            "ef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a",
+                        ),
+                        vout: 1,
+                    },
+                    sequence: 0xffffffff,
+                },
+            ],
+            outputs: vec![
+                TxOut {
+                    value: 112340000,
+                    script_pubkey: p2pkh("8280b37df378db99f66f85c95a783a76ac7a6d59"),
+                },
+                TxOut {
+                    value: 223450000,
+                    script_pubkey: p2pkh("3bde42dbee7e4dbe6a21b2d50ce2f0167faa8159"),
+                },
+            ],
+            lock_time: 17,
+        };
+        let script_code = p2pkh("1d0f172a0ecb48aee1be1f2687d2963ae33f71a1");
+
+        let sighash = segwit_v0_sighash(&tx, 1, &script_code, 600000000, SIGHASH_ALL).unwrap();
+        assert_eq!(
+            hex::encode(sighash),
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670"
+        );
+
+        assert!(segwit_v0_sighash(&tx, 2, &script_code, 600000000, SIGHASH_ALL).is_err());
+    }
+
+    fn x_only_key(hex: &str) -> VerifyingKey {
+        VerifyingKey::from_sec1_bytes(&hex::decode(alloc::format!("02{}", hex)).unwrap()).unwrap()
+    }
+
+    /// Wallet test vectors from BIP341
+    #[test]
+    fn taproot_output_key() {
+        let key_only = TaprootSpendInfo::new(
+            &x_only_key("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d"),
+            vec![],
+        )
+        .unwrap();
+        assert_eq!(key_only.merkle_root(), None);
+        assert_eq!(
+            hex::encode(key_only.output_key()),
+            "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"
+        );
+        assert!(key_only.control_block(0).is_err());
+
+        let leaf = TapLeaf::tapscript(
+            hex::decode("20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac")
+                .unwrap(),
+        );
+        let one_leaf = TaprootSpendInfo::new(
+            &x_only_key("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27"),
+            vec![leaf],
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(one_leaf.merkle_root().unwrap()),
+            "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"
+        );
+        assert_eq!(
+            hex::encode(one_leaf.script_pubkey()),
+            "5120147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3"
+        );
+        assert_eq!(
+            hex::encode(one_leaf.control_block(0).unwrap()),
+            "c1187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27"
+        );
+    }
+
+    #[test]
+    fn taproot_script_path() {
+        let leaves: Vec<_> = (0..3)
+            .map(|i| TapLeaf::tapscript(vec![0x51 + i])) // OP_1, OP_2, OP_3
+            .collect();
+        let spend_info = TaprootSpendInfo::new(
+            &x_only_key("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27"),
+            leaves.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(spend_info.output_key()),
+            "2be1feed0da57e3d5cf2076d7903ce0d0037924d2ef0efd8a7ed1f380def8f23"
+        );
+        assert_eq!(
+            hex::encode(spend_info.control_block(0).unwrap()),
+            "c0187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27\
+             c276fef1386890619b80e10a4a328572d97493add269df1a15a7f89f8ae8ec09\
+             a8199db85e1f94b911a63ffece012bb8afc92131e59a614341db4ed2312a3c48"
+        );
+        assert_eq!(
+            hex::encode(spend_info.control_block(2).unwrap()),
+            "c0187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27\
+             6496f0779f38b871013be71ee7dcce8fcdcc02afc4c688acb159fc5de2fba55e"
+        );
+
+        let p2wpkh = |byte| {
+            let mut script = vec![0x00, 0x14];
+            script.extend_from_slice(&[byte; 20]);
+            script
+        };
+        let tx = Transaction {
+            version: 2,
+            inputs: vec![
+                TxIn {
+                    previous_output: OutPoint {
+                        txid: [1; 32],
+                        vout: 0,
+                    },
+                    sequence: 0xffffffff,
+                },
+                TxIn {
+                    previous_output: OutPoint {
+                        txid: [2; 32],
+                        vout: 1,
+                    },
+                    sequence: 0xfffffffd,
+                },
+            ],
+            outputs: vec![
+                TxOut {
+                    value: 50000,
+                    script_pubkey: p2wpkh(0),
+                },
+                TxOut {
+                    value: 1000,
+                    script_pubkey: spend_info.script_pubkey(),
+                },
+            ],
+            lock_time: 0,
+        };
+        let prevouts = vec![
+            TxOut {
+                value: 60000,
+                script_pubkey: spend_info.script_pubkey(),
+            },
+            TxOut {
+                value: 70000,
+                script_pubkey: p2wpkh(7),
+            },
+        ];
+
+        let sighash =
+            taproot_script_path_sighash(&tx, 0, &prevouts, &leaves[0], SIGHASH_DEFAULT).unwrap();
+        assert_eq!(
+            hex::encode(sighash),
+            "72e6e5dbcc4cb5e86faf629d7f68463830ad6f7131866fe677f910a35ec5ef1b"
+        );
+        let sighash = taproot_script_path_sighash(
+            &tx,
+            1,
+            &prevouts,
+            &leaves[2],
+            SIGHASH_SINGLE | SIGHASH_ANYONECANPAY,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(sighash),
+            "d8804b0f3640fb5d813b0a95b0617a988ff9e21fc51ec050b886b204ffd1873b"
+        );
+        let sighash = taproot_key_path_sighash(&tx, 0, &prevouts, SIGHASH_NONE).unwrap();
+        assert_eq!(
+            hex::encode(sighash),
+            "9eb3c31a9fd453efe0b64d1cbf14bb4e2639fe69a3c4e1d19473bf72e820e149"
+        );
+
+        assert!(taproot_key_path_sighash(&tx, 0, &prevouts, SIGHASH_ANYONECANPAY).is_err());
+        assert!(taproot_key_path_sighash(&tx, 0, &prevouts[..1], SIGHASH_ALL).is_err());
+        assert!(taproot_key_path_sighash(&tx, 2, &prevouts, SIGHASH_ALL).is_err());
+    }
+
+    #[test]
+    fn der_low_s() {
+        let high_s = Signature::from_scalars(k256::Scalar::ONE, -k256::Scalar::ONE).unwrap();
+        let low_s = Signature::from_scalars(k256::Scalar::ONE, k256::Scalar::ONE).unwrap();
+        let expected = hex::decode("300602010102010101").unwrap();
+        assert_eq!(to_der_with_sighash_type(&high_s, SIGHASH_ALL), expected);
+        assert_eq!(to_der_with_sighash_type(&low_s, SIGHASH_ALL), expected);
+    }
+}
@@ -0,0 +1,232 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+use core::convert::TryInto;
+
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    pairing, G1Affine, G1Projective, G2Affine, G2Projective, Gt, Scalar,
+};
+use rand::{CryptoRng, RngCore};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{hex::Hex, As, Bytes, IfIsHumanReadable};
+use zeroize::Zeroize;
+
+/// Domain separation tag for hashing messages to G2
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// A point in G1, serialized in compressed form
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PublicKey(pub(super) G1Projective);
+
+/// A point in G2, serialized in compressed form
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Signature(pub(super) G2Projective);
+
+impl PublicKey {
+    pub fn to_bytes(&self) -> [u8; 48] {
+        G1Affine::from(self.0).to_compressed()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; 48] = bytes.try_into().ok()?;
+        Option::<G1Affine>::from(G1Affine::from_compressed(bytes))
+            .map(|point| Self(G1Projective::from(point)))
+    }
+
+    pub(super) fn from_secret(secret: &Scalar) -> Self {
+        Self(G1Projective::generator() * secret)
+    }
+}
+
+impl Signature {
+    pub fn to_bytes(&self) -> [u8; 96] {
+        G2Affine::from(self.0).to_compressed()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; 96] = bytes.try_into().ok()?;
+        Option::<G2Affine>::from(G2Affine::from_compressed(bytes))
+            .map(|point| Self(G2Projective::from(point)))
+    }
+
+    pub(super) fn sign(secret: &Scalar, msg: &[u8]) -> Self {
+        Self::sign_point(secret, &hash_to_g2(msg))
+    }
+
+    /// Sign a message that is already a point in G2
+    pub(super) fn sign_point(secret: &Scalar, msg_point: &G2Projective) -> Self {
+        Self(msg_point * secret)
+    }
+}
+
+impl Serialize for PublicKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        As::<IfIsHumanReadable<Hex, Bytes>>::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes: Vec<u8> = As::<IfIsHumanReadable<Hex, Bytes>>::deserialize(deserializer)?;
+        Self::from_bytes(&bytes).ok_or_else(|| D::Error::custom("invalid G1 point"))
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        As::<IfIsHumanReadable<Hex, Bytes>>::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes: Vec<u8> = As::<IfIsHumanReadable<Hex, Bytes>>::deserialize(deserializer)?;
+        Self::from_bytes(&bytes).ok_or_else(|| D::Error::custom("invalid G2 point"))
+    }
+}
+
+/// A scalar of BLS12-381 that is zeroized on drop
+#[derive(Debug, Clone, PartialEq, Zeroize)]
+#[zeroize(drop)]
+pub(super) struct SecretScalar(pub(super) Scalar);
+
+impl SecretScalar {
+    pub(super) fn random(mut rng: impl CryptoRng + RngCore) -> Self {
+        let mut bytes = [0; 64];
+        rng.fill_bytes(&mut bytes);
+        let scalar = Scalar::from_bytes_wide(&bytes);
+        bytes.zeroize();
+        Self(scalar)
+    }
+}
+
+impl Serialize for SecretScalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        As::<IfIsHumanReadable<Hex>>::serialize(&self.0.to_bytes(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretScalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes: [u8; 32] = As::<IfIsHumanReadable<Hex>>::deserialize(deserializer)?;
+        Option::<Scalar>::from(Scalar::from_bytes(&bytes))
+            .map(Self)
+            .ok_or_else(|| D::Error::custom("invalid BLS12-381 scalar"))
+    }
+}
+
+pub(super) fn hash_to_g2(msg: &[u8]) -> G2Projective {
+    <G2Projective as HashToCurve<ExpandMsgXmd<sha2::Sha256>>>::hash_to_curve(msg, DST)
+}
+
+/// Secret sharing index `i` is the evaluation point `i + 1`
+pub(super) fn share_index_scalar(index: usize) -> Scalar {
+    Scalar::from(index as u64 + 1)
+}
+
+/// Lagrange coefficient at 0 of the `i`th of `indices`
+pub(super) fn lagrange_coefficient(i: usize, indices: &[usize]) -> Option<Scalar> {
+    let x_i = share_index_scalar(indices[i]);
+    let (numerator, denominator) = indices.iter().enumerate().filter(|(j, _)| *j != i).fold(
+        (Scalar::one(), Scalar::one()),
+        |(num, den), (_, &index)| {
+            let x_j = share_index_scalar(index);
+            (num * x_j, den * (x_j - x_i))
+        },
+    );
+    Option::<Scalar>::from(denominator.invert()).map(|den_inv| numerator * den_inv)
+}
+
+pub fn verify(public_key: &PublicKey, msg: &[u8], signature: &Signature) -> bool {
+    verify_point(public_key, &hash_to_g2(msg), signature)
+}
+
+/// Verify a signature on a message that is already a point in G2
+pub(super) fn verify_point(
+    public_key: &PublicKey,
+    msg_point: &G2Projective,
+    signature: &Signature,
+) -> bool {
+    if bool::from(public_key.0.is_identity()) {
+        return false;
+    }
+    pairing(&G1Affine::generator(), &G2Affine::from(signature.0))
+        == pairing(&G1Affine::from(public_key.0), &G2Affine::from(msg_point))
+}
+
+pub fn aggregate(signatures: &[Signature]) -> Signature {
+    Signature(
+        signatures
+            .iter()
+            .fold(G2Projective::identity(), |sum, signature| sum + signature.0),
+    )
+}
+
+/// Verify an [aggregate] of signatures on `msgs[i]` by `public_keys[i]`.
+/// The basic scheme requires all messages to be distinct to rule out rogue key attacks.
+pub fn aggregate_verify(public_keys: &[PublicKey], msgs: &[&[u8]], signature: &Signature) -> bool {
+    if public_keys.is_empty()
+        || public_keys.len() != msgs.len()
+        || msgs.iter().collect::<BTreeSet<_>>().len() != msgs.len()
+        || public_keys
+            .iter()
+            .any(|public_key| bool::from(public_key.0.is_identity()))
+    {
+        return false;
+    }
+    let rhs = public_keys
+        .iter()
+        .zip(msgs.iter())
+        .fold(Gt::identity(), |sum, (public_key, msg)| {
+            sum + pairing(
+                &G1Affine::from(public_key.0),
+                &G2Affine::from(hash_to_g2(msg)),
+            )
+        });
+    pairing(&G1Affine::generator(), &G2Affine::from(signature.0)) == rhs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_verify_aggregate() {
+        let secrets: Vec<_> = (0..3)
+            .map(|_| SecretScalar::random(rand::thread_rng()))
+            .collect();
+        let public_keys: Vec<_> = secrets
+            .iter()
+            .map(|secret| PublicKey::from_secret(&secret.0))
+            .collect();
+        let msgs: [&[u8]; 3] = [b"zero", b"one", b"two"];
+        let signatures: Vec<_> = secrets
+            .iter()
+            .zip(msgs.iter())
+            .map(|(secret, msg)| Signature::sign(&secret.0, msg))
+            .collect();
+
+        assert!(verify(&public_keys[0], msgs[0], &signatures[0]));
+        assert!(!verify(&public_keys[0], msgs[1], &signatures[0]));
+
+        let signature = aggregate(&signatures);
+        assert!(aggregate_verify(&public_keys, &msgs, &signature));
+        assert!(!aggregate_verify(&public_keys[1..], &msgs[1..], &signature));
+        assert!(!aggregate_verify(
+            &public_keys[..2],
+            &[msgs[0], msgs[0]],
+            &aggregate(&signatures[..2])
+        ));
+
+        let bytes = signature.to_bytes();
+        assert_eq!(Signature::from_bytes(&bytes), Some(signature));
+        assert_eq!(
+            PublicKey::from_bytes(&public_keys[0].to_bytes()),
+            Some(public_keys[0])
+        );
+    }
+
+    #[test]
+    fn secret_scalar_zeroize_on_drop() {
+        assert_zeroized_on_drop!(SecretScalar::random(rand::thread_rng()), 0);
+    }
+}
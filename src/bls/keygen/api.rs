@@ -0,0 +1,69 @@
+use crate::{
+    collections::TypedUsize,
+    sdk::{
+        api::{PartyShareCounts, Protocol, TofnFatal, TofnResult},
+        implementer_api::{new_protocol, ProtocolBuilder},
+    },
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::r1;
+pub use super::secret_key_share::*;
+
+/// Maximum byte length of messages exchanged during keygen.
+/// The largest keygen message is the round 2 bcast with `threshold + 1` G1 points of 49 bytes each on the wire
+/// and a 32-byte reveal,
+/// which fits for any threshold below [MAX_TOTAL_SHARE_COUNT].
+pub const MAX_MSG_LEN: usize = 50_000;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KeygenShareId;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KeygenPartyId;
+
+pub type KeygenProtocol = Protocol<SecretKeyShare, KeygenShareId, KeygenPartyId>;
+pub type KeygenProtocolBuilder = ProtocolBuilder<SecretKeyShare, KeygenShareId>;
+pub type KeygenPartyShareCounts = PartyShareCounts<KeygenPartyId>;
+
+pub const MAX_TOTAL_SHARE_COUNT: usize = 1000;
+pub const MAX_PARTY_SHARE_COUNT: usize = MAX_TOTAL_SHARE_COUNT;
+
+/// Initialize a new keygen protocol
+pub fn new_keygen(
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    my_party_id: TypedUsize<KeygenPartyId>,
+    my_subshare_id: usize, // in 0..party_share_counts[my_party_id]
+) -> TofnResult<KeygenProtocol> {
+    // validate args
+    if party_share_counts
+        .iter()
+        .any(|(_, &c)| c > MAX_PARTY_SHARE_COUNT)
+    {
+        error!(
+            "detected a party with share count exceeding {}",
+            MAX_PARTY_SHARE_COUNT
+        );
+        return Err(TofnFatal);
+    }
+    let total_share_count: usize = party_share_counts.total_share_count();
+    let my_keygen_id = party_share_counts.party_to_share_id(my_party_id, my_subshare_id)?;
+
+    #[allow(clippy::suspicious_operation_groupings)]
+    if total_share_count <= threshold
+        || total_share_count > MAX_TOTAL_SHARE_COUNT
+        || my_party_id.as_usize() >= party_share_counts.party_count()
+    {
+        error!(
+            "invalid (total_share_count, threshold, my_party_id, my_subshare_id, max_share_count): ({},{},{},{},{})",
+            total_share_count, threshold, my_party_id, my_subshare_id, MAX_TOTAL_SHARE_COUNT
+        );
+        return Err(TofnFatal);
+    }
+
+    let round2 = r1::start(my_keygen_id, threshold, party_share_counts.clone())?;
+
+    new_protocol(party_share_counts, my_keygen_id, MAX_MSG_LEN, round2)
+}
@@ -0,0 +1,10 @@
+mod api;
+pub use api::*;
+
+mod r1;
+mod r2;
+mod r3;
+mod secret_key_share;
+
+#[cfg(test)]
+pub(super) mod tests; // pub(super) so that sign module can see tests::execute_keygen
@@ -0,0 +1,57 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    bls::curve::{PublicKey, SecretScalar},
+    collections::TypedUsize,
+    crypto_tools::{constants, hash},
+    sdk::{
+        api::TofnResult,
+        implementer_api::{serialize, ProtocolBuilder, RoundBuilder},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use super::{r2, KeygenPartyShareCounts, KeygenProtocolBuilder, KeygenShareId};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Bcast {
+    /// Diffie-Hellman key for the encryption of round 2 p2ps
+    pub(super) ek: PublicKey,
+    /// Hash commitment to this share's contribution `y_i` to the group key,
+    /// revealed in round 2 so that no one can choose theirs after seeing the others
+    pub(super) y_i_commit: hash::Output,
+}
+
+pub(super) fn start(
+    my_keygen_id: TypedUsize<KeygenShareId>,
+    threshold: usize,
+    party_share_counts: KeygenPartyShareCounts,
+) -> TofnResult<KeygenProtocolBuilder> {
+    let coeffs: Vec<SecretScalar> = (0..=threshold)
+        .map(|_| SecretScalar::random(rand::thread_rng()))
+        .collect();
+    let dk = SecretScalar::random(rand::thread_rng());
+
+    let (y_i_commit, y_i_reveal) = hash::commit(
+        constants::BLS_Y_I_COMMIT_TAG,
+        my_keygen_id,
+        PublicKey::from_secret(&coeffs[0].0).to_bytes(),
+    );
+
+    let bcast_out = Some(serialize(&Bcast {
+        ek: PublicKey::from_secret(&dk.0),
+        y_i_commit,
+    })?);
+
+    Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
+        Box::new(r2::R2 {
+            threshold,
+            party_share_counts,
+            coeffs,
+            dk,
+            y_i_reveal,
+        }),
+        bcast_out,
+        None,
+    )))
+}
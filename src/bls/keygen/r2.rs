@@ -0,0 +1,165 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use bls12_381::Scalar;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::{
+    bls::curve::{share_index_scalar, PublicKey, SecretScalar},
+    collections::{FillVecMap, P2ps, TypedUsize, VecMap},
+    crypto_tools::hash,
+    sdk::{
+        api::{Fault::ProtocolFault, TofnResult},
+        implementer_api::{serialize, Executer, ProtocolBuilder, ProtocolInfo, RoundBuilder},
+    },
+};
+
+use super::{r1, r3, KeygenPartyShareCounts, KeygenShareId, SecretKeyShare};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Bcast {
+    /// Feldman commitment to the coefficients of this share's polynomial
+    pub(super) commit: Vec<PublicKey>,
+    /// Opens the round 1 `y_i_commit` to the constant term of `commit`
+    pub(super) y_i_reveal: hash::Randomness,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct P2p {
+    /// Share of the recipient, one-time padded with [share_pad]
+    pub(super) share_ciphertext: [u8; 32],
+}
+
+pub(super) struct R2 {
+    pub(super) threshold: usize,
+    pub(super) party_share_counts: KeygenPartyShareCounts,
+    pub(super) coeffs: Vec<SecretScalar>,
+    pub(super) dk: SecretScalar,
+    pub(super) y_i_reveal: hash::Randomness,
+}
+
+impl Executer for R2 {
+    type FinalOutput = SecretKeyShare;
+    type Index = KeygenShareId;
+    type Bcast = r1::Bcast;
+    type P2p = ();
+
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_keygen_id = info.my_id();
+        let mut faulters = info.new_fillvecmap();
+
+        // anyone who did not send a bcast is a faulter
+        for (peer_keygen_id, bcast) in bcasts_in.iter() {
+            if bcast.is_none() {
+                warn!(
+                    "peer {} says: missing bcast from peer {} in round 2",
+                    my_keygen_id, peer_keygen_id
+                );
+                faulters.set(peer_keygen_id, ProtocolFault)?;
+            }
+        }
+        // anyone who sent p2ps is a faulter
+        for (peer_keygen_id, p2ps) in p2ps_in.iter() {
+            if p2ps.is_some() {
+                warn!(
+                    "peer {} says: unexpected p2ps from peer {} in round 2",
+                    my_keygen_id, peer_keygen_id
+                );
+                faulters.set(peer_keygen_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // everyone sent a bcast---unwrap all bcasts
+        let bcasts_in = bcasts_in.to_vecmap()?;
+
+        let bcast_out = Some(serialize(&Bcast {
+            commit: self
+                .coeffs
+                .iter()
+                .map(|coeff| PublicKey::from_secret(&coeff.0))
+                .collect(),
+            y_i_reveal: self.y_i_reveal.clone(),
+        })?);
+
+        let (peer_shares, my_share) = VecMap::from_vec(
+            (0..info.total_share_count())
+                .map(|index| evaluate(&self.coeffs, index))
+                .collect(),
+        )
+        .puncture_hole(my_keygen_id)?;
+
+        let p2ps_out = Some(peer_shares.map2_result(|(peer_keygen_id, share)| {
+            let pad = share_pad(
+                &bcasts_in.get(peer_keygen_id)?.ek,
+                &self.dk,
+                my_keygen_id,
+                peer_keygen_id,
+            );
+            serialize(&P2p {
+                share_ciphertext: xor(&share.0.to_bytes(), &pad),
+            })
+        })?);
+
+        Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
+            Box::new(r3::R3 {
+                threshold: self.threshold,
+                party_share_counts: self.party_share_counts,
+                dk: self.dk,
+                my_share,
+                r1bcasts: bcasts_in,
+            }),
+            bcast_out,
+            p2ps_out,
+        )))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+/// Evaluate the polynomial with coefficients `coeffs` at the point of share `index`
+fn evaluate(coeffs: &[SecretScalar], index: usize) -> SecretScalar {
+    let x = share_index_scalar(index);
+    SecretScalar(
+        coeffs
+            .iter()
+            .rev()
+            .fold(Scalar::zero(), |acc, coeff| acc * x + coeff.0),
+    )
+}
+
+/// One-time pad for the share that `from` sends to `to`,
+/// derived from the Diffie-Hellman secret of the ek of one and the dk of the other
+pub(super) fn share_pad(
+    ek: &PublicKey,
+    dk: &SecretScalar,
+    from: TypedUsize<KeygenShareId>,
+    to: TypedUsize<KeygenShareId>,
+) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(b"tofn-bls-keygen-share")
+        .chain_update(PublicKey(ek.0 * dk.0).to_bytes())
+        .chain_update(from.to_bytes())
+        .chain_update(to.to_bytes())
+        .finalize()
+        .into()
+}
+
+pub(super) fn xor(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut out = [0; 32];
+    for (out, (a, b)) in out.iter_mut().zip(a.iter().zip(b.iter())) {
+        *out = a ^ b;
+    }
+    out
+}
@@ -0,0 +1,206 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use bls12_381::{G1Projective, Scalar};
+use tracing::{error, warn};
+
+use crate::{
+    bls::curve::{share_index_scalar, PublicKey, SecretScalar},
+    collections::{FillVecMap, P2ps, VecMap},
+    crypto_tools::{constants, hash},
+    sdk::{
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
+        implementer_api::{Executer, ProtocolBuilder, ProtocolInfo},
+    },
+};
+
+use super::{
+    r1, r2,
+    secret_key_share::{GroupPublicInfo, SecretKeyShare, ShareSecretInfo},
+    KeygenPartyShareCounts, KeygenShareId,
+};
+
+pub(super) struct R3 {
+    pub(super) threshold: usize,
+    pub(super) party_share_counts: KeygenPartyShareCounts,
+    pub(super) dk: SecretScalar,
+    pub(super) my_share: SecretScalar,
+    pub(super) r1bcasts: VecMap<KeygenShareId, r1::Bcast>,
+}
+
+impl Executer for R3 {
+    type FinalOutput = SecretKeyShare;
+    type Index = KeygenShareId;
+    type Bcast = r2::Bcast;
+    type P2p = r2::P2p;
+
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_keygen_id = info.my_id();
+        let mut faulters = info.new_fillvecmap();
+
+        // anyone who did not send a bcast is a faulter
+        for (peer_keygen_id, bcast) in bcasts_in.iter() {
+            if bcast.is_none() {
+                warn!(
+                    "peer {} says: missing bcast from peer {} in round 3",
+                    my_keygen_id, peer_keygen_id
+                );
+                faulters.set(peer_keygen_id, ProtocolFault)?;
+            }
+        }
+        // anyone who did not send p2ps is a faulter
+        for (peer_keygen_id, p2ps) in p2ps_in.iter() {
+            if p2ps.is_none() {
+                warn!(
+                    "peer {} says: missing p2ps from peer {} in round 3",
+                    my_keygen_id, peer_keygen_id
+                );
+                faulters.set(peer_keygen_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // everyone sent their bcasts and p2ps---unwrap all bcasts and p2ps
+        let bcasts_in = bcasts_in.to_vecmap()?;
+        let p2ps_in = p2ps_in.to_fullp2ps()?;
+
+        // check commit lengths
+        for (peer_keygen_id, bcast) in bcasts_in.iter() {
+            if bcast.commit.len() != self.threshold + 1 {
+                warn!(
+                    "peer {} says: commit of invalid length {} (expected {}) from peer {}",
+                    my_keygen_id,
+                    bcast.commit.len(),
+                    self.threshold + 1,
+                    peer_keygen_id,
+                );
+                faulters.set(peer_keygen_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // check y_i commits
+        for (peer_keygen_id, bcast) in bcasts_in.iter() {
+            let peer_y_i_commit = hash::commit_with_randomness(
+                constants::BLS_Y_I_COMMIT_TAG,
+                peer_keygen_id,
+                bcast.commit[0].to_bytes(),
+                &bcast.y_i_reveal,
+            );
+            if peer_y_i_commit != self.r1bcasts.get(peer_keygen_id)?.y_i_commit {
+                warn!(
+                    "peer {} says: invalid y_i reveal by peer {}",
+                    my_keygen_id, peer_keygen_id
+                );
+                faulters.set(peer_keygen_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // decrypt and validate shares
+        // There is no complaint round: a party that received an invalid share accuses the sender,
+        // but other parties cannot tell whether the sender or the accuser is lying.
+        let my_index = share_index_scalar(my_keygen_id.as_usize());
+        let peer_shares = p2ps_in.map_to_me2_result(my_keygen_id, |(peer_keygen_id, p2p)| {
+            let pad = r2::share_pad(
+                &self.r1bcasts.get(peer_keygen_id)?.ek,
+                &self.dk,
+                peer_keygen_id,
+                my_keygen_id,
+            );
+            let share =
+                Option::<Scalar>::from(Scalar::from_bytes(&r2::xor(&p2p.share_ciphertext, &pad)));
+            let peer_commit = &bcasts_in.get(peer_keygen_id)?.commit;
+
+            match share {
+                Some(share)
+                    if PublicKey::from_secret(&share) == commit_eval(peer_commit, &my_index) =>
+                {
+                    Ok(Some(SecretScalar(share)))
+                }
+                _ => {
+                    warn!(
+                        "peer {} says: invalid share from peer {}",
+                        my_keygen_id, peer_keygen_id
+                    );
+                    Ok(None)
+                }
+            }
+        })?;
+        for (peer_keygen_id, share) in peer_shares.iter() {
+            if share.is_none() {
+                faulters.set(peer_keygen_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // my signing key is the sum of the shares from everyone
+        let signing_key = peer_shares
+            .into_iter()
+            .filter_map(|(_, share)| share)
+            .fold(self.my_share.0, |sum, share| sum + share.0);
+
+        // the commit to the group polynomial is the sum of everyone's commits
+        let group_commit: Vec<PublicKey> = (0..=self.threshold)
+            .map(|k| {
+                PublicKey(
+                    bcasts_in
+                        .iter()
+                        .fold(G1Projective::identity(), |sum, (_, bcast)| {
+                            sum + bcast.commit[k].0
+                        }),
+                )
+            })
+            .collect();
+
+        let all_verifying_keys: VecMap<KeygenShareId, PublicKey> = (0..info.total_share_count())
+            .map(|index| commit_eval(&group_commit, &share_index_scalar(index)))
+            .collect();
+
+        // sanity check
+        if *all_verifying_keys.get(my_keygen_id)? != PublicKey::from_secret(&signing_key) {
+            error!(
+                "peer {} says: my signing key does not match my verifying key",
+                my_keygen_id
+            );
+            return Err(TofnFatal);
+        }
+
+        Ok(ProtocolBuilder::Done(Ok(SecretKeyShare::new(
+            GroupPublicInfo::new(
+                self.party_share_counts,
+                self.threshold,
+                group_commit[0],
+                all_verifying_keys,
+            ),
+            ShareSecretInfo::new(my_keygen_id, SecretScalar(signing_key)),
+        ))))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+/// Evaluate the committed polynomial at `x` in the exponent
+fn commit_eval(commit: &[PublicKey], x: &Scalar) -> PublicKey {
+    PublicKey(
+        commit
+            .iter()
+            .rev()
+            .fold(G1Projective::identity(), |acc, coeff| acc * x + coeff.0),
+    )
+}
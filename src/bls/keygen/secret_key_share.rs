@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use super::{KeygenPartyShareCounts, KeygenShareId};
+use crate::{
+    bls::curve::{PublicKey, SecretScalar},
+    collections::{TypedUsize, VecMap},
+};
+
+/// final output of keygen: store this struct in tofnd kvstore
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecretKeyShare {
+    group: GroupPublicInfo,
+    share: ShareSecretInfo,
+}
+
+/// `GroupPublicInfo` is the same for all shares
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GroupPublicInfo {
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    public_key: PublicKey,
+    all_verifying_keys: VecMap<KeygenShareId, PublicKey>,
+}
+
+/// `ShareSecretInfo` secret info unique to each share
+/// `index` is not secret but it's stored here anyway
+/// because it's an essential part of secret data
+/// and parties need a way to know their own index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareSecretInfo {
+    index: TypedUsize<KeygenShareId>,
+    signing_key: SecretScalar,
+}
+
+impl GroupPublicInfo {
+    pub fn party_share_counts(&self) -> &KeygenPartyShareCounts {
+        &self.party_share_counts
+    }
+
+    pub fn share_count(&self) -> usize {
+        self.all_verifying_keys.len()
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Public key under which signatures of the group verify
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Public keys of the shares of the group signing key
+    pub fn all_verifying_keys(&self) -> &VecMap<KeygenShareId, PublicKey> {
+        &self.all_verifying_keys
+    }
+
+    pub(super) fn new(
+        party_share_counts: KeygenPartyShareCounts,
+        threshold: usize,
+        public_key: PublicKey,
+        all_verifying_keys: VecMap<KeygenShareId, PublicKey>,
+    ) -> Self {
+        Self {
+            party_share_counts,
+            threshold,
+            public_key,
+            all_verifying_keys,
+        }
+    }
+}
+
+impl ShareSecretInfo {
+    pub fn index(&self) -> TypedUsize<KeygenShareId> {
+        self.index
+    }
+
+    pub(super) fn new(index: TypedUsize<KeygenShareId>, signing_key: SecretScalar) -> Self {
+        Self { index, signing_key }
+    }
+
+    pub(crate) fn signing_key(&self) -> &bls12_381::Scalar {
+        &self.signing_key.0
+    }
+}
+
+impl SecretKeyShare {
+    pub fn group(&self) -> &GroupPublicInfo {
+        &self.group
+    }
+
+    pub fn share(&self) -> &ShareSecretInfo {
+        &self.share
+    }
+
+    // super::super so it's visible in sign
+    pub(in super::super) fn new(group: GroupPublicInfo, share: ShareSecretInfo) -> Self {
+        Self { group, share }
+    }
+}
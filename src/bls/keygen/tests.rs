@@ -0,0 +1,44 @@
+use alloc::vec;
+
+use super::*;
+use crate::{
+    bls::curve::PublicKey,
+    collections::{TypedUsize, VecMap},
+    sdk::local::execute_honest,
+};
+use tracing_test::traced_test;
+
+#[test]
+#[traced_test]
+fn basic_correctness() {
+    for (party_share_counts, threshold) in [(vec![1], 0), (vec![2, 0, 2], 2), (vec![3, 2, 1], 3)] {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(party_share_counts).unwrap();
+        let shares = execute_keygen(&party_share_counts, threshold);
+
+        let group = shares.get(TypedUsize::from_usize(0)).unwrap().group();
+        for (keygen_id, share) in shares.iter() {
+            assert_eq!(share.group(), group);
+            assert_eq!(share.share().index(), keygen_id);
+            assert_eq!(
+                *group.all_verifying_keys().get(keygen_id).unwrap(),
+                PublicKey::from_secret(share.share().signing_key())
+            );
+        }
+    }
+}
+
+pub fn execute_keygen(
+    party_share_counts: &KeygenPartyShareCounts,
+    threshold: usize,
+) -> VecMap<KeygenShareId, SecretKeyShare> {
+    let parties = party_share_counts
+        .iter()
+        .flat_map(|(party_id, &party_share_count)| {
+            (0..party_share_count).map(move |subshare_id| {
+                new_keygen(party_share_counts.clone(), threshold, party_id, subshare_id).unwrap()
+            })
+        })
+        .collect();
+
+    execute_honest(parties).unwrap()
+}
@@ -0,0 +1,15 @@
+//! Threshold BLS signatures on BLS12-381.
+//!
+//! Public keys are in G1 and signatures in G2, as in the minimal-pubkey-size variant of
+//! [draft-irtf-cfrg-bls-signature](https://datatracker.ietf.org/doc/draft-irtf-cfrg-bls-signature/)
+//! with the basic scheme ciphersuite [DST].
+//! Keygen is a Feldman DKG: no party ever learns the group signing key.
+//! Each party commits to its contribution to the group key before seeing the others'.
+//! Sign outputs an ordinary BLS signature under the group public key
+//! that verifies with [verify] and aggregates with other BLS signatures via [aggregate].
+//! [sign::new_blind_sign] signs a message blinded by an external requester, see [sign::blind].
+pub mod keygen;
+pub mod sign;
+
+mod curve;
+pub use curve::{aggregate, aggregate_verify, verify, PublicKey, Signature, DST};
@@ -0,0 +1,91 @@
+use super::r1;
+use crate::{
+    bls::{
+        curve::hash_to_g2,
+        keygen::{GroupPublicInfo, KeygenPartyId, KeygenShareId, SecretKeyShare, ShareSecretInfo},
+        Signature,
+    },
+    collections::{HoleVecMap, Subset, TypedUsize, VecMap},
+    sdk::{
+        api::{PartyShareCounts, Protocol, TofnFatal, TofnResult},
+        implementer_api::{new_protocol, ProtocolBuilder},
+    },
+};
+
+use bls12_381::G2Projective;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+/// Maximum byte length of messages exchanged during sign.
+/// The only sign message is a compressed G2 point.
+pub const MAX_MSG_LEN: usize = 200;
+
+pub type SignProtocol = Protocol<Signature, SignShareId, SignPartyId>;
+pub type SignProtocolBuilder = ProtocolBuilder<Signature, SignShareId>;
+
+// This includes all shares participating in the current signing protocol
+pub type KeygenShareIds = VecMap<SignShareId, TypedUsize<KeygenShareId>>;
+// This includes all shares (excluding self) participating in the current signing protocol
+pub type Peers = HoleVecMap<SignShareId, TypedUsize<KeygenShareId>>;
+// This is the set of parties participating in the current signing protocol
+pub type SignParties = Subset<KeygenPartyId>;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SignShareId;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SignPartyId;
+
+/// Initialize a new sign protocol for `msg`.
+/// Unlike ECDSA, BLS hashes the message itself, so `msg` is not a digest.
+/// Assume `group`, `share` are valid and check `sign_parties` against it.
+pub fn new_sign(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    msg: &[u8],
+) -> TofnResult<SignProtocol> {
+    new_sign_point(group, share, sign_parties, hash_to_g2(msg))
+}
+
+/// Initialize a new sign protocol for a message already hashed to G2.
+pub(super) fn new_sign_point(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    msg_point: G2Projective,
+) -> TofnResult<SignProtocol> {
+    let all_keygen_ids =
+        VecMap::from_vec(group.party_share_counts().share_id_subset(sign_parties)?);
+
+    // participant share count must be at least threshold + 1
+    if all_keygen_ids.len() <= group.threshold() {
+        error!(
+            "not enough participant shares: threshold [{}], participants [{}]",
+            group.threshold(),
+            all_keygen_ids.len(),
+        );
+        return Err(TofnFatal);
+    }
+
+    // find my keygen share_id
+    let my_sign_id = all_keygen_ids
+        .iter()
+        .find(|(_, &k)| k == share.index())
+        .map(|(s, _)| s)
+        .ok_or_else(|| {
+            error!("my keygen share_id {} is not a participant", share.index());
+            TofnFatal
+        })?;
+
+    let sign_party_share_counts =
+        PartyShareCounts::from_vec(group.party_share_counts().subset(sign_parties)?)?;
+
+    let round2 = r1::start(
+        SecretKeyShare::new(group.clone(), share.clone()),
+        msg_point,
+        all_keygen_ids,
+    )?;
+
+    new_protocol(sign_party_share_counts, my_sign_id, MAX_MSG_LEN, round2)
+}
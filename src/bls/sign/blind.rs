@@ -0,0 +1,100 @@
+//! Blind threshold BLS signatures.
+//!
+//! A requester [blind]s its message into a [BlindedMessage] `H(m) * b` for a random `b`
+//! and hands it to the signing parties, who run [new_blind_sign] and output `H(m) * b * x`.
+//! The requester [unblinds](BlindingFactor::unblind) this into `H(m) * x`,
+//! an ordinary BLS signature on `m` that verifies with [verify](crate::bls::verify).
+//! The signing parties learn nothing about `m` and cannot link the signature to its signing session.
+//!
+//! Unforgeability rests on the one-more computational Diffie-Hellman assumption (Boldyreva 2003).
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use bls12_381::{G2Affine, G2Projective, Scalar};
+use rand::{CryptoRng, RngCore};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{hex::Hex, As, Bytes, IfIsHumanReadable};
+use tracing::error;
+
+use crate::{
+    bls::{
+        curve::{hash_to_g2, SecretScalar},
+        keygen::{GroupPublicInfo, ShareSecretInfo},
+        Signature,
+    },
+    sdk::api::{TofnFatal, TofnResult},
+};
+
+use super::{api::new_sign_point, SignParties, SignProtocol};
+
+/// A message hashed to G2 and multiplied by a secret [BlindingFactor], serialized in compressed form
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BlindedMessage(G2Projective);
+
+/// The requester's secret for unblinding a signature on a [BlindedMessage]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlindingFactor(SecretScalar);
+
+/// Blind `msg` for [new_blind_sign].
+/// Keep the [BlindingFactor] secret: it links the blinded message to `msg`.
+pub fn blind(msg: &[u8], rng: impl CryptoRng + RngCore) -> (BlindedMessage, BlindingFactor) {
+    let factor = SecretScalar::random(rng);
+    (
+        BlindedMessage(hash_to_g2(msg) * factor.0),
+        BlindingFactor(factor),
+    )
+}
+
+/// Initialize a new sign protocol for a `blinded_msg` supplied by a requester.
+/// The output is a signature on the blinded message, to be unblinded by the requester.
+/// Assume `group`, `share` are valid and check `sign_parties` against it.
+pub fn new_blind_sign(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    blinded_msg: &BlindedMessage,
+) -> TofnResult<SignProtocol> {
+    if bool::from(blinded_msg.0.is_identity()) {
+        error!("blinded message is the point at infinity");
+        return Err(TofnFatal);
+    }
+
+    new_sign_point(group, share, sign_parties, blinded_msg.0)
+}
+
+impl BlindedMessage {
+    pub fn to_bytes(&self) -> [u8; 96] {
+        G2Affine::from(self.0).to_compressed()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; 96] = bytes.try_into().ok()?;
+        Option::<G2Affine>::from(G2Affine::from_compressed(bytes))
+            .map(|point| Self(G2Projective::from(point)))
+    }
+}
+
+impl BlindingFactor {
+    /// Turn a signature on the [BlindedMessage] into a signature on the original message.
+    /// The result is not checked: verify it with [verify](crate::bls::verify).
+    pub fn unblind(&self, blind_signature: &Signature) -> TofnResult<Signature> {
+        let factor_inv = Option::<Scalar>::from(self.0 .0.invert()).ok_or_else(|| {
+            error!("blinding factor is zero");
+            TofnFatal
+        })?;
+        Ok(Signature(blind_signature.0 * factor_inv))
+    }
+}
+
+impl Serialize for BlindedMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        As::<IfIsHumanReadable<Hex, Bytes>>::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlindedMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes: Vec<u8> = As::<IfIsHumanReadable<Hex, Bytes>>::deserialize(deserializer)?;
+        Self::from_bytes(&bytes).ok_or_else(|| D::Error::custom("invalid G2 point"))
+    }
+}
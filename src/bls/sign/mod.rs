@@ -0,0 +1,10 @@
+mod api;
+pub use api::*;
+mod blind;
+pub use blind::*;
+
+mod r1;
+mod r2;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,38 @@
+use alloc::boxed::Box;
+
+use bls12_381::G2Projective;
+
+use super::{r2, KeygenShareIds, SignProtocolBuilder};
+use crate::{
+    bls::{keygen::SecretKeyShare, Signature},
+    sdk::{
+        api::TofnResult,
+        implementer_api::{serialize, RoundBuilder},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Bcast {
+    pub(super) signature_share: Signature,
+}
+
+pub(super) fn start(
+    secret_key_share: SecretKeyShare,
+    msg_point: G2Projective,
+    all_keygen_ids: KeygenShareIds,
+) -> TofnResult<SignProtocolBuilder> {
+    let signature_share = Signature::sign_point(secret_key_share.share().signing_key(), &msg_point);
+
+    let bcast_out = Some(serialize(&Bcast { signature_share })?);
+
+    Ok(SignProtocolBuilder::NotDone(RoundBuilder::new(
+        Box::new(r2::R2 {
+            secret_key_share,
+            msg_point,
+            all_keygen_ids,
+        }),
+        bcast_out,
+        None,
+    )))
+}
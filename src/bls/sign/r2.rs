@@ -0,0 +1,137 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use bls12_381::G2Projective;
+use tracing::{error, warn};
+
+use super::{r1, KeygenShareIds, SignShareId};
+use crate::{
+    bls::{
+        curve::{lagrange_coefficient, verify_point},
+        keygen::SecretKeyShare,
+        Signature,
+    },
+    collections::{zip2, FillVecMap, P2ps},
+    sdk::{
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
+        implementer_api::{Executer, ProtocolBuilder, ProtocolInfo},
+    },
+};
+
+pub(super) struct R2 {
+    pub(super) secret_key_share: SecretKeyShare,
+    pub(super) msg_point: G2Projective,
+    pub(super) all_keygen_ids: KeygenShareIds,
+}
+
+impl Executer for R2 {
+    type FinalOutput = Signature;
+    type Index = SignShareId;
+    type Bcast = r1::Bcast;
+    type P2p = ();
+
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_sign_id = info.my_id();
+        let group = self.secret_key_share.group();
+        let threshold = group.threshold();
+        let mut faulters = info.new_fillvecmap();
+        let mut valid_indices = Vec::with_capacity(threshold + 1);
+        let mut valid_signature_shares = Vec::with_capacity(threshold + 1);
+
+        for (peer_sign_id, bcast_option, p2ps_option) in zip2(bcasts_in, p2ps_in) {
+            // anyone who did not send a bcast is a faulter
+            let signature_share = match bcast_option {
+                Some(bcast) => bcast.signature_share,
+                None => {
+                    warn!(
+                        "peer {} says: missing bcast from peer {} in round 2",
+                        my_sign_id, peer_sign_id
+                    );
+                    faulters.set(peer_sign_id, ProtocolFault)?;
+                    continue;
+                }
+            };
+
+            // anyone who sent p2ps is a faulter
+            if p2ps_option.is_some() {
+                warn!(
+                    "peer {} says: unexpected p2ps from peer {} in round 2",
+                    my_sign_id, peer_sign_id
+                );
+                faulters.set(peer_sign_id, ProtocolFault)?;
+                continue;
+            }
+
+            // verify signature share
+            let peer_keygen_id = *self.all_keygen_ids.get(peer_sign_id)?;
+            let verifying_key = group.all_verifying_keys().get(peer_keygen_id)?;
+
+            if !verify_point(verifying_key, &self.msg_point, &signature_share) {
+                warn!(
+                    "peer {} says: fail sig verify from peer {} in round 2",
+                    my_sign_id, peer_sign_id
+                );
+                faulters.set(peer_sign_id, ProtocolFault)?;
+                continue;
+            }
+
+            valid_indices.push(peer_keygen_id.as_usize());
+            valid_signature_shares.push(signature_share);
+
+            // have we got enough valid signature shares yet?
+            if valid_signature_shares.len() > threshold {
+                break;
+            }
+        }
+
+        // not enough valid signature shares => sad outcome
+        if valid_signature_shares.len() <= threshold {
+            warn!(
+                "peer {} says: insufficient valid signature shares {} to exceed threshold {}",
+                my_sign_id,
+                valid_signature_shares.len(),
+                threshold
+            );
+
+            // sanity check
+            if faulters.is_empty() {
+                error!(
+                    "peer {} says: insufficient valid signature shares but no faulters",
+                    my_sign_id
+                );
+                return Err(TofnFatal);
+            }
+
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // interpolate the group signature in the exponent
+        let signature = Signature(valid_signature_shares.iter().enumerate().try_fold(
+            G2Projective::identity(),
+            |sum, (i, signature_share)| -> TofnResult<G2Projective> {
+                let lambda = lagrange_coefficient(i, &valid_indices).ok_or_else(|| {
+                    error!("duplicate share indices in lagrange coefficient");
+                    TofnFatal
+                })?;
+                Ok(sum + signature_share.0 * lambda)
+            },
+        )?);
+
+        // sanity check
+        if !verify_point(group.public_key(), &self.msg_point, &signature) {
+            error!("peer {} says: group signature failed to verify", my_sign_id);
+            return Err(TofnFatal);
+        }
+
+        Ok(ProtocolBuilder::Done(Ok(signature)))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
@@ -0,0 +1,96 @@
+use alloc::vec;
+
+use super::*;
+use crate::{
+    bls::{
+        keygen::{tests::execute_keygen, KeygenPartyShareCounts},
+        verify,
+    },
+    collections::{Subset, TypedUsize},
+    sdk::local::execute_honest,
+};
+use tracing_test::traced_test;
+
+#[test]
+#[traced_test]
+fn basic_correctness() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![2, 1, 2, 1]).unwrap();
+    let threshold = 2;
+    let key_shares = execute_keygen(&party_share_counts, threshold);
+    let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+    let msg = b"bls threshold sign";
+
+    // parties 0 and 2 hold threshold + 1 or more shares
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(2)).unwrap();
+
+    let parties = party_share_counts
+        .share_id_subset(&sign_parties)
+        .unwrap()
+        .into_iter()
+        .map(|keygen_id| {
+            let key_share = key_shares.get(keygen_id).unwrap();
+            new_sign(key_share.group(), key_share.share(), &sign_parties, msg).unwrap()
+        })
+        .collect();
+
+    let signatures = execute_honest(parties).unwrap().into_vec();
+    for signature in signatures.iter() {
+        assert_eq!(*signature, signatures[0]);
+        assert!(verify(group.public_key(), msg, signature));
+    }
+    assert!(!verify(
+        group.public_key(),
+        b"another message",
+        &signatures[0]
+    ));
+}
+
+#[test]
+#[traced_test]
+fn blind_sign() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2, 1]).unwrap();
+    let threshold = 2;
+    let key_shares = execute_keygen(&party_share_counts, threshold);
+    let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+    let msg = b"blind credential";
+
+    // requester
+    let (blinded_msg, blinding_factor) = blind(msg, rand::thread_rng());
+    let blinded_msg = BlindedMessage::from_bytes(&blinded_msg.to_bytes()).unwrap();
+
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(1)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(2)).unwrap();
+
+    let parties = party_share_counts
+        .share_id_subset(&sign_parties)
+        .unwrap()
+        .into_iter()
+        .map(|keygen_id| {
+            let key_share = key_shares.get(keygen_id).unwrap();
+            new_blind_sign(
+                key_share.group(),
+                key_share.share(),
+                &sign_parties,
+                &blinded_msg,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let blind_signatures = execute_honest(parties).unwrap().into_vec();
+    for blind_signature in blind_signatures.iter() {
+        assert_eq!(*blind_signature, blind_signatures[0]);
+        assert!(!verify(group.public_key(), msg, blind_signature));
+    }
+
+    // requester
+    let signature = blinding_factor.unblind(&blind_signatures[0]).unwrap();
+    assert!(verify(group.public_key(), msg, &signature));
+
+    let (_, wrong_blinding_factor) = blind(msg, rand::thread_rng());
+    let signature = wrong_blinding_factor.unblind(&blind_signatures[0]).unwrap();
+    assert!(!verify(group.public_key(), msg, &signature));
+}
@@ -172,6 +172,16 @@ impl<'a, K, V> IntoIterator for &'a FillVecMap<K, V> {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for FillVecMap<K, V>
+where
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<Option<V>>()?.collect()
+    }
+}
+
 impl<K, V> FromIterator<Option<V>> for FillVecMap<K, V> {
     fn from_iter<Iter: IntoIterator<Item = Option<V>>>(iter: Iter) -> Self {
         let vec = Vec::from_iter(iter);
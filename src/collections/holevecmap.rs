@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use tracing::error;
 
 use crate::sdk::api::{TofnFatal, TofnResult};
@@ -18,6 +20,26 @@ impl<K, V> HoleVecMap<K, V> {
         Self { vec, hole }
     }
 
+    /// Call `f` on each index in `0..len` except `hole`.
+    /// Fail if `hole` is out of bounds or `f` fails.
+    pub fn from_fn<F>(len: usize, hole: TypedUsize<K>, f: F) -> TofnResult<Self>
+    where
+        F: FnMut(TypedUsize<K>) -> TofnResult<V>,
+    {
+        if hole.as_usize() >= len {
+            error!("hole {} out of bounds {}", hole, len);
+            return Err(TofnFatal);
+        }
+        Ok(Self::from_vecmap(
+            (0..len)
+                .filter(|&i| i != hole.as_usize())
+                .map(TypedUsize::from_usize)
+                .map(f)
+                .collect::<TofnResult<_>>()?,
+            hole,
+        ))
+    }
+
     pub fn get(&self, index: TypedUsize<K>) -> TofnResult<&V> {
         self.vec.get(self.map_index(index)?)
     }
@@ -54,6 +76,21 @@ impl<K, V> HoleVecMap<K, V> {
         HoleVecMapIter::new(self.vec.iter(), self.hole)
     }
 
+    pub fn iter_mut(&mut self) -> HoleVecMapIter<K, core::slice::IterMut<V>> {
+        HoleVecMapIter::new(self.vec.iter_mut(), self.hole)
+    }
+
+    /// Pair up the entries of `self` and `other` with the same index.
+    /// Fail if their holes or lengths differ.
+    pub fn zip<W>(self, other: HoleVecMap<K, W>) -> TofnResult<HoleVecMap<K, (V, W)>> {
+        if self.hole.as_usize() != other.hole.as_usize() {
+            error!("zip hole mismatch: {} vs {}", self.hole, other.hole);
+            return Err(TofnFatal);
+        }
+        let hole = self.hole;
+        Ok(HoleVecMap::from_vecmap(self.vec.zip(other.vec)?, hole))
+    }
+
     fn map_index(&self, index: TypedUsize<K>) -> TofnResult<TypedUsize<K>> {
         match index.as_usize() {
             i if i < self.hole.as_usize() => Ok(index),
@@ -81,6 +118,29 @@ impl<K, V> HoleVecMap<K, V> {
         ))
     }
 
+    /// Like `ref_map2_result` except that `f` is applied to all entries in parallel
+    /// if the `parallel` feature is enabled.
+    /// See [VecMap::par_ref_map2_result].
+    pub fn par_ref_map2_result<W, F>(&self, f: F) -> TofnResult<HoleVecMap<K, W>>
+    where
+        F: Fn((TypedUsize<K>, &V)) -> TofnResult<W> + Sync + Send,
+        V: Sync,
+        W: Send,
+    {
+        let hole = self.hole.as_usize();
+        Ok(HoleVecMap::<K, W>::from_vecmap(
+            self.vec.par_ref_map2_result(|(i, v)| {
+                // skip the hole
+                let i = match i.as_usize() {
+                    i if i < hole => i,
+                    i => i + 1,
+                };
+                f((TypedUsize::from_usize(i), v))
+            })?,
+            self.hole,
+        ))
+    }
+
     pub fn map<W, F>(self, f: F) -> HoleVecMap<K, W>
     where
         F: FnMut(V) -> W,
@@ -116,6 +176,18 @@ impl<K, V> HoleVecMap<K, V> {
     }
 }
 
+impl<K, V, W> HoleVecMap<K, (V, W)> {
+    /// Inverse of [HoleVecMap::zip]
+    pub fn unzip(self) -> (HoleVecMap<K, V>, HoleVecMap<K, W>) {
+        let hole = self.hole;
+        let (v, w): (Vec<V>, Vec<W>) = self.vec.into_vec().into_iter().unzip();
+        (
+            HoleVecMap::from_vecmap(VecMap::from_vec(v), hole),
+            HoleVecMap::from_vecmap(VecMap::from_vec(w), hole),
+        )
+    }
+}
+
 impl<K, V> IntoIterator for HoleVecMap<K, V> {
     type Item = (TypedUsize<K>, <alloc::vec::IntoIter<V> as Iterator>::Item);
     type IntoIter = HoleVecMapIter<K, alloc::vec::IntoIter<V>>;
@@ -135,3 +207,59 @@ impl<'a, K, V> IntoIterator for &'a HoleVecMap<K, V> {
         self.iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::HoleVecMap;
+    use crate::{
+        collections::{TypedUsize, VecMap},
+        sdk::api::TofnFatal,
+    };
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestIndex;
+
+    #[test]
+    fn from_fn() {
+        let hole = TypedUsize::<TestIndex>::from_usize(1);
+        let map = HoleVecMap::from_fn(4, hole, |i| Ok(i.as_usize() * 10)).unwrap();
+        assert_eq!(
+            map,
+            VecMap::from_vec(alloc::vec![0, 20, 30])
+                .remember_hole(hole)
+                .unwrap()
+        );
+        assert_eq!(map.get(TypedUsize::from_usize(3)).unwrap(), &30);
+
+        assert!(HoleVecMap::<_, usize>::from_fn(1, hole, |i| Ok(i.as_usize())).is_err());
+        assert!(HoleVecMap::<_, usize>::from_fn(4, hole, |_| Err(TofnFatal)).is_err());
+    }
+
+    #[test]
+    fn zip_unzip() {
+        let hole = TypedUsize::<TestIndex>::from_usize(1);
+        let v0 = VecMap::from_vec(alloc::vec![0, 2, 3])
+            .remember_hole(hole)
+            .unwrap();
+        let v1 = v0.clone().map(|x| x * 10);
+
+        let mut zipped = v0.clone().zip(v1.clone()).unwrap();
+        let indices: alloc::vec::Vec<_> = zipped.iter().map(|(i, _)| i.as_usize()).collect();
+        assert_eq!(indices, alloc::vec![0, 2, 3]);
+        assert_eq!(zipped.get(TypedUsize::from_usize(2)).unwrap(), &(2, 20));
+        assert!(zipped.get(hole).is_err());
+
+        for (i, (a, _)) in zipped.iter_mut() {
+            assert_ne!(i, hole);
+            *a += 1;
+        }
+        let (a, b) = zipped.unzip();
+        assert_eq!(a, v0.map(|x| x + 1));
+        assert_eq!(b, v1);
+
+        let other_hole = VecMap::from_vec(alloc::vec![0, 1, 3])
+            .remember_hole(TypedUsize::from_usize(2))
+            .unwrap();
+        assert!(a.zip(other_hole).is_err());
+    }
+}
@@ -1,7 +1,8 @@
 //! A subset of typed indices
 use super::{FillVecMap, TypedUsize};
-use crate::sdk::api::TofnResult;
+use crate::sdk::api::{TofnFatal, TofnResult};
 use serde::{Deserialize, Serialize};
+use tracing::error;
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Subset<K>(FillVecMap<K, ()>);
@@ -16,6 +17,34 @@ impl<K> Subset<K> {
         Self(v.ref_map(|_| ()))
     }
 
+    /// Fail if any of `indices` is not less than `max_size`
+    pub fn from_indices(
+        indices: impl IntoIterator<Item = TypedUsize<K>>,
+        max_size: usize,
+    ) -> TofnResult<Self> {
+        let mut subset = Self::with_max_size(max_size);
+        for index in indices {
+            subset.add(index)?;
+        }
+        Ok(subset)
+    }
+
+    /// Raise the max size to `new_max_size`, keeping all members.
+    /// Fail if `new_max_size` is less than the current max size.
+    pub fn grow(&mut self, new_max_size: usize) -> TofnResult<()> {
+        if new_max_size < self.max_size() {
+            error!(
+                "cannot shrink subset from max size {} to {}",
+                self.max_size(),
+                new_max_size
+            );
+            return Err(TofnFatal);
+        }
+        let grown = Self::from_indices(self.iter(), new_max_size)?;
+        *self = grown;
+        Ok(())
+    }
+
     pub fn max_size(&self) -> usize {
         self.0.size()
     }
@@ -41,4 +70,38 @@ impl<K> Subset<K> {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a, K> arbitrary::Arbitrary<'a> for Subset<K> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.arbitrary()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Subset;
+    use crate::collections::TypedUsize;
+
+    struct TestIndex;
+
+    #[test]
+    fn from_indices_and_grow() {
+        let indices = [1, 3]
+            .iter()
+            .map(|&i| TypedUsize::<TestIndex>::from_usize(i));
+        assert!(Subset::from_indices(indices.clone(), 3).is_err());
+
+        let mut subset = Subset::from_indices(indices, 4).unwrap();
+        assert_eq!(subset.member_count(), 2);
+        assert!(subset.add(TypedUsize::from_usize(5)).is_err());
+
+        assert!(subset.grow(3).is_err());
+        subset.grow(6).unwrap();
+        assert_eq!(subset.max_size(), 6);
+        subset.add(TypedUsize::from_usize(5)).unwrap();
+        let members: alloc::vec::Vec<_> = subset.iter().map(|i| i.as_usize()).collect();
+        assert_eq!(members, alloc::vec![1, 3, 5]);
+    }
+}
+
 // TODO don't know how to impl IntoIterator because don't know `IntoIter` type
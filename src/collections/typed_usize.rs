@@ -1,7 +1,10 @@
 use core::marker::PhantomData;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tracing::error;
 use zeroize::Zeroize;
 
+use crate::sdk::api::{TofnFatal, TofnResult};
+
 pub struct TypedUsize<K>(usize, PhantomData<K>);
 
 impl<K> TypedUsize<K> {
@@ -9,6 +12,16 @@ impl<K> TypedUsize<K> {
         TypedUsize(index, PhantomData)
     }
 
+    /// Like [TypedUsize::from_usize] but fail unless `index < bound`,
+    /// eg. for indices read from peer data
+    pub fn try_from_usize(index: usize, bound: usize) -> TofnResult<Self> {
+        if index >= bound {
+            error!("index {} out of bounds {}", index, bound);
+            return Err(TofnFatal);
+        }
+        Ok(Self::from_usize(index))
+    }
+
     pub fn as_usize(&self) -> usize {
         self.0
     }
@@ -56,6 +69,13 @@ impl<K> PartialEq for TypedUsize<K> {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a, K> arbitrary::Arbitrary<'a> for TypedUsize<K> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::from_usize(u.arbitrary()?))
+    }
+}
+
 impl<K> Serialize for TypedUsize<K> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -77,10 +97,19 @@ impl<'de, K> Deserialize<'de> for TypedUsize<K> {
 #[cfg(test)]
 mod tests {
     use super::TypedUsize;
-    use crate::sdk::implementer_api::{deserialize, serialize};
+    use crate::sdk::api::{deserialize, serialize};
 
     struct TestMarker;
 
+    #[test]
+    fn try_from_usize() {
+        assert_eq!(
+            TypedUsize::<TestMarker>::try_from_usize(2, 3).unwrap(),
+            TypedUsize::from_usize(2)
+        );
+        assert!(TypedUsize::<TestMarker>::try_from_usize(3, 3).is_err());
+    }
+
     #[test]
     fn serde_bincode() {
         // test: `TypedUsize` and `usize` serialize to the same bytes
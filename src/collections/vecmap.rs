@@ -7,7 +7,7 @@ use zeroize::Zeroize;
 
 use crate::sdk::api::{TofnFatal, TofnResult};
 
-use super::{vecmap_iter::VecMapIter, HoleVecMap, TypedUsize};
+use super::{vecmap_iter::VecMapIter, FillVecMap, HoleVecMap, TypedUsize};
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct VecMap<K, V>(Vec<V>, core::marker::PhantomData<TypedUsize<K>>);
@@ -94,6 +94,38 @@ impl<K, V> VecMap<K, V> {
         ))
     }
 
+    /// Like [VecMap::map_result] but with any error type
+    pub fn try_map<W, E, F>(self, f: F) -> Result<VecMap<K, W>, E>
+    where
+        F: FnMut(V) -> Result<W, E>,
+    {
+        Ok(VecMap::<K, W>::from_vec(
+            self.0.into_iter().map(f).collect::<Result<Vec<W>, E>>()?,
+        ))
+    }
+
+    /// Keep only the entries for which `f` returns `true`.
+    /// Unlike `Vec::retain`, remaining entries keep their index:
+    /// removed entries are `None` in the output.
+    pub fn retain<F>(self, mut f: F) -> FillVecMap<K, V>
+    where
+        F: FnMut(TypedUsize<K>, &V) -> bool,
+    {
+        self.into_iter()
+            .map(|(i, v)| if f(i, &v) { Some(v) } else { None })
+            .collect()
+    }
+
+    /// Pair up the entries of `self` and `other` with the same index.
+    /// Fail if their lengths differ.
+    pub fn zip<W>(self, other: VecMap<K, W>) -> TofnResult<VecMap<K, (V, W)>> {
+        if self.len() != other.len() {
+            error!("zip length mismatch: {} vs {}", self.len(), other.len());
+            return Err(TofnFatal);
+        }
+        Ok(VecMap::from_vec(self.0.into_iter().zip(other.0).collect()))
+    }
+
     pub fn map2<W, F>(self, f: F) -> VecMap<K, W>
     where
         F: FnMut((TypedUsize<K>, V)) -> W,
@@ -106,6 +138,36 @@ impl<K, V> VecMap<K, V> {
     {
         self.into_iter().map(f).collect()
     }
+
+    /// Like `iter().map(f).collect()` except that `f` is applied to all entries in parallel
+    /// if the `parallel` feature is enabled.
+    /// Use it for expensive, independent per-peer work such as proof verification.
+    #[cfg(feature = "parallel")]
+    pub fn par_ref_map2_result<W, F>(&self, f: F) -> TofnResult<VecMap<K, W>>
+    where
+        F: Fn((TypedUsize<K>, &V)) -> TofnResult<W> + Sync + Send,
+        V: Sync,
+        W: Send,
+    {
+        use rayon::prelude::*;
+        Ok(VecMap::from_vec(
+            self.0
+                .par_iter()
+                .enumerate()
+                .map(|(i, v)| f((TypedUsize::from_usize(i), v)))
+                .collect::<TofnResult<Vec<W>>>()?,
+        ))
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    pub fn par_ref_map2_result<W, F>(&self, f: F) -> TofnResult<VecMap<K, W>>
+    where
+        F: Fn((TypedUsize<K>, &V)) -> TofnResult<W> + Sync + Send,
+        V: Sync,
+        W: Send,
+    {
+        self.iter().map(f).collect()
+    }
 }
 
 impl<K, V> IntoIterator for VecMap<K, V> {
@@ -128,8 +190,88 @@ impl<'a, K, V> IntoIterator for &'a VecMap<K, V> {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for VecMap<K, V>
+where
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::from_vec(u.arbitrary()?))
+    }
+}
+
 impl<K, V> FromIterator<V> for VecMap<K, V> {
     fn from_iter<Iter: IntoIterator<Item = V>>(iter: Iter) -> Self {
         Self::from_vec(Vec::from_iter(iter))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::VecMap;
+    use crate::collections::TypedUsize;
+
+    #[derive(Debug, Clone, PartialEq, serde::Serialize)]
+    struct TestIndex;
+
+    #[test]
+    fn combinators() {
+        let v: VecMap<TestIndex, usize> = (0..5).collect();
+
+        assert_eq!(
+            v.clone()
+                .try_map(|x| if x < 5 { Ok(x * 2) } else { Err(x) }),
+            Ok((0..5).map(|x| x * 2).collect())
+        );
+        assert_eq!(
+            v.clone().try_map(|x| if x < 3 { Ok(x) } else { Err(x) }),
+            Err(3)
+        );
+
+        let odd = v.clone().retain(|_, x| x % 2 == 1);
+        assert_eq!(odd.size(), 5);
+        assert_eq!(odd.some_count(), 2);
+        assert_eq!(odd.get(TypedUsize::from_usize(3)).unwrap(), Some(&3));
+        assert!(odd.is_none(TypedUsize::from_usize(2)).unwrap());
+
+        let zipped = v.clone().zip(v.ref_map(|x| x + 10)).unwrap();
+        assert_eq!(zipped.get(TypedUsize::from_usize(4)).unwrap(), &(4, 14));
+        assert!(v
+            .zip(VecMap::<TestIndex, usize>::from_vec(alloc::vec![0]))
+            .is_err());
+
+        let mut v: VecMap<TestIndex, usize> = (0..2).collect();
+        *v.get_mut(TypedUsize::from_usize(1)).unwrap() = 7;
+        assert_eq!(v.into_vec(), alloc::vec![0, 7]);
+    }
+
+    /// Wire messages use bincode with varint integers:
+    /// a `VecMap` costs one varint length plus its densely packed elements
+    /// and a `FillVecMap` one extra tag byte per entry.
+    /// A `HoleVecMap` is sent as the `VecMap` of its entries, without its hole.
+    #[test]
+    fn wire_encoding() {
+        use crate::{
+            collections::{FillVecMap, HoleVecMap},
+            sdk::api::{deserialize, serialize},
+        };
+
+        let v: VecMap<TestIndex, u8> = (0..200).collect();
+        assert_eq!(serialize(&v).unwrap().len(), 1 + 200);
+        let v: VecMap<TestIndex, u8> = (0..=250).collect();
+        assert_eq!(serialize(&v).unwrap().len(), 3 + 251);
+
+        let mut v = FillVecMap::<TestIndex, u8>::with_size(100);
+        v.set(TypedUsize::from_usize(7), 7).unwrap();
+        assert_eq!(serialize(&v).unwrap().len(), 1 + 100 + 1);
+
+        // the recipient restores the hole, its own index, with `remember_hole`
+        let hole = TypedUsize::from_usize(3);
+        let v =
+            HoleVecMap::<TestIndex, u8>::from_fn(200, hole, |i| Ok(i.as_usize() as u8)).unwrap();
+        let bytes = serialize(&v.clone().forget_hole()).unwrap();
+        assert_eq!(bytes.len(), 1 + 199);
+        let decoded: VecMap<TestIndex, u8> = deserialize(&bytes).unwrap();
+        assert_eq!(decoded.remember_hole(hole).unwrap(), v);
+    }
+}
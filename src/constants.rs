@@ -1,2 +1,3 @@
 // Domain separation for protocols/schemes
 pub const ECDSA_TAG: u8 = 0x00;
+pub const TRANSCRIPT_TAG: u8 = 0x01;
@@ -14,6 +14,9 @@ pub const PEDERSEN_SECP256K1_ALTERNATE_GENERATOR_TAG: u8 = 0x09;
 
 pub const COMPOSITE_DLOG_PROOF_TAG: u8 = 0x0A;
 pub const PAILLIER_KEY_PROOF_TAG: u8 = 0x0B;
+pub const PAILLIER_PARTIAL_DECRYPTION_PROOF_TAG: u8 = 0x0C;
+pub const SCHNORR_SIGNATURE_TAG: u8 = 0x0D;
+pub const BLS_Y_I_COMMIT_TAG: u8 = 0x0E;
 
 /// The max size of each prime is 1024 bits.
 pub const MODULUS_MAX_SIZE: usize = 2048;
@@ -0,0 +1,151 @@
+//! Hashed ElGamal encryption on secp256k1 (DHIES).
+//!
+//! To encrypt `msg` to public key `Y = G * y`, sample `u` and compute `U = G * u`.
+//! Keys for a SHA-256 keystream and an HMAC-SHA256 tag are derived from the shared point `D = Y * u`.
+//! The holder of `y` recomputes `D = U * y`.
+//! Since `D` is linear in `y`, holders of Shamir shares of `y` can compute it jointly
+//! without learning `y`. See [crate::gg20::decrypt].
+use hmac::{Hmac, Mac};
+use k256::{ProjectivePoint, Scalar};
+use serde::{Deserialize, Serialize};
+use serde_with::{hex::Hex, serde_as, IfIsHumanReadable};
+use sha2::{Digest, Sha256};
+use tracing::error;
+use zeroize::Zeroize;
+
+use crate::{
+    crypto_tools::k256_serde::{self, SecretScalar},
+    sdk::api::{BytesVec, TofnFatal, TofnResult},
+};
+
+const KEYSTREAM_TAG: &[u8] = b"tofn-elgamal-keystream";
+const MAC_TAG: &[u8] = b"tofn-elgamal-mac";
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct Ciphertext {
+    U: k256_serde::ProjectivePoint,
+    #[serde_as(as = "IfIsHumanReadable<Hex>")]
+    payload: BytesVec,
+    #[serde_as(as = "IfIsHumanReadable<Hex>")]
+    tag: [u8; 32],
+}
+
+impl Ciphertext {
+    /// The ephemeral point `U = G * u`
+    pub fn ephemeral_point(&self) -> &ProjectivePoint {
+        self.U.as_ref()
+    }
+}
+
+/// Encrypt `msg` to `public_key`
+#[allow(non_snake_case)]
+pub fn encrypt(public_key: &ProjectivePoint, msg: &[u8]) -> TofnResult<Ciphertext> {
+    if *public_key == ProjectivePoint::IDENTITY {
+        error!("public key is the point at infinity");
+        return Err(TofnFatal);
+    }
+
+    let u = SecretScalar::random_with_thread_rng();
+    let U = ProjectivePoint::GENERATOR * u.as_ref();
+    let D = public_key * u.as_ref();
+
+    let mut payload = msg.to_vec();
+    apply_keystream(&D, &U, &mut payload);
+    let tag = tag_mac(&D, &U, &payload)?.finalize().into_bytes().into();
+
+    Ok(Ciphertext {
+        U: U.into(),
+        payload,
+        tag,
+    })
+}
+
+/// Decrypt `ciphertext` with `secret_key`
+pub fn decrypt(secret_key: &Scalar, ciphertext: &Ciphertext) -> TofnResult<BytesVec> {
+    decrypt_with_shared_point(&(ciphertext.ephemeral_point() * secret_key), ciphertext)
+}
+
+/// Decrypt `ciphertext` with the shared point `D = U * y`
+#[allow(non_snake_case)]
+pub fn decrypt_with_shared_point(
+    D: &ProjectivePoint,
+    ciphertext: &Ciphertext,
+) -> TofnResult<BytesVec> {
+    let U = ciphertext.ephemeral_point();
+    if tag_mac(D, U, &ciphertext.payload)?
+        .verify_slice(&ciphertext.tag)
+        .is_err()
+    {
+        error!("ciphertext failed to authenticate");
+        return Err(TofnFatal);
+    }
+
+    let mut msg = ciphertext.payload.clone();
+    apply_keystream(D, U, &mut msg);
+    Ok(msg)
+}
+
+/// XOR `bytes` with the SHA-256 keystream `H(tag || D || U || counter)`
+#[allow(non_snake_case)]
+fn apply_keystream(D: &ProjectivePoint, U: &ProjectivePoint, bytes: &mut [u8]) {
+    for (counter, chunk) in bytes.chunks_mut(32).enumerate() {
+        let mut block: [u8; 32] = Sha256::new()
+            .chain_update(KEYSTREAM_TAG)
+            .chain_update(k256_serde::point_to_bytes(D))
+            .chain_update(k256_serde::point_to_bytes(U))
+            .chain_update((counter as u64).to_be_bytes())
+            .finalize()
+            .into();
+        for (byte, key) in chunk.iter_mut().zip(block.iter()) {
+            *byte ^= key;
+        }
+        block.zeroize();
+    }
+}
+
+/// HMAC of `U || payload` keyed by `H(tag || D)`
+#[allow(non_snake_case)]
+fn tag_mac(D: &ProjectivePoint, U: &ProjectivePoint, payload: &[u8]) -> TofnResult<Hmac<Sha256>> {
+    let mut mac_key: [u8; 32] = Sha256::new()
+        .chain_update(MAC_TAG)
+        .chain_update(k256_serde::point_to_bytes(D))
+        .finalize()
+        .into();
+    let mac = Hmac::<Sha256>::new_from_slice(&mac_key).map_err(|_| {
+        error!("failure to initialize hmac");
+        TofnFatal
+    });
+    mac_key.zeroize();
+
+    Ok(mac?
+        .chain_update(k256_serde::point_to_bytes(U))
+        .chain_update(payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ecdsa::elliptic_curve::Field;
+
+    #[test]
+    fn encrypt_decrypt() {
+        let secret_key = Scalar::random(rand::thread_rng());
+        let public_key = ProjectivePoint::GENERATOR * secret_key;
+
+        for msg in [&b""[..], b"short", &[7; 100]] {
+            let ciphertext = encrypt(&public_key, msg).unwrap();
+            assert_eq!(decrypt(&secret_key, &ciphertext).unwrap(), msg);
+
+            // wrong key
+            let other_key = Scalar::random(rand::thread_rng());
+            assert!(decrypt(&other_key, &ciphertext).is_err());
+
+            // tampered payload
+            let mut tampered = ciphertext.clone();
+            tampered.payload.push(0);
+            assert!(decrypt(&secret_key, &tampered).is_err());
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use serde_with::{hex::Hex, serde_as, IfIsHumanReadable};
 use sha2::{digest::Update, Digest, Sha256};
 use zeroize::Zeroize;
 
@@ -7,16 +8,25 @@ use crate::collections::TypedUsize;
 
 // can't derive Serialize, Deserialize for sha3::digest::Output<Sha3_256>
 // so use [u8; 32] instead
+#[serde_as]
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-pub struct Output([u8; 32]);
+pub struct Output(#[serde_as(as = "IfIsHumanReadable<Hex>")] [u8; 32]);
 
+#[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize, Zeroize)]
 #[zeroize(drop)]
-pub struct Randomness([u8; 32]);
+pub struct Randomness(#[serde_as(as = "IfIsHumanReadable<Hex>")] [u8; 32]);
+
+impl Randomness {
+    pub fn random() -> Self {
+        let mut randomness = Self([0; 32]);
+        rand::thread_rng().fill_bytes(&mut randomness.0);
+        randomness
+    }
+}
 
 pub fn commit<K>(tag: u8, peer_id: TypedUsize<K>, msg: impl AsRef<[u8]>) -> (Output, Randomness) {
-    let mut randomness = Randomness([0; 32]);
-    rand::thread_rng().fill_bytes(&mut randomness.0);
+    let randomness = Randomness::random();
     (
         commit_with_randomness(tag, peer_id, msg, &randomness),
         randomness,
@@ -5,6 +5,7 @@
 //! [Implementing Serialize · Serde](https://serde.rs/impl-serialize.html)
 //! [Implementing Deserialize · Serde](https://serde.rs/impl-deserialize.html)
 
+use alloc::vec::Vec;
 use ecdsa::elliptic_curve::{
     consts::U33, generic_array::GenericArray, group::GroupEncoding, Field,
 };
@@ -38,6 +39,24 @@ impl SecretScalar {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SecretScalar {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(arbitrary_scalar(u)?))
+    }
+}
+
+/// Reduce 32 arbitrary bytes modulo the curve order.
+#[cfg(feature = "arbitrary")]
+pub(crate) fn arbitrary_scalar(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Scalar> {
+    use ecdsa::elliptic_curve::ops::Reduce;
+
+    let bytes: [u8; 32] = u.arbitrary()?;
+    Ok(<Scalar as Reduce<k256::U256>>::from_be_bytes_reduced(
+        *k256::FieldBytes::from_slice(&bytes[..]),
+    ))
+}
+
 #[cfg(feature = "malicious")]
 impl AsMut<Scalar> for SecretScalar {
     fn as_mut(&mut self) -> &mut Scalar {
@@ -59,7 +78,11 @@ impl Serialize for EncodedPoint {
     where
         S: Serializer,
     {
-        serializer.serialize_bytes(self.0.as_bytes())
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(self.0.as_bytes()))
+        } else {
+            serializer.serialize_bytes(self.0.as_bytes())
+        }
     }
 }
 
@@ -68,7 +91,11 @@ impl<'de> Deserialize<'de> for EncodedPoint {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_bytes(EncodedPointVisitor)
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(EncodedPointVisitor)
+        } else {
+            deserializer.deserialize_bytes(EncodedPointVisitor)
+        }
     }
 }
 
@@ -89,6 +116,26 @@ impl<'de> Visitor<'de> for EncodedPointVisitor {
             k256::EncodedPoint::from_bytes(v).map_err(E::custom)?,
         ))
     }
+
+    /// Human-readable formats such as JSON use a hex string
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(&hex::decode(v).map_err(E::custom)?)
+    }
+
+    /// Older versions of tofn encoded bytes as a sequence of integers in JSON
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        self.visit_bytes(&bytes)
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -126,6 +173,14 @@ impl AsRef<k256::ProjectivePoint> for ProjectivePoint {
     }
 }
 
+/// A multiple of the generator by an arbitrary scalar
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ProjectivePoint {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::GENERATOR * arbitrary_scalar(u)?)
+    }
+}
+
 #[cfg(feature = "malicious")]
 impl AsMut<k256::ProjectivePoint> for ProjectivePoint {
     fn as_mut(&mut self) -> &mut k256::ProjectivePoint {
@@ -225,6 +280,27 @@ mod tests {
         assert_eq!(ProjectivePoint(p), p_decoded);
     }
 
+    #[test]
+    fn json_round_trip() {
+        let p = ProjectivePoint::from(
+            k256::ProjectivePoint::GENERATOR * Scalar::random(rand::thread_rng()),
+        );
+        let p_json = serde_json::to_vec(&p).unwrap();
+        let p_decoded: ProjectivePoint = serde_json::from_slice(&p_json).unwrap();
+        assert_eq!(p, p_decoded);
+
+        // hex string
+        assert_eq!(
+            p_json,
+            serde_json::to_vec(&hex::encode(p.to_bytes())).unwrap()
+        );
+
+        // legacy sequence of integers
+        let p_json_legacy = serde_json::to_vec(&p.to_bytes().to_vec()).unwrap();
+        let p_decoded: ProjectivePoint = serde_json::from_slice(&p_json_legacy).unwrap();
+        assert_eq!(p, p_decoded);
+    }
+
     fn basic_round_trip_impl<T, U>(val: T, size: Option<usize>)
     where
         U: From<T> + Serialize + DeserializeOwned + PartialEq + Debug,
@@ -277,4 +353,9 @@ mod tests {
         modulus[31] -= 1;
         bincode.deserialize::<S>(&modulus).unwrap();
     }
+
+    #[test]
+    fn secret_scalar_zeroize_on_drop() {
+        assert_zeroized_on_drop!(SecretScalar::random_with_thread_rng(), 0);
+    }
 }
@@ -1,13 +1,19 @@
 use core::{
     array::TryFromSliceError,
     convert::{TryFrom, TryInto},
+    marker::PhantomData,
 };
 use ecdsa::elliptic_curve::ops::Reduce;
 use serde::{Deserialize, Serialize};
+use serde_with::{hex::Hex, serde_as, IfIsHumanReadable};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
 
 /// Sign only 32-byte hash digests
+#[serde_as]
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-pub struct MessageDigest([u8; 32]);
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct MessageDigest(#[serde_as(as = "IfIsHumanReadable<Hex>")] [u8; 32]);
 
 impl AsRef<[u8]> for MessageDigest {
     fn as_ref(&self) -> &[u8] {
@@ -33,3 +39,74 @@ impl From<&MessageDigest> for k256::Scalar {
         ))
     }
 }
+
+/// The hash function a group uses to turn messages into a [MessageDigest].
+///
+/// `MessageDigest` itself is hash-agnostic.
+/// Integrators that fix a policy once, e.g. via `type Digest = PolicyDigest<Keccak256Policy>`,
+/// get a type error wherever a digest computed under another policy is passed.
+pub trait DigestPolicy {
+    fn digest(message: &[u8]) -> MessageDigest;
+}
+
+/// Keccak-256 as used by Ethereum
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Keccak256Policy {}
+
+impl DigestPolicy for Keccak256Policy {
+    fn digest(message: &[u8]) -> MessageDigest {
+        MessageDigest(Keccak256::digest(message).into())
+    }
+}
+
+/// SHA-256
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Sha256Policy {}
+
+impl DigestPolicy for Sha256Policy {
+    fn digest(message: &[u8]) -> MessageDigest {
+        MessageDigest(Sha256::digest(message).into())
+    }
+}
+
+/// A [MessageDigest] computed under the digest policy `D`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PolicyDigest<D> {
+    digest: MessageDigest,
+    policy: PhantomData<D>,
+}
+
+impl<D: DigestPolicy> PolicyDigest<D> {
+    pub fn hash(message: &[u8]) -> Self {
+        Self::from_digest(D::digest(message))
+    }
+
+    /// Wrap a digest that the caller computed under `D` by other means, such as a protocol-specific sighash.
+    pub fn from_digest(digest: MessageDigest) -> Self {
+        Self {
+            digest,
+            policy: PhantomData,
+        }
+    }
+
+    pub fn digest(&self) -> &MessageDigest {
+        &self.digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_policies() {
+        assert_eq!(
+            hex::encode(PolicyDigest::<Keccak256Policy>::hash(b"").digest()),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(PolicyDigest::<Sha256Policy>::hash(b"").digest()),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
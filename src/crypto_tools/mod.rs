@@ -1,10 +1,16 @@
 pub mod constants;
+pub mod elgamal;
 pub mod hash;
 pub mod k256_serde;
 pub mod message_digest;
+#[cfg(feature = "protocols")]
 pub mod mta;
+#[cfg(feature = "protocols")]
 pub mod paillier;
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
 pub mod rng;
 pub mod ss;
 pub mod vss;
+#[cfg(feature = "protocols")]
 pub mod zkp;
@@ -2,7 +2,7 @@ use crate::{
     collections::TypedUsize,
     crypto_tools::paillier::{
         zk::{mta, ZkSetup},
-        Ciphertext, EncryptionKey, Plaintext, Randomness,
+        Ciphertext, EncryptionKey, Plaintext, PrecomputedRandomness, Randomness,
     },
     gg20::sign::SignShareId,
     sdk::api::TofnResult,
@@ -18,15 +18,21 @@ pub struct Secret {
     pub beta_prime_randomness: Randomness,
 }
 
+/// `randomness` is consumed by the encryption of `beta_prime`
 pub fn mta_response(
     a_ek: &EncryptionKey,
     a_ciphertext: &Ciphertext,
     b: &k256::Scalar,
+    randomness: PrecomputedRandomness,
 ) -> (Ciphertext, Secret) {
     let beta_prime = a_ek.random_plaintext();
-    let beta_prime_randomness = a_ek.sample_randomness();
-    let (c_b, beta) =
-        mta_response_from_randomness(a_ek, a_ciphertext, b, &beta_prime, &beta_prime_randomness);
+    let (beta_prime_ciphertext, beta_prime_randomness) =
+        a_ek.encrypt_with_precomputed(&beta_prime, randomness);
+    let c_b = a_ek.add(
+        &a_ek.mul(a_ciphertext, &Plaintext::from_scalar(b)),
+        &beta_prime_ciphertext,
+    );
+    let beta = beta_prime.to_scalar().negate();
     (
         c_b,
         Secret {
@@ -78,8 +84,9 @@ pub fn mta_response_with_proof(
     a_ek: &EncryptionKey,
     a_ciphertext: &Ciphertext,
     b: &k256::Scalar,
+    randomness: PrecomputedRandomness,
 ) -> (Ciphertext, mta::Proof, Secret) {
-    let (c_b, s) = mta_response(a_ek, a_ciphertext, b);
+    let (c_b, s) = mta_response(a_ek, a_ciphertext, b, randomness);
     let proof = a_zkp.mta_proof(
         &mta::Statement {
             prover_id,
@@ -104,8 +111,9 @@ pub fn mta_response_with_proof_wc(
     a_ek: &EncryptionKey,
     a_ciphertext: &Ciphertext,
     b: &k256::Scalar,
+    randomness: PrecomputedRandomness,
 ) -> TofnResult<(Ciphertext, mta::ProofWc, Secret)> {
-    let (c_b, s) = mta_response(a_ek, a_ciphertext, b);
+    let (c_b, s) = mta_response(a_ek, a_ciphertext, b, randomness);
     let proof_wc = a_zkp.mta_proof_wc(
         &mta::StatementWc {
             stmt: mta::Statement {
@@ -178,8 +186,16 @@ mod tests {
             },
             &a_range_proof,
         ));
-        let (c_b, b_mta_proof_wc, b_secret) =
-            mta_response_with_proof_wc(a_id, b_id, &a_zkp, &a_ek, &a_ciphertext, &b).unwrap();
+        let (c_b, b_mta_proof_wc, b_secret) = mta_response_with_proof_wc(
+            a_id,
+            b_id,
+            &a_zkp,
+            &a_ek,
+            &a_ciphertext,
+            &b,
+            a_ek.precompute_randomness(),
+        )
+        .unwrap();
 
         // MtA step 3: party a
         assert!(a_zkp.verify_mta_proof_wc(
@@ -207,5 +223,7 @@ mod tests {
             &c_b,
             &b_secret
         ));
+
+        assert_zeroized_on_drop!(b_secret, beta);
     }
 }
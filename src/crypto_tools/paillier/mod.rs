@@ -11,12 +11,17 @@ use ecdsa::elliptic_curve::ops::Reduce;
 use libpaillier::unknown_order::BigNumber;
 use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
 use zeroize::Zeroize;
 
-use crate::sdk::api::{TofnFatal, TofnResult};
+use crate::{
+    crypto_tools::constants::{MODULUS_MAX_SIZE, MODULUS_MIN_SIZE},
+    sdk::api::{TofnFatal, TofnResult},
+};
 
 use self::utils::{member_of_mod, member_of_mul_group};
 
+pub mod threshold;
 pub mod utils;
 pub mod zk;
 
@@ -51,6 +56,21 @@ pub struct SecretNumber(BigNumber);
 pub struct EncryptionKey(libpaillier::EncryptionKey);
 
 impl EncryptionKey {
+    /// Construct an encryption key from the big-endian bytes of its modulus `N`.
+    /// Return `TofnFatal` if `N` is not a composite of the expected size.
+    pub fn from_modulus_bytes(n: &[u8]) -> TofnResult<Self> {
+        let n = BigNumber::from_slice(n);
+        if n.bit_length() < MODULUS_MIN_SIZE || n.bit_length() > MODULUS_MAX_SIZE || n.is_prime() {
+            error!("invalid paillier modulus of {} bits", n.bit_length());
+            return Err(TofnFatal);
+        }
+        let ek = libpaillier::EncryptionKey::from_bytes(n.to_bytes()).map_err(|err| {
+            error!("invalid paillier modulus: {}", err);
+            TofnFatal
+        })?;
+        Ok(Self(ek))
+    }
+
     pub fn sample_randomness(&self) -> Randomness {
         Randomness(BigNumber::random(self.0.n()))
     }
@@ -89,6 +109,37 @@ impl EncryptionKey {
         Ciphertext(self.0.encrypt_with_randomness(&p.0, &r.0))
     }
 
+    /// Sample randomness `r` and precompute `r^N mod N^2`, the expensive part of encryption.
+    /// The result can be consumed later by [EncryptionKey::encrypt_with_precomputed].
+    pub fn precompute_randomness(&self) -> PrecomputedRandomness {
+        let r = self.sample_randomness();
+        let r_n = SecretNumber(r.0.modpow(self.0.n(), self.0.nn()));
+        PrecomputedRandomness {
+            n: self.0.n().clone(),
+            r,
+            r_n,
+        }
+    }
+
+    /// Encrypt a plaintext `p` using precomputed randomness.
+    /// Equivalent to [EncryptionKey::encrypt_with_randomness] with the randomness in `pre`
+    /// but much cheaper: `(1 + N)^p = 1 + pN mod N^2`, so only one modular multiplication remains.
+    ///
+    /// If `pre` was computed for a different key then fresh randomness is used instead.
+    pub fn encrypt_with_precomputed(
+        &self,
+        p: &Plaintext,
+        pre: PrecomputedRandomness,
+    ) -> (Ciphertext, Randomness) {
+        if &pre.n != self.0.n() {
+            warn!("precomputed randomness does not match encryption key; use fresh randomness");
+            return self.encrypt(p);
+        }
+        let nn = self.0.nn();
+        let g_p = BigNumber::one().modadd(&p.0.modmul(self.0.n(), nn), nn);
+        (Ciphertext(g_p.modmul(&pre.r_n.0, nn)), pre.r)
+    }
+
     /// Homomorphically add `c1` to `c2`
     pub fn add(&self, c1: &Ciphertext, c2: &Ciphertext) -> Ciphertext {
         Ciphertext(self.0.add_unchecked(&c1.0, &c2.0))
@@ -100,12 +151,42 @@ impl EncryptionKey {
     }
 }
 
+/// Randomness `r` for Paillier encryption together with `r^N mod N^2`.
+/// See [EncryptionKey::precompute_randomness].
+/// `r^N` must be kept secret: anyone who knows it can decrypt the ciphertext it produces.
+#[derive(Debug)]
+pub struct PrecomputedRandomness {
+    n: BigNumber, // modulus of the encryption key used to compute `r_n`
+    r: Randomness,
+    r_n: SecretNumber,
+}
+
 /// Wrapper for Paillier decryption key
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Zeroize)]
 #[zeroize(drop)]
 pub struct DecryptionKey(libpaillier::DecryptionKey);
 
 impl DecryptionKey {
+    /// Construct a decryption key from the big-endian bytes of the primes `p`, `q`.
+    pub fn from_prime_bytes(p: &[u8], q: &[u8]) -> TofnResult<Self> {
+        let (p, q) = (BigNumber::from_slice(p), BigNumber::from_slice(q));
+        if p == q || !p.is_prime() || !q.is_prime() {
+            error!("invalid paillier primes");
+            return Err(TofnFatal);
+        }
+        let dk =
+            libpaillier::DecryptionKey::with_safe_primes_unchecked(&p, &q).ok_or_else(|| {
+                error!("invalid paillier primes");
+                TofnFatal
+            })?;
+        Ok(Self(dk))
+    }
+
+    /// The encryption key corresponding to this decryption key
+    pub fn encryption_key(&self) -> EncryptionKey {
+        EncryptionKey(self.0.borrow().into())
+    }
+
     pub fn decrypt(&self, c: &Ciphertext) -> Plaintext {
         Plaintext(self.0.decrypt_unchecked(&c.0))
     }
@@ -266,6 +347,22 @@ mod tests {
         assert_eq!(s, s2);
     }
 
+    #[test]
+    fn precomputed_randomness() {
+        let pt = Plaintext::from_scalar(&k256::Scalar::random(rand::thread_rng()));
+        let (ek, dk) = keygen_unsafe(&mut rand::thread_rng()).unwrap();
+
+        let (ct, r) = ek.encrypt_with_precomputed(&pt, ek.precompute_randomness());
+        assert_eq!(ct, ek.encrypt_with_randomness(&pt, &r));
+        assert_eq!(dk.decrypt(&ct), pt);
+
+        // randomness for another key is not used
+        let (other_ek, _) = keygen_unsafe(&mut rand::thread_rng()).unwrap();
+        let (ct, r) = ek.encrypt_with_precomputed(&pt, other_ek.precompute_randomness());
+        assert!(ek.validate_randomness(&r));
+        assert_eq!(dk.decrypt(&ct), pt);
+    }
+
     #[test]
     fn secp256k1_order() {
         // Test that secp256k1 modulus is the order of the generator
@@ -0,0 +1,374 @@
+//! Threshold Paillier decryption as in Section 4 of Damgård-Jurik
+//! https://www.brics.dk/RS/00/45/BRICS-RS-00-45.pdf with `s = 1`.
+//!
+//! A dealer who knows the factorization of `N` picks the decryption exponent `d`
+//! such that `d = 0 mod phi(N)` and `d = 1 mod N`
+//! and splits it into Shamir shares `s_i` modulo `N * phi(N)`.
+//! Share `i` partially decrypts `c` to `c_i = c^(2 * delta * s_i)` where `delta = n!` for `n` shares,
+//! and proves that `c_i` matches its verification key `v_i = v^(delta * s_i)`.
+//! Any `t + 1` valid partial decryptions combine into the plaintext.
+//!
+//! Soundness of the partial decryption proofs requires `N` to be a product of safe primes.
+use alloc::vec::Vec;
+
+use libpaillier::unknown_order::BigNumber;
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{digest::Update, Digest, Sha256};
+use tracing::{error, warn};
+use zeroize::Zeroize;
+
+use crate::{
+    crypto_tools::constants,
+    sdk::api::{TofnFatal, TofnResult},
+};
+
+use super::{
+    utils::member_of_mul_group, Ciphertext, DecryptionKey, EncryptionKey, Plaintext, Randomness,
+};
+
+// Same parameters as the composite dlog proof
+const CHALLENGE_K: usize = 256;
+const SECURITY_PARAM_K_PRIME: usize = 128;
+
+/// Public parameters common to all shares of a threshold decryption key
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PublicParams {
+    ek: EncryptionKey,
+    share_count: usize,
+    v: BigNumber,
+}
+
+/// `v_i = v^(delta * s_i) mod N^2` for share `i`
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VerificationKey(BigNumber);
+
+/// Shamir share `s_i` of the decryption exponent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct DecryptionKeyShare(BigNumber);
+
+/// `c_i = c^(2 * delta * s_i) mod N^2` with a proof of consistency with `v_i`
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PartialDecryption {
+    c_i: BigNumber,
+    e: BigNumber,
+    z: BigNumber,
+}
+
+/// Split the decryption key `dk` into `share_count` shares, any `threshold + 1` of which can decrypt.
+/// The caller must erase `dk` afterwards, and with it the ability to decrypt alone.
+pub fn deal(
+    rng: &mut (impl CryptoRng + RngCore),
+    dk: &DecryptionKey,
+    threshold: usize,
+    share_count: usize,
+) -> TofnResult<(PublicParams, Vec<VerificationKey>, Vec<DecryptionKeyShare>)> {
+    if share_count <= threshold {
+        error!(
+            "invalid (share_count, threshold): ({}, {})",
+            share_count, threshold
+        );
+        return Err(TofnFatal);
+    }
+
+    let ek = dk.encryption_key();
+    let (n, nn) = (ek.0.n(), ek.0.nn());
+    let totient = dk.0.totient();
+    let modulus = n * totient;
+
+    // d = 0 mod phi(N) and d = 1 mod N
+    let totient_inv = totient.invert(n).ok_or_else(|| {
+        error!("phi(N) is not invertible mod N");
+        TofnFatal
+    })?;
+    let d = DecryptionKeyShare(totient * &totient_inv);
+
+    // coefficients of a random polynomial f of degree `threshold` with f(0) = d
+    let coeffs: Vec<DecryptionKeyShare> = core::iter::once(d)
+        .chain(
+            (0..threshold).map(|_| DecryptionKeyShare(BigNumber::random_with_rng(rng, &modulus))),
+        )
+        .collect();
+
+    let shares: Vec<DecryptionKeyShare> = (0..share_count)
+        .map(|index| {
+            let x = share_index_number(index);
+            DecryptionKeyShare(coeffs.iter().rev().fold(BigNumber::zero(), |acc, coeff| {
+                (acc * &x + &coeff.0) % &modulus
+            }))
+        })
+        .collect();
+
+    // v is a random square in Z*_{N^2}
+    let v = loop {
+        let r = BigNumber::random_with_rng(rng, nn);
+        if member_of_mul_group(&r, nn) {
+            break r.modmul(&r, nn);
+        }
+    };
+
+    let delta = factorial(share_count);
+    let verification_keys = shares
+        .iter()
+        .map(|share| VerificationKey(v.modpow(&(&delta * &share.0), nn)))
+        .collect();
+
+    Ok((
+        PublicParams { ek, share_count, v },
+        verification_keys,
+        shares,
+    ))
+}
+
+impl PublicParams {
+    pub fn encryption_key(&self) -> &EncryptionKey {
+        &self.ek
+    }
+
+    pub fn share_count(&self) -> usize {
+        self.share_count
+    }
+
+    /// Partially decrypt `c` with the share at `index`.
+    /// Assume `c` is a valid ciphertext for [PublicParams::encryption_key].
+    pub fn partial_decrypt(
+        &self,
+        rng: &mut (impl CryptoRng + RngCore),
+        index: usize,
+        share: &DecryptionKeyShare,
+        c: &Ciphertext,
+    ) -> PartialDecryption {
+        let nn = self.ek.0.nn();
+        let delta = factorial(self.share_count);
+        let witness = DecryptionKeyShare(&delta * &share.0);
+
+        let c_delta_s_i = c.0.modpow(&witness.0, nn);
+        let c_i = c_delta_s_i.modmul(&c_delta_s_i, nn);
+
+        // prove log_{c^4}(c_i^2) = log_v(v_i)
+        let c_4 = c.0.modpow(&BigNumber::from(4u64), nn);
+        let r = Randomness::generate_with_rng(rng, &(BigNumber::one() << self.mask_size(&delta)));
+        let a = c_4.modpow(&r.0, nn);
+        let b = self.v.modpow(&r.0, nn);
+        let v_i = self.v.modpow(&witness.0, nn);
+
+        let e = compute_challenge(self, index, &c_4, &c_i.modmul(&c_i, nn), &v_i, &a, &b);
+
+        // z = r + e * delta * s_i over the integers
+        let z = &r.0 + &e * &witness.0;
+
+        PartialDecryption { c_i, e, z }
+    }
+
+    /// Verify a partial decryption of `c` by the share at `index` with verification key `vk`
+    pub fn verify_partial_decryption(
+        &self,
+        index: usize,
+        vk: &VerificationKey,
+        c: &Ciphertext,
+        partial: &PartialDecryption,
+    ) -> bool {
+        let nn = self.ek.0.nn();
+        let delta = factorial(self.share_count);
+
+        if !self.ek.validate_ciphertext(c) {
+            warn!("invalid ciphertext");
+            return false;
+        }
+        if !member_of_mul_group(&partial.c_i, nn) || !member_of_mul_group(&vk.0, nn) {
+            warn!("partial decryption or verification key not in Z*_{{N^2}}");
+            return false;
+        }
+        if partial.z < BigNumber::zero() || partial.z.bit_length() > self.mask_size(&delta) {
+            warn!("partial decryption proof response out of range");
+            return false;
+        }
+
+        let c_4 = c.0.modpow(&BigNumber::from(4u64), nn);
+        let c_i_2 = partial.c_i.modmul(&partial.c_i, nn);
+
+        // a = c^(4z) * c_i^(-2e), b = v^z * v_i^(-e)
+        let (c_i_2_e_inv, v_i_e_inv) = match (
+            c_i_2.modpow(&partial.e, nn).invert(nn),
+            vk.0.modpow(&partial.e, nn).invert(nn),
+        ) {
+            (Some(c_i_2_e_inv), Some(v_i_e_inv)) => (c_i_2_e_inv, v_i_e_inv),
+            _ => {
+                warn!("partial decryption proof is not invertible");
+                return false;
+            }
+        };
+        let a = c_4.modpow(&partial.z, nn).modmul(&c_i_2_e_inv, nn);
+        let b = self.v.modpow(&partial.z, nn).modmul(&v_i_e_inv, nn);
+
+        if compute_challenge(self, index, &c_4, &c_i_2, &vk.0, &a, &b) != partial.e {
+            warn!("partial decryption proof failed to verify");
+            return false;
+        }
+
+        true
+    }
+
+    /// Combine valid partial decryptions from at least `threshold + 1` distinct shares.
+    /// Each item of `partials` is a share index and its partial decryption.
+    pub fn combine(&self, partials: &[(usize, &PartialDecryption)]) -> TofnResult<Plaintext> {
+        let (n, nn) = (self.ek.0.n(), self.ek.0.nn());
+        let delta = factorial(self.share_count);
+
+        // c' = prod_i c_i^(2 * lambda_i) = c^(4 * delta^2 * d) = 1 + 4 * delta^2 * m * N mod N^2
+        let mut c_prime = BigNumber::one();
+        for (i, (index, partial)) in partials.iter().enumerate() {
+            let (lambda, negative) = integer_lagrange_coefficient(i, partials, &delta)?;
+            let c_i_lambda = partial.c_i.modpow(&(lambda << 1), nn);
+            let c_i_lambda = if negative {
+                c_i_lambda.invert(nn).ok_or_else(|| {
+                    error!("partial decryption of share {} is not invertible", index);
+                    TofnFatal
+                })?
+            } else {
+                c_i_lambda
+            };
+            c_prime = c_prime.modmul(&c_i_lambda, nn);
+        }
+
+        if !(&c_prime % n).is_one() {
+            error!("partial decryptions do not combine to a decryption");
+            return Err(TofnFatal);
+        }
+
+        let four_delta_squared_inv = (BigNumber::from(4u64) * &delta * &delta)
+            .invert(n)
+            .ok_or_else(|| {
+                error!("4 * delta^2 is not invertible mod N");
+                TofnFatal
+            })?;
+
+        // L(c') = (c' - 1) / N
+        let l = (c_prime - BigNumber::one()) / n;
+
+        Ok(Plaintext(l.modmul(&four_delta_squared_inv, n)))
+    }
+
+    /// The bit length of a mask `r` required to hide `delta * s_i`
+    fn mask_size(&self, delta: &BigNumber) -> usize {
+        CHALLENGE_K + SECURITY_PARAM_K_PRIME + delta.bit_length() + self.ek.0.nn().bit_length()
+    }
+}
+
+/// Share index `i` is the evaluation point `i + 1`
+fn share_index_number(index: usize) -> BigNumber {
+    BigNumber::from(index as u64 + 1)
+}
+
+fn factorial(n: usize) -> BigNumber {
+    (1..=n as u64).fold(BigNumber::one(), |acc, i| acc * BigNumber::from(i))
+}
+
+/// `delta` times the Lagrange coefficient at 0 of the `i`th of `partials`,
+/// which is an integer. Return its absolute value and whether it is negative.
+fn integer_lagrange_coefficient(
+    i: usize,
+    partials: &[(usize, &PartialDecryption)],
+    delta: &BigNumber,
+) -> TofnResult<(BigNumber, bool)> {
+    let index_i = partials[i].0;
+    let mut numerator = delta.clone();
+    let mut denominator = BigNumber::one();
+    let mut negative = false;
+
+    for (j, (index_j, _)) in partials.iter().enumerate() {
+        if j == i {
+            continue;
+        }
+        if *index_j == index_i {
+            error!("duplicate share index {} in partial decryptions", index_i);
+            return Err(TofnFatal);
+        }
+        // x_j / (x_j - x_i)
+        numerator = numerator * share_index_number(*index_j);
+        if *index_j < index_i {
+            negative = !negative;
+            denominator = denominator * BigNumber::from((index_i - index_j) as u64);
+        } else {
+            denominator = denominator * BigNumber::from((index_j - index_i) as u64);
+        }
+    }
+
+    Ok((numerator / denominator, negative))
+}
+
+fn compute_challenge(
+    params: &PublicParams,
+    index: usize,
+    c_4: &BigNumber,
+    c_i_2: &BigNumber,
+    v_i: &BigNumber,
+    a: &BigNumber,
+    b: &BigNumber,
+) -> BigNumber {
+    BigNumber::from_slice(
+        Sha256::new()
+            .chain(constants::PAILLIER_PARTIAL_DECRYPTION_PROOF_TAG.to_be_bytes())
+            .chain((index as u64).to_be_bytes())
+            .chain(params.ek.0.n().to_bytes())
+            .chain(params.v.to_bytes())
+            .chain(c_4.to_bytes())
+            .chain(c_i_2.to_bytes())
+            .chain(v_i.to_bytes())
+            .chain(a.to_bytes())
+            .chain(b.to_bytes())
+            .finalize(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::keygen_unsafe, *};
+
+    #[test]
+    fn deal_decrypt_combine() {
+        let mut rng = rand::thread_rng();
+        let (ek, dk) = keygen_unsafe(&mut rng).unwrap();
+        let (threshold, share_count) = (2, 5);
+        let (params, vks, shares) = deal(&mut rng, &dk, threshold, share_count).unwrap();
+        assert_eq!(params.encryption_key(), &ek);
+
+        let pt = ek.random_plaintext();
+        let (c, _) = ek.encrypt(&pt);
+
+        let partials: Vec<_> = shares
+            .iter()
+            .enumerate()
+            .map(|(index, share)| params.partial_decrypt(&mut rng, index, share, &c))
+            .collect();
+        for (index, partial) in partials.iter().enumerate() {
+            assert!(params.verify_partial_decryption(index, &vks[index], &c, partial));
+        }
+
+        for subset in [[0, 2, 4], [3, 1, 2]] {
+            let subset: Vec<_> = subset.iter().map(|&i| (i, &partials[i])).collect();
+            assert_eq!(params.combine(&subset).unwrap(), pt);
+        }
+
+        // more than threshold + 1 shares also combine
+        let all: Vec<_> = partials.iter().enumerate().collect();
+        assert_eq!(params.combine(&all).unwrap(), pt);
+
+        // wrong index, wrong verification key, wrong ciphertext
+        assert!(!params.verify_partial_decryption(1, &vks[0], &c, &partials[0]));
+        assert!(!params.verify_partial_decryption(0, &vks[1], &c, &partials[0]));
+        let (other_c, _) = ek.encrypt(&pt);
+        assert!(!params.verify_partial_decryption(0, &vks[0], &other_c, &partials[0]));
+
+        // partial decryption with the wrong share
+        let bad_partial = params.partial_decrypt(&mut rng, 0, &shares[1], &c);
+        assert!(!params.verify_partial_decryption(0, &vks[0], &c, &bad_partial));
+        let subset = [(0, &bad_partial), (1, &partials[1]), (2, &partials[2])];
+        assert!(params.combine(&subset).is_err());
+
+        // duplicate share index
+        let subset = [(0, &partials[0]), (0, &partials[0]), (2, &partials[2])];
+        assert!(params.combine(&subset).is_err());
+    }
+}
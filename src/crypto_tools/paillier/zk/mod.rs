@@ -2,15 +2,26 @@
 
 use alloc::vec::Vec;
 
-use crate::{crypto_tools::constants, sdk::api::TofnResult};
-
-use super::{keygen, keygen_unsafe, DecryptionKey, EncryptionKey, Plaintext, Randomness};
+use crate::{
+    crypto_tools::constants,
+    sdk::api::{TofnFatal, TofnResult},
+};
+
+use super::{
+    keygen, keygen_unsafe, utils::member_of_mul_group, DecryptionKey, EncryptionKey, Plaintext,
+    Randomness,
+};
 use libpaillier::unknown_order::BigNumber;
 use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
+use tracing::error;
 use zeroize::Zeroize;
 
 pub(crate) mod mta;
+// public for benches only
+#[cfg(feature = "test-utils")]
+pub mod range;
+#[cfg(not(feature = "test-utils"))]
 pub(crate) mod range;
 
 mod paillier_key;
@@ -58,6 +69,29 @@ impl ZkSetup {
         Ok(Self::from_keypair(rng, keypair, domain))
     }
 
+    /// Construct a ZK setup from the big-endian bytes of the ring-Pedersen parameters `N~`, `h1`, `h2`
+    /// generated by another GG20 implementation.
+    /// There is no proof that the dlogs of `h1`, `h2` w.r.t each other exist: the caller must trust these parameters.
+    pub fn from_parts(n_tilde: &[u8], h1: &[u8], h2: &[u8]) -> TofnResult<Self> {
+        let dlog_stmt = CompositeDLogStmtBase {
+            n: BigNumber::from_slice(n_tilde),
+            g: BigNumber::from_slice(h1),
+            v: BigNumber::from_slice(h2),
+        };
+        let n = &dlog_stmt.n;
+        if n.bit_length() < constants::MODULUS_MIN_SIZE
+            || n.bit_length() > constants::MODULUS_MAX_SIZE
+            || n.is_prime()
+            || !member_of_mul_group(&dlog_stmt.g, n)
+            || !member_of_mul_group(&dlog_stmt.v, n)
+            || dlog_stmt.g == dlog_stmt.v
+        {
+            error!("invalid zk setup parameters");
+            return Err(TofnFatal);
+        }
+        Ok(Self { dlog_stmt })
+    }
+
     /// Add a layer of domain separation on the two composite dlog proofs
     fn compute_domain(domain: &[u8]) -> (Vec<u8>, Vec<u8>) {
         let mut domain1: Vec<u8> = domain.into();
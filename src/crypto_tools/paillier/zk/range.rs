@@ -11,7 +11,6 @@ use crate::{
             Ciphertext, EncryptionKey, Plaintext, Randomness,
         },
     },
-    gg20::sign::SignShareId,
     sdk::api::{TofnFatal, TofnResult},
 };
 use ecdsa::elliptic_curve::ops::Reduce;
@@ -26,9 +25,9 @@ use tracing::{error, warn};
 use super::secp256k1_modulus_cubed;
 
 #[derive(Clone, Debug)]
-pub struct Statement<'a> {
-    pub prover_id: TypedUsize<SignShareId>,
-    pub verifier_id: TypedUsize<SignShareId>,
+pub struct Statement<'a, K> {
+    pub prover_id: TypedUsize<K>,
+    pub verifier_id: TypedUsize<K>,
     pub ciphertext: &'a Ciphertext,
     pub ek: &'a EncryptionKey,
 }
@@ -49,8 +48,8 @@ pub struct Proof {
 }
 
 #[derive(Clone, Debug)]
-pub struct StatementWc<'a> {
-    pub stmt: Statement<'a>,
+pub struct StatementWc<'a, K> {
+    pub stmt: Statement<'a, K>,
     pub msg_g: &'a k256::ProjectivePoint,
     pub g: &'a k256::ProjectivePoint,
 }
@@ -65,12 +64,12 @@ impl ZkSetup {
     // statement (ciphertext, ek), witness (msg, randomness)
     //   such that ciphertext = Enc(ek, msg, randomness) and -q^3 < msg < q^3
     // full specification: appendix A.1 of https://eprint.iacr.org/2019/114.pdf
-    pub fn range_proof(&self, stmt: &Statement, wit: &Witness) -> Proof {
+    pub fn range_proof<K>(&self, stmt: &Statement<K>, wit: &Witness) -> Proof {
         self.range_proof_inner(constants::RANGE_PROOF_TAG, stmt, None, wit)
             .0
     }
 
-    pub fn verify_range_proof(&self, stmt: &Statement, proof: &Proof) -> bool {
+    pub fn verify_range_proof<K>(&self, stmt: &Statement<K>, proof: &Proof) -> bool {
         self.verify_range_proof_inner(constants::RANGE_PROOF_TAG, stmt, proof, None)
     }
 
@@ -79,7 +78,7 @@ impl ZkSetup {
     //   and msg_g = msg * g (this is the additional "check")
     // adapted from appendix A.1 of https://eprint.iacr.org/2019/114.pdf
     // full specification: section 4.4, proof \Pi_i of https://eprint.iacr.org/2016/013.pdf
-    pub fn range_proof_wc(&self, stmt: &StatementWc, wit: &Witness) -> TofnResult<ProofWc> {
+    pub fn range_proof_wc<K>(&self, stmt: &StatementWc<K>, wit: &Witness) -> TofnResult<ProofWc> {
         let (proof, u1) = self.range_proof_inner(
             constants::RANGE_PROOF_WC_TAG,
             &stmt.stmt,
@@ -97,7 +96,7 @@ impl ZkSetup {
         Ok(ProofWc { proof, u1 })
     }
 
-    pub fn verify_range_proof_wc(&self, stmt: &StatementWc, proof: &ProofWc) -> bool {
+    pub fn verify_range_proof_wc<K>(&self, stmt: &StatementWc<K>, proof: &ProofWc) -> bool {
         self.verify_range_proof_inner(
             constants::RANGE_PROOF_WC_TAG,
             &stmt.stmt,
@@ -107,9 +106,9 @@ impl ZkSetup {
     }
 
     /// Compute the challenge e in Z_q for the range proof
-    fn compute_range_proof_challenge(
+    fn compute_range_proof_challenge<K>(
         tag: u8,
-        stmt: &Statement,
+        stmt: &Statement<K>,
         msg_g_g: Option<(&k256::ProjectivePoint, &k256::ProjectivePoint)>, // (msg_g, g)
         z: &BigNumber,
         u: &Ciphertext,
@@ -136,10 +135,10 @@ impl ZkSetup {
     }
 
     #[allow(clippy::many_single_char_names)]
-    fn range_proof_inner(
+    fn range_proof_inner<K>(
         &self,
         tag: u8,
-        stmt: &Statement,
+        stmt: &Statement<K>,
         msg_g_g: Option<(&k256::ProjectivePoint, &k256::ProjectivePoint)>, // (msg_g, g)
         wit: &Witness,
     ) -> (Proof, Option<k256::ProjectivePoint>) {
@@ -205,10 +204,10 @@ impl ZkSetup {
         (Proof { z, u, w, s, s1, s2 }, u1)
     }
 
-    fn verify_range_proof_inner(
+    fn verify_range_proof_inner<K>(
         &self,
         tag: u8,
-        stmt: &Statement,
+        stmt: &Statement<K>,
         proof: &Proof,
         msg_g_g_u1: Option<(
             &k256::ProjectivePoint,
@@ -352,7 +351,9 @@ mod tests {
     use alloc::format;
     use alloc::string::{String, ToString};
 
-    use crate::{collections::TypedUsize, crypto_tools::paillier::keygen_unsafe};
+    use crate::{
+        collections::TypedUsize, crypto_tools::paillier::keygen_unsafe, gg20::sign::SignShareId,
+    };
 
     use super::{
         ZkSetup,
@@ -373,7 +374,7 @@ mod tests {
         let g = &k256::ProjectivePoint::GENERATOR;
         let msg_g = &(g * msg);
         let (ciphertext, randomness) = &ek.encrypt(&msg.into());
-        let prover_id = TypedUsize::from_usize(10);
+        let prover_id = TypedUsize::<SignShareId>::from_usize(10);
         let verifier_id = TypedUsize::from_usize(4);
         let bad_id = TypedUsize::from_usize(100);
 
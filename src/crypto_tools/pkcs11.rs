@@ -0,0 +1,102 @@
+//! A [SecretRecoveryKeyProvider] backed by a PKCS#11 token, eg. a YubiHSM 2 through `yubihsm_pkcs11.so`.
+//!
+//! The secret recovery key is a generic secret key object on the token that permits signing with
+//! `CKM_SHA256_HMAC`. Importing the bytes of an existing [SecretRecoveryKey](super::rng::SecretRecoveryKey)
+//! as that object re-derives the same Paillier keys as before.
+use std::path::Path;
+
+use core::convert::TryInto;
+
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    mechanism::Mechanism,
+    object::{Attribute, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use tracing::error;
+
+use super::rng::SecretRecoveryKeyProvider;
+use crate::sdk::api::{TofnFatal, TofnResult};
+
+pub struct Pkcs11SecretRecoveryKey {
+    session: Session,
+    key: ObjectHandle,
+}
+
+impl Pkcs11SecretRecoveryKey {
+    /// Log in to the token labelled `token_label` of the PKCS#11 module at `module_path`
+    /// and find the secret key labelled `key_label`.
+    pub fn open(
+        module_path: impl AsRef<Path>,
+        token_label: &str,
+        pin: &str,
+        key_label: &str,
+    ) -> TofnResult<Self> {
+        let pkcs11 = Pkcs11::new(module_path).map_err(log_error("loading PKCS#11 module"))?;
+        pkcs11
+            .initialize(CInitializeArgs::OsThreads)
+            .map_err(log_error("initializing PKCS#11 module"))?;
+
+        let slot = pkcs11
+            .get_slots_with_token()
+            .map_err(log_error("listing PKCS#11 slots"))?
+            .into_iter()
+            .find(|&slot| {
+                pkcs11
+                    .get_token_info(slot)
+                    .map_or(false, |info| info.label() == token_label)
+            })
+            .ok_or_else(|| {
+                error!("no PKCS#11 token labelled `{}`", token_label);
+                TofnFatal
+            })?;
+
+        let session = pkcs11
+            .open_ro_session(slot)
+            .map_err(log_error("opening PKCS#11 session"))?;
+        session
+            .login(UserType::User, Some(&AuthPin::new(pin.into())))
+            .map_err(log_error("logging in to PKCS#11 token"))?;
+
+        let keys = session
+            .find_objects(&[
+                Attribute::Class(ObjectClass::SECRET_KEY),
+                Attribute::Label(key_label.as_bytes().to_vec()),
+            ])
+            .map_err(log_error("finding secret recovery key"))?;
+        let key = match keys.as_slice() {
+            [key] => *key,
+            _ => {
+                error!(
+                    "expected one secret key labelled `{}`, found {}",
+                    key_label,
+                    keys.len()
+                );
+                return Err(TofnFatal);
+            }
+        };
+
+        Ok(Self { session, key })
+    }
+}
+
+impl SecretRecoveryKeyProvider for Pkcs11SecretRecoveryKey {
+    fn hmac_sha256(&self, message: &[u8]) -> TofnResult<[u8; 32]> {
+        let mac = self
+            .session
+            .sign(&Mechanism::Sha256Hmac, self.key, message)
+            .map_err(log_error("computing HMAC-SHA256 on PKCS#11 token"))?;
+        mac.as_slice().try_into().map_err(|_| {
+            error!("unexpected HMAC-SHA256 length {}", mac.len());
+            TofnFatal
+        })
+    }
+}
+
+fn log_error(context: &'static str) -> impl Fn(cryptoki::error::Error) -> TofnFatal {
+    move |err| {
+        error!("{}: {}", context, err);
+        TofnFatal
+    }
+}
@@ -3,7 +3,7 @@ use core::{
     convert::{TryFrom, TryInto},
 };
 
-use ecdsa::elliptic_curve::generic_array::GenericArray;
+use ecdsa::elliptic_curve::ff::PrimeField;
 use hmac::{Hmac, Mac};
 use rand::{CryptoRng, RngCore, SeedableRng};
 use rand_chacha::ChaCha20Rng;
@@ -28,15 +28,39 @@ impl TryFrom<&[u8]> for SecretRecoveryKey {
     }
 }
 
+/// Source of secret recovery key material.
+///
+/// Everything derived from a secret recovery key is seeded by an HMAC-SHA256 under it,
+/// so a provider only needs to compute such MACs.
+/// A hardware-backed provider can do so without the key ever entering process memory,
+/// eg. `crypto_tools::pkcs11::Pkcs11SecretRecoveryKey` with the `pkcs11` feature.
+pub trait SecretRecoveryKeyProvider {
+    /// HMAC-SHA256 of `message` under the secret recovery key
+    fn hmac_sha256(&self, message: &[u8]) -> TofnResult<[u8; 32]>;
+}
+
+impl SecretRecoveryKeyProvider for SecretRecoveryKey {
+    fn hmac_sha256(&self, message: &[u8]) -> TofnResult<[u8; 32]> {
+        hmac_sha256(&self.0, &[message])
+    }
+}
+
+/// Seed a RNG with the MAC of `message` under the secret recovery key of `provider`.
+fn rng_seed_from_provider<P: SecretRecoveryKeyProvider + ?Sized>(
+    provider: &P,
+    message: &[u8],
+) -> TofnResult<impl CryptoRng + RngCore> {
+    let mut seed = provider.hmac_sha256(message)?;
+    let rng = ChaCha20Rng::from_seed(seed);
+    seed.zeroize();
+    Ok(rng)
+}
+
 const SESSION_NONCE_LENGTH_MIN: usize = 4;
 const SESSION_NONCE_LENGTH_MAX: usize = 256;
 
-pub(crate) fn rng_seed<K>(
-    tag: u8,
-    party_id: TypedUsize<K>,
-    secret_recovery_key: &SecretRecoveryKey,
-    session_nonce: &[u8],
-) -> TofnResult<impl CryptoRng + RngCore> {
+/// Check that `session_nonce` is acceptable to [rng_seed].
+pub(crate) fn check_session_nonce(session_nonce: &[u8]) -> TofnResult<()> {
     if session_nonce.len() < SESSION_NONCE_LENGTH_MIN
         || session_nonce.len() > SESSION_NONCE_LENGTH_MAX
     {
@@ -48,53 +72,39 @@ pub(crate) fn rng_seed<K>(
         );
         return Err(TofnFatal);
     }
+    Ok(())
+}
 
-    // TODO: Use protocol domain separation: https://github.com/axelarnetwork/tofn/issues/184
-    let seed = Hmac::<Sha256>::new(secret_recovery_key.0[..].into())
-        .chain(tag.to_be_bytes())
-        .chain(party_id.to_bytes())
-        .chain(session_nonce)
-        .finalize()
-        .into_bytes()
-        .into();
+pub(crate) fn rng_seed<K, P: SecretRecoveryKeyProvider + ?Sized>(
+    tag: u8,
+    party_id: TypedUsize<K>,
+    secret_recovery_key: &P,
+    session_nonce: &[u8],
+) -> TofnResult<impl CryptoRng + RngCore> {
+    check_session_nonce(session_nonce)?;
 
-    Ok(ChaCha20Rng::from_seed(seed))
+    // TODO: Use protocol domain separation: https://github.com/axelarnetwork/tofn/issues/184
+    let message = [&tag.to_be_bytes()[..], &party_id.to_bytes(), session_nonce].concat();
+    rng_seed_from_provider(secret_recovery_key, &message)
 }
 
 /// Initialize a RNG by hashing the arguments.
 /// Intended for use generating a ECDSA signing key.
-pub(crate) fn rng_seed_ecdsa_signing_key(
+pub(crate) fn rng_seed_ecdsa_signing_key<P: SecretRecoveryKeyProvider + ?Sized>(
     protocol_tag: u8,
     tag: u8,
-    secret_recovery_key: &SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<impl CryptoRng + RngCore> {
-    if session_nonce.len() < SESSION_NONCE_LENGTH_MIN
-        || session_nonce.len() > SESSION_NONCE_LENGTH_MAX
-    {
-        error!(
-            "invalid session_nonce length {} not in [{},{}]",
-            session_nonce.len(),
-            SESSION_NONCE_LENGTH_MIN,
-            SESSION_NONCE_LENGTH_MAX
-        );
-        return Err(TofnFatal);
-    }
+    check_session_nonce(session_nonce)?;
 
-    // Take care not to copy [secret_recovery_key]
-    // This explicit declaration ensures that we use the following reference-to-reference conversion:
-    // https://docs.rs/generic-array/0.14.4/src/generic_array/lib.rs.html#553-563
-    let hmac_key: &GenericArray<_, _> = (secret_recovery_key.0[..]).into();
-
-    let seed = Hmac::<Sha256>::new(hmac_key)
-        .chain(protocol_tag.to_be_bytes())
-        .chain(tag.to_be_bytes())
-        .chain(session_nonce)
-        .finalize()
-        .into_bytes()
-        .into();
-
-    Ok(ChaCha20Rng::from_seed(seed))
+    let message = [
+        &protocol_tag.to_be_bytes()[..],
+        &tag.to_be_bytes(),
+        session_nonce,
+    ]
+    .concat();
+    rng_seed_from_provider(secret_recovery_key, &message)
 }
 
 /// Initialize a RNG by hashing the arguments.
@@ -152,6 +162,97 @@ pub(crate) fn rng_seed_ecdsa_ephemeral_scalar(
     Ok(ChaCha20Rng::from_seed(seed))
 }
 
+/// The ephemeral scalar of an ECDSA signature of `msg_to_sign` under `signing_key` as in RFC 6979,
+/// with HMAC-SHA256, so that signatures match those of standard ECDSA libraries.
+/// See <https://www.rfc-editor.org/rfc/rfc6979#section-3.2>
+pub(crate) fn rfc6979_ephemeral_scalar(
+    signing_key: &k256::Scalar,
+    msg_to_sign: &k256::Scalar,
+) -> TofnResult<k256::Scalar> {
+    let mut x = signing_key.to_bytes();
+    let h = msg_to_sign.to_bytes();
+
+    let mut v = [0x01; 32];
+    let mut k = [0x00; 32];
+    let result = (|| {
+        k = hmac_sha256(&k, &[&v, &[0x00], &x, &h])?;
+        v = hmac_sha256(&k, &[&v])?;
+        k = hmac_sha256(&k, &[&v, &[0x01], &x, &h])?;
+        v = hmac_sha256(&k, &[&v])?;
+        loop {
+            v = hmac_sha256(&k, &[&v])?;
+            let candidate: Option<k256::Scalar> = k256::Scalar::from_repr(v.into()).into();
+            if let Some(candidate) = candidate {
+                if !bool::from(candidate.is_zero()) {
+                    return Ok(candidate);
+                }
+            }
+            k = hmac_sha256(&k, &[&v, &[0x00]])?;
+            v = hmac_sha256(&k, &[&v])?;
+        }
+    })();
+
+    x.zeroize();
+    k.zeroize();
+    v.zeroize();
+    result
+}
+
+fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> TofnResult<[u8; 32]> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|_| {
+        error!("invalid hmac key");
+        TofnFatal
+    })?;
+    for part in parts {
+        Mac::update(&mut mac, part);
+    }
+    Ok(mac.finalize().into_bytes().into())
+}
+
+/// Initialize a RNG by hashing the arguments.
+/// Intended for use generating the nonce shares of a threshold signature without runtime randomness.
+/// Unlike a single-party signature, the nonce also depends on the other signers,
+/// so the same nonce share must never be used twice: `session_id` must be unique to each sign.
+/// `co_signers` identifies the other signers; it is hashed too so that a
+/// `session_id` reused with different co-signers still yields different nonce shares.
+pub(crate) fn rng_seed_sign_nonce<K>(
+    tag: u8,
+    party_id: TypedUsize<K>,
+    signing_key: &k256::Scalar,
+    msg_to_sign: &k256::Scalar,
+    co_signers: &[u8],
+    session_id: &[u8],
+) -> TofnResult<impl CryptoRng + RngCore> {
+    if session_id.len() < SESSION_NONCE_LENGTH_MIN || session_id.len() > SESSION_NONCE_LENGTH_MAX {
+        error!(
+            "invalid session_id length {} not in [{},{}]",
+            session_id.len(),
+            SESSION_NONCE_LENGTH_MIN,
+            SESSION_NONCE_LENGTH_MAX
+        );
+        return Err(TofnFatal);
+    }
+
+    let mut signing_key_bytes = signing_key.to_bytes();
+    let msg_to_sign_bytes = msg_to_sign.to_bytes();
+
+    let seed = Hmac::<Sha256>::new(&Default::default())
+        .chain(tag.to_be_bytes())
+        .chain(party_id.to_bytes())
+        .chain(signing_key_bytes)
+        .chain(msg_to_sign_bytes)
+        .chain((co_signers.len() as u64).to_be_bytes())
+        .chain(co_signers)
+        .chain(session_id)
+        .finalize()
+        .into_bytes()
+        .into();
+
+    signing_key_bytes.zeroize();
+
+    Ok(ChaCha20Rng::from_seed(seed))
+}
+
 #[cfg(test)]
 /// return the all-zero array with the first bytes set to the bytes of `index`
 pub fn dummy_secret_recovery_key(index: usize) -> SecretRecoveryKey {
@@ -162,3 +263,90 @@ pub fn dummy_secret_recovery_key(index: usize) -> SecretRecoveryKey {
     }
     SecretRecoveryKey(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use core::convert::{TryFrom, TryInto};
+
+    use rand::RngCore;
+
+    use super::{rfc6979_ephemeral_scalar, rng_seed, rng_seed_sign_nonce, SecretRecoveryKey};
+    use crate::collections::TypedUsize;
+    use crate::crypto_tools::message_digest::MessageDigest;
+
+    /// Seeds derived through [SecretRecoveryKeyProvider](super::SecretRecoveryKeyProvider)
+    /// must match those of earlier versions, which keyed the HMAC directly.
+    #[test]
+    fn rng_seed_unchanged() {
+        use hmac::{Hmac, Mac};
+        use rand::SeedableRng;
+        use sha2::{digest::Update, Sha256};
+
+        let secret_recovery_key = SecretRecoveryKey([3; 64]);
+        let party_id = TypedUsize::<()>::from_usize(5);
+        let session_nonce = b"tofn-rng";
+
+        let seed: [u8; 32] = Hmac::<Sha256>::new_from_slice(&secret_recovery_key.0)
+            .unwrap()
+            .chain([7u8])
+            .chain(party_id.to_bytes())
+            .chain(session_nonce)
+            .finalize()
+            .into_bytes()
+            .into();
+        let mut expected = [0; 32];
+        rand_chacha::ChaCha20Rng::from_seed(seed).fill_bytes(&mut expected);
+
+        let mut bytes = [0; 32];
+        rng_seed(7, party_id, &secret_recovery_key, session_nonce)
+            .unwrap()
+            .fill_bytes(&mut bytes);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn sign_nonce_co_signers() {
+        let nonce = |co_signers: &[u8], session_id: &[u8]| {
+            let mut bytes = [0; 32];
+            rng_seed_sign_nonce(
+                0,
+                TypedUsize::<()>::from_usize(1),
+                &k256::Scalar::ONE,
+                &k256::Scalar::ONE,
+                co_signers,
+                session_id,
+            )
+            .unwrap()
+            .fill_bytes(&mut bytes);
+            bytes
+        };
+        assert_eq!(nonce(b"ab", b"session"), nonce(b"ab", b"session"));
+        assert_ne!(nonce(b"ab", b"session"), nonce(b"ac", b"session"));
+
+        // bytes can't move between the co-signers and the session id
+        assert_ne!(nonce(b"ab", b"csession"), nonce(b"abc", b"session"));
+    }
+
+    /// Test vector for secp256k1 and SHA-256 also used by python-ecdsa and trezor:
+    /// signing key 1, message "Satoshi Nakamoto"
+    #[test]
+    fn rfc6979_test_vector() {
+        use sha2::Digest;
+
+        let signing_key = k256::Scalar::from(1u32);
+        let digest = sha2::Sha256::digest(b"Satoshi Nakamoto");
+        let msg_to_sign = k256::Scalar::from(&MessageDigest::try_from(&digest[..]).unwrap());
+        let expected: [u8; 32] =
+            hex::decode("8F8A276C19F4149656B280621E358CCE24F5F52542772691EE69063B74F15D15")
+                .unwrap()
+                .try_into()
+                .unwrap();
+
+        assert_eq!(
+            rfc6979_ephemeral_scalar(&signing_key, &msg_to_sign)
+                .unwrap()
+                .to_bytes()[..],
+            expected[..]
+        );
+    }
+}
@@ -21,13 +21,12 @@ impl Ss {
             .into_iter()
             .chain(
                 core::iter::repeat_with(|| k256::Scalar::random(rand::thread_rng()))
-                    .take(threshold - 1),
+                    .take(threshold.saturating_sub(1)),
             )
             .collect();
         Self { secret_coeffs }
     }
 
-    #[allow(dead_code)]
     pub fn new(threshold: usize) -> Self {
         let secret_coeffs: Vec<k256::Scalar> = (0..=threshold)
             .map(|_| k256::Scalar::random(rand::thread_rng()))
@@ -39,7 +38,6 @@ impl Ss {
         self.secret_coeffs.len() - 1
     }
 
-    #[allow(dead_code)]
     pub fn get_secret(&self) -> &k256::Scalar {
         &self.secret_coeffs[0]
     }
@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 use tracing::error;
 use zeroize::Zeroize;
 
-#[derive(Debug, Zeroize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Zeroize)]
 #[zeroize(drop)]
 pub struct Vss {
     secret_coeffs: Vec<k256::Scalar>,
@@ -27,6 +27,17 @@ impl Vss {
         Self { secret_coeffs }
     }
 
+    /// Like [Vss::new] but with the given `secret` as the constant coefficient.
+    pub fn new_with_secret(threshold: usize, secret: k256::Scalar) -> Self {
+        let secret_coeffs: Vec<k256::Scalar> = core::iter::once(secret)
+            .chain(
+                core::iter::repeat_with(|| k256::Scalar::random(rand::thread_rng()))
+                    .take(threshold),
+            )
+            .collect();
+        Self { secret_coeffs }
+    }
+
     pub fn get_threshold(&self) -> usize {
         self.secret_coeffs.len() - 1
     }
@@ -159,7 +170,36 @@ pub fn recover_secret_commit(
     )
 }
 
+/// Return `true` if every `threshold + 1` of `share_commits` interpolate to `secret_commit`.
+/// Assume `threshold < share_commits.len()`.
+pub fn share_commits_match(
+    share_commits: &[ShareCommit],
+    threshold: usize,
+    secret_commit: &k256::ProjectivePoint,
+) -> bool {
+    // it suffices to check the first `threshold` shares together with each other share
+    (threshold..share_commits.len()).all(|last| {
+        let mut subset = share_commits[..threshold].to_vec();
+        subset.push(share_commits[last].clone());
+        recover_secret_commit(&subset, threshold).map_or(false, |y| y == *secret_commit)
+    })
+}
+
 pub fn lagrange_coefficient(i: usize, indices: &[usize]) -> TofnResult<k256::Scalar> {
+    lagrange_coefficient_at_scalar(i, indices, &k256::Scalar::zero())
+}
+
+/// Like [lagrange_coefficient] but evaluate at share index `at` instead of at the secret,
+/// eg. to compute a share for a new index from the shares at `indices`.
+pub fn lagrange_coefficient_at(i: usize, indices: &[usize], at: usize) -> TofnResult<k256::Scalar> {
+    lagrange_coefficient_at_scalar(i, indices, &k256::Scalar::from(at as u32 + 1))
+}
+
+fn lagrange_coefficient_at_scalar(
+    i: usize,
+    indices: &[usize],
+    at: &k256::Scalar,
+) -> TofnResult<k256::Scalar> {
     let scalars: Vec<k256::Scalar> = indices
         .iter()
         .map(|&index| k256::Scalar::from(index as u32 + 1))
@@ -171,7 +211,7 @@ pub fn lagrange_coefficient(i: usize, indices: &[usize]) -> TofnResult<k256::Sca
             if j == i {
                 (num, den)
             } else {
-                (num * scalar_j, den * (scalar_j - &scalars[i]))
+                (num * (scalar_j - at), den * (scalar_j - &scalars[i]))
             }
         },
     );
@@ -310,4 +350,30 @@ mod tests {
             .fold(k256::Scalar::zero(), |acc, share| acc + share.get_scalar());
         assert_eq!(recovered_secret, *vss.get_secret());
     }
+
+    #[test]
+    fn interpolate_new_share() {
+        let (t, n) = (2, 5);
+        let vss = Vss::new(t);
+        let shares = vss.shares(n + 1);
+        let new_share = &shares[n];
+
+        // any t+1 of the first n shares interpolate to the share at index n
+        let subset: Vec<&Share> = shares[1..n].iter().take(t + 1).collect();
+        let indices: Vec<usize> = subset.iter().map(|share| share.index).collect();
+        let interpolated =
+            subset
+                .iter()
+                .enumerate()
+                .fold(k256::Scalar::zero(), |acc, (i, share)| {
+                    acc + share.get_scalar() * &lagrange_coefficient_at(i, &indices, n).unwrap()
+                });
+        assert_eq!(interpolated, *new_share.get_scalar());
+    }
+
+    #[test]
+    fn share_zeroize_on_drop() {
+        let share = Vss::new(2).shares(3).remove(0);
+        assert_zeroized_on_drop!(share, scalar);
+    }
 }
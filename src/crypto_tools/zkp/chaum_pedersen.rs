@@ -4,7 +4,6 @@ use crate::{
         constants,
         k256_serde::{self, SecretScalar},
     },
-    gg20::sign::SignShareId,
 };
 
 use ecdsa::elliptic_curve::ops::Reduce;
@@ -14,8 +13,8 @@ use sha2::{digest::Update, Digest, Sha256};
 use tracing::warn;
 
 #[derive(Clone, Debug)]
-pub struct Statement<'a> {
-    pub prover_id: TypedUsize<SignShareId>,
+pub struct Statement<'a, K> {
+    pub prover_id: TypedUsize<K>,
     pub base1: &'a k256::ProjectivePoint,
     pub base2: &'a k256::ProjectivePoint,
     pub target1: &'a k256::ProjectivePoint,
@@ -34,8 +33,8 @@ pub struct Proof {
     t: k256::Scalar,
 }
 
-fn compute_challenge(
-    stmt: &Statement,
+fn compute_challenge<K>(
+    stmt: &Statement<K>,
     alpha1: &k256_serde::ProjectivePoint,
     alpha2: &k256_serde::ProjectivePoint,
 ) -> k256::Scalar {
@@ -57,7 +56,7 @@ fn compute_challenge(
 //   such that target1 == scalar * base1 and target2 == scalar * base2
 // notation based on section 4.3 of GG20 https://eprint.iacr.org/2020/540.pdf
 // except: (g, R, Sigma, S, alpha, beta) ->  (base1, base2, target1, target2, alpha1, alpha2)
-pub fn prove(stmt: &Statement, wit: &Witness) -> Proof {
+pub fn prove<K>(stmt: &Statement<K>, wit: &Witness) -> Proof {
     let a = SecretScalar::random_with_thread_rng();
 
     // alpha = g^a
@@ -74,7 +73,7 @@ pub fn prove(stmt: &Statement, wit: &Witness) -> Proof {
     Proof { alpha1, alpha2, t }
 }
 
-pub fn verify(stmt: &Statement, proof: &Proof) -> bool {
+pub fn verify<K>(stmt: &Statement<K>, proof: &Proof) -> bool {
     // Ensure that t is in Z_q and base1, base2, target1, target2, alpha1, alpha2 are in G
     // This is handled by k256_serde on deserialize
 
@@ -117,6 +116,7 @@ pub(crate) mod malicious {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gg20::sign::SignShareId;
     use ecdsa::elliptic_curve::{Field, Group};
 
     #[test]
@@ -126,7 +126,7 @@ mod tests {
         let scalar = &k256::Scalar::random(rand::thread_rng());
         let target1 = &(base1 * scalar);
         let target2 = &(base2 * scalar);
-        let prover_id = TypedUsize::from_usize(1);
+        let prover_id = TypedUsize::<SignShareId>::from_usize(1);
         let stmt = Statement {
             prover_id,
             base1,
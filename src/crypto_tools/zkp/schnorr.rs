@@ -31,11 +31,21 @@ pub struct Proof {
     t: k256::Scalar,
 }
 
-/// Compute the challenge for Schnorr zk proof
-fn compute_challenge(stmt: &Statement, alpha: &k256::ProjectivePoint) -> k256::Scalar {
+/// Compute the challenge for Schnorr zk proof, bound to `msg` if any
+fn compute_challenge(
+    stmt: &Statement,
+    alpha: &k256::ProjectivePoint,
+    msg: Option<&[u8]>,
+) -> k256::Scalar {
+    let hasher = match msg {
+        None => Sha256::new().chain(constants::SCHNORR_PROOF_TAG.to_be_bytes()),
+        Some(msg) => Sha256::new()
+            .chain(constants::SCHNORR_SIGNATURE_TAG.to_be_bytes())
+            .chain((msg.len() as u64).to_be_bytes())
+            .chain(msg),
+    };
     <k256::Scalar as Reduce<k256::U256>>::from_be_bytes_reduced(
-        Sha256::new()
-            .chain(constants::SCHNORR_PROOF_TAG.to_be_bytes())
+        hasher
             .chain(stmt.prover_id.to_bytes())
             .chain(k256_serde::point_to_bytes(stmt.base))
             .chain(k256_serde::point_to_bytes(stmt.target))
@@ -47,19 +57,38 @@ fn compute_challenge(stmt: &Statement, alpha: &k256::ProjectivePoint) -> k256::S
 // statement (base, target), witness (scalar)
 //   such that target == scalar * base
 pub fn prove(stmt: &Statement, wit: &Witness) -> Proof {
+    prove_inner(stmt, wit, None)
+}
+
+pub fn verify(stmt: &Statement, proof: &Proof) -> bool {
+    verify_inner(stmt, proof, None)
+}
+
+/// Like [prove] but also bind the proof to `msg`,
+/// so that it is a signature on `msg` by the owner of `stmt.target`
+pub fn sign(stmt: &Statement, wit: &Witness, msg: &[u8]) -> Proof {
+    prove_inner(stmt, wit, Some(msg))
+}
+
+/// Verify a proof from [sign]
+pub fn verify_signature(stmt: &Statement, proof: &Proof, msg: &[u8]) -> bool {
+    verify_inner(stmt, proof, Some(msg))
+}
+
+fn prove_inner(stmt: &Statement, wit: &Witness, msg: Option<&[u8]>) -> Proof {
     let a = SecretScalar::random_with_thread_rng();
     let alpha = stmt.base * a.as_ref();
-    let c = compute_challenge(stmt, &alpha);
+    let c = compute_challenge(stmt, &alpha, msg);
     let t = a.as_ref() - &(c * wit.scalar);
 
     Proof { c, t }
 }
 
-pub fn verify(stmt: &Statement, proof: &Proof) -> bool {
+fn verify_inner(stmt: &Statement, proof: &Proof, msg: Option<&[u8]>) -> bool {
     // Ensure that c and t are in Z_q and target is in G
     // This is handled by k256_serde on deserialize
     let alpha = stmt.base * &proof.t + stmt.target * &proof.c;
-    let c_check = compute_challenge(stmt, &alpha);
+    let c_check = compute_challenge(stmt, &alpha, msg);
 
     if c_check == proof.c {
         true
@@ -126,4 +155,27 @@ mod tests {
         let bad_proof = prove(&stmt, &bad_wit);
         assert!(!verify(&stmt, &bad_proof));
     }
+
+    #[test]
+    fn signature() {
+        let base = &k256::ProjectivePoint::GENERATOR;
+        let scalar = &k256::Scalar::random(rand::thread_rng());
+        let target = &(base * scalar);
+        let stmt = Statement {
+            prover_id: TypedUsize::from_usize(2),
+            base,
+            target,
+        };
+        let wit = Witness { scalar };
+
+        let signature = sign(&stmt, &wit, b"msg");
+        assert!(verify_signature(&stmt, &signature, b"msg"));
+
+        // test: bad msg
+        assert!(!verify_signature(&stmt, &signature, b"other msg"));
+
+        // test: a signature is not a proof and vice versa
+        assert!(!verify(&stmt, &signature));
+        assert!(!verify_signature(&stmt, &prove(&stmt, &wit), b""));
+    }
 }
@@ -30,8 +30,8 @@ impl KeyPair {
     }
 }
 
-pub fn keygen(
-    secret_recovery_key: &rng::SecretRecoveryKey,
+pub fn keygen<P: rng::SecretRecoveryKeyProvider + ?Sized>(
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<KeyPair> {
     let rng =
@@ -104,7 +104,7 @@ const SIGN_TAG: u8 = 0x01;
 #[cfg(test)]
 mod tests {
     use super::{keygen, sign, verify};
-    use crate::{crypto_tools::rng::dummy_secret_recovery_key, multisig::sign::MessageDigest};
+    use crate::crypto_tools::{message_digest::MessageDigest, rng::dummy_secret_recovery_key};
     use core::convert::TryFrom;
 
     #[test]
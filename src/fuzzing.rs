@@ -0,0 +1,248 @@
+//! Fuzzing harness for inbound message handling.
+//!
+//! Each entry point runs a small honest protocol execution up to a round chosen by the fuzzer,
+//! then delivers arbitrary bytes via [Round::msg_in](crate::sdk::api::Round::msg_in)
+//! on behalf of a party chosen by the fuzzer.
+//! The injected bytes may cause that party---and only that party---to be accused.
+//! Any panic or [TofnFatal](crate::sdk::api::TofnFatal) is a bug.
+//!
+//! Input layout: `[round, from, bytes...]`.
+//! `round` selects the (1-based) round whose messages are being delivered when the bytes are injected,
+//! `from` selects the party that sends them.
+//!
+//! `cargo fuzz` targets live in the `fuzz` directory at the repo root.
+
+use core::convert::TryFrom;
+
+use crate::{
+    collections::{TypedUsize, VecMap},
+    crypto_tools::rng::SecretRecoveryKey,
+    gg20, multisig,
+    sdk::{
+        api::{PartyShareCounts, Protocol},
+        local::{execute_honest, execute_round, nobody_done},
+    },
+};
+
+const SESSION_NONCE: &[u8] = b"tofn-fuzzing";
+const PARTY_SHARE_COUNTS: [usize; 2] = [1, 2];
+const THRESHOLD: usize = 1;
+const MSG_TO_SIGN: [u8; 32] = [42; 32];
+
+/// Expensive setup shared by all fuzz inputs.
+/// Build it once and reuse it across inputs.
+pub struct Fixture {
+    gg20_party_keygen_data: VecMap<gg20::keygen::KeygenPartyId, gg20::keygen::PartyKeygenData>,
+    gg20_key_shares: VecMap<gg20::keygen::KeygenShareId, gg20::keygen::SecretKeyShare>,
+    multisig_key_shares: VecMap<multisig::keygen::KeygenShareId, multisig::keygen::SecretKeyShare>,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let gg20_party_keygen_data: VecMap<_, _> = party_share_counts()
+            .iter()
+            .map(|(party_id, _)| {
+                gg20::keygen::create_party_keypair_and_zksetup_unsafe(
+                    party_id,
+                    &secret_recovery_key(party_id),
+                    SESSION_NONCE,
+                )
+                .expect("fixture: party keygen data")
+            })
+            .collect();
+        Self {
+            gg20_key_shares: execute_honest(gg20_keygen(&gg20_party_keygen_data))
+                .expect("fixture: honest execution"),
+            gg20_party_keygen_data,
+            multisig_key_shares: execute_honest(multisig_keygen())
+                .expect("fixture: honest execution"),
+        }
+    }
+
+    fn gg20_sign(&self) -> VecMap<gg20::sign::SignShareId, gg20::sign::SignProtocol> {
+        let sign_parties = all_parties();
+        let msg_to_sign =
+            gg20::sign::MessageDigest::try_from(&MSG_TO_SIGN[..]).expect("fixture: message digest");
+        keygen_share_ids(&sign_parties).map(|keygen_share_id| {
+            let key_share = self
+                .gg20_key_shares
+                .get(keygen_share_id)
+                .expect("fixture: key share");
+            gg20::sign::new_sign(
+                key_share.group(),
+                key_share.share(),
+                &sign_parties,
+                &msg_to_sign,
+                #[cfg(feature = "malicious")]
+                gg20::sign::malicious::Behaviour::Honest,
+            )
+            .expect("fixture: new_sign")
+        })
+    }
+
+    fn multisig_sign(&self) -> VecMap<multisig::sign::SignShareId, multisig::sign::SignProtocol> {
+        let sign_parties = all_parties();
+        let msg_to_sign = multisig::sign::MessageDigest::try_from(&MSG_TO_SIGN[..])
+            .expect("fixture: message digest");
+        keygen_share_ids(&sign_parties).map(|keygen_share_id| {
+            let key_share = self
+                .multisig_key_shares
+                .get(keygen_share_id)
+                .expect("fixture: key share");
+            multisig::sign::new_sign(
+                key_share.group(),
+                key_share.share(),
+                &sign_parties,
+                &msg_to_sign,
+            )
+            .expect("fixture: new_sign")
+        })
+    }
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn gg20_keygen_msg_in(fixture: &Fixture, data: &[u8]) {
+    msg_in(gg20_keygen(&fixture.gg20_party_keygen_data), data)
+}
+
+pub fn gg20_sign_msg_in(fixture: &Fixture, data: &[u8]) {
+    msg_in(fixture.gg20_sign(), data)
+}
+
+pub fn multisig_keygen_msg_in(data: &[u8]) {
+    msg_in(multisig_keygen(), data)
+}
+
+pub fn multisig_sign_msg_in(fixture: &Fixture, data: &[u8]) {
+    msg_in(fixture.multisig_sign(), data)
+}
+
+fn gg20_keygen(
+    party_keygen_data: &VecMap<gg20::keygen::KeygenPartyId, gg20::keygen::PartyKeygenData>,
+) -> VecMap<gg20::keygen::KeygenShareId, gg20::keygen::KeygenProtocol> {
+    let party_share_counts = party_share_counts();
+    party_share_counts
+        .iter()
+        .flat_map(|(party_id, &party_share_count)| {
+            let party_keygen_data = party_keygen_data
+                .get(party_id)
+                .expect("fixture: party keygen data");
+            let party_share_counts = party_share_counts.clone();
+            (0..party_share_count).map(move |subshare_id| {
+                gg20::keygen::new_keygen(
+                    party_share_counts.clone(),
+                    THRESHOLD,
+                    party_id,
+                    subshare_id,
+                    party_keygen_data,
+                    #[cfg(feature = "malicious")]
+                    gg20::keygen::malicious::Behaviour::Honest,
+                )
+                .expect("fixture: new_keygen")
+            })
+        })
+        .collect()
+}
+
+fn multisig_keygen() -> VecMap<multisig::keygen::KeygenShareId, multisig::keygen::KeygenProtocol> {
+    let party_share_counts = party_share_counts();
+    party_share_counts
+        .iter()
+        .flat_map(|(party_id, &party_share_count)| {
+            let party_share_counts = party_share_counts.clone();
+            (0..party_share_count).map(move |subshare_id| {
+                multisig::keygen::new_keygen(
+                    party_share_counts.clone(),
+                    THRESHOLD,
+                    party_id,
+                    subshare_id,
+                    &secret_recovery_key(party_id),
+                    SESSION_NONCE,
+                )
+                .expect("fixture: new_keygen")
+            })
+        })
+        .collect()
+}
+
+fn party_share_counts<P>() -> PartyShareCounts<P> {
+    PartyShareCounts::from_vec(PARTY_SHARE_COUNTS.to_vec()).expect("fixture: party share counts")
+}
+
+fn all_parties<P>() -> crate::collections::Subset<P> {
+    let mut parties = crate::collections::Subset::with_max_size(PARTY_SHARE_COUNTS.len());
+    for i in 0..PARTY_SHARE_COUNTS.len() {
+        parties
+            .add(TypedUsize::from_usize(i))
+            .expect("fixture: sign parties");
+    }
+    parties
+}
+
+fn keygen_share_ids<S, K, P>(
+    sign_parties: &crate::collections::Subset<P>,
+) -> VecMap<S, TypedUsize<K>> {
+    VecMap::from_vec(
+        party_share_counts::<P>()
+            .share_id_subset(sign_parties)
+            .expect("fixture: keygen share ids"),
+    )
+}
+
+/// return the all-zero array with the first bytes set to the bytes of `index`
+fn secret_recovery_key<K>(index: TypedUsize<K>) -> SecretRecoveryKey {
+    let index_bytes = index.as_usize().to_be_bytes();
+    let mut bytes = [0; 64];
+    bytes[..index_bytes.len()].copy_from_slice(&index_bytes);
+    SecretRecoveryKey::try_from(&bytes[..]).expect("fixture: secret recovery key")
+}
+
+fn msg_in<F, K, P>(mut parties: VecMap<K, Protocol<F, K, P>>, data: &[u8]) {
+    let (target_round, from, bytes) = match data {
+        [round, from, bytes @ ..] => (
+            usize::from(*round),
+            TypedUsize::<P>::from_usize(usize::from(*from) % PARTY_SHARE_COUNTS.len()),
+            bytes,
+        ),
+        _ => return,
+    };
+
+    // the injected bytes are delivered before any honest messages of `target_round`
+    let mut current_round = 0;
+    while nobody_done(&parties) {
+        current_round += 1;
+        if current_round == target_round {
+            for (_, party) in parties.iter_mut() {
+                if let Protocol::NotDone(round) = party {
+                    round
+                        .msg_in(from, bytes)
+                        .expect("msg_in returned TofnFatal");
+                }
+            }
+        }
+        parties = execute_round(parties).expect("execution returned TofnFatal");
+        if current_round == target_round {
+            break;
+        }
+    }
+
+    // only `from` may be accused
+    for (share_id, party) in parties.iter() {
+        if let Protocol::Done(Err(faulters)) = party {
+            for (faulter, _) in faulters.iter_some() {
+                assert!(
+                    faulter == from,
+                    "share {} accused party {} but injected bytes came from party {}",
+                    share_id,
+                    faulter,
+                    from
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use alloc::vec::Vec;
+
+use k256::ProjectivePoint;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::r1;
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    crypto_tools::vss,
+    gg20::keygen::{
+        Enrollment, GroupPublicInfo, KeygenPartyId, KeygenPartyShareCounts, KeygenShareId,
+        PartyKeygenData, SecretKeyShare, SharePublicInfo, ShareSecretInfo,
+    },
+    sdk::{
+        api::{PartyShareCounts, Protocol, TofnFatal, TofnResult},
+        implementer_api::{new_protocol, ProtocolBuilder},
+    },
+};
+
+/// Maximum byte length of messages exchanged during party addition.
+/// The largest message is r1::Bcast from a quorum share,
+/// with a point and a Paillier ciphertext for each quorum share.
+/// [new_add_party] rejects quorums of more than [MAX_QUORUM_SHARE_COUNT] shares.
+pub const MAX_MSG_LEN: usize = 100_000;
+
+/// Largest quorum share count for which r1::Bcast fits in [MAX_MSG_LEN]
+pub const MAX_QUORUM_SHARE_COUNT: usize = 128;
+
+pub type AddPartyProtocol = Protocol<SecretKeyShare, AddPartyShareId, AddPartyPartyId>;
+pub type AddPartyProtocolBuilder = ProtocolBuilder<SecretKeyShare, AddPartyShareId>;
+
+// Keygen share ids of all quorum shares, followed by the share id of the new party
+pub type KeygenShareIds = VecMap<AddPartyShareId, TypedUsize<KeygenShareId>>;
+// This is the set of old parties that provision the new party
+pub type AddPartyQuorum = Subset<KeygenPartyId>;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AddPartyShareId;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AddPartyPartyId;
+
+/// Keygen party id of the party added to `group`.
+/// The new party must create its [PartyKeygenData] with this party id.
+pub fn new_party_id(group: &GroupPublicInfo) -> TypedUsize<KeygenPartyId> {
+    TypedUsize::from_usize(group.party_share_counts().party_count())
+}
+
+/// `group` extended by a new party with one share and the keys of `enrollment`.
+/// The group key and threshold are unchanged.
+/// Fail if the proofs of `enrollment` do not verify for [new_party_id].
+pub fn extend_group(
+    group: &GroupPublicInfo,
+    enrollment: &Enrollment,
+) -> TofnResult<GroupPublicInfo> {
+    if !enrollment.verify(new_party_id(group)) {
+        error!("enrollment of the new party failed to verify");
+        return Err(TofnFatal);
+    }
+    extend_group_unchecked(group, enrollment)
+}
+
+/// Update the key share of an old party outside the quorum after a successful party addition.
+/// Quorum parties get their updated key share as the output of [new_add_party].
+pub fn extend_key_share(
+    secret_key_share: &SecretKeyShare,
+    enrollment: &Enrollment,
+) -> TofnResult<SecretKeyShare> {
+    Ok(SecretKeyShare::new(
+        extend_group(secret_key_share.group(), enrollment)?,
+        secret_key_share.share().clone(),
+    ))
+}
+
+/// Like [extend_group] for an enrollment that has already been verified
+#[allow(non_snake_case)]
+pub(super) fn extend_group_unchecked(
+    group: &GroupPublicInfo,
+    enrollment: &Enrollment,
+) -> TofnResult<GroupPublicInfo> {
+    let new_keygen_id = TypedUsize::<KeygenShareId>::from_usize(group.share_count());
+
+    // any threshold + 1 public shares interpolate to the public share of the new party
+    let indices: Vec<usize> = (0..=group.threshold()).collect();
+    let X_new = indices
+        .iter()
+        .try_fold(ProjectivePoint::IDENTITY, |sum, &index| {
+            let X_i = group.all_shares().get(TypedUsize::from_usize(index))?.X_i();
+            Ok::<_, TofnFatal>(
+                sum + X_i.as_ref()
+                    * &vss::lagrange_coefficient_at(index, &indices, new_keygen_id.as_usize())?,
+            )
+        })?;
+
+    let party_share_counts = KeygenPartyShareCounts::from_vec(
+        group
+            .party_share_counts()
+            .iter()
+            .map(|(_, &count)| count)
+            .chain(core::iter::once(1))
+            .collect(),
+    )?;
+
+    let mut all_shares: Vec<SharePublicInfo> = group
+        .all_shares()
+        .iter()
+        .map(|(_, share)| share.clone())
+        .collect();
+    all_shares.push(SharePublicInfo::new(
+        X_new.into(),
+        enrollment.ek().clone(),
+        enrollment.zkp().clone(),
+    ));
+
+    Ok(GroupPublicInfo::new(
+        party_share_counts,
+        group.threshold(),
+        group.y().clone(),
+        VecMap::from_vec(all_shares),
+    ))
+}
+
+/// Initialize a party addition protocol for a share of a quorum party.
+/// Assume `group`, `share` are valid and check `quorum` against it.
+/// Output the key share of `share` extended by the new party.
+pub fn new_add_party(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    quorum: &AddPartyQuorum,
+) -> TofnResult<AddPartyProtocol> {
+    let (all_keygen_ids, add_party_share_counts) = check_quorum(group, quorum)?;
+
+    // find my add-party share_id
+    let my_add_party_id = all_keygen_ids
+        .iter()
+        .find(|(_, &k)| k == share.index())
+        .map(|(s, _)| s)
+        .ok_or_else(|| {
+            error!("my keygen share_id {} is not in the quorum", share.index());
+            TofnFatal
+        })?;
+
+    let round2 = r1::start_quorum(
+        my_add_party_id,
+        SecretKeyShare::new(group.clone(), share.clone()),
+        all_keygen_ids,
+    )?;
+
+    new_protocol(add_party_share_counts, my_add_party_id, MAX_MSG_LEN, round2)
+}
+
+/// Initialize a party addition protocol for the new party.
+/// `group` must be the public info of the quorum parties and
+/// `party_keygen_data` must be created with [new_party_id].
+/// Output the key share of the new party.
+pub fn new_add_party_joiner(
+    group: &GroupPublicInfo,
+    quorum: &AddPartyQuorum,
+    party_keygen_data: &PartyKeygenData,
+) -> TofnResult<AddPartyProtocol> {
+    let (all_keygen_ids, add_party_share_counts) = check_quorum(group, quorum)?;
+    let my_add_party_id = new_party_add_party_id(&all_keygen_ids);
+
+    let round2 = r1::start_new_party(group.clone(), party_keygen_data, all_keygen_ids)?;
+
+    new_protocol(add_party_share_counts, my_add_party_id, MAX_MSG_LEN, round2)
+}
+
+/// Keygen share ids of the quorum shares, without the new share
+pub(super) fn quorum_keygen_ids(all_keygen_ids: &KeygenShareIds) -> Vec<TypedUsize<KeygenShareId>> {
+    all_keygen_ids
+        .iter()
+        .take(all_keygen_ids.len() - 1)
+        .map(|(_, &keygen_id)| keygen_id)
+        .collect()
+}
+
+/// Share id of the new party
+pub(super) fn new_party_add_party_id(
+    all_keygen_ids: &KeygenShareIds,
+) -> TypedUsize<AddPartyShareId> {
+    TypedUsize::from_usize(all_keygen_ids.len() - 1)
+}
+
+/// Lagrange coefficient of quorum share `add_party_id` at the new share
+pub(super) fn lagrange_coefficient_at_new(
+    add_party_id: TypedUsize<AddPartyShareId>,
+    all_keygen_ids: &KeygenShareIds,
+) -> TofnResult<k256::Scalar> {
+    let indices: Vec<usize> = quorum_keygen_ids(all_keygen_ids)
+        .iter()
+        .map(|keygen_id| keygen_id.as_usize())
+        .collect();
+    let new_keygen_id = all_keygen_ids.get(new_party_add_party_id(all_keygen_ids))?;
+    vss::lagrange_coefficient_at(add_party_id.as_usize(), &indices, new_keygen_id.as_usize())
+}
+
+/// Return the keygen share ids of the quorum shares and the new share,
+/// and the party share counts of the quorum parties and the new party.
+fn check_quorum(
+    group: &GroupPublicInfo,
+    quorum: &AddPartyQuorum,
+) -> TofnResult<(KeygenShareIds, PartyShareCounts<AddPartyPartyId>)> {
+    let mut keygen_ids = group.party_share_counts().share_id_subset(quorum)?;
+
+    // quorum share count must be at least threshold + 1
+    if keygen_ids.len() <= group.threshold() || keygen_ids.len() > MAX_QUORUM_SHARE_COUNT {
+        error!(
+            "invalid quorum share count: threshold [{}], quorum [{}], max [{}]",
+            group.threshold(),
+            keygen_ids.len(),
+            MAX_QUORUM_SHARE_COUNT,
+        );
+        return Err(TofnFatal);
+    }
+    keygen_ids.push(TypedUsize::from_usize(group.share_count()));
+
+    let mut share_counts = group.party_share_counts().subset(quorum)?;
+    share_counts.push(1);
+
+    Ok((
+        VecMap::from_vec(keygen_ids),
+        PartyShareCounts::from_vec(share_counts)?,
+    ))
+}
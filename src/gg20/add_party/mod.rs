@@ -0,0 +1,29 @@
+//! Provision one new party with a single share of a gg20 key, keeping the group key and threshold.
+//! This is cheaper than a full reshare for the common "scale out by one signer" operation.
+//!
+//! A quorum of old parties holding at least `threshold + 1` shares runs the protocol with the new party,
+//! which gets the next keygen party id ([new_party_id]) and the next share id.
+//! The new share is `x_new = sum_i lambda_i * x_i` for the Lagrange coefficients `lambda_i` of the quorum shares
+//! at the new share id, but no quorum share reveals its contribution `lambda_i * x_i`:
+//!
+//! * Round 1: each quorum share `i` splits `lambda_i * x_i` into random pieces `s_ij`, one for each quorum share `j`,
+//!   and broadcasts `S_ij = G * s_ij` and `s_ij` encrypted to `j`.
+//!   Anyone can check that the `S_ij` add up to `lambda_i * X_i`.
+//!   The new party broadcasts its [Enrollment](crate::gg20::keygen::Enrollment).
+//! * Round 2: each quorum share `j` checks its pieces against `S_ij`
+//!   and broadcasts `sigma_j = sum_i s_ij` encrypted to the new party, or complaints about bad pieces.
+//! * Round 3: the new party checks each `sigma_j` against `sum_i S_ij`
+//!   and sets `x_new = sum_j sigma_j`, or broadcasts complaints about bad sums.
+//! * Round 4: every participant outputs its key share in the extended group, or the faulters.
+//!
+//! Old parties outside the quorum update their key share with [extend_key_share].
+mod api;
+pub use api::*;
+
+mod r1;
+mod r2;
+mod r3;
+mod r4;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,130 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use k256::{elliptic_curve::Field, ProjectivePoint, Scalar};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::{
+    api::{lagrange_coefficient_at_new, quorum_keygen_ids},
+    r2, AddPartyProtocolBuilder, AddPartyShareId, KeygenShareIds,
+};
+use crate::{
+    collections::TypedUsize,
+    crypto_tools::{k256_serde, paillier},
+    gg20::keygen::{Enrollment, GroupPublicInfo, PartyKeygenData, SecretKeyShare, ShareSecretInfo},
+    sdk::{
+        api::{TofnFatal, TofnResult},
+        implementer_api::{serialize, RoundBuilder},
+    },
+};
+
+/// Piece `s_ij` of the contribution of quorum share `i` for quorum share `j`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub(super) struct Piece {
+    pub(super) S_ij: k256_serde::ProjectivePoint,
+    pub(super) s_ij_ciphertext: paillier::Ciphertext,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) enum Bcast {
+    /// From quorum share `i`: a piece for each quorum share `j`
+    Quorum {
+        pieces: Vec<Piece>,
+    },
+    NewParty {
+        enrollment: Enrollment,
+    },
+}
+
+/// Quorum share `i` splits its contribution `lambda_i * x_i` to the new share into random additive pieces
+pub(super) fn start_quorum(
+    my_add_party_id: TypedUsize<AddPartyShareId>,
+    secret_key_share: SecretKeyShare,
+    all_keygen_ids: KeygenShareIds,
+) -> TofnResult<AddPartyProtocolBuilder> {
+    let group = secret_key_share.group();
+    let quorum_keygen_ids = quorum_keygen_ids(&all_keygen_ids);
+    let lambda_i = lagrange_coefficient_at_new(my_add_party_id, &all_keygen_ids)?;
+    let c_i = lambda_i * secret_key_share.share().x_i();
+
+    // random pieces that add up to c_i
+    let mut s_ijs: Vec<Scalar> = (1..quorum_keygen_ids.len())
+        .map(|_| Scalar::random(rand::thread_rng()))
+        .collect();
+    let s_ij_last = s_ijs.iter().fold(c_i, |acc, s_ij| acc - s_ij);
+    s_ijs.push(s_ij_last);
+
+    let pieces = quorum_keygen_ids
+        .iter()
+        .zip(s_ijs.iter())
+        .map(|(keygen_id, s_ij)| {
+            let ek_j = group.all_shares().get(*keygen_id)?.ek();
+            Ok(Piece {
+                S_ij: (ProjectivePoint::GENERATOR * s_ij).into(),
+                s_ij_ciphertext: ek_j.encrypt(&s_ij.into()).0,
+            })
+        })
+        .collect::<TofnResult<Vec<_>>>()?;
+
+    let bcast_out = Some(serialize(&Bcast::Quorum { pieces })?);
+
+    Ok(AddPartyProtocolBuilder::NotDone(RoundBuilder::new(
+        Box::new(r2::R2 {
+            group: group.clone(),
+            role: Role::Quorum(secret_key_share.share().clone()),
+            all_keygen_ids,
+        }),
+        bcast_out,
+        None,
+    )))
+}
+
+/// The new party publishes its Paillier key and zk setup
+pub(super) fn start_new_party(
+    group: GroupPublicInfo,
+    party_keygen_data: &PartyKeygenData,
+    all_keygen_ids: KeygenShareIds,
+) -> TofnResult<AddPartyProtocolBuilder> {
+    let bcast_out = Some(serialize(&Bcast::NewParty {
+        enrollment: party_keygen_data.enrollment(),
+    })?);
+
+    Ok(AddPartyProtocolBuilder::NotDone(RoundBuilder::new(
+        Box::new(r2::R2 {
+            group,
+            role: Role::NewParty(party_keygen_data.encryption_keypair.dk.clone()),
+            all_keygen_ids,
+        }),
+        bcast_out,
+        None,
+    )))
+}
+
+/// Secret input of a participant
+pub(super) enum Role {
+    Quorum(ShareSecretInfo),
+    NewParty(paillier::DecryptionKey),
+}
+
+impl Bcast {
+    pub(super) fn pieces(&self) -> TofnResult<&[Piece]> {
+        match self {
+            Bcast::Quorum { pieces } => Ok(pieces),
+            Bcast::NewParty { .. } => {
+                error!("expected pieces from a quorum share");
+                Err(TofnFatal)
+            }
+        }
+    }
+
+    pub(super) fn enrollment(&self) -> TofnResult<&Enrollment> {
+        match self {
+            Bcast::NewParty { enrollment } => Ok(enrollment),
+            Bcast::Quorum { .. } => {
+                error!("expected an enrollment from the new party");
+                Err(TofnFatal)
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use k256::{ProjectivePoint, Scalar};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{
+    api::{lagrange_coefficient_at_new, new_party_add_party_id, new_party_id, quorum_keygen_ids},
+    r1::{self, Role},
+    r3, AddPartyShareId, KeygenShareIds,
+};
+use crate::{
+    collections::{FillVecMap, P2ps, TypedUsize},
+    crypto_tools::{k256_serde, paillier},
+    gg20::keygen::{GroupPublicInfo, SecretKeyShare},
+    sdk::{
+        api::{Fault::ProtocolFault, TofnResult},
+        implementer_api::{
+            log_accuse_warn, log_fault_warn, serialize, Executer, ProtocolBuilder, ProtocolInfo,
+            RoundBuilder,
+        },
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum Bcast {
+    /// From quorum share `j`: `sigma_j`, the sum of its pieces, encrypted to the new party
+    Sum {
+        sigma_j_ciphertext: paillier::Ciphertext,
+    },
+    /// From quorum share `j`: the pieces that do not match their commitment
+    Complaints {
+        complaints: Vec<Complaint>,
+    },
+    NewParty,
+}
+
+/// Reveal the decryption of a ciphertext that does not match its commitment
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct Complaint {
+    pub(super) accused: TypedUsize<AddPartyShareId>,
+    pub(super) plaintext: k256_serde::SecretScalar,
+    pub(super) randomness: paillier::Randomness,
+}
+
+impl Bcast {
+    pub(super) fn sigma_j_ciphertext(&self) -> Option<&paillier::Ciphertext> {
+        match self {
+            Bcast::Sum { sigma_j_ciphertext } => Some(sigma_j_ciphertext),
+            _ => None,
+        }
+    }
+}
+
+/// Return `true` if `complaint` shows that `ciphertext` under `ek` does not decrypt to the discrete log of `commit`.
+/// Return `false` if the complaint is false.
+pub(super) fn verify_complaint(
+    ek: &paillier::EncryptionKey,
+    complaint: &Complaint,
+    ciphertext: &paillier::Ciphertext,
+    commit: &ProjectivePoint,
+) -> bool {
+    if !ek.validate_randomness(&complaint.randomness) {
+        return false;
+    }
+    let plaintext = complaint.plaintext.as_ref();
+    if ek.encrypt_with_randomness(&plaintext.into(), &complaint.randomness) != *ciphertext {
+        return false;
+    }
+    ProjectivePoint::GENERATOR * plaintext != *commit
+}
+
+pub(super) struct R2 {
+    pub(super) group: GroupPublicInfo,
+    pub(super) role: Role,
+    pub(super) all_keygen_ids: KeygenShareIds,
+}
+
+impl Executer for R2 {
+    type FinalOutput = SecretKeyShare;
+    type Index = AddPartyShareId;
+    type Bcast = r1::Bcast;
+    type P2p = ();
+
+    #[allow(non_snake_case)]
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        _p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_add_party_id = info.my_id();
+        let new_party = new_party_add_party_id(&self.all_keygen_ids);
+        let mut faulters = info.new_fillvecmap();
+
+        // `Executer` has already flagged missing or corrupted messages,
+        // so every bcast is present
+        let bcasts_in = bcasts_in.to_vecmap()?;
+
+        for (peer_add_party_id, bcast) in bcasts_in.iter() {
+            let valid = match bcast {
+                r1::Bcast::Quorum { pieces } if peer_add_party_id != new_party => {
+                    self.validate_pieces(my_add_party_id, peer_add_party_id, pieces)?
+                }
+                r1::Bcast::NewParty { enrollment } if peer_add_party_id == new_party => {
+                    enrollment.verify(new_party_id(&self.group))
+                }
+                _ => {
+                    log_fault_warn(my_add_party_id, peer_add_party_id, "unexpected bcast");
+                    false
+                }
+            };
+            if !valid {
+                faulters.set(peer_add_party_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        let bcast_out = match &self.role {
+            Role::NewParty(_) => Bcast::NewParty,
+            Role::Quorum(share) => {
+                let mut complaints = Vec::new();
+                let mut sigma_j = Scalar::zero();
+
+                // decrypt my pieces
+                for (peer_add_party_id, bcast) in bcasts_in.iter().take(new_party.as_usize()) {
+                    let piece = &bcast.pieces()?[my_add_party_id.as_usize()];
+                    let (s_ij, randomness) =
+                        share.dk().decrypt_with_randomness(&piece.s_ij_ciphertext);
+                    let s_ij = s_ij.to_scalar();
+
+                    if ProjectivePoint::GENERATOR * s_ij != *piece.S_ij.as_ref() {
+                        log_accuse_warn(my_add_party_id, peer_add_party_id, "invalid piece");
+                        complaints.push(Complaint {
+                            accused: peer_add_party_id,
+                            plaintext: s_ij.into(),
+                            randomness,
+                        });
+                        continue;
+                    }
+                    sigma_j += s_ij;
+                }
+
+                if complaints.is_empty() {
+                    let ek_new = bcasts_in.get(new_party)?.enrollment()?.ek();
+                    Bcast::Sum {
+                        sigma_j_ciphertext: ek_new.encrypt(&(&sigma_j).into()).0,
+                    }
+                } else {
+                    Bcast::Complaints { complaints }
+                }
+            }
+        };
+
+        Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
+            Box::new(r3::R3 {
+                group: self.group,
+                role: self.role,
+                all_keygen_ids: self.all_keygen_ids,
+                r1bcasts: bcasts_in,
+            }),
+            Some(serialize(&bcast_out)?),
+            None,
+        )))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+impl R2 {
+    /// The pieces from quorum share `i` are encrypted to the quorum shares and add up to `lambda_i * X_i`
+    #[allow(non_snake_case)]
+    fn validate_pieces(
+        &self,
+        my_add_party_id: TypedUsize<AddPartyShareId>,
+        peer_add_party_id: TypedUsize<AddPartyShareId>,
+        pieces: &[r1::Piece],
+    ) -> TofnResult<bool> {
+        let quorum_keygen_ids = quorum_keygen_ids(&self.all_keygen_ids);
+        if pieces.len() != quorum_keygen_ids.len() {
+            warn!(
+                "peer {} says: {} pieces from peer {}, expected {}",
+                my_add_party_id,
+                pieces.len(),
+                peer_add_party_id,
+                quorum_keygen_ids.len()
+            );
+            return Ok(false);
+        }
+
+        for (keygen_id, piece) in quorum_keygen_ids.iter().zip(pieces.iter()) {
+            let ek_j = self.group.all_shares().get(*keygen_id)?.ek();
+            if !ek_j.validate_ciphertext(&piece.s_ij_ciphertext) {
+                log_fault_warn(
+                    my_add_party_id,
+                    peer_add_party_id,
+                    "invalid piece ciphertext",
+                );
+                return Ok(false);
+            }
+        }
+
+        let peer_keygen_id = *self.all_keygen_ids.get(peer_add_party_id)?;
+        let X_i = self.group.all_shares().get(peer_keygen_id)?.X_i().as_ref();
+        let lambda_i = lagrange_coefficient_at_new(peer_add_party_id, &self.all_keygen_ids)?;
+        let S_i = pieces.iter().fold(ProjectivePoint::IDENTITY, |sum, piece| {
+            sum + piece.S_ij.as_ref()
+        });
+        if S_i != X_i * &lambda_i {
+            log_fault_warn(my_add_party_id, peer_add_party_id, "pieces do not add up");
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+}
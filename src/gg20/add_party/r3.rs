@@ -0,0 +1,208 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use k256::{ProjectivePoint, Scalar};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use super::{
+    api::new_party_add_party_id,
+    r1::{self, Role},
+    r2::{self, verify_complaint, Complaint},
+    r4, AddPartyShareId, KeygenShareIds,
+};
+use crate::{
+    collections::{FillVecMap, P2ps, TypedUsize, VecMap},
+    gg20::keygen::{GroupPublicInfo, SecretKeyShare, ShareSecretInfo},
+    sdk::{
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
+        implementer_api::{
+            log_accuse_warn, log_fault_info, log_fault_warn, serialize, Executer, ProtocolBuilder,
+            ProtocolInfo, RoundBuilder,
+        },
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum Bcast {
+    Ack,
+    /// From the new party: the sums that do not match their commitment
+    Complaints {
+        complaints: Vec<Complaint>,
+    },
+}
+
+/// Commitment `sum_i S_ij` to `sigma_j` of quorum share `j`
+#[allow(non_snake_case)]
+pub(super) fn sigma_j_commit(
+    r1bcasts: &VecMap<AddPartyShareId, r1::Bcast>,
+    j: TypedUsize<AddPartyShareId>,
+) -> TofnResult<ProjectivePoint> {
+    let new_party = TypedUsize::<AddPartyShareId>::from_usize(r1bcasts.len() - 1);
+    r1bcasts.iter().take(new_party.as_usize()).try_fold(
+        ProjectivePoint::IDENTITY,
+        |sum, (_, bcast)| {
+            let piece = bcast.pieces()?.get(j.as_usize()).ok_or_else(|| {
+                error!("missing piece for quorum share {}", j);
+                TofnFatal
+            })?;
+            Ok(sum + piece.S_ij.as_ref())
+        },
+    )
+}
+
+pub(super) struct R3 {
+    pub(super) group: GroupPublicInfo,
+    pub(super) role: Role,
+    pub(super) all_keygen_ids: KeygenShareIds,
+    pub(super) r1bcasts: VecMap<AddPartyShareId, r1::Bcast>,
+}
+
+impl Executer for R3 {
+    type FinalOutput = SecretKeyShare;
+    type Index = AddPartyShareId;
+    type Bcast = r2::Bcast;
+    type P2p = ();
+
+    #[allow(non_snake_case)]
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        _p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_add_party_id = info.my_id();
+        let new_party = new_party_add_party_id(&self.all_keygen_ids);
+        let ek_new = self.r1bcasts.get(new_party)?.enrollment()?.ek();
+        let mut faulters = info.new_fillvecmap();
+
+        // `Executer` has already flagged missing or corrupted messages,
+        // so every bcast is present
+        let bcasts_in = bcasts_in.to_vecmap()?;
+
+        for (peer_add_party_id, bcast) in bcasts_in.iter() {
+            let valid = match bcast {
+                r2::Bcast::Sum { sigma_j_ciphertext } if peer_add_party_id != new_party => {
+                    ek_new.validate_ciphertext(sigma_j_ciphertext)
+                }
+                r2::Bcast::Complaints { complaints } if peer_add_party_id != new_party => {
+                    !complaints.is_empty()
+                }
+                r2::Bcast::NewParty => peer_add_party_id == new_party,
+                _ => false,
+            };
+            if !valid {
+                log_fault_warn(my_add_party_id, peer_add_party_id, "unexpected bcast");
+                faulters.set(peer_add_party_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // verify complaints about pieces
+        if bcasts_in
+            .iter()
+            .any(|(_, bcast)| matches!(bcast, r2::Bcast::Complaints { .. }))
+        {
+            for (accuser, bcast) in bcasts_in.iter() {
+                let complaints = match bcast {
+                    r2::Bcast::Complaints { complaints } => complaints,
+                    _ => continue,
+                };
+                let accuser_keygen_id = *self.all_keygen_ids.get(accuser)?;
+                let ek_j = self.group.all_shares().get(accuser_keygen_id)?.ek();
+
+                for complaint in complaints {
+                    let accused = complaint.accused;
+                    if accused.as_usize() >= new_party.as_usize() {
+                        log_fault_info(my_add_party_id, accuser, "accused a non-quorum share");
+                        faulters.set(accuser, ProtocolFault)?;
+                        continue;
+                    }
+                    let piece = &self.r1bcasts.get(accused)?.pieces()?[accuser.as_usize()];
+                    if verify_complaint(
+                        ek_j,
+                        complaint,
+                        &piece.s_ij_ciphertext,
+                        piece.S_ij.as_ref(),
+                    ) {
+                        log_fault_info(my_add_party_id, accused, "invalid piece");
+                        faulters.set(accused, ProtocolFault)?;
+                    } else {
+                        log_fault_info(my_add_party_id, accuser, "false accusation");
+                        faulters.set(accuser, ProtocolFault)?;
+                    }
+                }
+            }
+
+            if faulters.is_empty() {
+                error!(
+                    "peer {} says: R3 failure protocol found no faulters",
+                    my_add_party_id
+                );
+                return Err(TofnFatal);
+            }
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        let (bcast_out, share) = match self.role {
+            Role::Quorum(share) => (Bcast::Ack, Some(share)),
+            Role::NewParty(dk) => {
+                let mut complaints = Vec::new();
+                let mut x_new = Scalar::zero();
+
+                // decrypt the sums
+                for (peer_add_party_id, bcast) in bcasts_in.iter().take(new_party.as_usize()) {
+                    let sigma_j_ciphertext = bcast.sigma_j_ciphertext().ok_or(TofnFatal)?;
+                    let (sigma_j, randomness) = dk.decrypt_with_randomness(sigma_j_ciphertext);
+                    let sigma_j = sigma_j.to_scalar();
+
+                    if ProjectivePoint::GENERATOR * sigma_j
+                        != sigma_j_commit(&self.r1bcasts, peer_add_party_id)?
+                    {
+                        log_accuse_warn(my_add_party_id, peer_add_party_id, "invalid sum");
+                        complaints.push(Complaint {
+                            accused: peer_add_party_id,
+                            plaintext: sigma_j.into(),
+                            randomness,
+                        });
+                        continue;
+                    }
+                    x_new += sigma_j;
+                }
+
+                if complaints.is_empty() {
+                    let new_keygen_id = *self.all_keygen_ids.get(new_party)?;
+                    (
+                        Bcast::Ack,
+                        Some(ShareSecretInfo::new(new_keygen_id, dk, x_new)),
+                    )
+                } else {
+                    warn!(
+                        "peer {} says: {} invalid sums",
+                        my_add_party_id,
+                        complaints.len()
+                    );
+                    (Bcast::Complaints { complaints }, None)
+                }
+            }
+        };
+
+        Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
+            Box::new(r4::R4 {
+                group: self.group,
+                share,
+                all_keygen_ids: self.all_keygen_ids,
+                r1bcasts: self.r1bcasts,
+                r2bcasts: bcasts_in,
+            }),
+            Some(serialize(&bcast_out)?),
+            None,
+        )))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
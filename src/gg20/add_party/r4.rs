@@ -0,0 +1,135 @@
+use alloc::boxed::Box;
+
+use k256::ProjectivePoint;
+use tracing::error;
+
+use super::{
+    api::{extend_group_unchecked, new_party_add_party_id},
+    r1, r2,
+    r2::verify_complaint,
+    r3::{self, sigma_j_commit},
+    AddPartyShareId, KeygenShareIds,
+};
+use crate::{
+    collections::{FillVecMap, P2ps, VecMap},
+    gg20::keygen::{GroupPublicInfo, SecretKeyShare, ShareSecretInfo},
+    sdk::{
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
+        implementer_api::{
+            log_fault_info, log_fault_warn, Executer, ProtocolBuilder, ProtocolInfo,
+        },
+    },
+};
+
+pub(super) struct R4 {
+    pub(super) group: GroupPublicInfo,
+    /// `None` if I am the new party and I complained
+    pub(super) share: Option<ShareSecretInfo>,
+    pub(super) all_keygen_ids: KeygenShareIds,
+    pub(super) r1bcasts: VecMap<AddPartyShareId, r1::Bcast>,
+    pub(super) r2bcasts: VecMap<AddPartyShareId, r2::Bcast>,
+}
+
+impl Executer for R4 {
+    type FinalOutput = SecretKeyShare;
+    type Index = AddPartyShareId;
+    type Bcast = r3::Bcast;
+    type P2p = ();
+
+    #[allow(non_snake_case)]
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        _p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_add_party_id = info.my_id();
+        let new_party = new_party_add_party_id(&self.all_keygen_ids);
+        let enrollment = self.r1bcasts.get(new_party)?.enrollment()?;
+        let mut faulters = info.new_fillvecmap();
+
+        // `Executer` has already flagged missing or corrupted messages,
+        // so every bcast is present
+        let bcasts_in = bcasts_in.to_vecmap()?;
+
+        for (peer_add_party_id, bcast) in bcasts_in.iter() {
+            let valid = match bcast {
+                r3::Bcast::Ack => true,
+                r3::Bcast::Complaints { complaints } => {
+                    peer_add_party_id == new_party && !complaints.is_empty()
+                }
+            };
+            if !valid {
+                log_fault_warn(my_add_party_id, peer_add_party_id, "unexpected bcast");
+                faulters.set(peer_add_party_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // verify complaints about sums
+        if let r3::Bcast::Complaints { complaints } = bcasts_in.get(new_party)? {
+            for complaint in complaints {
+                let accused = complaint.accused;
+                if accused.as_usize() >= new_party.as_usize() {
+                    log_fault_info(my_add_party_id, new_party, "accused a non-quorum share");
+                    faulters.set(new_party, ProtocolFault)?;
+                    continue;
+                }
+                let sigma_j_ciphertext = self
+                    .r2bcasts
+                    .get(accused)?
+                    .sigma_j_ciphertext()
+                    .ok_or(TofnFatal)?;
+                if verify_complaint(
+                    enrollment.ek(),
+                    complaint,
+                    sigma_j_ciphertext,
+                    &sigma_j_commit(&self.r1bcasts, accused)?,
+                ) {
+                    log_fault_info(my_add_party_id, accused, "invalid sum");
+                    faulters.set(accused, ProtocolFault)?;
+                } else {
+                    log_fault_info(my_add_party_id, new_party, "false accusation");
+                    faulters.set(new_party, ProtocolFault)?;
+                }
+            }
+
+            if faulters.is_empty() {
+                error!(
+                    "peer {} says: R4 failure protocol found no faulters",
+                    my_add_party_id
+                );
+                return Err(TofnFatal);
+            }
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        let group = extend_group_unchecked(&self.group, enrollment)?;
+        let share = self.share.ok_or_else(|| {
+            error!(
+                "peer {} says: the new party did not complain but I did",
+                my_add_party_id
+            );
+            TofnFatal
+        })?;
+
+        // sanity check: every sum matched its commitment
+        let X_i = group.all_shares().get(share.index())?.X_i().as_ref();
+        if ProjectivePoint::GENERATOR * share.x_i() != *X_i {
+            error!(
+                "peer {} says: my share does not match the extended group",
+                my_add_party_id
+            );
+            return Err(TofnFatal);
+        }
+
+        Ok(ProtocolBuilder::Done(Ok(SecretKeyShare::new(group, share))))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
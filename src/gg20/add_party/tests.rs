@@ -0,0 +1,111 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use ecdsa::hazmat::VerifyPrimitive;
+use k256::PublicKey;
+use tracing_test::traced_test;
+
+use super::*;
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    crypto_tools::rng::dummy_secret_recovery_key,
+    gg20::{
+        keygen::{
+            create_party_keypair_and_zksetup_unsafe, tests::execute_keygen, KeygenPartyShareCounts,
+        },
+        sign::{MessageDigest, SignParties},
+    },
+    sdk::local::{execute_honest, sign},
+};
+
+#[test]
+#[traced_test]
+fn basic_correctness() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 2, 1]).unwrap();
+    let threshold = 2;
+    let key_shares = execute_keygen(&party_share_counts, threshold);
+    let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+
+    // parties 0 and 1 hold threshold + 1 shares
+    let mut quorum = Subset::with_max_size(party_share_counts.party_count());
+    quorum.add(TypedUsize::from_usize(0)).unwrap();
+    quorum.add(TypedUsize::from_usize(1)).unwrap();
+
+    let new_party_keygen_data = create_party_keypair_and_zksetup_unsafe(
+        new_party_id(group),
+        &dummy_secret_recovery_key(42),
+        b"add_party",
+    )
+    .unwrap();
+
+    let mut parties: Vec<_> = party_share_counts
+        .share_id_subset(&quorum)
+        .unwrap()
+        .into_iter()
+        .map(|keygen_id| {
+            let key_share = key_shares.get(keygen_id).unwrap();
+            new_add_party(key_share.group(), key_share.share(), &quorum).unwrap()
+        })
+        .collect();
+    parties.push(new_add_party_joiner(group, &quorum, &new_party_keygen_data).unwrap());
+
+    let outputs: Vec<SecretKeyShare> = execute_honest(VecMap::from_vec(parties))
+        .unwrap()
+        .into_iter()
+        .map(|(_, key_share)| key_share)
+        .collect();
+    assert_eq!(outputs.len(), 4);
+
+    // party 2 is not in the quorum
+    let outsider_share = extend_key_share(
+        key_shares.get(TypedUsize::from_usize(3)).unwrap(),
+        &new_party_keygen_data.enrollment(),
+    )
+    .unwrap();
+
+    let extended_group = outputs[0].group();
+    assert_eq!(extended_group.share_count(), 5);
+    assert_eq!(extended_group.threshold(), threshold);
+    assert_eq!(extended_group.verifying_key(), group.verifying_key());
+    assert_eq!(outsider_share.group(), extended_group);
+
+    let mut all_shares = outputs;
+    let new_share = all_shares.pop().unwrap();
+    assert_eq!(new_share.share().index(), TypedUsize::from_usize(4));
+    all_shares.push(outsider_share);
+    all_shares.push(new_share);
+    for key_share in all_shares.iter() {
+        assert_eq!(key_share.group(), extended_group);
+        assert!(key_share.validate().is_valid());
+    }
+
+    // the new party signs together with party 1
+    let mut sign_parties = SignParties::with_max_size(4);
+    sign_parties.add(TypedUsize::from_usize(1)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(3)).unwrap();
+    let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+    let signature = sign(&VecMap::from_vec(all_shares), &sign_parties, &msg_to_sign).unwrap();
+
+    let pubkey: PublicKey = group.verifying_key().into();
+    assert!(pubkey
+        .as_affine()
+        .verify_prehashed((&msg_to_sign).into(), &signature)
+        .is_ok());
+}
+
+#[test]
+fn quorum_too_small() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 2, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 2);
+    let key_share = key_shares.get(TypedUsize::from_usize(1)).unwrap();
+
+    // party 1 alone holds only threshold shares
+    let mut quorum = Subset::with_max_size(party_share_counts.party_count());
+    quorum.add(TypedUsize::from_usize(1)).unwrap();
+    assert!(new_add_party(key_share.group(), key_share.share(), &quorum).is_err());
+
+    // party 2 is not in the quorum
+    quorum.add(TypedUsize::from_usize(0)).unwrap();
+    let outsider = key_shares.get(TypedUsize::from_usize(3)).unwrap();
+    assert!(new_add_party(outsider.group(), outsider.share(), &quorum).is_err());
+}
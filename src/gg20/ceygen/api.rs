@@ -11,6 +11,7 @@ use crate::{
         paillier::{self, zk::ZkSetup},
         rng,
         ss::{Share, Ss},
+        vss,
     },
     gg20::{
         self,
@@ -20,13 +21,14 @@ use crate::{
             SharePublicInfo,
         },
     },
-    sdk::api::{PartyShareCounts, TofnFatal, TofnResult},
+    sdk::api::{ErrorContext, PartyShareCounts, TofnFatal, TofnResult, TofnResultExt},
 };
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use bincode::Options;
 use core::{convert::TryInto, ops::Mul};
 use k256::{NonZeroScalar, SecretKey};
-pub use rng::SecretRecoveryKey;
+use rand::{seq::index::sample, RngCore};
+pub use rng::{SecretRecoveryKey, SecretRecoveryKeyProvider};
 use tracing::error;
 use tracing::info;
 
@@ -49,36 +51,167 @@ pub const MAX_MSG_LEN: usize = 5500;
 pub type Ceygen = (Vec<u8>, Vec<(TypedUsize<KeygenShareId>, Vec<u8>)>);
 
 /// Validate the party parameters, then split Alice's key into an bincode-encoded byte-array of keyshares.
-pub fn ceygen(parties: usize, threshold: usize, alice_key_byte_array: &[u8]) -> Result<Ceygen> {
+///
+/// The Paillier keys and zk setups of each party are derived from `session_nonce`,
+/// so it can bind the keys to an external context such as a chain id or ceremony id.
+/// It must be between 4 and 256 bytes long.
+pub fn ceygen(
+    parties: usize,
+    threshold: usize,
+    alice_key_byte_array: &[u8],
+    session_nonce: &[u8],
+) -> Result<Ceygen> {
+    ceygen_with_progress(
+        parties,
+        threshold,
+        alice_key_byte_array,
+        session_nonce,
+        |_| {},
+    )
+}
+
+/// Like [ceygen] but call `progress` with the number of parties whose Paillier keys
+/// and zk setups are ready, the slow part of ceygen.
+pub fn ceygen_with_progress(
+    parties: usize,
+    threshold: usize,
+    alice_key_byte_array: &[u8],
+    session_nonce: &[u8],
+    progress: impl FnMut(usize),
+) -> Result<Ceygen> {
     let alice_key = validate_secret_key(alice_key_byte_array)?;
-    let party_share_counts =
-        PartyShareCounts::from_vec(vec![1; parties]).expect("invalid party count");
+    rng::check_session_nonce(session_nonce)
+        .map_err(|_| anyhow::Error::msg("invalid session nonce length"))?;
+    let party_share_counts = PartyShareCounts::from_vec(vec![1; parties])
+        .map_err(|_| anyhow::Error::msg("invalid party count"))?;
     info!("generating secret key shares. This may take several moments.");
-    let secret_key_shares =
-        gg20::ceygen::initialize_honest_parties(&party_share_counts, threshold, *alice_key);
+    let secret_key_shares = gg20::ceygen::initialize_honest_parties(
+        &party_share_counts,
+        threshold,
+        *alice_key,
+        session_nonce,
+        progress,
+    )
+    .map_err(|err| anyhow::anyhow!("bad ceygen; need parties >= threshold+1: {}", err))?;
     info!("key shares generated.");
 
+    encode_ceygen(&party_share_counts, threshold, secret_key_shares)
+}
+
+/// Like [ceygen] but use the given Paillier keys and zk setups, one per party,
+/// instead of generating them from dummy recovery keys.
+/// Skipping safe prime generation makes this fast enough to run on demand.
+///
+/// The proofs in `party_keygen_data` are not verified:
+/// it should come from [create_party_keypair_and_zksetup] run by each party or a trusted dealer.
+pub fn ceygen_with_keygen_data(
+    threshold: usize,
+    alice_key_byte_array: &[u8],
+    party_keygen_data: &VecMap<KeygenPartyId, PartyKeygenData>,
+) -> Result<Ceygen> {
+    let alice_key = validate_secret_key(alice_key_byte_array)?;
+    let party_share_counts = PartyShareCounts::from_vec(vec![1; party_keygen_data.len()])
+        .map_err(|_| anyhow::Error::msg("invalid party count"))?;
+    let secret_key_shares = initialize_parties(
+        &party_share_counts,
+        threshold,
+        &Ss::new_byok(threshold, *alice_key),
+        party_keygen_data,
+    )
+    .map_err(|err| anyhow::anyhow!("bad ceygen; need parties >= threshold+1: {}", err))?;
+
+    encode_ceygen(&party_share_counts, threshold, secret_key_shares)
+}
+
+fn encode_ceygen(
+    party_share_counts: &PartyShareCounts<KeygenPartyId>,
+    threshold: usize,
+    secret_key_shares: VecMap<KeygenShareId, SecretKeyShare>,
+) -> Result<Ceygen> {
     // encode keyshares
+    let bincode = bincode::DefaultOptions::new();
     let secret_key_shares_encoded = secret_key_shares
         .into_iter()
-        .map(|(index, share)| {
-            let bincode = bincode::DefaultOptions::new();
-            (index, bincode.serialize(&share).unwrap())
-        })
-        .collect();
+        .map(|(index, share)| Ok((index, bincode.serialize(&share)?)))
+        .collect::<Result<_>>()?;
 
     // encode party_share_counts
-    let bincode = bincode::DefaultOptions::new();
     let party_share_counts_encoded = bincode
-        .serialize(&party_share_counts)
+        .serialize(party_share_counts)
         .map_err(|err| anyhow::Error::msg("Failed to serialize PartyShareCounts").context(err))?;
 
-    info!("ceygen generated {}-of-{} keys", threshold, parties);
+    info!(
+        "ceygen generated {}-of-{} keys",
+        threshold,
+        party_share_counts.party_count()
+    );
     Ok((party_share_counts_encoded, secret_key_shares_encoded))
 }
 
+/// Check a complete set of shares, eg. the output of [ceygen] read back from storage:
+/// every share must pass [SecretKeyShare::validate] with the same group public info,
+/// and each of `quorum_count` random quorums of `threshold + 1` shares must reconstruct `alice_key_byte_array`.
+pub fn validate_shares(
+    secret_key_shares: &VecMap<KeygenShareId, SecretKeyShare>,
+    alice_key_byte_array: &[u8],
+    quorum_count: usize,
+    rng: &mut impl RngCore,
+) -> Result<()> {
+    let alice_key = validate_secret_key(alice_key_byte_array)?;
+    let group = secret_key_shares
+        .iter()
+        .next()
+        .ok_or_else(|| anyhow!("no key shares"))?
+        .1
+        .group();
+    if secret_key_shares.len() != group.share_count() {
+        bail!(
+            "expected {} key shares, found {}",
+            group.share_count(),
+            secret_key_shares.len()
+        );
+    }
+    for (index, share) in secret_key_shares.iter() {
+        if share.share().index() != index {
+            bail!("share {} has index {}", index, share.share().index());
+        }
+        if share.group() != group {
+            bail!("share {} has different group public info", index);
+        }
+        if !share.validate().is_valid() {
+            bail!("share {} failed validation", index);
+        }
+    }
+
+    let quorum_size = group.threshold() + 1;
+    for _ in 0..quorum_count {
+        let quorum = sample(rng, secret_key_shares.len(), quorum_size).into_vec();
+        let x_is = quorum
+            .iter()
+            .map(|&index| {
+                Ok(*secret_key_shares
+                    .get(TypedUsize::from_usize(index))?
+                    .share()
+                    .x_i())
+            })
+            .collect::<TofnResult<Vec<_>>>()
+            .map_err(|_| anyhow!("missing share in quorum {:?}", quorum))?;
+        let secret = x_is
+            .iter()
+            .enumerate()
+            .try_fold(k256::Scalar::zero(), |sum, (i, x_i)| {
+                Ok(sum + *x_i * vss::lagrange_coefficient(i, &quorum)?)
+            })
+            .map_err(|_: TofnFatal| anyhow!("invalid quorum {:?}", quorum))?;
+        if secret != *alice_key {
+            bail!("shares {:?} do not reconstruct the key", quorum);
+        }
+    }
+    Ok(())
+}
+
 // validate alice_key and return a SecretKey if valid.
-fn validate_secret_key(alice_key_byte_array: &[u8]) -> Result<NonZeroScalar> {
+pub(crate) fn validate_secret_key(alice_key_byte_array: &[u8]) -> Result<NonZeroScalar> {
     Ok(SecretKey::from_be_bytes(alice_key_byte_array)
         .map_err(|err| anyhow::Error::msg("Failed to deserialize SecretKey").context(err))?
         .to_nonzero_scalar())
@@ -88,38 +221,76 @@ pub(crate) fn initialize_honest_parties(
     party_share_counts: &PartyShareCounts<KeygenPartyId>,
     threshold: usize,
     alice_key: k256::Scalar,
-) -> VecMap<KeygenShareId, SecretKeyShare> {
-    let session_nonce = b"foobar";
-    let shares = Ss::new_byok(threshold, alice_key).shares(party_share_counts.total_share_count());
+    session_nonce: &[u8],
+    mut progress: impl FnMut(usize),
+) -> TofnResult<VecMap<KeygenShareId, SecretKeyShare>> {
+    let party_keygen_data = party_share_counts
+        .iter()
+        .map(|(party_id, _)| {
+            // each party use the same secret recovery key for all its subshares
+            let secret_recovery_key = super::dummy_secret_recovery_key(party_id);
+            let party_keygen_data =
+                create_party_keypair_and_zksetup(party_id, &secret_recovery_key, session_nonce)?;
+            progress(party_id.as_usize() + 1);
+            Ok(party_keygen_data)
+        })
+        .collect::<TofnResult<_>>()?;
 
-    let (v_public_info, v_secret_info): (Vec<SharePublicInfo>, Vec<ShareSecretInfo>) =
-        party_share_counts
-            .iter()
-            .zip(shares.into_iter())
-            .flat_map(|((party_id, &party_share_count), share)| {
-                // each party use the same secret recovery key for all its subshares
-                let secret_recovery_key = super::dummy_secret_recovery_key(party_id);
-                let party_keygen_data =
-                    create_party_keypair_and_zksetup(party_id, &secret_recovery_key, session_nonce)
-                        .unwrap();
-
-                (0..party_share_count).map(move |subshare_id| {
-                    new_ceygen(
-                        party_share_counts.clone(),
-                        threshold,
-                        party_id,
-                        subshare_id,
-                        share.clone(),
-                        &party_keygen_data,
-                        #[cfg(feature = "malicious")]
-                        gg20::sign::malicious::Behaviour::Honest,
-                    )
-                    .expect("bad ceygen; need parties >= threshold+1")
-                })
-            })
-            .unzip();
+    initialize_parties(
+        party_share_counts,
+        threshold,
+        &Ss::new_byok(threshold, alice_key),
+        &party_keygen_data,
+    )
+}
 
-    let y = ProjectivePoint::GENERATOR.mul(alice_key);
+/// Deal the shares of `ss` to all parties and build their `SecretKeyShare`s.
+pub(crate) fn initialize_parties(
+    party_share_counts: &PartyShareCounts<KeygenPartyId>,
+    threshold: usize,
+    ss: &Ss,
+    party_keygen_data: &VecMap<KeygenPartyId, PartyKeygenData>,
+) -> TofnResult<VecMap<KeygenShareId, SecretKeyShare>> {
+    if party_keygen_data.len() != party_share_counts.party_count() {
+        error!(
+            "expected keygen data for {} parties, got {}",
+            party_share_counts.party_count(),
+            party_keygen_data.len()
+        );
+        return Err(TofnFatal);
+    }
+    let total_share_count = party_share_counts.total_share_count();
+    if threshold >= total_share_count {
+        error!(
+            "invalid (total_share_count, threshold): ({},{})",
+            total_share_count, threshold
+        );
+        return Err(TofnFatal);
+    }
+    let mut shares = ss.shares(total_share_count).into_iter();
+
+    let mut v_public_info = Vec::with_capacity(total_share_count);
+    let mut v_secret_info = Vec::with_capacity(total_share_count);
+    for (party_id, &party_share_count) in party_share_counts.iter() {
+        for subshare_id in 0..party_share_count {
+            let share = shares.next().ok_or(TofnFatal)?;
+            let (public_info, secret_info) = new_ceygen(
+                party_share_counts.clone(),
+                threshold,
+                party_id,
+                subshare_id,
+                share,
+                party_keygen_data.get(party_id)?,
+                #[cfg(feature = "malicious")]
+                gg20::sign::malicious::Behaviour::Honest,
+            )
+            .context(ErrorContext::new(module_path!(), "new_ceygen").peer(party_id.as_usize()))?;
+            v_public_info.push(public_info);
+            v_secret_info.push(secret_info);
+        }
+    }
+
+    let y = ProjectivePoint::GENERATOR.mul(*ss.get_secret());
 
     let group_public_info = GroupPublicInfo::new(
         party_share_counts.clone(),
@@ -128,10 +299,10 @@ pub(crate) fn initialize_honest_parties(
         VecMap::from_vec(v_public_info),
     );
 
-    v_secret_info
+    Ok(v_secret_info
         .into_iter()
         .map(|share_secret_info| SecretKeyShare::new(group_public_info.clone(), share_secret_info))
-        .collect()
+        .collect())
 }
 
 /// return the all-zero array with the first bytes set to the bytes of `index`
@@ -146,9 +317,9 @@ pub fn dummy_secret_recovery_key<K>(index: TypedUsize<K>) -> SecretRecoveryKey {
 
 // Since safe prime generation is expensive, a party is expected to generate
 // a keypair once for all it's shares and provide it to new_keygen
-pub fn create_party_keypair_and_zksetup(
+pub fn create_party_keypair_and_zksetup<P: SecretRecoveryKeyProvider + ?Sized>(
     my_party_id: TypedUsize<KeygenPartyId>,
-    secret_recovery_key: &SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<PartyKeygenData> {
     let encryption_keypair =
@@ -170,9 +341,9 @@ pub fn create_party_keypair_and_zksetup(
     })
 }
 
-pub fn recover_party_keypair(
+pub fn recover_party_keypair<P: SecretRecoveryKeyProvider + ?Sized>(
     my_party_id: TypedUsize<KeygenPartyId>,
-    secret_recovery_key: &SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<PartyKeyPair> {
     let mut rng = rng::rng_seed(KEYPAIR_TAG, my_party_id, secret_recovery_key, session_nonce)?;
@@ -183,9 +354,9 @@ pub fn recover_party_keypair(
 }
 
 // BEWARE: This is only made visible for faster integration testing
-pub fn create_party_keypair_and_zksetup_unsafe(
+pub fn create_party_keypair_and_zksetup_unsafe<P: SecretRecoveryKeyProvider + ?Sized>(
     my_party_id: TypedUsize<KeygenPartyId>,
-    secret_recovery_key: &SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<PartyKeygenData> {
     let encryption_keypair =
@@ -209,9 +380,9 @@ pub fn create_party_keypair_and_zksetup_unsafe(
 }
 
 // BEWARE: This is only made visible for faster integration testing
-pub fn recover_party_keypair_unsafe(
+pub fn recover_party_keypair_unsafe<P: SecretRecoveryKeyProvider + ?Sized>(
     my_party_id: TypedUsize<KeygenPartyId>,
-    secret_recovery_key: &SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<PartyKeyPair> {
     let mut rng = rng::rng_seed(KEYPAIR_TAG, my_party_id, secret_recovery_key, session_nonce)?;
@@ -282,3 +453,58 @@ pub fn new_ceygen(
 
     TofnResult::Ok((share_public_info, share_secret_info))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ceygen, ceygen_with_keygen_data, validate_shares};
+    use crate::{
+        collections::{TypedUsize, VecMap},
+        gg20::{
+            ceygen::{create_party_keypair_and_zksetup_unsafe, dummy_secret_recovery_key},
+            keygen::{KeygenShareId, SecretKeyShare},
+        },
+    };
+    use bincode::Options;
+
+    #[test]
+    fn session_nonce_length() {
+        let alice_key = k256::Scalar::from(42u32).to_bytes();
+        assert!(ceygen(2, 1, &alice_key, b"abc").is_err());
+        assert!(ceygen(2, 1, &alice_key, &[0; 257]).is_err());
+    }
+
+    #[test]
+    fn validate_shares_detects_corruption() {
+        let alice_key = k256::Scalar::from(42u32).to_bytes();
+        let party_keygen_data = (0..4)
+            .map(|i| {
+                let party_id = TypedUsize::from_usize(i);
+                create_party_keypair_and_zksetup_unsafe(
+                    party_id,
+                    &dummy_secret_recovery_key(party_id),
+                    b"tofn-ceygen-test",
+                )
+                .unwrap()
+            })
+            .collect();
+        let (_, encoded_shares) =
+            ceygen_with_keygen_data(2, &alice_key, &party_keygen_data).unwrap();
+        let mut shares: VecMap<KeygenShareId, SecretKeyShare> = encoded_shares
+            .iter()
+            .map(|(_, bytes)| bincode::DefaultOptions::new().deserialize(bytes).unwrap())
+            .collect();
+        let mut rng = rand::thread_rng();
+
+        validate_shares(&shares, &alice_key, 8, &mut rng).unwrap();
+
+        let other_key = k256::Scalar::from(43u32).to_bytes();
+        assert!(validate_shares(&shares, &other_key, 1, &mut rng).is_err());
+
+        // swap the secrets of two shares
+        let share_0 = shares.get(TypedUsize::from_usize(0)).unwrap().clone();
+        let share_1 = shares.get(TypedUsize::from_usize(1)).unwrap().clone();
+        *shares.get_mut(TypedUsize::from_usize(0)).unwrap() = share_1;
+        *shares.get_mut(TypedUsize::from_usize(1)).unwrap() = share_0;
+        assert!(validate_shares(&shares, &alice_key, 1, &mut rng).is_err());
+    }
+}
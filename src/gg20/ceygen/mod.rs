@@ -1,5 +1,8 @@
 mod api;
 pub use api::*;
 
+mod pvss;
+pub use pvss::*;
+
 // mod secret_key_share;
 // use super::keygen::secret_key_share::*;
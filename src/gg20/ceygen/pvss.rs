@@ -0,0 +1,373 @@
+//! Publicly verifiable ceygen.
+//!
+//! The dealer splits a key with a degree-`threshold` polynomial and publishes a [PvssDealing]:
+//! Feldman commitments to the polynomial, each share Paillier-encrypted to its recipient,
+//! and a range proof that the ciphertext encrypts the discrete log of the committed share.
+//! Each proof is made against the recipient's zk setup, whose factorization the dealer does not know.
+//!
+//! Given the recipients' [PvssRecipient]s, anyone can [verify](PvssDealing::verify) the dealing
+//! against the group public key without help from the recipients,
+//! eg. a bulletin board auditing the dealer.
+//! Recipients then [decrypt](PvssDealing::decrypt_party_shares) their `SecretKeyShare`s.
+//!
+//! As with [ceygen](super::ceygen), the dealer learns the key: PVSS only proves that it dealt it correctly.
+use alloc::vec::Vec;
+
+use k256::ProjectivePoint;
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use crate::{
+    collections::{TypedUsize, VecMap},
+    crypto_tools::{
+        paillier::{
+            self,
+            zk::{range, EncryptionKeyProof, ZkSetup, ZkSetupProof},
+            Ciphertext, EncryptionKey,
+        },
+        vss::{self, Vss},
+    },
+    gg20::keygen::{
+        secret_key_share::{GroupPublicInfo, SecretKeyShare, ShareSecretInfo},
+        KeygenPartyId, KeygenPartyShareCounts, KeygenShareId, PartyKeygenData, SharePublicInfo,
+    },
+    sdk::api::{TofnFatal, TofnResult},
+};
+
+use super::MAX_TOTAL_SHARE_COUNT;
+
+/// The public part of a recipient's [PartyKeygenData], with proofs of its well-formedness
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PvssRecipient {
+    ek: EncryptionKey,
+    ek_proof: EncryptionKeyProof,
+    zk_setup: ZkSetup,
+    zk_setup_proof: ZkSetupProof,
+}
+
+impl PvssRecipient {
+    pub fn new(party_keygen_data: &PartyKeygenData) -> Self {
+        Self {
+            ek: party_keygen_data.encryption_keypair.ek.clone(),
+            ek_proof: party_keygen_data.encryption_keypair_proof.clone(),
+            zk_setup: party_keygen_data.zk_setup.clone(),
+            zk_setup_proof: party_keygen_data.zk_setup_proof.clone(),
+        }
+    }
+
+    fn verify(&self, party_id: TypedUsize<KeygenPartyId>) -> bool {
+        self.ek
+            .verify_correctness(&self.ek_proof, &party_id.to_bytes())
+            && self
+                .zk_setup
+                .verify(&self.zk_setup_proof, &party_id.to_bytes())
+    }
+}
+
+/// Everything the dealer publishes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PvssDealing {
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    commit: vss::Commit,
+    encrypted_shares: VecMap<KeygenShareId, EncryptedShare>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedShare {
+    ciphertext: Ciphertext,
+    proof: range::ProofWc,
+}
+
+/// Split `alice_key` among the owners of `recipients` so that any `threshold + 1` shares recover it.
+/// The proofs in `recipients` are not verified here: that is up to [PvssDealing::verify].
+pub fn pvss_deal(
+    party_share_counts: &KeygenPartyShareCounts,
+    threshold: usize,
+    alice_key: &k256::Scalar,
+    recipients: &VecMap<KeygenPartyId, PvssRecipient>,
+) -> TofnResult<PvssDealing> {
+    let total_share_count = party_share_counts.total_share_count();
+    if recipients.len() != party_share_counts.party_count()
+        || threshold >= total_share_count
+        || total_share_count > MAX_TOTAL_SHARE_COUNT
+    {
+        error!(
+            "invalid (recipient_count, party_count, total_share_count, threshold): ({},{},{},{})",
+            recipients.len(),
+            party_share_counts.party_count(),
+            total_share_count,
+            threshold
+        );
+        return Err(TofnFatal);
+    }
+    if bool::from(alice_key.is_zero()) {
+        error!("secret key is zero");
+        return Err(TofnFatal);
+    }
+
+    let vss = Vss::new_with_secret(threshold, *alice_key);
+    let commit = vss.commit();
+
+    let encrypted_shares = vss
+        .shares(total_share_count)
+        .iter()
+        .map(|share| {
+            let share_id = TypedUsize::from_usize(share.get_index());
+            let recipient = recipients.get(party_share_counts.share_to_party_id(share_id)?)?;
+
+            let (ciphertext, randomness) = recipient
+                .ek
+                .encrypt(&paillier::Plaintext::from_scalar(share.get_scalar()));
+            let proof = recipient.zk_setup.range_proof_wc(
+                &share_statement(
+                    share_id,
+                    recipient,
+                    &ciphertext,
+                    &commit.share_commit(share_id.as_usize()),
+                ),
+                &range::Witness {
+                    msg: share.get_scalar(),
+                    randomness: &randomness,
+                },
+            )?;
+
+            Ok(EncryptedShare { ciphertext, proof })
+        })
+        .collect::<TofnResult<Vec<_>>>()?;
+
+    Ok(PvssDealing {
+        party_share_counts: party_share_counts.clone(),
+        threshold,
+        commit,
+        encrypted_shares: VecMap::from_vec(encrypted_shares),
+    })
+}
+
+impl PvssDealing {
+    /// Return `true` if `self` deals shares of the secret key for `group_public_key`
+    /// to the owners of `recipients`, consistent with a degree-`threshold` polynomial.
+    /// Needs no secret information.
+    pub fn verify(
+        &self,
+        group_public_key: &ProjectivePoint,
+        recipients: &VecMap<KeygenPartyId, PvssRecipient>,
+    ) -> bool {
+        let total_share_count = self.party_share_counts.total_share_count();
+        if recipients.len() != self.party_share_counts.party_count()
+            || self.threshold >= total_share_count
+            || total_share_count > MAX_TOTAL_SHARE_COUNT
+            || self.commit.len() != self.threshold + 1
+            || self.encrypted_shares.len() != total_share_count
+        {
+            warn!("pvss dealing has inconsistent counts");
+            return false;
+        }
+        if *group_public_key == ProjectivePoint::IDENTITY
+            || self.commit.secret_commit() != group_public_key
+        {
+            warn!("pvss dealing does not commit to the group public key");
+            return false;
+        }
+
+        for (party_id, recipient) in recipients.iter() {
+            if !recipient.verify(party_id) {
+                warn!("pvss recipient {} has invalid key proofs", party_id);
+                return false;
+            }
+        }
+
+        for (share_id, encrypted_share) in self.encrypted_shares.iter() {
+            let recipient = match self
+                .party_share_counts
+                .share_to_party_id(share_id)
+                .and_then(|party_id| recipients.get(party_id))
+            {
+                Ok(recipient) => recipient,
+                Err(_) => return false,
+            };
+            if !recipient
+                .ek
+                .validate_ciphertext(&encrypted_share.ciphertext)
+                || !recipient.zk_setup.verify_range_proof_wc(
+                    &share_statement(
+                        share_id,
+                        recipient,
+                        &encrypted_share.ciphertext,
+                        &self.commit.share_commit(share_id.as_usize()),
+                    ),
+                    &encrypted_share.proof,
+                )
+            {
+                warn!("pvss share {} failed to verify", share_id);
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Decrypt the shares of `my_party_id` with its `party_keygen_data`.
+    /// Assume `self` passed [PvssDealing::verify] for `recipients`.
+    pub fn decrypt_party_shares(
+        &self,
+        recipients: &VecMap<KeygenPartyId, PvssRecipient>,
+        my_party_id: TypedUsize<KeygenPartyId>,
+        party_keygen_data: &PartyKeygenData,
+    ) -> TofnResult<Vec<SecretKeyShare>> {
+        if party_keygen_data.encryption_keypair.ek != recipients.get(my_party_id)?.ek {
+            error!(
+                "keygen data of party {} does not match its recipient",
+                my_party_id
+            );
+            return Err(TofnFatal);
+        }
+
+        let all_shares = self
+            .encrypted_shares
+            .iter()
+            .map(|(share_id, _)| {
+                let recipient =
+                    recipients.get(self.party_share_counts.share_to_party_id(share_id)?)?;
+                Ok(SharePublicInfo::new(
+                    self.commit.share_commit(share_id.as_usize()).into(),
+                    recipient.ek.clone(),
+                    recipient.zk_setup.clone(),
+                ))
+            })
+            .collect::<TofnResult<Vec<_>>>()?;
+        let group = GroupPublicInfo::new(
+            self.party_share_counts.clone(),
+            self.threshold,
+            (*self.commit.secret_commit()).into(),
+            VecMap::from_vec(all_shares),
+        );
+
+        let dk = &party_keygen_data.encryption_keypair.dk;
+        (0..self.party_share_counts.party_share_count(my_party_id)?)
+            .map(|subshare_id| {
+                let share_id = self
+                    .party_share_counts
+                    .party_to_share_id(my_party_id, subshare_id)?;
+                let x_i = dk
+                    .decrypt(&self.encrypted_shares.get(share_id)?.ciphertext)
+                    .to_scalar();
+                if ProjectivePoint::GENERATOR * x_i
+                    != *group.all_shares().get(share_id)?.X_i().as_ref()
+                {
+                    error!("decrypted share {} does not match its commitment", share_id);
+                    return Err(TofnFatal);
+                }
+                Ok(SecretKeyShare::new(
+                    group.clone(),
+                    ShareSecretInfo::new(share_id, dk.clone(), x_i),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// The dealer has no share id, so the recipient's share id serves as both prover and verifier id.
+fn share_statement<'a>(
+    share_id: TypedUsize<KeygenShareId>,
+    recipient: &'a PvssRecipient,
+    ciphertext: &'a Ciphertext,
+    share_commit: &'a ProjectivePoint,
+) -> range::StatementWc<'a, KeygenShareId> {
+    range::StatementWc {
+        stmt: range::Statement {
+            prover_id: share_id,
+            verifier_id: share_id,
+            ciphertext,
+            ek: &recipient.ek,
+        },
+        msg_g: share_commit,
+        g: &ProjectivePoint::GENERATOR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gg20::ceygen::{create_party_keypair_and_zksetup_unsafe, dummy_secret_recovery_key};
+    use alloc::vec;
+    use ecdsa::elliptic_curve::Field;
+
+    #[test]
+    fn deal_verify_decrypt() {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2, 1]).unwrap();
+        let threshold = 2;
+        let party_keygen_data: VecMap<KeygenPartyId, PartyKeygenData> = party_share_counts
+            .iter()
+            .map(|(party_id, _)| {
+                create_party_keypair_and_zksetup_unsafe(
+                    party_id,
+                    &dummy_secret_recovery_key(party_id),
+                    b"pvss",
+                )
+                .unwrap()
+            })
+            .collect();
+        let recipients: VecMap<KeygenPartyId, PvssRecipient> = party_keygen_data
+            .iter()
+            .map(|(_, data)| PvssRecipient::new(data))
+            .collect();
+
+        let alice_key = k256::Scalar::random(rand::thread_rng());
+        let y = ProjectivePoint::GENERATOR * alice_key;
+        let dealing = pvss_deal(&party_share_counts, threshold, &alice_key, &recipients).unwrap();
+        assert!(dealing.verify(&y, &recipients));
+
+        // wrong group key
+        assert!(!dealing.verify(&(y + ProjectivePoint::GENERATOR), &recipients));
+
+        // swapped ciphertexts
+        let mut bad_dealing = dealing.clone();
+        let c0 = bad_dealing
+            .encrypted_shares
+            .get(TypedUsize::from_usize(0))
+            .unwrap()
+            .ciphertext
+            .clone();
+        let c1 = bad_dealing
+            .encrypted_shares
+            .get(TypedUsize::from_usize(1))
+            .unwrap()
+            .ciphertext
+            .clone();
+        bad_dealing
+            .encrypted_shares
+            .get_mut(TypedUsize::from_usize(0))
+            .unwrap()
+            .ciphertext = c1;
+        bad_dealing
+            .encrypted_shares
+            .get_mut(TypedUsize::from_usize(1))
+            .unwrap()
+            .ciphertext = c0;
+        assert!(!bad_dealing.verify(&y, &recipients));
+
+        let shares: Vec<SecretKeyShare> = party_keygen_data
+            .iter()
+            .flat_map(|(party_id, data)| {
+                dealing
+                    .decrypt_party_shares(&recipients, party_id, data)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(shares.len(), party_share_counts.total_share_count());
+        for share in &shares {
+            assert_eq!(*share.group().verifying_key().as_affine(), y.to_affine());
+            assert!(share.validate().is_valid());
+        }
+
+        // a party cannot decrypt with someone else's keys
+        assert!(dealing
+            .decrypt_party_shares(
+                &recipients,
+                TypedUsize::from_usize(0),
+                party_keygen_data.get(TypedUsize::from_usize(1)).unwrap()
+            )
+            .is_err());
+    }
+}
@@ -1,3 +1,4 @@
 // Domain separation for seeding the RNG
 pub const KEYPAIR_TAG: u8 = 0x00;
 pub const ZKSETUP_TAG: u8 = 0x01;
+pub const SIGN_NONCE_TAG: u8 = 0x02;
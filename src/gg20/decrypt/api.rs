@@ -0,0 +1,99 @@
+use k256::ProjectivePoint;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::r1;
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    crypto_tools::elgamal::{self, Ciphertext},
+    gg20::keygen::{
+        GroupPublicInfo, KeygenPartyId, KeygenShareId, SecretKeyShare, ShareSecretInfo,
+    },
+    sdk::{
+        api::{BytesVec, PartyShareCounts, Protocol, TofnFatal, TofnResult},
+        implementer_api::{new_protocol, ProtocolBuilder},
+    },
+};
+
+/// Maximum byte length of messages exchanged during decrypt.
+/// The only decrypt message is a point with a Chaum-Pedersen proof.
+pub const MAX_MSG_LEN: usize = 500;
+
+pub type DecryptProtocol = Protocol<BytesVec, DecryptShareId, DecryptPartyId>;
+pub type DecryptProtocolBuilder = ProtocolBuilder<BytesVec, DecryptShareId>;
+
+// This includes all shares participating in the current decrypt protocol
+pub type KeygenShareIds = VecMap<DecryptShareId, TypedUsize<KeygenShareId>>;
+// This is the set of parties participating in the current decrypt protocol
+pub type DecryptParties = Subset<KeygenPartyId>;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DecryptShareId;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DecryptPartyId;
+
+/// Encrypt `msg` so that any `threshold + 1` shares of `group` can decrypt it
+pub fn encrypt(group: &GroupPublicInfo, msg: &[u8]) -> TofnResult<Ciphertext> {
+    elgamal::encrypt(
+        &ProjectivePoint::from(*group.verifying_key().as_affine()),
+        msg,
+    )
+}
+
+/// Initialize a new decrypt protocol for `ciphertext`.
+/// Assume `group`, `share` are valid and check `decrypt_parties` against it.
+pub fn new_decrypt(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    decrypt_parties: &DecryptParties,
+    ciphertext: &Ciphertext,
+) -> TofnResult<DecryptProtocol> {
+    if *ciphertext.ephemeral_point() == ProjectivePoint::IDENTITY {
+        error!("ciphertext ephemeral point is the point at infinity");
+        return Err(TofnFatal);
+    }
+
+    let all_keygen_ids = VecMap::from_vec(
+        group
+            .party_share_counts()
+            .share_id_subset(decrypt_parties)?,
+    );
+
+    // participant share count must be at least threshold + 1
+    if all_keygen_ids.len() <= group.threshold() {
+        error!(
+            "not enough participant shares: threshold [{}], participants [{}]",
+            group.threshold(),
+            all_keygen_ids.len(),
+        );
+        return Err(TofnFatal);
+    }
+
+    // find my keygen share_id
+    let my_decrypt_id = all_keygen_ids
+        .iter()
+        .find(|(_, &k)| k == share.index())
+        .map(|(s, _)| s)
+        .ok_or_else(|| {
+            error!("my keygen share_id {} is not a participant", share.index());
+            TofnFatal
+        })?;
+
+    let decrypt_party_share_counts =
+        PartyShareCounts::from_vec(group.party_share_counts().subset(decrypt_parties)?)?;
+
+    let round2 = r1::start(
+        my_decrypt_id,
+        SecretKeyShare::new(group.clone(), share.clone()),
+        ciphertext.clone(),
+        all_keygen_ids,
+    )?;
+
+    new_protocol(
+        decrypt_party_share_counts,
+        my_decrypt_id,
+        MAX_MSG_LEN,
+        round2,
+    )
+}
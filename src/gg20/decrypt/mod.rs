@@ -0,0 +1,17 @@
+//! Threshold decryption of [hashed ElGamal](crate::crypto_tools::elgamal) ciphertexts
+//! addressed to the group public key of a gg20 keygen.
+//!
+//! Each share `i` broadcasts `D_i = U * x_i` for the ephemeral point `U` of the ciphertext
+//! with a Chaum-Pedersen proof that `D_i` and `X_i = G * x_i` have the same discrete log.
+//! Any `threshold + 1` valid `D_i` interpolate to the shared point `D = U * x`.
+//!
+//! The group key is used for both ECDSA signatures and ElGamal encryption.
+//! Neither security proof covers this joint use: run a separate keygen for decryption if that matters.
+mod api;
+pub use api::*;
+
+mod r1;
+mod r2;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,66 @@
+use alloc::boxed::Box;
+
+use k256::ProjectivePoint;
+use serde::{Deserialize, Serialize};
+
+use super::{r2, DecryptProtocolBuilder, DecryptShareId, KeygenShareIds};
+use crate::{
+    collections::TypedUsize,
+    crypto_tools::{elgamal::Ciphertext, k256_serde, zkp::chaum_pedersen},
+    gg20::keygen::SecretKeyShare,
+    sdk::{
+        api::TofnResult,
+        implementer_api::{serialize, RoundBuilder},
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub(super) struct Bcast {
+    pub(super) D_i: k256_serde::ProjectivePoint,
+    pub(super) D_i_proof: chaum_pedersen::Proof,
+}
+
+#[allow(non_snake_case)]
+pub(super) fn start(
+    my_decrypt_id: TypedUsize<DecryptShareId>,
+    secret_key_share: SecretKeyShare,
+    ciphertext: Ciphertext,
+    all_keygen_ids: KeygenShareIds,
+) -> TofnResult<DecryptProtocolBuilder> {
+    let x_i = secret_key_share.share().x_i();
+    let X_i = secret_key_share
+        .group()
+        .all_shares()
+        .get(secret_key_share.share().index())?
+        .X_i()
+        .as_ref();
+    let U = ciphertext.ephemeral_point();
+    let D_i = U * x_i;
+
+    let D_i_proof = chaum_pedersen::prove(
+        &chaum_pedersen::Statement {
+            prover_id: my_decrypt_id,
+            base1: &ProjectivePoint::GENERATOR,
+            base2: U,
+            target1: X_i,
+            target2: &D_i,
+        },
+        &chaum_pedersen::Witness { scalar: x_i },
+    );
+
+    let bcast_out = Some(serialize(&Bcast {
+        D_i: D_i.into(),
+        D_i_proof,
+    })?);
+
+    Ok(DecryptProtocolBuilder::NotDone(RoundBuilder::new(
+        Box::new(r2::R2 {
+            secret_key_share,
+            ciphertext,
+            all_keygen_ids,
+        }),
+        bcast_out,
+        None,
+    )))
+}
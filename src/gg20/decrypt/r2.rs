@@ -0,0 +1,142 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use k256::ProjectivePoint;
+use tracing::{error, warn};
+
+use super::{r1, DecryptShareId, KeygenShareIds};
+use crate::{
+    collections::{zip2, FillVecMap, P2ps},
+    crypto_tools::{
+        elgamal::{self, Ciphertext},
+        vss,
+        zkp::chaum_pedersen,
+    },
+    gg20::keygen::SecretKeyShare,
+    sdk::{
+        api::{BytesVec, Fault::ProtocolFault, TofnFatal, TofnResult},
+        implementer_api::{Executer, ProtocolBuilder, ProtocolInfo},
+    },
+};
+
+pub(super) struct R2 {
+    pub(super) secret_key_share: SecretKeyShare,
+    pub(super) ciphertext: Ciphertext,
+    pub(super) all_keygen_ids: KeygenShareIds,
+}
+
+impl Executer for R2 {
+    type FinalOutput = BytesVec;
+    type Index = DecryptShareId;
+    type Bcast = r1::Bcast;
+    type P2p = ();
+
+    #[allow(non_snake_case)]
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_decrypt_id = info.my_id();
+        let group = self.secret_key_share.group();
+        let threshold = group.threshold();
+        let U = self.ciphertext.ephemeral_point();
+        let mut faulters = info.new_fillvecmap();
+        let mut valid_indices = Vec::with_capacity(threshold + 1);
+        let mut valid_D_is = Vec::with_capacity(threshold + 1);
+
+        for (peer_decrypt_id, bcast_option, p2ps_option) in zip2(bcasts_in, p2ps_in) {
+            // anyone who did not send a bcast is a faulter
+            let bcast = match bcast_option {
+                Some(bcast) => bcast,
+                None => {
+                    warn!(
+                        "peer {} says: missing bcast from peer {} in round 2",
+                        my_decrypt_id, peer_decrypt_id
+                    );
+                    faulters.set(peer_decrypt_id, ProtocolFault)?;
+                    continue;
+                }
+            };
+
+            // anyone who sent p2ps is a faulter
+            if p2ps_option.is_some() {
+                warn!(
+                    "peer {} says: unexpected p2ps from peer {} in round 2",
+                    my_decrypt_id, peer_decrypt_id
+                );
+                faulters.set(peer_decrypt_id, ProtocolFault)?;
+                continue;
+            }
+
+            // verify D_i
+            let peer_keygen_id = *self.all_keygen_ids.get(peer_decrypt_id)?;
+            let peer_X_i = group.all_shares().get(peer_keygen_id)?.X_i().as_ref();
+
+            if !chaum_pedersen::verify(
+                &chaum_pedersen::Statement {
+                    prover_id: peer_decrypt_id,
+                    base1: &ProjectivePoint::GENERATOR,
+                    base2: U,
+                    target1: peer_X_i,
+                    target2: bcast.D_i.as_ref(),
+                },
+                &bcast.D_i_proof,
+            ) {
+                warn!(
+                    "peer {} says: fail D_i proof verify from peer {} in round 2",
+                    my_decrypt_id, peer_decrypt_id
+                );
+                faulters.set(peer_decrypt_id, ProtocolFault)?;
+                continue;
+            }
+
+            valid_indices.push(peer_keygen_id.as_usize());
+            valid_D_is.push(*bcast.D_i.as_ref());
+
+            // have we got enough valid D_i yet?
+            if valid_D_is.len() > threshold {
+                break;
+            }
+        }
+
+        // not enough valid D_i => sad outcome
+        if valid_D_is.len() <= threshold {
+            warn!(
+                "peer {} says: insufficient valid D_i {} to exceed threshold {}",
+                my_decrypt_id,
+                valid_D_is.len(),
+                threshold
+            );
+
+            // sanity check
+            if faulters.is_empty() {
+                error!(
+                    "peer {} says: insufficient valid D_i but no faulters",
+                    my_decrypt_id
+                );
+                return Err(TofnFatal);
+            }
+
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // interpolate D = U * x in the exponent
+        let D = valid_D_is.iter().enumerate().try_fold(
+            ProjectivePoint::IDENTITY,
+            |sum, (i, D_i)| -> TofnResult<ProjectivePoint> {
+                Ok(sum + D_i * &vss::lagrange_coefficient(i, &valid_indices)?)
+            },
+        )?;
+
+        // every D_i is valid, so failure means the ciphertext is not addressed to the group
+        let plaintext = elgamal::decrypt_with_shared_point(&D, &self.ciphertext)?;
+
+        Ok(ProtocolBuilder::Done(Ok(plaintext)))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
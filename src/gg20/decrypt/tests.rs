@@ -0,0 +1,45 @@
+use alloc::vec;
+
+use super::*;
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    gg20::keygen::{tests::execute_keygen, KeygenPartyShareCounts},
+    sdk::local::execute_honest,
+};
+use tracing_test::traced_test;
+
+#[test]
+#[traced_test]
+fn basic_correctness() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2, 1]).unwrap();
+    let threshold = 2;
+    let key_shares = execute_keygen(&party_share_counts, threshold);
+    let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+    let msg = b"payload addressed to the group";
+    let ciphertext = encrypt(group, msg).unwrap();
+
+    // parties 0 and 1 hold threshold + 1 shares
+    let mut decrypt_parties = Subset::with_max_size(party_share_counts.party_count());
+    decrypt_parties.add(TypedUsize::from_usize(0)).unwrap();
+    decrypt_parties.add(TypedUsize::from_usize(1)).unwrap();
+
+    let parties: VecMap<_, _> = party_share_counts
+        .share_id_subset(&decrypt_parties)
+        .unwrap()
+        .into_iter()
+        .map(|keygen_id| {
+            let key_share = key_shares.get(keygen_id).unwrap();
+            new_decrypt(
+                key_share.group(),
+                key_share.share(),
+                &decrypt_parties,
+                &ciphertext,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    for (_, output) in execute_honest(parties).unwrap() {
+        assert_eq!(output, msg);
+    }
+}
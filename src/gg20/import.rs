@@ -0,0 +1,343 @@
+//! Construct tofn key shares from GG18/GG20 key material generated by another implementation,
+//! so that existing keys can be used with tofn sign without resharing.
+//!
+//! All integers are big-endian byte strings, points are SEC1-encoded.
+//!
+//! Shares must be evaluations of the keygen polynomial at `share_id + 1` for share ids `0..share_count`,
+//! as in tofn and most GG18/GG20 implementations.
+//! Keys whose shares are evaluated at arbitrary points (such as tss-lib's `Ks`) cannot be imported as-is.
+//!
+//! Imported key material carries no proofs: unlike keygen, the Paillier keys and ring-Pedersen parameters
+//! of other shares are not proven to be well-formed.
+//! Import only material that was validated by the implementation that generated it.
+use alloc::vec::Vec;
+
+use k256::ProjectivePoint;
+use tracing::error;
+
+use crate::{
+    collections::{TypedUsize, VecMap},
+    crypto_tools::{
+        k256_serde,
+        paillier::{zk::ZkSetup, DecryptionKey, EncryptionKey},
+        vss,
+    },
+    gg20::keygen::{
+        GroupPublicInfo, KeygenPartyShareCounts, KeygenShareId, SecretKeyShare, SharePublicInfo,
+        ShareSecretInfo,
+    },
+    sdk::api::{TofnFatal, TofnResult},
+};
+
+/// Public key material of a single share
+#[derive(Debug, Clone, Copy)]
+#[allow(non_snake_case)]
+pub struct ImportedSharePublic<'a> {
+    /// `x_i * G`
+    pub X_i: &'a [u8],
+    /// Paillier modulus `N`
+    pub paillier_n: &'a [u8],
+    /// Ring-Pedersen parameters
+    pub n_tilde: &'a [u8],
+    pub h1: &'a [u8],
+    pub h2: &'a [u8],
+}
+
+/// Secret key material of my share
+#[derive(Debug, Clone, Copy)]
+pub struct ImportedShareSecret<'a> {
+    /// 32-byte secret key share
+    pub x_i: &'a [u8],
+    /// Paillier primes
+    pub paillier_p: &'a [u8],
+    pub paillier_q: &'a [u8],
+}
+
+/// Construct a `GroupPublicInfo` from the group public key `y` and the public key material of all shares.
+/// Return `TofnFatal` if the material is malformed or the `X_i` are inconsistent with `y` and `threshold`.
+pub fn import_group(
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    y: &[u8],
+    all_shares: &[ImportedSharePublic],
+) -> TofnResult<GroupPublicInfo> {
+    let share_count = party_share_counts.total_share_count();
+    if all_shares.len() != share_count || threshold >= share_count {
+        error!(
+            "invalid (share_count, threshold, imported_share_count): ({},{},{})",
+            share_count,
+            threshold,
+            all_shares.len()
+        );
+        return Err(TofnFatal);
+    }
+
+    let y = decode_point(y)?;
+
+    let all_shares: VecMap<KeygenShareId, SharePublicInfo> = all_shares
+        .iter()
+        .map(|share| {
+            Ok(SharePublicInfo::new(
+                decode_point(share.X_i)?,
+                EncryptionKey::from_modulus_bytes(share.paillier_n)?,
+                ZkSetup::from_parts(share.n_tilde, share.h1, share.h2)?,
+            ))
+        })
+        .collect::<TofnResult<_>>()?;
+
+    // every `threshold + 1` shares must recover `y`
+    // it suffices to check the first `threshold` shares together with each other share
+    let share_commits: Vec<_> = all_shares
+        .iter()
+        .map(|(share_id, share)| {
+            vss::ShareCommit::from_point(share_id.as_usize(), share.X_i().clone())
+        })
+        .collect();
+    for last in threshold..share_count {
+        let mut subset = share_commits[..threshold].to_vec();
+        subset.push(share_commits[last].clone());
+        if vss::recover_secret_commit(&subset, threshold)? != *y.as_ref() {
+            error!(
+                "imported share {} is inconsistent with the group public key",
+                last
+            );
+            return Err(TofnFatal);
+        }
+    }
+
+    Ok(GroupPublicInfo::new(
+        party_share_counts,
+        threshold,
+        y,
+        all_shares,
+    ))
+}
+
+/// Construct my `SecretKeyShare` from `group` and my secret key material.
+/// Return `TofnFatal` if the secret material does not match my public material in `group`.
+pub fn import_secret_key_share(
+    group: GroupPublicInfo,
+    share_id: TypedUsize<KeygenShareId>,
+    secret: &ImportedShareSecret,
+) -> TofnResult<SecretKeyShare> {
+    let share_public = group.all_shares().get(share_id)?;
+
+    let x_i = decode_scalar(secret.x_i)?;
+    if ProjectivePoint::GENERATOR * x_i != *share_public.X_i().as_ref() {
+        error!("imported x_i does not match X_i of share {}", share_id);
+        return Err(TofnFatal);
+    }
+
+    let dk = DecryptionKey::from_prime_bytes(secret.paillier_p, secret.paillier_q)?;
+    if dk.encryption_key() != *share_public.ek() {
+        error!(
+            "imported paillier key does not match the encryption key of share {}",
+            share_id
+        );
+        return Err(TofnFatal);
+    }
+
+    Ok(SecretKeyShare::new(
+        group,
+        ShareSecretInfo::new(share_id, dk, x_i),
+    ))
+}
+
+fn decode_point(bytes: &[u8]) -> TofnResult<k256_serde::ProjectivePoint> {
+    k256_serde::ProjectivePoint::from_bytes(bytes).ok_or_else(|| {
+        error!("failed to decode imported point");
+        TofnFatal
+    })
+}
+
+fn decode_scalar(bytes: &[u8]) -> TofnResult<k256::Scalar> {
+    let scalar = k256::SecretKey::from_be_bytes(bytes).map_err(|_| {
+        error!("failed to decode imported scalar");
+        TofnFatal
+    })?;
+    Ok(*scalar.to_nonzero_scalar())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+    use core::convert::TryFrom;
+
+    use ecdsa::hazmat::VerifyPrimitive;
+    use libpaillier::unknown_order::BigNumber;
+
+    use super::*;
+    use crate::{
+        collections::Subset,
+        crypto_tools::k256_serde::point_to_bytes,
+        gg20::sign::{new_sign, MessageDigest, SignShareId},
+        sdk::api::{BytesVec, Protocol},
+    };
+
+    #[cfg(feature = "malicious")]
+    use crate::gg20::sign::malicious::Behaviour::Honest;
+
+    #[allow(non_snake_case)]
+    struct RawShare {
+        x_i: [u8; 32],
+        X_i: [u8; 33],
+        p: Vec<u8>,
+        q: Vec<u8>,
+        n: Vec<u8>,
+    }
+
+    fn raw_shares(vss: &vss::Vss, share_count: usize) -> Vec<RawShare> {
+        vss.shares(share_count)
+            .iter()
+            .map(|share| {
+                let (p, q) = (BigNumber::prime(1024), BigNumber::prime(1024));
+                RawShare {
+                    x_i: share.get_scalar().to_bytes().into(),
+                    X_i: point_to_bytes(&(ProjectivePoint::GENERATOR * share.get_scalar())),
+                    n: (&p * &q).to_bytes(),
+                    p: p.to_bytes(),
+                    q: q.to_bytes(),
+                }
+            })
+            .collect()
+    }
+
+    /// Ring-Pedersen parameters `(N~, h1, h2)` with `h2 = h1^s`
+    fn ring_pedersen() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let n_tilde = BigNumber::prime(1024) * BigNumber::prime(1024);
+        let h1 = BigNumber::random(&n_tilde).modpow(&BigNumber::from(2u64), &n_tilde);
+        let h2 = h1.modpow(&BigNumber::random(&n_tilde), &n_tilde);
+        (n_tilde.to_bytes(), h1.to_bytes(), h2.to_bytes())
+    }
+
+    #[test]
+    fn import_and_sign() {
+        let (share_count, threshold) = (3, 1);
+        let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1; share_count]).unwrap();
+        let vss = vss::Vss::new(threshold);
+        let y = point_to_bytes(vss.commit().secret_commit());
+        let raw_shares = raw_shares(&vss, share_count);
+        let (n_tilde, h1, h2) = ring_pedersen();
+        let public_shares: Vec<_> = raw_shares
+            .iter()
+            .map(|raw| ImportedSharePublic {
+                X_i: &raw.X_i,
+                paillier_n: &raw.n,
+                n_tilde: &n_tilde,
+                h1: &h1,
+                h2: &h2,
+            })
+            .collect();
+
+        let group =
+            import_group(party_share_counts.clone(), threshold, &y, &public_shares).unwrap();
+        let key_shares: Vec<_> = raw_shares
+            .iter()
+            .enumerate()
+            .map(|(i, raw)| {
+                import_secret_key_share(
+                    group.clone(),
+                    TypedUsize::from_usize(i),
+                    &ImportedShareSecret {
+                        x_i: &raw.x_i,
+                        paillier_p: &raw.p,
+                        paillier_q: &raw.q,
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+
+        // mismatched secret material is rejected
+        assert!(import_secret_key_share(
+            group.clone(),
+            TypedUsize::from_usize(0),
+            &ImportedShareSecret {
+                x_i: &raw_shares[1].x_i,
+                paillier_p: &raw_shares[0].p,
+                paillier_q: &raw_shares[0].q,
+            },
+        )
+        .is_err());
+        assert!(import_secret_key_share(
+            group.clone(),
+            TypedUsize::from_usize(0),
+            &ImportedShareSecret {
+                x_i: &raw_shares[0].x_i,
+                paillier_p: &raw_shares[1].p,
+                paillier_q: &raw_shares[1].q,
+            },
+        )
+        .is_err());
+
+        // inconsistent X_i are rejected
+        let mut bad_public_shares = public_shares.clone();
+        bad_public_shares.swap(0, 2);
+        assert!(import_group(party_share_counts, threshold, &y, &bad_public_shares).is_err());
+
+        // sign with shares 0, 2
+        let mut sign_parties = Subset::with_max_size(share_count);
+        sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+        sign_parties.add(TypedUsize::from_usize(2)).unwrap();
+        let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+        let mut parties: VecMap<SignShareId, _> = [0, 2]
+            .iter()
+            .map(|&i| {
+                new_sign(
+                    key_shares[i].group(),
+                    key_shares[i].share(),
+                    &sign_parties,
+                    &msg_to_sign,
+                    #[cfg(feature = "malicious")]
+                    Honest,
+                )
+                .unwrap()
+            })
+            .collect();
+
+        while parties
+            .iter()
+            .all(|(_, party)| matches!(party, Protocol::NotDone(_)))
+        {
+            let mut msgs: Vec<(_, BytesVec)> = Vec::new();
+            for (from, party) in parties.iter() {
+                if let Protocol::NotDone(round) = party {
+                    msgs.extend(round.bcast_out().map(|bytes| (from, bytes.clone())));
+                    if let Some(p2ps) = round.p2ps_out() {
+                        msgs.extend(p2ps.iter().map(|(_, bytes)| (from, bytes.clone())));
+                    }
+                }
+            }
+            parties = parties
+                .into_iter()
+                .map(|(_, party)| match party {
+                    Protocol::NotDone(mut round) => {
+                        for (from, bytes) in msgs.iter() {
+                            let from = round
+                                .info()
+                                .party_share_counts()
+                                .share_to_party_id(*from)
+                                .unwrap();
+                            round.msg_in(from, bytes).unwrap();
+                        }
+                        round.execute_next_round().unwrap()
+                    }
+                    done => done,
+                })
+                .collect();
+        }
+
+        let pub_key = k256_serde::ProjectivePoint::from_bytes(&y)
+            .unwrap()
+            .as_ref()
+            .to_affine();
+        for (_, party) in parties.into_iter() {
+            let signature = match party {
+                Protocol::Done(Ok(signature)) => signature,
+                _ => panic!("sign did not succeed"),
+            };
+            let msg: k256::Scalar = (&msg_to_sign).into();
+            assert!(pub_key.verify_prehashed(msg, &signature).is_ok());
+        }
+    }
+}
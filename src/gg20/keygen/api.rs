@@ -1,26 +1,31 @@
-use super::r1;
+use super::{r1, r2};
 use crate::{
     collections::TypedUsize,
     crypto_tools::{
+        hash,
         paillier::{
             self,
             zk::{EncryptionKeyProof, ZkSetup, ZkSetupProof},
             DecryptionKey, EncryptionKey,
         },
-        rng,
+        rng, vss,
     },
     gg20::constants::{KEYPAIR_TAG, ZKSETUP_TAG},
     sdk::{
-        api::{PartyShareCounts, Protocol, TofnFatal, TofnResult},
-        implementer_api::{new_protocol, ProtocolBuilder},
+        api::{BytesVec, PartyShareCounts, Protocol, RoundDeadlines, TofnFatal, TofnResult},
+        implementer_api::{deserialize, new_protocol, serialize, ProtocolBuilder},
     },
 };
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use tracing::error;
 use zeroize::Zeroize;
 
 #[cfg(feature = "malicious")]
 use super::malicious;
+#[cfg(feature = "malicious")]
+use crate::sdk::implementer_api::with_msg_fault;
 
 /// Maximum byte length of messages exchanged during keygen.
 /// The sender of a message larger than this maximum will be accused as a faulter.
@@ -28,11 +33,16 @@ use super::malicious;
 /// The largest keygen message is r1::Bcast with size ~4833 bytes on the wire.
 /// There is also a variable-sized message in r2::Bcast that depends on the
 /// threshold: 34t + 73. For t = 100, this is still smaller than the limit.
+/// [new_keygen] rejects thresholds for which r2::Bcast would exceed the limit,
+/// ie. thresholds of about 160 and above.
 /// See https://github.com/axelarnetwork/tofn/issues/171
 pub const MAX_MSG_LEN: usize = 5500;
 
+/// Number of rounds executed by an honest keygen; faults may add rounds.
+pub const ROUND_COUNT: usize = 3;
+
 pub use super::secret_key_share::*;
-pub use rng::SecretRecoveryKey;
+pub use rng::{SecretRecoveryKey, SecretRecoveryKeyProvider};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub struct KeygenShareId;
@@ -40,18 +50,18 @@ pub struct KeygenShareId;
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub struct KeygenPartyId;
 
-pub type KeygenProtocol = Protocol<SecretKeyShare, KeygenShareId, KeygenPartyId, MAX_MSG_LEN>;
+pub type KeygenProtocol = Protocol<SecretKeyShare, KeygenShareId, KeygenPartyId>;
 pub type KeygenProtocolBuilder = ProtocolBuilder<SecretKeyShare, KeygenShareId>;
 pub type KeygenPartyShareCounts = PartyShareCounts<KeygenPartyId>;
 
-#[derive(Debug, Clone, Zeroize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Zeroize)]
 #[zeroize(drop)]
 pub struct PartyKeyPair {
     pub(crate) ek: EncryptionKey,
     pub(crate) dk: DecryptionKey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PartyKeygenData {
     pub(crate) encryption_keypair: PartyKeyPair,
     pub(crate) encryption_keypair_proof: EncryptionKeyProof,
@@ -59,11 +69,93 @@ pub struct PartyKeygenData {
     pub(crate) zk_setup_proof: ZkSetupProof,
 }
 
+/// Prefix of [PartyKeygenData::seal], followed by the format version as a big-endian `u16`
+const SEALED_KEYGEN_DATA_MAGIC: &[u8] = b"tofn-party-keygen-data";
+const SEALED_KEYGEN_DATA_VERSION: u16 = 0;
+const SEAL_TAG_LEN: usize = 32;
+
+impl PartyKeygenData {
+    /// Serialize for transfer to another process, eg. from the process that generated
+    /// the Paillier keypair and zk setup in an enclave to the process that runs keygen.
+    ///
+    /// The output is authenticated with HMAC-SHA256 under `sealing_key` and bound to `my_party_id`,
+    /// but it is NOT encrypted: it contains the Paillier secret key,
+    /// so it must travel over a confidential channel.
+    pub fn seal(
+        &self,
+        my_party_id: TypedUsize<KeygenPartyId>,
+        sealing_key: &[u8],
+    ) -> TofnResult<BytesVec> {
+        let mut bytes = SEALED_KEYGEN_DATA_MAGIC.to_vec();
+        bytes.extend_from_slice(&SEALED_KEYGEN_DATA_VERSION.to_be_bytes());
+        bytes.extend_from_slice(&serialize(self)?);
+        let tag = seal_mac(sealing_key, my_party_id, &bytes)?
+            .finalize()
+            .into_bytes();
+        bytes.extend_from_slice(&tag);
+        Ok(bytes)
+    }
+
+    /// Inverse of [PartyKeygenData::seal].
+    /// Return `TofnFatal` if `bytes` were not sealed under `sealing_key` for `my_party_id`.
+    pub fn unseal(
+        bytes: &[u8],
+        my_party_id: TypedUsize<KeygenPartyId>,
+        sealing_key: &[u8],
+    ) -> TofnResult<Self> {
+        let header_len = SEALED_KEYGEN_DATA_MAGIC.len() + 2;
+        if bytes.len() < header_len + SEAL_TAG_LEN {
+            error!("sealed keygen data too short: {} bytes", bytes.len());
+            return Err(TofnFatal);
+        }
+        let (sealed, tag) = bytes.split_at(bytes.len() - SEAL_TAG_LEN);
+        seal_mac(sealing_key, my_party_id, sealed)?
+            .verify_slice(tag)
+            .map_err(|_| {
+                error!(
+                    "sealed keygen data failed authentication for party {}",
+                    my_party_id
+                );
+                TofnFatal
+            })?;
+
+        let (header, payload) = sealed.split_at(header_len);
+        let (magic, version) = header.split_at(SEALED_KEYGEN_DATA_MAGIC.len());
+        let version = u16::from_be_bytes([version[0], version[1]]);
+        if magic != SEALED_KEYGEN_DATA_MAGIC || version != SEALED_KEYGEN_DATA_VERSION {
+            error!(
+                "unsupported sealed keygen data version {}, expected {}",
+                version, SEALED_KEYGEN_DATA_VERSION
+            );
+            return Err(TofnFatal);
+        }
+
+        deserialize(payload).ok_or_else(|| {
+            error!("failed to deserialize sealed keygen data");
+            TofnFatal
+        })
+    }
+}
+
+fn seal_mac(
+    sealing_key: &[u8],
+    my_party_id: TypedUsize<KeygenPartyId>,
+    sealed: &[u8],
+) -> TofnResult<Hmac<Sha256>> {
+    let mac = Hmac::<Sha256>::new_from_slice(sealing_key).map_err(|_| {
+        error!("invalid sealing key");
+        TofnFatal
+    })?;
+    Ok(mac
+        .chain_update(my_party_id.to_bytes())
+        .chain_update(sealed))
+}
+
 // Since safe prime generation is expensive, a party is expected to generate
 // a keypair once for all it's shares and provide it to new_keygen
-pub fn create_party_keypair_and_zksetup(
+pub fn create_party_keypair_and_zksetup<P: SecretRecoveryKeyProvider + ?Sized>(
     my_party_id: TypedUsize<KeygenPartyId>,
-    secret_recovery_key: &SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<PartyKeygenData> {
     let encryption_keypair =
@@ -85,9 +177,9 @@ pub fn create_party_keypair_and_zksetup(
     })
 }
 
-pub fn recover_party_keypair(
+pub fn recover_party_keypair<P: SecretRecoveryKeyProvider + ?Sized>(
     my_party_id: TypedUsize<KeygenPartyId>,
-    secret_recovery_key: &SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<PartyKeyPair> {
     let mut rng = rng::rng_seed(KEYPAIR_TAG, my_party_id, secret_recovery_key, session_nonce)?;
@@ -98,9 +190,9 @@ pub fn recover_party_keypair(
 }
 
 // BEWARE: This is only made visible for faster integration testing
-pub fn create_party_keypair_and_zksetup_unsafe(
+pub fn create_party_keypair_and_zksetup_unsafe<P: SecretRecoveryKeyProvider + ?Sized>(
     my_party_id: TypedUsize<KeygenPartyId>,
-    secret_recovery_key: &SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<PartyKeygenData> {
     let encryption_keypair =
@@ -124,9 +216,9 @@ pub fn create_party_keypair_and_zksetup_unsafe(
 }
 
 // BEWARE: This is only made visible for faster integration testing
-pub fn recover_party_keypair_unsafe(
+pub fn recover_party_keypair_unsafe<P: SecretRecoveryKeyProvider + ?Sized>(
     my_party_id: TypedUsize<KeygenPartyId>,
-    secret_recovery_key: &SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<PartyKeyPair> {
     let mut rng = rng::rng_seed(KEYPAIR_TAG, my_party_id, secret_recovery_key, session_nonce)?;
@@ -149,6 +241,9 @@ pub const MAX_PARTY_SHARE_COUNT: usize = MAX_TOTAL_SHARE_COUNT;
 // since #[cfg(tests)] only works for unit tests
 
 /// Initialize a new keygen protocol
+///
+/// Fail if `threshold` is so large that the round 2 bcast would exceed [MAX_MSG_LEN],
+/// ie. for thresholds of about 160 and above. The other keygen constructors share this limit.
 #[allow(clippy::too_many_arguments)]
 pub fn new_keygen(
     party_share_counts: KeygenPartyShareCounts,
@@ -158,6 +253,100 @@ pub fn new_keygen(
     party_keygen_data: &PartyKeygenData,
     #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
 ) -> TofnResult<KeygenProtocol> {
+    let my_keygen_id =
+        check_keygen_args(&party_share_counts, threshold, my_party_id, my_subshare_id)?;
+
+    #[cfg(feature = "malicious")]
+    let msg_fault = behaviour.msg_fault();
+
+    let round2 = r1::start(
+        my_keygen_id,
+        threshold,
+        party_share_counts.clone(),
+        party_keygen_data,
+        vss::Vss::new(threshold),
+        hash::Randomness::random(),
+        None,
+        #[cfg(feature = "malicious")]
+        behaviour,
+    )?;
+
+    let protocol = new_protocol(party_share_counts, my_keygen_id, MAX_MSG_LEN, round2)?;
+    #[cfg(feature = "malicious")]
+    let protocol = with_msg_fault(protocol, msg_fault)?;
+    Ok(protocol)
+}
+
+/// Configure a keygen protocol one option at a time, as an alternative to [new_keygen].
+/// Options added later do not break existing callers.
+pub struct KeygenBuilder<'a> {
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    my_party_id: TypedUsize<KeygenPartyId>,
+    my_subshare_id: usize,
+    party_keygen_data: &'a PartyKeygenData,
+    deadlines: Option<RoundDeadlines>,
+    #[cfg(feature = "malicious")]
+    behaviour: malicious::Behaviour,
+}
+
+impl<'a> KeygenBuilder<'a> {
+    /// Equivalent to [new_keygen] unless other options are set
+    pub fn new(
+        party_share_counts: KeygenPartyShareCounts,
+        threshold: usize,
+        my_party_id: TypedUsize<KeygenPartyId>,
+        my_subshare_id: usize,
+        party_keygen_data: &'a PartyKeygenData,
+    ) -> Self {
+        Self {
+            party_share_counts,
+            threshold,
+            my_party_id,
+            my_subshare_id,
+            party_keygen_data,
+            deadlines: None,
+            #[cfg(feature = "malicious")]
+            behaviour: malicious::Behaviour::Honest,
+        }
+    }
+
+    /// See [Round::set_deadlines](crate::sdk::api::Round::set_deadlines)
+    pub fn deadlines(mut self, deadlines: RoundDeadlines) -> Self {
+        self.deadlines = Some(deadlines);
+        self
+    }
+
+    #[cfg(feature = "malicious")]
+    pub fn behaviour(mut self, behaviour: malicious::Behaviour) -> Self {
+        self.behaviour = behaviour;
+        self
+    }
+
+    pub fn build(self) -> TofnResult<KeygenProtocol> {
+        let mut protocol = new_keygen(
+            self.party_share_counts,
+            self.threshold,
+            self.my_party_id,
+            self.my_subshare_id,
+            self.party_keygen_data,
+            #[cfg(feature = "malicious")]
+            self.behaviour,
+        )?;
+        if let (Some(deadlines), Protocol::NotDone(round)) = (self.deadlines, &mut protocol) {
+            round.set_deadlines(deadlines);
+        }
+        Ok(protocol)
+    }
+}
+
+/// Validate the arguments of [new_keygen] and return my keygen share id
+pub(super) fn check_keygen_args(
+    party_share_counts: &KeygenPartyShareCounts,
+    threshold: usize,
+    my_party_id: TypedUsize<KeygenPartyId>,
+    my_subshare_id: usize,
+) -> TofnResult<TypedUsize<KeygenShareId>> {
     // validate args
     if party_share_counts
         .iter()
@@ -184,14 +373,17 @@ pub fn new_keygen(
         return Err(TofnFatal);
     }
 
-    let round2 = r1::start(
-        my_keygen_id,
-        threshold,
-        party_share_counts.clone(),
-        party_keygen_data,
-        #[cfg(feature = "malicious")]
-        behaviour,
-    )?;
+    // r2::Bcast grows with the threshold; fail now instead of getting accused in round 2
+    // the largest share id has the longest encoding
+    let r2_bcast_len =
+        r2::bcast_wire_len(threshold, TypedUsize::from_usize(total_share_count - 1))?;
+    if r2_bcast_len > MAX_MSG_LEN {
+        error!(
+            "threshold {} is too large: round 2 bcast length {} exceeds max message length {}",
+            threshold, r2_bcast_len, MAX_MSG_LEN
+        );
+        return Err(TofnFatal);
+    }
 
-    new_protocol(party_share_counts, my_keygen_id, round2)
+    Ok(my_keygen_id)
 }
@@ -0,0 +1,189 @@
+//! Resume keygen after a crash.
+//!
+//! All of a party's keygen randomness is sampled before round 1,
+//! except Paillier encryption randomness in round 2 and proof nonces in round 3,
+//! which never influence the party's own state.
+//! A [KeygenCheckpoint] holds that randomness together with every message the party received.
+//! Replaying the received messages from the checkpoint recovers the exact protocol state the party had before the crash,
+//! so peers need not restart.
+//!
+//! A checkpoint contains secret data: store it like a `SecretKeyShare`.
+//! Discard it once keygen has finished.
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use super::{
+    api::check_keygen_args, r1, KeygenPartyId, KeygenPartyShareCounts, KeygenProtocol,
+    PartyKeygenData, MAX_MSG_LEN,
+};
+use crate::{
+    collections::TypedUsize,
+    crypto_tools::{hash, vss},
+    sdk::{
+        api::{BytesVec, Protocol, TofnFatal, TofnResult},
+        implementer_api::new_protocol,
+    },
+};
+
+#[cfg(feature = "malicious")]
+use super::malicious;
+#[cfg(feature = "malicious")]
+use crate::sdk::implementer_api::with_msg_fault;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeygenCheckpoint {
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    my_party_id: TypedUsize<KeygenPartyId>,
+    my_subshare_id: usize,
+    u_i_vss: vss::Vss,
+    y_i_reveal: hash::Randomness,
+    msgs_in: Vec<(TypedUsize<KeygenPartyId>, BytesVec)>,
+    rounds_sent: usize,
+}
+
+/// Output of [resume_keygen]
+pub struct ResumedKeygen {
+    pub protocol: KeygenProtocol,
+    /// `false` if the outgoing messages of the current round were sent before the crash.
+    /// Sending them again would get this party accused of sending duplicate messages.
+    pub send_msgs_out: bool,
+}
+
+impl KeygenCheckpoint {
+    /// Record a message that was passed to `msg_in`.
+    /// Persist the checkpoint before the next call to `execute_next_round`.
+    pub fn record_msg_in(&mut self, from: TypedUsize<KeygenPartyId>, bytes: &[u8]) {
+        self.msgs_in.push((from, bytes.to_vec()));
+    }
+
+    /// Record that the outgoing messages of the current round are about to be sent.
+    /// Persist the checkpoint before sending them:
+    /// if the party crashes before they are delivered, peers wait for messages that never arrive,
+    /// which is recoverable by restarting keygen,
+    /// whereas messages sent twice get this party accused as a faulter.
+    pub fn record_msgs_out_sent(&mut self) {
+        self.rounds_sent += 1;
+    }
+}
+
+/// Like [new_keygen](super::new_keygen) but also return a [KeygenCheckpoint] from which keygen can be resumed.
+#[allow(clippy::too_many_arguments)]
+pub fn new_keygen_with_checkpoint(
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    my_party_id: TypedUsize<KeygenPartyId>,
+    my_subshare_id: usize, // in 0..party_share_counts[my_party_id]
+    party_keygen_data: &PartyKeygenData,
+    #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
+) -> TofnResult<(KeygenProtocol, KeygenCheckpoint)> {
+    let checkpoint = KeygenCheckpoint {
+        party_share_counts,
+        threshold,
+        my_party_id,
+        my_subshare_id,
+        u_i_vss: vss::Vss::new(threshold),
+        y_i_reveal: hash::Randomness::random(),
+        msgs_in: Vec::new(),
+        rounds_sent: 0,
+    };
+    let protocol = start(
+        &checkpoint,
+        party_keygen_data,
+        #[cfg(feature = "malicious")]
+        behaviour,
+    )?;
+    Ok((protocol, checkpoint))
+}
+
+/// Rebuild the keygen protocol from `checkpoint` by replaying all recorded incoming messages.
+/// `party_keygen_data` must be the one passed to [new_keygen_with_checkpoint].
+pub fn resume_keygen(
+    checkpoint: &KeygenCheckpoint,
+    party_keygen_data: &PartyKeygenData,
+    #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
+) -> TofnResult<ResumedKeygen> {
+    let mut protocol = start(
+        checkpoint,
+        party_keygen_data,
+        #[cfg(feature = "malicious")]
+        behaviour,
+    )?;
+    let mut rounds_replayed = 0;
+
+    for (from, bytes) in checkpoint.msgs_in.iter() {
+        let mut round = match protocol {
+            Protocol::NotDone(round) => round,
+            Protocol::Done(_) => {
+                error!("checkpoint has messages after keygen is done");
+                return Err(TofnFatal);
+            }
+        };
+        round.msg_in(*from, bytes)?;
+        protocol = if round.expecting_more_msgs_this_round() {
+            Protocol::NotDone(round)
+        } else {
+            rounds_replayed += 1;
+            round.execute_next_round()?
+        };
+    }
+
+    info!(
+        "party {} resumed keygen after replaying {} rounds",
+        checkpoint.my_party_id, rounds_replayed
+    );
+
+    Ok(ResumedKeygen {
+        protocol,
+        send_msgs_out: checkpoint.rounds_sent <= rounds_replayed,
+    })
+}
+
+fn start(
+    checkpoint: &KeygenCheckpoint,
+    party_keygen_data: &PartyKeygenData,
+    #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
+) -> TofnResult<KeygenProtocol> {
+    let my_keygen_id = check_keygen_args(
+        &checkpoint.party_share_counts,
+        checkpoint.threshold,
+        checkpoint.my_party_id,
+        checkpoint.my_subshare_id,
+    )?;
+
+    if checkpoint.u_i_vss.get_threshold() != checkpoint.threshold {
+        error!(
+            "checkpoint vss threshold {} does not match keygen threshold {}",
+            checkpoint.u_i_vss.get_threshold(),
+            checkpoint.threshold
+        );
+        return Err(TofnFatal);
+    }
+
+    #[cfg(feature = "malicious")]
+    let msg_fault = behaviour.msg_fault();
+
+    let round2 = r1::start(
+        my_keygen_id,
+        checkpoint.threshold,
+        checkpoint.party_share_counts.clone(),
+        party_keygen_data,
+        checkpoint.u_i_vss.clone(),
+        checkpoint.y_i_reveal.clone(),
+        None,
+        #[cfg(feature = "malicious")]
+        behaviour,
+    )?;
+
+    let protocol = new_protocol(
+        checkpoint.party_share_counts.clone(),
+        my_keygen_id,
+        MAX_MSG_LEN,
+        round2,
+    )?;
+    #[cfg(feature = "malicious")]
+    let protocol = with_msg_fault(protocol, msg_fault)?;
+    Ok(protocol)
+}
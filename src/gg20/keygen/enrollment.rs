@@ -0,0 +1,141 @@
+//! Two-phase keygen: parties first publish an [Enrollment] with their Paillier key, zk setup and proofs,
+//! which can be collected and vetted offline with [verify_enrollments].
+//! Keygen with [new_keygen_enrolled] then omits this data from its round 1 bcast
+//! and does not verify the proofs again.
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use super::{
+    api::check_keygen_args, r1, KeygenPartyId, KeygenPartyShareCounts, KeygenProtocol,
+    PartyKeygenData, MAX_MSG_LEN,
+};
+use crate::{
+    collections::{TypedUsize, VecMap},
+    crypto_tools::{
+        hash,
+        paillier::{
+            zk::{EncryptionKeyProof, ZkSetup, ZkSetupProof},
+            EncryptionKey,
+        },
+        vss,
+    },
+    sdk::{
+        api::{TofnFatal, TofnResult},
+        implementer_api::new_protocol,
+    },
+};
+
+#[cfg(feature = "malicious")]
+use super::malicious;
+#[cfg(feature = "malicious")]
+use crate::sdk::implementer_api::with_msg_fault;
+
+/// Public part of [PartyKeygenData]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Enrollment {
+    pub(super) ek: EncryptionKey,
+    pub(super) ek_proof: EncryptionKeyProof,
+    pub(super) zkp: ZkSetup,
+    pub(super) zkp_proof: ZkSetupProof,
+}
+
+/// Enrollments of all keygen parties
+pub type Enrollments = VecMap<KeygenPartyId, Enrollment>;
+
+impl PartyKeygenData {
+    pub fn enrollment(&self) -> Enrollment {
+        Enrollment {
+            ek: self.encryption_keypair.ek.clone(),
+            ek_proof: self.encryption_keypair_proof.clone(),
+            zkp: self.zk_setup.clone(),
+            zkp_proof: self.zk_setup_proof.clone(),
+        }
+    }
+}
+
+impl Enrollment {
+    /// Verify the proofs of this enrollment, which are bound to `party_id`
+    pub fn verify(&self, party_id: TypedUsize<KeygenPartyId>) -> bool {
+        if !self
+            .ek
+            .verify_correctness(&self.ek_proof, &party_id.to_bytes())
+        {
+            warn!("ek proof from party {} failed to verify", party_id);
+            return false;
+        }
+        if !self.zkp.verify(&self.zkp_proof, &party_id.to_bytes()) {
+            warn!("zk setup proof from party {} failed to verify", party_id);
+            return false;
+        }
+        true
+    }
+
+    pub fn ek(&self) -> &EncryptionKey {
+        &self.ek
+    }
+
+    pub fn zkp(&self) -> &ZkSetup {
+        &self.zkp
+    }
+}
+
+/// Return the parties whose enrollment proofs fail to verify.
+/// Keygen with [new_keygen_enrolled] trusts that the returned list was empty.
+pub fn verify_enrollments(enrollments: &Enrollments) -> Vec<TypedUsize<KeygenPartyId>> {
+    enrollments
+        .iter()
+        .filter(|(party_id, enrollment)| !enrollment.verify(*party_id))
+        .map(|(party_id, _)| party_id)
+        .collect()
+}
+
+/// Like [new_keygen](super::new_keygen) for parties that were enrolled in advance.
+/// `enrollments` must have passed [verify_enrollments] and must be identical for all parties.
+#[allow(clippy::too_many_arguments)]
+pub fn new_keygen_enrolled(
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    my_party_id: TypedUsize<KeygenPartyId>,
+    my_subshare_id: usize, // in 0..party_share_counts[my_party_id]
+    party_keygen_data: &PartyKeygenData,
+    enrollments: Enrollments,
+    #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
+) -> TofnResult<KeygenProtocol> {
+    let my_keygen_id =
+        check_keygen_args(&party_share_counts, threshold, my_party_id, my_subshare_id)?;
+
+    if enrollments.len() != party_share_counts.party_count() {
+        error!(
+            "expected {} enrollments, got {}",
+            party_share_counts.party_count(),
+            enrollments.len()
+        );
+        return Err(TofnFatal);
+    }
+    if enrollments.get(my_party_id)?.ek != party_keygen_data.encryption_keypair.ek {
+        error!("my enrollment does not match my party keygen data");
+        return Err(TofnFatal);
+    }
+
+    #[cfg(feature = "malicious")]
+    let msg_fault = behaviour.msg_fault();
+
+    let round2 = r1::start(
+        my_keygen_id,
+        threshold,
+        party_share_counts.clone(),
+        party_keygen_data,
+        vss::Vss::new(threshold),
+        hash::Randomness::random(),
+        Some(enrollments),
+        #[cfg(feature = "malicious")]
+        behaviour,
+    )?;
+
+    let protocol = new_protocol(party_share_counts, my_keygen_id, MAX_MSG_LEN, round2)?;
+    #[cfg(feature = "malicious")]
+    let protocol = with_msg_fault(protocol, msg_fault)?;
+    Ok(protocol)
+}
@@ -0,0 +1,35 @@
+//! Keygen fast path for a single party that holds every share.
+//! Such a party would only exchange messages with itself, so it can skip the rounds
+//! and deal the shares of a fresh secret key locally, as in ceygen.
+use tracing::error;
+
+use super::{KeygenPartyShareCounts, KeygenShareId, PartyKeygenData, SecretKeyShare};
+use crate::{
+    collections::VecMap,
+    crypto_tools::ss::Ss,
+    gg20::ceygen::initialize_parties,
+    sdk::api::{TofnFatal, TofnResult},
+};
+
+/// Output of a full keygen in which `party_share_counts` has a single party,
+/// computed without running the protocol.
+pub fn keygen_single_party(
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    party_keygen_data: &PartyKeygenData,
+) -> TofnResult<VecMap<KeygenShareId, SecretKeyShare>> {
+    if party_share_counts.party_count() != 1 {
+        error!(
+            "single party keygen needs exactly 1 party, got {}",
+            party_share_counts.party_count()
+        );
+        return Err(TofnFatal);
+    }
+
+    initialize_parties(
+        &party_share_counts,
+        threshold,
+        &Ss::new(threshold),
+        &VecMap::from_vec(alloc::vec![party_keygen_data.clone()]),
+    )
+}
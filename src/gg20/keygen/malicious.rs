@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::collections::TypedUsize;
+use crate::{collections::TypedUsize, sdk::implementer_api::MsgFault};
 
 use super::KeygenShareId;
 
@@ -16,16 +16,60 @@ pub enum Behaviour {
     R1BadCommit,
     R1BadEncryptionKeyProof,
     R1BadZkSetupProof,
-    R2BadShare { victim: TypedUsize<KeygenShareId> },
-    R2BadEncryption { victim: TypedUsize<KeygenShareId> },
-    R3FalseAccusation { victim: TypedUsize<KeygenShareId> },
+    R1BadEncryptionKey,
+    R1BadZkSetup,
+    R1StaleCommit,
+    R2BadShare {
+        victim: TypedUsize<KeygenShareId>,
+    },
+    R2BadEncryption {
+        victim: TypedUsize<KeygenShareId>,
+    },
+    R3FalseAccusation {
+        victim: TypedUsize<KeygenShareId>,
+    },
     R3BadXIWitness,
+    // message-delivery faults in any round, numbered from 1
+    WithholdBcast {
+        round: usize,
+    },
+    WithholdP2p {
+        round: usize,
+        victim: TypedUsize<KeygenShareId>,
+    },
+    HalfOfPeers {
+        round: usize,
+    }, // send only to the first half of all shares
+    Replay {
+        round: usize,
+    }, // label messages with the previous round
+    FutureRound {
+        round: usize,
+    }, // label messages with the next round
+    Impersonate {
+        round: usize,
+        victim: TypedUsize<KeygenShareId>,
+    }, // send messages as `victim`
 }
 
 impl Behaviour {
     pub fn is_honest(&self) -> bool {
         matches!(self, Self::Honest)
     }
+
+    /// The message-delivery fault of this behaviour as `(round, fault)`, if any
+    pub(crate) fn msg_fault(&self) -> Option<(usize, MsgFault<KeygenShareId>)> {
+        use Behaviour::*;
+        match *self {
+            WithholdBcast { round } => Some((round, MsgFault::WithholdBcast)),
+            WithholdP2p { round, victim } => Some((round, MsgFault::WithholdP2p { victim })),
+            HalfOfPeers { round } => Some((round, MsgFault::HalfOfPeers)),
+            Replay { round } => Some((round, MsgFault::Replay)),
+            FutureRound { round } => Some((round, MsgFault::FutureRound)),
+            Impersonate { round, victim } => Some((round, MsgFault::Impersonate { victim })),
+            _ => None,
+        }
+    }
 }
 
 pub(crate) fn log_confess_info<K>(me: TypedUsize<K>, behaviour: &Behaviour, msg: &str) {
@@ -1,6 +1,15 @@
 mod api;
 pub use api::*;
 
+mod checkpoint;
+pub use checkpoint::*;
+
+mod enrollment;
+pub use enrollment::*;
+
+mod local;
+pub use local::*;
+
 mod r1;
 mod r2;
 mod r3;
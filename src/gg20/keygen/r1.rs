@@ -10,7 +10,9 @@ use crate::{
 };
 use serde::{Deserialize, Serialize};
 
-use super::{r2, KeygenPartyShareCounts, KeygenProtocolBuilder, KeygenShareId, PartyKeygenData};
+use super::{
+    r2, Enrollments, KeygenPartyShareCounts, KeygenProtocolBuilder, KeygenShareId, PartyKeygenData,
+};
 
 #[cfg(feature = "malicious")]
 use super::malicious::Behaviour;
@@ -24,24 +26,55 @@ pub(super) struct Bcast {
     pub(super) zkp_proof: paillier::zk::ZkSetupProof,
 }
 
+/// Round 1 bcast of [new_keygen_enrolled](super::new_keygen_enrolled):
+/// the Paillier key, zk setup and proofs are in the enrollments instead
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct BcastEnrolled {
+    pub(super) y_i_commit: hash::Output,
+}
+
 pub(super) fn start(
     my_keygen_id: TypedUsize<KeygenShareId>,
     threshold: usize,
     party_share_counts: KeygenPartyShareCounts,
     party_keygen_data: &PartyKeygenData,
+    u_i_vss: vss::Vss,
+    y_i_reveal: hash::Randomness,
+    enrollments: Option<Enrollments>,
     #[cfg(feature = "malicious")] behaviour: Behaviour,
 ) -> TofnResult<KeygenProtocolBuilder> {
-    let u_i_vss = vss::Vss::new(threshold);
-
-    let (y_i_commit, y_i_reveal) = hash::commit(
+    let y_i_commit = hash::commit_with_randomness(
         constants::Y_I_COMMIT_TAG,
         my_keygen_id,
         k256_serde::point_to_bytes(&(k256::ProjectivePoint::GENERATOR * u_i_vss.get_secret())),
+        &y_i_reveal,
     );
     corrupt!(
         y_i_commit,
         malicious::corrupt_commit(my_keygen_id, &behaviour, y_i_commit)
     );
+    corrupt!(
+        y_i_commit,
+        malicious::corrupt_stale_commit(my_keygen_id, &behaviour, y_i_commit)
+    );
+
+    let r2 = r2::R2 {
+        threshold,
+        party_share_counts,
+        dk: party_keygen_data.encryption_keypair.dk.clone(),
+        u_i_vss,
+        y_i_reveal,
+        #[cfg(feature = "malicious")]
+        behaviour: behaviour.clone(),
+    };
+
+    if let Some(enrollments) = enrollments {
+        return Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
+            Box::new(r2::R2Enrolled { r2, enrollments }),
+            Some(serialize(&BcastEnrolled { y_i_commit })?),
+            None,
+        )));
+    }
 
     let ek_proof = party_keygen_data.encryption_keypair_proof.clone();
     corrupt!(
@@ -55,24 +88,22 @@ pub(super) fn start(
         malicious::corrupt_zkp_proof(my_keygen_id, &behaviour, zkp_proof)
     );
 
+    let ek = party_keygen_data.encryption_keypair.ek.clone();
+    corrupt!(ek, malicious::corrupt_ek(my_keygen_id, &behaviour, ek)?);
+
+    let zkp = party_keygen_data.zk_setup.clone();
+    corrupt!(zkp, malicious::corrupt_zkp(my_keygen_id, &behaviour, zkp)?);
+
     let bcast_out = Some(serialize(&Bcast {
         y_i_commit,
-        ek: party_keygen_data.encryption_keypair.ek.clone(),
+        ek,
         ek_proof,
-        zkp: party_keygen_data.zk_setup.clone(),
+        zkp,
         zkp_proof,
     })?);
 
     Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
-        Box::new(r2::R2 {
-            threshold,
-            party_share_counts,
-            dk: party_keygen_data.encryption_keypair.dk.clone(),
-            u_i_vss,
-            y_i_reveal,
-            #[cfg(feature = "malicious")]
-            behaviour,
-        }),
+        Box::new(r2),
         bcast_out,
         None,
     )))
@@ -83,12 +114,18 @@ mod malicious {
     use crate::{
         collections::TypedUsize,
         crypto_tools::{
-            hash::Output,
-            paillier,
-            paillier::zk::{EncryptionKeyProof, ZkSetupProof},
+            constants,
+            hash::{self, Output},
+            k256_serde, paillier,
+            paillier::{
+                zk::{EncryptionKeyProof, ZkSetup, ZkSetupProof},
+                EncryptionKey,
+            },
         },
         gg20::keygen::{malicious::Behaviour, KeygenShareId},
+        sdk::api::TofnResult,
     };
+    use ecdsa::elliptic_curve::Field;
     use tracing::info;
 
     pub fn corrupt_commit(
@@ -104,6 +141,57 @@ mod malicious {
         }
     }
 
+    /// Commit to a secret other than the one revealed in r2,
+    /// as would happen if a peer replayed its commitment from a previous session.
+    pub fn corrupt_stale_commit(
+        my_keygen_id: TypedUsize<KeygenShareId>,
+        behaviour: &Behaviour,
+        commit: Output,
+    ) -> Output {
+        if let Behaviour::R1StaleCommit = behaviour {
+            info!("malicious peer {} does {:?}", my_keygen_id, behaviour);
+            let stale_y_i =
+                k256::ProjectivePoint::GENERATOR * k256::Scalar::random(rand::thread_rng());
+            hash::commit(
+                constants::Y_I_COMMIT_TAG,
+                my_keygen_id,
+                k256_serde::point_to_bytes(&stale_y_i),
+            )
+            .0
+        } else {
+            commit
+        }
+    }
+
+    /// Replace the Paillier encryption key with a fresh one built from non-safe primes.
+    /// The original proof no longer matches the modulus.
+    pub fn corrupt_ek(
+        my_keygen_id: TypedUsize<KeygenShareId>,
+        behaviour: &Behaviour,
+        ek: EncryptionKey,
+    ) -> TofnResult<EncryptionKey> {
+        if let Behaviour::R1BadEncryptionKey = behaviour {
+            info!("malicious peer {} does {:?}", my_keygen_id, behaviour);
+            Ok(paillier::keygen_unsafe(&mut rand::thread_rng())?.0)
+        } else {
+            Ok(ek)
+        }
+    }
+
+    /// Replace the ZK setup with a fresh one that the original proof does not cover.
+    pub fn corrupt_zkp(
+        my_keygen_id: TypedUsize<KeygenShareId>,
+        behaviour: &Behaviour,
+        zkp: ZkSetup,
+    ) -> TofnResult<ZkSetup> {
+        if let Behaviour::R1BadZkSetup = behaviour {
+            info!("malicious peer {} does {:?}", my_keygen_id, behaviour);
+            Ok(ZkSetup::new_unsafe(&mut rand::thread_rng(), &my_keygen_id.to_bytes())?.0)
+        } else {
+            Ok(zkp)
+        }
+    }
+
     pub fn corrupt_ek_proof(
         my_keygen_id: TypedUsize<KeygenShareId>,
         behaviour: &Behaviour,
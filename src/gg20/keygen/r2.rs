@@ -4,22 +4,32 @@ use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use crate::{
-    collections::{FillVecMap, P2ps, VecMap},
+    collections::{FillVecMap, P2ps, TypedUsize, VecMap},
     crypto_tools::{hash, paillier, vss},
     gg20::keygen::{r3, SecretKeyShare},
     sdk::{
-        api::{Fault::ProtocolFault, TofnResult},
-        implementer_api::{serialize, Executer, ProtocolBuilder, ProtocolInfo, RoundBuilder},
+        api::{
+            Fault::{self, ProtocolFault},
+            TofnResult,
+        },
+        implementer_api::{
+            encode_message, serialize, Executer, ExpectedMsgTypes, MsgType, ProtocolBuilder,
+            ProtocolInfo, RoundBuilder,
+        },
     },
 };
 
-use super::{r1, KeygenPartyShareCounts, KeygenShareId};
+use super::{r1, Enrollments, KeygenPartyShareCounts, KeygenProtocolBuilder, KeygenShareId};
 
 #[cfg(feature = "malicious")]
 use super::malicious::Behaviour;
 
-/// TODO: The byte length of this struct is proportional to the threshold: 34t + 73
-/// Instead it should be constant.
+/// The byte length of this struct is proportional to the threshold: 34t + 73.
+/// It cannot be made constant: every share needs all `t + 1` points of every `u_i_vss_commit`
+/// to validate its shares and to compute `y` and all `X_i`.
+/// Splitting the commit across p2ps or a commit-then-reveal step only moves those bytes elsewhere.
+/// Instead, `new_keygen` rejects any threshold for which this message would exceed `MAX_MSG_LEN`.
+/// This is a partial fix of issue 171: the message size stays proportional to the threshold.
 /// https://github.com/axelarnetwork/tofn/issues/171
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub(super) struct Bcast {
@@ -32,6 +42,26 @@ pub(super) struct P2p {
     pub(super) u_i_share_ciphertext: paillier::Ciphertext,
 }
 
+/// Byte length on the wire of the round 2 bcast from `from` for `threshold`
+pub(super) fn bcast_wire_len(
+    threshold: usize,
+    from: TypedUsize<KeygenShareId>,
+) -> TofnResult<usize> {
+    let (_, y_i_reveal) = hash::commit(0, from, b"");
+    let payload = serialize(&Bcast {
+        y_i_reveal,
+        u_i_vss_commit: vss::Vss::new(threshold).commit(),
+    })?;
+    Ok(encode_message(
+        payload,
+        from,
+        1, // round numbers on the wire start at 0
+        MsgType::Bcast,
+        ExpectedMsgTypes::BcastAndP2p,
+    )?
+    .len())
+}
+
 pub(super) struct R2 {
     pub(super) threshold: usize,
     pub(super) party_share_counts: KeygenPartyShareCounts,
@@ -56,29 +86,7 @@ impl Executer for R2 {
         p2ps_in: P2ps<Self::Index, Self::P2p>,
     ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
         let my_keygen_id = info.my_id();
-        let mut faulters = FillVecMap::with_size(info.total_share_count());
-
-        // anyone who did not send a bcast is a faulter
-        // TODO strictly speaking peer_keygen_id might be me so we should not use peer_?
-        for (peer_keygen_id, bcast) in bcasts_in.iter() {
-            if bcast.is_none() {
-                warn!(
-                    "peer {} says: missing bcast from peer {} in round 2",
-                    my_keygen_id, peer_keygen_id
-                );
-                faulters.set(peer_keygen_id, ProtocolFault)?;
-            }
-        }
-        // anyone who sent p2ps is a faulter
-        for (peer_keygen_id, p2ps) in p2ps_in.iter() {
-            if p2ps.is_some() {
-                warn!(
-                    "peer {} says: unexpected p2ps from peer {} in round 2",
-                    my_keygen_id, peer_keygen_id
-                );
-                faulters.set(peer_keygen_id, ProtocolFault)?;
-            }
-        }
+        let mut faulters = msgs_in_faulters(info, &bcasts_in, &p2ps_in)?;
         if !faulters.is_empty() {
             return Ok(ProtocolBuilder::Done(Err(faulters)));
         }
@@ -87,7 +95,7 @@ impl Executer for R2 {
         let bcasts_in = bcasts_in.to_vecmap()?;
 
         // check Paillier proofs
-        for (peer_keygen_id, bcast) in bcasts_in.iter() {
+        let proofs_ok = bcasts_in.par_ref_map2_result(|(peer_keygen_id, bcast)| {
             let peer_keygen_party_id = self.party_share_counts.share_to_party_id(peer_keygen_id)?;
 
             if !bcast
@@ -98,9 +106,7 @@ impl Executer for R2 {
                     "peer {} says: ek proof from peer {} failed to verify",
                     my_keygen_id, peer_keygen_id
                 );
-
-                faulters.set(peer_keygen_id, ProtocolFault)?;
-                continue;
+                return Ok(false);
             }
 
             if !bcast
@@ -111,9 +117,14 @@ impl Executer for R2 {
                     "peer {} says: zk setup proof from peer {} failed to verify",
                     my_keygen_id, peer_keygen_id,
                 );
+                return Ok(false);
+            }
 
+            Ok(true)
+        })?;
+        for (peer_keygen_id, proof_ok) in proofs_ok.iter() {
+            if !proof_ok {
                 faulters.set(peer_keygen_id, ProtocolFault)?;
-                continue;
             }
         }
 
@@ -121,6 +132,24 @@ impl Executer for R2 {
             return Ok(ProtocolBuilder::Done(Err(faulters)));
         }
 
+        (*self).share(info, bcasts_in)
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+impl R2 {
+    /// Send shares of `u_i` to all peers, given the verified round 1 bcasts
+    fn share(
+        self,
+        info: &ProtocolInfo<KeygenShareId>,
+        bcasts_in: VecMap<KeygenShareId, r1::Bcast>,
+    ) -> TofnResult<KeygenProtocolBuilder> {
+        let my_keygen_id = info.my_id();
+
         let (peer_u_i_shares, u_i_share) =
             VecMap::from_vec(self.u_i_vss.shares(info.total_share_count()))
                 .puncture_hole(my_keygen_id)?;
@@ -166,6 +195,47 @@ impl Executer for R2 {
             p2ps_out,
         )))
     }
+}
+
+/// [R2] for [new_keygen_enrolled](super::new_keygen_enrolled):
+/// take Paillier keys and zk setups from the enrollments, whose proofs were verified in advance
+pub(super) struct R2Enrolled {
+    pub(super) r2: R2,
+    pub(super) enrollments: Enrollments,
+}
+
+impl Executer for R2Enrolled {
+    type FinalOutput = SecretKeyShare;
+    type Index = KeygenShareId;
+    type Bcast = r1::BcastEnrolled;
+    type P2p = ();
+
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let faulters = msgs_in_faulters(info, &bcasts_in, &p2ps_in)?;
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        let party_share_counts = &self.r2.party_share_counts;
+        let enrollments = &self.enrollments;
+        let bcasts_in = bcasts_in.to_vecmap()?.map2_result(|(keygen_id, bcast)| {
+            let enrollment = enrollments.get(party_share_counts.share_to_party_id(keygen_id)?)?;
+            Ok(r1::Bcast {
+                y_i_commit: bcast.y_i_commit,
+                ek: enrollment.ek.clone(),
+                ek_proof: enrollment.ek_proof.clone(),
+                zkp: enrollment.zkp.clone(),
+                zkp_proof: enrollment.zkp_proof.clone(),
+            })
+        })?;
+
+        self.r2.share(info, bcasts_in)
+    }
 
     #[cfg(test)]
     fn as_any(&self) -> &dyn core::any::Any {
@@ -173,6 +243,37 @@ impl Executer for R2 {
     }
 }
 
+/// Anyone who did not send a bcast or who sent p2ps is a faulter
+fn msgs_in_faulters<B>(
+    info: &ProtocolInfo<KeygenShareId>,
+    bcasts_in: &FillVecMap<KeygenShareId, B>,
+    p2ps_in: &P2ps<KeygenShareId, ()>,
+) -> TofnResult<FillVecMap<KeygenShareId, Fault>> {
+    let my_keygen_id = info.my_id();
+    let mut faulters = FillVecMap::with_size(info.total_share_count());
+
+    // TODO strictly speaking peer_keygen_id might be me so we should not use peer_?
+    for (peer_keygen_id, bcast) in bcasts_in.iter() {
+        if bcast.is_none() {
+            warn!(
+                "peer {} says: missing bcast from peer {} in round 2",
+                my_keygen_id, peer_keygen_id
+            );
+            faulters.set(peer_keygen_id, ProtocolFault)?;
+        }
+    }
+    for (peer_keygen_id, p2ps) in p2ps_in.iter() {
+        if p2ps.is_some() {
+            warn!(
+                "peer {} says: unexpected p2ps from peer {} in round 2",
+                my_keygen_id, peer_keygen_id
+            );
+            faulters.set(peer_keygen_id, ProtocolFault)?;
+        }
+    }
+    Ok(faulters)
+}
+
 #[cfg(feature = "malicious")]
 mod malicious {
     use crate::{
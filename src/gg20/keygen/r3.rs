@@ -256,7 +256,7 @@ impl Executer for R3 {
                 r2bcasts: bcasts_in,
                 r2p2ps: p2ps_in,
                 y,
-                x_i,
+                x_i: x_i.into(),
                 all_X_i,
             }),
             bcast_out,
@@ -4,7 +4,7 @@ use tracing::warn;
 
 use crate::{
     collections::{zip2, FillVecMap, FullP2ps, P2ps, VecMap},
-    crypto_tools::{paillier, zkp::schnorr},
+    crypto_tools::{k256_serde, paillier, zkp::schnorr},
     gg20::keygen::{
         r1, r2, r3, r4::sad::R4Sad, GroupPublicInfo, KeygenPartyShareCounts, KeygenShareId,
         SecretKeyShare, SharePublicInfo, ShareSecretInfo,
@@ -24,7 +24,7 @@ pub(in super::super) struct R4Happy {
     pub(in super::super) r2bcasts: VecMap<KeygenShareId, r2::Bcast>,
     pub(in super::super) r2p2ps: FullP2ps<KeygenShareId, r2::P2p>,
     pub(in super::super) y: k256::ProjectivePoint,
-    pub(in super::super) x_i: k256::Scalar,
+    pub(in super::super) x_i: k256_serde::SecretScalar,
     pub(in super::super) all_X_i: VecMap<KeygenShareId, k256::ProjectivePoint>,
 }
 
@@ -113,7 +113,7 @@ impl Executer for R4Happy {
                 self.y.into(),
                 all_shares,
             ),
-            ShareSecretInfo::new(my_keygen_id, self.dk, self.x_i),
+            ShareSecretInfo::new(my_keygen_id, self.dk, *self.x_i.as_ref()),
         ))))
     }
 
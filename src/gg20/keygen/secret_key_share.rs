@@ -1,4 +1,4 @@
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use core::borrow::Borrow;
 
 use super::{KeygenPartyId, KeygenPartyShareCounts, KeygenShareId, PartyKeyPair};
@@ -7,14 +7,23 @@ use crate::{
     crypto_tools::{k256_serde, paillier, vss},
     sdk::{
         api::{BytesVec, TofnFatal, TofnResult},
-        implementer_api::{decode, encode},
+        implementer_api::{decode, deserialize, encode, serialize},
     },
 };
-use k256::{ecdsa::VerifyingKey, ProjectivePoint};
+use k256::{ecdsa::VerifyingKey, elliptic_curve::sec1::ToEncodedPoint, ProjectivePoint};
 use serde::{Deserialize, Serialize};
-use tracing::error;
+use tracing::{error, warn};
 use zeroize::Zeroize;
 
+/// Prefix of [SecretKeyShare::to_bytes], followed by the format version as a big-endian `u16`.
+/// Unversioned shares never start with `0xff` because it is not a valid first byte of a tofn-bincode `SecretKeyShare`.
+const SECRET_KEY_SHARE_MAGIC: &[u8] = b"\xfftks";
+
+/// Format version written by [SecretKeyShare::to_bytes].
+/// Bump it whenever the serialization of `SecretKeyShare` changes,
+/// and teach [SecretKeyShare::from_bytes_any_version] to migrate from the previous version.
+pub const SECRET_KEY_SHARE_VERSION: u16 = 1;
+
 /// final output of keygen: store this struct in tofnd kvstore
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SecretKeyShare {
@@ -53,6 +62,98 @@ pub struct ShareSecretInfo {
     x_i: k256::Scalar,
 }
 
+/// Outcome of [SecretKeyShare::validate]: each field is `true` if the corresponding check passed.
+/// Checks that depend on a failed earlier check are reported as failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShareValidationReport {
+    /// `threshold < share_count` and `party_share_counts` agree with the number of public shares
+    pub counts_consistent: bool,
+    /// my share index is a valid share id
+    pub index_in_range: bool,
+    /// `x_i * G` equals my published `X_i`
+    pub x_i_matches_public_share: bool,
+    /// my Paillier decryption key matches my published encryption key
+    pub dk_matches_ek: bool,
+    /// the published `X_i` interpolate to the group public key `y`
+    pub public_shares_match_group_key: bool,
+}
+
+impl ShareValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.counts_consistent
+            && self.index_in_range
+            && self.x_i_matches_public_share
+            && self.dk_matches_ek
+            && self.public_shares_match_group_key
+    }
+}
+
+/// Outcome of [check_group_consistency]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupConsistencyReport {
+    /// A party whose `GroupPublicInfo` is shared by the largest number of parties
+    pub reference_party: TypedUsize<KeygenPartyId>,
+    /// Parties whose `GroupPublicInfo` differs from that of `reference_party`
+    pub diverging_parties: Vec<TypedUsize<KeygenPartyId>>,
+    /// The public shares in the `GroupPublicInfo` of `reference_party` interpolate to its group public key
+    pub public_shares_match_group_key: bool,
+}
+
+impl GroupConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.diverging_parties.is_empty() && self.public_shares_match_group_key
+    }
+}
+
+/// Compare the `GroupPublicInfo` reported by each party after keygen.
+///
+/// The most common `GroupPublicInfo` is taken as reference; ties go to the party with the lowest id.
+/// Every party that reported something else is listed as diverging.
+/// Return `TofnFatal` if `reported` is empty.
+pub fn check_group_consistency(
+    reported: &VecMap<KeygenPartyId, GroupPublicInfo>,
+) -> TofnResult<GroupConsistencyReport> {
+    let (reference_party, reference) = reported
+        .iter()
+        .max_by_key(|(party_id, group)| {
+            let agreeing = reported.iter().filter(|(_, other)| other == group).count();
+            // prefer lower party ids among equally common values
+            (agreeing, core::cmp::Reverse(party_id.as_usize()))
+        })
+        .ok_or_else(|| {
+            error!("no GroupPublicInfo reported");
+            TofnFatal
+        })?;
+
+    let diverging_parties: Vec<_> = reported
+        .iter()
+        .filter(|(_, group)| *group != reference)
+        .map(|(party_id, _)| party_id)
+        .collect();
+    for party_id in diverging_parties.iter() {
+        error!(
+            "party {} reported a GroupPublicInfo that differs from party {}",
+            party_id, reference_party
+        );
+    }
+
+    let public_shares_match_group_key = reference.threshold < reference.share_count()
+        && reference.party_share_counts.total_share_count() == reference.share_count()
+        && reference.public_shares_match_group_key();
+    if !public_shares_match_group_key {
+        error!(
+            "public shares reported by party {} do not interpolate to the group public key",
+            reference_party
+        );
+    }
+
+    Ok(GroupConsistencyReport {
+        reference_party,
+        diverging_parties,
+        public_shares_match_group_key,
+    })
+}
+
 /// Subset of `SecretKeyShare` that goes on-chain.
 /// (Secret data is encrypted so it's ok to post publicly.)
 /// When combined with similar data from all parties,
@@ -75,6 +176,17 @@ impl GroupPublicInfo {
         self.threshold
     }
 
+    /// Like [GroupPublicInfo::verifying_key] but fail instead of panic
+    /// if the group key is the identity, eg. in a malformed stored share.
+    pub fn try_verifying_key(&self) -> TofnResult<VerifyingKey> {
+        k256::PublicKey::from_affine(self.y.as_ref().to_affine())
+            .map(VerifyingKey::from)
+            .map_err(|_| {
+                error!("group key is the identity");
+                TofnFatal
+            })
+    }
+
     /// Verification key corresponding to this group of signers.
     pub fn verifying_key(&self) -> VerifyingKey {
         let pp: &k256::ProjectivePoint = self.y.as_ref();
@@ -85,6 +197,39 @@ impl GroupPublicInfo {
         VerifyingKey::from(pk)
     }
 
+    /// 33-byte SEC1 compressed encoding of the group key
+    pub fn pubkey_compressed(&self) -> [u8; 33] {
+        let mut bytes = [0; 33];
+        bytes.copy_from_slice(self.verifying_key().to_encoded_point(true).as_bytes());
+        bytes
+    }
+
+    /// 65-byte SEC1 uncompressed encoding of the group key, eg. for Ethereum
+    pub fn pubkey_uncompressed(&self) -> [u8; 65] {
+        let mut bytes = [0; 65];
+        bytes.copy_from_slice(self.verifying_key().to_encoded_point(false).as_bytes());
+        bytes
+    }
+
+    /// 32-byte x-only encoding of the group key as in BIP-340, eg. for Taproot
+    pub fn pubkey_xonly(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        bytes.copy_from_slice(&self.pubkey_compressed()[1..]);
+        bytes
+    }
+
+    pub fn pubkey_compressed_hex(&self) -> String {
+        hex::encode(self.pubkey_compressed())
+    }
+
+    pub fn pubkey_uncompressed_hex(&self) -> String {
+        hex::encode(self.pubkey_uncompressed())
+    }
+
+    pub fn pubkey_xonly_hex(&self) -> String {
+        hex::encode(self.pubkey_xonly())
+    }
+
     pub fn all_shares_bytes(&self) -> TofnResult<BytesVec> {
         encode(&self.all_shares)
     }
@@ -93,6 +238,23 @@ impl GroupPublicInfo {
         &self.all_shares
     }
 
+    pub(crate) fn y(&self) -> &k256_serde::ProjectivePoint {
+        &self.y
+    }
+
+    /// Return `true` if every `threshold + 1` of the `X_i` interpolate to `y`.
+    /// Assume `threshold < share_count`.
+    pub(crate) fn public_shares_match_group_key(&self) -> bool {
+        let share_commits: Vec<_> = self
+            .all_shares
+            .iter()
+            .map(|(keygen_id, info)| {
+                vss::ShareCommit::from_point(keygen_id.as_usize(), info.X_i.clone())
+            })
+            .collect();
+        vss::share_commits_match(&share_commits, self.threshold, self.y.as_ref())
+    }
+
     pub(crate) fn new(
         party_share_counts: KeygenPartyShareCounts,
         threshold: usize,
@@ -165,6 +327,102 @@ impl SecretKeyShare {
         &self.share
     }
 
+    /// Check that this share is consistent with itself, eg. after restoring it from a backup.
+    /// Needs no Paillier operations or communication, so it is much cheaper than a sign.
+    pub fn validate(&self) -> ShareValidationReport {
+        let share_count = self.group.share_count();
+        let counts_consistent = self.group.threshold < share_count
+            && self.group.party_share_counts.total_share_count() == share_count;
+        if !counts_consistent {
+            error!(
+                "invalid (share_count, threshold, party share total): ({},{},{})",
+                share_count,
+                self.group.threshold,
+                self.group.party_share_counts.total_share_count()
+            );
+        }
+
+        let my_public_share = self.group.all_shares.get(self.share.index).ok();
+        let index_in_range = my_public_share.is_some();
+
+        let x_i_matches_public_share = my_public_share.map_or(false, |share| {
+            ProjectivePoint::GENERATOR * self.share.x_i == *share.X_i.as_ref()
+        });
+        if index_in_range && !x_i_matches_public_share {
+            error!("x_i does not match X_i of share {}", self.share.index);
+        }
+
+        let dk_matches_ek =
+            my_public_share.map_or(false, |share| self.share.dk.encryption_key() == share.ek);
+        if index_in_range && !dk_matches_ek {
+            error!("dk does not match ek of share {}", self.share.index);
+        }
+
+        let public_shares_match_group_key =
+            counts_consistent && self.group.public_shares_match_group_key();
+        if counts_consistent && !public_shares_match_group_key {
+            error!("public shares do not interpolate to the group public key");
+        }
+
+        ShareValidationReport {
+            counts_consistent,
+            index_in_range,
+            x_i_matches_public_share,
+            dk_matches_ek,
+            public_shares_match_group_key,
+        }
+    }
+
+    /// Serialize with a version tag for long-term storage.
+    /// Use [SecretKeyShare::from_bytes_any_version] to deserialize.
+    pub fn to_bytes(&self) -> TofnResult<BytesVec> {
+        let mut bytes = SECRET_KEY_SHARE_MAGIC.to_vec();
+        bytes.extend_from_slice(&SECRET_KEY_SHARE_VERSION.to_be_bytes());
+        bytes.extend_from_slice(&serialize(self)?);
+        Ok(bytes)
+    }
+
+    /// Deserialize the output of [SecretKeyShare::to_bytes] from this or any earlier version,
+    /// or an unversioned share stored by older tofn users in one of these formats:
+    /// - tofn's bincode config ([serialize])
+    /// - tofn's versioned envelope ([encode])
+    /// - bincode's default config, as written by `ceygen`
+    pub fn from_bytes_any_version(bytes: &[u8]) -> TofnResult<Self> {
+        if let Some(versioned) = bytes.strip_prefix(SECRET_KEY_SHARE_MAGIC) {
+            if versioned.len() < 2 {
+                error!("truncated secret key share version");
+                return Err(TofnFatal);
+            }
+            let (version, payload) = versioned.split_at(2);
+            let version = u16::from_be_bytes([version[0], version[1]]);
+            return match version {
+                1 => deserialize(payload).ok_or_else(|| {
+                    error!("failed to deserialize secret key share version {}", version);
+                    TofnFatal
+                }),
+                _ => {
+                    error!(
+                        "unsupported secret key share version {}, latest is {}",
+                        version, SECRET_KEY_SHARE_VERSION
+                    );
+                    Err(TofnFatal)
+                }
+            };
+        }
+
+        warn!("secret key share has no version tag, trying unversioned formats");
+        deserialize(bytes)
+            .or_else(|| decode(bytes))
+            .or_else(|| {
+                use bincode::Options;
+                bincode::DefaultOptions::new().deserialize(bytes).ok()
+            })
+            .ok_or_else(|| {
+                error!("failed to deserialize unversioned secret key share");
+                TofnFatal
+            })
+    }
+
     pub fn recovery_info(&self) -> TofnResult<BytesVec> {
         let index = self.share.index;
         let share = self.group.all_shares.get(index)?;
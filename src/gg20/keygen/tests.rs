@@ -7,7 +7,10 @@ use super::*;
 use crate::{
     collections::{zip2, HoleVecMap, TypedUsize, VecMap},
     crypto_tools::{rng, vss},
-    sdk::api::{BytesVec, Protocol},
+    sdk::{
+        api::{BytesVec, MsgType, Protocol},
+        local::{execute_honest, execute_round_with, nobody_done},
+    },
 };
 use tracing_test::traced_test;
 
@@ -161,6 +164,9 @@ fn execute_keygen_from_recovery(
         .iter()
         .map(|party| party.bcast_out().unwrap().clone())
         .collect();
+    for (from, bytes) in r2_bcasts.iter() {
+        assert_eq!(bytes.len(), r2::bcast_wire_len(threshold, from).unwrap());
+    }
     for party in r2_parties.iter_mut() {
         for (from, bytes) in r2_bcasts.iter() {
             party
@@ -358,6 +364,399 @@ fn share_recovery(
     assert_eq!(&recovered_shares, shares);
 }
 
+#[test]
+fn validate_key_shares() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2, 1]).unwrap();
+    let shares = execute_keygen(&party_share_counts, 2);
+    for (_, share) in shares.iter() {
+        assert!(share.validate().is_valid());
+    }
+
+    let share_0 = shares.get(TypedUsize::from_usize(0)).unwrap();
+    let share_1 = shares.get(TypedUsize::from_usize(1)).unwrap();
+
+    // secret material of another share
+    let report = SecretKeyShare::new(
+        share_0.group().clone(),
+        ShareSecretInfo::new(
+            share_0.share().index(),
+            share_1.share().dk().clone(),
+            *share_1.share().x_i(),
+        ),
+    )
+    .validate();
+    assert!(report.counts_consistent && report.index_in_range);
+    assert!(!report.x_i_matches_public_share && !report.dk_matches_ek);
+    assert!(report.public_shares_match_group_key);
+
+    // index out of range
+    let report = SecretKeyShare::new(
+        share_0.group().clone(),
+        ShareSecretInfo::new(
+            TypedUsize::from_usize(shares.len()),
+            share_0.share().dk().clone(),
+            *share_0.share().x_i(),
+        ),
+    )
+    .validate();
+    assert!(!report.index_in_range && !report.x_i_matches_public_share && !report.dk_matches_ek);
+
+    // group public key inconsistent with the public shares
+    let group = GroupPublicInfo::new(
+        party_share_counts,
+        2,
+        (k256::ProjectivePoint::GENERATOR * share_0.share().x_i()).into(),
+        share_0.group().all_shares().clone(),
+    );
+    let report = SecretKeyShare::new(group, share_0.share().clone()).validate();
+    assert!(report.x_i_matches_public_share && report.dk_matches_ek);
+    assert!(!report.public_shares_match_group_key);
+}
+
+#[test]
+fn group_consistency() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2, 1]).unwrap();
+    let shares = execute_keygen(&party_share_counts, 2);
+    let group = shares
+        .get(TypedUsize::from_usize(0))
+        .unwrap()
+        .group()
+        .clone();
+
+    let mut reported: VecMap<KeygenPartyId, _> = vec![group.clone(); 3].into_iter().collect();
+    assert!(check_group_consistency(&reported).unwrap().is_consistent());
+
+    let bad_group = GroupPublicInfo::new(
+        party_share_counts,
+        2,
+        group
+            .all_shares()
+            .get(TypedUsize::from_usize(0))
+            .unwrap()
+            .X_i()
+            .clone(),
+        group.all_shares().clone(),
+    );
+    *reported.get_mut(TypedUsize::from_usize(2)).unwrap() = bad_group.clone();
+    let report = check_group_consistency(&reported).unwrap();
+    assert_eq!(report.reference_party, TypedUsize::from_usize(0));
+    assert_eq!(report.diverging_parties, vec![TypedUsize::from_usize(2)]);
+    assert!(report.public_shares_match_group_key);
+
+    let reported: VecMap<KeygenPartyId, _> = vec![bad_group; 3].into_iter().collect();
+    let report = check_group_consistency(&reported).unwrap();
+    assert!(report.diverging_parties.is_empty());
+    assert!(!report.public_shares_match_group_key);
+
+    assert!(check_group_consistency(&VecMap::from_vec(Vec::new())).is_err());
+}
+
+#[test]
+fn pubkey_encodings() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1]).unwrap();
+    let shares = execute_keygen(&party_share_counts, 1);
+    let group = shares.get(TypedUsize::from_usize(0)).unwrap().group();
+
+    let compressed = group.pubkey_compressed();
+    let uncompressed = group.pubkey_uncompressed();
+    let xonly = group.pubkey_xonly();
+    assert_eq!(compressed[..], group.verifying_key().to_bytes()[..]);
+    assert!(compressed[0] == 0x02 || compressed[0] == 0x03);
+    assert_eq!(uncompressed[0], 0x04);
+    assert_eq!(compressed[1..], xonly[..]);
+    assert_eq!(uncompressed[1..33], xonly[..]);
+    assert_eq!(
+        k256::ecdsa::VerifyingKey::from_sec1_bytes(&uncompressed).unwrap(),
+        group.verifying_key()
+    );
+
+    assert_eq!(group.pubkey_compressed_hex(), hex::encode(compressed));
+    assert_eq!(group.pubkey_uncompressed_hex(), hex::encode(uncompressed));
+    assert_eq!(group.pubkey_xonly_hex(), hex::encode(xonly));
+}
+
+#[test]
+fn secret_key_share_versions() {
+    use crate::sdk::implementer_api::{encode, serialize};
+    use bincode::Options;
+
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1]).unwrap();
+    let shares = execute_keygen(&party_share_counts, 1);
+    let share = shares.get(TypedUsize::from_usize(0)).unwrap();
+
+    let versioned = share.to_bytes().unwrap();
+    assert_eq!(
+        &SecretKeyShare::from_bytes_any_version(&versioned).unwrap(),
+        share
+    );
+
+    // unversioned legacy formats
+    for unversioned in [
+        serialize(share).unwrap(),
+        encode(share).unwrap(),
+        bincode::DefaultOptions::new().serialize(share).unwrap(),
+    ] {
+        assert_eq!(
+            &SecretKeyShare::from_bytes_any_version(&unversioned).unwrap(),
+            share
+        );
+    }
+
+    // unknown version
+    let mut future = versioned.clone();
+    future[5] += 1;
+    assert!(SecretKeyShare::from_bytes_any_version(&future).is_err());
+
+    assert!(SecretKeyShare::from_bytes_any_version(&versioned[..5]).is_err());
+}
+
+#[test]
+fn seal_party_keygen_data() {
+    use crate::sdk::implementer_api::serialize;
+
+    let party_id = TypedUsize::from_usize(1);
+    let sealing_key = [7; 32];
+    let keygen_data =
+        create_party_keypair_and_zksetup_unsafe(party_id, &dummy_secret_recovery_key(1), b"foobar")
+            .unwrap();
+
+    let sealed = keygen_data.seal(party_id, &sealing_key).unwrap();
+    let unsealed = PartyKeygenData::unseal(&sealed, party_id, &sealing_key).unwrap();
+    assert_eq!(
+        serialize(&unsealed).unwrap(),
+        serialize(&keygen_data).unwrap()
+    );
+
+    assert!(PartyKeygenData::unseal(&sealed, party_id, &[8; 32]).is_err());
+    assert!(PartyKeygenData::unseal(&sealed, TypedUsize::from_usize(0), &sealing_key).is_err());
+
+    let mut tampered = sealed.clone();
+    let middle = tampered.len() / 2;
+    tampered[middle] ^= 1;
+    assert!(PartyKeygenData::unseal(&tampered, party_id, &sealing_key).is_err());
+    assert!(PartyKeygenData::unseal(&sealed[..sealed.len() - 1], party_id, &sealing_key).is_err());
+}
+
+#[test]
+fn resume_from_checkpoint() {
+    use crate::sdk::implementer_api::{deserialize, serialize};
+
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1, 1]).unwrap();
+    let threshold = 1;
+    let keygen_data: Vec<_> = (0..party_share_counts.party_count())
+        .map(|i| {
+            create_party_keypair_and_zksetup_unsafe(
+                TypedUsize::from_usize(i),
+                &dummy_secret_recovery_key(i),
+                b"foobar",
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let (party_0, mut checkpoint) = new_keygen_with_checkpoint(
+        party_share_counts.clone(),
+        threshold,
+        TypedUsize::from_usize(0),
+        0,
+        &keygen_data[0],
+        #[cfg(feature = "malicious")]
+        Honest,
+    )
+    .unwrap();
+    let mut parties = vec![party_0];
+    for (i, data) in keygen_data.iter().enumerate().skip(1) {
+        parties.push(
+            new_keygen(
+                party_share_counts.clone(),
+                threshold,
+                TypedUsize::from_usize(i),
+                0,
+                data,
+                #[cfg(feature = "malicious")]
+                Honest,
+            )
+            .unwrap(),
+        );
+    }
+
+    let mut parties: VecMap<KeygenShareId, _> = VecMap::from_vec(parties);
+    let mut round_num = 0;
+    while nobody_done(&parties) {
+        // party 0 records its outgoing messages as sent before sending them
+        checkpoint.record_msgs_out_sent();
+
+        // party 0 crashes after sending its round 2 messages,
+        // so its peers receive the messages it sent before the crash
+        let mut sent_before_crash = None;
+        if round_num == 2 {
+            let party_0 = parties.get_mut(TypedUsize::from_usize(0)).unwrap();
+            if let Protocol::NotDone(round) = party_0 {
+                sent_before_crash = Some((round.bcast_out().cloned(), round.p2ps_out().cloned()));
+            }
+            let persisted: KeygenCheckpoint =
+                deserialize(&serialize(&checkpoint).unwrap()).unwrap();
+            let resumed = resume_keygen(
+                &persisted,
+                &keygen_data[0],
+                #[cfg(feature = "malicious")]
+                Honest,
+            )
+            .unwrap();
+            assert!(!resumed.send_msgs_out);
+            match resumed.protocol {
+                Protocol::NotDone(ref round) => assert_eq!(round.info().round(), round_num),
+                Protocol::Done(_) => panic!("resumed keygen is done"),
+            }
+            *party_0 = resumed.protocol;
+            checkpoint = persisted;
+        }
+
+        // party 0 records every message it receives
+        parties = execute_round_with(parties, |from, msg_type, bytes| {
+            let bytes = match (&sent_before_crash, msg_type) {
+                (Some((Some(bcast), _)), MsgType::Bcast) if from.as_usize() == 0 => bcast.clone(),
+                (Some((_, Some(p2ps))), MsgType::P2p { to }) if from.as_usize() == 0 => {
+                    p2ps.get(to).unwrap().clone()
+                }
+                _ => bytes,
+            };
+            checkpoint.record_msg_in(party_share_counts.share_to_party_id(from).unwrap(), &bytes);
+            vec![bytes]
+        })
+        .unwrap();
+        round_num += 1;
+    }
+
+    let shares: Vec<SecretKeyShare> = parties
+        .into_iter()
+        .map(|(_, party)| match party {
+            Protocol::Done(Ok(share)) => share,
+            _ => panic!("keygen failed"),
+        })
+        .collect();
+    assert!(shares[0].validate().is_valid());
+    for share in shares.iter() {
+        assert_eq!(share.group(), shares[0].group());
+    }
+}
+
+#[test]
+fn enrolled_keygen() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2, 1]).unwrap();
+    let threshold = 2;
+    let keygen_data: Vec<_> = (0..party_share_counts.party_count())
+        .map(|i| {
+            create_party_keypair_and_zksetup_unsafe(
+                TypedUsize::from_usize(i),
+                &dummy_secret_recovery_key(i),
+                b"foobar",
+            )
+            .unwrap()
+        })
+        .collect();
+    let enrollments: Enrollments = keygen_data.iter().map(|data| data.enrollment()).collect();
+    assert!(verify_enrollments(&enrollments).is_empty());
+
+    let mut swapped = enrollments.clone();
+    *swapped.get_mut(TypedUsize::from_usize(0)).unwrap() =
+        enrollments.get(TypedUsize::from_usize(1)).unwrap().clone();
+    assert_eq!(
+        verify_enrollments(&swapped),
+        vec![TypedUsize::from_usize(0)]
+    );
+
+    let parties: Vec<_> = party_share_counts
+        .iter()
+        .flat_map(|(party_id, &party_share_count)| {
+            let party_share_counts = &party_share_counts;
+            let keygen_data = &keygen_data;
+            let enrollments = &enrollments;
+            (0..party_share_count).map(move |subshare_id| {
+                new_keygen_enrolled(
+                    party_share_counts.clone(),
+                    threshold,
+                    party_id,
+                    subshare_id,
+                    &keygen_data[party_id.as_usize()],
+                    enrollments.clone(),
+                    #[cfg(feature = "malicious")]
+                    Honest,
+                )
+                .unwrap()
+            })
+        })
+        .collect();
+
+    // the round 1 bcast no longer carries Paillier keys and zk setups
+    let bcast_len = |party: &KeygenProtocol| match party {
+        Protocol::NotDone(round) => round.bcast_out().unwrap().len(),
+        Protocol::Done(_) => panic!("keygen done in round 1"),
+    };
+    let unenrolled = new_keygen(
+        party_share_counts.clone(),
+        threshold,
+        TypedUsize::from_usize(0),
+        0,
+        &keygen_data[0],
+        #[cfg(feature = "malicious")]
+        Honest,
+    )
+    .unwrap();
+    assert!(bcast_len(&parties[0]) * 10 < bcast_len(&unenrolled));
+
+    let shares = execute_honest(VecMap::from_vec(parties))
+        .unwrap()
+        .into_vec();
+    for share in shares.iter() {
+        assert!(share.validate().is_valid());
+        assert_eq!(share.group(), shares[0].group());
+    }
+
+    // a party whose enrollment does not match its keygen data
+    assert!(new_keygen_enrolled(
+        party_share_counts,
+        threshold,
+        TypedUsize::from_usize(0),
+        0,
+        &keygen_data[0],
+        swapped,
+        #[cfg(feature = "malicious")]
+        Honest,
+    )
+    .is_err());
+}
+
+#[test]
+fn threshold_bounded_by_msg_len() {
+    let share_count = 300;
+    let last_share_id = TypedUsize::from_usize(share_count - 1);
+    let max_threshold = (0..share_count)
+        .take_while(|&t| r2::bcast_wire_len(t, last_share_id).unwrap() <= MAX_MSG_LEN)
+        .last()
+        .unwrap();
+    assert!(max_threshold >= 100 && max_threshold + 1 < share_count);
+
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![share_count]).unwrap();
+    let party_id = TypedUsize::from_usize(0);
+    let party_keygen_data =
+        create_party_keypair_and_zksetup_unsafe(party_id, &dummy_secret_recovery_key(0), b"foobar")
+            .unwrap();
+    let new_keygen = |threshold| {
+        new_keygen(
+            party_share_counts.clone(),
+            threshold,
+            party_id,
+            0,
+            &party_keygen_data,
+            #[cfg(feature = "malicious")]
+            Honest,
+        )
+    };
+    assert!(new_keygen(max_threshold).is_ok());
+    assert!(new_keygen(max_threshold + 1).is_err());
+}
+
 /// return the all-zero array with the first bytes set to the bytes of `index`
 pub fn dummy_secret_recovery_key(index: usize) -> rng::SecretRecoveryKey {
     let index_bytes = index.to_be_bytes();
@@ -10,6 +10,12 @@ macro_rules! corrupt {
     };
 }
 
+pub mod add_party;
 pub mod ceygen;
+pub mod decrypt;
+pub mod import;
 pub mod keygen;
+pub mod quorum_change;
+pub mod remove_party;
 pub mod sign;
+pub mod verify_evidence;
@@ -0,0 +1,387 @@
+use alloc::vec::Vec;
+
+use k256::ProjectivePoint;
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use super::r1;
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    crypto_tools::{paillier, zkp::schnorr},
+    gg20::keygen::{
+        Enrollment, GroupPublicInfo, KeygenPartyId, KeygenShareId, PartyKeygenData, SecretKeyShare,
+        ShareSecretInfo,
+    },
+    sdk::{
+        api::{PartyShareCounts, Protocol, TofnFatal, TofnResult},
+        implementer_api::{new_protocol, serialize, ProtocolBuilder},
+    },
+};
+
+/// Maximum byte length of messages exchanged during a quorum change.
+/// The largest message is r1::Bcast from a dealer,
+/// with a vss commit and a Paillier ciphertext for each share of the new group.
+/// [new_quorum_change] rejects new groups of more than [MAX_SHARE_COUNT] shares.
+pub const MAX_MSG_LEN: usize = 100_000;
+
+/// Largest share count of the new group for which r1::Bcast fits in [MAX_MSG_LEN]
+pub const MAX_SHARE_COUNT: usize = 128;
+
+/// Number of rounds executed by an honest quorum change
+pub const ROUND_COUNT: usize = 2;
+
+pub type QuorumChangeProtocol =
+    Protocol<QuorumChangeOutput, QuorumChangeShareId, QuorumChangePartyId>;
+pub type QuorumChangeProtocolBuilder = ProtocolBuilder<QuorumChangeOutput, QuorumChangeShareId>;
+
+// Old keygen share ids of the dealers, ie. all shares of the kept parties.
+// The share id of a participant in this protocol is its keygen share id in the new group.
+pub type DealerKeygenIds = VecMap<QuorumChangeShareId, TypedUsize<KeygenShareId>>;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct QuorumChangeShareId;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct QuorumChangePartyId;
+
+/// Parties to keep, parties to add and the new threshold of a group.
+/// All participants must agree on the same [QuorumChange] beforehand.
+///
+/// In the new group, the kept parties come first, in order and with all their shares,
+/// followed by one party with one share for each enrollment in `new_parties`.
+/// Parties not in `kept_parties` are removed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuorumChange {
+    epoch: u64,
+    kept_parties: Subset<KeygenPartyId>,
+    new_parties: Vec<Enrollment>,
+    new_threshold: usize,
+}
+
+impl QuorumChange {
+    /// `epoch` identifies the new group, eg. one more than the epoch of the old group.
+    /// Each enrollment in `new_parties` must be created with its [new_party_id].
+    pub fn new(
+        epoch: u64,
+        kept_parties: Subset<KeygenPartyId>,
+        new_parties: Vec<Enrollment>,
+        new_threshold: usize,
+    ) -> Self {
+        Self {
+            epoch,
+            kept_parties,
+            new_parties,
+            new_threshold,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn kept_parties(&self) -> &Subset<KeygenPartyId> {
+        &self.kept_parties
+    }
+
+    pub fn new_parties(&self) -> &[Enrollment] {
+        &self.new_parties
+    }
+
+    pub fn new_threshold(&self) -> usize {
+        self.new_threshold
+    }
+}
+
+/// Keygen party id in the new group of the `index`th new party.
+/// The new party must create its [PartyKeygenData] with this party id.
+pub fn new_party_id(
+    kept_parties: &Subset<KeygenPartyId>,
+    index: usize,
+) -> TypedUsize<KeygenPartyId> {
+    TypedUsize::from_usize(kept_parties.member_count() + index)
+}
+
+/// Output of a quorum change for each share of the new group
+#[derive(Debug, Clone)]
+pub struct QuorumChangeOutput {
+    /// My key share in the new group
+    pub secret_key_share: SecretKeyShare,
+    /// The same for all shares of the new group
+    pub transition: EpochTransition,
+}
+
+/// Record of a completed quorum change, signed by every share of the new group.
+///
+/// A quorum change either completes for all participants or for none of them.
+/// Until a participant holds a verified [EpochTransition], the old group remains in effect,
+/// so a failed run leaves no party in a half-migrated state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochTransition {
+    epoch: u64,
+    kept_parties: Subset<KeygenPartyId>,
+    group: GroupPublicInfo,
+    signatures: VecMap<KeygenShareId, schnorr::Proof>,
+}
+
+impl EpochTransition {
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn kept_parties(&self) -> &Subset<KeygenPartyId> {
+        &self.kept_parties
+    }
+
+    pub fn group(&self) -> &GroupPublicInfo {
+        &self.group
+    }
+
+    /// Check that this transition derives the new group from `old_group`:
+    /// the new group has the same key, the kept shares keep their Paillier keys,
+    /// its public shares interpolate to the group key
+    /// and every share of the new group signed the transition.
+    pub fn verify(&self, old_group: &GroupPublicInfo) -> bool {
+        let dealer_keygen_ids = match old_group
+            .party_share_counts()
+            .share_id_subset::<KeygenShareId>(&self.kept_parties)
+        {
+            Ok(ids) => ids,
+            Err(_) => return false,
+        };
+        let new_party_count = match self
+            .group
+            .party_share_counts()
+            .party_count()
+            .checked_sub(self.kept_parties.member_count())
+        {
+            Some(count) => count,
+            None => return false,
+        };
+        let expected_counts =
+            match new_party_share_counts(old_group, &self.kept_parties, new_party_count) {
+                Ok(counts) => counts,
+                Err(_) => return false,
+            };
+
+        if *self.group.party_share_counts() != expected_counts
+            || self.group.y() != old_group.y()
+            || self.group.threshold() >= self.group.share_count()
+        {
+            warn!("epoch transition: new group does not match the old group");
+            return false;
+        }
+
+        for (new_keygen_id, old_keygen_id) in dealer_keygen_ids.iter().enumerate() {
+            let (new_share, old_share) = match (
+                self.group
+                    .all_shares()
+                    .get(TypedUsize::from_usize(new_keygen_id)),
+                old_group.all_shares().get(*old_keygen_id),
+            ) {
+                (Ok(new_share), Ok(old_share)) => (new_share, old_share),
+                _ => return false,
+            };
+            if new_share.ek() != old_share.ek() || new_share.zkp() != old_share.zkp() {
+                warn!(
+                    "epoch transition: share {} has different Paillier keys",
+                    new_keygen_id
+                );
+                return false;
+            }
+        }
+
+        if !self.group.public_shares_match_group_key() {
+            warn!("epoch transition: public shares do not match the group key");
+            return false;
+        }
+
+        let msg = match signed_bytes(self.epoch, old_group, &self.kept_parties, &self.group) {
+            Ok(msg) => msg,
+            Err(_) => return false,
+        };
+        self.signatures.len() == self.group.share_count()
+            && self.signatures.iter().all(|(keygen_id, signature)| {
+                self.group
+                    .all_shares()
+                    .get(keygen_id)
+                    .map_or(false, |share| {
+                        schnorr::verify_signature(
+                            &schnorr::Statement {
+                                prover_id: keygen_id,
+                                base: &ProjectivePoint::GENERATOR,
+                                target: share.X_i().as_ref(),
+                            },
+                            signature,
+                            &msg,
+                        )
+                    })
+            })
+    }
+
+    pub(super) fn new(
+        epoch: u64,
+        kept_parties: Subset<KeygenPartyId>,
+        group: GroupPublicInfo,
+        signatures: VecMap<KeygenShareId, schnorr::Proof>,
+    ) -> Self {
+        Self {
+            epoch,
+            kept_parties,
+            group,
+            signatures,
+        }
+    }
+}
+
+/// Initialize a quorum change protocol for a share of a kept party.
+/// Every share of every kept party must participate.
+/// Assume `group`, `share` are valid and check `change` against it.
+pub fn new_quorum_change(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    change: &QuorumChange,
+) -> TofnResult<QuorumChangeProtocol> {
+    let (dealer_keygen_ids, party_share_counts) = check_change(group, change)?;
+
+    // find my quorum-change share_id
+    let my_quorum_change_id = dealer_keygen_ids
+        .iter()
+        .find(|(_, &k)| k == share.index())
+        .map(|(s, _)| s)
+        .ok_or_else(|| {
+            error!(
+                "my keygen share_id {} does not belong to a kept party",
+                share.index()
+            );
+            TofnFatal
+        })?;
+
+    let round2 = r1::start_dealer(
+        my_quorum_change_id,
+        SecretKeyShare::new(group.clone(), share.clone()),
+        change.clone(),
+        dealer_keygen_ids,
+    )?;
+
+    new_protocol(party_share_counts, my_quorum_change_id, MAX_MSG_LEN, round2)
+}
+
+/// Initialize a quorum change protocol for a new party.
+/// `group` must be the public info of the old group and
+/// `party_keygen_data` must be enrolled in `change`.
+pub fn new_quorum_change_joiner(
+    group: &GroupPublicInfo,
+    change: &QuorumChange,
+    party_keygen_data: &PartyKeygenData,
+) -> TofnResult<QuorumChangeProtocol> {
+    let (dealer_keygen_ids, party_share_counts) = check_change(group, change)?;
+
+    let index = change
+        .new_parties
+        .iter()
+        .position(|enrollment| *enrollment.ek() == party_keygen_data.encryption_keypair.ek)
+        .ok_or_else(|| {
+            error!("my enrollment is not in the quorum change");
+            TofnFatal
+        })?;
+    let my_quorum_change_id = TypedUsize::from_usize(dealer_keygen_ids.len() + index);
+
+    let round2 = r1::start_new_party(
+        group.clone(),
+        change.clone(),
+        party_keygen_data.encryption_keypair.dk.clone(),
+        dealer_keygen_ids,
+    )?;
+
+    new_protocol(party_share_counts, my_quorum_change_id, MAX_MSG_LEN, round2)
+}
+
+/// Paillier keys of share `quorum_change_id` in the new group
+pub(super) fn new_share_keys<'a>(
+    group: &'a GroupPublicInfo,
+    change: &'a QuorumChange,
+    dealer_keygen_ids: &DealerKeygenIds,
+    quorum_change_id: TypedUsize<QuorumChangeShareId>,
+) -> TofnResult<(&'a paillier::EncryptionKey, &'a paillier::zk::ZkSetup)> {
+    match quorum_change_id
+        .as_usize()
+        .checked_sub(dealer_keygen_ids.len())
+    {
+        None => {
+            let share = group
+                .all_shares()
+                .get(*dealer_keygen_ids.get(quorum_change_id)?)?;
+            Ok((share.ek(), share.zkp()))
+        }
+        Some(index) => {
+            let enrollment = change.new_parties.get(index).ok_or_else(|| {
+                error!("share {} out of bounds", quorum_change_id);
+                TofnFatal
+            })?;
+            Ok((enrollment.ek(), enrollment.zkp()))
+        }
+    }
+}
+
+/// Bytes signed by every share of the new group
+pub(super) fn signed_bytes(
+    epoch: u64,
+    old_group: &GroupPublicInfo,
+    kept_parties: &Subset<KeygenPartyId>,
+    group: &GroupPublicInfo,
+) -> TofnResult<Vec<u8>> {
+    serialize(&(epoch, old_group, kept_parties, group))
+}
+
+/// Share counts of the kept parties, followed by one share for each new party
+pub(super) fn new_party_share_counts<P>(
+    group: &GroupPublicInfo,
+    kept_parties: &Subset<KeygenPartyId>,
+    new_party_count: usize,
+) -> TofnResult<PartyShareCounts<P>> {
+    let mut share_counts = group.party_share_counts().subset(kept_parties)?;
+    share_counts.extend(core::iter::repeat(1).take(new_party_count));
+    PartyShareCounts::from_vec(share_counts)
+}
+
+/// Return the old keygen share ids of the dealers
+/// and the party share counts of the new group.
+fn check_change(
+    group: &GroupPublicInfo,
+    change: &QuorumChange,
+) -> TofnResult<(DealerKeygenIds, PartyShareCounts<QuorumChangePartyId>)> {
+    let dealer_keygen_ids = group
+        .party_share_counts()
+        .share_id_subset(&change.kept_parties)?;
+
+    // dealer share count must be at least threshold + 1
+    if dealer_keygen_ids.len() <= group.threshold() {
+        error!(
+            "not enough kept shares: threshold [{}], kept [{}]",
+            group.threshold(),
+            dealer_keygen_ids.len(),
+        );
+        return Err(TofnFatal);
+    }
+
+    let share_count = dealer_keygen_ids.len() + change.new_parties.len();
+    if change.new_threshold >= share_count || share_count > MAX_SHARE_COUNT {
+        error!(
+            "invalid new share count: threshold [{}], shares [{}], max [{}]",
+            change.new_threshold, share_count, MAX_SHARE_COUNT,
+        );
+        return Err(TofnFatal);
+    }
+
+    for (index, enrollment) in change.new_parties.iter().enumerate() {
+        if !enrollment.verify(new_party_id(&change.kept_parties, index)) {
+            error!("enrollment of new party {} failed to verify", index);
+            return Err(TofnFatal);
+        }
+    }
+
+    Ok((
+        VecMap::from_vec(dealer_keygen_ids),
+        new_party_share_counts(group, &change.kept_parties, change.new_parties.len())?,
+    ))
+}
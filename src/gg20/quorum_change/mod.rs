@@ -0,0 +1,22 @@
+//! Change the membership and threshold of a gg20 group in one atomic protocol run, keeping the group key.
+//!
+//! A [QuorumChange] lists the old parties to keep, the new parties to add and the new threshold.
+//! All shares of the kept parties deal the secret key to the new group and every share of the new group
+//! signs the resulting [EpochTransition]. Either every participant outputs the same signed transition or the run fails
+//! and the old group remains in effect.
+//!
+//! * Round 1: each kept share `i` deals a vss of `lambda_i * x_i` with the new threshold,
+//!   where `lambda_i` is its Lagrange coefficient among the kept shares,
+//!   and broadcasts the vss commit and each share encrypted to its recipient in the new group.
+//! * Round 2: each share of the new group checks each vss against `lambda_i * X_i` and its shares against the commits,
+//!   sets its new share to their sum and signs the transition to the new group, or broadcasts complaints about bad shares.
+//! * Round 3: every participant outputs its key share in the new group and the [EpochTransition], or the faulters.
+mod api;
+pub use api::*;
+
+mod r1;
+mod r2;
+mod r3;
+
+#[cfg(test)]
+mod tests;
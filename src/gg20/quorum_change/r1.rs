@@ -0,0 +1,122 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::{
+    api::new_share_keys, r2, DealerKeygenIds, QuorumChange, QuorumChangeProtocolBuilder,
+    QuorumChangeShareId,
+};
+use crate::{
+    collections::TypedUsize,
+    crypto_tools::{paillier, vss},
+    gg20::keygen::{GroupPublicInfo, SecretKeyShare},
+    sdk::{
+        api::{TofnFatal, TofnResult},
+        implementer_api::{serialize, RoundBuilder},
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) enum Bcast {
+    /// From dealer `i`: a vss of `lambda_i * x_i` and its share for each share `j` of the new group,
+    /// encrypted to `j`
+    Dealer {
+        vss_commit: vss::Commit,
+        share_ciphertexts: Vec<paillier::Ciphertext>,
+    },
+    NewParty,
+}
+
+/// Dealer `i` reshares its additive share `lambda_i * x_i` of the secret key among the new group
+pub(super) fn start_dealer(
+    my_quorum_change_id: TypedUsize<QuorumChangeShareId>,
+    secret_key_share: SecretKeyShare,
+    change: QuorumChange,
+    dealer_keygen_ids: DealerKeygenIds,
+) -> TofnResult<QuorumChangeProtocolBuilder> {
+    let group = secret_key_share.group();
+    let lambda_i = lagrange_coefficient(my_quorum_change_id, &dealer_keygen_ids)?;
+    let vss = vss::Vss::new_with_secret(
+        change.new_threshold(),
+        lambda_i * secret_key_share.share().x_i(),
+    );
+
+    let share_count = dealer_keygen_ids.len() + change.new_parties().len();
+    let share_ciphertexts = vss
+        .shares(share_count)
+        .iter()
+        .enumerate()
+        .map(|(j, share)| {
+            let (ek, _) = new_share_keys(
+                group,
+                &change,
+                &dealer_keygen_ids,
+                TypedUsize::from_usize(j),
+            )?;
+            Ok(ek.encrypt(&share.get_scalar().into()).0)
+        })
+        .collect::<TofnResult<Vec<_>>>()?;
+
+    let bcast_out = Some(serialize(&Bcast::Dealer {
+        vss_commit: vss.commit(),
+        share_ciphertexts,
+    })?);
+
+    Ok(QuorumChangeProtocolBuilder::NotDone(RoundBuilder::new(
+        Box::new(r2::R2 {
+            group: group.clone(),
+            change,
+            dk: secret_key_share.share().dk().clone(),
+            dealer_keygen_ids,
+        }),
+        bcast_out,
+        None,
+    )))
+}
+
+/// A new party has nothing to deal
+pub(super) fn start_new_party(
+    group: GroupPublicInfo,
+    change: QuorumChange,
+    dk: paillier::DecryptionKey,
+    dealer_keygen_ids: DealerKeygenIds,
+) -> TofnResult<QuorumChangeProtocolBuilder> {
+    Ok(QuorumChangeProtocolBuilder::NotDone(RoundBuilder::new(
+        Box::new(r2::R2 {
+            group,
+            change,
+            dk,
+            dealer_keygen_ids,
+        }),
+        Some(serialize(&Bcast::NewParty)?),
+        None,
+    )))
+}
+
+/// Lagrange coefficient of dealer `quorum_change_id` among all dealers
+pub(super) fn lagrange_coefficient(
+    quorum_change_id: TypedUsize<QuorumChangeShareId>,
+    dealer_keygen_ids: &DealerKeygenIds,
+) -> TofnResult<k256::Scalar> {
+    let indices: Vec<usize> = dealer_keygen_ids
+        .iter()
+        .map(|(_, keygen_id)| keygen_id.as_usize())
+        .collect();
+    vss::lagrange_coefficient(quorum_change_id.as_usize(), &indices)
+}
+
+impl Bcast {
+    pub(super) fn dealer(&self) -> TofnResult<(&vss::Commit, &[paillier::Ciphertext])> {
+        match self {
+            Bcast::Dealer {
+                vss_commit,
+                share_ciphertexts,
+            } => Ok((vss_commit, share_ciphertexts)),
+            Bcast::NewParty => {
+                error!("expected a vss from a dealer");
+                Err(TofnFatal)
+            }
+        }
+    }
+}
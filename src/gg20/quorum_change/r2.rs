@@ -0,0 +1,265 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use k256::{ProjectivePoint, Scalar};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{
+    api::{new_party_share_counts, new_share_keys, signed_bytes},
+    r1::{self, lagrange_coefficient},
+    r3, DealerKeygenIds, QuorumChange, QuorumChangeOutput, QuorumChangeShareId,
+};
+use crate::{
+    collections::{FillVecMap, P2ps, TypedUsize, VecMap},
+    crypto_tools::{k256_serde, paillier, vss, zkp::schnorr},
+    gg20::keygen::{GroupPublicInfo, SharePublicInfo, ShareSecretInfo},
+    sdk::{
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
+        implementer_api::{
+            log_accuse_warn, log_fault_warn, serialize, Executer, ProtocolBuilder, ProtocolInfo,
+            RoundBuilder,
+        },
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum Bcast {
+    /// My signature on the epoch transition with my new share
+    Happy {
+        signature: schnorr::Proof,
+    },
+    Sad {
+        complaints: Vec<Complaint>,
+    },
+}
+
+/// Reveal the decryption of a share that does not match the vss commit of `accused`
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct Complaint {
+    pub(super) accused: TypedUsize<QuorumChangeShareId>,
+    pub(super) share: k256_serde::SecretScalar,
+    pub(super) randomness: paillier::Randomness,
+}
+
+pub(super) struct R2 {
+    pub(super) group: GroupPublicInfo,
+    pub(super) change: QuorumChange,
+    pub(super) dk: paillier::DecryptionKey,
+    pub(super) dealer_keygen_ids: DealerKeygenIds,
+}
+
+impl Executer for R2 {
+    type FinalOutput = QuorumChangeOutput;
+    type Index = QuorumChangeShareId;
+    type Bcast = r1::Bcast;
+    type P2p = ();
+
+    #[allow(non_snake_case)]
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        _p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_quorum_change_id = info.my_id();
+        let dealer_count = self.dealer_keygen_ids.len();
+        let mut faulters = info.new_fillvecmap();
+
+        // `Executer` has already flagged missing or corrupted messages,
+        // so every bcast is present
+        let bcasts_in = bcasts_in.to_vecmap()?;
+
+        for (peer_quorum_change_id, bcast) in bcasts_in.iter() {
+            let valid = match bcast {
+                r1::Bcast::Dealer {
+                    vss_commit,
+                    share_ciphertexts,
+                } if peer_quorum_change_id.as_usize() < dealer_count => self.validate_dealer(
+                    my_quorum_change_id,
+                    peer_quorum_change_id,
+                    vss_commit,
+                    share_ciphertexts,
+                )?,
+                r1::Bcast::NewParty if peer_quorum_change_id.as_usize() >= dealer_count => true,
+                _ => {
+                    log_fault_warn(
+                        my_quorum_change_id,
+                        peer_quorum_change_id,
+                        "unexpected bcast",
+                    );
+                    false
+                }
+            };
+            if !valid {
+                faulters.set(peer_quorum_change_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // decrypt and validate my shares
+        let mut complaints = Vec::new();
+        let mut x_i = Scalar::zero();
+        for (peer_quorum_change_id, bcast) in bcasts_in.iter().take(dealer_count) {
+            let (vss_commit, share_ciphertexts) = bcast.dealer()?;
+            let (plaintext, randomness) = self
+                .dk
+                .decrypt_with_randomness(&share_ciphertexts[my_quorum_change_id.as_usize()]);
+            let share =
+                vss::Share::from_scalar(plaintext.to_scalar(), my_quorum_change_id.as_usize());
+
+            if !vss_commit.validate_share(&share) {
+                log_accuse_warn(
+                    my_quorum_change_id,
+                    peer_quorum_change_id,
+                    "invalid vss share",
+                );
+                complaints.push(Complaint {
+                    accused: peer_quorum_change_id,
+                    share: (*share.get_scalar()).into(),
+                    randomness,
+                });
+                continue;
+            }
+            x_i += share.get_scalar();
+        }
+
+        // the new group is determined by the dealer bcasts
+        let group = self.new_group(&bcasts_in)?;
+        let msg = signed_bytes(
+            self.change.epoch(),
+            &self.group,
+            self.change.kept_parties(),
+            &group,
+        )?;
+
+        let (bcast_out, share) = if complaints.is_empty() {
+            let new_keygen_id = TypedUsize::from_usize(my_quorum_change_id.as_usize());
+            let signature = schnorr::sign(
+                &schnorr::Statement {
+                    prover_id: new_keygen_id,
+                    base: &ProjectivePoint::GENERATOR,
+                    target: group.all_shares().get(new_keygen_id)?.X_i().as_ref(),
+                },
+                &schnorr::Witness { scalar: &x_i },
+                &msg,
+            );
+            (
+                Bcast::Happy { signature },
+                Some(ShareSecretInfo::new(new_keygen_id, self.dk, x_i)),
+            )
+        } else {
+            (Bcast::Sad { complaints }, None)
+        };
+
+        Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
+            Box::new(r3::R3 {
+                epoch: self.change.epoch(),
+                kept_parties: self.change.kept_parties().clone(),
+                group,
+                share,
+                msg,
+                r1bcasts: bcasts_in,
+            }),
+            Some(serialize(&bcast_out)?),
+            None,
+        )))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+impl R2 {
+    /// The vss of dealer `i` shares `lambda_i * x_i` with the new threshold
+    /// and its shares are encrypted to the shares of the new group
+    #[allow(non_snake_case)]
+    fn validate_dealer(
+        &self,
+        my_quorum_change_id: TypedUsize<QuorumChangeShareId>,
+        peer_quorum_change_id: TypedUsize<QuorumChangeShareId>,
+        vss_commit: &vss::Commit,
+        share_ciphertexts: &[paillier::Ciphertext],
+    ) -> TofnResult<bool> {
+        let share_count = self.dealer_keygen_ids.len() + self.change.new_parties().len();
+        if vss_commit.len() != self.change.new_threshold() + 1
+            || share_ciphertexts.len() != share_count
+        {
+            warn!(
+                "peer {} says: vss of invalid size from peer {}",
+                my_quorum_change_id, peer_quorum_change_id,
+            );
+            return Ok(false);
+        }
+
+        for (j, share_ciphertext) in share_ciphertexts.iter().enumerate() {
+            let (ek, _) = new_share_keys(
+                &self.group,
+                &self.change,
+                &self.dealer_keygen_ids,
+                TypedUsize::from_usize(j),
+            )?;
+            if !ek.validate_ciphertext(share_ciphertext) {
+                log_fault_warn(
+                    my_quorum_change_id,
+                    peer_quorum_change_id,
+                    "invalid share ciphertext",
+                );
+                return Ok(false);
+            }
+        }
+
+        let peer_keygen_id = *self.dealer_keygen_ids.get(peer_quorum_change_id)?;
+        let X_i = self.group.all_shares().get(peer_keygen_id)?.X_i().as_ref();
+        let lambda_i = lagrange_coefficient(peer_quorum_change_id, &self.dealer_keygen_ids)?;
+        if *vss_commit.secret_commit() != X_i * &lambda_i {
+            log_fault_warn(
+                my_quorum_change_id,
+                peer_quorum_change_id,
+                "vss does not share its key share",
+            );
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// Public share `X_j = sum_i commit_i(j)` of each share `j` in the new group
+    #[allow(non_snake_case)]
+    fn new_group(
+        &self,
+        r1bcasts: &VecMap<QuorumChangeShareId, r1::Bcast>,
+    ) -> TofnResult<GroupPublicInfo> {
+        let all_shares = (0..r1bcasts.len())
+            .map(|j| {
+                let X_j = r1bcasts
+                    .iter()
+                    .take(self.dealer_keygen_ids.len())
+                    .try_fold(ProjectivePoint::IDENTITY, |sum, (_, bcast)| {
+                        Ok::<_, TofnFatal>(sum + bcast.dealer()?.0.share_commit(j))
+                    })?;
+                let (ek, zkp) = new_share_keys(
+                    &self.group,
+                    &self.change,
+                    &self.dealer_keygen_ids,
+                    TypedUsize::from_usize(j),
+                )?;
+                Ok(SharePublicInfo::new(X_j.into(), ek.clone(), zkp.clone()))
+            })
+            .collect::<TofnResult<Vec<_>>>()?;
+
+        Ok(GroupPublicInfo::new(
+            new_party_share_counts(
+                &self.group,
+                self.change.kept_parties(),
+                self.change.new_parties().len(),
+            )?,
+            self.change.new_threshold(),
+            self.group.y().clone(),
+            VecMap::from_vec(all_shares),
+        ))
+    }
+}
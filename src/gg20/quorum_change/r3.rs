@@ -0,0 +1,168 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use k256::ProjectivePoint;
+use tracing::error;
+
+use super::{r1, r2, EpochTransition, QuorumChangeOutput, QuorumChangeShareId};
+use crate::{
+    collections::{FillVecMap, P2ps, Subset, TypedUsize, VecMap},
+    crypto_tools::{vss, zkp::schnorr},
+    gg20::keygen::{GroupPublicInfo, KeygenPartyId, SecretKeyShare, ShareSecretInfo},
+    sdk::{
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
+        implementer_api::{log_fault_info, Executer, ProtocolBuilder, ProtocolInfo},
+    },
+};
+
+pub(super) struct R3 {
+    pub(super) epoch: u64,
+    pub(super) kept_parties: Subset<KeygenPartyId>,
+    /// The new group
+    pub(super) group: GroupPublicInfo,
+    /// `None` if I complained
+    pub(super) share: Option<ShareSecretInfo>,
+    /// Bytes of the epoch transition signed by every share
+    pub(super) msg: Vec<u8>,
+    pub(super) r1bcasts: VecMap<QuorumChangeShareId, r1::Bcast>,
+}
+
+impl Executer for R3 {
+    type FinalOutput = QuorumChangeOutput;
+    type Index = QuorumChangeShareId;
+    type Bcast = r2::Bcast;
+    type P2p = ();
+
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        _p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_quorum_change_id = info.my_id();
+        let mut faulters = info.new_fillvecmap();
+
+        // `Executer` has already flagged missing or corrupted messages,
+        // so every bcast is present
+        let bcasts_in = bcasts_in.to_vecmap()?;
+
+        // verify complaints
+        if bcasts_in
+            .iter()
+            .any(|(_, bcast)| matches!(bcast, r2::Bcast::Sad { .. }))
+        {
+            for (accuser, bcast) in bcasts_in.iter() {
+                let complaints = match bcast {
+                    r2::Bcast::Sad { complaints } => complaints,
+                    r2::Bcast::Happy { .. } => continue,
+                };
+                if complaints.is_empty() {
+                    log_fault_info(my_quorum_change_id, accuser, "empty complaint");
+                    faulters.set(accuser, ProtocolFault)?;
+                    continue;
+                }
+
+                // the keys of the accuser are the same in the new group
+                let accuser_ek = self
+                    .group
+                    .all_shares()
+                    .get(TypedUsize::from_usize(accuser.as_usize()))?
+                    .ek();
+
+                for complaint in complaints {
+                    let accused = complaint.accused;
+                    let (vss_commit, share_ciphertexts) = match self.r1bcasts.get(accused) {
+                        Ok(r1::Bcast::Dealer {
+                            vss_commit,
+                            share_ciphertexts,
+                        }) => (vss_commit, share_ciphertexts),
+                        _ => {
+                            log_fault_info(my_quorum_change_id, accuser, "accused a non-dealer");
+                            faulters.set(accuser, ProtocolFault)?;
+                            continue;
+                        }
+                    };
+
+                    // the accuser must reveal the decryption of the ciphertext it received
+                    if !accuser_ek.validate_randomness(&complaint.randomness)
+                        || accuser_ek.encrypt_with_randomness(
+                            &complaint.share.as_ref().into(),
+                            &complaint.randomness,
+                        ) != share_ciphertexts[accuser.as_usize()]
+                    {
+                        log_fault_info(my_quorum_change_id, accuser, "bad decryption");
+                        faulters.set(accuser, ProtocolFault)?;
+                        continue;
+                    }
+
+                    let share =
+                        vss::Share::from_scalar(*complaint.share.as_ref(), accuser.as_usize());
+                    if vss_commit.validate_share(&share) {
+                        log_fault_info(my_quorum_change_id, accuser, "false accusation");
+                        faulters.set(accuser, ProtocolFault)?;
+                    } else {
+                        log_fault_info(my_quorum_change_id, accused, "invalid vss share");
+                        faulters.set(accused, ProtocolFault)?;
+                    }
+                }
+            }
+
+            if faulters.is_empty() {
+                error!(
+                    "peer {} says: R3 failure protocol found no faulters",
+                    my_quorum_change_id
+                );
+                return Err(TofnFatal);
+            }
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // verify signatures on the epoch transition
+        let mut signatures = Vec::with_capacity(bcasts_in.len());
+        for (peer_quorum_change_id, bcast) in bcasts_in.into_iter() {
+            let signature = match bcast {
+                r2::Bcast::Happy { signature } => signature,
+                r2::Bcast::Sad { .. } => return Err(TofnFatal),
+            };
+            let keygen_id = TypedUsize::from_usize(peer_quorum_change_id.as_usize());
+            if !schnorr::verify_signature(
+                &schnorr::Statement {
+                    prover_id: keygen_id,
+                    base: &ProjectivePoint::GENERATOR,
+                    target: self.group.all_shares().get(keygen_id)?.X_i().as_ref(),
+                },
+                &signature,
+                &self.msg,
+            ) {
+                log_fault_info(my_quorum_change_id, peer_quorum_change_id, "bad signature");
+                faulters.set(peer_quorum_change_id, ProtocolFault)?;
+            }
+            signatures.push(signature);
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        let share = self.share.ok_or_else(|| {
+            error!(
+                "peer {} says: nobody complained but I did",
+                my_quorum_change_id
+            );
+            TofnFatal
+        })?;
+
+        Ok(ProtocolBuilder::Done(Ok(QuorumChangeOutput {
+            transition: EpochTransition::new(
+                self.epoch,
+                self.kept_parties,
+                self.group.clone(),
+                VecMap::from_vec(signatures),
+            ),
+            secret_key_share: SecretKeyShare::new(self.group, share),
+        })))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
@@ -0,0 +1,146 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use ecdsa::hazmat::VerifyPrimitive;
+use k256::PublicKey;
+use tracing_test::traced_test;
+
+use super::*;
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    crypto_tools::rng::dummy_secret_recovery_key,
+    gg20::{
+        keygen::{
+            create_party_keypair_and_zksetup_unsafe, tests::execute_keygen, KeygenPartyShareCounts,
+            SecretKeyShare,
+        },
+        sign::{MessageDigest, SignParties},
+    },
+    sdk::local::{execute_honest, sign},
+};
+
+#[test]
+#[traced_test]
+fn basic_correctness() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 2, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 1);
+    let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+
+    // remove party 0, add a new party and raise the threshold
+    let mut kept_parties = Subset::with_max_size(party_share_counts.party_count());
+    kept_parties.add(TypedUsize::from_usize(1)).unwrap();
+    kept_parties.add(TypedUsize::from_usize(2)).unwrap();
+    let new_party_keygen_data = create_party_keypair_and_zksetup_unsafe(
+        new_party_id(&kept_parties, 0),
+        &dummy_secret_recovery_key(42),
+        b"quorum_change",
+    )
+    .unwrap();
+    let new_threshold = 2;
+    let change = QuorumChange::new(
+        1,
+        kept_parties.clone(),
+        alloc::vec![new_party_keygen_data.enrollment()],
+        new_threshold,
+    );
+
+    let mut parties: Vec<_> = key_shares
+        .iter()
+        .skip(1)
+        .map(|(_, key_share)| {
+            new_quorum_change(key_share.group(), key_share.share(), &change).unwrap()
+        })
+        .collect();
+    parties.push(new_quorum_change_joiner(group, &change, &new_party_keygen_data).unwrap());
+
+    let outputs: Vec<QuorumChangeOutput> = execute_honest(VecMap::from_vec(parties))
+        .unwrap()
+        .into_iter()
+        .map(|(_, output)| output)
+        .collect();
+    assert_eq!(outputs.len(), 4);
+
+    let transition = &outputs[0].transition;
+    let new_group = transition.group();
+    assert_eq!(transition.epoch(), 1);
+    assert_eq!(transition.kept_parties(), &kept_parties);
+    assert_eq!(
+        new_group.party_share_counts(),
+        &KeygenPartyShareCounts::from_vec(alloc::vec![2, 1, 1]).unwrap()
+    );
+    assert_eq!(new_group.threshold(), new_threshold);
+    assert_eq!(new_group.verifying_key(), group.verifying_key());
+    assert!(transition.verify(group));
+    assert!(!transition.verify(new_group));
+
+    for (i, output) in outputs.iter().enumerate() {
+        assert_eq!(output.secret_key_share.group(), new_group);
+        assert_eq!(
+            output.secret_key_share.share().index(),
+            TypedUsize::from_usize(i)
+        );
+        assert!(output.secret_key_share.validate().is_valid());
+    }
+
+    // the new threshold needs 3 shares: parties 0 and 2 in the new group
+    let new_key_shares: Vec<SecretKeyShare> = outputs
+        .into_iter()
+        .map(|output| output.secret_key_share)
+        .collect();
+    let mut sign_parties = SignParties::with_max_size(3);
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(2)).unwrap();
+    let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+    let signature = sign(
+        &VecMap::from_vec(new_key_shares),
+        &sign_parties,
+        &msg_to_sign,
+    )
+    .unwrap();
+
+    let pubkey: PublicKey = group.verifying_key().into();
+    assert!(pubkey
+        .as_affine()
+        .verify_prehashed((&msg_to_sign).into(), &signature)
+        .is_ok());
+}
+
+#[test]
+fn invalid_change() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 2, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 2);
+    let key_share = key_shares.get(TypedUsize::from_usize(1)).unwrap();
+    let (group, share) = (key_share.group(), key_share.share());
+
+    // party 1 alone holds only threshold shares
+    let mut kept_parties = Subset::with_max_size(party_share_counts.party_count());
+    kept_parties.add(TypedUsize::from_usize(1)).unwrap();
+    let change = QuorumChange::new(1, kept_parties.clone(), Vec::new(), 1);
+    assert!(new_quorum_change(group, share, &change).is_err());
+
+    // new threshold too large
+    kept_parties.add(TypedUsize::from_usize(0)).unwrap();
+    let change = QuorumChange::new(1, kept_parties.clone(), Vec::new(), 3);
+    assert!(new_quorum_change(group, share, &change).is_err());
+
+    // enrollment for the wrong party id
+    let party_keygen_data = create_party_keypair_and_zksetup_unsafe(
+        TypedUsize::from_usize(0),
+        &dummy_secret_recovery_key(42),
+        b"quorum_change",
+    )
+    .unwrap();
+    let change = QuorumChange::new(
+        1,
+        kept_parties.clone(),
+        alloc::vec![party_keygen_data.enrollment()],
+        2,
+    );
+    assert!(new_quorum_change(group, share, &change).is_err());
+
+    // party 2 is removed
+    let change = QuorumChange::new(1, kept_parties, Vec::new(), 2);
+    assert!(new_quorum_change(group, share, &change).is_ok());
+    let removed = key_shares.get(TypedUsize::from_usize(3)).unwrap();
+    assert!(new_quorum_change(removed.group(), removed.share(), &change).is_err());
+}
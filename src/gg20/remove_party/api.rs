@@ -0,0 +1,224 @@
+use k256::ProjectivePoint;
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use super::r1;
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    crypto_tools::zkp::schnorr,
+    gg20::keygen::{
+        GroupPublicInfo, KeygenPartyId, KeygenShareId, SecretKeyShare, ShareSecretInfo,
+    },
+    sdk::{
+        api::{PartyShareCounts, Protocol, TofnFatal, TofnResult},
+        implementer_api::{new_protocol, ProtocolBuilder},
+    },
+};
+
+/// Maximum byte length of messages exchanged during party removal.
+/// The largest message is r1::Bcast, a vss commit of the same size as in keygen round 2.
+pub const MAX_MSG_LEN: usize = 5500;
+
+pub type RemovePartyProtocol = Protocol<RemovePartyOutput, RemovePartyShareId, RemovePartyPartyId>;
+pub type RemovePartyProtocolBuilder = ProtocolBuilder<RemovePartyOutput, RemovePartyShareId>;
+
+// Old keygen share ids of the remaining shares.
+// The share id of a remaining share in this protocol is its keygen share id in the new group.
+pub type KeygenShareIds = VecMap<RemovePartyShareId, TypedUsize<KeygenShareId>>;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RemovePartyShareId;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RemovePartyPartyId;
+
+/// Output of party removal for each remaining share
+#[derive(Debug, Clone)]
+pub struct RemovePartyOutput {
+    /// My key share in the group without the removed party
+    pub secret_key_share: SecretKeyShare,
+    /// The same for all remaining shares
+    pub attestation: RemovalAttestation,
+}
+
+/// Evidence that a party was removed from a group:
+/// the new group and a proof of knowledge of each new share.
+/// Old key shares, including those of the removed party, cannot be combined with the new ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovalAttestation {
+    removed_party: TypedUsize<KeygenPartyId>,
+    group: GroupPublicInfo,
+    x_i_proofs: VecMap<KeygenShareId, schnorr::Proof>,
+}
+
+impl RemovalAttestation {
+    pub fn removed_party(&self) -> TypedUsize<KeygenPartyId> {
+        self.removed_party
+    }
+
+    pub fn group(&self) -> &GroupPublicInfo {
+        &self.group
+    }
+
+    /// Check that this attestation is for the removal of [RemovalAttestation::removed_party] from `old_group`:
+    /// the new group has the same key and threshold and the same Paillier keys for the remaining shares,
+    /// its public shares interpolate to the group key and every remaining share proved knowledge of its new share.
+    pub fn verify(&self, old_group: &GroupPublicInfo) -> bool {
+        let old_keygen_ids = match remaining_keygen_ids(old_group, self.removed_party) {
+            Ok(ids) => ids,
+            Err(_) => return false,
+        };
+        let expected_counts = match remaining_party_share_counts(old_group, self.removed_party) {
+            Ok(counts) => counts,
+            Err(_) => return false,
+        };
+
+        if *self.group.party_share_counts() != expected_counts
+            || self.group.share_count() != old_keygen_ids.len()
+            || self.group.threshold() != old_group.threshold()
+            || self.group.y() != old_group.y()
+        {
+            warn!("removal attestation: group does not match the old group");
+            return false;
+        }
+
+        for (new_keygen_id, share) in self.group.all_shares().iter() {
+            let old_share = match old_keygen_ids
+                .get(TypedUsize::from_usize(new_keygen_id.as_usize()))
+                .and_then(|&old_keygen_id| old_group.all_shares().get(old_keygen_id))
+            {
+                Ok(old_share) => old_share,
+                Err(_) => return false,
+            };
+            if share.ek() != old_share.ek() || share.zkp() != old_share.zkp() {
+                warn!(
+                    "removal attestation: share {} has different Paillier keys",
+                    new_keygen_id
+                );
+                return false;
+            }
+        }
+
+        if !self.group.public_shares_match_group_key() {
+            warn!("removal attestation: public shares do not match the group key");
+            return false;
+        }
+
+        self.x_i_proofs.len() == self.group.share_count()
+            && self.x_i_proofs.iter().all(|(keygen_id, proof)| {
+                self.group
+                    .all_shares()
+                    .get(keygen_id)
+                    .map_or(false, |share| {
+                        schnorr::verify(
+                            &schnorr::Statement {
+                                prover_id: keygen_id,
+                                base: &ProjectivePoint::GENERATOR,
+                                target: share.X_i().as_ref(),
+                            },
+                            proof,
+                        )
+                    })
+            })
+    }
+
+    pub(super) fn new(
+        removed_party: TypedUsize<KeygenPartyId>,
+        group: GroupPublicInfo,
+        x_i_proofs: VecMap<KeygenShareId, schnorr::Proof>,
+    ) -> Self {
+        Self {
+            removed_party,
+            group,
+            x_i_proofs,
+        }
+    }
+}
+
+/// Initialize a party removal protocol for a share of a remaining party.
+/// Every share of every party other than `removed_party` must participate.
+/// Assume `group`, `share` are valid.
+pub fn new_remove_party(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    removed_party: TypedUsize<KeygenPartyId>,
+) -> TofnResult<RemovePartyProtocol> {
+    let all_keygen_ids = remaining_keygen_ids(group, removed_party)?;
+
+    if all_keygen_ids.len() <= group.threshold() {
+        error!(
+            "not enough remaining shares: threshold [{}], remaining [{}]",
+            group.threshold(),
+            all_keygen_ids.len(),
+        );
+        return Err(TofnFatal);
+    }
+
+    // find my remove-party share_id
+    let my_remove_party_id = all_keygen_ids
+        .iter()
+        .find(|(_, &k)| k == share.index())
+        .map(|(s, _)| s)
+        .ok_or_else(|| {
+            error!(
+                "my keygen share_id {} belongs to the removed party {}",
+                share.index(),
+                removed_party
+            );
+            TofnFatal
+        })?;
+
+    let party_share_counts = remaining_party_share_counts(group, removed_party)?;
+
+    let round2 = r1::start(
+        my_remove_party_id,
+        SecretKeyShare::new(group.clone(), share.clone()),
+        removed_party,
+        all_keygen_ids,
+    )?;
+
+    new_protocol(party_share_counts, my_remove_party_id, MAX_MSG_LEN, round2)
+}
+
+/// Old keygen share ids of the shares of all parties other than `removed_party`
+pub(super) fn remaining_keygen_ids(
+    group: &GroupPublicInfo,
+    removed_party: TypedUsize<KeygenPartyId>,
+) -> TofnResult<KeygenShareIds> {
+    let party_count = group.party_share_counts().party_count();
+    if removed_party.as_usize() >= party_count {
+        error!(
+            "removed party {} out of bounds {}",
+            removed_party, party_count
+        );
+        return Err(TofnFatal);
+    }
+
+    let mut remaining_parties = Subset::with_max_size(party_count);
+    for (party_id, _) in group.party_share_counts().iter() {
+        if party_id != removed_party {
+            remaining_parties.add(party_id)?;
+        }
+    }
+
+    Ok(VecMap::from_vec(
+        group
+            .party_share_counts()
+            .share_id_subset(&remaining_parties)?,
+    ))
+}
+
+/// Party share counts of all parties other than `removed_party`
+pub(super) fn remaining_party_share_counts<P>(
+    group: &GroupPublicInfo,
+    removed_party: TypedUsize<KeygenPartyId>,
+) -> TofnResult<PartyShareCounts<P>> {
+    PartyShareCounts::from_vec(
+        group
+            .party_share_counts()
+            .iter()
+            .filter(|(party_id, _)| *party_id != removed_party)
+            .map(|(_, &count)| count)
+            .collect(),
+    )
+}
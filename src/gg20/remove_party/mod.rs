@@ -0,0 +1,22 @@
+//! Remove one party from a gg20 group, keeping the group key and threshold.
+//!
+//! All shares of all remaining parties reshare the secret key among themselves,
+//! so that old key shares, including those held by the removed party, are useless against the new ones.
+//! Remaining shares are renumbered: the `i`th remaining share gets keygen share id `i` in the new group
+//! and keeps its Paillier keys.
+//!
+//! * Round 1: each remaining share `i` deals a fresh vss of `lambda_i * x_i`,
+//!   where `lambda_i` is its Lagrange coefficient among the remaining shares,
+//!   broadcasts the vss commit and sends each share encrypted to its recipient.
+//! * Round 2: each share checks that each commit shares `lambda_i * X_i`, checks its shares against the commits
+//!   and sets its new share to their sum. It broadcasts a proof of knowledge of its new share, or complaints about bad shares.
+//! * Round 3: every share outputs its key share in the new group and a [RemovalAttestation], or the faulters.
+mod api;
+pub use api::*;
+
+mod r1;
+mod r2;
+mod r3;
+
+#[cfg(test)]
+mod tests;
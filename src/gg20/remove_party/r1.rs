@@ -0,0 +1,72 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use super::{r2, KeygenShareIds, RemovePartyProtocolBuilder, RemovePartyShareId};
+use crate::{
+    collections::{TypedUsize, VecMap},
+    crypto_tools::{paillier, vss},
+    gg20::keygen::{KeygenPartyId, SecretKeyShare},
+    sdk::{
+        api::TofnResult,
+        implementer_api::{serialize, RoundBuilder},
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Bcast {
+    pub(super) vss_commit: vss::Commit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct P2p {
+    pub(super) share_ciphertext: paillier::Ciphertext,
+}
+
+/// Reshare my additive share `lambda_i * x_i` of the secret key among the remaining shares
+pub(super) fn start(
+    my_remove_party_id: TypedUsize<RemovePartyShareId>,
+    secret_key_share: SecretKeyShare,
+    removed_party: TypedUsize<KeygenPartyId>,
+    all_keygen_ids: KeygenShareIds,
+) -> TofnResult<RemovePartyProtocolBuilder> {
+    let group = secret_key_share.group();
+    let indices: Vec<usize> = all_keygen_ids
+        .iter()
+        .map(|(_, keygen_id)| keygen_id.as_usize())
+        .collect();
+    let lambda_i = vss::lagrange_coefficient(my_remove_party_id.as_usize(), &indices)?;
+    let vss =
+        vss::Vss::new_with_secret(group.threshold(), lambda_i * secret_key_share.share().x_i());
+
+    // share ids in the new group are share ids in this protocol
+    let (peer_shares, my_share) =
+        VecMap::<RemovePartyShareId, _>::from_vec(vss.shares(all_keygen_ids.len()))
+            .puncture_hole(my_remove_party_id)?;
+
+    let p2ps_out = Some(peer_shares.map2_result(|(peer_remove_party_id, share)| {
+        let peer_keygen_id = *all_keygen_ids.get(peer_remove_party_id)?;
+        let (share_ciphertext, _) = group
+            .all_shares()
+            .get(peer_keygen_id)?
+            .ek()
+            .encrypt(&share.get_scalar().into());
+        serialize(&P2p { share_ciphertext })
+    })?);
+
+    let bcast_out = Some(serialize(&Bcast {
+        vss_commit: vss.commit(),
+    })?);
+
+    Ok(RemovePartyProtocolBuilder::NotDone(RoundBuilder::new(
+        Box::new(r2::R2 {
+            group: group.clone(),
+            share: secret_key_share.share().clone(),
+            removed_party,
+            all_keygen_ids,
+            my_share,
+        }),
+        bcast_out,
+        p2ps_out,
+    )))
+}
@@ -0,0 +1,210 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use k256::ProjectivePoint;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{r1, r3, KeygenShareIds, RemovePartyOutput, RemovePartyShareId};
+use crate::{
+    collections::{FillVecMap, P2ps, TypedUsize, VecMap},
+    crypto_tools::{k256_serde, paillier, vss, zkp::schnorr},
+    gg20::keygen::{GroupPublicInfo, KeygenPartyId, ShareSecretInfo},
+    sdk::{
+        api::{Fault::ProtocolFault, TofnResult},
+        implementer_api::{
+            log_accuse_warn, serialize, Executer, ProtocolBuilder, ProtocolInfo, RoundBuilder,
+        },
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum Bcast {
+    Happy { x_i_proof: schnorr::Proof },
+    Sad { complaints: Vec<Complaint> },
+}
+
+/// Reveal the decryption of a share that does not match the vss commit of `accused`
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct Complaint {
+    pub(super) accused: TypedUsize<RemovePartyShareId>,
+    pub(super) share: k256_serde::SecretScalar,
+    pub(super) randomness: paillier::Randomness,
+}
+
+pub(super) struct R2 {
+    pub(super) group: GroupPublicInfo,
+    pub(super) share: ShareSecretInfo,
+    pub(super) removed_party: TypedUsize<KeygenPartyId>,
+    pub(super) all_keygen_ids: KeygenShareIds,
+    pub(super) my_share: vss::Share,
+}
+
+impl Executer for R2 {
+    type FinalOutput = RemovePartyOutput;
+    type Index = RemovePartyShareId;
+    type Bcast = r1::Bcast;
+    type P2p = r1::P2p;
+
+    #[allow(non_snake_case)]
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_remove_party_id = info.my_id();
+        let threshold = self.group.threshold();
+        let mut faulters = info.new_fillvecmap();
+
+        // `Executer` has already flagged missing or corrupted messages,
+        // so every bcast and p2p is present
+        let bcasts_in = bcasts_in.to_vecmap()?;
+        let p2ps_in = p2ps_in.to_fullp2ps()?;
+
+        // each vss commit must share the additive share `lambda_i * x_i` of its sender
+        let indices: Vec<usize> = self
+            .all_keygen_ids
+            .iter()
+            .map(|(_, keygen_id)| keygen_id.as_usize())
+            .collect();
+        for (peer_remove_party_id, bcast) in bcasts_in.iter() {
+            if bcast.vss_commit.len() != threshold + 1 {
+                warn!(
+                    "peer {} says: vss commit of invalid length {} (expected {}) from peer {}",
+                    my_remove_party_id,
+                    bcast.vss_commit.len(),
+                    threshold + 1,
+                    peer_remove_party_id,
+                );
+                faulters.set(peer_remove_party_id, ProtocolFault)?;
+                continue;
+            }
+
+            let peer_keygen_id = *self.all_keygen_ids.get(peer_remove_party_id)?;
+            let peer_X_i = self.group.all_shares().get(peer_keygen_id)?.X_i().as_ref();
+            let lambda_i = vss::lagrange_coefficient(peer_remove_party_id.as_usize(), &indices)?;
+            if *bcast.vss_commit.secret_commit() != peer_X_i * &lambda_i {
+                warn!(
+                    "peer {} says: vss commit from peer {} does not share its key share",
+                    my_remove_party_id, peer_remove_party_id,
+                );
+                faulters.set(peer_remove_party_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // validate share ciphertexts
+        let my_keygen_id = *self.all_keygen_ids.get(my_remove_party_id)?;
+        let ek = self.group.all_shares().get(my_keygen_id)?.ek();
+        for (peer_remove_party_id, p2p) in p2ps_in.to_me(my_remove_party_id)? {
+            if !ek.validate_ciphertext(&p2p.share_ciphertext) {
+                warn!(
+                    "peer {} says: invalid share ciphertext from peer {}",
+                    my_remove_party_id, peer_remove_party_id
+                );
+                faulters.set(peer_remove_party_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // decrypt and validate shares
+        let mut complaints = Vec::new();
+        let mut x_i = *self.my_share.get_scalar();
+        for (peer_remove_party_id, p2p) in p2ps_in.to_me(my_remove_party_id)? {
+            let (plaintext, randomness) = self
+                .share
+                .dk()
+                .decrypt_with_randomness(&p2p.share_ciphertext);
+            let share =
+                vss::Share::from_scalar(plaintext.to_scalar(), my_remove_party_id.as_usize());
+
+            if !bcasts_in
+                .get(peer_remove_party_id)?
+                .vss_commit
+                .validate_share(&share)
+            {
+                log_accuse_warn(
+                    my_remove_party_id,
+                    peer_remove_party_id,
+                    "invalid vss share",
+                );
+                complaints.push(Complaint {
+                    accused: peer_remove_party_id,
+                    share: (*share.get_scalar()).into(),
+                    randomness,
+                });
+                continue;
+            }
+            x_i += share.get_scalar();
+        }
+
+        if !complaints.is_empty() {
+            return Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
+                Box::new(r3::R3 {
+                    group: self.group,
+                    share: None,
+                    removed_party: self.removed_party,
+                    all_keygen_ids: self.all_keygen_ids,
+                    r1bcasts: bcasts_in,
+                    r1p2ps: p2ps_in,
+                }),
+                Some(serialize(&Bcast::Sad { complaints })?),
+                None,
+            )));
+        }
+
+        // my new share is the sum of the shares of all additive shares
+        let all_X_i: VecMap<RemovePartyShareId, ProjectivePoint> = new_public_shares(&bcasts_in);
+        let x_i_proof = schnorr::prove(
+            &schnorr::Statement {
+                prover_id: TypedUsize::from_usize(my_remove_party_id.as_usize()),
+                base: &ProjectivePoint::GENERATOR,
+                target: all_X_i.get(my_remove_party_id)?,
+            },
+            &schnorr::Witness { scalar: &x_i },
+        );
+
+        let new_share = ShareSecretInfo::new(
+            TypedUsize::from_usize(my_remove_party_id.as_usize()),
+            self.share.dk().clone(),
+            x_i,
+        );
+
+        Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
+            Box::new(r3::R3 {
+                group: self.group,
+                share: Some(new_share),
+                removed_party: self.removed_party,
+                all_keygen_ids: self.all_keygen_ids,
+                r1bcasts: bcasts_in,
+                r1p2ps: p2ps_in,
+            }),
+            Some(serialize(&Bcast::Happy { x_i_proof })?),
+            None,
+        )))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+/// Public share `X_j` of each share `j` in the new group
+pub(super) fn new_public_shares(
+    r1bcasts: &VecMap<RemovePartyShareId, r1::Bcast>,
+) -> VecMap<RemovePartyShareId, ProjectivePoint> {
+    (0..r1bcasts.len())
+        .map(|j| {
+            r1bcasts
+                .iter()
+                .fold(ProjectivePoint::IDENTITY, |sum, (_, bcast)| {
+                    sum + bcast.vss_commit.share_commit(j)
+                })
+        })
+        .collect()
+}
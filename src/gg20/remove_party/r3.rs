@@ -0,0 +1,191 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use k256::ProjectivePoint;
+use tracing::error;
+
+use super::{
+    api::remaining_party_share_counts, r1, r2, KeygenShareIds, RemovalAttestation,
+    RemovePartyOutput, RemovePartyShareId,
+};
+use crate::{
+    collections::{FillVecMap, FullP2ps, P2ps, TypedUsize, VecMap},
+    crypto_tools::{vss, zkp::schnorr},
+    gg20::keygen::{
+        GroupPublicInfo, KeygenPartyId, SecretKeyShare, SharePublicInfo, ShareSecretInfo,
+    },
+    sdk::{
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
+        implementer_api::{log_fault_info, Executer, ProtocolBuilder, ProtocolInfo},
+    },
+};
+
+pub(super) struct R3 {
+    pub(super) group: GroupPublicInfo,
+    /// `None` if I complained
+    pub(super) share: Option<ShareSecretInfo>,
+    pub(super) removed_party: TypedUsize<KeygenPartyId>,
+    pub(super) all_keygen_ids: KeygenShareIds,
+    pub(super) r1bcasts: VecMap<RemovePartyShareId, r1::Bcast>,
+    pub(super) r1p2ps: FullP2ps<RemovePartyShareId, r1::P2p>,
+}
+
+impl Executer for R3 {
+    type FinalOutput = RemovePartyOutput;
+    type Index = RemovePartyShareId;
+    type Bcast = r2::Bcast;
+    type P2p = ();
+
+    #[allow(non_snake_case)]
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        _p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_remove_party_id = info.my_id();
+        let mut faulters = info.new_fillvecmap();
+
+        // `Executer` has already flagged missing or corrupted messages,
+        // so every bcast is present
+        let bcasts_in = bcasts_in.to_vecmap()?;
+
+        // verify complaints
+        if bcasts_in
+            .iter()
+            .any(|(_, bcast)| matches!(bcast, r2::Bcast::Sad { .. }))
+        {
+            for (accuser, bcast) in bcasts_in.iter() {
+                let complaints = match bcast {
+                    r2::Bcast::Sad { complaints } => complaints,
+                    r2::Bcast::Happy { .. } => continue,
+                };
+                if complaints.is_empty() {
+                    log_fault_info(my_remove_party_id, accuser, "empty complaint");
+                    faulters.set(accuser, ProtocolFault)?;
+                    continue;
+                }
+
+                let accuser_keygen_id = *self.all_keygen_ids.get(accuser)?;
+                let accuser_ek = self.group.all_shares().get(accuser_keygen_id)?.ek();
+
+                for complaint in complaints {
+                    let accused = complaint.accused;
+                    if accused == accuser || accused.as_usize() >= info.total_share_count() {
+                        log_fault_info(my_remove_party_id, accuser, "invalid accused");
+                        faulters.set(accuser, ProtocolFault)?;
+                        continue;
+                    }
+
+                    // validate randomness provided by the accuser
+                    if !accuser_ek.validate_randomness(&complaint.randomness) {
+                        log_fault_info(my_remove_party_id, accuser, "bad randomness");
+                        faulters.set(accuser, ProtocolFault)?;
+                        continue;
+                    }
+
+                    // the accuser must reveal the decryption of the ciphertext it received
+                    let share_ciphertext = accuser_ek.encrypt_with_randomness(
+                        &complaint.share.as_ref().into(),
+                        &complaint.randomness,
+                    );
+                    if share_ciphertext != self.r1p2ps.get(accused, accuser)?.share_ciphertext {
+                        log_fault_info(my_remove_party_id, accuser, "bad decryption");
+                        faulters.set(accuser, ProtocolFault)?;
+                        continue;
+                    }
+
+                    let share =
+                        vss::Share::from_scalar(*complaint.share.as_ref(), accuser.as_usize());
+                    if self
+                        .r1bcasts
+                        .get(accused)?
+                        .vss_commit
+                        .validate_share(&share)
+                    {
+                        log_fault_info(my_remove_party_id, accuser, "false accusation");
+                        faulters.set(accuser, ProtocolFault)?;
+                    } else {
+                        log_fault_info(my_remove_party_id, accused, "invalid vss share");
+                        faulters.set(accused, ProtocolFault)?;
+                    }
+                }
+            }
+
+            if faulters.is_empty() {
+                error!(
+                    "peer {} says: R3 failure protocol found no faulters",
+                    my_remove_party_id
+                );
+                return Err(TofnFatal);
+            }
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // verify proofs of knowledge of the new shares
+        let all_X_i = r2::new_public_shares(&self.r1bcasts);
+        let mut x_i_proofs = Vec::with_capacity(bcasts_in.len());
+        for (peer_remove_party_id, bcast) in bcasts_in.into_iter() {
+            let x_i_proof = match bcast {
+                r2::Bcast::Happy { x_i_proof } => x_i_proof,
+                r2::Bcast::Sad { .. } => return Err(TofnFatal),
+            };
+            if !schnorr::verify(
+                &schnorr::Statement {
+                    prover_id: TypedUsize::from_usize(peer_remove_party_id.as_usize()),
+                    base: &ProjectivePoint::GENERATOR,
+                    target: all_X_i.get(peer_remove_party_id)?,
+                },
+                &x_i_proof,
+            ) {
+                log_fault_info(my_remove_party_id, peer_remove_party_id, "bad x_i proof");
+                faulters.set(peer_remove_party_id, ProtocolFault)?;
+            }
+            x_i_proofs.push(x_i_proof);
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // the remaining shares keep their Paillier keys
+        let all_shares = all_X_i
+            .into_iter()
+            .map(|(remove_party_id, X_i)| {
+                let old_keygen_id = *self.all_keygen_ids.get(remove_party_id)?;
+                let old_share = self.group.all_shares().get(old_keygen_id)?;
+                Ok(SharePublicInfo::new(
+                    X_i.into(),
+                    old_share.ek().clone(),
+                    old_share.zkp().clone(),
+                ))
+            })
+            .collect::<TofnResult<Vec<_>>>()?;
+
+        let group = GroupPublicInfo::new(
+            remaining_party_share_counts(&self.group, self.removed_party)?,
+            self.group.threshold(),
+            self.group.y().clone(),
+            VecMap::from_vec(all_shares),
+        );
+        let share = self.share.ok_or_else(|| {
+            error!(
+                "peer {} says: nobody complained but I did",
+                my_remove_party_id
+            );
+            TofnFatal
+        })?;
+
+        Ok(ProtocolBuilder::Done(Ok(RemovePartyOutput {
+            attestation: RemovalAttestation::new(
+                self.removed_party,
+                group.clone(),
+                VecMap::from_vec(x_i_proofs),
+            ),
+            secret_key_share: SecretKeyShare::new(group, share),
+        })))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
@@ -0,0 +1,113 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use ecdsa::hazmat::VerifyPrimitive;
+use k256::PublicKey;
+use tracing_test::traced_test;
+
+use super::*;
+use crate::{
+    collections::{TypedUsize, VecMap},
+    gg20::{
+        keygen::{tests::execute_keygen, KeygenPartyShareCounts, SecretKeyShare},
+        sign::{MessageDigest, SignParties},
+    },
+    sdk::local::{execute_honest, sign},
+};
+
+#[test]
+#[traced_test]
+fn basic_correctness() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 2, 1]).unwrap();
+    let threshold = 2;
+    let key_shares = execute_keygen(&party_share_counts, threshold);
+    let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+
+    // remove party 0, leaving shares 1, 2, 3
+    let removed_party = TypedUsize::from_usize(0);
+    let parties: Vec<_> = key_shares
+        .iter()
+        .skip(1)
+        .map(|(_, key_share)| {
+            new_remove_party(key_share.group(), key_share.share(), removed_party).unwrap()
+        })
+        .collect();
+
+    let outputs: Vec<RemovePartyOutput> = execute_honest(VecMap::from_vec(parties))
+        .unwrap()
+        .into_iter()
+        .map(|(_, output)| output)
+        .collect();
+    assert_eq!(outputs.len(), 3);
+
+    let new_group = outputs[0].secret_key_share.group();
+    assert_eq!(new_group.share_count(), 3);
+    assert_eq!(new_group.party_share_counts().party_count(), 2);
+    assert_eq!(new_group.threshold(), threshold);
+    assert_eq!(new_group.verifying_key(), group.verifying_key());
+
+    let attestation = &outputs[0].attestation;
+    assert_eq!(attestation.removed_party(), removed_party);
+    assert_eq!(attestation.group(), new_group);
+    assert!(attestation.verify(group));
+    assert!(!attestation.verify(new_group));
+
+    for (i, output) in outputs.iter().enumerate() {
+        assert_eq!(output.secret_key_share.group(), new_group);
+        assert_eq!(
+            output.secret_key_share.share().index(),
+            TypedUsize::from_usize(i)
+        );
+        assert!(output.secret_key_share.validate().is_valid());
+    }
+
+    // old shares are of no use with new shares
+    let old_x_i = key_shares
+        .get(TypedUsize::from_usize(1))
+        .unwrap()
+        .share()
+        .x_i();
+    assert_ne!(outputs[0].secret_key_share.share().x_i(), old_x_i);
+
+    // the remaining parties sign
+    let new_key_shares: Vec<SecretKeyShare> = outputs
+        .into_iter()
+        .map(|output| output.secret_key_share)
+        .collect();
+    let mut sign_parties = SignParties::with_max_size(2);
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(1)).unwrap();
+    let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+    let signature = sign(
+        &VecMap::from_vec(new_key_shares),
+        &sign_parties,
+        &msg_to_sign,
+    )
+    .unwrap();
+
+    let pubkey: PublicKey = group.verifying_key().into();
+    assert!(pubkey
+        .as_affine()
+        .verify_prehashed((&msg_to_sign).into(), &signature)
+        .is_ok());
+}
+
+#[test]
+fn too_few_remaining_shares() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 2, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 2);
+    let key_share = key_shares.get(TypedUsize::from_usize(0)).unwrap();
+
+    // party 1 holds 2 shares, leaving only threshold shares
+    let removed_party = TypedUsize::from_usize(1);
+    assert!(new_remove_party(key_share.group(), key_share.share(), removed_party).is_err());
+
+    // the removed party cannot participate
+    let key_shares = execute_keygen(&party_share_counts, 1);
+    let key_share = key_shares.get(TypedUsize::from_usize(1)).unwrap();
+    assert!(new_remove_party(key_share.group(), key_share.share(), removed_party).is_err());
+
+    // out of bounds
+    let removed_party = TypedUsize::from_usize(3);
+    assert!(new_remove_party(key_share.group(), key_share.share(), removed_party).is_err());
+}
@@ -0,0 +1,276 @@
+//! Threshold ECDSA adaptor signatures.
+//!
+//! [new_adaptor_sign] runs the sign protocol with nonce point `T * k^{-1}` instead of `G * k^{-1}`
+//! for a given adaptor point `T = G * t`.
+//! The output [AdaptorSignature] is not a valid signature,
+//! but anyone who knows `t` can [complete](AdaptorSignature::complete) it into one,
+//! and anyone who sees both can [extract](AdaptorSignature::extract_secret) `t`.
+//! This is the building block of atomic swaps and payment channels.
+//!
+//! To prove that the nonce point has the same discrete log base `T` as `R` has base `G`,
+//! each party broadcasts `T * gamma_i` in round 4 alongside `Gamma_i = G * gamma_i`
+//! with a Chaum-Pedersen proof of equality of discrete logs.
+//! Since `R = delta^{-1} * sum_i Gamma_i`, the adapted nonce point is `delta^{-1} * sum_i T * gamma_i`.
+use k256::{ecdsa::VerifyingKey, ProjectivePoint, Scalar};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use crate::{
+    collections::{TypedUsize, VecMap},
+    crypto_tools::{k256_serde, message_digest::MessageDigest, zkp::chaum_pedersen},
+    gg20::keygen::{GroupPublicInfo, ShareSecretInfo},
+    sdk::{
+        api::{BytesVec, Protocol, Signature, TofnFatal, TofnResult},
+        implementer_api::{deserialize, serialize},
+    },
+};
+
+use super::{api, SignOutput, SignParties, SignPartyId, SignShareId};
+
+#[cfg(feature = "malicious")]
+use super::malicious;
+
+pub type AdaptorSignProtocol = Protocol<AdaptorSignature, SignShareId, SignPartyId>;
+
+/// Initialize a new sign protocol whose output is an [AdaptorSignature] for `adaptor_point`.
+/// Assume `group`, `share` are valid and check `sign_parties` against it.
+pub fn new_adaptor_sign(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    msg_to_sign: &MessageDigest,
+    adaptor_point: &ProjectivePoint,
+    #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
+) -> TofnResult<AdaptorSignProtocol> {
+    if *adaptor_point == ProjectivePoint::IDENTITY {
+        error!("adaptor point is the point at infinity");
+        return Err(TofnFatal);
+    }
+
+    super::api::new_sign_rounds(
+        group,
+        share,
+        sign_parties,
+        msg_to_sign,
+        Some(*adaptor_point),
+        None,
+        &mut super::RandomnessPool::new(),
+        SignOutput::into_adaptor_signature,
+        #[cfg(feature = "malicious")]
+        behaviour,
+    )
+}
+
+/// A pre-signature `(R', s')` that becomes a valid signature `(x(R'), s' / t)` once `t` is known.
+/// `R'` is not stored directly: it is recomputed from per-party shares of the nonce points,
+/// each with a proof that `R' = t * R`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdaptorSignature {
+    s: Scalar,
+    delta_inv: Scalar,
+    nonce_shares: VecMap<SignShareId, NonceShare>,
+}
+
+/// Adaptor data a party broadcasts in round 4
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub(super) struct AdaptorBcast {
+    pub(super) T_gamma_i: k256_serde::ProjectivePoint,
+    pub(super) proof: chaum_pedersen::Proof,
+}
+
+/// `(Gamma_i, T * gamma_i)` and a proof that they have the same discrete log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub(super) struct NonceShare {
+    pub(super) Gamma_i: k256_serde::ProjectivePoint,
+    pub(super) adaptor: AdaptorBcast,
+}
+
+/// Adaptor state carried from round 5 to the end of the protocol
+#[allow(non_snake_case)]
+pub(super) struct AdaptorNonce {
+    pub(super) R_adapted: ProjectivePoint,
+    pub(super) delta_inv: Scalar,
+    pub(super) nonce_shares: VecMap<SignShareId, NonceShare>,
+}
+
+impl AdaptorBcast {
+    #[allow(non_snake_case)]
+    pub(super) fn new(
+        prover_id: TypedUsize<SignShareId>,
+        adaptor_point: &ProjectivePoint,
+        gamma_i: &Scalar,
+        Gamma_i: &ProjectivePoint,
+    ) -> Self {
+        let T_gamma_i = adaptor_point * gamma_i;
+        let proof = chaum_pedersen::prove(
+            &chaum_pedersen::Statement {
+                prover_id,
+                base1: &ProjectivePoint::GENERATOR,
+                base2: adaptor_point,
+                target1: Gamma_i,
+                target2: &T_gamma_i,
+            },
+            &chaum_pedersen::Witness { scalar: gamma_i },
+        );
+        Self {
+            T_gamma_i: T_gamma_i.into(),
+            proof,
+        }
+    }
+}
+
+impl NonceShare {
+    pub(super) fn verify(
+        &self,
+        prover_id: TypedUsize<SignShareId>,
+        adaptor_point: &ProjectivePoint,
+    ) -> bool {
+        chaum_pedersen::verify(
+            &chaum_pedersen::Statement {
+                prover_id,
+                base1: &ProjectivePoint::GENERATOR,
+                base2: adaptor_point,
+                target1: self.Gamma_i.as_ref(),
+                target2: self.adaptor.T_gamma_i.as_ref(),
+            },
+            &self.adaptor.proof,
+        )
+    }
+}
+
+impl AdaptorNonce {
+    /// Assume `nonce_shares` are valid
+    pub(super) fn new(delta_inv: Scalar, nonce_shares: VecMap<SignShareId, NonceShare>) -> Self {
+        Self {
+            R_adapted: adapted_nonce_point(delta_inv, &nonce_shares),
+            delta_inv,
+            nonce_shares,
+        }
+    }
+}
+
+impl AdaptorSignature {
+    pub(super) fn new(s: Scalar, adaptor: AdaptorNonce) -> Self {
+        Self {
+            s,
+            delta_inv: adaptor.delta_inv,
+            nonce_shares: adaptor.nonce_shares,
+        }
+    }
+
+    /// Verify that completing `self` with the discrete log of `adaptor_point`
+    /// yields a valid signature of `msg_to_sign` under `verifying_key`.
+    pub fn verify(
+        &self,
+        verifying_key: &VerifyingKey,
+        msg_to_sign: &MessageDigest,
+        adaptor_point: &ProjectivePoint,
+    ) -> bool {
+        for (prover_id, nonce_share) in self.nonce_shares.iter() {
+            if !nonce_share.verify(prover_id, adaptor_point) {
+                warn!("adaptor nonce share of {} failed to verify", prover_id);
+                return false;
+            }
+        }
+        let y = ProjectivePoint::from(*verifying_key.as_affine());
+        self.verify_nonce_points(&y, msg_to_sign.into())
+    }
+
+    /// Check `R = G * (m / s') + y * (r / s')` where `r = x(R')`.
+    /// Assume the nonce shares are valid.
+    #[allow(non_snake_case)]
+    pub(super) fn verify_nonce_points(&self, y: &ProjectivePoint, msg_to_sign: Scalar) -> bool {
+        let R_adapted = adapted_nonce_point(self.delta_inv, &self.nonce_shares);
+        if R_adapted == ProjectivePoint::IDENTITY {
+            return false;
+        }
+        let r = match api::r_from_nonce_point(&R_adapted) {
+            Ok(r) => r,
+            Err(_) => return false,
+        };
+        let s_inv = self.s.invert();
+        if bool::from(s_inv.is_none()) {
+            return false;
+        }
+        let s_inv = s_inv.unwrap();
+
+        ProjectivePoint::GENERATOR * (msg_to_sign * s_inv) + y * &(r * s_inv) == self.nonce_point()
+    }
+
+    /// Complete `self` into a signature with `adaptor_secret`, the discrete log of the adaptor point.
+    #[allow(non_snake_case)]
+    pub fn complete(&self, adaptor_secret: &Scalar) -> TofnResult<Signature> {
+        let R_adapted = adapted_nonce_point(self.delta_inv, &self.nonce_shares);
+        if self.nonce_point() * adaptor_secret != R_adapted {
+            error!("adaptor secret does not match the adaptor point");
+            return Err(TofnFatal);
+        }
+        let adaptor_secret_inv =
+            Option::<Scalar>::from(adaptor_secret.invert()).ok_or_else(|| {
+                error!("adaptor secret is zero");
+                TofnFatal
+            })?;
+
+        let r = api::r_from_nonce_point(&R_adapted)?;
+        let signature = Signature::from_scalars(r, self.s * adaptor_secret_inv).map_err(|_| {
+            error!("scalars to signature conversion failed");
+            TofnFatal
+        })?;
+        Ok(signature.normalize_s().unwrap_or(signature))
+    }
+
+    /// Recover the discrete log of the adaptor point from `signature`, the completion of `self`.
+    #[allow(non_snake_case)]
+    pub fn extract_secret(&self, signature: &Signature) -> TofnResult<Scalar> {
+        let s_inv = Option::<Scalar>::from(signature.s().invert()).ok_or(TofnFatal)?;
+        let adaptor_secret = self.s * s_inv;
+
+        // completion may have negated s
+        let R = self.nonce_point();
+        let R_adapted = adapted_nonce_point(self.delta_inv, &self.nonce_shares);
+        [adaptor_secret, -adaptor_secret]
+            .iter()
+            .find(|t| R * *t == R_adapted)
+            .copied()
+            .ok_or_else(|| {
+                error!("signature is not a completion of this adaptor signature");
+                TofnFatal
+            })
+    }
+
+    pub fn to_bytes(&self) -> TofnResult<BytesVec> {
+        serialize(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> TofnResult<Self> {
+        deserialize(bytes).ok_or_else(|| {
+            error!("failed to deserialize adaptor signature");
+            TofnFatal
+        })
+    }
+
+    /// `R = G * k^{-1}`
+    fn nonce_point(&self) -> ProjectivePoint {
+        self.nonce_shares
+            .iter()
+            .fold(ProjectivePoint::IDENTITY, |acc, (_, share)| {
+                acc + share.Gamma_i.as_ref()
+            })
+            * self.delta_inv
+    }
+}
+
+/// `R' = T * k^{-1}`
+fn adapted_nonce_point(
+    delta_inv: Scalar,
+    nonce_shares: &VecMap<SignShareId, NonceShare>,
+) -> ProjectivePoint {
+    nonce_shares
+        .iter()
+        .fold(ProjectivePoint::IDENTITY, |acc, (_, share)| {
+            acc + share.adaptor.T_gamma_i.as_ref()
+        })
+        * delta_inv
+}
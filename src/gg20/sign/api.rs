@@ -4,19 +4,25 @@ use crate::{
         GroupPublicInfo, KeygenPartyId, KeygenShareId, SecretKeyShare, ShareSecretInfo,
     },
     sdk::{
-        api::{PartyShareCounts, Protocol, Signature, TofnFatal, TofnResult},
+        api::{PartyShareCounts, Protocol, RoundDeadlines, Signature, TofnFatal, TofnResult},
         implementer_api::{new_protocol, ProtocolBuilder},
     },
 };
+use ecdsa::elliptic_curve::{ops::Reduce, sec1::ToEncodedPoint};
+use k256::{ProjectivePoint, Scalar};
 use serde::{Deserialize, Serialize};
 use tracing::error;
 
-use super::r1;
+use super::{r1, AdaptorSignature, RandomnessPool};
 
 #[cfg(feature = "malicious")]
 use super::malicious;
+#[cfg(feature = "malicious")]
+use crate::sdk::implementer_api::with_msg_fault;
 
-pub use crate::crypto_tools::message_digest::MessageDigest;
+pub use crate::crypto_tools::message_digest::{
+    DigestPolicy, Keccak256Policy, MessageDigest, PolicyDigest, Sha256Policy,
+};
 
 /// Maximum byte length of messages exchanged during sign.
 /// The sender of a message larger than this maximum will be accused as a faulter.
@@ -24,9 +30,42 @@ pub use crate::crypto_tools::message_digest::MessageDigest;
 /// The largest sign message is r2::P2pHappy with size ~6828 bytes on the wire.
 pub const MAX_MSG_LEN: usize = 7500;
 
-pub type SignProtocol = Protocol<Signature, SignShareId, SignPartyId, MAX_MSG_LEN>;
+/// Number of rounds executed by an honest sign; faults may add rounds.
+pub const ROUND_COUNT: usize = 7;
+
+pub type SignProtocol = Protocol<Signature, SignShareId, SignPartyId>;
 pub type SignProtocolBuilder = ProtocolBuilder<Signature, SignShareId>;
 
+/// Final output of the sign rounds, shared by [SignProtocol] and [AdaptorSignProtocol](super::AdaptorSignProtocol)
+pub(super) enum SignOutput {
+    Signature(Signature),
+    Adaptor(AdaptorSignature),
+}
+
+pub(super) type SignRoundsBuilder = ProtocolBuilder<SignOutput, SignShareId>;
+
+impl SignOutput {
+    fn into_signature(self) -> TofnResult<Signature> {
+        match self {
+            Self::Signature(signature) => Ok(signature),
+            Self::Adaptor(_) => {
+                error!("sign output is an adaptor signature");
+                Err(TofnFatal)
+            }
+        }
+    }
+
+    pub(super) fn into_adaptor_signature(self) -> TofnResult<AdaptorSignature> {
+        match self {
+            Self::Adaptor(adaptor_signature) => Ok(adaptor_signature),
+            Self::Signature(_) => {
+                error!("sign output is not an adaptor signature");
+                Err(TofnFatal)
+            }
+        }
+    }
+}
+
 // This includes all shares participating in the current signing protocol
 pub type KeygenShareIds = VecMap<SignShareId, TypedUsize<KeygenShareId>>;
 // This includes all shares (excluding self) participating in the current signing protocol
@@ -42,6 +81,9 @@ pub struct SignPartyId;
 
 /// Initialize a new sign protocol
 /// Assume `group`, `share` are valid and check `sign_parties` against it.
+///
+/// Sign messages do not grow with the threshold, so sign adds no threshold limit of its own,
+/// but keygen rejects thresholds of about 160 and above, see [MAX_MSG_LEN](crate::gg20::keygen::MAX_MSG_LEN).
 pub fn new_sign(
     group: &GroupPublicInfo,
     share: &ShareSecretInfo,
@@ -49,6 +91,190 @@ pub fn new_sign(
     msg_to_sign: &MessageDigest,
     #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
 ) -> TofnResult<SignProtocol> {
+    new_sign_with_randomness_pool(
+        group,
+        share,
+        sign_parties,
+        msg_to_sign,
+        &mut RandomnessPool::new(),
+        #[cfg(feature = "malicious")]
+        behaviour,
+    )
+}
+
+/// Like [new_sign] but `msg_to_sign` must have been computed under the group's digest policy `D`.
+pub fn new_sign_with_policy<D: DigestPolicy>(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    msg_to_sign: &PolicyDigest<D>,
+    #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
+) -> TofnResult<SignProtocol> {
+    new_sign(
+        group,
+        share,
+        sign_parties,
+        msg_to_sign.digest(),
+        #[cfg(feature = "malicious")]
+        behaviour,
+    )
+}
+
+/// Like [new_sign] but Paillier encryptions use precomputed randomness from `randomness_pool`.
+/// Every entry used by this sign is removed from `randomness_pool`.
+/// Missing entries are computed on demand.
+pub fn new_sign_with_randomness_pool(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    msg_to_sign: &MessageDigest,
+    randomness_pool: &mut RandomnessPool,
+    #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
+) -> TofnResult<SignProtocol> {
+    new_sign_rounds(
+        group,
+        share,
+        sign_parties,
+        msg_to_sign,
+        None,
+        None,
+        randomness_pool,
+        SignOutput::into_signature,
+        #[cfg(feature = "malicious")]
+        behaviour,
+    )
+}
+
+/// Like [new_sign] but the nonce shares `k_i` and `gamma_i` of this share are derived
+/// from its secret key share, `msg_to_sign`, `session_id` and the co-signers
+/// instead of the runtime RNG.
+/// `session_id` must be unique to each sign: reusing it for the same message
+/// with different co-signers may leak the secret key share.
+///
+/// # Security
+///
+/// This mode gives no protection against a weak runtime RNG.
+/// Only the nonce shares are deterministic: Paillier encryption randomness,
+/// MtA masks and the masks of all zk proofs still come from `rand::thread_rng`,
+/// and a predictable or repeated value in any of them can leak the secret key share
+/// exactly as it would with [new_sign].
+pub fn new_sign_deterministic(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    msg_to_sign: &MessageDigest,
+    session_id: &[u8],
+    #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
+) -> TofnResult<SignProtocol> {
+    new_sign_rounds(
+        group,
+        share,
+        sign_parties,
+        msg_to_sign,
+        None,
+        Some(session_id),
+        &mut RandomnessPool::new(),
+        SignOutput::into_signature,
+        #[cfg(feature = "malicious")]
+        behaviour,
+    )
+}
+
+/// Configure a sign protocol one option at a time, as an alternative to the `new_sign*` functions.
+/// Options added later do not break existing callers.
+pub struct SignBuilder<'a> {
+    group: &'a GroupPublicInfo,
+    share: &'a ShareSecretInfo,
+    sign_parties: &'a SignParties,
+    msg_to_sign: &'a MessageDigest,
+    session_id: Option<&'a [u8]>,
+    randomness_pool: Option<&'a mut RandomnessPool>,
+    deadlines: Option<RoundDeadlines>,
+    #[cfg(feature = "malicious")]
+    behaviour: malicious::Behaviour,
+}
+
+impl<'a> SignBuilder<'a> {
+    /// Equivalent to [new_sign] unless other options are set
+    pub fn new(
+        group: &'a GroupPublicInfo,
+        share: &'a ShareSecretInfo,
+        sign_parties: &'a SignParties,
+        msg_to_sign: &'a MessageDigest,
+    ) -> Self {
+        Self {
+            group,
+            share,
+            sign_parties,
+            msg_to_sign,
+            session_id: None,
+            randomness_pool: None,
+            deadlines: None,
+            #[cfg(feature = "malicious")]
+            behaviour: malicious::Behaviour::Honest,
+        }
+    }
+
+    /// Derive the nonce shares from `session_id`, see [new_sign_deterministic].
+    /// All other randomness still comes from the runtime RNG, which must be sound.
+    pub fn session_id(mut self, session_id: &'a [u8]) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    /// See [new_sign_with_randomness_pool]
+    pub fn randomness_pool(mut self, randomness_pool: &'a mut RandomnessPool) -> Self {
+        self.randomness_pool = Some(randomness_pool);
+        self
+    }
+
+    /// See [Round::set_deadlines](crate::sdk::api::Round::set_deadlines)
+    pub fn deadlines(mut self, deadlines: RoundDeadlines) -> Self {
+        self.deadlines = Some(deadlines);
+        self
+    }
+
+    #[cfg(feature = "malicious")]
+    pub fn behaviour(mut self, behaviour: malicious::Behaviour) -> Self {
+        self.behaviour = behaviour;
+        self
+    }
+
+    pub fn build(self) -> TofnResult<SignProtocol> {
+        let mut fresh_pool = RandomnessPool::new();
+        let mut protocol = new_sign_rounds(
+            self.group,
+            self.share,
+            self.sign_parties,
+            self.msg_to_sign,
+            None,
+            self.session_id,
+            self.randomness_pool.unwrap_or(&mut fresh_pool),
+            SignOutput::into_signature,
+            #[cfg(feature = "malicious")]
+            self.behaviour,
+        )?;
+        if let (Some(deadlines), Protocol::NotDone(round)) = (self.deadlines, &mut protocol) {
+            round.set_deadlines(deadlines);
+        }
+        Ok(protocol)
+    }
+}
+
+/// Sign with nonce point `adaptor_point * k^{-1}` if `adaptor_point` is given, else `G * k^{-1}`.
+/// Derive nonce shares from `nonce_session_id` if given, else sample them.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn new_sign_rounds<F: 'static>(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    msg_to_sign: &MessageDigest,
+    adaptor_point: Option<ProjectivePoint>,
+    nonce_session_id: Option<&[u8]>,
+    randomness_pool: &mut RandomnessPool,
+    map_output: fn(SignOutput) -> TofnResult<F>,
+    #[cfg(feature = "malicious")] behaviour: malicious::Behaviour,
+) -> TofnResult<Protocol<F, SignShareId, SignPartyId>> {
     let all_keygen_ids =
         VecMap::from_vec(group.party_share_counts().share_id_subset(sign_parties)?);
 
@@ -75,14 +301,40 @@ pub fn new_sign(
     let sign_party_share_counts =
         PartyShareCounts::from_vec(group.party_share_counts().subset(sign_parties)?)?;
 
+    #[cfg(feature = "malicious")]
+    let msg_fault = behaviour.msg_fault();
+
     let round2 = r1::start(
         my_sign_id,
         SecretKeyShare::new(group.clone(), share.clone()),
         msg_to_sign.into(),
         all_keygen_ids,
+        adaptor_point,
+        nonce_session_id,
+        randomness_pool,
         #[cfg(feature = "malicious")]
         behaviour,
     )?;
 
-    new_protocol(sign_party_share_counts, my_sign_id, round2)
+    let protocol = new_protocol(
+        sign_party_share_counts,
+        my_sign_id,
+        MAX_MSG_LEN,
+        round2.map_output(map_output)?,
+    )?;
+    #[cfg(feature = "malicious")]
+    let protocol = with_msg_fault(protocol, msg_fault)?;
+    Ok(protocol)
+}
+
+/// The `r` component of a signature with nonce point `R`
+#[allow(non_snake_case)]
+pub(super) fn r_from_nonce_point(R: &ProjectivePoint) -> TofnResult<Scalar> {
+    // reference for r: https://docs.rs/k256/0.8.1/src/k256/ecdsa/sign.rs.html#223-225
+    Ok(<Scalar as Reduce<k256::U256>>::from_be_bytes_reduced(
+        *R.to_affine().to_encoded_point(true).x().ok_or_else(|| {
+            error!("Invalid R point");
+            TofnFatal
+        })?,
+    ))
 }
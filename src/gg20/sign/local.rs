@@ -0,0 +1,88 @@
+//! Sign fast path for a caller that holds `threshold + 1` or more shares of the key,
+//! such as a single party that holds every share.
+//! The caller can recover the secret key, so the rounds are skipped
+//! and the signature is computed locally.
+use alloc::vec::Vec;
+
+use ecdsa::{elliptic_curve::Field, hazmat::VerifyPrimitive};
+use k256::{ProjectivePoint, PublicKey, Scalar};
+use tracing::error;
+
+use super::{api::r_from_nonce_point, MessageDigest};
+use crate::{
+    crypto_tools::vss::lagrange_coefficient,
+    gg20::keygen::SecretKeyShare,
+    sdk::api::{Signature, TofnFatal, TofnResult},
+};
+
+/// Output of a full sign in which `key_shares` are all the participants,
+/// computed without running the protocol.
+pub fn sign_local(
+    key_shares: &[SecretKeyShare],
+    msg_to_sign: &MessageDigest,
+) -> TofnResult<Signature> {
+    let group = match key_shares.first() {
+        Some(key_share) => key_share.group(),
+        None => {
+            error!("no key shares");
+            return Err(TofnFatal);
+        }
+    };
+    if key_shares
+        .iter()
+        .any(|key_share| key_share.group() != group)
+    {
+        error!("key shares belong to different groups");
+        return Err(TofnFatal);
+    }
+    if key_shares.len() <= group.threshold() {
+        error!(
+            "not enough shares: threshold [{}], shares [{}]",
+            group.threshold(),
+            key_shares.len(),
+        );
+        return Err(TofnFatal);
+    }
+
+    let indices: Vec<usize> = key_shares
+        .iter()
+        .map(|key_share| key_share.share().index().as_usize())
+        .collect();
+    let x = key_shares.iter().enumerate().try_fold(
+        Scalar::ZERO,
+        |sum, (i, key_share)| -> TofnResult<Scalar> {
+            Ok(sum + key_share.share().x_i() * &lagrange_coefficient(i, &indices)?)
+        },
+    )?;
+
+    let pkey: PublicKey = group.try_verifying_key()?.into();
+    if ProjectivePoint::GENERATOR * x != pkey.to_projective() {
+        error!("key shares do not recover the group public key");
+        return Err(TofnFatal);
+    }
+
+    // same nonce convention as the protocol: R = g^{k^{-1}}, sigma = k * x
+    let k = Scalar::random(rand::thread_rng());
+    let k_inv = Option::<Scalar>::from(k.invert()).ok_or_else(|| {
+        error!("zero nonce");
+        TofnFatal
+    })?;
+    let r = r_from_nonce_point(&(ProjectivePoint::GENERATOR * k_inv))?;
+    let m: Scalar = msg_to_sign.into();
+    let s = m * k + r * (k * x);
+
+    let sig = {
+        let sig = Signature::from_scalars(r, s).map_err(|_| {
+            error!("scalars to signature conversion failed");
+            TofnFatal
+        })?;
+        sig.normalize_s().unwrap_or(sig)
+    };
+
+    if pkey.as_affine().verify_prehashed(m, &sig).is_err() {
+        error!("local signature failed to verify");
+        return Err(TofnFatal);
+    }
+
+    Ok(sig)
+}
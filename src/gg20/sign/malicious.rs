@@ -10,7 +10,7 @@ use crate::{
     sdk::{
         api::{BytesVec, MsgType},
         implementer_api::{
-            decode_message, deserialize, encode_message, serialize, ExpectedMsgTypes,
+            decode_message, deserialize, encode_message, serialize, ExpectedMsgTypes, MsgFault,
         },
     },
 };
@@ -24,32 +24,88 @@ use ecdsa::elliptic_curve::Field;
 #[derive(Clone, Debug)]
 pub enum Behaviour {
     Honest,
-    R1BadProof { victim: TypedUsize<SignShareId> },
+    R1BadProof {
+        victim: TypedUsize<SignShareId>,
+    },
     R1BadGammaI, // triggers r6::Output::FailType5
-    R2FalseAccusation { victim: TypedUsize<SignShareId> },
-    R2BadMta { victim: TypedUsize<SignShareId> },
-    R2BadMtaWc { victim: TypedUsize<SignShareId> },
+    R2FalseAccusation {
+        victim: TypedUsize<SignShareId>,
+    },
+    R2BadMta {
+        victim: TypedUsize<SignShareId>,
+    },
+    R2BadMtaWc {
+        victim: TypedUsize<SignShareId>,
+    },
     R3BadSigmaI, // triggers r7::Output::FailType7
-    R3FalseAccusationMta { victim: TypedUsize<SignShareId> },
-    R3FalseAccusationMtaWc { victim: TypedUsize<SignShareId> },
+    R3FalseAccusationMta {
+        victim: TypedUsize<SignShareId>,
+    },
+    R3FalseAccusationMtaWc {
+        victim: TypedUsize<SignShareId>,
+    },
     R3BadProof,
-    R3BadDeltaI,                                    // triggers r6::Output::FailType5
-    R3BadKI,                                        // triggers r6::Output::FailType5
-    R3BadAlpha { victim: TypedUsize<SignShareId> }, // triggers r6::Output::FailType5
-    R3BadBeta { victim: TypedUsize<SignShareId> },  // triggers r6::Output::FailType5
+    R3BadDeltaI, // triggers r6::Output::FailType5
+    R3BadKI,     // triggers r6::Output::FailType5
+    R3BadAlpha {
+        victim: TypedUsize<SignShareId>,
+    }, // triggers r6::Output::FailType5
+    R3BadBeta {
+        victim: TypedUsize<SignShareId>,
+    }, // triggers r6::Output::FailType5
     R4BadReveal,
-    R5BadProof { victim: TypedUsize<SignShareId> },
-    R6FalseAccusation { victim: TypedUsize<SignShareId> },
+    R5BadProof {
+        victim: TypedUsize<SignShareId>,
+    },
+    R6FalseAccusation {
+        victim: TypedUsize<SignShareId>,
+    },
     R6BadProof,
     R6FalseType5Claim,
+    R6BadTranscriptHash,
     R7BadSI,
     R7FalseType7Claim,
+    // message-delivery faults in any round, numbered from 1
+    WithholdBcast {
+        round: usize,
+    },
+    WithholdP2p {
+        round: usize,
+        victim: TypedUsize<SignShareId>,
+    },
+    HalfOfPeers {
+        round: usize,
+    }, // send only to the first half of all shares
+    Replay {
+        round: usize,
+    }, // label messages with the previous round
+    FutureRound {
+        round: usize,
+    }, // label messages with the next round
+    Impersonate {
+        round: usize,
+        victim: TypedUsize<SignShareId>,
+    }, // send messages as `victim`
 }
 
 impl Behaviour {
     pub fn is_honest(&self) -> bool {
         matches!(self, Self::Honest)
     }
+
+    /// The message-delivery fault of this behaviour as `(round, fault)`, if any
+    pub(crate) fn msg_fault(&self) -> Option<(usize, MsgFault<SignShareId>)> {
+        use Behaviour::*;
+        match *self {
+            WithholdBcast { round } => Some((round, MsgFault::WithholdBcast)),
+            WithholdP2p { round, victim } => Some((round, MsgFault::WithholdP2p { victim })),
+            HalfOfPeers { round } => Some((round, MsgFault::HalfOfPeers)),
+            Replay { round } => Some((round, MsgFault::Replay)),
+            FutureRound { round } => Some((round, MsgFault::FutureRound)),
+            Impersonate { round, victim } => Some((round, MsgFault::Impersonate { victim })),
+            _ => None,
+        }
+    }
 }
 
 pub(crate) fn log_confess_info<K>(sign_id: TypedUsize<K>, behaviour: &Behaviour, msg: &str) {
@@ -64,15 +120,11 @@ pub fn delta_inverse_r3(
     faulter_share_id: TypedUsize<SignShareId>,
     all_bcasts: VecMap<SignShareId, Option<BytesVec>>,
 ) -> (VecMap<SignShareId, Option<BytesVec>>, k256::Scalar) {
-    let mut all_bcasts_deserialized: Vec<r3::BcastHappy> = all_bcasts
-        .map(|bytes_option| {
-            deserialize(
-                &decode_message::<SignShareId>(&bytes_option.unwrap())
-                    .unwrap()
-                    .payload,
-            )
-            .unwrap()
-        })
+    let all_bcasts_decoded = all_bcasts
+        .map(|bytes_option| decode_message::<SignShareId>(&bytes_option.unwrap()).unwrap());
+    let round = all_bcasts_decoded.get(faulter_share_id).unwrap().round;
+    let mut all_bcasts_deserialized: Vec<r3::BcastHappy> = all_bcasts_decoded
+        .map(|wire_bytes| deserialize(&wire_bytes.payload).unwrap())
         .into_vec();
 
     let mut faulter_bcast = all_bcasts_deserialized.remove(faulter_share_id.as_usize());
@@ -94,6 +146,7 @@ pub fn delta_inverse_r3(
                 encode_message::<SignShareId>(
                     serialize(&bcast).unwrap(),
                     from,
+                    round,
                     MsgType::Bcast,
                     ExpectedMsgTypes::BcastOnly,
                 )
@@ -123,16 +176,13 @@ pub fn delta_inverse_r4(
     faulter_bcast: &mut BytesVec,
     faulter_p2ps: &mut HoleVecMap<SignShareId, BytesVec>,
 ) {
-    let mut faulter_bcast_deserialized = match deserialize::<r4::Bcast>(
-        &decode_message::<SignShareId>(faulter_bcast)
-            .unwrap()
-            .payload,
-    )
-    .unwrap()
-    {
-        r4::Bcast::SadType5(h, s) => (h, s),
-        _ => panic!("expected SadType5 variant, got something else"),
-    };
+    let faulter_bcast_decoded = decode_message::<SignShareId>(faulter_bcast).unwrap();
+    let round = faulter_bcast_decoded.round;
+    let mut faulter_bcast_deserialized =
+        match deserialize::<r4::Bcast>(&faulter_bcast_decoded.payload).unwrap() {
+            r4::Bcast::SadType5(h, s) => (h, s),
+            _ => panic!("expected SadType5 variant, got something else"),
+        };
 
     let mut faulter_p2ps_deserialized: HoleVecMap<_, P2pSadType5> = faulter_p2ps
         .ref_map2_result(|(_, bytes)| {
@@ -175,6 +225,7 @@ pub fn delta_inverse_r4(
         ))
         .unwrap(),
         faulter_share_id,
+        round,
         MsgType::Bcast,
         ExpectedMsgTypes::BcastAndP2p,
     )
@@ -185,6 +236,7 @@ pub fn delta_inverse_r4(
             encode_message::<SignShareId>(
                 serialize(&p2p).unwrap(),
                 faulter_share_id,
+                round,
                 MsgType::P2p { to },
                 ExpectedMsgTypes::BcastAndP2p,
             )
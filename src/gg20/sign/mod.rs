@@ -1,6 +1,15 @@
 mod api;
 pub use api::*;
 
+mod randomness_pool;
+pub use randomness_pool::RandomnessPool;
+
+mod local;
+pub use local::*;
+
+mod adaptor;
+pub use adaptor::*;
+
 mod r1;
 mod r2;
 mod r3;
@@ -4,8 +4,8 @@ use core::borrow::Borrow;
 
 use crate::{
     collections::TypedUsize,
-    crypto_tools::{constants, hash, k256_serde::point_to_bytes, paillier, vss},
-    gg20::keygen::SecretKeyShare,
+    crypto_tools::{constants, hash, k256_serde::point_to_bytes, paillier, rng, vss},
+    gg20::{self, keygen::SecretKeyShare},
     sdk::{
         api::TofnResult,
         implementer_api::{serialize, RoundBuilder},
@@ -15,7 +15,7 @@ use ecdsa::elliptic_curve::Field;
 use k256::Scalar;
 use serde::{Deserialize, Serialize};
 
-use super::{r2, KeygenShareIds, SignProtocolBuilder, SignShareId};
+use super::{r2, KeygenShareIds, RandomnessPool, SignRoundsBuilder, SignShareId};
 
 #[cfg(feature = "malicious")]
 use super::malicious::Behaviour;
@@ -38,8 +38,11 @@ pub(super) fn start(
     secret_key_share: SecretKeyShare,
     msg_to_sign: Scalar,
     all_keygen_ids: KeygenShareIds,
+    adaptor_point: Option<k256::ProjectivePoint>,
+    nonce_session_id: Option<&[u8]>,
+    randomness_pool: &mut RandomnessPool,
     #[cfg(feature = "malicious")] behaviour: Behaviour,
-) -> TofnResult<SignProtocolBuilder> {
+) -> TofnResult<SignRoundsBuilder> {
     // `HoleVecMap` has limited options for construction,
     // so we store a separate `peer_keygen_ids` to generate future `HoleVecMap`s.
     let (peer_keygen_ids, my_keygen_id) = all_keygen_ids.clone().puncture_hole(my_sign_id)?;
@@ -54,8 +57,28 @@ pub(super) fn start(
 
     let w_i = secret_key_share.share().x_i() * lambda_i_S;
 
-    let k_i = k256::Scalar::random(rand::thread_rng());
-    let gamma_i = k256::Scalar::random(rand::thread_rng());
+    let (k_i, gamma_i) = match nonce_session_id {
+        Some(session_id) => {
+            // bind the nonce shares to the co-signers
+            let co_signers: Vec<u8> = all_keygen_ids
+                .iter()
+                .flat_map(|(_, keygen_id)| keygen_id.to_bytes())
+                .collect();
+            let mut rng = rng::rng_seed_sign_nonce(
+                gg20::constants::SIGN_NONCE_TAG,
+                my_sign_id,
+                secret_key_share.share().x_i(),
+                &msg_to_sign,
+                &co_signers,
+                session_id,
+            )?;
+            (Scalar::random(&mut rng), Scalar::random(&mut rng))
+        }
+        None => (
+            Scalar::random(rand::thread_rng()),
+            Scalar::random(rand::thread_rng()),
+        ),
+    };
     let Gamma_i = k256::ProjectivePoint::GENERATOR * gamma_i;
     let (Gamma_i_commit, Gamma_i_reveal) = hash::commit(
         constants::GAMMA_I_COMMIT_TAG,
@@ -81,7 +104,22 @@ pub(super) fn start(
         .get(my_keygen_id)?
         .ek();
 
-    let (k_i_ciphertext, k_i_randomness) = ek.encrypt(&k_i.borrow().into());
+    let (k_i_ciphertext, k_i_randomness) = ek.encrypt_with_precomputed(
+        &k_i.borrow().into(),
+        randomness_pool.take(secret_key_share.group(), my_keygen_id)?,
+    );
+
+    // reserve randomness for my two MtA responses to each peer in round 2
+    let mut mta_randomness = RandomnessPool::new();
+    for (_, &peer_keygen_id) in &peer_keygen_ids {
+        randomness_pool.take_into(
+            &mut mta_randomness,
+            secret_key_share.group(),
+            peer_keygen_id,
+            2,
+        )?;
+    }
+
     let p2ps_out = Some(
         peer_keygen_ids.ref_map2_result(|(peer_sign_id, &peer_keygen_id)| {
             let peer_zkp = secret_key_share
@@ -117,19 +155,21 @@ pub(super) fn start(
         k_i_ciphertext,
     })?);
 
-    Ok(SignProtocolBuilder::NotDone(RoundBuilder::new(
+    Ok(SignRoundsBuilder::NotDone(RoundBuilder::new(
         Box::new(r2::R2 {
             secret_key_share,
             msg_to_sign,
             peer_keygen_ids,
             all_keygen_ids,
             my_keygen_id,
-            gamma_i,
+            gamma_i: gamma_i.into(),
             Gamma_i,
             Gamma_i_reveal,
-            w_i,
-            k_i,
+            adaptor_point,
+            w_i: w_i.into(),
+            k_i: k_i.into(),
             k_i_randomness,
+            mta_randomness,
 
             #[cfg(feature = "malicious")]
             behaviour,
@@ -3,12 +3,14 @@ use alloc::boxed::Box;
 use crate::{
     collections::{FillVecMap, P2ps, TypedUsize},
     crypto_tools::{
-        hash, mta,
+        hash,
+        k256_serde::SecretScalar,
+        mta,
         paillier::{self, Ciphertext},
     },
     gg20::keygen::{KeygenShareId, SecretKeyShare},
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnResult},
+        api::{Fault::ProtocolFault, TofnResult},
         implementer_api::{serialize, Executer, ProtocolBuilder, ProtocolInfo, RoundBuilder},
     },
 };
@@ -16,7 +18,7 @@ use k256::{ProjectivePoint, Scalar};
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
-use super::{r1, r3, KeygenShareIds, Peers, SignShareId};
+use super::{r1, r3, KeygenShareIds, Peers, RandomnessPool, SignOutput, SignShareId};
 
 #[cfg(feature = "malicious")]
 use super::malicious::Behaviour;
@@ -28,12 +30,14 @@ pub(super) struct R2 {
     pub(super) peer_keygen_ids: Peers,
     pub(super) all_keygen_ids: KeygenShareIds,
     pub(super) my_keygen_id: TypedUsize<KeygenShareId>,
-    pub(super) gamma_i: Scalar,
+    pub(super) gamma_i: SecretScalar,
     pub(super) Gamma_i: ProjectivePoint,
     pub(super) Gamma_i_reveal: hash::Randomness,
-    pub(super) w_i: Scalar,
-    pub(super) k_i: Scalar,
+    pub(super) adaptor_point: Option<ProjectivePoint>,
+    pub(super) w_i: SecretScalar,
+    pub(super) k_i: SecretScalar,
     pub(super) k_i_randomness: paillier::Randomness,
+    pub(super) mta_randomness: RandomnessPool,
 
     #[cfg(feature = "malicious")]
     pub(super) behaviour: Behaviour,
@@ -60,13 +64,13 @@ pub struct P2pSad {
 }
 
 impl Executer for R2 {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r1::Bcast;
     type P2p = r1::P2p;
 
     fn execute(
-        self: Box<Self>,
+        mut self: Box<Self>,
         info: &ProtocolInfo<Self::Index>,
         bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
         p2ps_in: P2ps<Self::Index, Self::P2p>,
@@ -105,7 +109,7 @@ impl Executer for R2 {
         // verify zk proof for first message of MtA
         let zkp_complaints =
             self.peer_keygen_ids
-                .ref_map2_result(|(peer_sign_id, peer_keygen_id)| {
+                .par_ref_map2_result(|(peer_sign_id, peer_keygen_id)| {
                     let peer_ek = &self
                         .secret_key_share
                         .group()
@@ -193,7 +197,9 @@ impl Executer for R2 {
                 peer_zkp,
                 peer_ek,
                 peer_k_i_ciphertext,
-                &self.gamma_i,
+                self.gamma_i.as_ref(),
+                self.mta_randomness
+                    .take(self.secret_key_share.group(), peer_keygen_id)?,
             );
 
             corrupt!(
@@ -210,7 +216,9 @@ impl Executer for R2 {
                 peer_zkp,
                 peer_ek,
                 peer_k_i_ciphertext,
-                &self.w_i,
+                self.w_i.as_ref(),
+                self.mta_randomness
+                    .take(self.secret_key_share.group(), peer_keygen_id)?,
             )?;
 
             corrupt!(
@@ -244,6 +252,7 @@ impl Executer for R2 {
                 gamma_i: self.gamma_i,
                 Gamma_i: self.Gamma_i,
                 Gamma_i_reveal: self.Gamma_i_reveal,
+                adaptor_point: self.adaptor_point,
                 w_i: self.w_i,
                 k_i: self.k_i,
                 k_i_randomness: self.k_i_randomness,
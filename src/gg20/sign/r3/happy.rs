@@ -1,7 +1,7 @@
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 
-use super::super::{r1, r2, Peers, SignShareId};
+use super::super::{r1, r2, Peers, SignOutput, SignShareId};
 use crate::{
     collections::{FillVecMap, FullP2ps, HoleVecMap, P2ps, TypedUsize, VecMap},
     crypto_tools::{hash::Randomness, k256_serde, mta::Secret, paillier, vss, zkp::pedersen},
@@ -16,7 +16,7 @@ use crate::{
         },
     },
     sdk::{
-        api::{Signature, TofnFatal, TofnResult},
+        api::{TofnFatal, TofnResult},
         implementer_api::{serialize, Executer, ProtocolBuilder, ProtocolInfo, RoundBuilder},
     },
 };
@@ -34,11 +34,12 @@ pub(in super::super) struct R3Happy {
     pub(in super::super) peer_keygen_ids: Peers,
     pub(in super::super) all_keygen_ids: KeygenShareIds,
     pub(in super::super) my_keygen_id: TypedUsize<KeygenShareId>,
-    pub(in super::super) gamma_i: Scalar,
+    pub(in super::super) gamma_i: k256_serde::SecretScalar,
     pub(in super::super) Gamma_i: ProjectivePoint,
     pub(in super::super) Gamma_i_reveal: Randomness,
-    pub(in super::super) w_i: Scalar,
-    pub(in super::super) k_i: Scalar,
+    pub(in super::super) adaptor_point: Option<ProjectivePoint>,
+    pub(in super::super) w_i: k256_serde::SecretScalar,
+    pub(in super::super) k_i: k256_serde::SecretScalar,
     pub(in super::super) k_i_randomness: paillier::Randomness,
     pub(in super::super) beta_secrets: HoleVecMap<SignShareId, Secret>,
     pub(in super::super) nu_secrets: HoleVecMap<SignShareId, Secret>,
@@ -70,7 +71,7 @@ pub(in super::super) enum Accusation {
 }
 
 impl Executer for R3Happy {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = ();
     type P2p = r2::P2p;
@@ -130,7 +131,7 @@ impl Executer for R3Happy {
 
         let mta_complaints =
             self.peer_keygen_ids
-                .ref_map2_result(|(peer_sign_id, &peer_keygen_id)| {
+                .par_ref_map2_result(|(peer_sign_id, &peer_keygen_id)| {
                     let p2p_in = p2ps_in.get(peer_sign_id, my_sign_id)?;
 
                     let peer_stmt = paillier::zk::mta::Statement {
@@ -219,53 +220,56 @@ impl Executer for R3Happy {
             )));
         }
 
-        let alphas = self.peer_keygen_ids.ref_map2_result(|(peer_sign_id, _)| {
-            let p2p_in = p2ps_in.get(peer_sign_id, my_sign_id)?;
+        let alphas = self
+            .peer_keygen_ids
+            .par_ref_map2_result(|(peer_sign_id, _)| {
+                let p2p_in = p2ps_in.get(peer_sign_id, my_sign_id)?;
 
-            let alpha = self
-                .secret_key_share
-                .share()
-                .dk()
-                .decrypt(&p2p_in.alpha_ciphertext)
-                .to_scalar();
+                let alpha = self
+                    .secret_key_share
+                    .share()
+                    .dk()
+                    .decrypt(&p2p_in.alpha_ciphertext)
+                    .to_scalar();
 
-            Ok(alpha)
-        })?;
+                Ok(alpha)
+            })?;
 
-        let mus = self.peer_keygen_ids.ref_map2_result(|(peer_sign_id, _)| {
-            let p2p_in = p2ps_in.get(peer_sign_id, my_sign_id)?;
+        let mus = self
+            .peer_keygen_ids
+            .par_ref_map2_result(|(peer_sign_id, _)| {
+                let p2p_in = p2ps_in.get(peer_sign_id, my_sign_id)?;
 
-            let mu = self
-                .secret_key_share
-                .share()
-                .dk()
-                .decrypt(&p2p_in.mu_ciphertext)
-                .to_scalar();
+                let mu = self
+                    .secret_key_share
+                    .share()
+                    .dk()
+                    .decrypt(&p2p_in.mu_ciphertext)
+                    .to_scalar();
 
-            Ok(mu)
-        })?;
+                Ok(mu)
+            })?;
 
         // compute delta_i = k_i * gamma_i + sum_{j != i} alpha_ij + beta_ji
-        let delta_i = alphas
-            .into_iter()
-            .zip(self.beta_secrets.iter())
-            .fold(self.k_i * self.gamma_i, |acc, ((_, alpha), (_, beta))| {
-                acc + alpha + beta.beta
-            });
+        let delta_i = alphas.into_iter().zip(self.beta_secrets.iter()).fold(
+            self.k_i.as_ref() * self.gamma_i.as_ref(),
+            |acc, ((_, alpha), (_, beta))| acc + alpha + beta.beta,
+        );
 
         // many malicious behaviours require corrupt delta_i to prepare
         corrupt!(delta_i, self.corrupt_delta_i(my_sign_id, delta_i));
-        corrupt!(delta_i, self.corrupt_k_i(my_sign_id, delta_i, self.gamma_i));
+        corrupt!(
+            delta_i,
+            self.corrupt_k_i(my_sign_id, delta_i, *self.gamma_i.as_ref())
+        );
         corrupt!(delta_i, self.corrupt_alpha(my_sign_id, delta_i));
         corrupt!(delta_i, self.corrupt_beta(my_sign_id, delta_i));
 
         // compute sigma_i = k_i * w_i + sum_{j != i} mu_ij + nu_ji
-        let sigma_i = mus
-            .into_iter()
-            .zip(self.nu_secrets.iter())
-            .fold(self.k_i * self.w_i, |acc, ((_, mu), (_, nu))| {
-                acc + mu + nu.beta
-            });
+        let sigma_i = mus.into_iter().zip(self.nu_secrets.iter()).fold(
+            self.k_i.as_ref() * self.w_i.as_ref(),
+            |acc, ((_, mu), (_, nu))| acc + mu + nu.beta,
+        );
 
         corrupt!(sigma_i, self.corrupt_sigma(my_sign_id, sigma_i));
 
@@ -299,10 +303,11 @@ impl Executer for R3Happy {
                 gamma_i: self.gamma_i,
                 Gamma_i: self.Gamma_i,
                 Gamma_i_reveal: self.Gamma_i_reveal,
+                adaptor_point: self.adaptor_point,
                 k_i: self.k_i,
                 k_i_randomness: self.k_i_randomness,
-                sigma_i,
-                l_i,
+                sigma_i: sigma_i.into(),
+                l_i: l_i.into(),
                 beta_secrets: self.beta_secrets,
                 r1bcasts: self.r1bcasts,
                 r2p2ps: p2ps_in,
@@ -8,7 +8,7 @@ use crate::{
         sign::{r3::common::R3Path, KeygenShareIds},
     },
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnFatal, TofnResult},
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
         implementer_api::{log_fault_info, Executer, ProtocolBuilder, ProtocolInfo},
     },
 };
@@ -16,7 +16,7 @@ use crate::{
 use tracing::error;
 
 use super::{
-    super::{r1, r2, SignShareId},
+    super::{r1, r2, SignOutput, SignShareId},
     common::check_message_types,
 };
 
@@ -29,7 +29,7 @@ pub(in super::super) struct R3Sad {
 }
 
 impl Executer for R3Sad {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = ();
     type P2p = r2::P2p;
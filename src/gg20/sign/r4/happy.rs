@@ -8,11 +8,11 @@ use crate::{
         sign::{
             r4::{self, Bcast},
             type5_common::{BcastSadType5, MtaPlaintext, P2pSadType5},
-            KeygenShareIds,
+            AdaptorBcast, KeygenShareIds,
         },
     },
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnResult},
+        api::{Fault::ProtocolFault, TofnResult},
         implementer_api::{serialize, Executer, ProtocolBuilder, ProtocolInfo, RoundBuilder},
     },
 };
@@ -20,7 +20,7 @@ use k256::{ProjectivePoint, Scalar};
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
-use super::super::{r1, r2, r3, r5, Peers, SignShareId};
+use super::super::{r1, r2, r3, r5, Peers, SignOutput, SignShareId};
 
 #[cfg(feature = "malicious")]
 use super::super::malicious::Behaviour;
@@ -32,13 +32,14 @@ pub(in super::super) struct R4Happy {
     pub(in super::super) peer_keygen_ids: Peers,
     pub(in super::super) all_keygen_ids: KeygenShareIds,
     pub(in super::super) my_keygen_id: TypedUsize<KeygenShareId>,
-    pub(in super::super) gamma_i: Scalar,
+    pub(in super::super) gamma_i: k256_serde::SecretScalar,
     pub(in super::super) Gamma_i: ProjectivePoint,
     pub(in super::super) Gamma_i_reveal: Randomness,
-    pub(in super::super) k_i: Scalar,
+    pub(in super::super) adaptor_point: Option<ProjectivePoint>,
+    pub(in super::super) k_i: k256_serde::SecretScalar,
     pub(in super::super) k_i_randomness: paillier::Randomness,
-    pub(in super::super) sigma_i: Scalar,
-    pub(in super::super) l_i: Scalar,
+    pub(in super::super) sigma_i: k256_serde::SecretScalar,
+    pub(in super::super) l_i: k256_serde::SecretScalar,
     pub(in super::super) beta_secrets: HoleVecMap<SignShareId, Secret>,
     pub(in super::super) r1bcasts: VecMap<SignShareId, r1::Bcast>,
     pub(in super::super) r2p2ps: FullP2ps<SignShareId, r2::P2pHappy>,
@@ -52,10 +53,11 @@ pub(in super::super) struct R4Happy {
 pub struct BcastHappy {
     pub(in super::super) Gamma_i: k256_serde::ProjectivePoint,
     pub(in super::super) Gamma_i_reveal: Randomness,
+    pub(in super::super) adaptor: Option<AdaptorBcast>,
 }
 
 impl Executer for R4Happy {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r3::BcastHappy;
     type P2p = r3::P2pSad;
@@ -134,6 +136,14 @@ impl Executer for R4Happy {
         let bcast_happy = BcastHappy {
             Gamma_i: self.Gamma_i.into(),
             Gamma_i_reveal,
+            adaptor: self.adaptor_point.as_ref().map(|adaptor_point| {
+                AdaptorBcast::new(
+                    my_sign_id,
+                    adaptor_point,
+                    self.gamma_i.as_ref(),
+                    &self.Gamma_i,
+                )
+            }),
         };
 
         // compute delta_inv
@@ -168,9 +178,9 @@ impl Executer for R4Happy {
             let bcast_out = Some(serialize(&Bcast::SadType5(
                 bcast_happy,
                 BcastSadType5 {
-                    k_i: self.k_i,
+                    k_i: *self.k_i.as_ref(),
                     k_i_randomness: self.k_i_randomness.clone(),
-                    gamma_i: self.gamma_i,
+                    gamma_i: *self.gamma_i.as_ref(),
                 },
             ))?);
 
@@ -211,6 +221,7 @@ impl Executer for R4Happy {
                 r2p2ps: self.r2p2ps,
                 r3bcasts: bcasts_in,
                 delta_inv: delta_inv.unwrap(),
+                adaptor_point: self.adaptor_point,
 
                 #[cfg(feature = "malicious")]
                 behaviour: self.behaviour,
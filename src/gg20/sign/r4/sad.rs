@@ -1,20 +1,25 @@
 use alloc::boxed::Box;
 use alloc::format;
-use alloc::vec::Vec;
 
 use crate::{
     collections::{zip2, FillVecMap, FullP2ps, P2ps, VecMap},
-    crypto_tools::{paillier, vss},
-    gg20::{keygen::SecretKeyShare, sign::KeygenShareIds},
+    gg20::{
+        keygen::SecretKeyShare,
+        sign::KeygenShareIds,
+        verify_evidence::{Culprit, MtaEvidence},
+    },
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnFatal, TofnResult},
-        implementer_api::{log_fault_info, Executer, ProtocolBuilder, ProtocolInfo},
+        api::{
+            Fault::{BadMtaProof, ProtocolFault},
+            MtaFailure, TofnFatal, TofnResult,
+        },
+        implementer_api::{log_fault_info, serialize, Executer, ProtocolBuilder, ProtocolInfo},
     },
 };
 
 use tracing::{error, warn};
 
-use super::super::{r1, r2, r3, SignShareId};
+use super::super::{r1, r2, r3, SignOutput, SignShareId};
 
 #[allow(non_snake_case)]
 pub(in super::super) struct R4Sad {
@@ -25,7 +30,7 @@ pub(in super::super) struct R4Sad {
 }
 
 impl Executer for R4Sad {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r3::BcastHappy;
     type P2p = r3::P2pSad;
@@ -85,91 +90,50 @@ impl Executer for R4Sad {
             for (accused_sign_id, accusation) in accusations {
                 debug_assert_ne!(accused_sign_id, accuser_sign_id); // self accusation is impossible
 
-                let accused_keygen_id = *self.all_keygen_ids.get(accused_sign_id)?;
-                let accuser_keygen_id = *self.all_keygen_ids.get(accuser_sign_id)?;
-
-                let accuser_zkp = self
-                    .secret_key_share
-                    .group()
-                    .all_shares()
-                    .get(accuser_keygen_id)?
-                    .zkp();
-
-                let accuser_ek = self
-                    .secret_key_share
-                    .group()
-                    .all_shares()
-                    .get(accuser_keygen_id)?
-                    .ek();
-
                 let p2p = self.r2p2ps.get(accused_sign_id, accuser_sign_id)?;
+                let k_i_ciphertext = &self.r1bcasts.get(accuser_sign_id)?.k_i_ciphertext;
 
                 // check mta proofs
-                let (log_msg, correct_proof) = match accusation.mta_complaint {
-                    r3::Accusation::MtA => {
-                        let accused_stmt = paillier::zk::mta::Statement {
-                            prover_id: accused_sign_id,
-                            verifier_id: accuser_sign_id,
-                            ciphertext1: &self.r1bcasts.get(accuser_sign_id)?.k_i_ciphertext,
-                            ciphertext2: &p2p.alpha_ciphertext,
-                            ek: accuser_ek,
-                        };
-
-                        (
-                            "MtA",
-                            accuser_zkp.verify_mta_proof(&accused_stmt, &p2p.alpha_proof),
-                        )
-                    }
-                    r3::Accusation::MtAwc => {
-                        let accused_lambda_i_S = &vss::lagrange_coefficient(
-                            accused_sign_id.as_usize(),
-                            &self
-                                .all_keygen_ids
-                                .iter()
-                                .map(|(_, keygen_accused_id)| keygen_accused_id.as_usize())
-                                .collect::<Vec<_>>(),
-                        )?;
-
-                        let accused_W_i = self
-                            .secret_key_share
-                            .group()
-                            .all_shares()
-                            .get(accused_keygen_id)?
-                            .X_i()
-                            .as_ref()
-                            * accused_lambda_i_S;
-
-                        let accused_stmt = paillier::zk::mta::StatementWc {
-                            stmt: paillier::zk::mta::Statement {
-                                prover_id: accused_sign_id,
-                                verifier_id: accuser_sign_id,
-                                ciphertext1: &self.r1bcasts.get(accuser_sign_id)?.k_i_ciphertext,
-                                ciphertext2: &p2p.mu_ciphertext,
-                                ek: accuser_ek,
-                            },
-                            x_g: &accused_W_i,
-                        };
-
-                        (
-                            "MtAwc",
-                            accuser_zkp.verify_mta_proof_wc(&accused_stmt, &p2p.mu_proof),
-                        )
-                    }
+                let evidence = match accusation.mta_complaint {
+                    r3::Accusation::MtA => MtaEvidence::new_mta(
+                        self.all_keygen_ids.clone(),
+                        k_i_ciphertext.clone(),
+                        p2p.alpha_ciphertext.clone(),
+                        p2p.alpha_proof.clone(),
+                    ),
+                    r3::Accusation::MtAwc => MtaEvidence::new_mta_wc(
+                        self.all_keygen_ids.clone(),
+                        k_i_ciphertext.clone(),
+                        p2p.mu_ciphertext.clone(),
+                        p2p.mu_proof.clone(),
+                    ),
                     r3::Accusation::None => continue,
                 };
 
-                match correct_proof {
-                    true => {
+                match evidence.verify(
+                    self.secret_key_share.group(),
+                    accused_sign_id,
+                    accuser_sign_id,
+                )? {
+                    Culprit::Verifier => {
                         log_fault_info(my_sign_id, accuser_sign_id, "false r2 p2p accusation");
                         faulters.set(accuser_sign_id, ProtocolFault)?;
                     }
-                    false => {
+                    Culprit::Prover => {
                         log_fault_info(
                             my_sign_id,
                             accused_sign_id,
-                            &format!("invalid r2 p2p {} proof", log_msg),
+                            &format!("invalid r2 p2p {:?} proof", evidence.proof_kind()),
                         );
-                        faulters.set(accused_sign_id, ProtocolFault)?;
+                        let failure = MtaFailure {
+                            prover: accused_sign_id.as_usize(),
+                            verifier: accuser_sign_id.as_usize(),
+                            proof: evidence.proof_kind(),
+                            verifier_ciphertext_hash: evidence.verifier_ciphertext_hash()?,
+                            prover_ciphertext_hash: evidence.prover_ciphertext_hash()?,
+                            evidence: serialize(&evidence)?,
+                        };
+                        faulters.set(accused_sign_id, BadMtaProof(failure))?;
                     }
                 };
             }
@@ -14,7 +14,7 @@ use crate::{
         sign::{r5::common::R5Path, type5_common},
     },
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnFatal, TofnResult},
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
         implementer_api::{serialize, Executer, ProtocolBuilder, ProtocolInfo, RoundBuilder},
     },
 };
@@ -23,7 +23,10 @@ use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use super::{
-    super::{r1, r2, r3, r4, r6, KeygenShareIds, Peers, SignShareId},
+    super::{
+        r1, r2, r3, r4, r6, AdaptorNonce, KeygenShareIds, NonceShare, Peers, SignOutput,
+        SignShareId,
+    },
     common::check_message_types,
 };
 
@@ -37,16 +40,17 @@ pub(in super::super) struct R5 {
     pub(in super::super) peer_keygen_ids: Peers,
     pub(in super::super) all_keygen_ids: KeygenShareIds,
     pub(in super::super) my_keygen_id: TypedUsize<KeygenShareId>,
-    pub(in super::super) gamma_i: Scalar,
-    pub(in super::super) k_i: Scalar,
+    pub(in super::super) gamma_i: k256_serde::SecretScalar,
+    pub(in super::super) k_i: k256_serde::SecretScalar,
     pub(in super::super) k_i_randomness: paillier::Randomness,
-    pub(in super::super) sigma_i: Scalar,
-    pub(in super::super) l_i: Scalar,
+    pub(in super::super) sigma_i: k256_serde::SecretScalar,
+    pub(in super::super) l_i: k256_serde::SecretScalar,
     pub(in super::super) beta_secrets: HoleVecMap<SignShareId, Secret>,
     pub(in super::super) r1bcasts: VecMap<SignShareId, r1::Bcast>,
     pub(in super::super) r2p2ps: FullP2ps<SignShareId, r2::P2pHappy>,
     pub(in super::super) r3bcasts: VecMap<SignShareId, r3::BcastHappy>,
     pub(in super::super) delta_inv: Scalar,
+    pub(in super::super) adaptor_point: Option<ProjectivePoint>,
 
     #[cfg(feature = "malicious")]
     pub(in super::super) behaviour: Behaviour,
@@ -64,7 +68,7 @@ pub(in super::super) struct P2p {
 }
 
 impl Executer for R5 {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r4::Bcast;
     type P2p = type5_common::P2pSadType5;
@@ -131,6 +135,59 @@ impl Executer for R5 {
             return Ok(ProtocolBuilder::Done(Err(faulters)));
         }
 
+        // verify adaptor nonce shares
+        let adaptor = match self.adaptor_point {
+            Some(adaptor_point) => {
+                for (peer_sign_id, bcast) in &bcasts_in {
+                    let valid = match &bcast.adaptor {
+                        Some(adaptor) => NonceShare {
+                            Gamma_i: bcast.Gamma_i.clone(),
+                            adaptor: adaptor.clone(),
+                        }
+                        .verify(peer_sign_id, &adaptor_point),
+                        None => false,
+                    };
+                    if !valid {
+                        warn!(
+                            "peer {} says: missing or invalid adaptor nonce share from peer {}",
+                            my_sign_id, peer_sign_id
+                        );
+                        faulters.set(peer_sign_id, ProtocolFault)?;
+                    }
+                }
+                if !faulters.is_empty() {
+                    return Ok(ProtocolBuilder::Done(Err(faulters)));
+                }
+
+                let nonce_shares = bcasts_in
+                    .iter()
+                    .map(|(_, bcast)| {
+                        Ok(NonceShare {
+                            Gamma_i: bcast.Gamma_i.clone(),
+                            adaptor: bcast.adaptor.clone().ok_or(TofnFatal)?,
+                        })
+                    })
+                    .collect::<TofnResult<_>>()?;
+
+                Some(AdaptorNonce::new(self.delta_inv, nonce_shares))
+            }
+            None => {
+                for (peer_sign_id, bcast) in &bcasts_in {
+                    if bcast.adaptor.is_some() {
+                        warn!(
+                            "peer {} says: unexpected adaptor nonce share from peer {}",
+                            my_sign_id, peer_sign_id
+                        );
+                        faulters.set(peer_sign_id, ProtocolFault)?;
+                    }
+                }
+                if !faulters.is_empty() {
+                    return Ok(ProtocolBuilder::Done(Err(faulters)));
+                }
+                None
+            }
+        };
+
         let Gamma = bcasts_in
             .iter()
             .fold(ProjectivePoint::IDENTITY, |acc, (_, bcast)| {
@@ -138,7 +195,7 @@ impl Executer for R5 {
             });
 
         let R = Gamma * self.delta_inv;
-        let R_i = R * self.k_i;
+        let R_i = R * self.k_i.as_ref();
 
         // statement and witness
         let k_i_ciphertext = &self.r1bcasts.get(my_sign_id)?.k_i_ciphertext;
@@ -150,7 +207,7 @@ impl Executer for R5 {
             .ek();
 
         let wit = &zk::range::Witness {
-            msg: &self.k_i,
+            msg: self.k_i.as_ref(),
             randomness: &self.k_i_randomness,
         };
 
@@ -205,6 +262,7 @@ impl Executer for R5 {
                 r3bcasts: self.r3bcasts,
                 r4bcasts: bcasts_in,
                 R,
+                adaptor,
 
                 #[cfg(feature = "malicious")]
                 behaviour: self.behaviour,
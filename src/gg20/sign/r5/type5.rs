@@ -12,14 +12,14 @@ use crate::{
         },
     },
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnFatal, TofnResult},
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
         implementer_api::{Executer, ProtocolBuilder, ProtocolInfo},
     },
 };
 use tracing::{error, warn};
 
 use super::{
-    super::{r1, r3, SignShareId},
+    super::{r1, r3, SignOutput, SignShareId},
     common::check_message_types,
 };
 
@@ -33,7 +33,7 @@ pub(in super::super) struct R5Type5 {
 }
 
 impl Executer for R5Type5 {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r4::Bcast;
     type P2p = type5_common::P2pSadType5;
@@ -3,7 +3,7 @@ use alloc::boxed::Box;
 use super::{
     r1, r2, r3, r4, r5, r7,
     type5_common::{BcastSadType5, MtaPlaintext, P2pSadType5},
-    KeygenShareIds, Peers, SignShareId,
+    AdaptorNonce, KeygenShareIds, Peers, SignOutput, SignShareId,
 };
 use crate::{
     collections::{FillVecMap, FullP2ps, HoleVecMap, P2ps, TypedUsize, VecMap},
@@ -14,7 +14,7 @@ use crate::{
     },
     gg20::keygen::{KeygenShareId, SecretKeyShare},
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnResult},
+        api::{Fault::ProtocolFault, TofnResult, TranscriptHash},
         implementer_api::{serialize, Executer, ProtocolBuilder, ProtocolInfo, RoundBuilder},
     },
 };
@@ -32,17 +32,18 @@ pub(super) struct R6 {
     pub(super) peer_keygen_ids: Peers,
     pub(super) all_keygen_ids: KeygenShareIds,
     pub(super) my_keygen_id: TypedUsize<KeygenShareId>,
-    pub(super) gamma_i: Scalar,
-    pub(super) k_i: Scalar,
+    pub(super) gamma_i: k256_serde::SecretScalar,
+    pub(super) k_i: k256_serde::SecretScalar,
     pub(super) k_i_randomness: paillier::Randomness,
-    pub(super) sigma_i: Scalar,
-    pub(super) l_i: Scalar,
+    pub(super) sigma_i: k256_serde::SecretScalar,
+    pub(super) l_i: k256_serde::SecretScalar,
     pub(super) beta_secrets: HoleVecMap<SignShareId, mta::Secret>,
     pub(super) r1bcasts: VecMap<SignShareId, r1::Bcast>,
     pub(super) r2p2ps: FullP2ps<SignShareId, r2::P2pHappy>,
     pub(super) r3bcasts: VecMap<SignShareId, r3::BcastHappy>,
     pub(super) r4bcasts: VecMap<SignShareId, r4::BcastHappy>,
     pub(super) R: ProjectivePoint,
+    pub(super) adaptor: Option<AdaptorNonce>,
 
     #[cfg(feature = "malicious")]
     pub(super) behaviour: Behaviour,
@@ -66,6 +67,9 @@ pub enum P2p {
 pub struct BcastHappy {
     pub(super) S_i: k256_serde::ProjectivePoint,
     pub(super) S_i_proof_wc: pedersen::ProofWc,
+    /// Hash of the messages of rounds 1 to 5 as seen by the sender,
+    /// checked by every peer before it releases its signature share
+    pub(super) transcript_hash: TranscriptHash,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,7 +78,7 @@ pub struct P2pSad {
 }
 
 impl Executer for R6 {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r5::Bcast;
     type P2p = r5::P2p;
@@ -120,7 +124,7 @@ impl Executer for R6 {
         // verify proofs
         let zkp_complaints =
             self.peer_keygen_ids
-                .ref_map2_result(|(peer_sign_id, &peer_keygen_id)| {
+                .par_ref_map2_result(|(peer_sign_id, &peer_keygen_id)| {
                     let bcast = bcasts_in.get(peer_sign_id)?;
                     let zkp = &self
                         .secret_key_share
@@ -229,13 +233,13 @@ impl Executer for R6 {
                         })
                     })?;
 
-            let k_i = self.k_i;
+            let k_i = *self.k_i.as_ref();
             corrupt!(k_i, self.corrupt_k_i(my_sign_id, k_i));
 
             let bcast_out = Some(serialize(&Bcast::SadType5(BcastSadType5 {
                 k_i,
                 k_i_randomness: self.k_i_randomness.clone(),
-                gamma_i: self.gamma_i,
+                gamma_i: *self.gamma_i.as_ref(),
             }))?);
 
             let p2ps_out = Some(mta_plaintexts.map2_result(|(_, mta_plaintext)| {
@@ -260,7 +264,7 @@ impl Executer for R6 {
         }
 
         // happy path: compute S_i and proof
-        let S_i = self.R * self.sigma_i;
+        let S_i = self.R * self.sigma_i.as_ref();
         let S_i_proof_wc = pedersen::prove_wc(
             &pedersen::StatementWc {
                 stmt: pedersen::Statement {
@@ -271,8 +275,8 @@ impl Executer for R6 {
                 g: &self.R,
             },
             &pedersen::Witness {
-                msg: &self.sigma_i,
-                randomness: &self.l_i,
+                msg: self.sigma_i.as_ref(),
+                randomness: self.l_i.as_ref(),
             },
         )?;
 
@@ -281,9 +285,16 @@ impl Executer for R6 {
             self.corrupt_S_i_proof_wc(my_sign_id, S_i_proof_wc)
         );
 
+        let transcript_hash = info.transcript_hash();
+        corrupt!(
+            transcript_hash,
+            self.corrupt_transcript_hash(my_sign_id, transcript_hash)
+        );
+
         let bcast_out = Some(serialize(&Bcast::Happy(BcastHappy {
             S_i: S_i.into(),
             S_i_proof_wc,
+            transcript_hash,
         }))?);
 
         Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
@@ -300,8 +311,10 @@ impl Executer for R6 {
                 r2p2ps: self.r2p2ps,
                 r3bcasts: self.r3bcasts,
                 R: self.R,
+                adaptor: self.adaptor,
                 r5bcasts: bcasts_in,
                 r5p2ps: p2ps_in,
+                transcript_hash: info.transcript_hash(),
 
                 #[cfg(feature = "malicious")]
                 behaviour: self.behaviour,
@@ -328,7 +341,7 @@ mod malicious {
             malicious::{log_confess_info, Behaviour::*},
             SignShareId,
         },
-        sdk::api::TofnResult,
+        sdk::api::{TofnResult, TranscriptHash},
     };
 
     impl R6 {
@@ -405,6 +418,18 @@ mod malicious {
             range_proof
         }
 
+        pub fn corrupt_transcript_hash(
+            &self,
+            my_sign_id: TypedUsize<SignShareId>,
+            mut transcript_hash: TranscriptHash,
+        ) -> TranscriptHash {
+            if let R6BadTranscriptHash = self.behaviour {
+                log_confess_info(my_sign_id, &self.behaviour, "");
+                transcript_hash[0] ^= 1;
+            }
+            transcript_hash
+        }
+
         pub fn corrupt_curve_generator(
             &self,
             my_sign_id: TypedUsize<SignShareId>,
@@ -3,7 +3,7 @@ use alloc::boxed::Box;
 use crate::{
     collections::{FillVecMap, FullP2ps, P2ps, TypedUsize, VecMap},
     crypto_tools::{
-        paillier,
+        k256_serde, paillier,
         zkp::{chaum_pedersen, pedersen},
     },
     gg20::{
@@ -14,19 +14,21 @@ use crate::{
                 common::{check_message_types, R7Path},
                 Bcast, BcastHappy, BcastSadType7, P2p,
             },
-            KeygenShareIds, SignShareId,
+            AdaptorNonce, KeygenShareIds, SignOutput, SignShareId,
         },
     },
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnFatal, TofnResult},
+        api::{
+            Fault::{ProtocolFault, TranscriptMismatch},
+            TofnFatal, TofnResult, TranscriptHash,
+        },
         implementer_api::{serialize, Executer, ProtocolBuilder, ProtocolInfo, RoundBuilder},
     },
 };
-use ecdsa::elliptic_curve::{ops::Reduce, sec1::ToEncodedPoint};
 use k256::{ProjectivePoint, PublicKey, Scalar};
-use tracing::{error, warn};
+use tracing::warn;
 
-use super::super::{r1, r2, r3, r5, r6, r8, Peers};
+use super::super::{api::r_from_nonce_point, r1, r2, r3, r5, r6, r8, Peers};
 
 #[cfg(feature = "malicious")]
 use super::super::malicious::Behaviour;
@@ -38,22 +40,25 @@ pub(in super::super) struct R7Happy {
     pub(in super::super) peer_keygen_ids: Peers,
     pub(in super::super) all_keygen_ids: KeygenShareIds,
     pub(in super::super) my_keygen_id: TypedUsize<KeygenShareId>,
-    pub(in super::super) k_i: Scalar,
+    pub(in super::super) k_i: k256_serde::SecretScalar,
     pub(in super::super) k_i_randomness: paillier::Randomness,
-    pub(in super::super) sigma_i: Scalar,
+    pub(in super::super) sigma_i: k256_serde::SecretScalar,
     pub(in super::super) r1bcasts: VecMap<SignShareId, r1::Bcast>,
     pub(in super::super) r2p2ps: FullP2ps<SignShareId, r2::P2pHappy>,
     pub(in super::super) r3bcasts: VecMap<SignShareId, r3::BcastHappy>,
     pub(in super::super) R: ProjectivePoint,
+    pub(in super::super) adaptor: Option<AdaptorNonce>,
     pub(in super::super) r5bcasts: VecMap<SignShareId, r5::Bcast>,
     pub(in super::super) r5p2ps: FullP2ps<SignShareId, r5::P2p>,
+    /// Hash of the messages of rounds 1 to 5 as seen by me
+    pub(in super::super) transcript_hash: TranscriptHash,
 
     #[cfg(feature = "malicious")]
     pub(in super::super) behaviour: Behaviour,
 }
 
 impl Executer for R7Happy {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r6::Bcast;
     type P2p = r6::P2p;
@@ -115,6 +120,23 @@ impl Executer for R7Happy {
             }
         })?;
 
+        // everyone must have seen the same messages before anyone releases a signature share:
+        // a relay may have delivered different messages to different peers,
+        // eg. the same bcast with different contents.
+        // A mismatch does not show who misbehaved, so it is not a protocol fault.
+        for (peer_sign_id, bcast) in &bcasts_in {
+            if bcast.transcript_hash != self.transcript_hash {
+                warn!(
+                    "peer {} says: transcript hash of peer {} differs from mine",
+                    my_sign_id, peer_sign_id
+                );
+                faulters.set(peer_sign_id, TranscriptMismatch)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
         // verify proofs
         for (peer_sign_id, bcast) in &bcasts_in {
             let peer_stmt = &pedersen::StatementWc {
@@ -149,7 +171,7 @@ impl Executer for R7Happy {
         // malicious actor falsely claim type 7 fault by comparing against a corrupted S_i_sum
         corrupt!(S_i_sum, self.corrupt_S_i_sum(info.my_id(), S_i_sum));
 
-        let vk_as_pk: PublicKey = self.secret_key_share.group().verifying_key().into();
+        let vk_as_pk: PublicKey = self.secret_key_share.group().try_verifying_key()?.into();
         if S_i_sum != vk_as_pk.to_projective() {
             warn!("peer {} says: 'type 7' fault detected", my_sign_id);
 
@@ -172,16 +194,16 @@ impl Executer for R7Happy {
                     prover_id: my_sign_id,
                     base1: &k256::ProjectivePoint::GENERATOR,
                     base2: &self.R,
-                    target1: &(k256::ProjectivePoint::GENERATOR * self.sigma_i),
+                    target1: &(k256::ProjectivePoint::GENERATOR * self.sigma_i.as_ref()),
                     target2: bcasts_in.get(my_sign_id)?.S_i.as_ref(),
                 },
                 &chaum_pedersen::Witness {
-                    scalar: &self.sigma_i,
+                    scalar: self.sigma_i.as_ref(),
                 },
             );
 
             let bcast_out = Some(serialize(&Bcast::SadType7(BcastSadType7 {
-                k_i: self.k_i,
+                k_i: *self.k_i.as_ref(),
                 k_i_randomness: self.k_i_randomness.clone(),
                 proof,
             }))?);
@@ -203,20 +225,14 @@ impl Executer for R7Happy {
         }
 
         // compute r, s_i
-        // reference for r: https://docs.rs/k256/0.8.1/src/k256/ecdsa/sign.rs.html#223-225
-        let r = <k256::Scalar as Reduce<k256::U256>>::from_be_bytes_reduced(
-            *self
-                .R
-                .to_affine()
-                .to_encoded_point(true)
-                .x()
-                .ok_or_else(|| {
-                    error!("Invalid R point");
-                    TofnFatal
-                })?,
-        );
-
-        let s_i = self.msg_to_sign * self.k_i + r * self.sigma_i;
+        // in adaptor mode the nonce point of the signature is `T * k^{-1}` instead of `R`
+        let r = r_from_nonce_point(
+            &self
+                .adaptor
+                .as_ref()
+                .map_or(self.R, |adaptor| adaptor.R_adapted),
+        )?;
+        let s_i = self.msg_to_sign * self.k_i.as_ref() + r * self.sigma_i.as_ref();
 
         corrupt!(s_i, self.corrupt_s_i(my_sign_id, s_i));
 
@@ -228,6 +244,7 @@ impl Executer for R7Happy {
                 msg_to_sign: self.msg_to_sign,
                 R: self.R,
                 r,
+                adaptor: self.adaptor,
                 r5bcasts: self.r5bcasts,
                 r6bcasts: bcasts_in,
             }),
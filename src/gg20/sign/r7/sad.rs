@@ -5,10 +5,10 @@ use crate::{
     crypto_tools::paillier,
     gg20::{
         keygen::SecretKeyShare,
-        sign::{r7::common::R7Path, KeygenShareIds, SignShareId},
+        sign::{r7::common::R7Path, KeygenShareIds, SignOutput, SignShareId},
     },
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnFatal, TofnResult},
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
         implementer_api::{log_fault_info, Executer, ProtocolBuilder, ProtocolInfo},
     },
 };
@@ -31,7 +31,7 @@ pub(in super::super) struct R7Sad {
 }
 
 impl Executer for R7Sad {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r6::Bcast;
     type P2p = r6::P2p;
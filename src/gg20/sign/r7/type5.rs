@@ -15,14 +15,14 @@ use crate::{
         },
     },
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnFatal, TofnResult},
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
         implementer_api::{Executer, ProtocolBuilder, ProtocolInfo},
     },
 };
 use k256::ProjectivePoint;
 use tracing::{error, warn};
 
-use super::super::{r1, r3, r5, r6, SignShareId};
+use super::super::{r1, r3, r5, r6, SignOutput, SignShareId};
 
 #[allow(non_snake_case)]
 pub(in super::super) struct R7Type5 {
@@ -38,7 +38,7 @@ pub(in super::super) struct R7Type5 {
 }
 
 impl Executer for R7Type5 {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r6::Bcast;
     type P2p = r6::P2p;
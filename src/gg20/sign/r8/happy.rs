@@ -14,7 +14,7 @@ use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
 use super::{
-    super::{r5, r6, r7, SignShareId},
+    super::{r5, r6, r7, AdaptorNonce, AdaptorSignature, SignOutput, SignShareId},
     common::check_message_types,
 };
 
@@ -24,6 +24,7 @@ pub(in super::super) struct R8Happy {
     pub(in super::super) msg_to_sign: Scalar,
     pub(in super::super) R: ProjectivePoint,
     pub(in super::super) r: Scalar,
+    pub(in super::super) adaptor: Option<AdaptorNonce>,
     pub(in super::super) r5bcasts: VecMap<SignShareId, r5::Bcast>,
     pub(in super::super) r6bcasts: VecMap<SignShareId, r6::BcastHappy>,
 }
@@ -35,7 +36,7 @@ pub(in super::super) struct Bcast {
 }
 
 impl Executer for R8Happy {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r7::Bcast;
     type P2p = r7::P2p;
@@ -85,19 +86,34 @@ impl Executer for R8Happy {
             .iter()
             .fold(Scalar::ZERO, |acc, (_, bcast)| acc + bcast.s_i);
 
-        let sig = {
-            let sig = Signature::from_scalars(self.r, s).map_err(|_| {
-                error!("scalars to signature conversion failed");
-                TofnFatal
-            })?;
-            sig.normalize_s().unwrap_or(sig)
-        };
+        let pkey: PublicKey = self.secret_key_share.group().try_verifying_key()?.into();
 
-        let pkey: PublicKey = self.secret_key_share.group().verifying_key().into();
-        let point = pkey.as_affine();
-
-        if point.verify_prehashed(self.msg_to_sign, &sig).is_ok() {
-            return Ok(ProtocolBuilder::Done(Ok(sig)));
+        match self.adaptor {
+            Some(adaptor) => {
+                let adaptor_signature = AdaptorSignature::new(s, adaptor);
+                if adaptor_signature.verify_nonce_points(&pkey.to_projective(), self.msg_to_sign) {
+                    return Ok(ProtocolBuilder::Done(Ok(SignOutput::Adaptor(
+                        adaptor_signature,
+                    ))));
+                }
+            }
+            None => {
+                let sig = {
+                    let sig = Signature::from_scalars(self.r, s).map_err(|_| {
+                        error!("scalars to signature conversion failed");
+                        TofnFatal
+                    })?;
+                    sig.normalize_s().unwrap_or(sig)
+                };
+
+                if pkey
+                    .as_affine()
+                    .verify_prehashed(self.msg_to_sign, &sig)
+                    .is_ok()
+                {
+                    return Ok(ProtocolBuilder::Done(Ok(SignOutput::Signature(sig))));
+                }
+            }
         }
 
         // verify proofs
@@ -10,7 +10,7 @@ use crate::{
         sign::{r2, r8::common::R8Path, KeygenShareIds},
     },
     sdk::{
-        api::{Fault::ProtocolFault, Signature, TofnFatal, TofnResult},
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
         implementer_api::{Executer, ProtocolBuilder, ProtocolInfo},
     },
 };
@@ -19,7 +19,7 @@ use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
 use super::{
-    super::{r1, r6, r7, Peers, SignShareId},
+    super::{r1, r6, r7, Peers, SignOutput, SignShareId},
     common::check_message_types,
 };
 
@@ -42,7 +42,7 @@ pub(in super::super) struct Bcast {
 }
 
 impl Executer for R8Type7 {
-    type FinalOutput = Signature;
+    type FinalOutput = SignOutput;
     type Index = SignShareId;
     type Bcast = r7::Bcast;
     type P2p = r7::P2p;
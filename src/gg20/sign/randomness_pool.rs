@@ -0,0 +1,104 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    collections::TypedUsize,
+    crypto_tools::paillier::PrecomputedRandomness,
+    gg20::keygen::{GroupPublicInfo, KeygenShareId},
+    sdk::api::TofnResult,
+};
+
+/// Precomputed Paillier encryption randomness `r^N mod N^2` for the encryption keys of a keygen group.
+///
+/// Each sign consumes one entry for the signer's own key and two entries for the key of each other participant.
+/// Entries missing from the pool are computed on demand at the cost of one modular exponentiation each.
+///
+/// Computing entries is slow and can happen in the background between sign sessions:
+/// call [RandomnessPool::precompute] on another thread and hand the result to [RandomnessPool::push].
+/// Entries must never be reused; `new_sign_with_randomness_pool` removes every entry it uses from the pool.
+#[derive(Debug, Default)]
+pub struct RandomnessPool {
+    entries: BTreeMap<usize, Vec<PrecomputedRandomness>>,
+}
+
+impl RandomnessPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compute a single entry for the encryption key of `keygen_id` without touching any pool.
+    pub fn precompute(
+        group: &GroupPublicInfo,
+        keygen_id: TypedUsize<KeygenShareId>,
+    ) -> TofnResult<PrecomputedRandomness> {
+        Ok(group
+            .all_shares()
+            .get(keygen_id)?
+            .ek()
+            .precompute_randomness())
+    }
+
+    /// Add an entry computed by [RandomnessPool::precompute] for `keygen_id`.
+    pub fn push(&mut self, keygen_id: TypedUsize<KeygenShareId>, entry: PrecomputedRandomness) {
+        self.entries
+            .entry(keygen_id.as_usize())
+            .or_default()
+            .push(entry);
+    }
+
+    /// Compute entries for the encryption key of `keygen_id` until the pool holds `count` of them.
+    pub fn fill(
+        &mut self,
+        group: &GroupPublicInfo,
+        keygen_id: TypedUsize<KeygenShareId>,
+        count: usize,
+    ) -> TofnResult<()> {
+        while self.len(keygen_id) < count {
+            let entry = Self::precompute(group, keygen_id)?;
+            self.push(keygen_id, entry);
+        }
+        Ok(())
+    }
+
+    /// Number of entries available for the encryption key of `keygen_id`
+    pub fn len(&self, keygen_id: TypedUsize<KeygenShareId>) -> usize {
+        self.entries
+            .get(&keygen_id.as_usize())
+            .map_or(0, |entries| entries.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.values().all(|entries| entries.is_empty())
+    }
+
+    /// Remove an entry for the encryption key of `keygen_id`, or compute a fresh one if none is available.
+    pub(super) fn take(
+        &mut self,
+        group: &GroupPublicInfo,
+        keygen_id: TypedUsize<KeygenShareId>,
+    ) -> TofnResult<PrecomputedRandomness> {
+        match self
+            .entries
+            .get_mut(&keygen_id.as_usize())
+            .and_then(|entries| entries.pop())
+        {
+            Some(entry) => Ok(entry),
+            None => Self::precompute(group, keygen_id),
+        }
+    }
+
+    /// Move `count` entries for the encryption key of `keygen_id` from `self` into `other`,
+    /// computing fresh entries as needed.
+    pub(super) fn take_into(
+        &mut self,
+        other: &mut Self,
+        group: &GroupPublicInfo,
+        keygen_id: TypedUsize<KeygenShareId>,
+        count: usize,
+    ) -> TofnResult<()> {
+        for _ in 0..count {
+            let entry = self.take(group, keygen_id)?;
+            other.push(keygen_id, entry);
+        }
+        Ok(())
+    }
+}
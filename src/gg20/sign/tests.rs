@@ -8,13 +8,18 @@ use super::*;
 use crate::{
     collections::{FillVecMap, HoleVecMap, Subset, TypedUsize, VecMap},
     gg20::{
-        keygen::{tests::execute_keygen, KeygenPartyShareCounts, KeygenShareId, SecretKeyShare},
-        sign::api::{new_sign, SignShareId},
+        keygen::{
+            create_party_keypair_and_zksetup_unsafe, keygen_single_party,
+            tests::{dummy_secret_recovery_key, execute_keygen},
+            KeygenPartyShareCounts, KeygenShareId, SecretKeyShare,
+        },
+        sign::api::{new_sign, new_sign_with_randomness_pool, SignShareId},
     },
     sdk::implementer_api::{decode_message, deserialize, encode_message},
     sdk::{
-        api::{BytesVec, Fault, Protocol, Round, Signature},
+        api::{transcript_hash, BytesVec, Fault, Protocol, Round, RoundDeadlines, Signature},
         implementer_api::{serialize, ExpectedMsgTypes, MsgType},
+        local::execute_honest,
     },
 };
 use ecdsa::{
@@ -28,7 +33,7 @@ use tracing_test::traced_test;
 #[cfg(feature = "malicious")]
 use crate::gg20::sign::malicious::Behaviour::Honest;
 
-type Party = Round<Signature, SignShareId, SignPartyId, MAX_MSG_LEN>;
+type Party = Round<Signature, SignShareId, SignPartyId>;
 type Parties = Vec<Party>;
 type PartyBcast = Result<VecMap<SignShareId, BytesVec>, ()>;
 type PartyP2p = Result<VecMap<SignShareId, HoleVecMap<SignShareId, BytesVec>>, ()>;
@@ -97,6 +102,103 @@ fn basic_correctness() {
     }
 }
 
+#[test]
+fn randomness_pool() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 1);
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(2)).unwrap();
+
+    let key_share = key_shares.get(TypedUsize::from_usize(0)).unwrap();
+    let (me, peer) = (TypedUsize::from_usize(0), TypedUsize::from_usize(2));
+    let mut pool = RandomnessPool::new();
+    pool.fill(key_share.group(), me, 3).unwrap();
+    pool.fill(key_share.group(), peer, 3).unwrap();
+
+    let party = match new_sign_with_randomness_pool(
+        key_share.group(),
+        key_share.share(),
+        &sign_parties,
+        &msg_to_sign(),
+        &mut pool,
+        #[cfg(feature = "malicious")]
+        Honest,
+    )
+    .unwrap()
+    {
+        Protocol::NotDone(round) => round,
+        Protocol::Done(_) => panic!("`new_sign` returned a `Done` protocol"),
+    };
+
+    // one entry for my own encryption, two for my MtA responses to the peer
+    assert_eq!(pool.len(me), 2);
+    assert_eq!(pool.len(peer), 1);
+    let mta_randomness = &round_cast::<r2::R2>(&party).mta_randomness;
+    assert_eq!(mta_randomness.len(me), 0);
+    assert_eq!(mta_randomness.len(peer), 2);
+
+    // missing entries are computed on demand
+    let mut pool = RandomnessPool::new();
+    new_sign_with_randomness_pool(
+        key_share.group(),
+        key_share.share(),
+        &sign_parties,
+        &msg_to_sign(),
+        &mut pool,
+        #[cfg(feature = "malicious")]
+        Honest,
+    )
+    .unwrap();
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn abandoned_round_zeroize_on_drop() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 1);
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(1)).unwrap();
+
+    let key_share = key_shares.get(TypedUsize::from_usize(0)).unwrap();
+    let party = match new_sign(
+        key_share.group(),
+        key_share.share(),
+        &sign_parties,
+        &msg_to_sign(),
+        #[cfg(feature = "malicious")]
+        Honest,
+    )
+    .unwrap()
+    {
+        Protocol::NotDone(round) => round,
+        Protocol::Done(_) => panic!("`new_sign` returned a `Done` protocol"),
+    };
+
+    // a copy of round 2 that is dropped before the protocol completes
+    let r2 = round_cast::<r2::R2>(&party);
+    let abandoned = r2::R2 {
+        secret_key_share: r2.secret_key_share.clone(),
+        msg_to_sign: r2.msg_to_sign,
+        peer_keygen_ids: r2.peer_keygen_ids.clone(),
+        all_keygen_ids: r2.all_keygen_ids.clone(),
+        my_keygen_id: r2.my_keygen_id,
+        gamma_i: (*r2.gamma_i.as_ref()).into(),
+        Gamma_i: r2.Gamma_i,
+        Gamma_i_reveal: r2.Gamma_i_reveal.clone(),
+        adaptor_point: r2.adaptor_point,
+        w_i: (*r2.w_i.as_ref()).into(),
+        k_i: (*r2.k_i.as_ref()).into(),
+        k_i_randomness: r2.k_i_randomness.clone(),
+        mta_randomness: RandomnessPool::new(),
+
+        #[cfg(feature = "malicious")]
+        behaviour: Honest,
+    };
+    assert_zeroized_on_drop!(abandoned, gamma_i, w_i, k_i);
+}
+
 #[allow(non_snake_case, clippy::many_single_char_names)]
 fn execute_sign(
     key_shares: VecMap<KeygenShareId, SecretKeyShare>,
@@ -156,7 +258,7 @@ fn execute_sign(
     // TEST: secret key shares yield the pubkey
     let x = r1_parties
         .iter()
-        .map(|party| round_cast::<r2::R2>(party).w_i)
+        .map(|party| *round_cast::<r2::R2>(party).w_i.as_ref())
         .fold(k256::Scalar::ZERO, |acc, w_i| acc + w_i);
 
     let y = ProjectivePoint::GENERATOR * x;
@@ -173,17 +275,21 @@ fn execute_sign(
 
     let k = r1_parties
         .iter()
-        .map(|party| round_cast::<r2::R2>(party).k_i)
+        .map(|party| *round_cast::<r2::R2>(party).k_i.as_ref())
         .fold(k256::Scalar::ZERO, |acc, k_i| acc + k_i);
 
     let gamma = r1_parties
         .iter()
-        .map(|party| round_cast::<r2::R2>(party).gamma_i)
+        .map(|party| *round_cast::<r2::R2>(party).gamma_i.as_ref())
         .fold(k256::Scalar::ZERO, |acc, gamma_i| acc + gamma_i);
 
-    let (r2_parties, ..) = execute_round(r1_parties, 2, true, true);
+    let mut transcript = Vec::new();
 
-    let (r3_parties, ..) = execute_round(r2_parties, 3, false, true);
+    let (r2_parties, bcasts, p2ps) = execute_round(r1_parties, 2, true, true);
+    transcript.extend(round_msgs(bcasts, p2ps));
+
+    let (r3_parties, bcasts, p2ps) = execute_round(r2_parties, 3, false, true);
+    transcript.extend(round_msgs(bcasts, p2ps));
 
     // TEST: MtA for delta_i, sigma_i
     let k_gamma = r3_parties
@@ -203,12 +309,13 @@ fn execute_sign(
 
     let k_x = r3_parties
         .iter()
-        .map(|party| round_cast::<r4::R4Happy>(party).sigma_i)
+        .map(|party| *round_cast::<r4::R4Happy>(party).sigma_i.as_ref())
         .fold(k256::Scalar::ZERO, |acc, sigma_i| acc + sigma_i);
 
     assert_eq!(k_x, k * x);
 
-    let (r4_parties, ..) = execute_round(r3_parties, 4, true, false);
+    let (r4_parties, bcasts, p2ps) = execute_round(r3_parties, 4, true, false);
+    transcript.extend(round_msgs(bcasts, p2ps));
 
     // TEST: everyone correctly computed delta = k * gamma
     for party in &r4_parties {
@@ -217,7 +324,8 @@ fn execute_sign(
         assert_eq!(delta_inv * k_gamma, k256::Scalar::ONE);
     }
 
-    let (r5_parties, ..) = execute_round(r4_parties, 5, true, false);
+    let (r5_parties, bcasts, p2ps) = execute_round(r4_parties, 5, true, false);
+    transcript.extend(round_msgs(bcasts, p2ps));
 
     // TEST: everyone correctly computed R
     let R = k256::ProjectivePoint::GENERATOR * k.invert().unwrap();
@@ -227,7 +335,21 @@ fn execute_sign(
         assert_eq!(party_R, R);
     }
 
-    let (r6_parties, ..) = execute_round(r5_parties, 6, true, true);
+    let (r6_parties, bcasts, p2ps) = execute_round(r5_parties, 6, true, true);
+    transcript.extend(round_msgs(bcasts, p2ps));
+
+    // TEST: everyone confirms the transcript of all messages delivered so far
+    let transcript_hash = transcript_hash::<SignShareId, _>(&transcript).unwrap();
+    for party in &r6_parties {
+        assert_eq!(
+            round_cast::<r7::R7Happy>(party).transcript_hash,
+            transcript_hash
+        );
+        match r6_bcast(party) {
+            r6::Bcast::Happy(bcast) => assert_eq!(bcast.transcript_hash, transcript_hash),
+            r6::Bcast::SadType5(_) => panic!("unexpected r6 sad path"),
+        }
+    }
 
     let (r7_parties, ..) = execute_round(r6_parties, 7, true, false);
 
@@ -256,6 +378,153 @@ fn execute_sign(
     assert!(pub_key.verify_prehashed(m.into(), &sig).is_ok());
 }
 
+#[test]
+fn single_party_fast_path() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![3]).unwrap();
+    let party_keygen_data = create_party_keypair_and_zksetup_unsafe(
+        TypedUsize::from_usize(0),
+        &dummy_secret_recovery_key(0),
+        b"foobar",
+    )
+    .unwrap();
+    let key_shares = keygen_single_party(party_share_counts, 1, &party_keygen_data).unwrap();
+    assert_eq!(key_shares.len(), 3);
+
+    let sign_shares: Vec<_> = key_shares
+        .iter()
+        .skip(1)
+        .map(|(_, key_share)| key_share.clone())
+        .collect();
+    let msg_to_sign = msg_to_sign();
+    let sig = sign_local(&sign_shares, &msg_to_sign).unwrap();
+
+    let pub_key: PublicKey = sign_shares[0].group().verifying_key().into();
+    let m: k256::Scalar = (&msg_to_sign).into();
+    assert!(pub_key.as_affine().verify_prehashed(m, &sig).is_ok());
+
+    // threshold + 1 shares are needed
+    assert!(sign_local(&sign_shares[..1], &msg_to_sign).is_err());
+}
+
+#[test]
+fn adaptor_signature() {
+    use ecdsa::elliptic_curve::Field;
+
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 1);
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(2)).unwrap();
+
+    let adaptor_secret = k256::Scalar::random(rand::thread_rng());
+    let adaptor_point = ProjectivePoint::GENERATOR * adaptor_secret;
+    let msg_to_sign = msg_to_sign();
+
+    let parties: VecMap<SignShareId, _> = [0, 2]
+        .iter()
+        .map(|&i| {
+            let key_share = key_shares.get(TypedUsize::from_usize(i)).unwrap();
+            new_adaptor_sign(
+                key_share.group(),
+                key_share.share(),
+                &sign_parties,
+                &msg_to_sign,
+                &adaptor_point,
+                #[cfg(feature = "malicious")]
+                Honest,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let adaptor_signatures = execute_honest(parties).unwrap().into_vec();
+    assert_eq!(adaptor_signatures[0], adaptor_signatures[1]);
+    let adaptor_signature =
+        AdaptorSignature::from_bytes(&adaptor_signatures[0].to_bytes().unwrap()).unwrap();
+
+    let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+    assert!(adaptor_signature.verify(&group.verifying_key(), &msg_to_sign, &adaptor_point));
+    assert!(!adaptor_signature.verify(
+        &group.verifying_key(),
+        &msg_to_sign,
+        &(adaptor_point + ProjectivePoint::GENERATOR)
+    ));
+
+    // only the adaptor secret completes the signature
+    assert!(adaptor_signature
+        .complete(&(adaptor_secret + k256::Scalar::ONE))
+        .is_err());
+    let sig = adaptor_signature.complete(&adaptor_secret).unwrap();
+    let pub_key: PublicKey = group.verifying_key().into();
+    let m: k256::Scalar = (&msg_to_sign).into();
+    assert!(pub_key.as_affine().verify_prehashed(m, &sig).is_ok());
+
+    assert_eq!(
+        adaptor_signature.extract_secret(&sig).unwrap(),
+        adaptor_secret
+    );
+}
+
+#[test]
+fn deterministic_nonce() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 1);
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(2)).unwrap();
+
+    let sign = |session_id: &[u8]| -> Vec<Signature> {
+        let parties = [0, 2]
+            .iter()
+            .map(|&i| {
+                let key_share = key_shares.get(TypedUsize::from_usize(i)).unwrap();
+                new_sign_deterministic(
+                    key_share.group(),
+                    key_share.share(),
+                    &sign_parties,
+                    &msg_to_sign(),
+                    session_id,
+                    #[cfg(feature = "malicious")]
+                    Honest,
+                )
+                .unwrap()
+            })
+            .collect();
+        execute_honest(parties).unwrap().into_vec()
+    };
+
+    // same session id => same signature despite fresh Paillier randomness
+    let signatures = sign(b"session 0");
+    assert_eq!(signatures[0], signatures[1]);
+    assert_eq!(sign(b"session 0")[0], signatures[0]);
+    assert_ne!(sign(b"session 1")[0], signatures[0]);
+
+    let pub_key: PublicKey = key_shares
+        .get(TypedUsize::from_usize(0))
+        .unwrap()
+        .group()
+        .verifying_key()
+        .into();
+    let m: k256::Scalar = (&msg_to_sign()).into();
+    assert!(pub_key
+        .as_affine()
+        .verify_prehashed(m, &signatures[0])
+        .is_ok());
+
+    // session id is too short
+    let key_share = key_shares.get(TypedUsize::from_usize(0)).unwrap();
+    assert!(new_sign_deterministic(
+        key_share.group(),
+        key_share.share(),
+        &sign_parties,
+        &msg_to_sign(),
+        b"",
+        #[cfg(feature = "malicious")]
+        Honest,
+    )
+    .is_err());
+}
+
 #[test]
 #[traced_test]
 /// This unit test is now redundant.
@@ -332,12 +601,9 @@ fn malicious_delta_inverse() {
         })
         .fold(k256::Scalar::ZERO, |acc, delta_i| acc + delta_i);
 
-    let share_0_bcast_out: r3::BcastHappy = deserialize(
-        &decode_message::<SignShareId>(r3_shares[0].bcast_out().unwrap())
-            .unwrap()
-            .payload,
-    )
-    .unwrap();
+    let share_0_wire_bytes =
+        decode_message::<SignShareId>(r3_shares[0].bcast_out().unwrap()).unwrap();
+    let share_0_bcast_out: r3::BcastHappy = deserialize(&share_0_wire_bytes.payload).unwrap();
 
     *r3_shares[0].bcast_out_mut() = Some(
         encode_message(
@@ -347,6 +613,7 @@ fn malicious_delta_inverse() {
             })
             .unwrap(),
             TypedUsize::<SignShareId>::from_usize(0),
+            share_0_wire_bytes.round,
             MsgType::Bcast,
             ExpectedMsgTypes::BcastOnly,
         )
@@ -387,6 +654,91 @@ fn malicious_delta_inverse() {
     }
 }
 
+#[test]
+fn transcript_hash_mismatch() {
+    let msg_to_sign = msg_to_sign();
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 1);
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(2)).unwrap();
+
+    let r1_parties: Vec<_> = [0, 2]
+        .iter()
+        .map(|&i| {
+            let key_share = key_shares.get(TypedUsize::from_usize(i)).unwrap();
+            match new_sign(
+                key_share.group(),
+                key_share.share(),
+                &sign_parties,
+                &msg_to_sign,
+                #[cfg(feature = "malicious")]
+                Honest,
+            )
+            .unwrap()
+            {
+                Protocol::NotDone(round) => round,
+                Protocol::Done(_) => panic!("`new_sign` returned a `Done` protocol"),
+            }
+        })
+        .collect();
+
+    let (r2_parties, ..) = execute_round(r1_parties, 2, true, true);
+    let (r3_parties, ..) = execute_round(r2_parties, 3, false, true);
+    let (r4_parties, ..) = execute_round(r3_parties, 4, true, false);
+    let (r5_parties, ..) = execute_round(r4_parties, 5, true, false);
+    let (mut r6_parties, ..) = execute_round(r5_parties, 6, true, true);
+
+    // the 1st share claims to have seen a different transcript
+    let mut bcast = match r6_bcast(&r6_parties[1]) {
+        r6::Bcast::Happy(bcast) => bcast,
+        r6::Bcast::SadType5(_) => panic!("unexpected r6 sad path"),
+    };
+    bcast.transcript_hash[0] ^= 1;
+    *r6_parties[1].bcast_out_mut() = Some(
+        encode_message(
+            serialize(&r6::Bcast::Happy(bcast)).unwrap(),
+            TypedUsize::<SignShareId>::from_usize(1),
+            decode_message::<SignShareId>(r6_parties[1].bcast_out().unwrap())
+                .unwrap()
+                .round,
+            MsgType::Bcast,
+            ExpectedMsgTypes::BcastOnly,
+        )
+        .unwrap(),
+    );
+
+    // TEST: nobody releases a signature share and everyone reports the 1st share's mismatch
+    let mut expected_faulters = FillVecMap::with_size(sign_parties.member_count());
+    expected_faulters
+        .set(TypedUsize::from_usize(1), Fault::TranscriptMismatch)
+        .unwrap();
+    for result in execute_final_round(r6_parties, 7, true, false) {
+        assert_eq!(result.unwrap_err(), expected_faulters);
+    }
+}
+
+/// All messages delivered in a round
+fn round_msgs(bcasts: PartyBcast, p2ps: PartyP2p) -> impl Iterator<Item = BytesVec> {
+    let bcasts = bcasts
+        .into_iter()
+        .flat_map(|bcasts| bcasts.into_iter().map(|(_, bcast)| bcast));
+    let p2ps = p2ps.into_iter().flat_map(|p2ps| {
+        p2ps.into_iter()
+            .flat_map(|(_, p2ps)| p2ps.into_iter().map(|(_, p2p)| p2p))
+    });
+    bcasts.chain(p2ps)
+}
+
+fn r6_bcast(party: &Party) -> r6::Bcast {
+    deserialize(
+        &decode_message::<SignShareId>(party.bcast_out().unwrap())
+            .unwrap()
+            .payload,
+    )
+    .unwrap()
+}
+
 fn round_cast<T: 'static>(party: &Party) -> &T {
     return party.round_as_any().downcast_ref::<T>().unwrap();
 }
@@ -512,3 +864,31 @@ fn retrieve_and_set_p2ps(parties: &mut Parties, expect_p2p: bool, round_num: usi
 
     Ok(all_p2ps.into_iter().map(|(_, (_, msg))| msg).collect())
 }
+
+#[test]
+fn builder() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 1);
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(1)).unwrap();
+    let key_share = key_shares.get(TypedUsize::from_usize(0)).unwrap();
+    let msg = msg_to_sign();
+    let builder = || SignBuilder::new(key_share.group(), key_share.share(), &sign_parties, &msg);
+
+    let deadline = core::time::Duration::from_secs(30);
+    let mut pool = RandomnessPool::new();
+    match builder()
+        .session_id(b"session 0")
+        .randomness_pool(&mut pool)
+        .deadlines(RoundDeadlines::new(deadline))
+        .build()
+        .unwrap()
+    {
+        Protocol::NotDone(round) => assert_eq!(round.deadline(), Some(deadline)),
+        Protocol::Done(_) => panic!("sign done too early"),
+    }
+
+    // options are checked as in the `new_sign*` functions
+    assert!(builder().session_id(b"").build().is_err());
+}
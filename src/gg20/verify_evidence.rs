@@ -0,0 +1,190 @@
+//! Verify fault evidence exported by gg20 protocols using only public group data.
+//!
+//! Nothing here depends on protocol execution or share secrets:
+//! an auditor or an on-chain runtime can check a [Fault::BadMtaProof](crate::sdk::api::Fault::BadMtaProof)
+//! against the [GroupPublicInfo] of the key with [verify_mta_failure].
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{error, warn};
+
+use crate::{
+    collections::TypedUsize,
+    crypto_tools::{
+        paillier::{self, zk::mta},
+        vss,
+    },
+    gg20::{
+        keygen::GroupPublicInfo,
+        sign::{KeygenShareIds, SignShareId},
+    },
+    sdk::{
+        api::{MtaFailure, MtaProof, TofnFatal, TofnResult},
+        implementer_api::{deserialize, serialize},
+    },
+};
+
+/// Public data of an MtA from one sign share to another, enough to verify the proof of the prover.
+/// Exported as [MtaFailure::evidence].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MtaEvidence {
+    all_keygen_ids: KeygenShareIds,
+    verifier_ciphertext: paillier::Ciphertext,
+    prover_ciphertext: paillier::Ciphertext,
+    proof: Proof,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Proof {
+    MtA(mta::Proof),
+    MtAwc(mta::ProofWc),
+}
+
+/// The share at fault according to verified evidence
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Culprit {
+    /// The proof is invalid
+    Prover,
+    /// The proof is valid: the verifier made a false accusation
+    Verifier,
+}
+
+impl MtaEvidence {
+    pub(crate) fn new_mta(
+        all_keygen_ids: KeygenShareIds,
+        verifier_ciphertext: paillier::Ciphertext,
+        prover_ciphertext: paillier::Ciphertext,
+        proof: mta::Proof,
+    ) -> Self {
+        Self {
+            all_keygen_ids,
+            verifier_ciphertext,
+            prover_ciphertext,
+            proof: Proof::MtA(proof),
+        }
+    }
+
+    pub(crate) fn new_mta_wc(
+        all_keygen_ids: KeygenShareIds,
+        verifier_ciphertext: paillier::Ciphertext,
+        prover_ciphertext: paillier::Ciphertext,
+        proof: mta::ProofWc,
+    ) -> Self {
+        Self {
+            all_keygen_ids,
+            verifier_ciphertext,
+            prover_ciphertext,
+            proof: Proof::MtAwc(proof),
+        }
+    }
+
+    pub fn proof_kind(&self) -> MtaProof {
+        match self.proof {
+            Proof::MtA(_) => MtaProof::MtA,
+            Proof::MtAwc(_) => MtaProof::MtAwc,
+        }
+    }
+
+    /// SHA-256 of the serialized ciphertext the verifier sent to start the MtA
+    pub fn verifier_ciphertext_hash(&self) -> TofnResult<[u8; 32]> {
+        ciphertext_hash(&self.verifier_ciphertext)
+    }
+
+    /// SHA-256 of the serialized ciphertext the prover replied with
+    pub fn prover_ciphertext_hash(&self) -> TofnResult<[u8; 32]> {
+        ciphertext_hash(&self.prover_ciphertext)
+    }
+
+    /// Verify the proof of the MtA from sign share `prover` to sign share `verifier` of a key with public info `group`.
+    /// Return `TofnFatal` if the evidence is inconsistent with `group`.
+    #[allow(non_snake_case)]
+    pub fn verify(
+        &self,
+        group: &GroupPublicInfo,
+        prover: TypedUsize<SignShareId>,
+        verifier: TypedUsize<SignShareId>,
+    ) -> TofnResult<Culprit> {
+        if prover == verifier {
+            error!("prover and verifier are both sign share {}", prover);
+            return Err(TofnFatal);
+        }
+        if self.all_keygen_ids.len() <= group.threshold() {
+            error!(
+                "{} sign shares for threshold {}",
+                self.all_keygen_ids.len(),
+                group.threshold()
+            );
+            return Err(TofnFatal);
+        }
+
+        let prover_keygen_id = *self.all_keygen_ids.get(prover)?;
+        let verifier_share = group
+            .all_shares()
+            .get(*self.all_keygen_ids.get(verifier)?)?;
+
+        let stmt = mta::Statement {
+            prover_id: prover,
+            verifier_id: verifier,
+            ciphertext1: &self.verifier_ciphertext,
+            ciphertext2: &self.prover_ciphertext,
+            ek: verifier_share.ek(),
+        };
+
+        let valid = match &self.proof {
+            Proof::MtA(proof) => verifier_share.zkp().verify_mta_proof(&stmt, proof),
+            Proof::MtAwc(proof) => {
+                let indices: Vec<usize> = self
+                    .all_keygen_ids
+                    .iter()
+                    .map(|(_, keygen_id)| keygen_id.as_usize())
+                    .collect();
+                let prover_lambda_i_S = vss::lagrange_coefficient(prover.as_usize(), &indices)?;
+                let prover_W_i =
+                    group.all_shares().get(prover_keygen_id)?.X_i().as_ref() * &prover_lambda_i_S;
+
+                verifier_share.zkp().verify_mta_proof_wc(
+                    &mta::StatementWc {
+                        stmt,
+                        x_g: &prover_W_i,
+                    },
+                    proof,
+                )
+            }
+        };
+
+        Ok(if valid {
+            Culprit::Verifier
+        } else {
+            Culprit::Prover
+        })
+    }
+}
+
+/// Verify the evidence in `failure` against the public info `group` of the signing key:
+/// the evidence must match the proof kind and ciphertext hashes of `failure`.
+/// Return `TofnFatal` if the evidence is malformed or inconsistent.
+pub fn verify_mta_failure(group: &GroupPublicInfo, failure: &MtaFailure) -> TofnResult<Culprit> {
+    let evidence: MtaEvidence = deserialize(&failure.evidence).ok_or_else(|| {
+        error!("failed to deserialize MtA evidence");
+        TofnFatal
+    })?;
+
+    if evidence.proof_kind() != failure.proof
+        || evidence.verifier_ciphertext_hash()? != failure.verifier_ciphertext_hash
+        || evidence.prover_ciphertext_hash()? != failure.prover_ciphertext_hash
+    {
+        warn!("MtA evidence does not match the reported failure");
+        return Err(TofnFatal);
+    }
+
+    evidence.verify(
+        group,
+        TypedUsize::from_usize(failure.prover),
+        TypedUsize::from_usize(failure.verifier),
+    )
+}
+
+fn ciphertext_hash(ciphertext: &paillier::Ciphertext) -> TofnResult<[u8; 32]> {
+    Ok(Sha256::digest(&serialize(ciphertext)?).into())
+}
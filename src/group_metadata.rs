@@ -0,0 +1,205 @@
+//! Non-secret group metadata shared by the [gg20](crate::gg20) and [multisig](crate::multisig) backends,
+//! so that services running both can keep a single registry of groups.
+use alloc::vec::Vec;
+
+use k256::ecdsa::VerifyingKey;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "protocols")]
+use crate::{collections::VecMap, gg20, multisig, sdk::api::TofnFatal};
+use crate::{
+    crypto_tools::k256_serde,
+    sdk::api::{PartyShareCounts, TofnResult},
+};
+#[cfg(feature = "protocols")]
+use tracing::error;
+
+/// The protocol that produced a group
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Backend {
+    Gg20,
+    Multisig,
+}
+
+/// Metadata of a group that both backends understand
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupMetadata {
+    backend: Backend,
+    party_share_counts: Vec<usize>,
+    threshold: usize,
+    share_pubkeys: Vec<k256_serde::ProjectivePoint>,
+    verifying_key: Option<k256_serde::ProjectivePoint>,
+}
+
+impl GroupMetadata {
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Party share counts typed for either backend, eg. `party_share_counts::<gg20::keygen::KeygenPartyId>()`
+    pub fn party_share_counts<P>(&self) -> TofnResult<PartyShareCounts<P>> {
+        PartyShareCounts::from_vec(self.party_share_counts.clone())
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Public info of each signer in keygen share id order:
+    /// `X_i` for gg20, the pubkey of each share for multisig
+    pub fn share_pubkeys(&self) -> &[k256_serde::ProjectivePoint] {
+        &self.share_pubkeys
+    }
+
+    /// Group verifying key. Multisig groups have none.
+    pub fn verifying_key(&self) -> Option<VerifyingKey> {
+        self.verifying_key.as_ref().and_then(|point| {
+            k256::PublicKey::from_affine(point.as_ref().to_affine())
+                .ok()
+                .map(VerifyingKey::from)
+        })
+    }
+
+    /// Rebuild the group of a multisig key.
+    #[cfg(feature = "protocols")]
+    /// Fail for other backends: their share pubkeys do not belong to multisig key shares.
+    pub fn to_multisig(&self) -> TofnResult<multisig::keygen::GroupPublicInfo> {
+        if self.backend != Backend::Multisig {
+            error!(
+                "cannot rebuild a multisig group from {:?} metadata",
+                self.backend
+            );
+            return Err(TofnFatal);
+        }
+        let party_share_counts = self.party_share_counts()?;
+        if party_share_counts.total_share_count() != self.share_pubkeys.len() {
+            error!(
+                "total share count {} disagrees with pubkey count {}",
+                party_share_counts.total_share_count(),
+                self.share_pubkeys.len()
+            );
+            return Err(TofnFatal);
+        }
+        Ok(multisig::keygen::GroupPublicInfo::new(
+            party_share_counts,
+            self.threshold,
+            VecMap::from_vec(self.share_pubkeys.clone()),
+        ))
+    }
+}
+
+#[cfg(feature = "protocols")]
+fn party_share_count_vec<P>(party_share_counts: &PartyShareCounts<P>) -> Vec<usize> {
+    party_share_counts.iter().map(|(_, &count)| count).collect()
+}
+
+#[cfg(feature = "protocols")]
+impl From<&gg20::keygen::GroupPublicInfo> for GroupMetadata {
+    fn from(group: &gg20::keygen::GroupPublicInfo) -> Self {
+        Self {
+            backend: Backend::Gg20,
+            party_share_counts: party_share_count_vec(group.party_share_counts()),
+            threshold: group.threshold(),
+            share_pubkeys: group
+                .all_shares()
+                .iter()
+                .map(|(_, share)| share.X_i().clone())
+                .collect(),
+            verifying_key: Some(
+                k256::PublicKey::from(group.verifying_key())
+                    .to_projective()
+                    .into(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "protocols")]
+impl From<&gg20::keygen::SecretKeyShare> for GroupMetadata {
+    fn from(key_share: &gg20::keygen::SecretKeyShare) -> Self {
+        key_share.group().into()
+    }
+}
+
+#[cfg(feature = "protocols")]
+impl From<&multisig::keygen::GroupPublicInfo> for GroupMetadata {
+    fn from(group: &multisig::keygen::GroupPublicInfo) -> Self {
+        Self {
+            backend: Backend::Multisig,
+            party_share_counts: party_share_count_vec(group.party_share_counts()),
+            threshold: group.threshold(),
+            share_pubkeys: group
+                .all_pubkeys()
+                .iter()
+                .map(|(_, pubkey)| pubkey.clone())
+                .collect(),
+            verifying_key: None,
+        }
+    }
+}
+
+#[cfg(feature = "protocols")]
+impl From<&multisig::keygen::SecretKeyShare> for GroupMetadata {
+    fn from(key_share: &multisig::keygen::SecretKeyShare) -> Self {
+        key_share.group().into()
+    }
+}
+
+#[cfg(all(test, feature = "protocols"))]
+mod tests {
+    use super::{Backend, GroupMetadata};
+    use crate::{
+        collections::TypedUsize,
+        gg20, multisig,
+        sdk::{
+            api::{deserialize, serialize},
+            local::keygen_unsafe,
+        },
+    };
+
+    #[test]
+    fn multisig_round_trip() {
+        let party_share_counts =
+            multisig::keygen::KeygenPartyShareCounts::from_vec(alloc::vec![2, 1]).unwrap();
+        let key_shares = multisig::keygen::tests::execute_keygen(&party_share_counts, 1);
+        let key_share = key_shares.get(TypedUsize::from_usize(0)).unwrap();
+
+        let metadata = GroupMetadata::from(key_share);
+        assert_eq!(metadata.backend(), Backend::Multisig);
+        assert!(metadata.verifying_key().is_none());
+        assert_eq!(metadata.to_multisig().unwrap(), *key_share.group());
+
+        let bytes = serialize(&metadata).unwrap();
+        assert_eq!(deserialize::<GroupMetadata>(&bytes).unwrap(), metadata);
+    }
+
+    #[test]
+    fn gg20() {
+        let party_share_counts =
+            gg20::keygen::KeygenPartyShareCounts::from_vec(alloc::vec![1, 2]).unwrap();
+        let key_shares = keygen_unsafe(party_share_counts.clone(), 1).unwrap();
+        let key_share = key_shares.get(TypedUsize::from_usize(0)).unwrap();
+
+        let metadata = GroupMetadata::from(key_share);
+        assert_eq!(metadata.backend(), Backend::Gg20);
+        assert_eq!(
+            metadata.verifying_key(),
+            Some(key_share.group().verifying_key())
+        );
+        assert_eq!(
+            metadata
+                .party_share_counts::<gg20::keygen::KeygenPartyId>()
+                .unwrap(),
+            party_share_counts
+        );
+        for ((_, share), pubkey) in key_share
+            .group()
+            .all_shares()
+            .iter()
+            .zip(metadata.share_pubkeys())
+        {
+            assert_eq!(share.X_i(), pubkey);
+        }
+        assert!(metadata.to_multisig().is_err());
+    }
+}
@@ -0,0 +1,393 @@
+//! Keystore for the key shares and group metadata of gg20 keys,
+//! as an alternative to a directory of share files.
+//!
+//! Everything is kept in a single SQLite file encrypted with SQLCipher under a passphrase.
+//! Keys are stored under a caller-chosen name.
+use std::path::Path;
+
+use alloc::{string::String, vec::Vec};
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{
+    collections::{TypedUsize, VecMap},
+    gg20::keygen::{KeygenPartyId, KeygenShareId, SecretKeyShare},
+    group_metadata::GroupMetadata,
+    sdk::api::{deserialize, serialize, PartyShareCounts},
+};
+
+/// Schema changes, applied in order to bring a keystore up to date.
+/// The number of changes applied so far is stored in SQLite's `user_version`.
+const MIGRATIONS: &[&str] = &[SCHEMA_V1, SCHEMA_V2];
+
+const SCHEMA_V1: &str = "
+    CREATE TABLE keys (
+        name TEXT PRIMARY KEY NOT NULL,
+        threshold INTEGER NOT NULL,
+        share_count INTEGER NOT NULL,
+        group_pubkey TEXT NOT NULL,
+        party_share_counts BLOB NOT NULL,
+        group_metadata BLOB NOT NULL
+    );
+    CREATE TABLE shares (
+        key_name TEXT NOT NULL REFERENCES keys(name) ON DELETE CASCADE,
+        share_index INTEGER NOT NULL,
+        share BLOB NOT NULL,
+        PRIMARY KEY (key_name, share_index)
+    );
+";
+
+/// Uids of the keygen parties, as in tofnd's `KeygenInit`
+const SCHEMA_V2: &str = "
+    CREATE TABLE party_uids (
+        key_name TEXT NOT NULL REFERENCES keys(name) ON DELETE CASCADE,
+        party_id INTEGER NOT NULL,
+        uid TEXT NOT NULL,
+        PRIMARY KEY (key_name, party_id)
+    );
+";
+
+/// Summary of a stored key, as returned by [Keystore::list]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyInfo {
+    pub name: String,
+    pub threshold: usize,
+    pub share_count: usize,
+    /// SEC1 compressed, hex-encoded
+    pub group_pubkey: String,
+}
+
+pub struct Keystore {
+    conn: Connection,
+}
+
+impl Keystore {
+    /// Open the keystore at `path`, creating it if it does not exist.
+    /// Fail if `passphrase` does not decrypt an existing keystore.
+    pub fn open(path: impl AsRef<Path>, passphrase: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .with_context(|| alloc::format!("opening keystore {}", path.display()))?;
+        conn.pragma_update(None, "key", passphrase)?;
+        Self::init(conn).with_context(|| {
+            alloc::format!(
+                "{} is not a keystore or the passphrase is wrong",
+                path.display()
+            )
+        })
+    }
+
+    /// Re-encrypt the whole keystore under `new_passphrase`; the old passphrase no longer opens it.
+    /// Shares are re-wrapped as they are, no protocol is run.
+    pub fn rekey(&mut self, new_passphrase: &str) -> Result<()> {
+        if new_passphrase.is_empty() {
+            return Err(anyhow!(
+                "refusing to rekey the keystore to an empty passphrase"
+            ));
+        }
+        self.conn.pragma_update(None, "rekey", new_passphrase)?;
+        Ok(())
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        // the first read of an encrypted database fails if the key is wrong
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        let migrations = MIGRATIONS.get(version..).ok_or_else(|| {
+            anyhow!(
+                "unsupported keystore version {}, expected at most {}",
+                version,
+                MIGRATIONS.len()
+            )
+        })?;
+        for migration in migrations {
+            conn.execute_batch(migration)?;
+        }
+        conn.pragma_update(None, "user_version", MIGRATIONS.len())?;
+        Ok(Self { conn })
+    }
+
+    /// Store the `secret_key_shares` of a new key under `name`, eg. all shares from ceygen
+    /// or only those of one party from keygen.
+    /// Fail if there already is a key called `name`.
+    pub fn insert_key(
+        &mut self,
+        name: &str,
+        party_share_counts: &PartyShareCounts<KeygenPartyId>,
+        secret_key_shares: &VecMap<KeygenShareId, SecretKeyShare>,
+    ) -> Result<()> {
+        let group = secret_key_shares
+            .iter()
+            .next()
+            .ok_or_else(|| anyhow!("no key shares to store"))?
+            .1
+            .group();
+
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO keys (name, threshold, share_count, group_pubkey, party_share_counts, group_metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                name,
+                group.threshold() as i64,
+                group.share_count() as i64,
+                group.pubkey_compressed_hex(),
+                to_bytes(party_share_counts)?,
+                to_bytes(&GroupMetadata::from(group))?,
+            ],
+        )
+        .with_context(|| alloc::format!("storing key `{}`", name))?;
+        for (_, share) in secret_key_shares.iter() {
+            let index = share.share().index();
+            tx.execute(
+                "INSERT INTO shares (key_name, share_index, share) VALUES (?1, ?2, ?3)",
+                params![
+                    name,
+                    index.as_usize() as i64,
+                    share.to_bytes().map_err(|err| anyhow!(
+                        "serializing share {}: {}",
+                        index,
+                        err
+                    ))?,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record the uids of the keygen parties of the key `name`, in party id order.
+    pub fn set_party_uids(&mut self, name: &str, party_uids: &[String]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM party_uids WHERE key_name = ?1", params![name])?;
+        for (party_id, uid) in party_uids.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO party_uids (key_name, party_id, uid) VALUES (?1, ?2, ?3)",
+                params![name, party_id as i64, uid],
+            )
+            .with_context(|| alloc::format!("storing party uids of key `{}`", name))?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Uids of the keygen parties of the key `name` in party id order; empty if none were recorded.
+    pub fn party_uids(&self, name: &str) -> Result<Vec<String>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT uid FROM party_uids WHERE key_name = ?1 ORDER BY party_id")?;
+        let uids = statement
+            .query_map(params![name], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(uids)
+    }
+
+    pub fn party_share_counts(&self, name: &str) -> Result<PartyShareCounts<KeygenPartyId>> {
+        from_bytes(&self.key_column(name, "party_share_counts")?)
+    }
+
+    pub fn group_metadata(&self, name: &str) -> Result<GroupMetadata> {
+        from_bytes(&self.key_column(name, "group_metadata")?)
+    }
+
+    /// Indices of the stored shares of the key `name`
+    pub fn share_indices(&self, name: &str) -> Result<Vec<TypedUsize<KeygenShareId>>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT share_index FROM shares WHERE key_name = ?1 ORDER BY share_index")?;
+        let indices = statement
+            .query_map(params![name], |row| {
+                Ok(TypedUsize::from_usize(row.get::<_, i64>(0)? as usize))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(indices)
+    }
+
+    pub fn secret_key_share(
+        &self,
+        name: &str,
+        index: TypedUsize<KeygenShareId>,
+    ) -> Result<SecretKeyShare> {
+        let bytes: Vec<u8> = self
+            .conn
+            .query_row(
+                "SELECT share FROM shares WHERE key_name = ?1 AND share_index = ?2",
+                params![name, index.as_usize() as i64],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("key `{}` has no share {}", name, index))?;
+        SecretKeyShare::from_bytes_any_version(&bytes)
+            .map_err(|err| anyhow!("deserializing share {} of `{}`: {}", index, name, err))
+    }
+
+    /// All stored keys, sorted by name
+    pub fn list(&self) -> Result<Vec<KeyInfo>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT name, threshold, share_count, group_pubkey FROM keys ORDER BY name")?;
+        let keys = statement
+            .query_map([], |row| {
+                Ok(KeyInfo {
+                    name: row.get(0)?,
+                    threshold: row.get::<_, i64>(1)? as usize,
+                    share_count: row.get::<_, i64>(2)? as usize,
+                    group_pubkey: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(keys)
+    }
+
+    /// Delete the key `name` with all its shares.
+    /// Return `false` if there is no such key.
+    pub fn delete(&mut self, name: &str) -> Result<bool> {
+        Ok(self
+            .conn
+            .execute("DELETE FROM keys WHERE name = ?1", params![name])?
+            > 0)
+    }
+
+    fn key_column(&self, name: &str, column: &str) -> Result<Vec<u8>> {
+        self.conn
+            .query_row(
+                &alloc::format!("SELECT {} FROM keys WHERE name = ?1", column),
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("no key `{}` in keystore", name))
+    }
+}
+
+fn to_bytes<T: serde::Serialize>(value: &T) -> Result<Vec<u8>> {
+    serialize(value).map_err(|err| anyhow!("serialization failure: {}", err))
+}
+
+fn from_bytes<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    deserialize(bytes).ok_or_else(|| anyhow!("deserialization failure"))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use bincode::Options;
+
+    use super::*;
+    use crate::gg20::ceygen::{
+        ceygen_with_keygen_data, create_party_keypair_and_zksetup_unsafe, dummy_secret_recovery_key,
+    };
+
+    fn shares() -> (
+        PartyShareCounts<KeygenPartyId>,
+        VecMap<KeygenShareId, SecretKeyShare>,
+    ) {
+        let party_keygen_data = (0..3)
+            .map(|i| {
+                let party_id = TypedUsize::from_usize(i);
+                create_party_keypair_and_zksetup_unsafe(
+                    party_id,
+                    &dummy_secret_recovery_key(party_id),
+                    b"tofn-keystore",
+                )
+                .unwrap()
+            })
+            .collect();
+        let alice_key = k256::Scalar::from(42u32).to_bytes();
+        let (party_share_counts, shares) =
+            ceygen_with_keygen_data(1, &alice_key, &party_keygen_data).unwrap();
+
+        let bincode = bincode::DefaultOptions::new();
+        (
+            bincode.deserialize(&party_share_counts).unwrap(),
+            shares
+                .iter()
+                .map(|(_, bytes)| bincode.deserialize(bytes).unwrap())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn insert_list_delete() {
+        let mut keystore = Keystore::init(Connection::open_in_memory().unwrap()).unwrap();
+        let (party_share_counts, shares) = shares();
+        let group = shares.get(TypedUsize::from_usize(0)).unwrap().group();
+
+        keystore
+            .insert_key("alice", &party_share_counts, &shares)
+            .unwrap();
+        assert!(keystore
+            .insert_key("alice", &party_share_counts, &shares)
+            .is_err());
+
+        assert_eq!(
+            keystore.list().unwrap(),
+            [KeyInfo {
+                name: "alice".into(),
+                threshold: 1,
+                share_count: 3,
+                group_pubkey: group.pubkey_compressed_hex(),
+            }]
+        );
+        assert_eq!(
+            keystore.party_share_counts("alice").unwrap(),
+            party_share_counts
+        );
+        assert_eq!(
+            keystore.group_metadata("alice").unwrap(),
+            GroupMetadata::from(group)
+        );
+        assert_eq!(
+            keystore.share_indices("alice").unwrap(),
+            shares.iter().map(|(index, _)| index).collect::<Vec<_>>()
+        );
+        for (index, share) in shares.iter() {
+            assert_eq!(
+                keystore.secret_key_share("alice", index).unwrap().share(),
+                share.share()
+            );
+        }
+
+        let party_uids: Vec<String> = ["a", "b", "c"].iter().map(|uid| uid.to_string()).collect();
+        assert!(keystore.party_uids("alice").unwrap().is_empty());
+        keystore.set_party_uids("alice", &party_uids).unwrap();
+        assert_eq!(keystore.party_uids("alice").unwrap(), party_uids);
+
+        assert!(keystore.delete("alice").unwrap());
+        assert!(!keystore.delete("alice").unwrap());
+        assert!(keystore.list().unwrap().is_empty());
+        assert!(keystore
+            .secret_key_share("alice", TypedUsize::from_usize(0))
+            .is_err());
+    }
+
+    #[test]
+    fn rekey() {
+        let path = std::env::temp_dir().join(alloc::format!(
+            "tofn-keystore-rekey-{}.sqlite",
+            std::process::id()
+        ));
+        let (party_share_counts, shares) = shares();
+        {
+            let mut keystore = Keystore::open(&path, "old passphrase").unwrap();
+            keystore
+                .insert_key("alice", &party_share_counts, &shares)
+                .unwrap();
+            assert!(keystore.rekey("").is_err());
+            keystore.rekey("new passphrase").unwrap();
+        }
+
+        assert!(Keystore::open(&path, "old passphrase").is_err());
+        let keystore = Keystore::open(&path, "new passphrase").unwrap();
+        assert_eq!(keystore.list().unwrap().len(), 1);
+        assert_eq!(
+            keystore
+                .secret_key_share("alice", TypedUsize::from_usize(0))
+                .unwrap()
+                .share(),
+            shares.get(TypedUsize::from_usize(0)).unwrap().share()
+        );
+        drop(keystore);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -2,16 +2,66 @@
 
 // `traced_test`attribute depends on `std`, so we enable it in tests.
 // TODO: probably can be fixed in `tracing`.
-#[cfg(test)]
+// The `metrics` feature needs `std` for timing, the `keystore` and `pkcs11` features for files.
+#[cfg(any(test, feature = "metrics", feature = "keystore", feature = "pkcs11"))]
 extern crate std;
 
 extern crate alloc;
 
+/// Drop a value in place and assert that the memory of each listed field is zeroed afterwards,
+/// eg. `assert_zeroized_on_drop!(round, k_i, gamma_i)`.
+/// Defined before all modules so that their tests can use it:
+/// <https://danielkeep.github.io/tlborm/book/mbe-min-scoping.html>
+#[cfg(test)]
+macro_rules! assert_zeroized_on_drop {
+    ($value:expr, $($field:tt),+) => {{
+        fn size_of_pointee<T>(_: *const T) -> usize {
+            core::mem::size_of::<T>()
+        }
+        let mut slot = core::mem::MaybeUninit::new($value);
+        let ptr = slot.as_mut_ptr();
+        // SAFETY: `slot` still owns the memory of the value after it is dropped,
+        // and the fields we read are plain bytes that were zeroized rather than freed
+        unsafe {
+            core::ptr::drop_in_place(ptr);
+            $(
+                let field = core::ptr::addr_of!((*ptr).$field);
+                let bytes = core::slice::from_raw_parts(field as *const u8, size_of_pointee(field));
+                assert!(
+                    bytes.iter().all(|&byte| byte == 0),
+                    "`{}` is not zeroized on drop",
+                    stringify!($field)
+                );
+            )+
+        }
+    }};
+}
+
+pub mod bitcoin;
+#[cfg(feature = "bls")]
+pub mod bls;
 pub mod collections;
 mod constants;
 // todo(tk): made crypto tools public to use MessageDigest in cli; make private again
 pub mod crypto_tools;
 pub mod ecdsa;
+#[cfg(feature = "protocols")]
 pub mod gg20;
+pub mod group_metadata;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+#[cfg(feature = "protocols")]
 pub mod multisig;
 pub mod sdk;
+#[cfg(feature = "protocols")]
+pub mod threshold_paillier;
+pub mod verify;
+
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+
+#[cfg(feature = "wire-spec")]
+pub mod wire_spec;
@@ -1,32 +1,44 @@
 use anyhow::Result;
-use bincode::Options;
 use chrono::{Datelike, Timelike, Utc};
 use clap::{Args, Parser, Subcommand};
 use ecdsa::hazmat::VerifyPrimitive;
 use k256::PublicKey;
 use std::{
     convert::TryFrom,
-    fs,
     path::{Path, PathBuf},
+    time::Instant,
 };
 #[allow(unused_imports)]
 use tofn::{
     collections::{TypedUsize, VecMap},
-    crypto_tools::message_digest::MessageDigest,
+    crypto_tools::message_digest::{DigestPolicy, Keccak256Policy, MessageDigest, Sha256Policy},
     gg20,
     gg20::{
         ceygen::Ceygen,
         keygen::{KeygenPartyId, KeygenShareId, SecretKeyShare},
+        quorum_change::{new_quorum_change, QuorumChange},
         sign::{new_sign, SignParties, SignShareId},
     },
-    sdk::api::{PartyShareCounts, Protocol},
+    sdk::{
+        api::{derive_ethereum_address, to_recoverable_signature, PartyShareCounts, Protocol},
+        local::{execute_protocol_with_progress, RoundTraffic},
+    },
 };
 use tracing::info;
 use zeroize::Zeroize;
 
-use self::execute::execute_protocol;
+use self::storage::{
+    create_private_dir, read_from_file, read_manifest, write_atomic, Manifest, StorageFormat,
+};
 
 pub(crate) const PARTY_SHARE_COUNTS_FILE: &str = "party_share_counts";
+pub(crate) const MANIFEST_FILE: &str = "manifest.json";
+/// Random quorums checked by `ceygen --validate`
+const VALIDATE_QUORUM_COUNT: usize = 16;
+#[cfg(feature = "keystore")]
+const KEYSTORE_PASSPHRASE_VAR: &str = "TOFN_KEYSTORE_PASSPHRASE";
+#[cfg(feature = "keystore")]
+const KEYSTORE_NEW_PASSPHRASE_VAR: &str = "TOFN_KEYSTORE_NEW_PASSPHRASE";
 
 /// CLI, mostly for debugging and local key generation
 #[derive(Parser, Debug)]
@@ -43,6 +55,13 @@ struct Cli {
 enum Commands {
     Ceygen(CeygenCli),
     Sign(SignCli),
+    Rotate(RotateCli),
+    #[cfg(unix)]
+    Serve(ServeCli),
+    #[cfg(feature = "keystore")]
+    Keystore(KeystoreCli),
+    #[cfg(feature = "grpc-server")]
+    Grpc(GrpcCli),
 }
 
 #[derive(Debug, Args)]
@@ -57,21 +76,182 @@ struct CeygenCli {
     /// If no key given, a random key is generated.
     #[clap(short = 'k', long = "alice_key")]
     alice_key_byte_array: Option<Vec<u8>>,
+    /// Context the keys are bound to, eg. a chain id or ceremony id; 4 to 256 bytes
+    #[clap(short = 'n', long = "session_nonce")]
+    session_nonce: String,
     #[clap(short = 'o', long = "output_directory")]
     dir: Option<String>,
+    /// On-disk format of the key shares: `bincode`, `json` or `cbor`
+    #[clap(short = 'f', long = "format", default_value = "bincode")]
+    format: StorageFormat,
+    /// Store the key shares in a keystore instead of a directory
+    #[cfg(feature = "keystore")]
+    #[clap(flatten)]
+    keystore: KeystoreArgs,
+    /// Read the stored shares back, validate each of them and check that random quorums reconstruct the key
+    #[clap(long = "validate")]
+    validate: bool,
 }
 
+/// Where to read key shares from
 #[derive(Debug, Args)]
-struct SignCli {
+struct SharesArgs {
     /// Directory where keys are stored
     #[clap(short = 'd', long = "directory")]
-    dir: String,
+    #[cfg_attr(not(feature = "keystore"), clap(required = true))]
+    #[cfg_attr(feature = "keystore", clap(required_unless_present = "keystore"))]
+    dir: Option<String>,
+    /// Read the key shares from a keystore instead of a directory
+    #[cfg(feature = "keystore")]
+    #[clap(flatten)]
+    keystore: KeystoreArgs,
+}
+
+#[derive(Debug, Args)]
+struct SignCli {
+    #[clap(flatten)]
+    shares: SharesArgs,
     /// Parties to use for signing; Eg if signing with parties 0,1,3, use -p 0 -p 1 -p 3
     #[clap(short = 'p', long = "parties", required = true)]
     parties: Vec<usize>,
-    /// 32 byte array to sign, default to [42;32]
+    /// Hex-encoded 32-byte digest to sign
     #[clap(short = 'm', long = "msg_digest")]
     msg_digest: Option<String>,
+    /// File whose contents to hash with `--hash` and sign
+    #[clap(long = "msg-file")]
+    msg_file: Option<PathBuf>,
+    /// UTF-8 message to hash with `--hash` and sign
+    #[clap(long = "msg-utf8")]
+    msg_utf8: Option<String>,
+    /// Hash function for `--msg-file` and `--msg-utf8`: `keccak256` or `sha256`
+    #[clap(long = "hash")]
+    hash: Option<MessageHash>,
+    /// Signature encoding: `der`, `compact` (64-byte `r || s`) or `rsv` (65-byte `r || s || v`)
+    #[clap(short = 'f', long = "format", default_value = "der")]
+    format: SignatureFormat,
+}
+
+/// Hash functions `sign` can apply to a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageHash {
+    Keccak256,
+    Sha256,
+}
+
+impl MessageHash {
+    fn digest(&self, message: &[u8]) -> MessageDigest {
+        match self {
+            Self::Keccak256 => Keccak256Policy::digest(message),
+            Self::Sha256 => Sha256Policy::digest(message),
+        }
+    }
+}
+
+impl std::str::FromStr for MessageHash {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "keccak256" => Ok(Self::Keccak256),
+            "sha256" => Ok(Self::Sha256),
+            _ => Err(anyhow::anyhow!("unknown hash function `{}`", s)),
+        }
+    }
+}
+
+/// Hex encodings of a low-s signature printed by `sign`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SignatureFormat {
+    Der,
+    Compact,
+    Rsv,
+}
+
+impl std::str::FromStr for SignatureFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "der" => Ok(Self::Der),
+            "compact" => Ok(Self::Compact),
+            "rsv" => Ok(Self::Rsv),
+            _ => Err(anyhow::anyhow!("unknown signature format `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct RotateCli {
+    /// Directory with the shares of all parties, eg. from ceygen
+    #[clap(short = 'd', long = "directory")]
+    dir: PathBuf,
+}
+
+#[cfg(unix)]
+#[derive(Debug, Args)]
+struct ServeCli {
+    #[clap(flatten)]
+    shares: SharesArgs,
+    /// Parties whose shares this daemon holds; Eg -p 0 -p 1
+    #[clap(short = 'p', long = "parties", required = true)]
+    parties: Vec<usize>,
+    /// Unix socket to accept sign requests on, one JSON object per line:
+    /// `{"digest": "<hex>", "signers": [0, 1], "session": "<id>"}`
+    #[clap(short = 's', long = "socket")]
+    socket: PathBuf,
+    /// Unix socket of a relay that forwards round messages to and from the daemons of other signers.
+    /// Without a relay, this daemon must hold the shares of all signers.
+    #[clap(short = 'r', long = "relay")]
+    relay: Option<PathBuf>,
+}
+
+#[cfg(feature = "keystore")]
+#[derive(Debug, Args)]
+struct KeystoreArgs {
+    /// Encrypted keystore file. The passphrase is read from `TOFN_KEYSTORE_PASSPHRASE`
+    #[clap(long = "keystore", requires = "name")]
+    keystore: Option<PathBuf>,
+    /// Name of the key in the keystore
+    #[clap(long = "name")]
+    name: Option<String>,
+}
+
+#[cfg(feature = "keystore")]
+#[derive(Debug, Args)]
+struct KeystoreCli {
+    /// Encrypted keystore file. The passphrase is read from `TOFN_KEYSTORE_PASSPHRASE`
+    #[clap(long = "keystore")]
+    path: PathBuf,
+    #[clap(subcommand)]
+    command: KeystoreCommands,
+}
+
+#[cfg(feature = "keystore")]
+#[derive(Subcommand, Debug)]
+enum KeystoreCommands {
+    /// List the stored keys
+    List,
+    /// Delete a key with all its shares
+    Delete {
+        #[clap(long = "name")]
+        name: String,
+    },
+    /// Re-encrypt the keystore under the passphrase in `TOFN_KEYSTORE_NEW_PASSPHRASE`
+    Rekey,
+}
+
+#[cfg(feature = "grpc-server")]
+#[derive(Debug, Args)]
+struct GrpcCli {
+    /// Address to serve tofnd's `GG20` gRPC service on
+    #[clap(short = 'a', long = "address", default_value = "127.0.0.1:50051")]
+    address: std::net::SocketAddr,
+    /// File holding the hex-encoded 64-byte secret recovery key of this party
+    #[clap(short = 'r', long = "recovery_key")]
+    recovery_key: PathBuf,
+    /// Encrypted keystore file for the key shares. The passphrase is read from `TOFN_KEYSTORE_PASSPHRASE`
+    #[clap(long = "keystore")]
+    keystore: PathBuf,
 }
 
 pub fn main() -> anyhow::Result<()> {
@@ -82,6 +262,13 @@ pub fn main() -> anyhow::Result<()> {
     match args.command {
         Commands::Ceygen(cli) => ceygen(cli),
         Commands::Sign(cli) => sign(cli),
+        Commands::Rotate(cli) => rotate(cli),
+        #[cfg(unix)]
+        Commands::Serve(cli) => serve(cli),
+        #[cfg(feature = "keystore")]
+        Commands::Keystore(cli) => keystore(cli),
+        #[cfg(feature = "grpc-server")]
+        Commands::Grpc(cli) => grpc(cli),
     }
 }
 
@@ -92,112 +279,549 @@ fn ceygen(cli: CeygenCli) -> anyhow::Result<()> {
     use rand_core::{OsRng, RngCore};
     let mut key = [0u8; 32];
     OsRng.fill_bytes(&mut key);
-    let ceygen = tofn::gg20::ceygen::ceygen(cli.parties, cli.threshold, &key)?;
+    let start = Instant::now();
+    let ceygen = tofn::gg20::ceygen::ceygen_with_progress(
+        cli.parties,
+        cli.threshold,
+        &key,
+        cli.session_nonce.as_bytes(),
+        |done| {
+            info!(
+                "ceygen party {}/{} ready, {:.1?} elapsed",
+                done,
+                cli.parties,
+                start.elapsed()
+            )
+        },
+    )?;
+
+    // remember where the shares went, so `--validate` can read them back
+    #[cfg(feature = "keystore")]
+    let stored = if let (Some(path), Some(name)) = (&cli.keystore.keystore, &cli.keystore.name) {
+        insert_ceygen_results(ceygen, path, name)?;
+        SharesArgs {
+            dir: None,
+            keystore: cli.keystore,
+        }
+    } else {
+        let dir = write_ceygen_results(ceygen, cli.dir.map(PathBuf::from), cli.format)?;
+        SharesArgs {
+            dir: Some(dir.to_string_lossy().into_owned()),
+            keystore: cli.keystore,
+        }
+    };
+    #[cfg(not(feature = "keystore"))]
+    let stored = SharesArgs {
+        dir: Some(
+            write_ceygen_results(ceygen, cli.dir.map(PathBuf::from), cli.format)?
+                .to_string_lossy()
+                .into_owned(),
+        ),
+    };
+
+    let validated = if cli.validate {
+        validate_ceygen_results(&stored, cli.parties, &key)
+    } else {
+        Ok(())
+    };
     key.zeroize();
-    write_ceygen_results(ceygen, cli.dir.map(PathBuf::from))?;
+    validated
+}
+
+/// Read back all `parties` shares from `stored` and check them against `key`.
+fn validate_ceygen_results(stored: &SharesArgs, parties: usize, key: &[u8]) -> Result<()> {
+    let parties: Vec<usize> = (0..parties).collect();
+    let (_, secret_key_shares) = read_key_shares(stored, &parties)?;
+    tofn::gg20::ceygen::validate_shares(
+        &secret_key_shares,
+        key,
+        VALIDATE_QUORUM_COUNT,
+        &mut rand_core::OsRng,
+    )?;
+    info!(
+        "validated {} shares and {} random quorums",
+        parties.len(),
+        VALIDATE_QUORUM_COUNT
+    );
     Ok(())
 }
 
-/// Read keys `key_array` from `dir` and sign message `msg_digest`.
+/// Read keys `key_array` from `dir` and sign the message given in `cli`.
 fn sign(cli: SignCli) -> anyhow::Result<()> {
-    // read data from keygen directory
+    let msg_to_sign = message_digest(&cli)?;
+    let (party_share_counts, secret_key_shares) = read_key_shares(&cli.shares, &cli.parties)?;
 
-    let bincode = bincode::DefaultOptions::new();
-    let v_serialized = fs::read(Path::new(&format!(
-        "{}/{}",
-        cli.dir, PARTY_SHARE_COUNTS_FILE
-    )))
-    .unwrap();
-    let party_share_counts: PartyShareCounts<KeygenPartyId> =
-        bincode.deserialize(&v_serialized).unwrap();
+    // sign
+    let sign_parties = party_subset(&party_share_counts, &cli.parties)?;
 
-    let secret_key_shares: VecMap<KeygenShareId, SecretKeyShare> = cli
-        .parties
-        .iter()
-        .map(|index| {
-            let bincode = bincode::DefaultOptions::new();
-            let v_serialized = fs::read(Path::new(&format!("{}/{}", cli.dir, index))).unwrap();
-            bincode.deserialize(&v_serialized).unwrap()
+    // `secret_key_shares` holds the shares of `sign_parties` only, so find each by its keygen share id
+    let sign_shares = party_share_counts
+        .share_id_subset(&sign_parties)
+        .map_err(fatal)?
+        .into_iter()
+        .map(|keygen_share_id| {
+            let secret_key_share = secret_key_shares
+                .iter()
+                .map(|(_, share)| share)
+                .find(|share| share.share().index() == keygen_share_id)
+                .ok_or_else(|| anyhow::anyhow!("missing key share {}", keygen_share_id))?;
+            new_sign(
+                secret_key_share.group(),
+                secret_key_share.share(),
+                &sign_parties,
+                &msg_to_sign,
+                #[cfg(feature = "malicious")]
+                gg20::sign::malicious::Behaviour::Honest,
+            )
+            .map_err(fatal)
         })
-        .collect();
-
-    // sign
-    let sign_parties = {
-        let mut sign_parties = SignParties::with_max_size(party_share_counts.party_count());
-        for i in &cli.parties {
-            sign_parties.add(TypedUsize::from_usize(*i)).unwrap();
+        .collect::<Result<VecMap<SignShareId, _>>>()?;
+    let signatures = execute_protocol_with_progress(
+        sign_shares,
+        progress("sign", gg20::sign::ROUND_COUNT, Instant::now()),
+    )
+    .map_err(fatal)?
+    .into_iter()
+    .map(|(index, output)| match output {
+        Protocol::Done(Ok(signature)) => Ok(signature),
+        Protocol::Done(Err(faulters)) => {
+            Err(anyhow::anyhow!("sign failed with faulters {:?}", faulters))
         }
-        sign_parties
-    };
-
-    let keygen_share_ids = VecMap::<SignShareId, _>::from_vec(
-        party_share_counts.share_id_subset(&sign_parties).unwrap(),
-    );
-    let msg_digest = match cli.msg_digest.as_ref() {
-        Some(s) => hex::decode(s).expect("Decoding failed"),
-        None => vec![42; 32],
-    };
-    let msg_to_sign = MessageDigest::try_from(&*msg_digest).unwrap();
-    let sign_shares = keygen_share_ids.map(|keygen_share_id| {
-        let secret_key_share = secret_key_shares.get(keygen_share_id).unwrap();
-        new_sign(
-            secret_key_share.group(),
-            secret_key_share.share(),
-            &sign_parties,
-            &msg_to_sign,
-            #[cfg(feature = "malicious")]
-            gg20::sign::malicious::Behaviour::Honest,
-        )
-        .unwrap()
-    });
-    let sign_share_outputs = execute_protocol(sign_shares).unwrap();
-    let signatures = sign_share_outputs.map(|output| match output {
-        Protocol::NotDone(_) => panic!("sign share not done yet"),
-        Protocol::Done(result) => result.expect("sign share finished with error"),
-    });
+        Protocol::NotDone(_) => Err(anyhow::anyhow!("sign share {} did not finish", index)),
+    })
+    .collect::<Result<Vec<_>>>()?;
 
     // grab pubkey from one of the shares
-    let vkey = secret_key_shares
-        .get(TypedUsize::from_usize(0))
-        .unwrap()
-        .group()
-        .verifying_key();
+    let group = secret_key_shares
+        .iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("no key shares to sign with"))?
+        .1
+        .group();
+    let vkey = group.verifying_key();
 
     // verify a signature
-    let sig = signatures.get(TypedUsize::from_usize(0)).unwrap();
+    let sig = signatures
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("sign produced no signatures"))?;
     let pk: PublicKey = vkey.into();
-    assert!(pk
+    if pk
         .as_affine()
         .verify_prehashed((&msg_to_sign).into(), sig)
-        .is_ok());
+        .is_err()
+    {
+        anyhow::bail!("signature does not verify against the group pubkey");
+    }
 
     info!(
-        "message: {:?} successfully signed by parties: {:?}",
-        msg_to_sign, cli.parties
+        "message: {:?} successfully signed by parties: {:?} for pubkey {}",
+        msg_to_sign,
+        cli.parties,
+        group.pubkey_compressed_hex()
+    );
+
+    // the recovery id is that of the low-s form, so print every format in that form
+    let recoverable_sig = to_recoverable_signature(&vkey, msg_to_sign.as_ref(), sig)
+        .ok_or_else(|| anyhow::anyhow!("no recovery id recovers the group pubkey"))?;
+    let sig = sig.normalize_s().unwrap_or(*sig);
+    let encoded = match cli.format {
+        SignatureFormat::Der => hex::encode(sig.to_der().as_bytes()),
+        SignatureFormat::Compact => hex::encode(sig.as_ref()),
+        SignatureFormat::Rsv => hex::encode(recoverable_sig.as_ref()),
+    };
+    println!("signature: {}", encoded);
+    println!("recovery id: {}", u8::from(recoverable_sig.recovery_id()));
+    println!(
+        "ethereum address: 0x{}",
+        hex::encode(derive_ethereum_address(&vkey))
     );
     Ok(())
 }
 
-/// Write ceygen results to an output directory.
-fn write_ceygen_results(ceygen: Ceygen, output_dir: Option<PathBuf>) -> Result<()> {
+/// Refresh all shares in `cli.dir` with a quorum change that keeps every party and the threshold,
+/// check the new shares, then swap them in and keep the old directory as a timestamped backup.
+fn rotate(cli: RotateCli) -> Result<()> {
+    let dir = cli.dir.canonicalize()?;
+    let manifest = read_manifest(dir.join(MANIFEST_FILE))?;
+    let party_share_counts: PartyShareCounts<KeygenPartyId> =
+        read_from_file(dir.join(PARTY_SHARE_COUNTS_FILE))?;
+    let old_shares: Vec<SecretKeyShare> = (0..party_share_counts.total_share_count())
+        .map(|index| read_share_file(&dir, TypedUsize::from_usize(index)))
+        .collect::<Result<_>>()?;
+    let old_group = old_shares
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("no key shares in {}", dir.display()))?
+        .group();
+    if let Some(manifest) = &manifest {
+        manifest.check_group(old_group)?;
+    }
+    for share in &old_shares {
+        if share.group() != old_group || !share.validate().is_valid() {
+            anyhow::bail!("share {} is invalid, not rotating", share.share().index());
+        }
+    }
+
+    // refresh: keep every party with the same threshold
+    let epoch = manifest.as_ref().map_or(0, |manifest| manifest.epoch) + 1;
+    let change = QuorumChange::new(
+        epoch,
+        tofn::sdk::local::all_parties(&party_share_counts).map_err(fatal)?,
+        Vec::new(),
+        old_group.threshold(),
+    );
+    let parties = old_shares
+        .iter()
+        .map(|share| new_quorum_change(share.group(), share.share(), &change))
+        .collect::<Result<VecMap<_, _>, _>>()
+        .map_err(fatal)?;
+    let outputs = execute_protocol_with_progress(
+        parties,
+        progress("rotate", gg20::quorum_change::ROUND_COUNT, Instant::now()),
+    )
+    .map_err(fatal)?
+    .into_iter()
+    .map(|(index, output)| match output {
+        Protocol::Done(Ok(output)) => Ok(output),
+        Protocol::Done(Err(faulters)) => Err(anyhow::anyhow!(
+            "refresh failed with faulters {:?}",
+            faulters
+        )),
+        Protocol::NotDone(_) => Err(anyhow::anyhow!("share {} did not finish", index)),
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+    // validate the new shares against the signed epoch transition
+    let transition = &outputs[0].transition;
+    if !transition.verify(old_group) {
+        anyhow::bail!("epoch transition does not verify against the old group");
+    }
+    let new_shares: Vec<SecretKeyShare> = outputs
+        .iter()
+        .map(|output| output.secret_key_share.clone())
+        .collect();
+    for share in &new_shares {
+        if share.group() != transition.group() || !share.validate().is_valid() {
+            anyhow::bail!("refreshed share {} is invalid", share.share().index());
+        }
+    }
+
+    // write the new shares next to the old ones, then swap the directories
+    let format = manifest.map_or(StorageFormat::Bincode, |manifest| manifest.format);
+    let timestamp = timestamp();
+    let sibling = |suffix: &str| {
+        let mut name = dir.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}-{}", suffix, timestamp));
+        dir.with_file_name(name)
+    };
+    let new_dir = sibling("rotated");
+    let backup_dir = sibling("backup");
+    write_shares(
+        &new_dir,
+        transition.group().party_share_counts(),
+        &new_shares,
+        format,
+        epoch,
+    )?;
+    std::fs::rename(&dir, &backup_dir)?;
+    std::fs::rename(&new_dir, &dir).map_err(|err| {
+        anyhow::anyhow!(
+            "moving {} to {} failed, the old shares are in {}: {}",
+            new_dir.display(),
+            dir.display(),
+            backup_dir.display(),
+            err
+        )
+    })?;
+
+    info!(
+        "rotated {} shares in {} to epoch {}, old shares backed up to {}",
+        new_shares.len(),
+        dir.display(),
+        epoch,
+        backup_dir.display()
+    );
+    Ok(())
+}
+
+/// The digest to sign: either given directly, or the hash of a message from a file or the command line.
+/// Exactly one of them must be given, and `--hash` only with a message.
+fn message_digest(cli: &SignCli) -> Result<MessageDigest> {
+    let message = match (&cli.msg_digest, &cli.msg_file, &cli.msg_utf8) {
+        (Some(digest), None, None) => {
+            if cli.hash.is_some() {
+                anyhow::bail!("--hash applies to --msg-file and --msg-utf8, not to a digest");
+            }
+            let digest = hex::decode(digest)?;
+            return MessageDigest::try_from(&digest[..])
+                .map_err(|_| anyhow::anyhow!("the message digest must be 32 bytes"));
+        }
+        (None, Some(path), None) => std::fs::read(path)?,
+        (None, None, Some(message)) => message.as_bytes().to_vec(),
+        _ => anyhow::bail!("give exactly one of --msg_digest, --msg-file or --msg-utf8"),
+    };
+    let hash = cli
+        .hash
+        .ok_or_else(|| anyhow::anyhow!("choose how to hash the message with --hash"))?;
+    let digest = hash.digest(&message);
+    info!("{:?} digest of the message: {}", hash, hex::encode(&digest));
+    Ok(digest)
+}
+
+/// Load the key shares of `parties` once, then sign on request until killed.
+#[cfg(unix)]
+fn serve(cli: ServeCli) -> Result<()> {
+    let (party_share_counts, secret_key_shares) = read_key_shares(&cli.shares, &cli.parties)?;
+    let relay = cli
+        .relay
+        .as_deref()
+        .map(serve::Relay::connect)
+        .transpose()?;
+    serve::Daemon::new(party_share_counts, secret_key_shares, relay).serve(&cli.socket)
+}
+
+/// Read the party share counts and all key shares of `parties`, in order of keygen share id,
+/// from the keystore if one is given, otherwise from the directory `args.dir`.
+/// Unless `parties` are all the parties, the position of a share differs from its keygen share id.
+fn read_key_shares(
+    args: &SharesArgs,
+    parties: &[usize],
+) -> Result<(
+    PartyShareCounts<KeygenPartyId>,
+    VecMap<KeygenShareId, SecretKeyShare>,
+)> {
+    #[cfg(feature = "keystore")]
+    if let (Some(path), Some(name)) = (&args.keystore.keystore, &args.keystore.name) {
+        let keystore = open_keystore(path)?;
+        let party_share_counts = keystore.party_share_counts(name)?;
+        let secret_key_shares = party_share_counts
+            .share_id_subset(&party_subset(&party_share_counts, parties)?)
+            .map_err(fatal)?
+            .into_iter()
+            .map(|index| keystore.secret_key_share(name, index))
+            .collect::<Result<_>>()?;
+        return Ok((party_share_counts, secret_key_shares));
+    }
+
+    // read data from keygen directory
+    // files may be in any supported storage format
+    let dir = Path::new(
+        args.dir
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("no key directory given"))?,
+    );
+    let manifest = read_manifest(dir.join(MANIFEST_FILE))?;
+    let party_share_counts: PartyShareCounts<KeygenPartyId> =
+        read_from_file(dir.join(PARTY_SHARE_COUNTS_FILE))?;
+
+    let secret_key_shares: VecMap<KeygenShareId, SecretKeyShare> = party_share_counts
+        .share_id_subset(&party_subset(&party_share_counts, parties)?)
+        .map_err(fatal)?
+        .into_iter()
+        .map(|index| read_share_file(dir, index))
+        .collect::<Result<_>>()?;
+
+    if let (Some(manifest), Some((_, share))) = (manifest, secret_key_shares.iter().next()) {
+        manifest.check_group(share.group())?;
+    }
+    Ok((party_share_counts, secret_key_shares))
+}
+
+/// The subset of `party_share_counts`'s parties given by `parties`
+fn party_subset(
+    party_share_counts: &PartyShareCounts<KeygenPartyId>,
+    parties: &[usize],
+) -> Result<SignParties> {
+    let mut subset = SignParties::with_max_size(party_share_counts.party_count());
+    for &party in parties {
+        subset.add(TypedUsize::from_usize(party)).map_err(|_| {
+            anyhow::anyhow!(
+                "invalid party {}: the key has {} parties",
+                party,
+                party_share_counts.party_count()
+            )
+        })?;
+    }
+    Ok(subset)
+}
+
+/// Read the key share with keygen share id `index` from its file in `dir`
+fn read_share_file(dir: &Path, index: TypedUsize<KeygenShareId>) -> Result<SecretKeyShare> {
+    let share: SecretKeyShare = read_from_file(dir.join(index.to_string()))?;
+    if share.share().index() != index {
+        anyhow::bail!(
+            "file {} holds key share {}",
+            dir.join(index.to_string()).display(),
+            share.share().index()
+        );
+    }
+    Ok(share)
+}
+
+/// Write ceygen results to an output directory in the given storage `format` and return its path.
+fn write_ceygen_results(
+    ceygen: Ceygen,
+    output_dir: Option<PathBuf>,
+    format: StorageFormat,
+) -> Result<PathBuf> {
     let path = output_dir.unwrap_or_else(|| {
         let timestamp = timestamp();
         PathBuf::from(format!("tofn_ceygen_{timestamp}"))
     });
-    std::fs::create_dir(path.clone())?;
 
-    // write secret key shares and party share counts to dir
+    // ceygen output is bincode-encoded; re-encode if another format was requested
     let (psce, skse) = ceygen;
-    let path_s = path.to_str().unwrap();
-    skse.into_iter().for_each(|(index, encoded_share)| {
-        std::fs::write(Path::new(&(format!("{}/{}", path_s, index))), encoded_share).unwrap();
-    });
-    std::fs::write(Path::new(&format!("{}/party_share_counts", path_s)), psce)?;
+    let party_share_counts: PartyShareCounts<KeygenPartyId> =
+        StorageFormat::Bincode.decode(&psce)?;
+    let shares: Vec<SecretKeyShare> = skse
+        .iter()
+        .map(|(_, encoded_share)| StorageFormat::Bincode.decode(encoded_share))
+        .collect::<Result<_>>()?;
+    write_shares(&path, &party_share_counts, &shares, format, 0)?;
+
+    info!(
+        "ceygen keyshares written to: {} ({})",
+        path.display(),
+        format
+    );
+    Ok(path)
+}
+
+/// Write `shares` to the new directory `path` in the given storage `format`.
+/// Every file is written atomically and the manifest is written last,
+/// so a directory without a manifest is the remains of an interrupted write.
+fn write_shares(
+    path: &Path,
+    party_share_counts: &PartyShareCounts<KeygenPartyId>,
+    shares: &[SecretKeyShare],
+    format: StorageFormat,
+    epoch: u64,
+) -> Result<()> {
+    create_private_dir(path)?;
+    let group = shares
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("no key shares to write"))?
+        .group();
+    for share in shares {
+        write_atomic(
+            &path.join(share.share().index().to_string()),
+            &format.encode(share)?,
+        )?;
+    }
+    write_atomic(
+        &path.join(PARTY_SHARE_COUNTS_FILE),
+        &format.encode(party_share_counts)?,
+    )?;
+    let manifest = Manifest {
+        epoch,
+        ..Manifest::new(group, format)
+    };
+    write_atomic(
+        &path.join(MANIFEST_FILE),
+        &serde_json::to_vec_pretty(&manifest)?,
+    )
+}
+
+/// Decode ceygen results and store them in the keystore at `path` under `name`.
+#[cfg(feature = "keystore")]
+fn insert_ceygen_results(ceygen: Ceygen, path: &Path, name: &str) -> Result<()> {
+    let (psce, skse) = ceygen;
+    let party_share_counts: PartyShareCounts<KeygenPartyId> =
+        StorageFormat::Bincode.decode(&psce)?;
+    let secret_key_shares: VecMap<KeygenShareId, SecretKeyShare> = skse
+        .iter()
+        .map(|(_, encoded_share)| StorageFormat::Bincode.decode(encoded_share))
+        .collect::<Result<_>>()?;
 
-    info!("ceygen keyshares written to: {}", path_s);
+    open_keystore(path)?.insert_key(name, &party_share_counts, &secret_key_shares)?;
+    info!(
+        "ceygen keyshares stored as `{}` in {}",
+        name,
+        path.display()
+    );
     Ok(())
 }
 
+#[cfg(feature = "keystore")]
+fn keystore(cli: KeystoreCli) -> Result<()> {
+    let mut keystore = open_keystore(&cli.path)?;
+    match cli.command {
+        KeystoreCommands::List => {
+            for key in keystore.list()? {
+                println!(
+                    "{}\t{}-of-{}\t{}",
+                    key.name, key.threshold, key.share_count, key.group_pubkey
+                );
+            }
+        }
+        KeystoreCommands::Delete { name } => {
+            if !keystore.delete(&name)? {
+                anyhow::bail!("no key `{}` in {}", name, cli.path.display());
+            }
+            info!("deleted `{}` from {}", name, cli.path.display());
+        }
+        KeystoreCommands::Rekey => {
+            let new_passphrase = std::env::var(KEYSTORE_NEW_PASSPHRASE_VAR).map_err(|_| {
+                anyhow::anyhow!("set the new passphrase in {}", KEYSTORE_NEW_PASSPHRASE_VAR)
+            })?;
+            keystore.rekey(&new_passphrase)?;
+            info!(
+                "re-encrypted {} under the new passphrase",
+                cli.path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "keystore")]
+fn open_keystore(path: &Path) -> Result<tofn::keystore::Keystore> {
+    let passphrase = std::env::var(KEYSTORE_PASSPHRASE_VAR).map_err(|_| {
+        anyhow::anyhow!("set the keystore passphrase in {}", KEYSTORE_PASSPHRASE_VAR)
+    })?;
+    tofn::keystore::Keystore::open(path, &passphrase)
+}
+
+/// Serve tofnd's gRPC API until killed.
+#[cfg(feature = "grpc-server")]
+fn grpc(cli: GrpcCli) -> Result<()> {
+    let keystore = open_keystore(&cli.keystore)?;
+    let mut recovery_key = hex::decode(std::fs::read_to_string(&cli.recovery_key)?.trim())?;
+    let secret_recovery_key = gg20::keygen::SecretRecoveryKey::try_from(&recovery_key[..])
+        .map_err(|_| anyhow::anyhow!("secret recovery key must be 64 bytes"));
+    recovery_key.zeroize();
+    tokio::runtime::Runtime::new()?.block_on(grpc::serve(
+        cli.address,
+        keystore,
+        secret_recovery_key?,
+    ))
+}
+
+fn fatal(err: tofn::sdk::api::TofnFatal) -> anyhow::Error {
+    anyhow::anyhow!("{}", err)
+}
+
+/// helper, log progress of a protocol with `round_count` honest rounds started at `start`
+fn progress(
+    protocol: &'static str,
+    round_count: usize,
+    start: Instant,
+) -> impl FnMut(&RoundTraffic) {
+    let mut total_bytes = 0;
+    move |traffic| {
+        total_bytes += traffic.byte_count;
+        info!(
+            "{} round {}/{}: {} messages, {} bytes ({} total), {:.1?} elapsed",
+            protocol,
+            traffic.round,
+            round_count,
+            traffic.msg_count,
+            traffic.byte_count,
+            total_bytes,
+            start.elapsed()
+        );
+    }
+}
+
 /// helper, get a quick timestamp
 fn timestamp() -> String {
     let now = Utc::now();
@@ -212,135 +836,1016 @@ fn timestamp() -> String {
     )
 }
 
-mod execute {
-    //! Single-threaded generic protocol execution
-    // copy pasted from tests/single_thread/execute.rs
+mod storage {
+    //! On-disk storage formats for key shares.
+    //! Older builds wrote shares either as bincode or as JSON;
+    //! [read_from_file] accepts every format so that existing share directories keep working.
+
+    use std::{
+        ffi::OsString,
+        fmt,
+        fs::{self, DirBuilder, OpenOptions},
+        io::{ErrorKind, Write},
+        path::{Path, PathBuf},
+        str::FromStr,
+    };
+
+    use anyhow::{anyhow, bail, Context, Result};
+    use bincode::Options;
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use tofn::gg20::keygen::GroupPublicInfo;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum StorageFormat {
+        Bincode,
+        Json,
+        Cbor,
+    }
+
+    impl StorageFormat {
+        pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>> {
+            Ok(match self {
+                Self::Bincode => bincode::DefaultOptions::new().serialize(value)?,
+                Self::Json => serde_json::to_vec_pretty(value)?,
+                Self::Cbor => {
+                    let mut bytes = Vec::new();
+                    ciborium::ser::into_writer(value, &mut bytes)?;
+                    bytes
+                }
+            })
+        }
+
+        pub fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T> {
+            Ok(match self {
+                Self::Bincode => bincode::DefaultOptions::new().deserialize(bytes)?,
+                Self::Json => serde_json::from_slice(bytes)?,
+                Self::Cbor => ciborium::de::from_reader(bytes)?,
+            })
+        }
+    }
+
+    impl FromStr for StorageFormat {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self> {
+            match s {
+                "bincode" => Ok(Self::Bincode),
+                "json" => Ok(Self::Json),
+                "cbor" => Ok(Self::Cbor),
+                _ => Err(anyhow!("unknown storage format `{}`", s)),
+            }
+        }
+    }
 
+    impl fmt::Display for StorageFormat {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Bincode => write!(f, "bincode"),
+                Self::Json => write!(f, "json"),
+                Self::Cbor => write!(f, "cbor"),
+            }
+        }
+    }
+
+    /// Read a value from `path`, detecting its storage format.
+    /// Bincode is tried last: arbitrary bytes are almost never valid JSON or CBOR of the right shape,
+    /// whereas a JSON or CBOR document could accidentally parse as bincode.
+    pub fn read_from_file<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        StorageFormat::Json
+            .decode(&bytes)
+            .or_else(|_| StorageFormat::Cbor.decode(&bytes))
+            .or_else(|_| StorageFormat::Bincode.decode(&bytes))
+            .with_context(|| {
+                format!(
+                    "{} is not a bincode, JSON or CBOR encoded file",
+                    path.display()
+                )
+            })
+    }
+
+    /// Bump when the layout of a ceygen output directory changes.
+    pub const MANIFEST_VERSION: u16 = 1;
+
+    /// Summary of a ceygen output directory, always written as JSON.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct Manifest {
+        pub version: u16,
+        pub format: StorageFormat,
+        pub threshold: usize,
+        pub share_count: usize,
+        /// SEC1 compressed, hex-encoded
+        pub group_pubkey: String,
+        /// Number of `tofn rotate` runs since ceygen
+        #[serde(default)]
+        pub epoch: u64,
+    }
+
+    impl Manifest {
+        pub fn new(group: &GroupPublicInfo, format: StorageFormat) -> Self {
+            Self {
+                version: MANIFEST_VERSION,
+                format,
+                threshold: group.threshold(),
+                share_count: group.share_count(),
+                group_pubkey: group.pubkey_compressed_hex(),
+                epoch: 0,
+            }
+        }
+
+        /// Check that `group` is the group this manifest was written for.
+        pub fn check_group(&self, group: &GroupPublicInfo) -> Result<()> {
+            if self.group_pubkey != group.pubkey_compressed_hex()
+                || self.threshold != group.threshold()
+                || self.share_count != group.share_count()
+            {
+                bail!("key shares do not match the manifest");
+            }
+            Ok(())
+        }
+    }
+
+    /// Read the manifest at `path`.
+    /// Directories written by older builds have no manifest, in which case return `None`.
+    pub fn read_manifest(path: impl AsRef<Path>) -> Result<Option<Manifest>> {
+        let path = path.as_ref();
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).with_context(|| format!("reading {}", path.display())),
+        };
+        let manifest: Manifest = serde_json::from_slice(&bytes)
+            .with_context(|| format!("{} is not a valid manifest", path.display()))?;
+        if manifest.version > MANIFEST_VERSION {
+            bail!(
+                "unsupported manifest version {}, expected at most {}",
+                manifest.version,
+                MANIFEST_VERSION
+            );
+        }
+        Ok(Some(manifest))
+    }
+
+    /// Create the directory `path`, readable only by its owner.
+    pub fn create_private_dir(path: &Path) -> Result<()> {
+        let mut builder = DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder
+            .create(path)
+            .with_context(|| format!("creating {}", path.display()))
+    }
+
+    /// Write `bytes` to a temporary file readable only by its owner, then rename it to `path`.
+    /// A crash leaves either the old file or the new one at `path`, never a partial write.
+    pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+        let mut tmp_path = OsString::from(path.as_os_str());
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(&tmp_path)
+            .with_context(|| format!("creating {}", tmp_path.display()))?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+
+        fs::rename(&tmp_path, path).with_context(|| format!("writing {}", path.display()))
+    }
+}
+
+#[cfg(unix)]
+mod serve {
+    //! A minimal signer daemon: sign requests arrive on a unix socket,
+    //! round messages of signers held by other daemons travel through a relay.
+    //!
+    //! The relay is any process listening on a unix socket that forwards every line it receives
+    //! to all other connected daemons. Each line is a JSON-encoded [RoundMsg].
+    //! Requests are served one at a time.
+    //!
+    //! Relayed messages are not authenticated: the sender of a [RoundMsg] is whatever its `from` says.
+    //! Any client of the relay can send messages as any signer share and get honest shares accused,
+    //! so the relay and every process that can connect to it must be trusted.
+
+    use std::{
+        convert::TryFrom,
+        io::{BufRead, BufReader, Lines, Write},
+        os::unix::net::{UnixListener, UnixStream},
+        path::Path,
+        time::Duration,
+    };
+
+    use anyhow::{anyhow, bail, Result};
+    use serde::{Deserialize, Serialize};
     use tofn::{
-        collections::{HoleVecMap, TypedUsize, VecMap},
-        sdk::api::{BytesVec, Protocol, TofnResult},
+        collections::{TypedUsize, VecMap},
+        crypto_tools::message_digest::MessageDigest,
+        gg20::{
+            keygen::{KeygenPartyId, KeygenShareId, SecretKeyShare},
+            sign::{new_sign, SignParties, SignPartyId, SignProtocol, SignShareId},
+        },
+        sdk::api::{PartyShareCounts, Protocol, Round, Signature},
     };
-    use tracing::{debug, warn};
+    use tracing::{info, warn};
 
-    pub fn execute_protocol<F, K, P, const MAX_MSG_IN_LEN: usize>(
-        mut parties: VecMap<K, Protocol<F, K, P, MAX_MSG_IN_LEN>>,
-    ) -> TofnResult<VecMap<K, Protocol<F, K, P, MAX_MSG_IN_LEN>>>
-    where
-        K: Clone,
-    {
-        let mut current_round = 0;
-        while nobody_done(&parties) {
-            current_round += 1;
-            parties = next_round(parties, current_round)?;
+    use crate::fatal;
+
+    type SignRound = Round<Signature, SignShareId, SignPartyId>;
+
+    /// Give up on a sign if the relay is silent for this long
+    const RELAY_TIMEOUT: Duration = Duration::from_secs(60);
+
+    #[derive(Debug, Deserialize)]
+    pub struct SignRequest {
+        /// Hex-encoded 32-byte message digest
+        pub digest: String,
+        /// Party ids of the signers
+        pub signers: Vec<usize>,
+        /// Must be the same for all daemons taking part in the same sign, and unique to it.
+        /// Required and non-empty: messages of other signs on the same relay are told apart by it.
+        pub session: String,
+    }
+
+    /// `{"signature": "<hex DER>"}` or `{"error": "<message>"}`
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum SignResponse {
+        Signature(String),
+        Error(String),
+    }
+
+    /// A round message sent by the share with sign share id `from`
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct RoundMsg {
+        pub session: String,
+        pub round: usize,
+        pub from: usize,
+        /// Hex-encoded
+        pub payload: String,
+    }
+
+    pub struct Relay {
+        writer: UnixStream,
+        reader: Lines<BufReader<UnixStream>>,
+    }
+
+    impl Relay {
+        pub fn connect(path: &Path) -> Result<Self> {
+            let stream = UnixStream::connect(path)?;
+            stream.set_read_timeout(Some(RELAY_TIMEOUT))?;
+            info!("connected to relay {}", path.display());
+            Ok(Self {
+                writer: stream.try_clone()?,
+                reader: BufReader::new(stream).lines(),
+            })
+        }
+
+        fn send(&mut self, msg: &RoundMsg) -> Result<()> {
+            serde_json::to_writer(&mut self.writer, msg)?;
+            self.writer.write_all(b"\n")?;
+            Ok(())
+        }
+
+        fn recv(&mut self) -> Result<RoundMsg> {
+            let line = self
+                .reader
+                .next()
+                .ok_or_else(|| anyhow!("relay closed the connection"))??;
+            Ok(serde_json::from_str(&line)?)
         }
-        Ok(parties)
     }
 
-    pub fn nobody_done<F, K, P, const MAX_MSG_IN_LEN: usize>(
-        parties: &VecMap<K, Protocol<F, K, P, MAX_MSG_IN_LEN>>,
-    ) -> bool {
-        // warn if there's disagreement
-        let (mut done, mut not_done) = (
-            Vec::with_capacity(parties.len()),
-            Vec::with_capacity(parties.len()),
-        );
-        for (i, party) in parties.iter() {
-            if matches!(party, Protocol::Done(_)) {
-                done.push(i);
-            } else {
-                not_done.push(i);
+    pub struct Daemon {
+        party_share_counts: PartyShareCounts<KeygenPartyId>,
+        secret_key_shares: VecMap<KeygenShareId, SecretKeyShare>,
+        relay: Option<Relay>,
+    }
+
+    impl Daemon {
+        pub fn new(
+            party_share_counts: PartyShareCounts<KeygenPartyId>,
+            secret_key_shares: VecMap<KeygenShareId, SecretKeyShare>,
+            relay: Option<Relay>,
+        ) -> Self {
+            Self {
+                party_share_counts,
+                secret_key_shares,
+                relay,
+            }
+        }
+
+        pub fn serve(mut self, socket: &Path) -> Result<()> {
+            let listener = UnixListener::bind(socket)?;
+            info!("serving sign requests on {}", socket.display());
+            for stream in listener.incoming() {
+                if let Err(err) = self.handle(stream?) {
+                    warn!("dropped connection: {:#}", err);
+                }
+            }
+            Ok(())
+        }
+
+        fn handle(&mut self, stream: UnixStream) -> Result<()> {
+            let mut writer = stream.try_clone()?;
+            for line in BufReader::new(stream).lines() {
+                let response = match serde_json::from_str(&line?)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|request| self.sign(&request))
+                {
+                    Ok(signature) => {
+                        SignResponse::Signature(hex::encode(signature.to_der().as_bytes()))
+                    }
+                    Err(err) => SignResponse::Error(format!("{:#}", err)),
+                };
+                serde_json::to_writer(&mut writer, &response)?;
+                writer.write_all(b"\n")?;
+            }
+            Ok(())
+        }
+
+        fn sign(&mut self, request: &SignRequest) -> Result<Signature> {
+            if request.session.is_empty() {
+                bail!("session must not be empty");
+            }
+            let digest = MessageDigest::try_from(&hex::decode(&request.digest)?[..])
+                .map_err(|_| anyhow!("digest must be 32 bytes"))?;
+            let mut sign_parties =
+                SignParties::with_max_size(self.party_share_counts.party_count());
+            for &signer in &request.signers {
+                sign_parties
+                    .add(TypedUsize::from_usize(signer))
+                    .map_err(|_| anyhow!("invalid signer {}", signer))?;
+            }
+            info!(
+                "session `{}`: signing {} with parties {:?}",
+                request.session, request.digest, request.signers
+            );
+
+            let mut protocols = Vec::new();
+            for (_, share) in self.secret_key_shares.iter() {
+                let party_id = self
+                    .party_share_counts
+                    .share_to_party_id(share.share().index())
+                    .map_err(fatal)?;
+                if sign_parties.is_member(party_id).map_err(fatal)? {
+                    protocols.push(
+                        new_sign(
+                            share.group(),
+                            share.share(),
+                            &sign_parties,
+                            &digest,
+                            #[cfg(feature = "malicious")]
+                            tofn::gg20::sign::malicious::Behaviour::Honest,
+                        )
+                        .map_err(fatal)?,
+                    );
+                }
             }
+            if protocols.is_empty() {
+                bail!("this daemon holds none of the signers' shares");
+            }
+
+            self.execute(protocols, &request.session)
         }
-        if !done.is_empty() && !not_done.is_empty() {
+
+        /// Execute `protocols` to completion, exchanging their messages with each other
+        /// and with the other daemons through the relay.
+        fn execute(
+            &mut self,
+            mut protocols: Vec<SignProtocol>,
+            session: &str,
+        ) -> Result<Signature> {
+            let mut early_msgs: Vec<RoundMsg> = Vec::new();
+            loop {
+                let mut rounds = Vec::with_capacity(protocols.len());
+                for protocol in protocols {
+                    match protocol {
+                        Protocol::NotDone(round) => rounds.push(round),
+                        Protocol::Done(Ok(signature)) => return Ok(signature),
+                        Protocol::Done(Err(faulters)) => {
+                            bail!("sign failed with faulters {:?}", faulters)
+                        }
+                    }
+                }
+                let round_num = rounds[0].info().round();
+                let total_share_count = rounds[0].info().share_info().total_share_count();
+                let my_ids: Vec<usize> = rounds
+                    .iter()
+                    .map(|round| round.info().share_info().my_id().as_usize())
+                    .collect();
+
+                // send my messages to my own rounds and to the relay
+                let mut msgs_out = Vec::new();
+                for (round, &from) in rounds.iter().zip(&my_ids) {
+                    let p2ps = round.p2ps_out().into_iter().flat_map(|p2ps| p2ps.iter());
+                    for payload in round.bcast_out().into_iter().chain(p2ps.map(|(_, p)| p)) {
+                        msgs_out.push(RoundMsg {
+                            session: session.to_string(),
+                            round: round_num,
+                            from,
+                            payload: hex::encode(payload),
+                        });
+                    }
+                }
+                for msg in &msgs_out {
+                    deliver(&mut rounds, msg)?;
+                    if let Some(relay) = self.relay.as_mut() {
+                        relay.send(msg)?;
+                    }
+                }
+
+                // receive messages from other daemons, keeping those for later rounds
+                let (now, later) = early_msgs
+                    .into_iter()
+                    .partition::<Vec<_>, _>(|msg| msg.round == round_num);
+                early_msgs = later;
+                for msg in &now {
+                    deliver(&mut rounds, msg)?;
+                }
+                while rounds
+                    .iter()
+                    .any(|round| round.expecting_more_msgs_this_round())
+                {
+                    let relay = self.relay.as_mut().ok_or_else(|| {
+                        anyhow!("some signers are not held by this daemon and there is no relay")
+                    })?;
+                    let msg = relay.recv()?;
+                    if msg.session != session || my_ids.contains(&msg.from) {
+                        continue;
+                    }
+                    if msg.round == round_num {
+                        deliver(&mut rounds, &msg)?;
+                    } else if msg.round == round_num + 1
+                        && msg.from < total_share_count
+                        && early_msgs
+                            .iter()
+                            .filter(|early| early.from == msg.from)
+                            .count()
+                            < total_share_count
+                    {
+                        // a signer is at most one round ahead of me
+                        // and sends at most one message to each share in a round
+                        early_msgs.push(msg);
+                    } else if msg.round > round_num {
+                        warn!(
+                            "session `{}`: drop early round {} message from share {} in round {}",
+                            session, msg.round, msg.from, round_num
+                        );
+                    }
+                }
+
+                protocols = rounds
+                    .into_iter()
+                    .map(|round| round.execute_next_round())
+                    .collect::<Result<_, _>>()
+                    .map_err(fatal)?;
+            }
+        }
+    }
+
+    /// Deliver `msg` to each of `rounds`: every round receives every message, including p2ps addressed to others.
+    /// Anyone on the relay can send a message, so drop and log one that is not from a signer share
+    /// rather than fail the sign.
+    fn deliver(rounds: &mut [SignRound], msg: &RoundMsg) -> Result<()> {
+        let total_share_count = match rounds.first() {
+            Some(round) => round.info().share_info().total_share_count(),
+            None => return Ok(()),
+        };
+        if msg.from >= total_share_count {
             warn!(
-                "disagreement: done parties {:?}, not done parties {:?}",
-                done, not_done
+                "session `{}`: drop round {} message from share {} out of {} signer shares",
+                msg.session, msg.round, msg.from, total_share_count
             );
+            return Ok(());
+        }
+        let payload = match hex::decode(&msg.payload) {
+            Ok(payload) => payload,
+            Err(err) => {
+                warn!(
+                    "session `{}`: drop round {} message from share {} with invalid payload: {}",
+                    msg.session, msg.round, msg.from, err
+                );
+                return Ok(());
+            }
+        };
+        for round in rounds.iter_mut() {
+            let from = round
+                .info()
+                .share_index()
+                .share_to_party_id(TypedUsize::from_usize(msg.from))
+                .map_err(fatal)?;
+            round.msg_in(from, &payload).map_err(fatal)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "grpc-server")]
+mod grpc {
+    //! A drop-in replacement for tofnd's `GG20` gRPC service.
+    //!
+    //! Each `Keygen` or `Sign` stream drives all shares of this party. Messages between them are
+    //! delivered locally; only messages for other parties are sent to the client, and traffic the
+    //! client routes back from this party's own uid is ignored.
+    //! Key shares and party uids are kept in the keystore under the key uid.
+
+    use std::{
+        convert::TryFrom,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+    };
+
+    use anyhow::{anyhow, bail, Result};
+    use tofn::{
+        collections::{TypedUsize, VecMap},
+        crypto_tools::message_digest::MessageDigest,
+        gg20::{
+            keygen::{
+                create_party_keypair_and_zksetup, new_keygen, recover_party_keypair, KeygenPartyId,
+                KeygenShareId, SecretKeyShare, SecretRecoveryKey,
+            },
+            sign::{new_sign, SignParties},
+        },
+        keystore::Keystore,
+        sdk::{
+            api::{peek_header, BytesVec, Protocol, ProtocolFaulters},
+            grpc_types::{
+                criminals, key_presence_response, keygen_result, message_in, message_out,
+                recover_response, sign_result, traffic_in, traffic_out, KeyPresenceRequest,
+                KeyPresenceResponse, KeygenInit, KeygenOutput, KeygenResult, MessageIn, MessageOut,
+                RecoverRequest, RecoverResponse, SignInit, SignResult,
+            },
+        },
+    };
+    use tokio::{runtime::Handle, sync::mpsc};
+    use tokio_stream::wrappers::ReceiverStream;
+    use tonic::{transport::Server, Request, Response, Status, Streaming};
+    use tracing::{info, warn};
+
+    use crate::{fatal, storage::StorageFormat};
+
+    #[allow(clippy::all)]
+    mod service {
+        include!(concat!(env!("OUT_DIR"), "/tofnd.GG20.rs"));
+    }
+    use service::g_g20_server::{GG20Server, GG20};
+
+    type MessageOutStream = ReceiverStream<Result<MessageOut, Status>>;
+
+    /// Bound on outgoing messages queued for a slow client
+    const OUTGOING_CAPACITY: usize = 256;
+
+    pub async fn serve(
+        address: SocketAddr,
+        keystore: Keystore,
+        secret_recovery_key: SecretRecoveryKey,
+    ) -> Result<()> {
+        let service = Gg20Service {
+            keystore: Arc::new(Mutex::new(keystore)),
+            secret_recovery_key: Arc::new(secret_recovery_key),
+        };
+        info!("serving tofnd gRPC API on {}", address);
+        Server::builder()
+            .add_service(GG20Server::new(service))
+            .serve(address)
+            .await?;
+        Ok(())
+    }
+
+    struct Gg20Service {
+        keystore: Arc<Mutex<Keystore>>,
+        secret_recovery_key: Arc<SecretRecoveryKey>,
+    }
+
+    #[tonic::async_trait]
+    impl GG20 for Gg20Service {
+        type KeygenStream = MessageOutStream;
+        type SignStream = MessageOutStream;
+
+        async fn recover(
+            &self,
+            request: Request<RecoverRequest>,
+        ) -> Result<Response<RecoverResponse>, Status> {
+            let keystore = self.keystore.clone();
+            let secret_recovery_key = self.secret_recovery_key.clone();
+            let request = request.into_inner();
+            let response = tokio::task::spawn_blocking(move || {
+                recover(&keystore, &secret_recovery_key, &request)
+            })
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+            let response = match response {
+                Ok(()) => recover_response::Response::Success,
+                Err(err) => {
+                    warn!("recover failed: {:#}", err);
+                    recover_response::Response::Fail
+                }
+            };
+            Ok(Response::new(RecoverResponse {
+                response: response as i32,
+            }))
+        }
+
+        async fn keygen(
+            &self,
+            request: Request<Streaming<MessageIn>>,
+        ) -> Result<Response<Self::KeygenStream>, Status> {
+            let keystore = self.keystore.clone();
+            let secret_recovery_key = self.secret_recovery_key.clone();
+            Ok(spawn_stream(request, move |channel| {
+                keygen(channel, &keystore, &secret_recovery_key)
+            }))
+        }
+
+        async fn sign(
+            &self,
+            request: Request<Streaming<MessageIn>>,
+        ) -> Result<Response<Self::SignStream>, Status> {
+            let keystore = self.keystore.clone();
+            Ok(spawn_stream(request, move |channel| {
+                sign(channel, &keystore)
+            }))
+        }
+
+        async fn key_presence(
+            &self,
+            request: Request<KeyPresenceRequest>,
+        ) -> Result<Response<KeyPresenceResponse>, Status> {
+            let request = request.into_inner();
+            let response = match lock(&self.keystore).share_indices(&request.key_uid) {
+                Ok(indices) if indices.is_empty() => key_presence_response::Response::Absent,
+                Ok(_) => key_presence_response::Response::Present,
+                Err(err) => {
+                    warn!("key presence of `{}` failed: {:#}", request.key_uid, err);
+                    key_presence_response::Response::Fail
+                }
+            };
+            Ok(Response::new(KeyPresenceResponse {
+                response: response as i32,
+            }))
         }
-        done.is_empty()
     }
 
-    fn next_round<F, K, P, const MAX_MSG_IN_LEN: usize>(
-        parties: VecMap<K, Protocol<F, K, P, MAX_MSG_IN_LEN>>,
-        current_round: usize,
-    ) -> TofnResult<VecMap<K, Protocol<F, K, P, MAX_MSG_IN_LEN>>>
+    /// Run `f` on a blocking thread with the request stream, streaming its messages back.
+    /// Errors end the response stream with an `aborted` status.
+    fn spawn_stream<F>(request: Request<Streaming<MessageIn>>, f: F) -> Response<MessageOutStream>
     where
-        K: Clone,
+        F: FnOnce(&mut Channel) -> Result<()> + Send + 'static,
     {
-        // extract current round from parties
-        let mut rounds: VecMap<K, _> = parties
-            .into_iter()
-            .map(|(i, party)| match party {
-                Protocol::NotDone(round) => round,
-                Protocol::Done(_) => panic!("next_round called but party {} is done", i),
-            })
-            .collect();
+        let (outgoing, rx) = mpsc::channel(OUTGOING_CAPACITY);
+        let mut channel = Channel {
+            incoming: request.into_inner(),
+            outgoing,
+            runtime: Handle::current(),
+        };
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = f(&mut channel) {
+                warn!("stream failed: {:#}", err);
+                let _ = channel
+                    .outgoing
+                    .blocking_send(Err(Status::aborted(format!("{:#}", err))));
+            }
+        });
+        Response::new(ReceiverStream::new(rx))
+    }
 
-        // deliver bcasts
-        let bcasts: VecMap<K, Option<BytesVec>> = rounds
-            .iter()
-            .map(|(_, round)| round.bcast_out().cloned())
-            .collect();
-        for (from, bcast) in bcasts.into_iter() {
-            if let Some(bytes) = bcast {
-                if from.as_usize() == 0 {
-                    debug!("round {} bcast byte length {}", current_round, bytes.len());
-                }
+    /// Blocking access to one `Keygen` or `Sign` stream
+    struct Channel {
+        incoming: Streaming<MessageIn>,
+        outgoing: mpsc::Sender<Result<MessageOut, Status>>,
+        runtime: Handle,
+    }
 
-                for (_, round) in rounds.iter_mut() {
-                    round.msg_in(
-                        round
-                            .info()
-                            .party_share_counts()
-                            .share_to_party_id(from)
-                            .unwrap(),
-                        &bytes,
-                    )?;
+    impl Channel {
+        fn recv(&mut self) -> Result<message_in::Data> {
+            loop {
+                let msg = self
+                    .runtime
+                    .block_on(self.incoming.message())?
+                    .ok_or_else(|| anyhow!("client closed the stream"))?;
+                match msg.data {
+                    Some(message_in::Data::Abort(true)) => bail!("client aborted"),
+                    Some(message_in::Data::Abort(false)) | None => continue,
+                    Some(data) => return Ok(data),
                 }
             }
         }
 
-        // deliver p2ps
-        let all_p2ps: VecMap<K, Option<HoleVecMap<K, BytesVec>>> = rounds
+        fn send(&self, data: message_out::Data) -> Result<()> {
+            self.outgoing
+                .blocking_send(Ok(MessageOut { data: Some(data) }))
+                .map_err(|_| anyhow!("client closed the stream"))
+        }
+    }
+
+    fn keygen(
+        channel: &mut Channel,
+        keystore: &Mutex<Keystore>,
+        secret_recovery_key: &SecretRecoveryKey,
+    ) -> Result<()> {
+        let init = match channel.recv()? {
+            message_in::Data::KeygenInit(init) => init,
+            _ => bail!("expected keygen init"),
+        };
+        let party_share_counts = init.to_party_share_counts().map_err(fatal)?;
+        let my_party_id: TypedUsize<KeygenPartyId> = init.my_party_id().map_err(fatal)?;
+        if !lock(keystore).share_indices(&init.new_key_uid)?.is_empty() {
+            bail!("key `{}` already exists", init.new_key_uid);
+        }
+        info!(
+            "keygen `{}` as party {} of {:?}",
+            init.new_key_uid, my_party_id, init.party_uids
+        );
+
+        let party_keygen_data = create_party_keypair_and_zksetup(
+            my_party_id,
+            secret_recovery_key,
+            init.new_key_uid.as_bytes(),
+        )
+        .map_err(fatal)?;
+        let protocols = (0..party_share_counts
+            .party_share_count(my_party_id)
+            .map_err(fatal)?)
+            .map(|subshare_id| {
+                new_keygen(
+                    party_share_counts.clone(),
+                    init.threshold as usize,
+                    my_party_id,
+                    subshare_id,
+                    &party_keygen_data,
+                    #[cfg(feature = "malicious")]
+                    tofn::gg20::keygen::malicious::Behaviour::Honest,
+                )
+            })
+            .collect::<Result<_, _>>()
+            .map_err(fatal)?;
+
+        let result = match execute(channel, protocols, &init.party_uids)? {
+            Ok(shares) => {
+                let output = keygen_output(&shares)?;
+                let shares: VecMap<KeygenShareId, SecretKeyShare> = shares.into_iter().collect();
+                let mut keystore = lock(keystore);
+                keystore.insert_key(&init.new_key_uid, &party_share_counts, &shares)?;
+                keystore.set_party_uids(&init.new_key_uid, &init.party_uids)?;
+                keygen_result::KeygenResultData::Data(output)
+            }
+            Err(faulters) => keygen_result::KeygenResultData::Criminals(
+                criminals(&faulters, &init.party_uids).map_err(fatal)?,
+            ),
+        };
+        channel.send(message_out::Data::KeygenResult(KeygenResult {
+            keygen_result_data: Some(result),
+        }))
+    }
+
+    /// Public key and recovery info of this party's keygen `shares`, see [recover]
+    fn keygen_output(shares: &[SecretKeyShare]) -> Result<KeygenOutput> {
+        let group = shares
+            .first()
+            .ok_or_else(|| anyhow!("keygen produced no key shares"))?
+            .group();
+        let private_recover_info = shares
+            .iter()
+            .map(|share| share.recovery_info())
+            .collect::<Result<Vec<BytesVec>, _>>()
+            .map_err(fatal)?;
+        Ok(KeygenOutput {
+            pub_key: group.pubkey_compressed().to_vec(),
+            group_recover_info: group.all_shares_bytes().map_err(fatal)?,
+            private_recover_info: StorageFormat::Bincode.encode(&private_recover_info)?,
+        })
+    }
+
+    fn sign(channel: &mut Channel, keystore: &Mutex<Keystore>) -> Result<()> {
+        let init = match channel.recv()? {
+            message_in::Data::SignInit(init) => init,
+            _ => bail!("expected sign init"),
+        };
+        let (keygen_party_uids, party_share_counts, shares) = {
+            let keystore = lock(keystore);
+            let indices = keystore.share_indices(&init.key_uid)?;
+            if indices.is_empty() {
+                warn!("no key `{}`, asking the client to recover", init.key_uid);
+                return channel.send(message_out::Data::NeedRecover(true));
+            }
+            let shares = indices
+                .into_iter()
+                .map(|index| keystore.secret_key_share(&init.key_uid, index))
+                .collect::<Result<Vec<_>>>()?;
+            (
+                keystore.party_uids(&init.key_uid)?,
+                keystore.party_share_counts(&init.key_uid)?,
+                shares,
+            )
+        };
+        let sign_parties: SignParties = init.to_sign_parties(&keygen_party_uids).map_err(fatal)?;
+        let digest = MessageDigest::try_from(&init.message_to_sign[..])
+            .map_err(|_| anyhow!("message to sign must be a 32-byte digest"))?;
+        info!(
+            "sign `{}` with key `{}` by {:?}",
+            init.new_sig_uid, init.key_uid, init.party_uids
+        );
+
+        // party uids in sign party id order
+        let sign_party_uids: Vec<String> = keygen_party_uids
             .iter()
-            .map(|(_, round)| round.p2ps_out().cloned())
+            .enumerate()
+            .filter(|&(party_id, _)| {
+                sign_parties
+                    .is_member(TypedUsize::from_usize(party_id))
+                    .unwrap_or(false)
+            })
+            .map(|(_, uid)| uid.clone())
             .collect();
-        for (from, p2ps) in all_p2ps.into_iter() {
-            if let Some(p2ps) = p2ps {
-                if from.as_usize() == 0 {
-                    debug!(
-                        "round {} p2p byte length {}",
-                        current_round,
-                        p2ps.get(TypedUsize::from_usize(1)).unwrap().len()
-                    );
+        let my_party_id = party_share_counts
+            .share_to_party_id(shares[0].share().index())
+            .map_err(fatal)?;
+        if !sign_parties.is_member(my_party_id).map_err(fatal)? {
+            bail!("this party is not a signer of `{}`", init.new_sig_uid);
+        }
+        let protocols = shares
+            .iter()
+            .map(|share| {
+                new_sign(
+                    share.group(),
+                    share.share(),
+                    &sign_parties,
+                    &digest,
+                    #[cfg(feature = "malicious")]
+                    tofn::gg20::sign::malicious::Behaviour::Honest,
+                )
+            })
+            .collect::<Result<_, _>>()
+            .map_err(fatal)?;
+
+        let result = match execute(channel, protocols, &sign_party_uids)? {
+            Ok(signatures) => {
+                sign_result::SignResultData::Signature(signatures[0].to_der().as_bytes().to_vec())
+            }
+            Err(faulters) => sign_result::SignResultData::Criminals(
+                criminals(&faulters, &sign_party_uids).map_err(fatal)?,
+            ),
+        };
+        channel.send(message_out::Data::SignResult(SignResult {
+            sign_result_data: Some(result),
+        }))
+    }
+
+    /// Execute `protocols`, the shares of one party, to completion.
+    /// `party_uids` are the uids of all parties in protocol party id order.
+    fn execute<F, K, P>(
+        channel: &mut Channel,
+        mut protocols: Vec<Protocol<F, K, P>>,
+        party_uids: &[String],
+    ) -> Result<Result<Vec<F>, ProtocolFaulters<P>>> {
+        let mut early_traffic = Vec::new();
+        loop {
+            let mut rounds = Vec::with_capacity(protocols.len());
+            let mut outputs = Vec::with_capacity(protocols.len());
+            for protocol in protocols {
+                match protocol {
+                    Protocol::NotDone(round) => rounds.push(round),
+                    Protocol::Done(Ok(output)) => outputs.push(output),
+                    Protocol::Done(Err(faulters)) => return Ok(Err(faulters)),
                 }
-                for (_, bytes) in p2ps {
-                    for (_, round) in rounds.iter_mut() {
-                        round.msg_in(
-                            round
-                                .info()
-                                .party_share_counts()
-                                .share_to_party_id(from)
-                                .unwrap(), // no easy access to from_party_id
-                            &bytes,
-                        )?;
+            }
+            if rounds.is_empty() {
+                return Ok(Ok(outputs));
+            }
+            if !outputs.is_empty() {
+                bail!("shares of this party finished in different rounds");
+            }
+
+            let round_num = rounds[0].info().round();
+            let my_party_id = rounds[0].info().party_id();
+            let my_uid = party_uids
+                .get(my_party_id.as_usize())
+                .ok_or_else(|| anyhow!("no party uid for party {}", my_party_id))?
+                .clone();
+
+            // deliver all my messages to my own rounds, including p2ps addressed to other parties:
+            // every party must receive every message. Send the rest to the client.
+            let mut local = Vec::new();
+            for round in rounds.iter() {
+                for traffic in traffic_out(round, party_uids).map_err(fatal)? {
+                    if traffic.is_broadcast || traffic.to_party_uid != my_uid {
+                        channel.send(message_out::Data::Traffic(traffic.clone()))?;
                     }
+                    local.push(traffic.payload);
+                }
+            }
+            for payload in &local {
+                for round in rounds.iter_mut() {
+                    round.msg_in(my_party_id, payload).map_err(fatal)?;
                 }
             }
-        }
 
-        // compute next round's parties
-        rounds
-            .into_iter()
-            .map(|(i, round)| {
-                if round.expecting_more_msgs_this_round() {
-                    warn!(
-                        "all messages delivered this round but party {} still expecting messages",
-                        i,
-                    );
+            // receive traffic from other parties, keeping that of later rounds
+            let (now, later): (Vec<_>, Vec<_>) = early_traffic
+                .into_iter()
+                .partition(|(round, _)| *round == round_num);
+            early_traffic = later;
+            for (_, traffic) in &now {
+                for round in rounds.iter_mut() {
+                    traffic_in(round, traffic, party_uids).map_err(fatal)?;
                 }
-                round.execute_next_round()
+            }
+            while rounds
+                .iter()
+                .any(|round| round.expecting_more_msgs_this_round())
+            {
+                let traffic = match channel.recv()? {
+                    message_in::Data::Traffic(traffic) => traffic,
+                    _ => bail!("expected traffic"),
+                };
+                if traffic.from_party_uid == my_uid {
+                    continue;
+                }
+                let traffic_round =
+                    peek_header::<K>(&traffic.payload).map_or(round_num, |header| header.round);
+                if traffic_round > round_num {
+                    early_traffic.push((traffic_round, traffic));
+                    continue;
+                }
+                for round in rounds.iter_mut() {
+                    traffic_in(round, &traffic, party_uids).map_err(fatal)?;
+                }
+            }
+
+            protocols = rounds
+                .into_iter()
+                .map(|round| round.execute_next_round())
+                .collect::<Result<_, _>>()
+                .map_err(fatal)?;
+        }
+    }
+
+    /// Recover this party's key shares from the output of a past keygen, see [keygen_output].
+    fn recover(
+        keystore: &Mutex<Keystore>,
+        secret_recovery_key: &SecretRecoveryKey,
+        request: &RecoverRequest,
+    ) -> Result<()> {
+        let (init, output) = match (&request.keygen_init, &request.keygen_output) {
+            (Some(init), Some(output)) => (init, output),
+            _ => bail!("missing keygen init or output"),
+        };
+        if !lock(keystore).share_indices(&init.new_key_uid)?.is_empty() {
+            info!("key `{}` already present", init.new_key_uid);
+            return Ok(());
+        }
+        let party_share_counts = init.to_party_share_counts().map_err(fatal)?;
+        let my_party_id: TypedUsize<KeygenPartyId> = init.my_party_id().map_err(fatal)?;
+        let private_recover_info: Vec<BytesVec> =
+            StorageFormat::Bincode.decode(&output.private_recover_info)?;
+        if private_recover_info.len()
+            != party_share_counts
+                .party_share_count(my_party_id)
+                .map_err(fatal)?
+        {
+            bail!("recovery info does not match the party share count");
+        }
+
+        let party_keypair = recover_party_keypair(
+            my_party_id,
+            secret_recovery_key,
+            init.new_key_uid.as_bytes(),
+        )
+        .map_err(fatal)?;
+        let shares: VecMap<KeygenShareId, SecretKeyShare> = private_recover_info
+            .iter()
+            .enumerate()
+            .map(|(subshare_id, recovery_info)| {
+                SecretKeyShare::recover(
+                    &party_keypair,
+                    recovery_info,
+                    &output.group_recover_info,
+                    &output.pub_key,
+                    my_party_id,
+                    subshare_id,
+                    party_share_counts.clone(),
+                    init.threshold as usize,
+                )
             })
-            .collect::<TofnResult<_>>()
+            .collect::<Result<_, _>>()
+            .map_err(fatal)?;
+
+        let mut keystore = lock(keystore);
+        keystore.insert_key(&init.new_key_uid, &party_share_counts, &shares)?;
+        keystore.set_party_uids(&init.new_key_uid, &init.party_uids)?;
+        info!("recovered key `{}`", init.new_key_uid);
+        Ok(())
+    }
+
+    fn lock(keystore: &Mutex<Keystore>) -> std::sync::MutexGuard<'_, Keystore> {
+        // a panic while holding the lock leaves the keystore itself consistent
+        keystore
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
     }
 }
@@ -0,0 +1,174 @@
+//! Trusted-dealer keygen for multisig, as in [gg20::ceygen](crate::gg20::ceygen).
+//!
+//! The signing key of each share is a Shamir share of Alice's key,
+//! so any `threshold + 1` shares can recover it.
+//! Multisig signatures still verify against the pubkey of each share,
+//! not against Alice's pubkey.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use anyhow::Result;
+use bincode::Options;
+use tracing::{error, info};
+
+use super::keygen::{
+    GroupPublicInfo, KeygenPartyId, KeygenShareId, SecretKeyShare, ShareSecretInfo,
+    MAX_TOTAL_SHARE_COUNT,
+};
+use crate::{
+    collections::{TypedUsize, VecMap},
+    crypto_tools::{k256_serde, ss::Ss},
+    gg20::ceygen::validate_secret_key,
+    sdk::api::{PartyShareCounts, TofnFatal, TofnResult},
+};
+
+/// The tuple of bincode-encoded PartyShareCounts, and bincode-encoded SecretKeyShares.
+pub type Ceygen = (Vec<u8>, Vec<(TypedUsize<KeygenShareId>, Vec<u8>)>);
+
+/// Validate the party parameters, then split Alice's key into an bincode-encoded byte-array of keyshares.
+pub fn ceygen(parties: usize, threshold: usize, alice_key_byte_array: &[u8]) -> Result<Ceygen> {
+    let alice_key = validate_secret_key(alice_key_byte_array)?;
+    let party_share_counts = PartyShareCounts::from_vec(vec![1; parties])
+        .map_err(|_| anyhow::Error::msg("invalid party count"))?;
+    let secret_key_shares = initialize_parties(
+        &party_share_counts,
+        threshold,
+        &Ss::new_byok(threshold, *alice_key),
+    )
+    .map_err(|err| anyhow::anyhow!("bad ceygen; need parties >= threshold+1: {}", err))?;
+
+    // encode keyshares
+    let bincode = bincode::DefaultOptions::new();
+    let secret_key_shares_encoded = secret_key_shares
+        .into_iter()
+        .map(|(index, share)| Ok((index, bincode.serialize(&share)?)))
+        .collect::<Result<_>>()?;
+
+    // encode party_share_counts
+    let party_share_counts_encoded = bincode
+        .serialize(&party_share_counts)
+        .map_err(|err| anyhow::Error::msg("Failed to serialize PartyShareCounts").context(err))?;
+
+    info!(
+        "multisig ceygen generated {}-of-{} keys",
+        threshold, parties
+    );
+    Ok((party_share_counts_encoded, secret_key_shares_encoded))
+}
+
+/// Deal the shares of `ss` to all parties and build their `SecretKeyShare`s.
+pub(crate) fn initialize_parties(
+    party_share_counts: &PartyShareCounts<KeygenPartyId>,
+    threshold: usize,
+    ss: &Ss,
+) -> TofnResult<VecMap<KeygenShareId, SecretKeyShare>> {
+    let total_share_count = party_share_counts.total_share_count();
+    if threshold >= total_share_count || total_share_count > MAX_TOTAL_SHARE_COUNT {
+        error!(
+            "invalid (total_share_count, threshold, max_share_count): ({},{},{})",
+            total_share_count, threshold, MAX_TOTAL_SHARE_COUNT
+        );
+        return Err(TofnFatal);
+    }
+    if ss.get_threshold() > threshold {
+        error!(
+            "secret sharing threshold {} exceeds threshold {}",
+            ss.get_threshold(),
+            threshold
+        );
+        return Err(TofnFatal);
+    }
+
+    let shares = ss.shares(total_share_count);
+    let all_pubkeys: VecMap<KeygenShareId, k256_serde::ProjectivePoint> = shares
+        .iter()
+        .map(|share| (k256::ProjectivePoint::GENERATOR * share.get_scalar()).into())
+        .collect();
+    let group = GroupPublicInfo::new(party_share_counts.clone(), threshold, all_pubkeys);
+
+    Ok(shares
+        .iter()
+        .enumerate()
+        .map(|(index, share)| {
+            SecretKeyShare::new(
+                group.clone(),
+                ShareSecretInfo::new(TypedUsize::from_usize(index), *share.get_scalar()),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use bincode::Options;
+    use ecdsa::hazmat::VerifyPrimitive;
+
+    use super::ceygen;
+    use crate::{
+        collections::{Subset, TypedUsize, VecMap},
+        crypto_tools::vss::lagrange_coefficient,
+        multisig::{
+            keygen::{KeygenShareId, SecretKeyShare},
+            sign::{new_sign, MessageDigest, SignShareId},
+        },
+        sdk::local::execute_honest,
+    };
+
+    #[test]
+    fn byok_sign() {
+        let alice_key = k256::Scalar::from(42u32);
+        let (_, encoded_shares) = ceygen(3, 1, &alice_key.to_bytes()).unwrap();
+        let key_shares: VecMap<KeygenShareId, SecretKeyShare> = encoded_shares
+            .iter()
+            .map(|(_, bytes)| bincode::DefaultOptions::new().deserialize(bytes).unwrap())
+            .collect();
+        assert_eq!(key_shares.len(), 3);
+
+        // any threshold + 1 shares recover alice's key
+        let indices = [0, 2];
+        let recovered =
+            indices
+                .iter()
+                .enumerate()
+                .fold(k256::Scalar::zero(), |sum, (i, &index)| {
+                    let share = key_shares.get(TypedUsize::from_usize(index)).unwrap();
+                    sum + share.share().signing_key() * &lagrange_coefficient(i, &indices).unwrap()
+                });
+        assert_eq!(recovered, alice_key);
+
+        // the shares sign as usual
+        let msg = MessageDigest::try_from(&[42; 32][..]).unwrap();
+        let mut sign_parties = Subset::with_max_size(3);
+        for &index in &indices {
+            sign_parties.add(TypedUsize::from_usize(index)).unwrap();
+        }
+        let parties: VecMap<SignShareId, _> = indices
+            .iter()
+            .map(|&index| {
+                let key_share = key_shares.get(TypedUsize::from_usize(index)).unwrap();
+                new_sign(key_share.group(), key_share.share(), &sign_parties, &msg).unwrap()
+            })
+            .collect();
+        let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+        let hashed_msg = k256::Scalar::from(&msg);
+        for (_, sig_shares) in execute_honest(parties).unwrap() {
+            assert_eq!(sig_shares.len(), 2);
+            for sig_share in sig_shares {
+                let keygen_id = group
+                    .party_share_counts()
+                    .party_to_share_id(sig_share.party_id, sig_share.subshare_id)
+                    .unwrap();
+                group
+                    .all_pubkeys()
+                    .get(keygen_id)
+                    .unwrap()
+                    .as_ref()
+                    .to_affine()
+                    .verify_prehashed(hashed_msg.into(), &sig_share.signature)
+                    .unwrap();
+            }
+        }
+    }
+}
@@ -6,17 +6,19 @@ use crate::{
         implementer_api::{new_protocol, ProtocolBuilder},
     },
 };
+use k256::ecdsa::SigningKey;
 use serde::{Deserialize, Serialize};
 use tracing::error;
+use zeroize::Zeroize;
 
 use super::r1;
 pub use super::secret_key_share::*;
 
 /// Maximum byte length of messages exchanged during keygen.
-pub const MAX_MSG_LEN: usize = 100;
+pub const MAX_MSG_LEN: usize = 200;
 
 pub use super::secret_key_share::*;
-pub use rng::SecretRecoveryKey;
+pub use rng::{SecretRecoveryKey, SecretRecoveryKeyProvider};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub struct KeygenShareId;
@@ -24,7 +26,7 @@ pub struct KeygenShareId;
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub struct KeygenPartyId;
 
-pub type KeygenProtocol = Protocol<SecretKeyShare, KeygenShareId, KeygenPartyId, MAX_MSG_LEN>;
+pub type KeygenProtocol = Protocol<SecretKeyShare, KeygenShareId, KeygenPartyId>;
 pub type KeygenProtocolBuilder = ProtocolBuilder<SecretKeyShare, KeygenShareId>;
 pub type KeygenPartyShareCounts = PartyShareCounts<KeygenPartyId>;
 
@@ -34,14 +36,66 @@ pub const MAX_PARTY_SHARE_COUNT: usize = MAX_TOTAL_SHARE_COUNT;
 
 /// Initialize a new keygen protocol
 // #[allow(clippy::too_many_arguments)]
-pub fn new_keygen(
+pub fn new_keygen<P: SecretRecoveryKeyProvider + ?Sized>(
     party_share_counts: KeygenPartyShareCounts,
     threshold: usize,
     my_party_id: TypedUsize<KeygenPartyId>,
     my_subshare_id: usize, // in 0..party_share_counts[my_party_id]
-    secret_recovery_key: &rng::SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<KeygenProtocol> {
+    let my_keygen_id =
+        check_keygen_args(&party_share_counts, threshold, my_party_id, my_subshare_id)?;
+
+    let round2 = r1::start(
+        my_keygen_id,
+        threshold,
+        party_share_counts.clone(),
+        secret_recovery_key,
+        session_nonce,
+    )?;
+
+    new_protocol(party_share_counts, my_keygen_id, MAX_MSG_LEN, round2)
+}
+
+/// Like [new_keygen] but use an existing `signing_key`, eg. from an HSM, instead of deriving one.
+/// The protocol only assembles the verifying keys of all shares.
+pub fn new_keygen_with_signing_key(
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    my_party_id: TypedUsize<KeygenPartyId>,
+    my_subshare_id: usize, // in 0..party_share_counts[my_party_id]
+    signing_key: &SigningKey,
+    session_nonce: &[u8],
+) -> TofnResult<KeygenProtocol> {
+    let my_keygen_id =
+        check_keygen_args(&party_share_counts, threshold, my_party_id, my_subshare_id)?;
+
+    let mut signing_key_bytes = signing_key.to_bytes();
+    let signing_key = k256::SecretKey::from_be_bytes(&signing_key_bytes).map_err(|_| {
+        error!("invalid signing key");
+        TofnFatal
+    });
+    signing_key_bytes.zeroize();
+
+    let round2 = r1::start_with_signing_key(
+        my_keygen_id,
+        threshold,
+        party_share_counts.clone(),
+        *signing_key?.to_nonzero_scalar(),
+        session_nonce,
+    )?;
+
+    new_protocol(party_share_counts, my_keygen_id, MAX_MSG_LEN, round2)
+}
+
+/// Validate the arguments of [new_keygen] and return my keygen share id
+fn check_keygen_args(
+    party_share_counts: &KeygenPartyShareCounts,
+    threshold: usize,
+    my_party_id: TypedUsize<KeygenPartyId>,
+    my_subshare_id: usize,
+) -> TofnResult<TypedUsize<KeygenShareId>> {
     // TODO refactor arg validation code with gg20
     // validate args
     if party_share_counts
@@ -69,13 +123,5 @@ pub fn new_keygen(
         return Err(TofnFatal);
     }
 
-    let round2 = r1::start(
-        my_keygen_id,
-        threshold,
-        party_share_counts.clone(),
-        secret_recovery_key,
-        session_nonce,
-    )?;
-
-    new_protocol(party_share_counts, my_keygen_id, round2)
+    Ok(my_keygen_id)
 }
@@ -6,4 +6,4 @@ mod r2;
 mod secret_key_share;
 
 #[cfg(test)]
-pub(super) mod tests; // pub(super) so that sign module can see tests::execute_keygen
+pub(crate) mod tests; // pub(crate) so that other modules can see tests::execute_keygen
@@ -5,25 +5,47 @@ use crate::{
     crypto_tools::{k256_serde, rng},
     multisig,
     sdk::{
-        api::TofnResult,
+        api::{TofnFatal, TofnResult},
         implementer_api::{serialize, ProtocolBuilder, RoundBuilder},
     },
 };
-use ecdsa::elliptic_curve::Field;
+use ecdsa::{
+    elliptic_curve::{ops::Reduce, Field},
+    hazmat::SignPrimitive,
+};
+use hmac::digest::FixedOutput;
+use k256::ecdsa::Signature;
 use serde::{Deserialize, Serialize};
+use sha2::{digest::Update, Digest, Sha256};
 
 use super::{r2, KeygenPartyShareCounts, KeygenProtocolBuilder, KeygenShareId};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bcast {
     pub(super) verifying_key: k256_serde::ProjectivePoint,
+    /// Proof of possession of the signing key, see [pop_msg]
+    pub(super) pop: Signature,
 }
 
-pub fn start(
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Bcast {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        use crate::crypto_tools::k256_serde::arbitrary_scalar;
+
+        let verifying_key = u.arbitrary()?;
+        let (r, s) = (arbitrary_scalar(u)?, arbitrary_scalar(u)?);
+        Ok(Self {
+            verifying_key,
+            pop: Signature::from_scalars(r, s).map_err(|_| arbitrary::Error::IncorrectFormat)?,
+        })
+    }
+}
+
+pub fn start<P: rng::SecretRecoveryKeyProvider + ?Sized>(
     my_keygen_id: TypedUsize<KeygenShareId>,
     threshold: usize,
     party_share_counts: KeygenPartyShareCounts,
-    secret_recovery_key: &rng::SecretRecoveryKey,
+    secret_recovery_key: &P,
     session_nonce: &[u8],
 ) -> TofnResult<KeygenProtocolBuilder> {
     let rng = rng::rng_seed(
@@ -33,20 +55,61 @@ pub fn start(
         session_nonce,
     )?;
 
-    let signing_key = k256::Scalar::random(rng);
+    start_with_signing_key(
+        my_keygen_id,
+        threshold,
+        party_share_counts,
+        k256::Scalar::random(rng),
+        session_nonce,
+    )
+}
+
+pub fn start_with_signing_key(
+    my_keygen_id: TypedUsize<KeygenShareId>,
+    threshold: usize,
+    party_share_counts: KeygenPartyShareCounts,
+    signing_key: k256::Scalar,
+    session_nonce: &[u8],
+) -> TofnResult<KeygenProtocolBuilder> {
     let verifying_key = k256::ProjectivePoint::GENERATOR * signing_key;
 
+    // self-sign a tag bound to this session so that nobody can register a key it does not control
+    let msg = pop_msg(my_keygen_id, &verifying_key, session_nonce);
+    let pop = signing_key
+        .try_sign_prehashed(rng::rfc6979_ephemeral_scalar(&signing_key, &msg)?, msg)
+        .map_err(|_| TofnFatal)?
+        .0;
+
     let bcast_out = Some(serialize(&Bcast {
         verifying_key: verifying_key.into(),
+        pop,
     })?);
 
     Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
         Box::new(r2::R2 {
             threshold,
             party_share_counts,
-            signing_key,
+            signing_key: signing_key.into(),
+            session_nonce: session_nonce.to_vec(),
         }),
         bcast_out,
         None,
     )))
 }
+
+/// The message signed by share `keygen_id` to prove possession of the signing key of `verifying_key`
+pub(super) fn pop_msg(
+    keygen_id: TypedUsize<KeygenShareId>,
+    verifying_key: &k256::ProjectivePoint,
+    session_nonce: &[u8],
+) -> k256::Scalar {
+    <k256::Scalar as Reduce<k256::U256>>::from_be_bytes_reduced(
+        Sha256::new()
+            .chain(multisig::KEYGEN_POP_TAG.to_be_bytes())
+            .chain(keygen_id.to_bytes())
+            .chain(k256_serde::point_to_bytes(verifying_key))
+            .chain((session_nonce.len() as u64).to_be_bytes())
+            .chain(session_nonce)
+            .finalize_fixed(),
+    )
+}
@@ -1,8 +1,11 @@
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
+
+use ecdsa::hazmat::VerifyPrimitive;
 use tracing::warn;
 
 use crate::{
     collections::{FillVecMap, P2ps},
+    crypto_tools::k256_serde::SecretScalar,
     sdk::{
         api::{Fault::ProtocolFault, TofnResult},
         implementer_api::{Executer, ProtocolBuilder, ProtocolInfo},
@@ -18,7 +21,8 @@ use super::{
 pub(super) struct R2 {
     pub(super) threshold: usize,
     pub(super) party_share_counts: KeygenPartyShareCounts,
-    pub(super) signing_key: k256::Scalar,
+    pub(super) signing_key: SecretScalar,
+    pub(super) session_nonce: Vec<u8>,
 }
 
 impl Executer for R2 {
@@ -60,12 +64,32 @@ impl Executer for R2 {
             return Ok(ProtocolBuilder::Done(Err(faulters)));
         }
 
+        // anyone whose proof of possession fails to verify is a faulter
+        for (peer_keygen_id, bcast) in bcasts_in.iter_some() {
+            let verifying_key = bcast.verifying_key.as_ref();
+            let msg = r1::pop_msg(peer_keygen_id, verifying_key, &self.session_nonce);
+            if verifying_key
+                .to_affine()
+                .verify_prehashed(msg, &bcast.pop)
+                .is_err()
+            {
+                warn!(
+                    "peer {} says: invalid proof of possession from peer {} in round 2",
+                    my_keygen_id, peer_keygen_id
+                );
+                faulters.set(peer_keygen_id, ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
         // everyone sent a bcast---unwrap all bcasts
         let all_verifying_keys = bcasts_in.map_to_vecmap(|bcast| bcast.verifying_key)?;
 
         Ok(ProtocolBuilder::Done(Ok(SecretKeyShare::new(
             GroupPublicInfo::new(self.party_share_counts, self.threshold, all_verifying_keys),
-            ShareSecretInfo::new(my_keygen_id, self.signing_key),
+            ShareSecretInfo::new(my_keygen_id, *self.signing_key.as_ref()),
         ))))
     }
 
@@ -7,12 +7,15 @@ use crate::{
     sdk::api::{BytesVec, TofnResult},
 };
 use serde::{Deserialize, Serialize};
+use serde_with::{hex::Hex, serde_as, IfIsHumanReadable};
 use zeroize::Zeroize;
 
 /// Keygen share output to be sent over the wire
 /// TODO [encoded_pubkey] should be a `[u8; 33]` except `serde` doesn't support length-33 arrays
+#[serde_as]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct KeygenShare {
+    #[serde_as(as = "IfIsHumanReadable<Hex>")]
     pub encoded_pubkey: BytesVec, // SEC1-encoded secp256k1 curve point
     pub party_id: TypedUsize<KeygenPartyId>,
     pub subshare_id: usize,
@@ -78,7 +81,7 @@ impl GroupPublicInfo {
             .collect()
     }
 
-    pub(super) fn new(
+    pub(crate) fn new(
         party_share_counts: KeygenPartyShareCounts,
         threshold: usize,
         all_pubkeys: VecMap<KeygenShareId, k256_serde::ProjectivePoint>,
@@ -96,7 +99,10 @@ impl ShareSecretInfo {
         self.index
     }
 
-    pub(super) fn new(index: TypedUsize<KeygenShareId>, signing_key: k256::Scalar) -> Self {
+    pub(in super::super) fn new(
+        index: TypedUsize<KeygenShareId>,
+        signing_key: k256::Scalar,
+    ) -> Self {
         Self { index, signing_key }
     }
 
@@ -105,6 +111,33 @@ impl ShareSecretInfo {
     }
 }
 
+/// A consistent key share: `all_pubkeys` are the pubkeys of arbitrary signing keys,
+/// one of which is the signing key of this share.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SecretKeyShare {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let party_share_counts: KeygenPartyShareCounts = u.arbitrary()?;
+        let share_count = party_share_counts.total_share_count();
+        let threshold = u.int_in_range(0..=share_count - 1)?;
+        let signing_keys: VecMap<KeygenShareId, k256::Scalar> = (0..share_count)
+            .map(|_| k256_serde::arbitrary_scalar(u))
+            .collect::<arbitrary::Result<_>>()?;
+        let index = TypedUsize::from_usize(u.int_in_range(0..=share_count - 1)?);
+
+        let all_pubkeys = signing_keys.ref_map(|signing_key| {
+            k256_serde::ProjectivePoint::from(k256::ProjectivePoint::GENERATOR * signing_key)
+        });
+        let signing_key = *signing_keys
+            .get(index)
+            .map_err(|_| arbitrary::Error::IncorrectFormat)?;
+
+        Ok(Self::new(
+            GroupPublicInfo::new(party_share_counts, threshold, all_pubkeys),
+            ShareSecretInfo::new(index, signing_key),
+        ))
+    }
+}
+
 impl SecretKeyShare {
     pub fn group(&self) -> &GroupPublicInfo {
         &self.group
@@ -120,3 +153,37 @@ impl SecretKeyShare {
         Self { group, share }
     }
 }
+
+#[cfg(all(test, feature = "arbitrary"))]
+mod tests {
+    use super::SecretKeyShare;
+    use crate::sdk::api::{deserialize, serialize};
+    use arbitrary::{Arbitrary, Unstructured};
+    use rand::RngCore;
+
+    #[test]
+    fn arbitrary_round_trip() {
+        let mut bytes = [0; 1024];
+        for _ in 0..20 {
+            rand::thread_rng().fill_bytes(&mut bytes);
+            let share = SecretKeyShare::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+
+            let share_bytes = serialize(&share).unwrap();
+            assert_eq!(deserialize::<SecretKeyShare>(&share_bytes).unwrap(), share);
+
+            let pubkey = share
+                .group()
+                .all_pubkeys()
+                .get(share.share().index())
+                .unwrap();
+            assert_eq!(
+                *pubkey.as_ref(),
+                k256::ProjectivePoint::GENERATOR * share.share().signing_key()
+            );
+            assert_eq!(
+                share.group().share_count(),
+                share.group().party_share_counts().total_share_count()
+            );
+        }
+    }
+}
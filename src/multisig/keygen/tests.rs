@@ -5,9 +5,12 @@ use alloc::vec::Vec;
 
 use super::{secret_key_share::SecretKeyShare, *};
 use crate::{
-    collections::VecMap,
+    collections::{TypedUsize, VecMap},
     crypto_tools::rng::{dummy_secret_recovery_key, SecretRecoveryKey},
-    sdk::api::{BytesVec, Protocol},
+    sdk::{
+        api::{BytesVec, Protocol},
+        local::execute_honest,
+    },
 };
 use tracing_test::traced_test;
 
@@ -19,6 +22,46 @@ fn basic_correctness() {
     }
 }
 
+#[test]
+fn byok_signing_keys() {
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![2, 1]).unwrap();
+    let signing_keys: VecMap<KeygenShareId, _> = (0..party_share_counts.total_share_count())
+        .map(|_| k256::ecdsa::SigningKey::random(rand::thread_rng()))
+        .collect();
+
+    let parties: VecMap<KeygenShareId, _> = signing_keys
+        .iter()
+        .map(|(keygen_id, signing_key)| {
+            let (party_id, subshare_id) = party_share_counts
+                .share_to_party_subshare_ids(keygen_id)
+                .unwrap();
+            new_keygen_with_signing_key(
+                party_share_counts.clone(),
+                1,
+                party_id,
+                subshare_id,
+                signing_key,
+                b"foobar",
+            )
+            .unwrap()
+        })
+        .collect();
+    let shares = execute_honest(parties).unwrap();
+
+    for (keygen_id, share) in shares.iter() {
+        let signing_key = signing_keys.get(keygen_id).unwrap();
+        assert_eq!(share.share().index(), keygen_id);
+        assert_eq!(
+            share.share().signing_key().to_bytes(),
+            signing_key.to_bytes()
+        );
+        for (peer_keygen_id, pubkey) in share.group().all_pubkeys().iter() {
+            let peer_verifying_key = signing_keys.get(peer_keygen_id).unwrap().verifying_key();
+            assert_eq!(pubkey.to_bytes()[..], peer_verifying_key.to_bytes()[..]);
+        }
+    }
+}
+
 struct TestCase {
     party_share_counts: KeygenPartyShareCounts,
     threshold: usize,
@@ -168,3 +211,335 @@ fn execute_keygen_from_recovery(
 
     all_secret_key_shares
 }
+
+#[test]
+#[traced_test]
+fn chunked_delivery() {
+    const MTU: usize = 24;
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2]).unwrap();
+
+    // happy path: chunks arrive in reverse order
+    let mut parties = new_r1_parties(&party_share_counts);
+    let chunks = r1_bcast_chunks(&parties, &party_share_counts, MTU);
+    for party in parties.iter_mut() {
+        for (from, chunk) in chunks.iter().rev() {
+            assert!(chunk.len() <= MTU);
+            party.msg_in(*from, chunk).unwrap();
+        }
+        assert!(!party.expecting_more_msgs_this_round());
+    }
+    for party in parties {
+        assert!(matches!(
+            party.execute_next_round().unwrap(),
+            Protocol::Done(Ok(_))
+        ));
+    }
+
+    // sad path: a duplicate chunk is attributed to its sender
+    let faulter = TypedUsize::<KeygenPartyId>::from_usize(1);
+    let mut parties = new_r1_parties(&party_share_counts);
+    let chunks = r1_bcast_chunks(&parties, &party_share_counts, MTU);
+    let duplicate = chunks.iter().find(|(from, _)| *from == faulter).unwrap();
+    for party in parties.iter_mut() {
+        for (from, chunk) in chunks.iter().chain(core::iter::once(duplicate)) {
+            party.msg_in(*from, chunk).unwrap();
+        }
+    }
+    for party in parties {
+        match party.execute_next_round().unwrap() {
+            Protocol::Done(Err(faulters)) => {
+                assert_eq!(faulters.iter_some().count(), 1);
+                assert_eq!(
+                    faulters.get(faulter).unwrap(),
+                    Some(&crate::sdk::api::Fault::CorruptedMessage)
+                );
+            }
+            _ => panic!("expect failure"),
+        }
+    }
+}
+
+#[test]
+fn rogue_key_faulter() {
+    use crate::sdk::implementer_api::{decode_message, deserialize, encode_message, serialize};
+    use ecdsa::elliptic_curve::Field;
+
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2]).unwrap();
+    let mut parties = new_r1_parties(&party_share_counts);
+
+    // share 2 (party 1) registers a key it does not control
+    let bcasts: Vec<BytesVec> = parties
+        .iter()
+        .map(|party| party.bcast_out().unwrap().clone())
+        .collect();
+    let wire_bytes = decode_message::<KeygenShareId>(&bcasts[2]).unwrap();
+    let mut bcast: r1::Bcast = deserialize(&wire_bytes.payload).unwrap();
+    bcast.verifying_key =
+        (k256::ProjectivePoint::GENERATOR * k256::Scalar::random(rand::thread_rng())).into();
+    let rogue_bcast = encode_message(
+        serialize(&bcast).unwrap(),
+        wire_bytes.from,
+        wire_bytes.round,
+        wire_bytes.msg_type,
+        wire_bytes.expected_msg_types,
+    )
+    .unwrap();
+
+    for party in parties.iter_mut() {
+        for (share_id, bytes) in bcasts.iter().enumerate() {
+            let bytes = if share_id == 2 { &rogue_bcast } else { bytes };
+            let from = party_share_counts
+                .share_to_party_id(TypedUsize::from_usize(share_id))
+                .unwrap();
+            party.msg_in(from, bytes).unwrap();
+        }
+    }
+    for party in parties {
+        match party.execute_next_round().unwrap() {
+            Protocol::Done(Err(faulters)) => {
+                assert_eq!(faulters.iter_some().count(), 1);
+                assert_eq!(
+                    faulters.get(TypedUsize::from_usize(1)).unwrap(),
+                    Some(&crate::sdk::api::Fault::ProtocolFault)
+                );
+            }
+            _ => panic!("expect failure"),
+        }
+    }
+}
+
+#[test]
+fn non_canonical_faulter() {
+    use crate::sdk::implementer_api::{decode_message, deserialize, encode_message, serialize};
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2]).unwrap();
+    let mut parties = new_r1_parties(&party_share_counts);
+
+    // share 2 (party 1) sends its verifying key uncompressed: the same point, another encoding
+    let bcasts: Vec<BytesVec> = parties
+        .iter()
+        .map(|party| party.bcast_out().unwrap().clone())
+        .collect();
+    let wire_bytes = decode_message::<KeygenShareId>(&bcasts[2]).unwrap();
+    let bcast: r1::Bcast = deserialize(&wire_bytes.payload).unwrap();
+    let uncompressed = bcast.verifying_key.as_ref().to_encoded_point(false);
+    let mut payload = serialize(uncompressed.as_bytes()).unwrap();
+    payload.extend_from_slice(&wire_bytes.payload[34..]); // 1 byte length, 33 bytes compressed key
+    let decoded: r1::Bcast = deserialize(&payload).unwrap();
+    assert_eq!(decoded.verifying_key, bcast.verifying_key);
+    let non_canonical_bcast = encode_message(
+        payload,
+        wire_bytes.from,
+        wire_bytes.round,
+        wire_bytes.msg_type,
+        wire_bytes.expected_msg_types,
+    )
+    .unwrap();
+
+    for party in parties.iter_mut() {
+        for (share_id, bytes) in bcasts.iter().enumerate() {
+            let bytes = if share_id == 2 {
+                &non_canonical_bcast
+            } else {
+                bytes
+            };
+            let from = party_share_counts
+                .share_to_party_id(TypedUsize::from_usize(share_id))
+                .unwrap();
+            party.msg_in(from, bytes).unwrap();
+        }
+    }
+    for party in parties {
+        match party.execute_next_round().unwrap() {
+            Protocol::Done(Err(faulters)) => {
+                assert_eq!(faulters.iter_some().count(), 1);
+                assert_eq!(
+                    faulters.get(TypedUsize::from_usize(1)).unwrap(),
+                    Some(&crate::sdk::api::Fault::CorruptedMessage)
+                );
+            }
+            _ => panic!("expect failure"),
+        }
+    }
+}
+
+type R1Party = crate::sdk::api::Round<SecretKeyShare, KeygenShareId, KeygenPartyId>;
+
+fn new_r1_parties(party_share_counts: &KeygenPartyShareCounts) -> Vec<R1Party> {
+    party_share_counts
+        .iter()
+        .flat_map(|(party_id, &party_share_count)| {
+            (0..party_share_count).map(move |subshare_id| {
+                match new_keygen(
+                    party_share_counts.clone(),
+                    1,
+                    party_id,
+                    subshare_id,
+                    &dummy_secret_recovery_key(party_id.as_usize()),
+                    b"chunked",
+                )
+                .unwrap()
+                {
+                    Protocol::NotDone(round) => round,
+                    Protocol::Done(_) => panic!("`new_keygen` returned a `Done` protocol"),
+                }
+            })
+        })
+        .collect()
+}
+
+fn r1_bcast_chunks(
+    parties: &[R1Party],
+    party_share_counts: &KeygenPartyShareCounts,
+    mtu: usize,
+) -> Vec<(TypedUsize<KeygenPartyId>, BytesVec)> {
+    parties
+        .iter()
+        .enumerate()
+        .flat_map(|(share_id, party)| {
+            let from = party_share_counts
+                .share_to_party_id(TypedUsize::from_usize(share_id))
+                .unwrap();
+            crate::sdk::api::split_message(party.bcast_out().unwrap(), mtu)
+                .unwrap()
+                .into_iter()
+                .map(move |chunk| (from, chunk))
+        })
+        .collect()
+}
+
+/// Golden fixtures, one set per serialization version.
+/// Stored values of an older version must still decode; messages of an older version must fail with a version error.
+/// See `tests/fixtures/README.md`.
+mod golden {
+    use alloc::vec::Vec;
+
+    use crate::{
+        collections::TypedUsize,
+        crypto_tools::k256_serde,
+        multisig::keygen::{
+            new_keygen_with_signing_key, r1, KeygenPartyShareCounts, KeygenShareId, SecretKeyShare,
+        },
+        sdk::{
+            api::Protocol,
+            implementer_api::{
+                decode, decode_message, deserialize, encode, ExpectedMsgTypes, MsgType, WireBytes,
+            },
+        },
+    };
+
+    const SECRET_KEY_SHARE_V0: &[u8] =
+        include_bytes!("../../../tests/fixtures/v0/multisig_secret_key_share.bin");
+    const R1_BCAST_V0: &[u8] =
+        include_bytes!("../../../tests/fixtures/v0/multisig_keygen_r1_bcast.bin");
+    const SECRET_KEY_SHARE_V1: &[u8] =
+        include_bytes!("../../../tests/fixtures/v1/multisig_secret_key_share.bin");
+    const R1_BCAST_V1: &[u8] =
+        include_bytes!("../../../tests/fixtures/v1/multisig_keygen_r1_bcast.bin");
+
+    fn scalar_point(i: u64) -> k256::ProjectivePoint {
+        k256::ProjectivePoint::GENERATOR * k256::Scalar::from(i)
+    }
+
+    fn check_secret_key_share(share: &SecretKeyShare) {
+        let group = share.group();
+        assert_eq!(group.party_share_counts().party_count(), 2);
+        assert_eq!(group.share_count(), 3);
+        assert_eq!(group.threshold(), 1);
+        for (i, pubkey) in group.all_pubkeys().iter() {
+            assert_eq!(*pubkey.as_ref(), scalar_point(i.as_usize() as u64 + 1));
+        }
+        assert_eq!(share.share().index(), TypedUsize::from_usize(1));
+        assert_eq!(*share.share().signing_key(), k256::Scalar::from(2u64));
+    }
+
+    fn check_r1_bcast_metadata(wire_bytes: &WireBytes<KeygenShareId>) {
+        assert!(matches!(wire_bytes.msg_type, MsgType::Bcast));
+        assert_eq!(wire_bytes.from, TypedUsize::from_usize(1));
+        assert_eq!(wire_bytes.round, 0);
+        assert_eq!(wire_bytes.expected_msg_types, ExpectedMsgTypes::BcastOnly);
+    }
+
+    #[test]
+    fn secret_key_share_v0() {
+        let share: SecretKeyShare = decode(SECRET_KEY_SHARE_V0).unwrap();
+        check_secret_key_share(&share);
+
+        // version 1 did not change the encoding of a key share
+        assert_eq!(encode(&share).unwrap(), SECRET_KEY_SHARE_V1);
+    }
+
+    #[test]
+    fn secret_key_share_v1() {
+        let share: SecretKeyShare = decode(SECRET_KEY_SHARE_V1).unwrap();
+        check_secret_key_share(&share);
+
+        assert_eq!(encode(&share).unwrap(), SECRET_KEY_SHARE_V1);
+        assert!(decode::<SecretKeyShare>(&with_version(SECRET_KEY_SHARE_V1, 2)).is_none());
+    }
+
+    #[test]
+    fn r1_bcast_v0() {
+        // v0 bcasts predate the proof of possession: they fail with a version error
+        assert!(decode_message::<KeygenShareId>(R1_BCAST_V0).is_none());
+
+        // the envelope is unchanged, so the contents are still readable
+        let wire_bytes: WireBytes<KeygenShareId> = decode(R1_BCAST_V0).unwrap();
+        check_r1_bcast_metadata(&wire_bytes);
+        let verifying_key: k256_serde::ProjectivePoint = deserialize(&wire_bytes.payload).unwrap();
+        assert_eq!(*verifying_key.as_ref(), scalar_point(2));
+    }
+
+    /// Session nonce `b"golden"`
+    #[test]
+    fn r1_bcast_v1() {
+        let wire_bytes = decode_message::<KeygenShareId>(R1_BCAST_V1).unwrap();
+        check_r1_bcast_metadata(&wire_bytes);
+        let bcast: r1::Bcast = deserialize(&wire_bytes.payload).unwrap();
+        assert_eq!(*bcast.verifying_key.as_ref(), scalar_point(2));
+
+        assert_eq!(encode(&wire_bytes).unwrap(), R1_BCAST_V1);
+        assert!(decode_message::<KeygenShareId>(&with_version(R1_BCAST_V1, 2)).is_none());
+
+        // the proof of possession is deterministic
+        let signing_key =
+            k256::ecdsa::SigningKey::from_bytes(&k256::Scalar::from(2u64).to_bytes()).unwrap();
+        let round = match new_keygen_with_signing_key(
+            KeygenPartyShareCounts::from_vec(alloc::vec![1, 2]).unwrap(),
+            1,
+            TypedUsize::from_usize(1),
+            0,
+            &signing_key,
+            b"golden",
+        )
+        .unwrap()
+        {
+            Protocol::NotDone(round) => round,
+            Protocol::Done(_) => panic!("`new_keygen` returned a `Done` protocol"),
+        };
+        assert_eq!(round.bcast_out().unwrap().as_slice(), R1_BCAST_V1);
+    }
+
+    /// JSON uses hex strings for curve points and scalars, not sequences of integers
+    #[test]
+    fn secret_key_share_json() {
+        let share: SecretKeyShare = decode(SECRET_KEY_SHARE_V1).unwrap();
+        let json = serde_json::to_string(&share).unwrap();
+
+        let pubkey_hex = hex::encode(k256_serde::point_to_bytes(&scalar_point(1)));
+        assert!(json.contains(&alloc::format!("\"{}\"", pubkey_hex)));
+        assert!(json.contains(&hex::encode_upper(k256::Scalar::from(2u64).to_bytes())));
+
+        let share_decoded: SecretKeyShare = serde_json::from_str(&json).unwrap();
+        assert_eq!(share, share_decoded);
+        assert_eq!(encode(&share_decoded).unwrap(), SECRET_KEY_SHARE_V1);
+    }
+
+    /// The version is the first field of the outer encoding; small versions occupy one byte.
+    fn with_version(bytes: &[u8], version: u8) -> Vec<u8> {
+        let mut bytes = bytes.to_vec();
+        bytes[0] = version;
+        bytes
+    }
+}
@@ -1,6 +1,16 @@
+pub mod ceygen;
 pub mod keygen;
 pub mod sign;
 
+mod verify;
+pub use verify::*;
+
+mod weights;
+pub use weights::*;
+
 // Domain separation for seeding the RNG
 const KEYGEN_TAG: u8 = 0x00;
 const SIGN_TAG: u8 = 0x01;
+
+// Domain separation for the keygen proof of possession
+const KEYGEN_POP_TAG: u8 = 0x02;
@@ -3,8 +3,10 @@ use alloc::vec::Vec;
 use super::r1;
 use crate::{
     collections::{HoleVecMap, Subset, TypedUsize, VecMap},
-    multisig::keygen::{
-        GroupPublicInfo, KeygenPartyId, KeygenShareId, SecretKeyShare, ShareSecretInfo,
+    crypto_tools::k256_serde,
+    multisig::{
+        keygen::{GroupPublicInfo, KeygenPartyId, KeygenShareId, SecretKeyShare, ShareSecretInfo},
+        PartyWeights,
     },
     sdk::{
         api::{PartyShareCounts, Protocol, Signature, TofnFatal, TofnResult},
@@ -15,7 +17,9 @@ use crate::{
 use serde::{Deserialize, Serialize};
 use tracing::error;
 
-pub use crate::crypto_tools::message_digest::MessageDigest;
+pub use crate::crypto_tools::message_digest::{
+    DigestPolicy, Keccak256Policy, MessageDigest, PolicyDigest, Sha256Policy,
+};
 
 /// SignProtocol output for a single share in happy path
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -25,13 +29,108 @@ pub struct SignatureShare {
     pub subshare_id: usize,
 }
 
-/// Exactly threshold + 1 valid signatures
-pub type SignProtocolOutput = Vec<SignatureShare>;
+/// A valid signature and its signer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignerSignature {
+    pub share_id: TypedUsize<KeygenShareId>,
+    pub party_id: TypedUsize<KeygenPartyId>,
+    pub subshare_id: usize,
+    pub pubkey: k256_serde::ProjectivePoint,
+    pub signature: Signature,
+}
+
+impl SignerSignature {
+    pub fn signature_share(&self) -> SignatureShare {
+        SignatureShare {
+            signature: self.signature,
+            party_id: self.party_id,
+            subshare_id: self.subshare_id,
+        }
+    }
+}
+
+/// SignProtocol output in happy path: exactly threshold + 1 valid signatures,
+/// or just enough to exceed the weight threshold of [new_sign_weighted],
+/// ordered by signer share id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignOutput {
+    signatures: Vec<SignerSignature>,
+}
+
+impl SignOutput {
+    /// Fail unless `signatures` are ordered by distinct share ids
+    pub fn new(signatures: Vec<SignerSignature>) -> TofnResult<Self> {
+        if signatures
+            .windows(2)
+            .any(|pair| pair[0].share_id.as_usize() >= pair[1].share_id.as_usize())
+        {
+            error!("signatures are not ordered by distinct share ids");
+            return Err(TofnFatal);
+        }
+        Ok(Self { signatures })
+    }
+
+    pub fn get(&self, share_id: TypedUsize<KeygenShareId>) -> Option<&SignerSignature> {
+        self.signatures
+            .binary_search_by_key(&share_id.as_usize(), |sig| sig.share_id.as_usize())
+            .ok()
+            .map(|i| &self.signatures[i])
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<SignerSignature> {
+        self.signatures.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// Input for [verify](crate::multisig::verify)
+    pub fn signature_shares(&self) -> Vec<SignatureShare> {
+        self.iter().map(SignerSignature::signature_share).collect()
+    }
+
+    /// Packed encoding for on-chain verifiers:
+    /// for each signer in share id order, its 33-byte SEC1 compressed pubkey
+    /// followed by its 64-byte `r || s` signature.
+    pub fn to_onchain_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len() * (33 + 64));
+        for sig in self.iter() {
+            bytes.extend_from_slice(&sig.pubkey.to_bytes());
+            bytes.extend_from_slice(sig.signature.as_ref());
+        }
+        bytes
+    }
+}
+
+impl IntoIterator for SignOutput {
+    type Item = SignerSignature;
+    type IntoIter = alloc::vec::IntoIter<SignerSignature>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.signatures.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SignOutput {
+    type Item = &'a SignerSignature;
+    type IntoIter = core::slice::Iter<'a, SignerSignature>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub type SignProtocolOutput = SignOutput;
 
 /// Maximum byte length of messages exchanged during sign.
 pub const MAX_MSG_LEN: usize = 100;
 
-pub type SignProtocol = Protocol<SignProtocolOutput, SignShareId, SignPartyId, MAX_MSG_LEN>;
+pub type SignProtocol = Protocol<SignProtocolOutput, SignShareId, SignPartyId>;
 pub type SignProtocolBuilder = ProtocolBuilder<SignProtocolOutput, SignShareId>;
 
 // This includes all shares participating in the current signing protocol
@@ -47,26 +146,116 @@ pub struct SignShareId;
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct SignPartyId;
 
+/// Like [new_sign] but `msg_to_sign` must have been computed under the group's digest policy `D`.
+pub fn new_sign_with_policy<D: DigestPolicy>(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    msg_to_sign: &PolicyDigest<D>,
+) -> TofnResult<SignProtocol> {
+    new_sign(group, share, sign_parties, msg_to_sign.digest())
+}
+
 /// Initialize a new sign protocol
 /// Assume `group`, `share` are valid and check `sign_parties` against it.
+/// Only the shares of `sign_parties` take part, so other parties may be offline.
 pub fn new_sign(
     group: &GroupPublicInfo,
     share: &ShareSecretInfo,
     sign_parties: &SignParties,
     msg_to_sign: &MessageDigest,
 ) -> TofnResult<SignProtocol> {
+    new_sign_with_options(
+        group,
+        share,
+        sign_parties,
+        msg_to_sign,
+        &SignOptions::default(),
+    )
+}
+
+/// Like [new_sign] but a quorum is defined by `weights` instead of the group threshold:
+/// the protocol is done once the signers' total weight exceeds the weight threshold.
+pub fn new_sign_weighted(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    msg_to_sign: &MessageDigest,
+    weights: &PartyWeights,
+) -> TofnResult<SignProtocol> {
+    new_sign_with_options(
+        group,
+        share,
+        sign_parties,
+        msg_to_sign,
+        &SignOptions {
+            weights: Some(weights.clone()),
+            ..SignOptions::default()
+        },
+    )
+}
+
+/// How a signer chooses the ephemeral scalar of its signature
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Nonce {
+    /// Derived from the signer's share id, signing key and message with tofn's own construction
+    Tofn,
+    /// As in RFC 6979, so that each signature matches that of standard ECDSA libraries
+    /// for the same signing key and message
+    Rfc6979,
+}
+
+impl Default for Nonce {
+    fn default() -> Self {
+        Self::Tofn
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignOptions {
+    /// See [new_sign_weighted]
+    pub weights: Option<PartyWeights>,
+    pub nonce: Nonce,
+}
+
+/// Like [new_sign] with the given `options`
+pub fn new_sign_with_options(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    sign_parties: &SignParties,
+    msg_to_sign: &MessageDigest,
+    options: &SignOptions,
+) -> TofnResult<SignProtocol> {
+    let weights = options.weights.as_ref();
+
     // TODO refactor copied code from gg20
     let all_keygen_ids =
         VecMap::from_vec(group.party_share_counts().share_id_subset(sign_parties)?);
 
-    // participant share count must be at least threshold + 1
-    if all_keygen_ids.len() <= group.threshold() {
-        error!(
-            "not enough participant shares: threshold [{}], participants [{}]",
-            group.threshold(),
-            all_keygen_ids.len(),
-        );
-        return Err(TofnFatal);
+    match weights {
+        // participant weight must exceed the weight threshold
+        Some(weights) => {
+            weights.check_parties(group.party_share_counts())?;
+            if !weights.is_quorum(sign_parties)? {
+                error!(
+                    "not enough participant weight: threshold [{}], participants [{}]",
+                    weights.threshold(),
+                    weights.total(sign_parties)?,
+                );
+                return Err(TofnFatal);
+            }
+        }
+        // participant share count must be at least threshold + 1
+        None => {
+            if all_keygen_ids.len() <= group.threshold() {
+                error!(
+                    "not enough participant shares: threshold [{}], participants [{}]",
+                    group.threshold(),
+                    all_keygen_ids.len(),
+                );
+                return Err(TofnFatal);
+            }
+        }
     }
 
     // find my keygen share_id
@@ -87,9 +276,11 @@ pub fn new_sign(
         SecretKeyShare::new(group.clone(), share.clone()),
         msg_to_sign,
         all_keygen_ids,
+        options.nonce,
+        options.weights.clone(),
     )?;
 
-    new_protocol(sign_party_share_counts, my_sign_id, round2)
+    new_protocol(sign_party_share_counts, my_sign_id, MAX_MSG_LEN, round2)
 }
 
 #[cfg(test)]
@@ -1,10 +1,10 @@
 use alloc::boxed::Box;
 
-use super::{r2, KeygenShareIds, MessageDigest, SignProtocolBuilder, SignShareId};
+use super::{r2, KeygenShareIds, MessageDigest, Nonce, SignProtocolBuilder, SignShareId};
 use crate::{
     collections::TypedUsize,
     crypto_tools::rng,
-    multisig::{self, keygen::SecretKeyShare},
+    multisig::{self, keygen::SecretKeyShare, PartyWeights},
     sdk::{
         api::{TofnFatal, TofnResult},
         implementer_api::{serialize, RoundBuilder},
@@ -19,22 +19,39 @@ pub struct Bcast {
     pub(super) signature: Signature,
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Bcast {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        use crate::crypto_tools::k256_serde::arbitrary_scalar;
+
+        let (r, s) = (arbitrary_scalar(u)?, arbitrary_scalar(u)?);
+        Ok(Self {
+            signature: Signature::from_scalars(r, s)
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?,
+        })
+    }
+}
+
 pub(super) fn start(
     my_sign_id: TypedUsize<SignShareId>,
     secret_key_share: SecretKeyShare,
     msg_to_sign: &MessageDigest,
     all_keygen_ids: KeygenShareIds,
+    nonce: Nonce,
+    weights: Option<PartyWeights>,
 ) -> TofnResult<SignProtocolBuilder> {
     let msg_to_sign = k256::Scalar::from(msg_to_sign);
     let signing_key = secret_key_share.share().signing_key();
 
-    let rng = rng::rng_seed_ecdsa_ephemeral_scalar_with_party_id(
-        multisig::SIGN_TAG,
-        my_sign_id,
-        signing_key,
-        &msg_to_sign,
-    )?;
-    let ephemeral_scalar = k256::Scalar::random(rng);
+    let ephemeral_scalar = match nonce {
+        Nonce::Tofn => k256::Scalar::random(rng::rng_seed_ecdsa_ephemeral_scalar_with_party_id(
+            multisig::SIGN_TAG,
+            my_sign_id,
+            signing_key,
+            &msg_to_sign,
+        )?),
+        Nonce::Rfc6979 => rng::rfc6979_ephemeral_scalar(signing_key, &msg_to_sign)?,
+    };
 
     let signature = signing_key
         .try_sign_prehashed(ephemeral_scalar, msg_to_sign)
@@ -49,6 +66,7 @@ pub(super) fn start(
             secret_key_share,
             msg_to_sign,
             all_keygen_ids,
+            weights,
         }),
         bcast_out,
         None,
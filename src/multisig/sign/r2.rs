@@ -1,10 +1,10 @@
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 
-use super::{r1, KeygenShareIds, SignProtocolOutput, SignShareId, SignatureShare};
+use super::{r1, KeygenShareIds, SignOutput, SignProtocolOutput, SignShareId, SignerSignature};
 use crate::{
-    collections::{zip2, FillVecMap, P2ps},
-    multisig::keygen::SecretKeyShare,
+    collections::{zip2, FillVecMap, P2ps, Subset},
+    multisig::{keygen::SecretKeyShare, PartyWeights},
     sdk::{
         api::{Fault::ProtocolFault, TofnFatal, TofnResult},
         implementer_api::{Executer, ProtocolBuilder, ProtocolInfo},
@@ -17,6 +17,7 @@ pub(super) struct R2 {
     pub(super) secret_key_share: SecretKeyShare,
     pub(super) msg_to_sign: k256::Scalar,
     pub(super) all_keygen_ids: KeygenShareIds,
+    pub(super) weights: Option<PartyWeights>,
 }
 
 impl Executer for R2 {
@@ -34,7 +35,13 @@ impl Executer for R2 {
         let my_sign_id = info.my_id();
         let threshold = self.secret_key_share.group().threshold();
         let mut faulters = info.new_fillvecmap();
-        let mut valid_signatures = Vec::with_capacity(threshold + 1);
+        let mut valid_signatures = Vec::with_capacity(info.total_share_count());
+        let mut signers = Subset::with_max_size(
+            self.secret_key_share
+                .group()
+                .party_share_counts()
+                .party_count(),
+        );
 
         for (peer_sign_id, bcast_option, p2ps_option) in zip2(bcasts_in, p2ps_in) {
             // anyone who did not send a bcast is a faulter
@@ -62,13 +69,13 @@ impl Executer for R2 {
 
             // verify signature
             let peer_keygen_id = *self.all_keygen_ids.get(peer_sign_id)?;
-            let verifying_key = self
+            let pubkey = self
                 .secret_key_share
                 .group()
                 .all_pubkeys()
                 .get(peer_keygen_id)?
-                .as_ref()
-                .to_affine();
+                .clone();
+            let verifying_key = pubkey.as_ref().to_affine();
 
             if verifying_key
                 .verify_prehashed(self.msg_to_sign, &signature)
@@ -89,36 +96,57 @@ impl Executer for R2 {
                 .party_share_counts()
                 .share_to_party_subshare_ids(peer_keygen_id)?;
 
-            valid_signatures.push(SignatureShare {
-                signature,
+            valid_signatures.push(SignerSignature {
+                share_id: peer_keygen_id,
                 party_id,
                 subshare_id,
+                pubkey,
+                signature,
             });
+        }
 
-            // have we got enough valid sigs yet?
-            if valid_signatures.len() > threshold {
-                return Ok(ProtocolBuilder::Done(Ok(valid_signatures)));
+        // verify every signature so that all invalid ones are reported,
+        // even if the valid ones already form a quorum
+        if !faulters.is_empty() {
+            warn!(
+                "peer {} says: invalid signatures from peers {:?} in round 2",
+                my_sign_id,
+                faulters
+                    .iter_some()
+                    .map(|(peer_sign_id, _)| peer_sign_id)
+                    .collect::<Vec<_>>()
+            );
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        // output the valid signatures up to the first quorum
+        let mut quorum_len = None;
+        for (i, signature) in valid_signatures.iter().enumerate() {
+            signers.add(signature.party_id)?;
+            let quorum = match &self.weights {
+                Some(weights) => weights.is_quorum(&signers)?,
+                None => i + 1 > threshold,
+            };
+            if quorum {
+                quorum_len = Some(i + 1);
+                break;
             }
         }
+        if let Some(len) = quorum_len {
+            valid_signatures.truncate(len);
+            return Ok(ProtocolBuilder::Done(Ok(SignOutput::new(
+                valid_signatures,
+            )?)));
+        }
 
-        // not enough valid signatures => sad outcome
-        warn!(
-            "peer {} says: insufficient valid signatures {} to exceed threshold {}",
+        // sanity check: signers are chosen to form a quorum
+        error!(
+            "peer {} says: insufficient valid signatures {} to exceed threshold {} but no faulters",
             my_sign_id,
             valid_signatures.len(),
             threshold
         );
-
-        // sanity check
-        if faulters.is_empty() {
-            error!(
-                "peer {} says: insufficient valid signatures but no faulters",
-                my_sign_id
-            );
-            return Err(TofnFatal);
-        }
-
-        Ok(ProtocolBuilder::Done(Err(faulters)))
+        Err(TofnFatal)
     }
 
     #[cfg(test)]
@@ -8,16 +8,25 @@ use super::*;
 use crate::{
     collections::{FillVecMap, HoleVecMap, Subset, TypedUsize, VecMap},
     multisig::{
-        keygen::{tests::execute_keygen, KeygenPartyShareCounts, KeygenShareId, SecretKeyShare},
-        sign::api::{new_sign, SignShareId},
+        keygen::{
+            new_keygen_with_signing_key, tests::execute_keygen, KeygenPartyShareCounts,
+            KeygenShareId, SecretKeyShare,
+        },
+        sign::api::{
+            new_sign, new_sign_weighted, new_sign_with_options, Nonce, SignOptions, SignShareId,
+        },
+        verify_weighted, PartyWeights,
+    },
+    sdk::{
+        api::{BytesVec, Fault, Protocol, Round},
+        local::execute_honest,
     },
-    sdk::api::{BytesVec, Fault, Protocol, Round},
 };
 use ecdsa::hazmat::VerifyPrimitive;
 use tracing::debug;
 use tracing_test::traced_test;
 
-type Party = Round<SignProtocolOutput, SignShareId, SignPartyId, MAX_MSG_LEN>;
+type Party = Round<SignProtocolOutput, SignShareId, SignPartyId>;
 type Parties = Vec<Party>;
 type PartyBcast = Result<VecMap<SignShareId, BytesVec>, ()>;
 type PartyP2p = Result<VecMap<SignShareId, HoleVecMap<SignShareId, BytesVec>>, ()>;
@@ -83,6 +92,214 @@ fn basic_correctness() {
     }
 }
 
+#[test]
+fn weighted_quorum() {
+    // party 1 alone outweighs the others but holds fewer than threshold + 1 shares
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![2, 1, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 2);
+    let weights = PartyWeights::new(vec![1, 10, 1], 5).unwrap();
+    let msg_to_sign = msg_to_sign();
+
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    let key_share = key_shares.get(TypedUsize::from_usize(0)).unwrap();
+    assert!(new_sign_weighted(
+        key_share.group(),
+        key_share.share(),
+        &sign_parties,
+        &msg_to_sign,
+        &weights
+    )
+    .is_err());
+
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(1)).unwrap();
+    let key_share = key_shares.get(TypedUsize::from_usize(2)).unwrap();
+    assert!(new_sign(
+        key_share.group(),
+        key_share.share(),
+        &sign_parties,
+        &msg_to_sign
+    )
+    .is_err());
+    let party = new_sign_weighted(
+        key_share.group(),
+        key_share.share(),
+        &sign_parties,
+        &msg_to_sign,
+        &weights,
+    )
+    .unwrap();
+
+    let outputs = execute_honest(VecMap::<SignShareId, _>::from_vec(vec![party])).unwrap();
+    let output = outputs.get(TypedUsize::from_usize(0)).unwrap();
+    assert_eq!(output.len(), 1);
+    let result = verify_weighted(
+        key_share.group(),
+        &weights,
+        &msg_to_sign,
+        &output.signature_shares(),
+    )
+    .unwrap();
+    assert!(result.is_valid());
+}
+
+#[test]
+fn rfc6979_matches_k256() {
+    use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+    use sha2::Digest;
+
+    let msg = b"Satoshi Nakamoto";
+    let signing_key = SigningKey::random(rand::thread_rng());
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1]).unwrap();
+    let key_shares = execute_honest(VecMap::<KeygenShareId, _>::from_vec(vec![
+        new_keygen_with_signing_key(
+            party_share_counts,
+            0,
+            TypedUsize::from_usize(0),
+            0,
+            &signing_key,
+            b"foobar",
+        )
+        .unwrap(),
+    ]))
+    .unwrap();
+    let key_share = key_shares.get(TypedUsize::from_usize(0)).unwrap();
+
+    let mut sign_parties = Subset::with_max_size(1);
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    let msg_to_sign = MessageDigest::try_from(&sha2::Sha256::digest(msg)[..]).unwrap();
+    let party = new_sign_with_options(
+        key_share.group(),
+        key_share.share(),
+        &sign_parties,
+        &msg_to_sign,
+        &SignOptions {
+            nonce: Nonce::Rfc6979,
+            ..SignOptions::default()
+        },
+    )
+    .unwrap();
+    let outputs = execute_honest(VecMap::<SignShareId, _>::from_vec(vec![party])).unwrap();
+    let signature = outputs
+        .get(TypedUsize::from_usize(0))
+        .unwrap()
+        .iter()
+        .next()
+        .unwrap()
+        .signature;
+
+    let expected: Signature = signing_key.sign(msg);
+    assert_eq!(signature, expected);
+}
+
+#[test]
+fn sign_parties_subset() {
+    // party 1 is offline; parties 0, 2 hold exactly threshold + 1 shares
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![2, 3, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 2);
+    let msg_to_sign = msg_to_sign();
+    let mut sign_parties = Subset::with_max_size(party_share_counts.party_count());
+    sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+    sign_parties.add(TypedUsize::from_usize(2)).unwrap();
+
+    // a share outside `sign_parties` cannot join
+    let key_share = key_shares.get(TypedUsize::from_usize(2)).unwrap();
+    assert!(new_sign(
+        key_share.group(),
+        key_share.share(),
+        &sign_parties,
+        &msg_to_sign
+    )
+    .is_err());
+
+    let signer_ids = [0, 1, 5];
+    let parties: VecMap<SignShareId, _> = signer_ids
+        .iter()
+        .map(|&i| {
+            let key_share = key_shares.get(TypedUsize::from_usize(i)).unwrap();
+            new_sign(
+                key_share.group(),
+                key_share.share(),
+                &sign_parties,
+                &msg_to_sign,
+            )
+            .unwrap()
+        })
+        .collect();
+    for (_, output) in execute_honest(parties).unwrap() {
+        let share_ids: Vec<_> = output.iter().map(|sig| sig.share_id.as_usize()).collect();
+        assert_eq!(share_ids, signer_ids);
+    }
+}
+
+#[test]
+fn invalid_signature_faulter() {
+    use crate::sdk::implementer_api::{decode_message, deserialize, encode_message, serialize};
+
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1, 1]).unwrap();
+    let key_shares = execute_keygen(&party_share_counts, 1);
+    let msg_to_sign = msg_to_sign();
+    let sign_parties = crate::sdk::local::all_parties(&party_share_counts).unwrap();
+
+    let mut parties: Parties = key_shares
+        .iter()
+        .map(|(_, key_share)| {
+            match new_sign(
+                key_share.group(),
+                key_share.share(),
+                &sign_parties,
+                &msg_to_sign,
+            )
+            .unwrap()
+            {
+                Protocol::NotDone(round) => round,
+                Protocol::Done(_) => panic!("`new_sign` returned a `Done` protocol"),
+            }
+        })
+        .collect();
+
+    // party 2 broadcasts the signature of party 0
+    let bcasts: Vec<BytesVec> = parties
+        .iter()
+        .map(|party| party.bcast_out().unwrap().clone())
+        .collect();
+    let wire_bytes = decode_message::<SignShareId>(&bcasts[2]).unwrap();
+    let mut bcast: r1::Bcast = deserialize(&wire_bytes.payload).unwrap();
+    bcast.signature =
+        deserialize::<r1::Bcast>(&decode_message::<SignShareId>(&bcasts[0]).unwrap().payload)
+            .unwrap()
+            .signature;
+    let bad_bcast = encode_message(
+        serialize(&bcast).unwrap(),
+        wire_bytes.from,
+        wire_bytes.round,
+        wire_bytes.msg_type,
+        wire_bytes.expected_msg_types,
+    )
+    .unwrap();
+
+    for party in parties.iter_mut() {
+        for (from, bytes) in bcasts.iter().enumerate() {
+            let bytes = if from == 2 { &bad_bcast } else { bytes };
+            party.msg_in(TypedUsize::from_usize(from), bytes).unwrap();
+        }
+    }
+
+    // parties 0, 1 form a quorum but party 2 is still reported
+    for party in parties {
+        let faulters = match party.execute_next_round().unwrap() {
+            Protocol::Done(Err(faulters)) => faulters,
+            _ => panic!("expected faulters"),
+        };
+        assert_eq!(faulters.some_count(), 1);
+        assert!(matches!(
+            faulters.get(TypedUsize::from_usize(2)).unwrap(),
+            Some(Fault::ProtocolFault)
+        ));
+    }
+}
+
 #[allow(non_snake_case, clippy::many_single_char_names)]
 fn execute_sign(
     key_shares: VecMap<KeygenShareId, SecretKeyShare>,
@@ -155,15 +372,24 @@ fn execute_sign(
             .party_share_counts
             .party_to_share_id(sig_share.party_id, sig_share.subshare_id)
             .unwrap();
-        let verifying_key = all_verifying_keys
-            .get(keygen_id)
-            .unwrap()
+        assert_eq!(sig_share.share_id, keygen_id);
+        assert_eq!(all_sig_shares.get(keygen_id), Some(sig_share));
+        let pubkey = all_verifying_keys.get(keygen_id).unwrap();
+        assert_eq!(&sig_share.pubkey, pubkey);
+        pubkey
             .as_ref()
-            .to_affine();
-        verifying_key
+            .to_affine()
             .verify_prehashed(hashed_msg.into(), &sig_share.signature)
             .unwrap();
     }
+
+    // TEST: on-chain encoding
+    let onchain_bytes = all_sig_shares.to_onchain_bytes();
+    assert_eq!(onchain_bytes.len(), all_sig_shares.len() * (33 + 64));
+    for (chunk, sig_share) in onchain_bytes.chunks(33 + 64).zip(all_sig_shares) {
+        assert_eq!(&chunk[..33], &sig_share.pubkey.to_bytes()[..]);
+        assert_eq!(&chunk[33..], sig_share.signature.as_ref());
+    }
 }
 
 fn execute_final_round(
@@ -242,3 +468,57 @@ fn retrieve_and_set_p2ps(parties: &mut Parties, expect_p2p: bool, round_num: usi
 
     Ok(all_p2ps.into_iter().map(|(_, (_, msg))| msg).collect())
 }
+
+/// Golden fixtures, one per serialization version.
+/// Messages of an older version must fail with a version error.
+/// See `tests/fixtures/README.md`.
+mod golden {
+    use crate::{
+        collections::TypedUsize,
+        multisig::sign::{r1, SignShareId},
+        sdk::implementer_api::{
+            decode, decode_message, deserialize, encode, ExpectedMsgTypes, MsgType, WireBytes,
+        },
+    };
+
+    const R1_BCAST_V0: &[u8] =
+        include_bytes!("../../../tests/fixtures/v0/multisig_sign_r1_bcast.bin");
+    const R1_BCAST_V1: &[u8] =
+        include_bytes!("../../../tests/fixtures/v1/multisig_sign_r1_bcast.bin");
+
+    fn check_r1_bcast(wire_bytes: &WireBytes<SignShareId>) {
+        assert!(matches!(wire_bytes.msg_type, MsgType::Bcast));
+        assert_eq!(wire_bytes.from, TypedUsize::from_usize(1));
+        assert_eq!(wire_bytes.round, 0);
+        assert_eq!(wire_bytes.expected_msg_types, ExpectedMsgTypes::BcastOnly);
+
+        let bcast: r1::Bcast = deserialize(&wire_bytes.payload).unwrap();
+        let (r, s) = bcast.signature.split_scalars();
+        assert_eq!(*r, k256::Scalar::from(3u64));
+        assert_eq!(*s, k256::Scalar::from(4u64));
+    }
+
+    #[test]
+    fn r1_bcast_v0() {
+        // messages must be of the current version, even if their encoding did not change
+        assert!(decode_message::<SignShareId>(R1_BCAST_V0).is_none());
+
+        // the envelope and the bcast are unchanged, so the contents are still readable
+        let wire_bytes: WireBytes<SignShareId> = decode(R1_BCAST_V0).unwrap();
+        check_r1_bcast(&wire_bytes);
+        assert_eq!(&encode(&wire_bytes).unwrap()[1..], &R1_BCAST_V0[1..]);
+    }
+
+    #[test]
+    fn r1_bcast_v1() {
+        let wire_bytes = decode_message::<SignShareId>(R1_BCAST_V1).unwrap();
+        check_r1_bcast(&wire_bytes);
+
+        assert_eq!(encode(&wire_bytes).unwrap(), R1_BCAST_V1);
+
+        // the version is the first byte of the outer encoding
+        let mut bytes = R1_BCAST_V1.to_vec();
+        bytes[0] = 2;
+        assert!(decode_message::<SignShareId>(&bytes).is_none());
+    }
+}
@@ -0,0 +1,222 @@
+use alloc::vec::Vec;
+
+use ecdsa::hazmat::VerifyPrimitive;
+use tracing::warn;
+
+use super::{
+    keygen::{GroupPublicInfo, KeygenShareId},
+    sign::{MessageDigest, SignatureShare},
+    PartyWeights,
+};
+use crate::{
+    collections::{Subset, TypedUsize},
+    sdk::api::TofnResult,
+};
+
+/// Why a [SignatureShare] is invalid
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum InvalidSignature {
+    /// `(party_id, subshare_id)` is not a share of the group
+    UnknownSigner,
+    /// An earlier signature is from the same share
+    Duplicate,
+    /// The signature does not verify against the pubkey of its share
+    BadSignature,
+}
+
+/// Output of [verify]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyResult {
+    /// Threshold of the group
+    pub threshold: usize,
+    /// Shares with a valid signature, in the order of `signatures`
+    pub valid: Vec<TypedUsize<KeygenShareId>>,
+    /// Position in `signatures` of each invalid signature
+    pub invalid: Vec<(usize, InvalidSignature)>,
+    /// Weight of the parties with a valid signature, for [verify_weighted]
+    pub weight: Option<WeightTally>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct WeightTally {
+    pub valid_weight: u64,
+    pub threshold: u64,
+}
+
+impl VerifyResult {
+    /// At least `threshold + 1` distinct shares signed,
+    /// or the signers' weight exceeds the weight threshold for [verify_weighted]
+    pub fn is_valid(&self) -> bool {
+        match self.weight {
+            Some(tally) => tally.valid_weight > tally.threshold,
+            None => self.valid.len() > self.threshold,
+        }
+    }
+}
+
+/// Verify each of `signatures` on `msg_to_sign` against the pubkey of its signer in `group`.
+pub fn verify(
+    group: &GroupPublicInfo,
+    msg_to_sign: &MessageDigest,
+    signatures: &[SignatureShare],
+) -> VerifyResult {
+    let hashed_msg = k256::Scalar::from(msg_to_sign);
+    let mut valid = Vec::with_capacity(signatures.len());
+    let mut invalid = Vec::new();
+
+    for (i, sig_share) in signatures.iter().enumerate() {
+        let (keygen_id, pubkey) = match group
+            .party_share_counts()
+            .party_to_share_id(sig_share.party_id, sig_share.subshare_id)
+            .and_then(|keygen_id| Ok((keygen_id, group.all_pubkeys().get(keygen_id)?)))
+        {
+            Ok(found) => found,
+            Err(_) => {
+                warn!(
+                    "signature {} from unknown signer (party {}, subshare {})",
+                    i, sig_share.party_id, sig_share.subshare_id
+                );
+                invalid.push((i, InvalidSignature::UnknownSigner));
+                continue;
+            }
+        };
+
+        if valid.contains(&keygen_id) {
+            warn!("signature {} duplicates share {}", i, keygen_id);
+            invalid.push((i, InvalidSignature::Duplicate));
+            continue;
+        }
+
+        if pubkey
+            .as_ref()
+            .to_affine()
+            .verify_prehashed(hashed_msg, &sig_share.signature)
+            .is_err()
+        {
+            warn!("signature {} from share {} fails to verify", i, keygen_id);
+            invalid.push((i, InvalidSignature::BadSignature));
+            continue;
+        }
+
+        valid.push(keygen_id);
+    }
+
+    VerifyResult {
+        threshold: group.threshold(),
+        valid,
+        invalid,
+        weight: None,
+    }
+}
+
+/// Like [verify] but a quorum is defined by `weights`.
+/// Each party counts once, however many of its shares signed.
+pub fn verify_weighted(
+    group: &GroupPublicInfo,
+    weights: &PartyWeights,
+    msg_to_sign: &MessageDigest,
+    signatures: &[SignatureShare],
+) -> TofnResult<VerifyResult> {
+    weights.check_parties(group.party_share_counts())?;
+    let mut result = verify(group, msg_to_sign, signatures);
+
+    let mut signers = Subset::with_max_size(group.party_share_counts().party_count());
+    for &keygen_id in &result.valid {
+        signers.add(group.party_share_counts().share_to_party_id(keygen_id)?)?;
+    }
+    result.weight = Some(WeightTally {
+        valid_weight: weights.total(&signers)?,
+        threshold: weights.threshold(),
+    });
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::convert::TryFrom;
+
+    use ecdsa::{elliptic_curve::Field, hazmat::SignPrimitive};
+
+    use super::{verify, verify_weighted, InvalidSignature, WeightTally};
+    use crate::{
+        collections::TypedUsize,
+        multisig::{
+            keygen::{tests::execute_keygen, KeygenPartyShareCounts},
+            sign::{MessageDigest, SignatureShare},
+            PartyWeights,
+        },
+    };
+
+    #[test]
+    fn threshold_and_invalid_signatures() {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![2, 1]).unwrap();
+        let key_shares = execute_keygen(&party_share_counts, 1);
+        let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+        let msg = MessageDigest::try_from(&[42; 32][..]).unwrap();
+
+        let sig_shares: Vec<SignatureShare> = key_shares
+            .iter()
+            .map(|(keygen_id, key_share)| {
+                let (party_id, subshare_id) = party_share_counts
+                    .share_to_party_subshare_ids(keygen_id)
+                    .unwrap();
+                let signature = key_share
+                    .share()
+                    .signing_key()
+                    .try_sign_prehashed(
+                        k256::Scalar::random(rand::thread_rng()),
+                        k256::Scalar::from(&msg).into(),
+                    )
+                    .unwrap()
+                    .0;
+                SignatureShare {
+                    signature,
+                    party_id,
+                    subshare_id,
+                }
+            })
+            .collect();
+
+        let result = verify(group, &msg, &sig_shares);
+        assert!(result.is_valid());
+        assert_eq!(result.valid.len(), 3);
+        assert!(result.invalid.is_empty());
+
+        // one valid signature is not enough
+        let mut unknown = sig_shares[1].clone();
+        unknown.subshare_id = 5;
+        let mut bad = sig_shares[1].clone();
+        bad.signature = sig_shares[2].signature;
+        let result = verify(
+            group,
+            &msg,
+            &[sig_shares[0].clone(), sig_shares[0].clone(), unknown, bad],
+        );
+        assert!(!result.is_valid());
+        assert_eq!(result.valid, alloc::vec![TypedUsize::from_usize(0)]);
+        assert_eq!(
+            result.invalid,
+            alloc::vec![
+                (1, InvalidSignature::Duplicate),
+                (2, InvalidSignature::UnknownSigner),
+                (3, InvalidSignature::BadSignature),
+            ]
+        );
+
+        // party 0 holds shares 0, 1; party 1 holds share 2
+        let weights = PartyWeights::new(alloc::vec![1, 10], 5).unwrap();
+        let result = verify_weighted(group, &weights, &msg, &sig_shares[..2]).unwrap();
+        assert_eq!(result.valid.len(), 2);
+        assert_eq!(
+            result.weight,
+            Some(WeightTally {
+                valid_weight: 1,
+                threshold: 5
+            })
+        );
+        assert!(!result.is_valid());
+        let result = verify_weighted(group, &weights, &msg, &sig_shares[2..]).unwrap();
+        assert!(result.is_valid());
+    }
+}
@@ -0,0 +1,103 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::keygen::{KeygenPartyId, KeygenPartyShareCounts};
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    sdk::api::{TofnFatal, TofnResult},
+};
+
+/// Signing weight of each party, eg. its stake.
+/// A quorum is a set of parties whose total weight exceeds `threshold`,
+/// regardless of how many shares each party holds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartyWeights {
+    weights: VecMap<KeygenPartyId, u64>,
+    threshold: u64,
+}
+
+impl PartyWeights {
+    /// Fail if the total weight does not exceed `threshold`
+    pub fn new(weights: Vec<u64>, threshold: u64) -> TofnResult<Self> {
+        let total_weight = weights
+            .iter()
+            .try_fold(0u64, |sum, &weight| sum.checked_add(weight))
+            .ok_or_else(|| {
+                error!("total weight overflows");
+                TofnFatal
+            })?;
+        if total_weight <= threshold {
+            error!(
+                "total weight {} does not exceed threshold {}",
+                total_weight, threshold
+            );
+            return Err(TofnFatal);
+        }
+        Ok(Self {
+            weights: VecMap::from_vec(weights),
+            threshold,
+        })
+    }
+
+    pub fn threshold(&self) -> u64 {
+        self.threshold
+    }
+
+    pub fn party_count(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn weight(&self, party_id: TypedUsize<KeygenPartyId>) -> TofnResult<u64> {
+        self.weights.get(party_id).copied()
+    }
+
+    /// Total weight of `parties`
+    pub fn total(&self, parties: &Subset<KeygenPartyId>) -> TofnResult<u64> {
+        parties
+            .iter()
+            .try_fold(0, |sum, party_id| Ok(sum + self.weight(party_id)?))
+    }
+
+    /// Total weight of `parties` exceeds the threshold
+    pub fn is_quorum(&self, parties: &Subset<KeygenPartyId>) -> TofnResult<bool> {
+        Ok(self.total(parties)? > self.threshold)
+    }
+
+    /// Fail unless there is a weight for each party of `party_share_counts`
+    pub(crate) fn check_parties(
+        &self,
+        party_share_counts: &KeygenPartyShareCounts,
+    ) -> TofnResult<()> {
+        if self.party_count() != party_share_counts.party_count() {
+            error!(
+                "expected weights for {} parties, got {}",
+                party_share_counts.party_count(),
+                self.party_count()
+            );
+            return Err(TofnFatal);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PartyWeights;
+    use crate::collections::{Subset, TypedUsize};
+
+    #[test]
+    fn quorum() {
+        assert!(PartyWeights::new(alloc::vec![1, 2], 3).is_err());
+        assert!(PartyWeights::new(alloc::vec![u64::MAX, 1], 0).is_err());
+
+        let weights = PartyWeights::new(alloc::vec![5, 1, 3], 5).unwrap();
+        let mut parties = Subset::with_max_size(3);
+        parties.add(TypedUsize::from_usize(0)).unwrap();
+        assert_eq!(weights.total(&parties).unwrap(), 5);
+        assert!(!weights.is_quorum(&parties).unwrap());
+        parties.add(TypedUsize::from_usize(1)).unwrap();
+        assert!(weights.is_quorum(&parties).unwrap());
+    }
+}
@@ -1,15 +1,17 @@
 //! API for tofn users
 use alloc::vec::Vec;
+use core::convert::TryInto;
 
 pub use k256::ecdsa::{recoverable::Signature as RecoverableSignature, Signature, VerifyingKey};
 
+use blake2::Blake2b;
 use ecdsa::hazmat::VerifyPrimitive;
 use k256::{
     ecdsa::recoverable::Id,
     elliptic_curve::{
         generic_array::{
             sequence::Split,
-            typenum::{U12, U20},
+            typenum::{U12, U20, U32},
             GenericArray,
         },
         ops::Reduce,
@@ -19,17 +21,29 @@ use k256::{
 };
 use sha3::{digest::Update, Digest, Keccak256};
 
-pub type TofnResult<T> = Result<T, TofnFatal>;
 pub type BytesVec = Vec<u8>;
 
+pub use crate::crypto_tools::message_digest::{
+    DigestPolicy, Keccak256Policy, PolicyDigest, Sha256Policy,
+};
+
 pub use super::{
+    error::{ErrorContext, TofnFatal, TofnResult, TofnResultExt},
     party_share_counts::PartyShareCounts,
-    protocol::{Fault, Protocol, ProtocolFaulters, ProtocolOutput},
-    round::Round,
+    share_index::ShareIndex,
 };
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub struct TofnFatal;
+#[cfg(feature = "protocols")]
+pub use super::{
+    deadlines::RoundDeadlines,
+    msg_in_quota::MsgInQuota,
+    observer::Observer,
+    protocol::{Fault, MtaFailure, MtaProof, Protocol, ProtocolFaulters, ProtocolOutput},
+    replay::{replay, Divergence, Replay, Transcript},
+    round::{Round, RoundSnapshot, MEMORY_BUDGET_EXCEEDED},
+    round_graph::{RoundDescription, RoundGraph},
+    transcript_hash::{transcript_hash, TranscriptHash, TranscriptHasher},
+};
 
 // TODO make these into const generics wherever they're used
 pub const MAX_TOTAL_SHARE_COUNT: usize = 1000;
@@ -37,10 +51,10 @@ pub const MAX_PARTY_SHARE_COUNT: usize = MAX_TOTAL_SHARE_COUNT;
 
 /// Expose tofn's (de)serialization functions
 /// that use the appropriate bincode config options.
-pub use super::wire_bytes::{deserialize, serialize};
+pub use super::wire_bytes::{deserialize, serialize, split_message};
 
-#[cfg(feature = "malicious")]
-pub use super::wire_bytes::MsgType;
+/// Route encoded messages without deserializing them
+pub use super::wire_bytes::{peek_header, MsgHeader, MsgType};
 
 pub fn to_recoverable_signature(
     verifying_key: &VerifyingKey,
@@ -86,3 +100,89 @@ pub fn derive_ethereum_address(vkey: &VerifyingKey) -> [u8; 20] {
     let (_, last_bytes): (GenericArray<u8, U12>, GenericArray<u8, U20>) = hash.split();
     last_bytes.into()
 }
+
+/// Like [to_recoverable_signature] for groups that follow Ethereum's Keccak-256 digest policy,
+/// so that a digest computed under another policy is rejected at compile time.
+pub fn to_ethereum_signature(
+    verifying_key: &VerifyingKey,
+    message: &PolicyDigest<Keccak256Policy>,
+    signature: &Signature,
+) -> Option<RecoverableSignature> {
+    to_recoverable_signature(verifying_key, message.digest().as_ref(), signature)
+}
+
+/// Encode `signature` in the 65-byte format `r || s || v` of Substrate's `sp_core::ecdsa::Signature`,
+/// where `v` is the recovery id `0` or `1`.
+/// `message` is the 32-byte digest that was signed.
+pub fn to_substrate_signature(
+    verifying_key: &VerifyingKey,
+    message: &[u8],
+    signature: &Signature,
+) -> Option<[u8; 65]> {
+    to_recoverable_signature(verifying_key, message, signature)?
+        .as_ref()
+        .try_into()
+        .ok()
+}
+
+/// Substrate `AccountId32` of an ECDSA key: the BLAKE2b-256 hash of the compressed SEC1 encoding.
+pub fn derive_substrate_account_id(vkey: &VerifyingKey) -> [u8; 32] {
+    let hash: GenericArray<u8, U32> =
+        Blake2b::<U32>::digest(vkey.to_encoded_point(true).as_bytes());
+    hash.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use super::*;
+    use crate::{
+        crypto_tools::{message_digest::MessageDigest, rng::SecretRecoveryKey},
+        ecdsa,
+    };
+
+    #[test]
+    fn substrate_account_id() {
+        let generator = VerifyingKey::from_sec1_bytes(
+            &hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(derive_substrate_account_id(&generator)),
+            "2975f1d28b92b6e84499b83b0797ef5235553eeb7edaa0cea243c1128c2fe737"
+        );
+    }
+
+    #[test]
+    fn substrate_signature() {
+        let key_pair = ecdsa::keygen(
+            &SecretRecoveryKey::try_from(&[7; 64][..]).unwrap(),
+            b"nonce",
+        )
+        .unwrap();
+        let verifying_key =
+            VerifyingKey::from_sec1_bytes(key_pair.encoded_verifying_key()).unwrap();
+        let message = [42; 32];
+        let signature = ecdsa::sign(
+            key_pair.signing_key(),
+            &MessageDigest::try_from(&message[..]).unwrap(),
+        )
+        .unwrap();
+
+        let substrate_signature =
+            to_substrate_signature(&verifying_key, &message, &signature).unwrap();
+        assert_eq!(
+            substrate_signature[..64],
+            signature.normalize_s().unwrap_or(signature).as_ref()[..]
+        );
+        assert!(substrate_signature[64] <= 1);
+
+        let recovered_key = RecoverableSignature::try_from(&substrate_signature[..])
+            .unwrap()
+            .recover_verify_key_from_digest_bytes(FieldBytes::from_slice(&message))
+            .unwrap();
+        assert_eq!(recovered_key, verifying_key);
+    }
+}
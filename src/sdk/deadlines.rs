@@ -0,0 +1,76 @@
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+
+/// Time allotted to each round of a protocol, measured from when the round becomes current.
+/// Register with [Round::set_deadlines](super::api::Round::set_deadlines)
+/// and read back with [Round::deadline](super::api::Round::deadline).
+///
+/// tofn has no clock: the executor enforces deadlines.
+/// Once a round's deadline has passed, call [Round::execute_next_round](super::api::Round::execute_next_round)
+/// even if [Round::expecting_more_msgs_this_round](super::api::Round::expecting_more_msgs_this_round):
+/// peers whose messages are missing are accused of [Fault::MissingMessage](super::api::Fault::MissingMessage).
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct RoundDeadlines {
+    default: Option<Duration>,
+    rounds: BTreeMap<usize, Duration>,
+}
+
+impl RoundDeadlines {
+    /// The same deadline for every round
+    pub fn new(default: Duration) -> Self {
+        Self {
+            default: Some(default),
+            rounds: BTreeMap::new(),
+        }
+    }
+
+    /// Override the deadline of `round`, numbered from 0
+    pub fn with_round(mut self, round: usize, deadline: Duration) -> Self {
+        self.rounds.insert(round, deadline);
+        self
+    }
+
+    pub fn get(&self, round: usize) -> Option<Duration> {
+        self.rounds.get(&round).copied().or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::RoundDeadlines;
+    use crate::{
+        collections::TypedUsize,
+        crypto_tools::rng::dummy_secret_recovery_key,
+        multisig::keygen::{new_keygen, KeygenPartyShareCounts},
+        sdk::api::Protocol,
+    };
+
+    #[test]
+    fn round_deadline() {
+        let deadlines =
+            RoundDeadlines::new(Duration::from_secs(10)).with_round(1, Duration::from_secs(60));
+        assert_eq!(deadlines.get(0), Some(Duration::from_secs(10)));
+        assert_eq!(deadlines.get(1), Some(Duration::from_secs(60)));
+        assert_eq!(RoundDeadlines::default().get(0), None);
+
+        let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1]).unwrap();
+        let mut round = match new_keygen(
+            party_share_counts,
+            0,
+            TypedUsize::from_usize(0),
+            0,
+            &dummy_secret_recovery_key(0),
+            b"deadlines",
+        )
+        .unwrap()
+        {
+            Protocol::NotDone(round) => round,
+            Protocol::Done(_) => panic!("`new_keygen` returned a `Done` protocol"),
+        };
+        assert_eq!(round.deadline(), None);
+        round.set_deadlines(deadlines);
+        assert_eq!(round.deadline(), Some(Duration::from_secs(10)));
+    }
+}
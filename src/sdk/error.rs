@@ -0,0 +1,132 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+pub type TofnResult<T> = Result<T, TofnFatal>;
+
+/// A fatal error.
+/// Carries the [ErrorContext]s added by [TofnResultExt::context] on its way to the caller, innermost first.
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct TofnFatal {
+    context: Vec<ErrorContext>,
+}
+
+/// [TofnFatal] without context.
+/// Lets `Err(TofnFatal)` and `ok_or(TofnFatal)` create errors as if `TofnFatal` were a unit struct.
+#[allow(non_upper_case_globals)]
+pub const TofnFatal: TofnFatal = TofnFatal {
+    context: Vec::new(),
+};
+
+/// Where a [TofnFatal] passed through
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ErrorContext {
+    pub module: &'static str,
+    pub operation: &'static str,
+    pub round: Option<usize>,
+    pub peer: Option<usize>,
+}
+
+impl ErrorContext {
+    /// Use `module_path!()` for `module`
+    pub fn new(module: &'static str, operation: &'static str) -> Self {
+        Self {
+            module,
+            operation,
+            round: None,
+            peer: None,
+        }
+    }
+
+    pub fn round(mut self, round: usize) -> Self {
+        self.round = Some(round);
+        self
+    }
+
+    pub fn peer(mut self, peer: usize) -> Self {
+        self.peer = Some(peer);
+        self
+    }
+}
+
+impl TofnFatal {
+    /// Innermost first
+    pub fn context(&self) -> &[ErrorContext] {
+        &self.context
+    }
+
+    pub fn with_context(mut self, context: ErrorContext) -> Self {
+        self.context.push(context);
+        self
+    }
+}
+
+pub trait TofnResultExt {
+    /// Add `context` to the error, if any
+    fn context(self, context: ErrorContext) -> Self;
+}
+
+impl<T> TofnResultExt for TofnResult<T> {
+    fn context(self, context: ErrorContext) -> Self {
+        self.map_err(|err| err.with_context(context))
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}", self.operation, self.module)?;
+        if let Some(round) = self.round {
+            write!(f, ", round {}", round)?;
+        }
+        if let Some(peer) = self.peer {
+            write!(f, ", peer {}", peer)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for TofnFatal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tofn fatal error")?;
+        for context in &self.context {
+            write!(f, " in {}", context)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for TofnFatal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.context.is_empty() {
+            write!(f, "TofnFatal")
+        } else {
+            write!(f, "TofnFatal({})", self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::{ErrorContext, TofnFatal, TofnResult, TofnResultExt};
+
+    #[test]
+    fn context_chain() {
+        let result: TofnResult<()> = Err(TofnFatal);
+        let err = result
+            .context(ErrorContext::new("inner", "decode"))
+            .context(ErrorContext::new("outer", "execute").round(2).peer(3))
+            .unwrap_err();
+
+        assert_eq!(err.context().len(), 2);
+        assert_eq!(err.context()[0].operation, "decode");
+        assert_eq!(
+            err.to_string(),
+            "tofn fatal error in decode (inner) in execute (outer, round 2, peer 3)"
+        );
+        assert_eq!(alloc::format!("{:?}", TofnFatal), "TofnFatal");
+
+        let ok: TofnResult<u8> = Ok(1);
+        assert_eq!(ok.context(ErrorContext::new("m", "op")), Ok(1));
+    }
+}
@@ -1,7 +1,7 @@
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
 use tracing::warn;
 
 use crate::{
@@ -9,20 +9,24 @@ use crate::{
     sdk::{
         api::{BytesVec, Fault, TofnFatal, TofnResult},
         protocol_info::ProtocolInfo,
-        wire_bytes::deserialize,
+        wire_bytes::deserialize_canonical,
     },
 };
 
+#[cfg(feature = "wire-spec")]
+use crate::wire_spec::RoundSpec;
+
 use super::{
     protocol_builder::ProtocolBuilder,
+    round_graph::RoundDescription,
     wire_bytes::ExpectedMsgTypes::{self, *},
 };
 
 pub trait Executer: Send + Sync {
     type FinalOutput;
     type Index;
-    type Bcast: DeserializeOwned;
-    type P2p: DeserializeOwned;
+    type Bcast: Serialize + DeserializeOwned;
+    type P2p: Serialize + DeserializeOwned;
     fn execute(
         self: Box<Self>,
         info: &ProtocolInfo<Self::Index>,
@@ -49,6 +53,13 @@ pub trait ExecuterRaw: Send + Sync {
         faulters: FillVecMap<Self::Index, Fault>,
     ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>>;
 
+    fn description(&self) -> RoundDescription;
+
+    /// Trace the formats of the payloads of a bcast and a p2p sent in this round.
+    /// See [wire_spec](crate::wire_spec).
+    #[cfg(feature = "wire-spec")]
+    fn trace_msgs(&self, bcast: Option<&[u8]>, p2p: Option<&[u8]>) -> TofnResult<RoundSpec>;
+
     #[cfg(test)]
     fn as_any(&self) -> &dyn core::any::Any {
         unimplemented!("(ExecuterRaw) return `self` to enable runtime reflection: https://bennetthardwick.com/dont-use-boxed-trait-objects-for-struct-internals")
@@ -122,6 +133,19 @@ impl<T: Executer> ExecuterRaw for T {
         self.execute(info, bcasts_in_deserialized, p2ps_in)
     }
 
+    fn description(&self) -> RoundDescription {
+        RoundDescription {
+            executer: core::any::type_name::<T>(),
+            bcast: core::any::type_name::<T::Bcast>(),
+            p2p: core::any::type_name::<T::P2p>(),
+        }
+    }
+
+    #[cfg(feature = "wire-spec")]
+    fn trace_msgs(&self, bcast: Option<&[u8]>, p2p: Option<&[u8]>) -> TofnResult<RoundSpec> {
+        crate::wire_spec::trace_round::<T::Bcast, T::P2p>(bcast, p2p)
+    }
+
     #[cfg(test)]
     #[inline]
     fn as_any(&self) -> &dyn core::any::Any {
@@ -182,6 +206,7 @@ pub fn timeout_faulters<K>(
 }
 
 /// Attempt to deserialize bcasts.
+/// A bcast that is not canonically encoded is corrupted, see [deserialize_canonical].
 /// Set `faulters` appropriately.
 pub fn deserialize_bcasts<K, Bcast>(
     my_id: TypedUsize<K>,
@@ -189,13 +214,13 @@ pub fn deserialize_bcasts<K, Bcast>(
     faulters: &mut FillVecMap<K, Fault>,
 ) -> TofnResult<FillVecMap<K, Bcast>>
 where
-    Bcast: DeserializeOwned,
+    Bcast: Serialize + DeserializeOwned,
 {
     bcasts_in
         .into_iter()
         .map(|(from, bytes_option)| {
             if let Some(bytes) = bytes_option {
-                if let Some(val) = deserialize(&bytes) {
+                if let Some(val) = deserialize_canonical(&bytes) {
                     Ok(Some(val))
                 } else {
                     warn!(
@@ -213,6 +238,7 @@ where
 }
 
 /// Attempt to deserialize p2ps.
+/// A p2p that is not canonically encoded is corrupted, see [deserialize_canonical].
 /// Set `faulters` appropriately.
 pub fn deserialize_p2ps<K, P2p>(
     my_id: TypedUsize<K>,
@@ -220,9 +246,10 @@ pub fn deserialize_p2ps<K, P2p>(
     faulters: &mut FillVecMap<K, Fault>,
 ) -> TofnResult<FillP2ps<K, Option<P2p>>>
 where
-    P2p: DeserializeOwned,
+    P2p: Serialize + DeserializeOwned,
 {
-    let p2ps_deserialized: FillP2ps<K, Option<P2p>> = p2ps_in.map(|bytes| deserialize(&bytes));
+    let p2ps_deserialized: FillP2ps<K, Option<P2p>> =
+        p2ps_in.map(|bytes| deserialize_canonical(&bytes));
 
     for (from, p2ps) in p2ps_deserialized.iter() {
         for (to, p2p) in p2ps.iter() {
@@ -0,0 +1,432 @@
+//! Protobuf messages exchanged between tofnd and its clients, and conversions to and from tofn types.
+//!
+//! Field numbers match tofnd's `grpc.proto`, so these messages are wire-compatible with tofnd's generated types.
+//! Party uids are listed in protocol order: the uid at index `i` belongs to the party with id `i`.
+use alloc::{string::String, vec::Vec};
+
+use tracing::error;
+
+use crate::{
+    collections::{Subset, TypedUsize},
+    sdk::api::{Fault, PartyShareCounts, ProtocolFaulters, Round, TofnFatal, TofnResult},
+};
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TrafficIn {
+    #[prost(string, tag = "1")]
+    pub from_party_uid: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub payload: Vec<u8>,
+    #[prost(bool, tag = "3")]
+    pub is_broadcast: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TrafficOut {
+    #[prost(string, tag = "1")]
+    pub to_party_uid: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub payload: Vec<u8>,
+    #[prost(bool, tag = "3")]
+    pub is_broadcast: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct KeygenInit {
+    #[prost(string, tag = "1")]
+    pub new_key_uid: String,
+    #[prost(string, repeated, tag = "2")]
+    pub party_uids: Vec<String>,
+    #[prost(uint32, repeated, tag = "5")]
+    pub party_share_counts: Vec<u32>,
+    #[prost(uint32, tag = "3")]
+    pub my_party_index: u32,
+    #[prost(uint32, tag = "4")]
+    pub threshold: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SignInit {
+    #[prost(string, tag = "1")]
+    pub new_sig_uid: String,
+    #[prost(string, tag = "2")]
+    pub key_uid: String,
+    #[prost(string, repeated, tag = "3")]
+    pub party_uids: Vec<String>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub message_to_sign: Vec<u8>,
+}
+
+/// Request of tofnd's streaming `Keygen` and `Sign` rpcs
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MessageIn {
+    #[prost(oneof = "message_in::Data", tags = "1, 2, 3, 4")]
+    pub data: Option<message_in::Data>,
+}
+
+pub mod message_in {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Data {
+        #[prost(message, tag = "1")]
+        KeygenInit(super::KeygenInit),
+        #[prost(message, tag = "2")]
+        SignInit(super::SignInit),
+        #[prost(message, tag = "3")]
+        Traffic(super::TrafficIn),
+        #[prost(bool, tag = "4")]
+        Abort(bool),
+    }
+}
+
+/// Response of tofnd's streaming `Keygen` and `Sign` rpcs
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MessageOut {
+    #[prost(oneof = "message_out::Data", tags = "1, 2, 3, 4")]
+    pub data: Option<message_out::Data>,
+}
+
+pub mod message_out {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Data {
+        #[prost(message, tag = "1")]
+        Traffic(super::TrafficOut),
+        #[prost(message, tag = "2")]
+        KeygenResult(super::KeygenResult),
+        #[prost(message, tag = "3")]
+        SignResult(super::SignResult),
+        #[prost(bool, tag = "4")]
+        NeedRecover(bool),
+    }
+}
+
+/// `MessageOut.KeygenResult` in tofnd's proto
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct KeygenResult {
+    #[prost(oneof = "keygen_result::KeygenResultData", tags = "1, 2")]
+    pub keygen_result_data: Option<keygen_result::KeygenResultData>,
+}
+
+pub mod keygen_result {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum KeygenResultData {
+        #[prost(message, tag = "1")]
+        Data(super::KeygenOutput),
+        #[prost(message, tag = "2")]
+        Criminals(super::CriminalList),
+    }
+}
+
+/// `MessageOut.SignResult` in tofnd's proto
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SignResult {
+    #[prost(oneof = "sign_result::SignResultData", tags = "1, 2")]
+    pub sign_result_data: Option<sign_result::SignResultData>,
+}
+
+pub mod sign_result {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum SignResultData {
+        /// DER-encoded
+        #[prost(bytes, tag = "1")]
+        Signature(alloc::vec::Vec<u8>),
+        #[prost(message, tag = "2")]
+        Criminals(super::CriminalList),
+    }
+}
+
+/// `MessageOut.KeygenResult.KeygenOutput` in tofnd's proto
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct KeygenOutput {
+    /// SEC1 compressed
+    #[prost(bytes = "vec", tag = "1")]
+    pub pub_key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub group_recover_info: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub private_recover_info: Vec<u8>,
+}
+
+/// `MessageOut.CriminalList` in tofnd's proto
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CriminalList {
+    #[prost(message, repeated, tag = "1")]
+    pub criminals: Vec<Criminal>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Criminal {
+    #[prost(string, tag = "1")]
+    pub party_uid: String,
+    #[prost(enumeration = "CrimeType", tag = "2")]
+    pub crime_type: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum CrimeType {
+    Unspecified = 0,
+    NonMalicious = 1,
+    Malicious = 2,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RecoverRequest {
+    #[prost(message, optional, tag = "1")]
+    pub keygen_init: Option<KeygenInit>,
+    #[prost(message, optional, tag = "2")]
+    pub keygen_output: Option<KeygenOutput>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RecoverResponse {
+    #[prost(enumeration = "recover_response::Response", tag = "1")]
+    pub response: i32,
+}
+
+pub mod recover_response {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Response {
+        Unspecified = 0,
+        Success = 1,
+        Fail = 2,
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct KeyPresenceRequest {
+    #[prost(string, tag = "1")]
+    pub key_uid: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub pub_key: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct KeyPresenceResponse {
+    #[prost(enumeration = "key_presence_response::Response", tag = "1")]
+    pub response: i32,
+}
+
+pub mod key_presence_response {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Response {
+        Unspecified = 0,
+        Present = 1,
+        Absent = 2,
+        Fail = 3,
+    }
+}
+
+impl KeygenInit {
+    /// Share counts of all parties.
+    /// An empty `party_share_counts` means one share per party, as in older tofnd clients.
+    pub fn to_party_share_counts<P>(&self) -> TofnResult<PartyShareCounts<P>> {
+        if self.party_share_counts.is_empty() {
+            return PartyShareCounts::from_vec(alloc::vec![1; self.party_uids.len()]);
+        }
+        if self.party_share_counts.len() != self.party_uids.len() {
+            error!(
+                "party_share_counts length {} does not match party_uids length {}",
+                self.party_share_counts.len(),
+                self.party_uids.len()
+            );
+            return Err(TofnFatal);
+        }
+        PartyShareCounts::from_vec(
+            self.party_share_counts
+                .iter()
+                .map(|&count| count as usize)
+                .collect(),
+        )
+    }
+
+    pub fn my_party_id<P>(&self) -> TofnResult<TypedUsize<P>> {
+        TypedUsize::try_from_usize(self.my_party_index as usize, self.party_uids.len())
+    }
+}
+
+impl SignInit {
+    /// The sign participants as a subset of the keygen parties.
+    /// `keygen_party_uids` are the `party_uids` of the `KeygenInit` that created `key_uid`.
+    pub fn to_sign_parties<P>(&self, keygen_party_uids: &[String]) -> TofnResult<Subset<P>> {
+        let mut sign_parties = Subset::with_max_size(keygen_party_uids.len());
+        for uid in self.party_uids.iter() {
+            sign_parties.add(party_id(keygen_party_uids, uid)?)?;
+        }
+        Ok(sign_parties)
+    }
+}
+
+/// Outgoing messages of `round` addressed to parties in `party_uids`
+pub fn traffic_out<F, K, P>(
+    round: &Round<F, K, P>,
+    party_uids: &[String],
+) -> TofnResult<Vec<TrafficOut>> {
+    let mut traffic = Vec::new();
+    if let Some(bcast) = round.bcast_out() {
+        traffic.push(TrafficOut {
+            to_party_uid: String::new(),
+            payload: bcast.clone(),
+            is_broadcast: true,
+        });
+    }
+    if let Some(p2ps) = round.p2ps_out() {
+        for (to, payload) in p2ps.iter() {
+            let to = round.info().share_index().share_to_party_id(to)?;
+            let to_party_uid = party_uids.get(to.as_usize()).ok_or_else(|| {
+                error!("no party uid for party {}", to);
+                TofnFatal
+            })?;
+            traffic.push(TrafficOut {
+                to_party_uid: to_party_uid.clone(),
+                payload: payload.clone(),
+                is_broadcast: false,
+            });
+        }
+    }
+    Ok(traffic)
+}
+
+/// Deliver `traffic` to `round`.
+/// Return `TofnFatal` if the sender is not in `party_uids`.
+pub fn traffic_in<F, K, P>(
+    round: &mut Round<F, K, P>,
+    traffic: &TrafficIn,
+    party_uids: &[String],
+) -> TofnResult<()> {
+    let from = party_id(party_uids, &traffic.from_party_uid)?;
+    round.msg_in(from, &traffic.payload)
+}
+
+/// The parties in `faulters` by uid.
+/// Missing messages may be an honest party's timeout and transcript mismatches may be a relay's doing,
+/// so they are not marked malicious.
+pub fn criminals<P>(
+    faulters: &ProtocolFaulters<P>,
+    party_uids: &[String],
+) -> TofnResult<CriminalList> {
+    let criminals = faulters
+        .iter_some()
+        .map(|(party_id, fault)| {
+            let party_uid = party_uids.get(party_id.as_usize()).ok_or_else(|| {
+                error!("no party uid for party {}", party_id);
+                TofnFatal
+            })?;
+            let crime_type = match fault {
+                Fault::MissingMessage | Fault::TranscriptMismatch => CrimeType::NonMalicious,
+                _ => CrimeType::Malicious,
+            };
+            Ok(Criminal {
+                party_uid: party_uid.clone(),
+                crime_type: crime_type as i32,
+            })
+        })
+        .collect::<TofnResult<_>>()?;
+    Ok(CriminalList { criminals })
+}
+
+fn party_id<P>(party_uids: &[String], uid: &str) -> TofnResult<TypedUsize<P>> {
+    party_uids
+        .iter()
+        .position(|party_uid| party_uid == uid)
+        .map(TypedUsize::from_usize)
+        .ok_or_else(|| {
+            error!("unknown party uid {}", uid);
+            TofnFatal
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    use super::*;
+
+    struct TestPartyId;
+
+    fn uids(uids: &[&str]) -> Vec<String> {
+        uids.iter().map(|uid| uid.to_string()).collect()
+    }
+
+    #[test]
+    fn keygen_init() {
+        let mut init = KeygenInit {
+            new_key_uid: "key".to_string(),
+            party_uids: uids(&["a", "b", "c"]),
+            party_share_counts: Vec::new(),
+            my_party_index: 2,
+            threshold: 1,
+        };
+        assert_eq!(
+            init.to_party_share_counts::<TestPartyId>()
+                .unwrap()
+                .total_share_count(),
+            3
+        );
+        assert_eq!(init.my_party_id::<TestPartyId>().unwrap().as_usize(), 2);
+
+        init.party_share_counts = vec![1, 2, 3];
+        assert_eq!(
+            init.to_party_share_counts::<TestPartyId>()
+                .unwrap()
+                .total_share_count(),
+            6
+        );
+
+        init.party_share_counts = vec![1, 2];
+        assert!(init.to_party_share_counts::<TestPartyId>().is_err());
+
+        init.my_party_index = 3;
+        assert!(init.my_party_id::<TestPartyId>().is_err());
+    }
+
+    #[test]
+    fn criminal_list() {
+        let mut faulters = ProtocolFaulters::<TestPartyId>::with_size(3);
+        faulters
+            .set(TypedUsize::from_usize(0), Fault::MissingMessage)
+            .unwrap();
+        faulters
+            .set(TypedUsize::from_usize(2), Fault::CorruptedMessage)
+            .unwrap();
+        let party_uids = uids(&["a", "b", "c"]);
+
+        assert_eq!(
+            criminals(&faulters, &party_uids).unwrap().criminals,
+            vec![
+                Criminal {
+                    party_uid: "a".to_string(),
+                    crime_type: CrimeType::NonMalicious as i32,
+                },
+                Criminal {
+                    party_uid: "c".to_string(),
+                    crime_type: CrimeType::Malicious as i32,
+                },
+            ]
+        );
+        assert!(criminals(&faulters, &party_uids[..2]).is_err());
+    }
+
+    #[test]
+    fn sign_init() {
+        let keygen_party_uids = uids(&["a", "b", "c"]);
+        let mut init = SignInit {
+            new_sig_uid: "sig".to_string(),
+            key_uid: "key".to_string(),
+            party_uids: uids(&["c", "a"]),
+            message_to_sign: vec![42; 32],
+        };
+        let sign_parties = init
+            .to_sign_parties::<TestPartyId>(&keygen_party_uids)
+            .unwrap();
+        assert_eq!(sign_parties.member_count(), 2);
+        assert!(sign_parties.is_member(TypedUsize::from_usize(0)).unwrap());
+        assert!(!sign_parties.is_member(TypedUsize::from_usize(1)).unwrap());
+
+        init.party_uids = uids(&["a", "d"]);
+        assert!(init
+            .to_sign_parties::<TestPartyId>(&keygen_party_uids)
+            .is_err());
+    }
+}
@@ -1,11 +1,34 @@
-//! API for protocol implementers, but not for users of protocols
+//! API for protocol implementers, but not for users of protocols.
+//!
+//! Every protocol in this crate is built on this API, and so can yours.
+//! A protocol is a chain of rounds:
+//!
+//! 1. Start the protocol with [new_protocol], given the maximum length of its messages,
+//!    the outgoing messages of the first round
+//!    and a [RoundBuilder] holding the state that executes once those messages arrive.
+//! 2. Each round's state implements [Executer]: [Executer::execute] receives the deserialized
+//!    bcasts and p2ps of all shares and returns either the next [RoundBuilder]
+//!    or [ProtocolBuilder::Done] with the output or the faulters.
+//! 3. Serialize outgoing payloads with [serialize]; build p2ps with [ProtocolInfo::new_p2ps_out].
+//!
+//! [Executer] moves to the sad path as soon as any message is missing or corrupted.
+//! Robust protocols implement [ExecuterRaw] instead,
+//! with the help of [timeout_faulters], [deserialize_bcasts] and [deserialize_p2ps].
+//!
+//! Chain protocols into one session with [ProtocolBuilder::and_then].
+//! Run the result with [Round](super::api::Round) like any other protocol.
+//! See `tests/integration/coin_flip.rs` for a complete protocol.
 pub use super::executer::{
     deserialize_bcasts, deserialize_p2ps, timeout_faulters, Executer, ExecuterRaw,
 };
 pub use super::protocol::new_protocol;
 pub use super::protocol_builder::{ProtocolBuilder, ProtocolBuilderOutput, RoundBuilder};
 pub use super::protocol_info::ProtocolInfo;
-pub use super::wire_bytes::{decode, deserialize, encode, serialize};
+pub use super::round_graph::RoundDescription;
+pub use super::wire_bytes::{
+    decode, decode_canonical, decode_message, decode_message_ref, deserialize, encode,
+    encode_message, serialize, ExpectedMsgTypes, MsgType, WireBytes, WireBytesRef,
+};
 
 mod utils {
     use crate::collections::TypedUsize;
@@ -25,5 +48,8 @@ mod utils {
 }
 pub use utils::{log_accuse_warn, log_fault_info, log_fault_warn};
 
-#[cfg(any(test, feature = "malicious"))]
-pub use super::wire_bytes::{decode_message, encode_message, ExpectedMsgTypes, MsgType};
+#[cfg(feature = "wire-spec")]
+pub(crate) use super::wire_bytes::trace_envelope;
+
+#[cfg(feature = "malicious")]
+pub(crate) use super::round::malicious::{with_msg_fault, MsgFault};
@@ -0,0 +1,479 @@
+//! Run whole protocols in-process, eg. for tests, examples and tooling.
+//! Every message is delivered to every party in each round,
+//! except those a malicious share withholds, see `Round::delivers_to`.
+//!
+//! Parties hold each other's secrets in memory, so do not use these helpers
+//! for keys that protect real value.
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use rand::RngCore;
+use tracing::{error, warn};
+use zeroize::Zeroize;
+
+use super::api::{
+    transcript_hash, BytesVec, MsgType, PartyShareCounts, Protocol, Round, Signature, TofnFatal,
+    TofnResult, TranscriptHash,
+};
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    crypto_tools::rng::SecretRecoveryKey,
+    gg20::{
+        keygen::{
+            create_party_keypair_and_zksetup, create_party_keypair_and_zksetup_unsafe, new_keygen,
+            KeygenPartyId, KeygenPartyShareCounts, KeygenShareId, PartyKeygenData, SecretKeyShare,
+        },
+        sign::{new_sign, MessageDigest, SignParties, SignShareId},
+    },
+};
+
+#[cfg(feature = "malicious")]
+use crate::gg20::{keygen, sign};
+
+const SESSION_NONCE: &[u8] = b"tofn::sdk::local";
+
+/// Messages delivered in one round of [execute_protocol_with_progress], summed over all parties
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundTraffic {
+    /// Index starts at 1
+    pub round: usize,
+    pub msg_count: usize,
+    pub byte_count: usize,
+}
+
+/// Execute `parties` until at least one of them is done
+pub fn execute_protocol<F, K, P>(
+    parties: VecMap<K, Protocol<F, K, P>>,
+) -> TofnResult<VecMap<K, Protocol<F, K, P>>> {
+    execute_protocol_with_progress(parties, |_| {})
+}
+
+/// Like [execute_protocol] but call `progress` after each round is executed,
+/// eg. to report progress against a protocol's `ROUND_COUNT`.
+pub fn execute_protocol_with_progress<F, K, P>(
+    parties: VecMap<K, Protocol<F, K, P>>,
+    progress: impl FnMut(&RoundTraffic),
+) -> TofnResult<VecMap<K, Protocol<F, K, P>>> {
+    execute(parties, progress, |_| {})
+}
+
+/// Like [execute_protocol] but also return the [transcript_hash] of all delivered messages
+pub fn execute_protocol_with_transcript_hash<F, K, P>(
+    parties: VecMap<K, Protocol<F, K, P>>,
+) -> TofnResult<(VecMap<K, Protocol<F, K, P>>, TranscriptHash)> {
+    let mut msgs = Vec::new();
+    let parties = execute(parties, |_| {}, |bytes| msgs.push(bytes.to_vec()))?;
+    Ok((parties, transcript_hash::<K, _>(msgs)?))
+}
+
+/// Call `record` on each message as it is delivered
+fn execute<F, K, P>(
+    mut parties: VecMap<K, Protocol<F, K, P>>,
+    mut progress: impl FnMut(&RoundTraffic),
+    mut record: impl FnMut(&[u8]),
+) -> TofnResult<VecMap<K, Protocol<F, K, P>>> {
+    while nobody_done(&parties) {
+        let (next_parties, traffic) =
+            next_round(parties, &mut record, &mut |_, _, bytes| alloc::vec![bytes])?;
+        progress(&traffic);
+        parties = next_parties;
+    }
+    Ok(parties)
+}
+
+/// Execute `parties` to completion and return the output of each party.
+/// Fail if any party is not done or did not succeed.
+pub fn execute_honest<F, K, P>(parties: VecMap<K, Protocol<F, K, P>>) -> TofnResult<VecMap<K, F>> {
+    execute_protocol(parties)?
+        .into_iter()
+        .map(|(i, party)| match party {
+            Protocol::Done(Ok(output)) => Ok(output),
+            Protocol::Done(Err(_)) => {
+                error!("party {} found faulters in an honest execution", i);
+                Err(TofnFatal)
+            }
+            Protocol::NotDone(_) => {
+                error!("party {} not done", i);
+                Err(TofnFatal)
+            }
+        })
+        .collect()
+}
+
+pub fn nobody_done<F, K, P>(parties: &VecMap<K, Protocol<F, K, P>>) -> bool {
+    // warn if there's disagreement
+    let (mut done, mut not_done) = (
+        Vec::with_capacity(parties.len()),
+        Vec::with_capacity(parties.len()),
+    );
+    for (i, party) in parties.iter() {
+        if matches!(party, Protocol::Done(_)) {
+            done.push(i);
+        } else {
+            not_done.push(i);
+        }
+    }
+    if !done.is_empty() && !not_done.is_empty() {
+        warn!(
+            "disagreement: done parties {:?}, not done parties {:?}",
+            done, not_done
+        );
+    }
+    done.is_empty()
+}
+
+/// Deliver the outgoing messages of the current round and execute the next round of every party that is not done.
+/// `tamper` returns the copies of each message to deliver in its place,
+/// eg. none to withhold it or two to duplicate it.
+/// Harnesses call this round by round to inject faults, see [execute_round].
+pub fn execute_round_with<F, K, P>(
+    parties: VecMap<K, Protocol<F, K, P>>,
+    mut tamper: impl FnMut(TypedUsize<K>, MsgType<K>, BytesVec) -> Vec<BytesVec>,
+) -> TofnResult<VecMap<K, Protocol<F, K, P>>> {
+    Ok(next_round(parties, &mut |_| {}, &mut tamper)?.0)
+}
+
+/// Like [execute_round_with] but deliver every message as it was sent
+pub fn execute_round<F, K, P>(
+    parties: VecMap<K, Protocol<F, K, P>>,
+) -> TofnResult<VecMap<K, Protocol<F, K, P>>> {
+    execute_round_with(parties, |_, _, bytes| alloc::vec![bytes])
+}
+
+fn next_round<F, K, P>(
+    mut parties: VecMap<K, Protocol<F, K, P>>,
+    record: &mut impl FnMut(&[u8]),
+    tamper: &mut impl FnMut(TypedUsize<K>, MsgType<K>, BytesVec) -> Vec<BytesVec>,
+) -> TofnResult<(VecMap<K, Protocol<F, K, P>>, RoundTraffic)> {
+    let rounds = || {
+        parties.iter().filter_map(|(from, party)| match party {
+            Protocol::NotDone(round) => Some((from, round)),
+            Protocol::Done(_) => None,
+        })
+    };
+    let mut traffic = RoundTraffic {
+        round: rounds()
+            .next()
+            .map_or(0, |(_, round)| round.info().round() + 1),
+        msg_count: 0,
+        byte_count: 0,
+    };
+
+    // collect bcasts, then p2ps, each with its recipients
+    let mut msgs: Vec<(TypedUsize<K>, MsgType<K>, BytesVec, Vec<bool>)> = Vec::new();
+    for (from, round) in rounds() {
+        if let Some(bytes) = round.bcast_out() {
+            let recipients = recipients(round, MsgType::Bcast);
+            msgs.push((from, MsgType::Bcast, bytes.clone(), recipients));
+        }
+    }
+    for (from, round) in rounds() {
+        if let Some(p2ps) = round.p2ps_out() {
+            for (to, bytes) in p2ps.iter() {
+                let recipients = recipients(round, MsgType::P2p { to });
+                msgs.push((from, MsgType::P2p { to }, bytes.clone(), recipients));
+            }
+        }
+    }
+
+    // deliver
+    for (from, msg_type, bytes, recipients) in msgs {
+        for bytes in tamper(from, msg_type, bytes) {
+            traffic.msg_count += 1;
+            traffic.byte_count += bytes.len();
+            record(&bytes);
+            for (to, party) in parties.iter_mut() {
+                if let (true, Protocol::NotDone(round)) = (recipients[to.as_usize()], party) {
+                    let from_party_id = round.info().share_index().share_to_party_id(from)?;
+                    round.msg_in(from_party_id, &bytes)?;
+                }
+            }
+        }
+    }
+
+    // compute next round's parties
+    let parties = parties
+        .into_iter()
+        .map(|(i, party)| match party {
+            Protocol::NotDone(round) => {
+                if round.expecting_more_msgs_this_round() {
+                    warn!(
+                        "all messages delivered this round but party {} still expecting messages",
+                        i,
+                    );
+                }
+                round.execute_next_round()
+            }
+            done => Ok(done),
+        })
+        .collect::<TofnResult<_>>()?;
+    Ok((parties, traffic))
+}
+
+/// Which shares receive outgoing message `msg_type` of `round`, by share id
+fn recipients<F, K, P>(round: &Round<F, K, P>, msg_type: MsgType<K>) -> Vec<bool> {
+    let total_share_count = round.info().share_info().total_share_count();
+    #[cfg(feature = "malicious")]
+    {
+        (0..total_share_count)
+            .map(|to| round.delivers_to(msg_type, TypedUsize::from_usize(to)))
+            .collect()
+    }
+    #[cfg(not(feature = "malicious"))]
+    {
+        let _ = msg_type;
+        alloc::vec![true; total_share_count]
+    }
+}
+
+/// gg20 keygen with a fresh random secret recovery key for each party
+pub fn keygen(
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+) -> TofnResult<VecMap<KeygenShareId, SecretKeyShare>> {
+    keygen_with(
+        party_share_counts,
+        threshold,
+        create_party_keypair_and_zksetup,
+    )
+}
+
+// BEWARE: This is only made visible for faster testing
+/// As [keygen] but with insecure Paillier keys that are fast to generate
+pub fn keygen_unsafe(
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+) -> TofnResult<VecMap<KeygenShareId, SecretKeyShare>> {
+    keygen_with(
+        party_share_counts,
+        threshold,
+        create_party_keypair_and_zksetup_unsafe,
+    )
+}
+
+fn keygen_with(
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    create_party_keygen_data: fn(
+        TypedUsize<KeygenPartyId>,
+        &SecretRecoveryKey,
+        &[u8],
+    ) -> TofnResult<PartyKeygenData>,
+) -> TofnResult<VecMap<KeygenShareId, SecretKeyShare>> {
+    let mut parties = Vec::with_capacity(party_share_counts.total_share_count());
+    for (party_id, &party_share_count) in party_share_counts.iter() {
+        // each party uses the same keygen data for all its subshares
+        let party_keygen_data =
+            create_party_keygen_data(party_id, &random_secret_recovery_key()?, SESSION_NONCE)?;
+        for subshare_id in 0..party_share_count {
+            parties.push(new_keygen(
+                party_share_counts.clone(),
+                threshold,
+                party_id,
+                subshare_id,
+                &party_keygen_data,
+                #[cfg(feature = "malicious")]
+                keygen::malicious::Behaviour::Honest,
+            )?);
+        }
+    }
+    execute_honest(VecMap::from_vec(parties))
+}
+
+/// gg20 sign of `msg_to_sign` by `sign_parties`.
+/// `key_shares` must contain the shares of every party in `sign_parties`.
+pub fn sign(
+    key_shares: &VecMap<KeygenShareId, SecretKeyShare>,
+    sign_parties: &SignParties,
+    msg_to_sign: &MessageDigest,
+) -> TofnResult<Signature> {
+    let party_share_counts = key_shares
+        .iter()
+        .next()
+        .ok_or_else(|| {
+            error!("no key shares");
+            TofnFatal
+        })?
+        .1
+        .group()
+        .party_share_counts();
+    let keygen_share_ids =
+        VecMap::<SignShareId, _>::from_vec(party_share_counts.share_id_subset(sign_parties)?);
+
+    let parties = keygen_share_ids
+        .into_iter()
+        .map(|(_, keygen_share_id)| {
+            let key_share = key_shares.get(keygen_share_id)?;
+            new_sign(
+                key_share.group(),
+                key_share.share(),
+                sign_parties,
+                msg_to_sign,
+                #[cfg(feature = "malicious")]
+                sign::malicious::Behaviour::Honest,
+            )
+        })
+        .collect::<TofnResult<_>>()?;
+
+    let signatures = execute_honest(parties)?;
+    let (_, signature) = signatures.iter().next().ok_or(TofnFatal)?;
+    if signatures.iter().any(|(_, other)| other != signature) {
+        error!("honest signers disagree on the signature");
+        return Err(TofnFatal);
+    }
+    Ok(*signature)
+}
+
+/// gg20 keygen followed by a sign of `msg_to_sign` by all parties
+pub fn keygen_and_sign(
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    msg_to_sign: &MessageDigest,
+) -> TofnResult<(VecMap<KeygenShareId, SecretKeyShare>, Signature)> {
+    let sign_parties = all_parties(&party_share_counts)?;
+    let key_shares = keygen(party_share_counts, threshold)?;
+    let signature = sign(&key_shares, &sign_parties, msg_to_sign)?;
+    Ok((key_shares, signature))
+}
+
+/// Every party of `party_share_counts`
+pub fn all_parties<P>(party_share_counts: &PartyShareCounts<P>) -> TofnResult<Subset<P>> {
+    let mut parties = Subset::with_max_size(party_share_counts.party_count());
+    for (party_id, _) in party_share_counts.iter() {
+        parties.add(party_id)?;
+    }
+    Ok(parties)
+}
+
+fn random_secret_recovery_key() -> TofnResult<SecretRecoveryKey> {
+    let mut bytes = [0; 64];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let secret_recovery_key = SecretRecoveryKey::try_from(&bytes[..]).map_err(|_| TofnFatal);
+    bytes.zeroize();
+    secret_recovery_key
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use ecdsa::hazmat::VerifyPrimitive;
+    use k256::PublicKey;
+
+    use super::{all_parties, execute_protocol_with_progress, keygen_unsafe, sign, SESSION_NONCE};
+    use crate::{
+        collections::{TypedUsize, VecMap},
+        crypto_tools::rng::dummy_secret_recovery_key,
+        gg20::{
+            keygen::{self, KeygenPartyShareCounts},
+            sign::{self, new_sign, MessageDigest, SignParties},
+        },
+        sdk::api::Protocol,
+    };
+
+    #[test]
+    fn keygen_and_sign_unsafe() {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 2, 1]).unwrap();
+        let key_shares = keygen_unsafe(party_share_counts.clone(), 2).unwrap();
+        assert_eq!(key_shares.len(), 4);
+
+        let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+        let pubkey: PublicKey = key_shares
+            .iter()
+            .next()
+            .unwrap()
+            .1
+            .group()
+            .verifying_key()
+            .into();
+
+        // all parties
+        let signature = sign(
+            &key_shares,
+            &all_parties(&party_share_counts).unwrap(),
+            &msg_to_sign,
+        )
+        .unwrap();
+        assert!(pubkey
+            .as_affine()
+            .verify_prehashed((&msg_to_sign).into(), &signature)
+            .is_ok());
+
+        // a subset of parties
+        let mut sign_parties = SignParties::with_max_size(party_share_counts.party_count());
+        sign_parties.add(TypedUsize::from_usize(1)).unwrap();
+        sign_parties.add(TypedUsize::from_usize(2)).unwrap();
+        let signature = sign(&key_shares, &sign_parties, &msg_to_sign).unwrap();
+        assert!(pubkey
+            .as_affine()
+            .verify_prehashed((&msg_to_sign).into(), &signature)
+            .is_ok());
+    }
+
+    #[test]
+    fn round_counts() {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 1]).unwrap();
+        let keygen_parties: VecMap<keygen::KeygenShareId, _> = party_share_counts
+            .iter()
+            .map(|(party_id, _)| {
+                let party_keygen_data = keygen::create_party_keypair_and_zksetup_unsafe(
+                    party_id,
+                    &dummy_secret_recovery_key(party_id.as_usize()),
+                    SESSION_NONCE,
+                )
+                .unwrap();
+                keygen::new_keygen(
+                    party_share_counts.clone(),
+                    1,
+                    party_id,
+                    0,
+                    &party_keygen_data,
+                    #[cfg(feature = "malicious")]
+                    keygen::malicious::Behaviour::Honest,
+                )
+                .unwrap()
+            })
+            .collect();
+        let (rounds, key_shares) = execute_counting_rounds(keygen_parties);
+        assert_eq!(rounds, keygen::ROUND_COUNT);
+
+        let sign_parties = all_parties(&party_share_counts).unwrap();
+        let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+        let sign_parties: VecMap<sign::SignShareId, _> = key_shares
+            .iter()
+            .map(|(_, key_share)| {
+                new_sign(
+                    key_share.group(),
+                    key_share.share(),
+                    &sign_parties,
+                    &msg_to_sign,
+                    #[cfg(feature = "malicious")]
+                    sign::malicious::Behaviour::Honest,
+                )
+                .unwrap()
+            })
+            .collect();
+        let (rounds, _) = execute_counting_rounds(sign_parties);
+        assert_eq!(rounds, sign::ROUND_COUNT);
+    }
+
+    fn execute_counting_rounds<F, K, P>(
+        parties: VecMap<K, Protocol<F, K, P>>,
+    ) -> (usize, VecMap<K, F>) {
+        let mut rounds = 0;
+        let parties = execute_protocol_with_progress(parties, |traffic| {
+            rounds += 1;
+            assert_eq!(traffic.round, rounds);
+            assert!(traffic.msg_count > 0 && traffic.byte_count > 0);
+        })
+        .unwrap();
+        let outputs = parties
+            .into_iter()
+            .map(|(_, party)| match party {
+                Protocol::Done(Ok(output)) => output,
+                _ => panic!("party not done"),
+            })
+            .collect();
+        (rounds, outputs)
+    }
+}
@@ -0,0 +1,72 @@
+//! Counters and histograms via the [metrics](https://crates.io/crates/metrics) facade,
+//! enabled by the `metrics` feature. Install a recorder to collect them.
+//!
+//! - `tofn_rounds_executed_total{round}`
+//! - `tofn_round_execution_seconds{round}`: time spent deserializing messages and verifying proofs
+//! - `tofn_messages_rejected_total{reason}`: messages discarded by `msg_in`
+//! - `tofn_faults_total{fault}`: faulters in protocol outputs
+//!
+//! Without the feature these functions do nothing.
+#![cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+
+use super::protocol::ProtocolOutput;
+
+#[cfg(feature = "metrics")]
+use {super::protocol::Fault, alloc::string::ToString};
+
+/// Started when a round begins execution
+pub(super) struct RoundTimer {
+    #[cfg(feature = "metrics")]
+    start: std::time::Instant,
+}
+
+impl RoundTimer {
+    pub(super) fn start() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    pub(super) fn round_executed(self, round: usize) {
+        #[cfg(feature = "metrics")]
+        {
+            let round = round.to_string();
+            ::metrics::increment_counter!("tofn_rounds_executed_total", "round" => round.clone());
+            ::metrics::histogram!(
+                "tofn_round_execution_seconds",
+                self.start.elapsed().as_secs_f64(),
+                "round" => round
+            );
+        }
+    }
+}
+
+pub(super) fn msg_rejected(reason: &'static str) {
+    #[cfg(feature = "metrics")]
+    {
+        ::metrics::increment_counter!("tofn_messages_rejected_total", "reason" => reason);
+    }
+}
+
+pub(super) fn protocol_done<F, P>(output: &ProtocolOutput<F, P>) {
+    #[cfg(feature = "metrics")]
+    {
+        if let Err(faulters) = output {
+            for (_, fault) in faulters.iter_some() {
+                ::metrics::increment_counter!("tofn_faults_total", "fault" => fault_label(fault));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn fault_label(fault: &Fault) -> &'static str {
+    match fault {
+        Fault::MissingMessage => "missing_message",
+        Fault::CorruptedMessage => "corrupted_message",
+        Fault::ProtocolFault => "protocol_fault",
+        Fault::BadMtaProof(_) => "bad_mta_proof",
+        Fault::TranscriptMismatch => "transcript_mismatch",
+    }
+}
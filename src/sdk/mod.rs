@@ -1,13 +1,44 @@
 pub mod api;
+#[cfg(feature = "protocols")]
+pub mod local;
 
-/// Do not expose [implementer_api] publicly for now.
-/// Currently the only protocol implementation using this API is [gg20] and it's inside this crate.
-pub(crate) mod implementer_api;
+#[cfg(feature = "grpc-types")]
+pub mod grpc_types;
 
-mod executer;
+#[cfg(feature = "protocols")]
+pub mod implementer_api;
+
+mod error;
 mod party_share_counts;
+mod share_index;
+// only the (de)serialization functions are used without `protocols`
+#[cfg_attr(not(feature = "protocols"), allow(dead_code))]
+mod wire_bytes;
+
+// protocol execution
+#[cfg(feature = "protocols")]
+mod deadlines;
+#[cfg(feature = "protocols")]
+mod executer;
+#[cfg(feature = "protocols")]
+mod metrics;
+#[cfg(feature = "protocols")]
+mod msg_in_quota;
+#[cfg(feature = "protocols")]
+mod observer;
+#[cfg(feature = "protocols")]
 mod protocol;
+#[cfg(feature = "protocols")]
 mod protocol_builder;
+#[cfg(feature = "protocols")]
 mod protocol_info;
+#[cfg(feature = "protocols")]
+mod replay;
+#[cfg(feature = "protocols")]
 mod round;
-mod wire_bytes;
+#[cfg(feature = "protocols")]
+mod round_graph;
+#[cfg(feature = "protocols")]
+mod spans;
+#[cfg(feature = "protocols")]
+mod transcript_hash;
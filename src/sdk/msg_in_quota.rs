@@ -0,0 +1,111 @@
+/// Cap on what each peer party may send in one round, counting every call to
+/// [Round::msg_in](super::api::Round::msg_in) including chunks and rejected messages.
+/// Register with [Round::set_msg_in_quota](super::api::Round::set_msg_in_quota).
+///
+/// A party that exceeds its quota is accused of [Fault::CorruptedMessage](super::api::Fault::CorruptedMessage),
+/// everything buffered from it this round is freed and its further messages this round are dropped.
+/// This bounds the memory a hostile peer can make a party hold during a long round,
+/// eg. by spamming small chunks of a message it never completes.
+///
+/// Quotas are per party, not per share: allow for the share count of the largest party.
+/// By default there is no quota.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct MsgInQuota {
+    max_msgs: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+impl MsgInQuota {
+    /// At most `max_msgs` messages and `max_bytes` bytes from each party per round
+    pub fn new(max_msgs: usize, max_bytes: usize) -> Self {
+        Self {
+            max_msgs: Some(max_msgs),
+            max_bytes: Some(max_bytes),
+        }
+    }
+
+    pub fn max_msgs(&self) -> Option<usize> {
+        self.max_msgs
+    }
+
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
+
+    /// `msgs` messages totalling `bytes` bytes are within this quota
+    pub fn allows(&self, msgs: usize, bytes: usize) -> bool {
+        self.max_msgs.map_or(true, |max| msgs <= max)
+            && self.max_bytes.map_or(true, |max| bytes <= max)
+    }
+}
+
+/// What a party has sent so far this round
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct MsgInUsage {
+    pub msgs: usize,
+    pub bytes: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MsgInQuota;
+    use crate::{
+        collections::TypedUsize,
+        crypto_tools::rng::dummy_secret_recovery_key,
+        multisig::keygen::{new_keygen, KeygenPartyShareCounts, KeygenProtocol},
+        sdk::api::{split_message, Fault, Protocol},
+    };
+
+    fn keygen(party_id: usize) -> KeygenProtocol {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 1]).unwrap();
+        new_keygen(
+            party_share_counts,
+            1,
+            TypedUsize::from_usize(party_id),
+            0,
+            &dummy_secret_recovery_key(party_id),
+            b"msg_in_quota",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn quota_exceeded() {
+        let quota = MsgInQuota::new(1, 1000);
+        assert!(quota.allows(1, 1000));
+        assert!(!quota.allows(2, 0));
+        assert!(!quota.allows(0, 1001));
+        assert!(MsgInQuota::default().allows(usize::MAX, usize::MAX));
+
+        let mut rounds: alloc::vec::Vec<_> = (0..2)
+            .map(|i| match keygen(i) {
+                Protocol::NotDone(round) => round,
+                Protocol::Done(_) => panic!("keygen done too early"),
+            })
+            .collect();
+        let honest_bcast = rounds[0].bcast_out().unwrap().clone();
+        let spam_bcast = rounds[1].bcast_out().unwrap().clone();
+        rounds[0].set_msg_in_quota(quota);
+
+        // party 1 spams chunks of a message it never completes
+        let chunks = split_message(&spam_bcast, 30).unwrap();
+        assert!(chunks.len() > 2);
+        let (from_0, from_1) = (TypedUsize::from_usize(0), TypedUsize::from_usize(1));
+        let round = &mut rounds[0];
+        round.msg_in(from_0, &honest_bcast).unwrap();
+        for chunk in chunks.iter().take(2) {
+            round.msg_in(from_1, chunk).unwrap();
+        }
+
+        match rounds.remove(0).execute_next_round().unwrap() {
+            Protocol::Done(Err(faulters)) => {
+                assert!(faulters.get(from_0).unwrap().is_none());
+                assert!(matches!(
+                    faulters.get(from_1).unwrap(),
+                    Some(Fault::CorruptedMessage)
+                ));
+            }
+            _ => panic!("expect party 1 to be accused"),
+        }
+    }
+}
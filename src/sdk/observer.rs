@@ -0,0 +1,145 @@
+use alloc::boxed::Box;
+
+use crate::collections::TypedUsize;
+
+use super::protocol::Fault;
+
+/// Callbacks on the progress of a [Protocol](super::api::Protocol), eg. for audit logging.
+/// Register with [Round::set_observer](super::api::Round::set_observer).
+///
+/// Rounds are numbered from 0 and parties are identified by party id, as in [Round::msg_in](super::api::Round::msg_in).
+/// All methods do nothing by default.
+pub trait Observer<P>: Send + Sync {
+    fn round_started(&mut self, _round: usize) {}
+
+    /// A message from `from` was stored for processing.
+    /// Chunks of a split message are reported once, when the message is reassembled.
+    fn msg_accepted(&mut self, _round: usize, _from: TypedUsize<P>) {}
+
+    /// A message from `from` was discarded and `from` will be accused of [Fault::CorruptedMessage].
+    fn msg_rejected(&mut self, _round: usize, _from: TypedUsize<P>) {}
+
+    /// `faulter` is blamed in the output of the protocol.
+    /// Called once per faulter, before [Observer::protocol_done].
+    fn fault_recorded(&mut self, _round: usize, _faulter: TypedUsize<P>, _fault: &Fault) {}
+
+    /// The protocol is done. `success` is `false` if there are faulters.
+    fn protocol_done(&mut self, _round: usize, _success: bool) {}
+}
+
+pub(super) type BoxObserver<P> = Box<dyn Observer<P>>;
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, vec, vec::Vec};
+    use std::sync::{Arc, Mutex};
+
+    use super::Observer;
+    use crate::{
+        collections::{TypedUsize, VecMap},
+        crypto_tools::rng::dummy_secret_recovery_key,
+        multisig::keygen::{new_keygen, KeygenPartyId, KeygenPartyShareCounts},
+        sdk::api::{Fault, Protocol},
+    };
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        RoundStarted(usize),
+        Accepted(usize, usize),
+        Rejected(usize, usize),
+        Fault(usize, usize, Fault),
+        Done(usize, bool),
+    }
+
+    struct Recorder(Arc<Mutex<Vec<Event>>>);
+
+    impl Observer<KeygenPartyId> for Recorder {
+        fn round_started(&mut self, round: usize) {
+            self.0.lock().unwrap().push(Event::RoundStarted(round));
+        }
+        fn msg_accepted(&mut self, round: usize, from: TypedUsize<KeygenPartyId>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(Event::Accepted(round, from.as_usize()));
+        }
+        fn msg_rejected(&mut self, round: usize, from: TypedUsize<KeygenPartyId>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(Event::Rejected(round, from.as_usize()));
+        }
+        fn fault_recorded(
+            &mut self,
+            round: usize,
+            faulter: TypedUsize<KeygenPartyId>,
+            fault: &Fault,
+        ) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(Event::Fault(round, faulter.as_usize(), fault.clone()));
+        }
+        fn protocol_done(&mut self, round: usize, success: bool) {
+            self.0.lock().unwrap().push(Event::Done(round, success));
+        }
+    }
+
+    #[test]
+    fn observe_keygen() {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 1]).unwrap();
+        let mut parties: VecMap<KeygenPartyId, _> = party_share_counts
+            .iter()
+            .map(|(party_id, _)| {
+                match new_keygen(
+                    party_share_counts.clone(),
+                    1,
+                    party_id,
+                    0,
+                    &dummy_secret_recovery_key(party_id.as_usize()),
+                    b"observer",
+                )
+                .unwrap()
+                {
+                    Protocol::NotDone(round) => round,
+                    Protocol::Done(_) => panic!("`new_keygen` returned a `Done` protocol"),
+                }
+            })
+            .collect();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let me = TypedUsize::from_usize(0);
+        let peer = TypedUsize::from_usize(1);
+        parties
+            .get_mut(me)
+            .unwrap()
+            .set_observer(Box::new(Recorder(events.clone())));
+
+        let bcasts: Vec<_> = parties
+            .iter()
+            .map(|(_, party)| party.bcast_out().unwrap().clone())
+            .collect();
+        let party = parties.get_mut(me).unwrap();
+        party.msg_in(me, &bcasts[0]).unwrap();
+        party.msg_in(peer, &bcasts[1]).unwrap();
+        party.msg_in(peer, &bcasts[1]).unwrap(); // duplicate
+
+        let party = parties.into_iter().next().unwrap().1;
+        assert!(matches!(
+            party.execute_next_round().unwrap(),
+            Protocol::Done(Err(_))
+        ));
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                Event::RoundStarted(0),
+                Event::Accepted(0, 0),
+                Event::Accepted(0, 1),
+                Event::Rejected(0, 1),
+                Event::Fault(1, 1, Fault::CorruptedMessage),
+                Event::Done(1, false),
+            ]
+        );
+    }
+}
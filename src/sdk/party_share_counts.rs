@@ -2,7 +2,7 @@ use alloc::vec::Vec;
 
 use crate::{
     collections::{Subset, TypedUsize, VecMap, VecMapIter},
-    sdk::api::{TofnFatal, TofnResult, MAX_PARTY_SHARE_COUNT, MAX_TOTAL_SHARE_COUNT},
+    sdk::api::{ShareIndex, TofnFatal, TofnResult, MAX_PARTY_SHARE_COUNT, MAX_TOTAL_SHARE_COUNT},
 };
 use serde::{Deserialize, Serialize};
 use tracing::error;
@@ -68,6 +68,10 @@ impl<P> PartyShareCounts<P> {
         );
         Err(TofnFatal)
     }
+    /// Constant-time lookups for when many are needed, see [ShareIndex]
+    pub fn share_index<K>(&self) -> ShareIndex<P, K> {
+        ShareIndex::new(self)
+    }
     pub fn share_to_party_id<K>(&self, share_id: TypedUsize<K>) -> TofnResult<TypedUsize<P>> {
         Ok(self.share_to_party_subshare_ids(share_id)?.0)
     }
@@ -152,6 +156,21 @@ impl<P> PartyShareCounts<P> {
     }
 }
 
+/// Arbitrary instances are kept small so that they are cheap to use as protocol inputs.
+#[cfg(feature = "arbitrary")]
+impl<'a, P> arbitrary::Arbitrary<'a> for PartyShareCounts<P> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        const MAX_PARTY_COUNT: usize = 5;
+        const MAX_SHARE_COUNT: usize = 3;
+
+        let party_count = u.int_in_range(1..=MAX_PARTY_COUNT)?;
+        let party_share_counts = (0..party_count)
+            .map(|_| u.int_in_range(1..=MAX_SHARE_COUNT))
+            .collect::<arbitrary::Result<Vec<_>>>()?;
+        Self::from_vec(party_share_counts).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
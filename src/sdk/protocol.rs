@@ -1,16 +1,42 @@
+use alloc::vec::Vec;
+use core::fmt;
+
 use super::{
-    api::TofnResult, party_share_counts::PartyShareCounts, protocol_builder::ProtocolBuilder,
-    protocol_info::ProtocolInfoDeluxe, round::Round,
+    api::{BytesVec, ErrorContext, TofnResult, TofnResultExt},
+    party_share_counts::PartyShareCounts,
+    protocol_builder::ProtocolBuilder,
+    protocol_info::ProtocolInfoDeluxe,
+    round::{Redacted, Round},
 };
 use crate::collections::{FillVecMap, TypedUsize};
 use serde::{Deserialize, Serialize};
 
 #[allow(clippy::large_enum_variant)]
-pub enum Protocol<F, K, P, const MAX_MSG_IN_LEN: usize> {
-    NotDone(Round<F, K, P, MAX_MSG_IN_LEN>),
+pub enum Protocol<F, K, P> {
+    NotDone(Round<F, K, P>),
     Done(ProtocolOutput<F, P>),
 }
 
+/// The output of a successful protocol is `[REDACTED]`: it may be a secret key share.
+/// See [Round]'s `Debug` for a protocol that is not done.
+impl<F, K, P> fmt::Debug for Protocol<F, K, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotDone(round) => f.debug_tuple("NotDone").field(round).finish(),
+            Self::Done(Ok(_)) => f.debug_tuple("Done").field(&Ok::<_, ()>(Redacted)).finish(),
+            Self::Done(Err(faulters)) => {
+                let faulters: Vec<(usize, &Fault)> = faulters
+                    .iter_some()
+                    .map(|(party_id, fault)| (party_id.as_usize(), fault))
+                    .collect();
+                f.debug_tuple("Done")
+                    .field(&Err::<(), _>(faulters))
+                    .finish()
+            }
+        }
+    }
+}
+
 pub type ProtocolOutput<F, P> = Result<F, ProtocolFaulters<P>>;
 pub type ProtocolFaulters<P> = FillVecMap<P, Fault>; // party (not subhsare) faults
 
@@ -19,14 +45,51 @@ pub enum Fault {
     MissingMessage,
     CorruptedMessage,
     ProtocolFault,
+    /// A [ProtocolFault](Fault::ProtocolFault) in which a share's MtA proof failed to verify
+    BadMtaProof(MtaFailure),
+    /// A share's hash of the messages of previous rounds differs from mine,
+    /// see [ProtocolInfo::transcript_hash](crate::sdk::implementer_api::ProtocolInfo::transcript_hash).
+    /// Not proof of misbehaviour: a relay that delivers different messages to different parties causes it too.
+    TranscriptMismatch,
+}
+
+/// Which MtA proof from `prover` to `verifier` failed to verify, and on which ciphertexts.
+///
+/// Compare the hashes with those of the ciphertexts the prover sent:
+/// a mismatch points to corruption in transit or a serialization bug rather than a cheating prover.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MtaFailure {
+    /// Share id of the faulter in the protocol
+    pub prover: usize,
+    pub verifier: usize,
+    pub proof: MtaProof,
+    /// SHA-256 of the serialized ciphertext the verifier sent to start the MtA
+    pub verifier_ciphertext_hash: [u8; 32],
+    /// SHA-256 of the serialized ciphertext the prover replied with
+    pub prover_ciphertext_hash: [u8; 32],
+    /// Serialized [MtaEvidence](crate::gg20::verify_evidence::MtaEvidence),
+    /// checked by [verify_mta_failure](crate::gg20::verify_evidence::verify_mta_failure)
+    pub evidence: BytesVec,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MtaProof {
+    MtA,
+    /// MtA with check
+    MtAwc,
 }
 
 // not an associated function of `Protocol`
 // because we want to expose it only in the implementer api
-pub fn new_protocol<F, K, P, const MAX_MSG_IN_LEN: usize>(
+/// `max_msg_in_len` is the maximum byte length of an incoming message, see [Round::set_max_msg_in_len].
+/// It must be positive and at most 1 MB.
+pub fn new_protocol<F, K, P>(
     party_share_counts: PartyShareCounts<P>,
     share_id: TypedUsize<K>,
+    max_msg_in_len: usize,
     first_round: ProtocolBuilder<F, K>,
-) -> TofnResult<Protocol<F, K, P, MAX_MSG_IN_LEN>> {
-    first_round.build(ProtocolInfoDeluxe::new(party_share_counts, share_id)?)
+) -> TofnResult<Protocol<F, K, P>> {
+    ProtocolInfoDeluxe::new(party_share_counts, share_id, max_msg_in_len)
+        .and_then(|info| first_round.build(info, Vec::new()))
+        .context(ErrorContext::new(module_path!(), "new_protocol"))
 }
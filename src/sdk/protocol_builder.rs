@@ -1,13 +1,17 @@
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
 
-use crate::collections::{FillVecMap, HoleVecMap};
+use crate::collections::{FillP2ps, FillVecMap, HoleVecMap};
 
 use super::{
     api::{BytesVec, TofnResult},
     executer::ExecuterRaw,
+    metrics,
     protocol::{Fault, Protocol},
-    protocol_info::ProtocolInfoDeluxe,
+    protocol_info::{ProtocolInfo, ProtocolInfoDeluxe},
     round::Round,
+    round_graph::RoundDescription,
+    spans,
+    wire_bytes::ExpectedMsgTypes,
 };
 
 pub enum ProtocolBuilder<F, K> {
@@ -35,21 +39,175 @@ impl<F, K> RoundBuilder<F, K> {
     }
 }
 
+impl<F: 'static, K: 'static> ProtocolBuilder<F, K> {
+    /// Map the final output of the protocol with `map`.
+    /// Lets several protocols with different outputs share the same rounds.
+    pub fn map_output<G: 'static>(
+        self,
+        map: fn(F) -> TofnResult<G>,
+    ) -> TofnResult<ProtocolBuilder<G, K>> {
+        Ok(match self {
+            Self::NotDone(builder) => ProtocolBuilder::NotDone(RoundBuilder::new(
+                Box::new(MapOutput {
+                    round: builder.round,
+                    map,
+                }),
+                builder.bcast_out,
+                builder.p2ps_out,
+            )),
+            Self::Done(Ok(output)) => ProtocolBuilder::Done(Ok(map(output)?)),
+            Self::Done(Err(faulters)) => ProtocolBuilder::Done(Err(faulters)),
+        })
+    }
+
+    /// Chain a second protocol onto this one.
+    /// Once this protocol is done, its output is passed to `next`
+    /// and the rounds of the resulting protocol run back-to-back in the same session.
+    /// Both protocols must use the same share ids.
+    /// Faulters of this protocol end the whole chain.
+    pub fn and_then<G: 'static, N>(self, next: N) -> TofnResult<ProtocolBuilder<G, K>>
+    where
+        N: FnOnce(F) -> TofnResult<ProtocolBuilder<G, K>> + Send + Sync + 'static,
+    {
+        self.and_then_boxed(Box::new(next))
+    }
+
+    fn and_then_boxed<G: 'static>(
+        self,
+        next: NextProtocol<F, G, K>,
+    ) -> TofnResult<ProtocolBuilder<G, K>> {
+        Ok(match self {
+            Self::NotDone(builder) => ProtocolBuilder::NotDone(RoundBuilder::new(
+                Box::new(AndThen {
+                    round: builder.round,
+                    next,
+                }),
+                builder.bcast_out,
+                builder.p2ps_out,
+            )),
+            Self::Done(Ok(output)) => next(output)?,
+            Self::Done(Err(faulters)) => ProtocolBuilder::Done(Err(faulters)),
+        })
+    }
+}
+
 impl<F, K> ProtocolBuilder<F, K> {
-    pub(super) fn build<P, const MAX_MSG_IN_LEN: usize>(
+    /// `spare_buffers` are recycled to hold the outgoing messages of the new round, if any.
+    pub(super) fn build<P>(
         self,
-        info: ProtocolInfoDeluxe<K, P>,
-    ) -> TofnResult<Protocol<F, K, P, MAX_MSG_IN_LEN>> {
+        mut info: ProtocolInfoDeluxe<K, P>,
+        spare_buffers: Vec<BytesVec>,
+    ) -> TofnResult<Protocol<F, K, P>> {
         Ok(match self {
             Self::NotDone(builder) => Protocol::NotDone(Round::new(
                 builder.round,
                 info,
                 builder.bcast_out,
                 builder.p2ps_out,
+                spare_buffers,
             )?),
-            Self::Done(output) => Protocol::Done(info.share_to_party_faults(output)?),
+            Self::Done(output) => {
+                let output = info.share_to_party_faults(output)?;
+                info.span().in_scope(|| spans::protocol_done(&output));
+                metrics::protocol_done(&output);
+                info.notify(|observer, round| {
+                    if let Err(faulters) = &output {
+                        for (faulter, fault) in faulters.iter_some() {
+                            observer.fault_recorded(round, faulter, fault);
+                        }
+                    }
+                    observer.protocol_done(round, output.is_ok());
+                });
+                Protocol::Done(output)
+            }
         })
     }
 }
 
+/// A round whose final output is mapped by [ProtocolBuilder::map_output]
+struct MapOutput<F, G, K> {
+    round: Box<dyn ExecuterRaw<FinalOutput = F, Index = K>>,
+    map: fn(F) -> TofnResult<G>,
+}
+
+impl<F: 'static, G: 'static, K: 'static> ExecuterRaw for MapOutput<F, G, K> {
+    type FinalOutput = G;
+    type Index = K;
+
+    fn execute_raw(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, BytesVec>,
+        p2ps_in: FillP2ps<Self::Index, BytesVec>,
+        expected_msg_types: FillVecMap<Self::Index, ExpectedMsgTypes>,
+        faulters: FillVecMap<Self::Index, Fault>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        self.round
+            .execute_raw(info, bcasts_in, p2ps_in, expected_msg_types, faulters)?
+            .map_output(self.map)
+    }
+
+    fn description(&self) -> RoundDescription {
+        self.round.description()
+    }
+
+    #[cfg(feature = "wire-spec")]
+    fn trace_msgs(
+        &self,
+        bcast: Option<&[u8]>,
+        p2p: Option<&[u8]>,
+    ) -> TofnResult<crate::wire_spec::RoundSpec> {
+        self.round.trace_msgs(bcast, p2p)
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self.round.as_any()
+    }
+}
+
+type NextProtocol<F, G, K> = Box<dyn FnOnce(F) -> TofnResult<ProtocolBuilder<G, K>> + Send + Sync>;
+
+/// A round of the first protocol in a chain built by [ProtocolBuilder::and_then]
+struct AndThen<F, G, K> {
+    round: Box<dyn ExecuterRaw<FinalOutput = F, Index = K>>,
+    next: NextProtocol<F, G, K>,
+}
+
+impl<F: 'static, G: 'static, K: 'static> ExecuterRaw for AndThen<F, G, K> {
+    type FinalOutput = G;
+    type Index = K;
+
+    fn execute_raw(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, BytesVec>,
+        p2ps_in: FillP2ps<Self::Index, BytesVec>,
+        expected_msg_types: FillVecMap<Self::Index, ExpectedMsgTypes>,
+        faulters: FillVecMap<Self::Index, Fault>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        self.round
+            .execute_raw(info, bcasts_in, p2ps_in, expected_msg_types, faulters)?
+            .and_then_boxed(self.next)
+    }
+
+    fn description(&self) -> RoundDescription {
+        self.round.description()
+    }
+
+    #[cfg(feature = "wire-spec")]
+    fn trace_msgs(
+        &self,
+        bcast: Option<&[u8]>,
+        p2p: Option<&[u8]>,
+    ) -> TofnResult<crate::wire_spec::RoundSpec> {
+        self.round.trace_msgs(bcast, p2p)
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self.round.as_any()
+    }
+}
+
 pub type ProtocolBuilderOutput<F, K> = Result<F, FillVecMap<K, Fault>>; // subshare faults
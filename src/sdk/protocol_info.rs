@@ -1,16 +1,41 @@
 use crate::{
-    collections::{FillHoleVecMap, FillVecMap, TypedUsize},
-    sdk::{api::TofnResult, protocol::ProtocolOutput, protocol_builder::ProtocolBuilderOutput},
+    collections::{FillHoleVecMap, FillP2ps, FillVecMap, HoleVecMap, TypedUsize},
+    sdk::{
+        api::{BytesVec, TofnFatal, TofnResult, TranscriptHash, TranscriptHasher},
+        protocol::ProtocolOutput,
+        protocol_builder::ProtocolBuilderOutput,
+        wire_bytes::{serialize, MAX_MSG_LEN},
+    },
 };
 
-use super::party_share_counts::PartyShareCounts;
+use super::{
+    deadlines::RoundDeadlines,
+    msg_in_quota::MsgInQuota,
+    observer::{BoxObserver, Observer},
+    party_share_counts::PartyShareCounts,
+    share_index::ShareIndex,
+    spans,
+};
+use tracing::{error, Span};
+
+#[cfg(feature = "malicious")]
+use super::round::malicious::MsgFault;
 
 // party-level info persisted throughout the protocol ("deluxe" depends on `P`)
 pub struct ProtocolInfoDeluxe<K, P> {
     party_share_counts: PartyShareCounts<P>,
+    share_index: ShareIndex<P, K>,
     party_id: TypedUsize<P>,
     share_info: ProtocolInfo<K>,
     round: usize,
+    max_msg_in_len: usize,
+    observer: Option<BoxObserver<P>>,
+    deadlines: RoundDeadlines,
+    msg_in_quota: MsgInQuota,
+    memory_budget: Option<usize>,
+    #[cfg(feature = "malicious")]
+    msg_fault: Option<(usize, MsgFault<K>)>, // (round, fault)
+    span: Span,
 }
 
 // share-level info persisted throughout the protocol
@@ -18,6 +43,7 @@ pub struct ProtocolInfoDeluxe<K, P> {
 pub struct ProtocolInfo<K> {
     share_count: usize,
     share_id: TypedUsize<K>,
+    transcript: TranscriptHasher<K>,
 }
 
 impl<K> ProtocolInfo<K> {
@@ -29,6 +55,15 @@ impl<K> ProtocolInfo<K> {
         self.share_id
     }
 
+    /// Hash of the messages accepted in all previous rounds, see [TranscriptHasher].
+    /// Messages of accused parties are not accepted.
+    ///
+    /// Every party receives every message, so honest parties that see the same messages have the same hash.
+    /// Compare hashes to detect a relay that delivered different messages to different parties.
+    pub fn transcript_hash(&self) -> TranscriptHash {
+        self.transcript.hash()
+    }
+
     pub fn new_fillholevecmap<V>(&self) -> TofnResult<FillHoleVecMap<K, V>> {
         FillHoleVecMap::with_size(self.share_count, self.share_id)
     }
@@ -36,6 +71,24 @@ impl<K> ProtocolInfo<K> {
     pub fn new_fillvecmap<V>(&self) -> FillVecMap<K, V> {
         FillVecMap::with_size(self.share_count)
     }
+
+    /// A value for each peer from `f`, with a hole at my id
+    pub fn new_p2ps<V, F>(&self, f: F) -> TofnResult<HoleVecMap<K, V>>
+    where
+        F: FnMut(TypedUsize<K>) -> TofnResult<V>,
+    {
+        HoleVecMap::from_fn(self.share_count, self.share_id, f)
+    }
+
+    /// As [ProtocolInfo::new_p2ps] but serialize each payload,
+    /// ready for [RoundBuilder](super::protocol_builder::RoundBuilder)
+    pub fn new_p2ps_out<T, F>(&self, mut f: F) -> TofnResult<HoleVecMap<K, BytesVec>>
+    where
+        T: serde::Serialize,
+        F: FnMut(TypedUsize<K>) -> TofnResult<T>,
+    {
+        self.new_p2ps(|peer_id| serialize(&f(peer_id)?))
+    }
 }
 
 impl<K, P> ProtocolInfoDeluxe<K, P> {
@@ -51,6 +104,10 @@ impl<K, P> ProtocolInfoDeluxe<K, P> {
         &self.party_share_counts
     }
 
+    pub fn share_index(&self) -> &ShareIndex<P, K> {
+        &self.share_index
+    }
+
     pub fn round(&self) -> usize {
         self.round
     }
@@ -59,22 +116,127 @@ impl<K, P> ProtocolInfoDeluxe<K, P> {
         self.round += 1
     }
 
+    /// Add the messages accepted in `round` to the transcript, in canonical order
+    pub(super) fn record_transcript(
+        &mut self,
+        round: usize,
+        bcasts_in: &FillVecMap<K, BytesVec>,
+        p2ps_in: &FillP2ps<K, BytesVec>,
+    ) -> TofnResult<()> {
+        let transcript = &mut self.share_info.transcript;
+        for (from, bcast) in bcasts_in.iter() {
+            if let Some(bcast) = bcast {
+                transcript.add_msg(round, from, None, bcast)?;
+            }
+            for (to, p2p) in p2ps_in.iter_from(from)? {
+                if let Some(p2p) = p2p {
+                    transcript.add_msg(round, from, Some(to), p2p)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The `tofn_protocol` span, see [spans](super::spans)
+    pub(super) fn span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn max_msg_in_len(&self) -> usize {
+        self.max_msg_in_len
+    }
+
+    /// Fail unless `max_msg_in_len` is positive and within the limit of the wire format
+    pub(super) fn set_max_msg_in_len(&mut self, max_msg_in_len: usize) -> TofnResult<()> {
+        if max_msg_in_len == 0 || max_msg_in_len as u64 > MAX_MSG_LEN {
+            error!(
+                "max_msg_in_len {} is not in [1, {}]",
+                max_msg_in_len, MAX_MSG_LEN
+            );
+            return Err(TofnFatal);
+        }
+        self.max_msg_in_len = max_msg_in_len;
+        Ok(())
+    }
+
+    pub(super) fn deadlines(&self) -> &RoundDeadlines {
+        &self.deadlines
+    }
+
+    pub(super) fn set_deadlines(&mut self, deadlines: RoundDeadlines) {
+        self.deadlines = deadlines;
+    }
+
+    pub(super) fn msg_in_quota(&self) -> MsgInQuota {
+        self.msg_in_quota
+    }
+
+    pub(super) fn set_msg_in_quota(&mut self, msg_in_quota: MsgInQuota) {
+        self.msg_in_quota = msg_in_quota;
+    }
+
+    pub(super) fn memory_budget(&self) -> Option<usize> {
+        self.memory_budget
+    }
+
+    pub(super) fn set_memory_budget(&mut self, memory_budget: Option<usize>) {
+        self.memory_budget = memory_budget;
+    }
+
+    /// The fault of this share in sending its messages of the current round, if any
+    #[cfg(feature = "malicious")]
+    pub(super) fn msg_fault(&self) -> Option<MsgFault<K>> {
+        self.msg_fault
+            .filter(|(round, _)| *round == self.round)
+            .map(|(_, fault)| fault)
+    }
+
+    #[cfg(feature = "malicious")]
+    pub(super) fn set_msg_fault(&mut self, round: usize, msg_fault: MsgFault<K>) {
+        self.msg_fault = Some((round, msg_fault));
+    }
+
+    pub(super) fn set_observer(&mut self, observer: BoxObserver<P>) {
+        self.observer = Some(observer);
+    }
+
+    /// Call `notify` on the registered [Observer], if any
+    pub(super) fn notify(&mut self, notify: impl FnOnce(&mut dyn Observer<P>, usize)) {
+        if let Some(observer) = self.observer.as_mut() {
+            notify(observer.as_mut(), self.round);
+        }
+    }
+
     // private methods
     pub(super) fn new(
         party_share_counts: PartyShareCounts<P>,
         share_id: TypedUsize<K>,
+        max_msg_in_len: usize,
     ) -> TofnResult<Self> {
-        let party_id = party_share_counts.share_to_party_id(share_id)?;
+        let share_index = party_share_counts.share_index();
+        let party_id = share_index.share_to_party_id(share_id)?;
         let share_count = party_share_counts.total_share_count();
-        Ok(Self {
+        let mut info = Self {
             party_share_counts,
+            share_index,
             party_id,
             share_info: ProtocolInfo {
                 share_count,
                 share_id,
+                transcript: TranscriptHasher::new(),
             },
             round: 0,
-        })
+            max_msg_in_len: 0,
+            observer: None,
+            deadlines: RoundDeadlines::default(),
+            msg_in_quota: MsgInQuota::default(),
+            memory_budget: None,
+            #[cfg(feature = "malicious")]
+            msg_fault: None,
+            span: spans::protocol_span(party_id.as_usize(), share_id.as_usize()),
+        };
+        info.set_max_msg_in_len(max_msg_in_len)?;
+        Ok(info)
     }
 
     pub(super) fn share_to_party_faults<F>(
@@ -89,10 +251,8 @@ impl<K, P> ProtocolInfoDeluxe<K, P> {
                 // TODO how to choose among multiple faults by one party?
                 // For now just overwrite and use the final fault
                 for (share_id, share_fault) in share_faulters.into_iter_some() {
-                    party_faulters.set(
-                        self.party_share_counts.share_to_party_id(share_id)?,
-                        share_fault,
-                    )?;
+                    party_faulters
+                        .set(self.share_index.share_to_party_id(share_id)?, share_fault)?;
                 }
                 Err(party_faulters)
             }
@@ -0,0 +1,282 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::api::{BytesVec, Protocol, Round, TofnFatal, TofnResult};
+use crate::collections::TypedUsize;
+
+/// Everything one party sent and received, for [replay].
+///
+/// [record_round](Transcript::record_round) each [Round] as it becomes current,
+/// [record_msg_in](Transcript::record_msg_in) each message passed to [Round::msg_in]
+/// and call [record_done](Transcript::record_done) when the protocol is done.
+///
+/// A transcript contains the messages of the party, not its secrets,
+/// but store it with care: together with the party's inputs it reveals the party's state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript<K, P> {
+    rounds: Vec<RoundTranscript<K, P>>,
+    done: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RoundTranscript<K, P> {
+    bcast_out: Option<BytesVec>,
+    p2ps_out: Option<Vec<(TypedUsize<K>, BytesVec)>>,
+    msgs_in: Vec<(TypedUsize<P>, BytesVec)>,
+}
+
+/// Where a replayed party first sent something other than the recorded party
+#[derive(Debug, Clone, PartialEq)]
+pub enum Divergence<K> {
+    Bcast {
+        round: usize,
+    },
+    P2p {
+        round: usize,
+        to: TypedUsize<K>,
+    },
+    /// One of the parties is done before the other
+    RoundCount {
+        recorded: usize,
+        replayed: usize,
+    },
+}
+
+/// Output of [replay]
+pub struct Replay<F, K, P> {
+    /// The replayed party, stopped at the first divergence if any
+    pub protocol: Protocol<F, K, P>,
+    pub divergence: Option<Divergence<K>>,
+}
+
+impl<K, P> Default for Transcript<K, P> {
+    fn default() -> Self {
+        Self {
+            rounds: Vec::new(),
+            done: false,
+        }
+    }
+}
+
+impl<K, P> Transcript<K, P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the outgoing messages of `round`, which has just become current
+    pub fn record_round<F>(&mut self, round: &Round<F, K, P>) {
+        self.rounds.push(RoundTranscript {
+            bcast_out: round.bcast_out().cloned(),
+            p2ps_out: round
+                .p2ps_out()
+                .map(|p2ps| p2ps.iter().map(|(to, bytes)| (to, bytes.clone())).collect()),
+            msgs_in: Vec::new(),
+        });
+    }
+
+    /// Record a message passed to [Round::msg_in] of the current round
+    pub fn record_msg_in(&mut self, from: TypedUsize<P>, bytes: &[u8]) -> TofnResult<()> {
+        match self.rounds.last_mut() {
+            Some(round) => {
+                round.msgs_in.push((from, bytes.to_vec()));
+                Ok(())
+            }
+            None => {
+                error!("record_msg_in called before record_round");
+                Err(TofnFatal)
+            }
+        }
+    }
+
+    /// Record that the last round was executed and the protocol is done
+    pub fn record_done(&mut self) {
+        self.done = true;
+    }
+
+    pub fn round_count(&self) -> usize {
+        self.rounds.len()
+    }
+}
+
+/// Re-execute the rounds of a recorded party, feeding `protocol` the recorded incoming messages
+/// and byte-comparing its outgoing messages against the recorded ones.
+///
+/// `protocol` must be a fresh party constructed with the same inputs as the recorded one,
+/// including any secret recovery key, session nonce or session id from which it derives its randomness.
+/// Randomness sampled at runtime differs between runs, eg. Paillier encryption randomness in gg20,
+/// so only protocols that derive all their randomness from their inputs replay without divergence.
+pub fn replay<F, K, P>(
+    protocol: Protocol<F, K, P>,
+    transcript: &Transcript<K, P>,
+) -> TofnResult<Replay<F, K, P>> {
+    let recorded = transcript.rounds.len();
+    let mut protocol = protocol;
+
+    for (index, recorded_round) in transcript.rounds.iter().enumerate() {
+        let mut round = match protocol {
+            Protocol::NotDone(round) => round,
+            Protocol::Done(_) => {
+                return Ok(diverged(
+                    protocol,
+                    Divergence::RoundCount {
+                        recorded,
+                        replayed: index,
+                    },
+                ))
+            }
+        };
+
+        if let Some(divergence) = compare(index, &round, recorded_round) {
+            return Ok(diverged(Protocol::NotDone(round), divergence));
+        }
+
+        for (from, bytes) in recorded_round.msgs_in.iter() {
+            round.msg_in(*from, bytes)?;
+        }
+
+        // the last recorded round was executed only if the recorded party is done
+        if index + 1 == recorded && !transcript.done {
+            return Ok(Replay {
+                protocol: Protocol::NotDone(round),
+                divergence: None,
+            });
+        }
+        protocol = round.execute_next_round()?;
+    }
+
+    if let Protocol::NotDone(_) = protocol {
+        if transcript.done {
+            return Ok(diverged(
+                protocol,
+                Divergence::RoundCount {
+                    recorded,
+                    replayed: recorded + 1,
+                },
+            ));
+        }
+    }
+
+    Ok(Replay {
+        protocol,
+        divergence: None,
+    })
+}
+
+fn diverged<F, K, P>(protocol: Protocol<F, K, P>, divergence: Divergence<K>) -> Replay<F, K, P> {
+    Replay {
+        protocol,
+        divergence: Some(divergence),
+    }
+}
+
+fn compare<F, K, P>(
+    index: usize,
+    round: &Round<F, K, P>,
+    recorded: &RoundTranscript<K, P>,
+) -> Option<Divergence<K>> {
+    if round.bcast_out() != recorded.bcast_out.as_ref() {
+        return Some(Divergence::Bcast { round: index });
+    }
+
+    match (round.p2ps_out(), &recorded.p2ps_out) {
+        (None, None) => None,
+        (Some(p2ps), Some(recorded_p2ps)) => {
+            if p2ps.len() != recorded_p2ps.len() + 1 {
+                return Some(Divergence::P2p {
+                    round: index,
+                    to: p2ps.get_hole(),
+                });
+            }
+            p2ps.iter()
+                .zip(recorded_p2ps.iter())
+                .find(|((to, bytes), (recorded_to, recorded_bytes))| {
+                    to.as_usize() != recorded_to.as_usize() || bytes != &recorded_bytes
+                })
+                .map(|((to, _), _)| Divergence::P2p { round: index, to })
+        }
+        (Some(p2ps), None) => Some(Divergence::P2p {
+            round: index,
+            to: p2ps.get_hole(),
+        }),
+        (None, Some(recorded_p2ps)) => Some(Divergence::P2p {
+            round: index,
+            to: recorded_p2ps
+                .first()
+                .map_or(TypedUsize::from_usize(0), |(to, _)| *to),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{replay, Divergence, Transcript};
+    use crate::{
+        collections::{TypedUsize, VecMap},
+        crypto_tools::rng::dummy_secret_recovery_key,
+        multisig::keygen::{
+            new_keygen, KeygenPartyId, KeygenPartyShareCounts, KeygenProtocol, KeygenShareId,
+        },
+        sdk::api::Protocol,
+    };
+
+    fn keygen(party_id: usize, session_nonce: &[u8]) -> KeygenProtocol {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 1]).unwrap();
+        new_keygen(
+            party_share_counts,
+            1,
+            TypedUsize::from_usize(party_id),
+            0,
+            &dummy_secret_recovery_key(party_id),
+            session_nonce,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn multisig_keygen() {
+        // record party 0 while executing keygen
+        let mut transcript = Transcript::<KeygenShareId, KeygenPartyId>::new();
+        let mut rounds: VecMap<KeygenShareId, _> = (0..2)
+            .map(|i| match keygen(i, b"replay") {
+                Protocol::NotDone(round) => round,
+                Protocol::Done(_) => panic!("keygen done too early"),
+            })
+            .collect();
+        transcript.record_round(rounds.iter().next().unwrap().1);
+
+        let bcasts: alloc::vec::Vec<_> = rounds
+            .iter()
+            .map(|(_, round)| round.bcast_out().unwrap().clone())
+            .collect();
+        for (i, bytes) in bcasts.iter().enumerate() {
+            let from = TypedUsize::from_usize(i);
+            for (_, round) in rounds.iter_mut() {
+                round.msg_in(from, bytes).unwrap();
+            }
+            transcript.record_msg_in(from, bytes).unwrap();
+        }
+        let outputs: alloc::vec::Vec<_> = rounds
+            .into_iter()
+            .map(|(_, round)| match round.execute_next_round().unwrap() {
+                Protocol::Done(Ok(output)) => output,
+                _ => panic!("keygen failed"),
+            })
+            .collect();
+        transcript.record_done();
+
+        // an identical party replays to the same output
+        let replayed = replay(keygen(0, b"replay"), &transcript).unwrap();
+        assert_eq!(replayed.divergence, None);
+        match replayed.protocol {
+            Protocol::Done(Ok(output)) => assert_eq!(output, outputs[0]),
+            _ => panic!("replay failed"),
+        }
+
+        // a party with different randomness diverges in its first message
+        let replayed = replay(keygen(0, b"other nonce"), &transcript).unwrap();
+        assert_eq!(replayed.divergence, Some(Divergence::Bcast { round: 0 }));
+        assert!(matches!(replayed.protocol, Protocol::NotDone(_)));
+    }
+}
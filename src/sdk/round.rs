@@ -1,26 +1,34 @@
 use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
+use core::{fmt, time::Duration};
 
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, warn, Span};
 
 use crate::{
-    collections::{zip3, FillP2ps, FillVecMap, HoleVecMap, TypedUsize},
+    collections::{zip3, FillP2ps, FillVecMap, HoleVecMap, TypedUsize, VecMap},
     sdk::{
-        api::{BytesVec, Fault, ProtocolFaulters, TofnFatal, TofnResult},
+        api::{
+            BytesVec, ErrorContext, Fault, ProtocolFaulters, TofnFatal, TofnResult, TofnResultExt,
+        },
         wire_bytes::ExpectedMsgTypes::{self, *},
     },
 };
 
 use super::{
-    api::Protocol,
+    api::{MsgInQuota, Protocol, RoundDeadlines},
     executer::ExecuterRaw,
+    metrics,
+    msg_in_quota::MsgInUsage,
+    observer::Observer,
     protocol_info::ProtocolInfoDeluxe,
-    wire_bytes::{self, MsgType::*, WireBytes},
+    round_graph::RoundDescription,
+    spans,
+    wire_bytes::{self, MsgType::*, WireBytesRef, MIN_CHUNK_LEN},
 };
 
-/// MAX_MSG_IN_LEN is the maximum byte length of messages exchanged during sign.
-/// The sender of a message larger than this maximum will be accused as a faulter.
-pub struct Round<F, K, P, const MAX_MSG_IN_LEN: usize> {
+/// The sender of a message longer than [Round::max_msg_in_len] will be accused as a faulter.
+pub struct Round<F, K, P> {
     info: ProtocolInfoDeluxe<K, P>,
     round: Box<dyn ExecuterRaw<FinalOutput = F, Index = K>>,
     bcast_out: Option<BytesVec>,
@@ -29,10 +37,53 @@ pub struct Round<F, K, P, const MAX_MSG_IN_LEN: usize> {
     p2ps_in: FillP2ps<K, BytesVec>,
     expected_msg_types: FillVecMap<K, ExpectedMsgTypes>,
     msg_in_faulters: ProtocolFaulters<P>,
+    chunks_in: BTreeMap<(usize, Option<usize>), ChunksIn>, // keyed by (from, to)
+    msgs_in_usage: VecMap<P, MsgInUsage>,
+    held_bytes: usize, // message bytes held this round, see [Round::memory_usage]
+    span: Span,
+}
+
+/// A view of a [Round] without its secrets, for supervisors to keep and log.
+/// See [Round::snapshot].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RoundSnapshot {
+    pub round: usize,
+    pub share_id: usize,
+    pub party_id: usize,
+    pub description: RoundDescription,
+    /// The outgoing bcast, which every peer receives anyway
+    pub bcast_out: Option<BytesVec>,
+    /// Byte length of each outgoing p2p, by recipient share id.
+    /// The payloads are omitted: they may be secret to their recipient.
+    pub p2p_out_lens: Option<Vec<(usize, usize)>>,
+    /// Share ids whose bcast was received this round
+    pub bcasts_in: Vec<usize>,
+    /// `(from, to)` share ids of the p2ps received this round
+    pub p2ps_in: Vec<(usize, usize)>,
+    /// Party ids accused by [Round::msg_in] this round
+    pub msg_in_faulters: Vec<usize>,
+    pub memory_usage: usize,
+    pub deadline: Option<Duration>,
+}
+
+/// Stands in for secret fields in `Debug` output
+pub(super) struct Redacted;
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+/// Chunks received so far of a message split by [split_message](wire_bytes::split_message)
+struct ChunksIn {
+    count: usize,
+    len: usize, // total bytes received so far
+    chunks: BTreeMap<usize, BytesVec>,
 }
 
 // api: Round methods for tofn users
-impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
+impl<F, K, P> Round<F, K, P> {
     pub fn bcast_out(&self) -> Option<&BytesVec> {
         self.bcast_out.as_ref()
     }
@@ -43,54 +94,102 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
 
     /// we assume message autenticity
     /// thus, it's a fatal error if `from` is out of bounds
+    ///
+    /// `bytes` may be a whole message or a chunk from [split_message](super::api::split_message)
+    ///
+    /// `msg_in` only checks message metadata and stores the payload,
+    /// so it is cheap enough to call from a transport thread.
+    /// Payloads are deserialized and all proofs are verified in [Round::execute_next_round].
+    ///
+    /// Messages from a party over its [MsgInQuota] are dropped, see [Round::set_msg_in_quota].
+    /// So are messages from a party already accused this round, whose messages are discarded anyway.
+    /// Fail if a message would exceed the memory budget, see [Round::set_memory_budget].
     pub fn msg_in(&mut self, from: TypedUsize<P>, bytes: &[u8]) -> TofnResult<()> {
+        let round = self.info.round();
+        self.msg_in_faulters
+            .get(from)
+            .map(|fault| fault.is_some())
+            .and_then(|accused| {
+                if accused {
+                    Ok(false)
+                } else {
+                    self.charge_msg_in_quota(from, bytes.len())
+                }
+            })
+            .and_then(|accepted| {
+                if accepted {
+                    self.msg_in_inner(from, bytes)
+                } else {
+                    Ok(())
+                }
+            })
+            .context(
+                ErrorContext::new(module_path!(), "msg_in")
+                    .round(round)
+                    .peer(from.as_usize()),
+            )
+    }
+
+    fn msg_in_inner(&mut self, from: TypedUsize<P>, bytes: &[u8]) -> TofnResult<()> {
+        let _span = self.span.clone().entered();
+        spans::msg_in(from.as_usize(), bytes.len());
+
         let share_id = self.info().share_info().my_id();
         let party_id = self.info().party_id();
 
         // guard against large-message attack
-        if bytes.len() > MAX_MSG_IN_LEN {
+        let max_msg_in_len = self.info.max_msg_in_len();
+        if bytes.len() > max_msg_in_len {
             warn!(
                 "peer {} (party {}) says: msg_in bytes length {} exceeds maximum {} from party {}",
                 share_id,
                 party_id,
                 bytes.len(),
-                MAX_MSG_IN_LEN,
+                max_msg_in_len,
                 from
             );
-            self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+            self.msg_in_fault(from, "too_long")?;
             return Ok(());
         }
 
-        // deserialize metadata
-        let bytes_meta: WireBytes<K> = match wire_bytes::decode_message(bytes) {
+        // deserialize metadata, which must be canonically encoded
+        // the payload is copied only if it is stored below
+        let bytes_meta: WireBytesRef<K> = match wire_bytes::decode_message_canonical(bytes) {
             Some(w) => w,
             None => {
                 warn!(
                     "peer {} (party {}) says: msg_in fail to deserialize metadata for msg from party {}",
                     share_id, party_id, from
                 );
-                self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+                self.msg_in_fault(from, "bad_metadata")?;
                 return Ok(());
             }
         };
 
         // verify share_id belongs to this party
-        match self
-            .info
-            .party_share_counts()
-            .share_to_party_id(bytes_meta.from)
-        {
+        match self.info.share_index().share_to_party_id(bytes_meta.from) {
             Ok(from_party_id) if from_party_id == from => (), // happy path
             _ => {
                 warn!(
                     "peer {} (party {}) says: msg_in share id {} does not belong to party {}",
                     share_id, party_id, bytes_meta.from, from
                 );
-                self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+                self.msg_in_fault(from, "wrong_party")?;
                 return Ok(());
             }
         }
 
+        // verify the message was sent in this round
+        // (catches replays of old messages and messages sent ahead of time)
+        if bytes_meta.round != self.info.round() {
+            warn!(
+                "peer {} (party {}) says: msg_in from peer {} (party {}) was sent in round {} but current round is {}",
+                share_id, party_id, bytes_meta.from, from, bytes_meta.round, self.info.round(),
+            );
+            self.msg_in_fault(from, "wrong_round")?;
+            return Ok(());
+        }
+
         // store and check expected message types from this share_id
         let expected_msg_type = match self.expected_msg_types.get(bytes_meta.from)? {
             Some(msg_type) => {
@@ -99,7 +198,7 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
                         "peer {} (party {}) says: msg_in share id {} gave conflicting expected message types",
                         share_id, party_id, bytes_meta.from
                     );
-                    self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+                    self.msg_in_fault(from, "conflicting_msg_types")?;
                     return Ok(());
                 }
                 *msg_type
@@ -117,39 +216,52 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
             Bcast => {
                 if matches!(expected_msg_type, BcastAndP2p | BcastOnly) {
                     if self.bcasts_in.is_none(bytes_meta.from)? {
-                        self.bcasts_in.set(bytes_meta.from, bytes_meta.payload)?;
+                        self.hold_msg_in(from, bytes_meta.payload.len())?;
+                        self.bcasts_in
+                            .set(bytes_meta.from, bytes_meta.payload.to_vec())?;
+                        self.msg_accepted(from);
                     } else {
                         warn!(
                             "peer {} (party {}) says: duplicate bcast message from peer {} (party {}) in round {}",
                             share_id, party_id, bytes_meta.from, from, self.info.round(),
                         );
-                        self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+                        self.msg_in_fault(from, "duplicate")?;
                     }
                 } else {
                     warn!(
                         "peer {} (party {}) says: peer {} (party {}) declared {:?} in round {} but sent Bcast",
                         share_id, party_id, bytes_meta.from, from, expected_msg_type, self.info.round(),
                     );
-                    self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+                    self.msg_in_fault(from, "unexpected_msg_type")?;
                 }
             }
             P2p { to } => {
                 if matches!(expected_msg_type, BcastAndP2p | P2pOnly) {
                     if self.p2ps_in.is_none(bytes_meta.from, to)? {
-                        self.p2ps_in.set(bytes_meta.from, to, bytes_meta.payload)?;
+                        self.hold_msg_in(from, bytes_meta.payload.len())?;
+                        self.p2ps_in
+                            .set(bytes_meta.from, to, bytes_meta.payload.to_vec())?;
+                        self.msg_accepted(from);
                     } else {
                         warn!(
                             "peer {} (party {}) says: duplicate p2p to {} message from peer {} (party {}) in round {}",
                             share_id, party_id, to, bytes_meta.from, from, self.info.round(),
                         );
-                        self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+                        self.msg_in_fault(from, "duplicate")?;
                     }
                 } else {
                     warn!(
                         "peer {} (party {}) says: peer {} (party {}) declared {:?} in round {} but sent P2p",
                         share_id, party_id, bytes_meta.from, from, expected_msg_type, self.info.round(),
                     );
-                    self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+                    self.msg_in_fault(from, "unexpected_msg_type")?;
+                }
+            }
+            Chunk { to, index, count } => {
+                if let Some(bytes) =
+                    self.chunk_in(from, bytes_meta.from, to, index, count, bytes_meta.payload)?
+                {
+                    return self.reassembled_msg_in(from, bytes_meta.from, to, &bytes);
                 }
             }
             // Special case: total_share_count == 1 and expected_msg_types == P2pOnly
@@ -162,14 +274,14 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
                         "peer {} (party {}) says: received TotalShareCount1P2pOnly message from peer {} (party {}) in round {} but total_share_count is {}",
                         share_id, party_id, bytes_meta.from, from, self.info.round(), self.info().share_info().total_share_count(),
                     );
-                    self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+                    self.msg_in_fault(from, "unexpected_msg_type")?;
                 }
                 if !matches!(expected_msg_type, P2pOnly) {
                     warn!(
                         "peer {} (party {}) says: received TotalShareCount1P2pOnly message from peer {} (party {}) in round {} but expected_msg_type is total_share_count is {:?}",
                         share_id, party_id, bytes_meta.from, from, self.info.round(), expected_msg_type,
                     );
-                    self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+                    self.msg_in_fault(from, "unexpected_msg_type")?;
                 }
                 info!(
                     "peer {} (party {}) says: special case: received TotalShareCount1P2pOnly message from peer {} (party {}) in round {}",
@@ -185,9 +297,19 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
         debug_assert_eq!(self.expected_msg_types.size(), self.bcasts_in.size());
         debug_assert_eq!(self.expected_msg_types.size(), self.p2ps_in.size());
 
-        for (_from, expected_msg_type_option, bcast_option, p2ps) in
+        for (from, expected_msg_type_option, bcast_option, p2ps) in
             zip3(&self.expected_msg_types, &self.bcasts_in, &self.p2ps_in)
         {
+            // messages of accused parties are discarded anyway: don't wait for them
+            let accused = self
+                .info
+                .share_index()
+                .share_to_party_id(from)
+                .and_then(|party_id| self.msg_in_faulters.get(party_id))
+                .map_or(false, |fault| fault.is_some());
+            if accused {
+                continue;
+            }
             if let Some(expected_msg_type) = expected_msg_type_option {
                 if (matches!(expected_msg_type, BcastAndP2p | BcastOnly) && bcast_option.is_none())
                     || (matches!(expected_msg_type, BcastAndP2p | P2pOnly) && !p2ps.is_full())
@@ -203,7 +325,11 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
     }
 
     /// Execute the next round.
-    pub fn execute_next_round(mut self) -> TofnResult<Protocol<F, K, P, MAX_MSG_IN_LEN>> {
+    /// This is where all received payloads are deserialized and all proofs are verified;
+    /// it may be CPU-heavy.
+    pub fn execute_next_round(mut self) -> TofnResult<Protocol<F, K, P>> {
+        let _span = self.span.clone().entered();
+        let timer = metrics::RoundTimer::start();
         let my_share_id = self.info().share_info().my_id();
         let my_party_id = self.info().party_id();
         let curr_round_num = self.info.round();
@@ -211,6 +337,13 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
 
         self.info.advance_round();
 
+        for (from, to) in self.chunks_in.keys() {
+            warn!(
+                "peer {} (party {}) says: incomplete chunked message from peer {} to {:?} in round {}",
+                my_share_id, my_party_id, from, to, curr_round_num,
+            );
+        }
+
         // for each msg_in faulter party P: for each share S belonging to P: unset all of S's messages and mark S as a faulter
         if !self.msg_in_faulters.is_empty() {
             let faulter_party_ids = self.msg_in_faulters.as_subset();
@@ -234,30 +367,188 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
             }
         }
 
-        self.round
+        self.info
+            .record_transcript(curr_round_num, &self.bcasts_in, &self.p2ps_in)?;
+
+        let executer = self.round.description().executer;
+        let builder = self
+            .round
             .execute_raw(
                 self.info.share_info(),
                 self.bcasts_in,
                 self.p2ps_in,
                 self.expected_msg_types,
                 share_faulters,
-            )?
-            .build(self.info)
+            )
+            .context(ErrorContext::new(executer, "execute_raw").round(curr_round_num))?;
+        timer.round_executed(curr_round_num);
+
+        // reuse this round's outgoing message buffers for the next round
+        let spare_buffers = self
+            .bcast_out
+            .into_iter()
+            .chain(
+                self.p2ps_out
+                    .into_iter()
+                    .flat_map(|p2ps| p2ps.into_iter().map(|(_, bytes)| bytes)),
+            )
+            .collect();
+
+        builder
+            .build(self.info, spare_buffers)
+            .context(ErrorContext::new(module_path!(), "execute_next_round").round(curr_round_num))
     }
 
     pub fn info(&self) -> &ProtocolInfoDeluxe<K, P> {
         &self.info
     }
 
+    /// The executer of this round and the types of its messages
+    pub fn description(&self) -> RoundDescription {
+        self.round.description()
+    }
+
+    /// Time allotted to this round by [Round::set_deadlines], if any.
+    /// See [RoundDeadlines] for how to enforce it.
+    pub fn deadline(&self) -> Option<Duration> {
+        self.info.deadlines().get(self.info.round())
+    }
+
+    /// Set the deadlines of this and all later rounds, replacing any previous ones.
+    pub fn set_deadlines(&mut self, deadlines: RoundDeadlines) {
+        self.info.set_deadlines(deadlines);
+    }
+
+    /// Set the [MsgInQuota] of this and all later rounds, replacing any previous one.
+    /// Messages already received this round count towards the new quota.
+    pub fn set_msg_in_quota(&mut self, msg_in_quota: MsgInQuota) {
+        self.info.set_msg_in_quota(msg_in_quota);
+    }
+
+    /// The state of this round without its secrets: the executer, which holds
+    /// this party's secrets and the protocol's intermediate values, is left out.
+    pub fn snapshot(&self) -> RoundSnapshot {
+        RoundSnapshot {
+            round: self.info.round(),
+            share_id: self.info.share_info().my_id().as_usize(),
+            party_id: self.info.party_id().as_usize(),
+            description: self.description(),
+            bcast_out: self.bcast_out.clone(),
+            p2p_out_lens: self.p2ps_out.as_ref().map(|p2ps| {
+                p2ps.iter()
+                    .map(|(to, bytes)| (to.as_usize(), bytes.len()))
+                    .collect()
+            }),
+            bcasts_in: self
+                .bcasts_in
+                .iter_some()
+                .map(|(from, _)| from.as_usize())
+                .collect(),
+            p2ps_in: self
+                .p2ps_in
+                .iter()
+                .flat_map(|(from, p2ps)| {
+                    p2ps.iter()
+                        .filter(|(_, p2p)| p2p.is_some())
+                        .map(move |(to, _)| (from.as_usize(), to.as_usize()))
+                })
+                .collect(),
+            msg_in_faulters: self
+                .msg_in_faulters
+                .iter_some()
+                .map(|(from, _)| from.as_usize())
+                .collect(),
+            memory_usage: self.held_bytes,
+            deadline: self.deadline(),
+        }
+    }
+
+    /// Maximum byte length of an incoming message, whole or reassembled from chunks
+    pub fn max_msg_in_len(&self) -> usize {
+        self.info.max_msg_in_len()
+    }
+
+    /// Set the maximum byte length of incoming messages in this and all later rounds,
+    /// replacing the protocol's default.
+    /// Fail unless `max_msg_in_len` is positive and at most 1 MB.
+    ///
+    /// Every party must accept the longest messages its peers send:
+    /// a limit below the protocol's default may cause honest peers to be accused.
+    pub fn set_max_msg_in_len(&mut self, max_msg_in_len: usize) -> TofnResult<()> {
+        self.info.set_max_msg_in_len(max_msg_in_len)
+    }
+
+    /// Cap the message bytes held by this protocol instance in this and all later rounds,
+    /// replacing any previous budget. `None` means no budget.
+    /// See [Round::memory_usage] for what is counted.
+    ///
+    /// [Round::msg_in] fails with a [TofnFatal] whose innermost [ErrorContext] has operation
+    /// [MEMORY_BUDGET_EXCEEDED] instead of storing a message that would exceed the budget.
+    /// The sender is not accused: the budget is a local limit that peers cannot see.
+    /// [Round::execute_next_round] fails likewise if the next round's outgoing messages alone are over budget.
+    ///
+    /// Only message buffers are counted: the budget does not bound the rest of the protocol's state,
+    /// eg. Paillier keys and intermediates.
+    pub fn set_memory_budget(&mut self, max_bytes: Option<usize>) {
+        self.info.set_memory_budget(max_bytes);
+    }
+
+    /// Bytes of the outgoing messages of this round and of the incoming payloads and chunks held so far.
+    /// Discarded messages, eg. duplicates and messages of accused parties, are not counted.
+    pub fn memory_usage(&self) -> usize {
+        self.held_bytes
+    }
+
+    /// Record `session_id` in the `tofn_protocol` span of the `tracing-spans` feature.
+    pub fn set_session_id(&self, session_id: &[u8]) {
+        spans::record_session_id(self.info.span(), session_id);
+    }
+
+    /// Register `observer` for the rest of the protocol, replacing any previous one.
+    /// `observer` is immediately notified that the current round has started.
+    pub fn set_observer(&mut self, observer: Box<dyn Observer<P>>) {
+        self.info.set_observer(observer);
+        self.info
+            .notify(|observer, round| observer.round_started(round));
+    }
+
+    /// Trace the formats of the messages sent by this party in this round.
+    /// See [wire_spec](crate::wire_spec).
+    #[cfg(feature = "wire-spec")]
+    pub(crate) fn trace_msgs_out(&self) -> TofnResult<crate::wire_spec::RoundSpec> {
+        let payload = |bytes: &BytesVec| {
+            wire_bytes::decode_message_ref::<K>(bytes)
+                .map(|wire_bytes| wire_bytes.payload)
+                .ok_or_else(|| {
+                    error!("failed to decode outgoing message");
+                    TofnFatal
+                })
+        };
+        let bcast = self.bcast_out.as_ref().map(payload).transpose()?;
+        let p2p = self
+            .p2ps_out
+            .as_ref()
+            .and_then(|p2ps| p2ps.iter().next())
+            .map(|(_, bytes)| payload(bytes))
+            .transpose()?;
+
+        let mut spec = self.round.trace_msgs(bcast, p2p)?;
+        spec.round = self.info.round();
+        Ok(spec)
+    }
+
     // private methods
+    /// Outgoing messages are encoded into buffers taken from `spare_buffers` when available.
     pub(super) fn new(
         round: Box<dyn ExecuterRaw<FinalOutput = F, Index = K>>,
-        info: ProtocolInfoDeluxe<K, P>,
+        mut info: ProtocolInfoDeluxe<K, P>,
         bcast_out: Option<BytesVec>,
         p2ps_out: Option<HoleVecMap<K, BytesVec>>,
+        mut spare_buffers: Vec<BytesVec>,
     ) -> TofnResult<Self> {
         let total_share_count = info.share_info().total_share_count();
         let my_share_id = info.share_info().my_id();
+        let round_num = info.round();
 
         // validate args
         if let Some(ref p2ps) = p2ps_out {
@@ -287,20 +578,26 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
             (Some(_), None) => BcastOnly,
             (Some(_), Some(_)) => BcastAndP2p,
         };
-        // can't use Option::map because closure returns Result and uses ? operator
-        let bcast_out = match bcast_out {
-            Some(payload) => Some(wire_bytes::encode_message(
+        let mut encode = |payload: &[u8], msg_type| -> TofnResult<BytesVec> {
+            let mut bytes = spare_buffers.pop().unwrap_or_default();
+            wire_bytes::encode_message_into(
+                &mut bytes,
                 payload,
                 my_share_id,
-                Bcast,
+                round_num,
+                msg_type,
                 expected_msg_types,
-            )?),
+            )?;
+            Ok(bytes)
+        };
+
+        // can't use Option::map because closure returns Result and uses ? operator
+        let bcast_out = match bcast_out {
+            Some(payload) => Some(encode(&payload, Bcast)?),
             None => None,
         };
         let p2ps_out = match p2ps_out {
-            Some(p2ps) => Some(p2ps.map2_result(|(to, payload)| {
-                wire_bytes::encode_message(payload, my_share_id, P2p { to }, expected_msg_types)
-            })?),
+            Some(p2ps) => Some(p2ps.map2_result(|(to, payload)| encode(&payload, P2p { to }))?),
             None => None,
         };
 
@@ -313,6 +610,7 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
             Some(wire_bytes::encode_message(
                 BytesVec::new(), // empty payload
                 my_share_id,
+                round_num,
                 TotalShareCount1P2pOnly,
                 P2pOnly,
             )?)
@@ -320,6 +618,39 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
             bcast_out
         };
 
+        info.notify(|observer, round| observer.round_started(round));
+
+        let span = spans::round_span(info.span(), round_num);
+        span.in_scope(|| {
+            if let Some(ref bcast) = bcast_out {
+                spans::msg_out(None, bcast.len());
+            }
+            if let Some(ref p2ps) = p2ps_out {
+                for (to, bytes) in p2ps.iter() {
+                    spans::msg_out(Some(to.as_usize()), bytes.len());
+                }
+            }
+        });
+
+        #[cfg(feature = "malicious")]
+        let (bcast_out, p2ps_out) =
+            malicious::relabel_msgs_out(info.msg_fault(), bcast_out, p2ps_out)?;
+
+        let held_bytes = bcast_out.iter().map(Vec::len).sum::<usize>()
+            + p2ps_out
+                .iter()
+                .flat_map(|p2ps| p2ps.iter().map(|(_, bytes)| bytes.len()))
+                .sum::<usize>();
+        if let Some(budget) = info.memory_budget() {
+            if held_bytes > budget {
+                error!(
+                    "peer {} (party {}) says: outgoing messages of {} bytes exceed memory budget {} in round {}",
+                    my_share_id, info.party_id(), held_bytes, budget, round_num,
+                );
+                return Err(memory_budget_exceeded());
+            }
+        }
+
         let party_count = info.party_share_counts().party_count();
         let bcasts_in = info.share_info().new_fillvecmap();
         let expected_msg_types = info.share_info().new_fillvecmap();
@@ -332,9 +663,204 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
             p2ps_in: FillP2ps::with_size(total_share_count),
             expected_msg_types,
             msg_in_faulters: FillVecMap::with_size(party_count),
+            chunks_in: BTreeMap::new(),
+            msgs_in_usage: (0..party_count).map(|_| MsgInUsage::default()).collect(),
+            held_bytes,
+            span,
         })
     }
 
+    /// Accuse `from` of sending a corrupted message this round
+    /// and free everything buffered from its shares: its messages are discarded anyway.
+    /// `reason` labels the rejection in [metrics].
+    fn msg_in_fault(&mut self, from: TypedUsize<P>, reason: &'static str) -> TofnResult<()> {
+        spans::msg_outcome(from.as_usize(), false);
+        metrics::msg_rejected(reason);
+        self.info
+            .notify(|observer, round| observer.msg_rejected(round, from));
+        self.msg_in_faulters.set(from, Fault::CorruptedMessage)?;
+        self.free_msgs_in(from)
+    }
+
+    /// Count a message of `len` bytes from `from` against its [MsgInQuota].
+    /// Return `false` if the message must be dropped because `from` is over quota.
+    ///
+    /// The first time `from` exceeds its quota it is accused.
+    fn charge_msg_in_quota(&mut self, from: TypedUsize<P>, len: usize) -> TofnResult<bool> {
+        let quota = self.info.msg_in_quota();
+        let usage = self.msgs_in_usage.get_mut(from)?;
+        let within_quota_before = quota.allows(usage.msgs, usage.bytes);
+        usage.msgs = usage.msgs.saturating_add(1);
+        usage.bytes = usage.bytes.saturating_add(len);
+        let (msgs, bytes) = (usage.msgs, usage.bytes);
+        if quota.allows(msgs, bytes) {
+            return Ok(true);
+        }
+        if !within_quota_before {
+            return Ok(false);
+        }
+
+        let _span = self.span.clone().entered();
+        warn!(
+            "peer {} (party {}) says: party {} exceeded msg_in quota {:?} with {} msgs, {} bytes in round {}",
+            self.info().share_info().my_id(), self.info().party_id(), from, quota, msgs, bytes, self.info.round(),
+        );
+        self.msg_in_fault(from, "quota_exceeded")?;
+        Ok(false)
+    }
+
+    /// Free the messages, whole or partly reassembled, of all shares of `from`
+    fn free_msgs_in(&mut self, from: TypedUsize<P>) -> TofnResult<()> {
+        for from_share in self.info.share_index().party_shares(from)? {
+            let bcast_len = self.bcasts_in.get(from_share)?.map_or(0, Vec::len);
+            let p2ps_len = self
+                .p2ps_in
+                .iter_from(from_share)?
+                .filter_map(|(_, p2p)| p2p.as_ref().map(Vec::len))
+                .sum::<usize>();
+            self.bcasts_in.unset(from_share)?;
+            self.p2ps_in.unset_all(from_share)?;
+
+            let from_share = from_share.as_usize();
+            let chunks_len = self
+                .chunks_in
+                .iter()
+                .filter(|((chunk_from, _), _)| *chunk_from == from_share)
+                .map(|(_, chunks_in)| chunks_in.len)
+                .sum::<usize>();
+            self.chunks_in
+                .retain(|(chunk_from, _), _| *chunk_from != from_share);
+
+            self.held_bytes = self
+                .held_bytes
+                .saturating_sub(bcast_len + p2ps_len + chunks_len);
+        }
+        Ok(())
+    }
+
+    /// Count `len` bytes from `from` against the memory budget, if any, before storing them.
+    /// Fail without accusing `from` if they would exceed the budget.
+    fn hold_msg_in(&mut self, from: TypedUsize<P>, len: usize) -> TofnResult<()> {
+        let held_bytes = self.held_bytes.saturating_add(len);
+        if let Some(budget) = self.info.memory_budget() {
+            if held_bytes > budget {
+                error!(
+                    "peer {} (party {}) says: msg_in of {} bytes from party {} exceeds memory budget {} with {} bytes held in round {}",
+                    self.info().share_info().my_id(), self.info().party_id(), len, from, budget, self.held_bytes, self.info.round(),
+                );
+                return Err(memory_budget_exceeded());
+            }
+        }
+        self.held_bytes = held_bytes;
+        Ok(())
+    }
+
+    fn msg_accepted(&mut self, from: TypedUsize<P>) {
+        spans::msg_outcome(from.as_usize(), true);
+        self.info
+            .notify(|observer, round| observer.msg_accepted(round, from));
+    }
+
+    /// Store a chunk of a message split by [split_message](wire_bytes::split_message).
+    /// Return the reassembled message if this was its last missing chunk.
+    fn chunk_in(
+        &mut self,
+        from: TypedUsize<P>,
+        from_share: TypedUsize<K>,
+        to: Option<TypedUsize<K>>,
+        index: usize,
+        count: usize,
+        data: &[u8],
+    ) -> TofnResult<Option<BytesVec>> {
+        let share_id = self.info().share_info().my_id();
+        let party_id = self.info().party_id();
+        let max_msg_in_len = self.info.max_msg_in_len();
+        let max_count = (max_msg_in_len + MIN_CHUNK_LEN - 1) / MIN_CHUNK_LEN;
+        let total_share_count = self.info().share_info().total_share_count();
+        let key = (from_share.as_usize(), to.map(|to| to.as_usize()));
+
+        // check everything before allocating a reassembly buffer
+        let fault = if index >= count
+            || data.is_empty()
+            || (index + 1 < count && data.len() < MIN_CHUNK_LEN)
+        {
+            Some("invalid chunk")
+        } else if to.map_or(false, |to| to.as_usize() >= total_share_count) {
+            Some("chunk recipient out of range")
+        } else if count > max_count {
+            Some("too many chunks")
+        } else {
+            match self.chunks_in.get(&key) {
+                Some(chunks_in) if count != chunks_in.count => Some("conflicting chunk count"),
+                Some(chunks_in) if chunks_in.chunks.contains_key(&index) => Some("duplicate chunk"),
+                Some(chunks_in) if chunks_in.len + data.len() > max_msg_in_len => {
+                    Some("chunked message too long")
+                }
+                _ => None,
+            }
+        };
+        if let Some(fault) = fault {
+            warn!(
+                "peer {} (party {}) says: msg_in {} {} of {} from peer {} (party {}) to {:?} in round {}",
+                share_id, party_id, fault, index, count, from_share, from, to, self.info.round(),
+            );
+            self.msg_in_fault(from, "bad_chunk")?;
+            return Ok(None);
+        }
+        self.hold_msg_in(from, data.len())?;
+
+        let chunks_in = self.chunks_in.entry(key).or_insert_with(|| ChunksIn {
+            count,
+            len: 0,
+            chunks: BTreeMap::new(),
+        });
+        chunks_in.len += data.len();
+        chunks_in.chunks.insert(index, data.to_vec());
+        if chunks_in.chunks.len() < count {
+            return Ok(None);
+        }
+
+        // the reassembled message is charged again if it is stored
+        let chunks_in = self.chunks_in.remove(&key).ok_or(TofnFatal)?;
+        self.held_bytes = self.held_bytes.saturating_sub(chunks_in.len);
+        let mut bytes = BytesVec::with_capacity(chunks_in.len);
+        for (_, chunk) in chunks_in.chunks {
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(Some(bytes))
+    }
+
+    /// Process a message reassembled from chunks.
+    /// The message must be of the type declared by its chunks.
+    fn reassembled_msg_in(
+        &mut self,
+        from: TypedUsize<P>,
+        from_share: TypedUsize<K>,
+        to: Option<TypedUsize<K>>,
+        bytes: &[u8],
+    ) -> TofnResult<()> {
+        let consistent = match wire_bytes::decode_message_ref::<K>(bytes) {
+            Some(bytes_meta) => {
+                bytes_meta.from == from_share
+                    && match (bytes_meta.msg_type, to) {
+                        (Bcast, None) | (TotalShareCount1P2pOnly, None) => true,
+                        (P2p { to: msg_to }, Some(to)) => msg_to == to,
+                        _ => false,
+                    }
+            }
+            None => false,
+        };
+        if !consistent {
+            warn!(
+                "peer {} (party {}) says: msg_in reassembled message from peer {} (party {}) to {:?} in round {} is inconsistent with its chunks",
+                self.info().share_info().my_id(), self.info().party_id(), from_share, from, to, self.info.round(),
+            );
+            self.msg_in_fault(from, "bad_chunk")?;
+            return Ok(());
+        }
+        self.msg_in_inner(from, bytes)
+    }
+
     #[cfg(test)]
     pub fn round_as_any(&self) -> &dyn core::any::Any {
         self.round.as_any()
@@ -346,21 +872,168 @@ impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
     }
 }
 
+/// Shows [Round::snapshot]; secrets are `[REDACTED]`
+impl<F, K, P> fmt::Debug for Round<F, K, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Round")
+            .field("snapshot", &self.snapshot())
+            .field("executer", &Redacted)
+            .finish()
+    }
+}
+
+/// Operation of the [ErrorContext] of a [TofnFatal] caused by messages over the memory budget,
+/// see [Round::set_memory_budget]
+pub const MEMORY_BUDGET_EXCEEDED: &str = "memory_budget";
+
+fn memory_budget_exceeded() -> TofnFatal {
+    TofnFatal.with_context(ErrorContext::new(module_path!(), MEMORY_BUDGET_EXCEEDED))
+}
+
 #[cfg(feature = "malicious")]
 pub mod malicious {
     use tracing::{error, info};
 
-    use crate::sdk::{
-        api::TofnFatal,
-        wire_bytes::{
-            malicious::corrupt_payload,
-            MsgType::{self, *},
+    use crate::{
+        collections::{HoleVecMap, TypedUsize},
+        sdk::{
+            api::{BytesVec, Protocol, TofnFatal},
+            wire_bytes::{
+                self,
+                malicious::corrupt_payload,
+                MsgType::{self, *},
+            },
         },
     };
 
     use super::{Round, TofnResult};
 
-    impl<F, K, P, const MAX_MSG_IN_LEN: usize> Round<F, K, P, MAX_MSG_IN_LEN> {
+    /// Misbehaviour of a malicious share in sending its messages of one round,
+    /// as opposed to tampering with their contents.
+    /// Protocols map their `Behaviour` to it, eg. [WithholdBcast](crate::gg20::sign::malicious::Behaviour::WithholdBcast).
+    pub(crate) enum MsgFault<K> {
+        /// Send no bcast
+        WithholdBcast,
+        /// Send no p2p to `victim`
+        WithholdP2p { victim: TypedUsize<K> },
+        /// Send every message only to the first half of all shares
+        HalfOfPeers,
+        /// Send every message labelled with the previous round, as a replay of that round would be
+        Replay,
+        /// Send every message labelled with the next round, as a message sent ahead of time would be
+        FutureRound,
+        /// Send every message on behalf of `victim`
+        Impersonate { victim: TypedUsize<K> },
+    }
+
+    // `#[derive(...)]` doesn't work for all `K`
+    impl<K> Copy for MsgFault<K> {}
+
+    impl<K> Clone for MsgFault<K> {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
+
+    /// Make `protocol` commit `msg_fault` = `(round, fault)`, if any.
+    /// Rounds are numbered from 1.
+    pub(crate) fn with_msg_fault<F, K, P>(
+        mut protocol: Protocol<F, K, P>,
+        msg_fault: Option<(usize, MsgFault<K>)>,
+    ) -> TofnResult<Protocol<F, K, P>> {
+        if let (Some((round, fault)), Protocol::NotDone(first_round)) = (msg_fault, &mut protocol) {
+            first_round.set_msg_fault(round, fault)?;
+        }
+        Ok(protocol)
+    }
+
+    /// Re-encode the outgoing messages of a round as `fault` sends them
+    pub(super) fn relabel_msgs_out<K>(
+        fault: Option<MsgFault<K>>,
+        bcast_out: Option<BytesVec>,
+        p2ps_out: Option<HoleVecMap<K, BytesVec>>,
+    ) -> TofnResult<(Option<BytesVec>, Option<HoleVecMap<K, BytesVec>>)> {
+        let fault = match fault {
+            Some(fault @ (Replay | FutureRound | Impersonate { .. })) => fault,
+            _ => return Ok((bcast_out, p2ps_out)),
+        };
+        let bcast_out = match bcast_out {
+            Some(bytes) => Some(relabel(fault, &bytes)?),
+            None => None,
+        };
+        let p2ps_out = match p2ps_out {
+            Some(p2ps) => Some(p2ps.map2_result(|(_, bytes)| relabel(fault, &bytes))?),
+            None => None,
+        };
+        Ok((bcast_out, p2ps_out))
+    }
+
+    fn relabel<K>(fault: MsgFault<K>, bytes: &[u8]) -> TofnResult<BytesVec> {
+        let msg = wire_bytes::decode_message_ref::<K>(bytes).ok_or_else(|| {
+            error!("can't decode outgoing message");
+            TofnFatal
+        })?;
+        let (from, round) = match fault {
+            Replay => (
+                msg.from,
+                msg.round.checked_sub(1).ok_or_else(|| {
+                    error!("no previous round to replay");
+                    TofnFatal
+                })?,
+            ),
+            FutureRound => (msg.from, msg.round + 1),
+            Impersonate { victim } => (victim, msg.round),
+            WithholdBcast | WithholdP2p { .. } | HalfOfPeers => (msg.from, msg.round),
+        };
+        wire_bytes::encode_message(
+            msg.payload.to_vec(),
+            from,
+            round,
+            msg.msg_type,
+            msg.expected_msg_types,
+        )
+    }
+
+    use MsgFault::*;
+
+    impl<F, K, P> Round<F, K, P> {
+        /// Does share `to` receive outgoing message `msg_type` of this round?
+        /// Always `true` unless this share is malicious and withholds it.
+        /// Transports that run malicious shares, eg. [execute_protocol](crate::sdk::local::execute_protocol),
+        /// must not deliver the message to `to` otherwise.
+        pub fn delivers_to(&self, msg_type: MsgType<K>, to: TypedUsize<K>) -> bool {
+            match self.info.msg_fault() {
+                Some(WithholdBcast) => !matches!(msg_type, Bcast | TotalShareCount1P2pOnly),
+                Some(WithholdP2p { victim }) => {
+                    !matches!(msg_type, P2p { to: p2p_to } if p2p_to == victim)
+                }
+                Some(HalfOfPeers) => to.as_usize() < self.info.share_info().total_share_count() / 2,
+                _ => true,
+            }
+        }
+
+        /// Commit `fault` in round `round`, numbered from 1
+        pub(crate) fn set_msg_fault(&mut self, round: usize, fault: MsgFault<K>) -> TofnResult<()> {
+            info!(
+                "malicious party {} will misbehave in sending its messages of round {}",
+                self.info.share_info().my_id(),
+                round
+            );
+            let round = round.checked_sub(1).ok_or_else(|| {
+                error!("rounds are numbered from 1");
+                TofnFatal
+            })?;
+            self.info.set_msg_fault(round, fault);
+            let (bcast_out, p2ps_out) = relabel_msgs_out(
+                self.info.msg_fault(),
+                self.bcast_out.take(),
+                self.p2ps_out.take(),
+            )?;
+            self.bcast_out = bcast_out;
+            self.p2ps_out = p2ps_out;
+            Ok(())
+        }
+
         pub fn corrupt_msg_payload(&mut self, msg_type: MsgType<K>) -> TofnResult<()> {
             info!(
                 "malicious party {} corrupt msg",
@@ -388,8 +1061,190 @@ pub mod malicious {
                     error!("can't corrupt messages of type TotalShareCount1P2pOnly");
                     return Err(TofnFatal);
                 }
+                Chunk { .. } => {
+                    error!("can't corrupt messages of type Chunk");
+                    return Err(TofnFatal);
+                }
             }
             Ok(())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::MEMORY_BUDGET_EXCEEDED;
+    use crate::{
+        collections::TypedUsize,
+        crypto_tools::rng::dummy_secret_recovery_key,
+        multisig::keygen::{
+            new_keygen, KeygenPartyId, KeygenPartyShareCounts, KeygenShareId, SecretKeyShare,
+            MAX_MSG_LEN,
+        },
+        sdk::{
+            api::{split_message, Fault, Protocol, Round},
+            wire_bytes::{
+                decode_message_ref, encode_message, ExpectedMsgTypes, MsgType::Chunk, MIN_CHUNK_LEN,
+            },
+        },
+    };
+
+    /// The first round of share `i` of a multisig keygen by 2 parties with 1 share each
+    fn new_keygen_round(
+        i: usize,
+        session_nonce: &[u8],
+    ) -> Round<SecretKeyShare, KeygenShareId, KeygenPartyId> {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 1]).unwrap();
+        match new_keygen(
+            party_share_counts,
+            1,
+            TypedUsize::from_usize(i),
+            0,
+            &dummy_secret_recovery_key(i),
+            session_nonce,
+        )
+        .unwrap()
+        {
+            Protocol::NotDone(round) => round,
+            Protocol::Done(_) => panic!("keygen done too early"),
+        }
+    }
+
+    #[test]
+    fn memory_budget() {
+        let new_round = |i| new_keygen_round(i, b"memory_budget");
+        let payload_len = |bytes: &[u8]| {
+            decode_message_ref::<KeygenShareId>(bytes)
+                .unwrap()
+                .payload
+                .len()
+        };
+        let (party_0, party_1) = (TypedUsize::from_usize(0), TypedUsize::from_usize(1));
+        let bcast_0 = new_round(0).bcast_out().unwrap().clone();
+        let bcast_1 = new_round(1).bcast_out().unwrap().clone();
+        let held_0 = bcast_0.len() + payload_len(&bcast_0);
+        let held_all = held_0 + payload_len(&bcast_1);
+
+        // room for my own bcast and all payloads but one byte
+        let mut round = new_round(0);
+        assert_eq!(round.memory_usage(), bcast_0.len());
+        round.set_memory_budget(Some(held_all - 1));
+        round.msg_in(party_0, &bcast_0).unwrap();
+        assert_eq!(round.memory_usage(), held_0);
+
+        // a message over budget is a local error: it is dropped and its sender is not accused
+        let err = round.msg_in(party_1, &bcast_1).unwrap_err();
+        assert_eq!(err.context()[0].operation, MEMORY_BUDGET_EXCEEDED);
+        assert!(round.snapshot().msg_in_faulters.is_empty());
+        assert_eq!(round.memory_usage(), held_0);
+        assert!(round.expecting_more_msgs_this_round());
+
+        let mut round = new_round(0);
+        round.set_memory_budget(Some(held_all));
+        round.msg_in(party_0, &bcast_0).unwrap();
+        round.msg_in(party_1, &bcast_1).unwrap();
+        assert!(round.snapshot().msg_in_faulters.is_empty());
+        assert_eq!(round.memory_usage(), held_all);
+        assert!(!round.expecting_more_msgs_this_round());
+
+        // a duplicate frees everything held from its sender
+        round.msg_in(party_1, &bcast_1).unwrap();
+        assert_eq!(round.snapshot().msg_in_faulters, alloc::vec![1]);
+        assert_eq!(round.memory_usage(), held_0);
+    }
+
+    #[test]
+    fn chunk_bounds() {
+        let new_round = |i| new_keygen_round(i, b"chunk_bounds");
+        let from = TypedUsize::from_usize(1);
+        let chunks = split_message(new_round(1).bcast_out().unwrap(), 30).unwrap();
+
+        // a chunk to a nonexistent share, a chunk count no message within the length limit can have
+        for msg_type in [
+            Chunk {
+                to: Some(TypedUsize::from_usize(2)),
+                index: 0,
+                count: 2,
+            },
+            Chunk {
+                to: None,
+                index: 0,
+                count: MAX_MSG_LEN,
+            },
+        ] {
+            let bad_chunk = encode_message::<KeygenShareId>(
+                alloc::vec![0; MIN_CHUNK_LEN],
+                TypedUsize::from_usize(1),
+                0,
+                msg_type,
+                ExpectedMsgTypes::BcastOnly,
+            )
+            .unwrap();
+            let mut round = new_round(0);
+            round.msg_in(from, &bad_chunk).unwrap();
+            assert_eq!(round.snapshot().msg_in_faulters, alloc::vec![1]);
+            assert!(round.chunks_in.is_empty());
+
+            // chunks from an accused party are not buffered
+            round.msg_in(from, &chunks[0]).unwrap();
+            assert!(round.chunks_in.is_empty());
+        }
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut rounds: alloc::vec::Vec<_> =
+            (0..2).map(|i| new_keygen_round(i, b"snapshot")).collect();
+        let bcast_0 = rounds[0].bcast_out().unwrap().clone();
+        let bcast_1 = rounds[1].bcast_out().unwrap().clone();
+
+        let mut round = rounds.remove(0);
+        round.msg_in(TypedUsize::from_usize(1), &bcast_1).unwrap();
+        let snapshot = round.snapshot();
+        assert_eq!(snapshot.round, 0);
+        assert_eq!(snapshot.bcast_out, Some(bcast_0.clone()));
+        assert_eq!(snapshot.p2p_out_lens, None);
+        assert_eq!(snapshot.bcasts_in, alloc::vec![1]);
+        assert!(snapshot.msg_in_faulters.is_empty());
+        assert_eq!(snapshot.clone(), snapshot);
+
+        let debug = alloc::format!("{:?}", round);
+        assert!(debug.contains("[REDACTED]"));
+        assert!(debug.contains(&alloc::format!("{:?}", snapshot)));
+
+        round.msg_in(TypedUsize::from_usize(0), &bcast_0).unwrap();
+        let done = round.execute_next_round().unwrap();
+        assert!(matches!(done, Protocol::Done(Ok(_))));
+        assert_eq!(alloc::format!("{:?}", done), "Done(Ok([REDACTED]))");
+    }
+
+    #[test]
+    fn max_msg_in_len() {
+        let mut rounds: alloc::vec::Vec<_> = (0..2)
+            .map(|i| new_keygen_round(i, b"max_msg_in_len"))
+            .collect();
+        let bcast_0 = rounds[0].bcast_out().unwrap().clone();
+        let bcast_1 = rounds[1].bcast_out().unwrap().clone();
+
+        let round = &mut rounds[0];
+        assert_eq!(round.max_msg_in_len(), MAX_MSG_LEN);
+        assert!(round.set_max_msg_in_len(0).is_err());
+        assert!(round.set_max_msg_in_len(1_000_001).is_err());
+        assert_eq!(round.max_msg_in_len(), MAX_MSG_LEN);
+
+        // party 1 is accused of sending a message that is too long
+        round.msg_in(TypedUsize::from_usize(0), &bcast_0).unwrap();
+        round.set_max_msg_in_len(bcast_1.len() - 1).unwrap();
+        round.msg_in(TypedUsize::from_usize(1), &bcast_1).unwrap();
+        match rounds.remove(0).execute_next_round().unwrap() {
+            Protocol::Done(Err(faulters)) => {
+                assert!(faulters.get(TypedUsize::from_usize(0)).unwrap().is_none());
+                assert!(matches!(
+                    faulters.get(TypedUsize::from_usize(1)).unwrap(),
+                    Some(Fault::CorruptedMessage)
+                ));
+            }
+            _ => panic!("expect party 1 to be accused"),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use alloc::{collections::BTreeSet, format, string::String, vec::Vec};
+
+use serde::Serialize;
+use tracing::error;
+
+use super::{
+    api::{TofnFatal, TofnResult},
+    round::Round,
+};
+
+/// The executer of a round and the types of the messages it consumes,
+/// as given by [core::any::type_name]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+pub struct RoundDescription {
+    pub executer: &'static str,
+    pub bcast: &'static str,
+    pub p2p: &'static str,
+}
+
+/// The rounds of a protocol as observed by one party, for debugging.
+///
+/// [record](RoundGraph::record) each [Round] as it becomes current
+/// and call [end_run](RoundGraph::end_run) when the protocol is done.
+/// Record several runs, eg. honest and malicious, to cover more branches of the graph.
+/// Render with [to_dot](RoundGraph::to_dot) or [to_json](RoundGraph::to_json).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RoundGraph {
+    nodes: Vec<RoundNode>,
+    edges: BTreeSet<(usize, usize)>,
+    #[serde(skip)]
+    last: Option<usize>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+struct RoundNode {
+    round: usize,
+    description: RoundDescription,
+    max_bcast_len: Option<usize>,
+    max_p2p_len: Option<usize>,
+}
+
+impl RoundGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `round` to the current run.
+    /// Message sizes are the largest outgoing message sizes seen so far.
+    pub fn record<F, K, P>(&mut self, round: &Round<F, K, P>) {
+        let round_num = round.info().round();
+        let description = round.description();
+        let bcast_len = round.bcast_out().map(Vec::len);
+        let p2p_len = round
+            .p2ps_out()
+            .and_then(|p2ps| p2ps.iter().map(|(_, bytes)| bytes.len()).max());
+
+        let index = match self
+            .nodes
+            .iter()
+            .position(|node| node.round == round_num && node.description == description)
+        {
+            Some(index) => index,
+            None => {
+                self.nodes.push(RoundNode {
+                    round: round_num,
+                    description,
+                    max_bcast_len: None,
+                    max_p2p_len: None,
+                });
+                self.nodes.len() - 1
+            }
+        };
+
+        let node = &mut self.nodes[index];
+        node.max_bcast_len = node.max_bcast_len.max(bcast_len);
+        node.max_p2p_len = node.max_p2p_len.max(p2p_len);
+
+        if let Some(last) = self.last {
+            self.edges.insert((last, index));
+        }
+        self.last = Some(index);
+    }
+
+    /// The next [record](RoundGraph::record)ed round starts a new run
+    pub fn end_run(&mut self) {
+        self.last = None;
+    }
+
+    /// Render in the Graphviz DOT language
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph tofn {\n    node [shape=box];\n");
+        for (index, node) in self.nodes.iter().enumerate() {
+            let mut label = format!("round {}\\n{}", node.round, node.description.executer);
+            if let Some(len) = node.max_bcast_len {
+                label += &format!("\\nbcast: {} ({} bytes)", node.description.bcast, len);
+            }
+            if let Some(len) = node.max_p2p_len {
+                label += &format!("\\np2p: {} ({} bytes)", node.description.p2p, len);
+            }
+            dot += &format!(
+                "    n{} [label=\"{}\"];\n",
+                index,
+                label.replace('"', "\\\"")
+            );
+        }
+        for (from, to) in &self.edges {
+            dot += &format!("    n{} -> n{};\n", from, to);
+        }
+        dot += "}\n";
+        dot
+    }
+
+    pub fn to_json(&self) -> TofnResult<String> {
+        serde_json::to_string_pretty(self).map_err(|err| {
+            error!("round graph serialization failure: {}", err);
+            TofnFatal
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoundGraph;
+    use crate::{
+        collections::VecMap,
+        crypto_tools::rng::dummy_secret_recovery_key,
+        multisig::keygen::{new_keygen, KeygenPartyId, KeygenPartyShareCounts},
+        sdk::api::Protocol,
+    };
+
+    #[test]
+    fn multisig_keygen_graph() {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(alloc::vec![1, 1]).unwrap();
+        let parties: VecMap<KeygenPartyId, _> = party_share_counts
+            .iter()
+            .map(|(party_id, _)| {
+                match new_keygen(
+                    party_share_counts.clone(),
+                    1,
+                    party_id,
+                    0,
+                    &dummy_secret_recovery_key(party_id.as_usize()),
+                    b"round graph",
+                )
+                .unwrap()
+                {
+                    Protocol::NotDone(round) => round,
+                    Protocol::Done(_) => panic!("`new_keygen` returned a `Done` protocol"),
+                }
+            })
+            .collect();
+
+        let mut graph = RoundGraph::new();
+        for _ in 0..2 {
+            graph.record(parties.iter().next().unwrap().1);
+            graph.end_run();
+        }
+        assert_eq!(graph.nodes.len(), 1);
+        assert!(graph.edges.is_empty());
+
+        let node = &graph.nodes[0];
+        assert_eq!(node.round, 0);
+        assert!(node.description.executer.contains("multisig::keygen::r2"));
+        assert!(node.max_bcast_len.is_some());
+        assert!(node.max_p2p_len.is_none());
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph tofn {"));
+        assert!(dot.contains("round 0"));
+        assert!(graph.to_json().unwrap().contains("\"executer\""));
+    }
+}
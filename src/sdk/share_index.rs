@@ -0,0 +1,127 @@
+use alloc::vec::Vec;
+
+use tracing::error;
+
+use super::party_share_counts::PartyShareCounts;
+use crate::{
+    collections::{TypedUsize, VecMap},
+    sdk::api::{TofnFatal, TofnResult},
+};
+
+/// Constant-time lookups between the share ids `K` and party ids `P` of a [PartyShareCounts].
+/// Unlike the lookups of [PartyShareCounts], these do not scan all parties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShareIndex<P, K> {
+    /// `(party_id, subshare_id)` of each share
+    shares: VecMap<K, (TypedUsize<P>, usize)>,
+    /// `(first share id, share count)` of each party
+    parties: VecMap<P, (usize, usize)>,
+}
+
+impl<P, K> ShareIndex<P, K> {
+    pub fn new(party_share_counts: &PartyShareCounts<P>) -> Self {
+        let mut shares = Vec::with_capacity(party_share_counts.total_share_count());
+        let mut parties = Vec::with_capacity(party_share_counts.party_count());
+        for (party_id, &share_count) in party_share_counts.iter() {
+            parties.push((shares.len(), share_count));
+            shares.extend((0..share_count).map(|subshare_id| (party_id, subshare_id)));
+        }
+        Self {
+            shares: VecMap::from_vec(shares),
+            parties: VecMap::from_vec(parties),
+        }
+    }
+
+    pub fn total_share_count(&self) -> usize {
+        self.shares.len()
+    }
+
+    pub fn party_count(&self) -> usize {
+        self.parties.len()
+    }
+
+    pub fn share_to_party_subshare_ids(
+        &self,
+        share_id: TypedUsize<K>,
+    ) -> TofnResult<(TypedUsize<P>, usize)> {
+        self.shares.get(share_id).copied()
+    }
+
+    pub fn share_to_party_id(&self, share_id: TypedUsize<K>) -> TofnResult<TypedUsize<P>> {
+        Ok(self.share_to_party_subshare_ids(share_id)?.0)
+    }
+
+    pub fn party_to_share_id(
+        &self,
+        party_id: TypedUsize<P>,
+        subshare_id: usize,
+    ) -> TofnResult<TypedUsize<K>> {
+        let &(first, share_count) = self.parties.get(party_id)?;
+        if subshare_id >= share_count {
+            error!(
+                "subshare_id {} exceeds party_share_count {}",
+                subshare_id, share_count
+            );
+            return Err(TofnFatal);
+        }
+        Ok(TypedUsize::from_usize(first + subshare_id))
+    }
+
+    /// Share ids of `party_id` in subshare id order
+    pub fn party_shares(
+        &self,
+        party_id: TypedUsize<P>,
+    ) -> TofnResult<impl Iterator<Item = TypedUsize<K>>> {
+        let &(first, share_count) = self.parties.get(party_id)?;
+        Ok((first..first + share_count).map(TypedUsize::from_usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::ShareIndex;
+    use crate::{collections::TypedUsize, sdk::api::PartyShareCounts};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestParty;
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestShare;
+
+    #[test]
+    fn agrees_with_party_share_counts() {
+        let party_share_counts =
+            PartyShareCounts::<TestParty>::from_vec(alloc::vec![2, 0, 3, 1]).unwrap();
+        let index = ShareIndex::<TestParty, TestShare>::new(&party_share_counts);
+        assert_eq!(index.total_share_count(), 6);
+        assert_eq!(index.party_count(), 4);
+
+        for share in 0..6 {
+            let share_id = TypedUsize::<TestShare>::from_usize(share);
+            let (party_id, subshare_id) = index.share_to_party_subshare_ids(share_id).unwrap();
+            assert_eq!(
+                (party_id, subshare_id),
+                party_share_counts
+                    .share_to_party_subshare_ids(share_id)
+                    .unwrap()
+            );
+            assert_eq!(
+                index.party_to_share_id(party_id, subshare_id).unwrap(),
+                share_id
+            );
+        }
+        assert!(index.share_to_party_id(TypedUsize::from_usize(6)).is_err());
+        assert!(index
+            .party_to_share_id(TypedUsize::from_usize(1), 0)
+            .is_err());
+        assert!(index.party_shares(TypedUsize::from_usize(4)).is_err());
+
+        let shares: Vec<_> = index
+            .party_shares(TypedUsize::from_usize(2))
+            .unwrap()
+            .map(|share_id| share_id.as_usize())
+            .collect();
+        assert_eq!(shares, alloc::vec![2, 3, 4]);
+    }
+}
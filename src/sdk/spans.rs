@@ -0,0 +1,72 @@
+//! Structured `tracing` spans and events, enabled by the `tracing-spans` feature.
+//!
+//! Each protocol instance has a `tofn_protocol` span with fields `party_id`, `share_id`
+//! and `session_id` (see [Round::set_session_id](super::api::Round::set_session_id)).
+//! Each round has a child `tofn_round` span with field `round`.
+//! Message events are emitted at `TRACE` level inside the round span.
+//! Without the feature all spans are [Span::none] and no events are emitted.
+use alloc::vec::Vec;
+
+use tracing::{field, info_span, trace, Span};
+
+use super::protocol::ProtocolOutput;
+
+const ENABLED: bool = cfg!(feature = "tracing-spans");
+
+pub(super) fn protocol_span(party_id: usize, share_id: usize) -> Span {
+    if ENABLED {
+        info_span!(
+            "tofn_protocol",
+            party_id,
+            share_id,
+            session_id = field::Empty
+        )
+    } else {
+        Span::none()
+    }
+}
+
+pub(super) fn round_span(protocol_span: &Span, round: usize) -> Span {
+    if ENABLED {
+        info_span!(parent: protocol_span, "tofn_round", round)
+    } else {
+        Span::none()
+    }
+}
+
+pub(super) fn record_session_id(protocol_span: &Span, session_id: &[u8]) {
+    if ENABLED {
+        protocol_span.record("session_id", &field::display(hex::encode(session_id)));
+    }
+}
+
+pub(super) fn msg_out(to_share: Option<usize>, bytes: usize) {
+    if ENABLED {
+        trace!(to_share = ?to_share, bytes, "msg_out");
+    }
+}
+
+pub(super) fn msg_in(from_party: usize, bytes: usize) {
+    if ENABLED {
+        trace!(from_party, bytes, "msg_in");
+    }
+}
+
+pub(super) fn msg_outcome(from_party: usize, accepted: bool) {
+    if ENABLED {
+        trace!(from_party, accepted, "msg_in outcome");
+    }
+}
+
+pub(super) fn protocol_done<F, P>(output: &ProtocolOutput<F, P>) {
+    if ENABLED {
+        let faulters: Vec<usize> = match output {
+            Ok(_) => Vec::new(),
+            Err(faulters) => faulters
+                .iter_some()
+                .map(|(faulter, _)| faulter.as_usize())
+                .collect(),
+        };
+        trace!(success = output.is_ok(), faulters = ?faulters, "protocol_done");
+    }
+}
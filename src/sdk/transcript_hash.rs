@@ -0,0 +1,281 @@
+//! Hash the messages of a protocol execution in a canonical order,
+//! so that parties that received the same messages in any order compute the same hash.
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::marker::PhantomData;
+
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use super::{
+    api::{TofnFatal, TofnResult},
+    wire_bytes::{self, MsgType, WireBytesRef},
+};
+use crate::{collections::TypedUsize, constants::TRANSCRIPT_TAG};
+
+pub type TranscriptHash = [u8; 32];
+
+/// `(round, from, to)` of a message, where `to` is `None` for a bcast.
+/// Messages are ordered by position.
+type Position = (usize, usize, Option<usize>);
+
+/// Running hash of the transcript of a protocol execution.
+///
+/// Messages are added in canonical order: by round, then by sender share id,
+/// then each sender's bcast before its p2ps, which are ordered by recipient share id.
+/// The hash is SHA-256 of [TRANSCRIPT_TAG] followed by each message
+/// [serialize](super::api::serialize)d as the tuple `(round, from, to, payload)`,
+/// where rounds start at 0 and `to` is `None` for a bcast.
+pub struct TranscriptHasher<K> {
+    hasher: Sha256,
+    last: Option<Position>,
+    index: PhantomData<K>,
+}
+
+impl<K> Default for TranscriptHasher<K> {
+    fn default() -> Self {
+        Self {
+            hasher: Sha256::new_with_prefix(TRANSCRIPT_TAG.to_be_bytes()),
+            last: None,
+            index: PhantomData,
+        }
+    }
+}
+
+impl<K> TranscriptHasher<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the payload of the message from `from` to `to`, or the bcast from `from` if `to` is `None`.
+    /// Fail unless the message comes after the last message added.
+    pub fn add_msg(
+        &mut self,
+        round: usize,
+        from: TypedUsize<K>,
+        to: Option<TypedUsize<K>>,
+        payload: &[u8],
+    ) -> TofnResult<()> {
+        let position = (round, from.as_usize(), to.map(|to| to.as_usize()));
+        if let Some(last) = self.last {
+            if last >= position {
+                error!(
+                    "transcript message {:?} is not after the last message {:?}",
+                    position, last
+                );
+                return Err(TofnFatal);
+            }
+        }
+
+        self.hasher
+            .update(wire_bytes::serialize(&(round, from, to, payload))?);
+        self.last = Some(position);
+        Ok(())
+    }
+
+    /// The hash of the messages added so far
+    pub fn hash(&self) -> TranscriptHash {
+        self.hasher.clone().finalize().into()
+    }
+
+    pub fn finalize(self) -> TranscriptHash {
+        self.hasher.finalize().into()
+    }
+}
+
+/// The hash of the transcript made of all `msgs`, encoded as sent by a [Round](super::api::Round),
+/// eg. as relayed to every party. `msgs` may be in any order. See [TranscriptHasher].
+///
+/// Fail if a message is not canonically encoded, is a chunk from [split_message](super::api::split_message)
+/// or has the same round, sender and recipient as another message.
+/// The dummy bcast sent when the total share count is 1 carries no payload and is skipped.
+pub fn transcript_hash<K, B: AsRef<[u8]>>(
+    msgs: impl IntoIterator<Item = B>,
+) -> TofnResult<TranscriptHash> {
+    let msgs: Vec<B> = msgs.into_iter().collect();
+
+    let mut payloads = BTreeMap::<Position, &[u8]>::new();
+    for bytes in msgs.iter() {
+        let wire_bytes: WireBytesRef<K> = wire_bytes::decode_message_canonical(bytes.as_ref())
+            .ok_or_else(|| {
+                error!("transcript message is not a canonically encoded message");
+                TofnFatal
+            })?;
+        let to = match wire_bytes.msg_type {
+            MsgType::Bcast => None,
+            MsgType::P2p { to } => Some(to.as_usize()),
+            MsgType::TotalShareCount1P2pOnly => continue,
+            MsgType::Chunk { .. } => {
+                error!("transcript message is a chunk: reassemble it first");
+                return Err(TofnFatal);
+            }
+        };
+
+        let position = (wire_bytes.round, wire_bytes.from.as_usize(), to);
+        if payloads.insert(position, wire_bytes.payload).is_some() {
+            error!("duplicate transcript message {:?}", position);
+            return Err(TofnFatal);
+        }
+    }
+
+    let mut hasher = TranscriptHasher::<K>::new();
+    for ((round, from, to), payload) in payloads {
+        hasher.add_msg(
+            round,
+            TypedUsize::from_usize(from),
+            to.map(TypedUsize::from_usize),
+            payload,
+        )?;
+    }
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+    use core::convert::TryFrom;
+
+    use super::{transcript_hash, TranscriptHasher};
+    use crate::{
+        collections::{Subset, TypedUsize, VecMap},
+        multisig::{
+            keygen::{new_keygen_with_signing_key, KeygenPartyShareCounts, KeygenShareId},
+            sign::{new_sign_with_options, MessageDigest, Nonce, SignOptions, SignShareId},
+        },
+        sdk::{
+            api::{split_message, BytesVec, Protocol},
+            implementer_api::{encode_message, ExpectedMsgTypes, MsgType},
+            local::execute_protocol_with_transcript_hash,
+        },
+    };
+
+    struct TestIndex;
+
+    fn msg(round: usize, from: usize, to: Option<usize>, payload: &[u8]) -> BytesVec {
+        encode_message::<TestIndex>(
+            payload.to_vec(),
+            TypedUsize::from_usize(from),
+            round,
+            match to {
+                Some(to) => MsgType::P2p {
+                    to: TypedUsize::from_usize(to),
+                },
+                None => MsgType::Bcast,
+            },
+            ExpectedMsgTypes::BcastAndP2p,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn canonical_order() {
+        let msgs = vec![
+            msg(0, 1, None, b"b"),
+            msg(1, 0, Some(1), &[1, 2, 3]),
+            msg(0, 0, None, b"a"),
+            msg(1, 0, None, b""),
+            msg(1, 1, Some(0), &[0; 300]),
+        ];
+        let expected = "6a22475ab08683db93c3f219e214d1e71c4cee0917a292ec4140712e86ba9060";
+        assert_eq!(
+            hex::encode(transcript_hash::<TestIndex, _>(&msgs).unwrap()),
+            expected
+        );
+        let reversed: Vec<_> = msgs.iter().rev().collect();
+        assert_eq!(
+            hex::encode(transcript_hash::<TestIndex, _>(reversed).unwrap()),
+            expected
+        );
+
+        // a different payload changes the hash
+        let mut tampered = msgs.clone();
+        tampered[0] = msg(0, 1, None, b"c");
+        assert_ne!(
+            hex::encode(transcript_hash::<TestIndex, _>(&tampered).unwrap()),
+            expected
+        );
+
+        // duplicates and chunks have no place in a transcript
+        let mut duplicate = msgs.clone();
+        duplicate.push(msg(0, 1, None, b"d"));
+        assert!(transcript_hash::<TestIndex, _>(&duplicate).is_err());
+        let chunks = split_message(&msgs[4], 100).unwrap();
+        assert!(transcript_hash::<TestIndex, _>(&chunks).is_err());
+
+        // messages must be added in canonical order
+        let mut hasher = TranscriptHasher::<TestIndex>::new();
+        hasher
+            .add_msg(1, TypedUsize::from_usize(0), None, b"")
+            .unwrap();
+        assert!(hasher
+            .add_msg(0, TypedUsize::from_usize(1), None, b"b")
+            .is_err());
+        assert!(hasher
+            .add_msg(1, TypedUsize::from_usize(0), None, b"")
+            .is_err());
+    }
+
+    /// Multisig keygen and sign derive all their randomness from their inputs,
+    /// so any two honest executions with the same inputs have the same transcript.
+    /// Party share counts `[1, 2]`, threshold 1, share `i` has signing key `i + 1`.
+    #[test]
+    fn multisig_transcripts() {
+        let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2]).unwrap();
+        let keygen_parties: VecMap<KeygenShareId, _> = (0..3)
+            .map(|i| {
+                let signing_key = k256::ecdsa::SigningKey::from_bytes(
+                    &k256::Scalar::from(i as u64 + 1).to_bytes(),
+                )
+                .unwrap();
+                let (party_id, subshare_id) = party_share_counts
+                    .share_to_party_subshare_ids(TypedUsize::from_usize(i))
+                    .unwrap();
+                new_keygen_with_signing_key(
+                    party_share_counts.clone(),
+                    1,
+                    party_id,
+                    subshare_id,
+                    &signing_key,
+                    b"transcript",
+                )
+                .unwrap()
+            })
+            .collect();
+        let (keygen_parties, keygen_hash) =
+            execute_protocol_with_transcript_hash(keygen_parties).unwrap();
+        assert_eq!(
+            hex::encode(keygen_hash),
+            "465bb82957235ffd9f904df55df848d0dad8b7c448f0b538d31524c747e21614"
+        );
+
+        let mut sign_parties = Subset::with_max_size(2);
+        sign_parties.add(TypedUsize::from_usize(0)).unwrap();
+        sign_parties.add(TypedUsize::from_usize(1)).unwrap();
+        let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+        let sign_parties: VecMap<SignShareId, _> = keygen_parties
+            .into_iter()
+            .map(|(_, party)| match party {
+                Protocol::Done(Ok(key_share)) => new_sign_with_options(
+                    key_share.group(),
+                    key_share.share(),
+                    &sign_parties,
+                    &msg_to_sign,
+                    &SignOptions {
+                        nonce: Nonce::Rfc6979,
+                        ..SignOptions::default()
+                    },
+                )
+                .unwrap(),
+                _ => panic!("keygen failed"),
+            })
+            .collect();
+        let (sign_parties, sign_hash) =
+            execute_protocol_with_transcript_hash(sign_parties).unwrap();
+        assert_eq!(
+            hex::encode(sign_hash),
+            "438e65bbea199b80ab3c52f649bbaa7601ad96d302e91fa1eacef5fac0d3544e"
+        );
+        for (_, party) in sign_parties {
+            assert!(matches!(party, Protocol::Done(Ok(_))));
+        }
+    }
+}
@@ -1,7 +1,7 @@
-use alloc::string::ToString;
+use alloc::{string::ToString, vec, vec::Vec};
 
 use crate::{collections::TypedUsize, sdk::api::TofnFatal};
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
 use super::api::{BytesVec, TofnResult};
@@ -14,31 +14,104 @@ use bincode::{
 };
 
 /// Max message length allowed to be (de)serialized
-const MAX_MSG_LEN: u64 = 1000 * 1000; // 1 MB
+pub(super) const MAX_MSG_LEN: u64 = 1000 * 1000; // 1 MB
+/// Minimum payload length of every chunk of a message except its last,
+/// which bounds the number of chunks a message can be split into
+pub(super) const MIN_CHUNK_LEN: usize = 8;
 
 /// Tofn version for serialized data.
-const TOFN_SERIALIZATION_VERSION: u16 = 0;
+/// Bump it whenever the encoding of any message or stored value changes, see `tests/fixtures/README.md`.
+///
+/// Version 1 added message chunks, the proof of possession in the multisig keygen bcast
+/// and the transcript hash in the gg20 sign round 6 bcast.
+const TOFN_SERIALIZATION_VERSION: u16 = 1;
+
+/// Oldest version accepted by [decode].
+/// Stored values, eg. key shares, are encoded the same way in every version since, so they still decode.
+/// Messages must be of the current version, see [decode_message_ref].
+const MIN_DECODE_VERSION: u16 = 0;
 
 pub fn encode_message<K>(
     payload: BytesVec,
     from: TypedUsize<K>,
+    round: usize,
     msg_type: MsgType<K>,
     expected_msg_types: ExpectedMsgTypes,
 ) -> TofnResult<BytesVec> {
-    encode(&WireBytes {
-        msg_type,
+    let mut bytes = BytesVec::new();
+    encode_message_into(
+        &mut bytes,
+        &payload,
         from,
-        payload,
+        round,
+        msg_type,
         expected_msg_types,
-    })
+    )?;
+    Ok(bytes)
+}
+
+/// Same as [encode_message] except the result is written to `bytes`, reusing its allocation.
+/// Any existing contents of `bytes` are discarded.
+pub fn encode_message_into<K>(
+    bytes: &mut BytesVec,
+    payload: &[u8],
+    from: TypedUsize<K>,
+    round: usize,
+    msg_type: MsgType<K>,
+    expected_msg_types: ExpectedMsgTypes,
+) -> TofnResult<()> {
+    encode_into(
+        bytes,
+        &WireBytesRef {
+            msg_type,
+            from,
+            round,
+            payload,
+            expected_msg_types,
+        },
+    )
 }
 
 /// Encode a value of generic type `T` with versioning
 pub fn encode<T: Serialize>(payload: &T) -> TofnResult<BytesVec> {
-    serialize(&BytesVecVersioned {
-        version: TOFN_SERIALIZATION_VERSION,
-        payload: serialize(payload)?,
-    })
+    let mut bytes = BytesVec::new();
+    encode_into(&mut bytes, payload)?;
+    Ok(bytes)
+}
+
+/// Same as [encode] except the result is written to `bytes`, reusing its allocation.
+/// Any existing contents of `bytes` are discarded.
+///
+/// The output is identical to serializing a [BytesVersioned] whose `payload` is `serialize(payload)`,
+/// but `payload` is serialized only once, directly into an exact-size buffer.
+pub fn encode_into<T: Serialize>(bytes: &mut BytesVec, payload: &T) -> TofnResult<()> {
+    let bincode = bincoder();
+    let map_err = |err: bincode::Error| {
+        error!("serialization failure: {}", err.to_string());
+        TofnFatal
+    };
+
+    // a `BytesVec` is serialized as its length followed by its bytes
+    let payload_len = bincode.serialized_size(payload).map_err(map_err)?;
+    let header = (TOFN_SERIALIZATION_VERSION, payload_len);
+    let len = bincode.serialized_size(&header).map_err(map_err)? + payload_len;
+    if len > MAX_MSG_LEN {
+        error!(
+            "serialization failure: encoded length {} exceeds maximum {}",
+            len, MAX_MSG_LEN
+        );
+        return Err(TofnFatal);
+    }
+
+    bytes.clear();
+    bytes.reserve_exact(len as usize);
+    bincode
+        .serialize_into(&mut *bytes, &header)
+        .map_err(map_err)?;
+    bincode
+        .serialize_into(&mut *bytes, payload)
+        .map_err(map_err)?;
+    Ok(())
 }
 
 /// Serialize a value using bincode and log errors
@@ -57,7 +130,9 @@ where
 /// Deserialize bytes to a type using bincode and log errors.
 /// Return an Option type since deserialization isn't treated as a Fatal error
 /// in tofn (for the purposes of fault identification).
-pub fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+///
+/// `T` may borrow from `bytes`.
+pub fn deserialize<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> Option<T> {
     let bincode = bincoder();
 
     bincode
@@ -68,30 +143,189 @@ pub fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
         .ok()
 }
 
+/// Same as [deserialize] except `bytes` must also be the canonical encoding of the result:
+/// serializing the result must reproduce `bytes` exactly.
+///
+/// bincode accepts some encodings that it never produces, eg. varints longer than necessary,
+/// and curve points may be SEC1-encoded compressed or uncompressed.
+/// Rejecting them leaves exactly one encoding of each message,
+/// so that honest parties that agree on the messages also agree on the transcript bytes.
+pub fn deserialize_canonical<'a, T>(bytes: &'a [u8]) -> Option<T>
+where
+    T: Serialize + Deserialize<'a>,
+{
+    let value = deserialize(bytes)?;
+    if serialize(&value).ok()? != bytes {
+        warn!("deserialization failure: non-canonical encoding");
+        return None;
+    }
+    Some(value)
+}
+
 /// Decode a versioned byte array to a value of generic type `T`
 /// Note that deserialization failures are non-fatal: do not return TofnResult
-pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
-    let bytes_versioned: BytesVecVersioned = deserialize(bytes).or_else(|| {
+///
+/// `T` may borrow from `bytes`; the versioned envelope is never copied.
+/// `bytes` may be of any version since [MIN_DECODE_VERSION].
+pub fn decode<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> Option<T> {
+    decode_since_version(bytes, MIN_DECODE_VERSION)
+}
+
+fn decode_since_version<'a, T: Deserialize<'a>>(bytes: &'a [u8], min_version: u16) -> Option<T> {
+    let bytes_versioned: BytesVersioned = deserialize(bytes).or_else(|| {
         warn!("outer deserialization failure");
         None
     })?;
 
-    if bytes_versioned.version != TOFN_SERIALIZATION_VERSION {
+    if !(min_version..=TOFN_SERIALIZATION_VERSION).contains(&bytes_versioned.version) {
         warn!(
-            "encoding version {}, expected {}",
-            bytes_versioned.version, TOFN_SERIALIZATION_VERSION
+            "encoding version {}, expected {} to {}",
+            bytes_versioned.version, min_version, TOFN_SERIALIZATION_VERSION
         );
         return None;
     }
 
-    deserialize(&bytes_versioned.payload).or_else(|| {
+    deserialize(bytes_versioned.payload).or_else(|| {
         warn!("inner deserialization failure");
         None
     })
 }
 
+/// Same as [decode] except `bytes` must also be the canonical encoding of the result,
+/// see [deserialize_canonical]. In particular, `bytes` must be of the current version.
+pub fn decode_canonical<'a, T>(bytes: &'a [u8]) -> Option<T>
+where
+    T: Serialize + Deserialize<'a>,
+{
+    check_canonical(decode(bytes)?, bytes)
+}
+
+/// Return `value` if `bytes` is its encoding
+fn check_canonical<T: Serialize>(value: T, bytes: &[u8]) -> Option<T> {
+    if encode(&value).ok()? != bytes {
+        warn!("decoding failure: non-canonical encoding");
+        return None;
+    }
+    Some(value)
+}
+
 pub fn decode_message<K>(bytes: &[u8]) -> Option<WireBytes<K>> {
-    decode(bytes)
+    decode_since_version(bytes, TOFN_SERIALIZATION_VERSION)
+}
+
+/// Same as [decode_message] except the payload borrows from `bytes`.
+///
+/// Unlike [decode], messages must be of the current version:
+/// peers that send an older version may be running a protocol with different rounds.
+pub fn decode_message_ref<K>(bytes: &[u8]) -> Option<WireBytesRef<'_, K>> {
+    decode_since_version(bytes, TOFN_SERIALIZATION_VERSION)
+}
+
+/// Same as [decode_message_ref] except `bytes` must be canonical, see [deserialize_canonical].
+pub fn decode_message_canonical<K>(bytes: &[u8]) -> Option<WireBytesRef<'_, K>> {
+    check_canonical(decode_message_ref(bytes)?, bytes)
+}
+
+/// Routing information of an encoded message, see [peek_header]
+#[derive(Debug, Clone, Copy)]
+pub struct MsgHeader<K> {
+    pub msg_type: MsgType<K>,
+    pub from: TypedUsize<K>,
+    /// Round in which the message was sent, index starts at 0
+    pub round: usize,
+}
+
+impl<K> MsgHeader<K> {
+    /// The share a p2p message or chunk is for, or `None` for a bcast
+    pub fn to(&self) -> Option<TypedUsize<K>> {
+        match self.msg_type {
+            MsgType::Bcast | MsgType::TotalShareCount1P2pOnly => None,
+            MsgType::P2p { to } => Some(to),
+            MsgType::Chunk { to, .. } => to,
+        }
+    }
+}
+
+/// Read the routing information of an encoded message without deserializing its payload,
+/// eg. for a relay that forwards messages to the right local share.
+/// Share ids are those of the protocol, not party ids.
+/// Return `None` if `bytes` is not an encoded message.
+pub fn peek_header<K>(bytes: &[u8]) -> Option<MsgHeader<K>> {
+    let wire_bytes: WireBytesRef<K> = decode_message_ref(bytes)?;
+    Some(MsgHeader {
+        msg_type: wire_bytes.msg_type,
+        from: wire_bytes.from,
+        round: wire_bytes.round,
+    })
+}
+
+/// Split an encoded outgoing message into chunks of at most `mtu` bytes each,
+/// for transports that cannot carry large messages.
+/// Recipients pass each chunk to `Round::msg_in` in any order;
+/// the message is reassembled once all its chunks have arrived.
+///
+/// If `bytes` is no longer than `mtu` then it is returned unchanged as the only chunk.
+/// Fail if `mtu` leaves fewer than 8 payload bytes per chunk.
+pub fn split_message(bytes: &[u8], mtu: usize) -> TofnResult<Vec<BytesVec>> {
+    if bytes.len() <= mtu {
+        return Ok(vec![bytes.to_vec()]);
+    }
+
+    // `K` does not affect the encoding
+    let wire_bytes: WireBytesRef<()> = decode_message_ref(bytes).ok_or_else(|| {
+        error!("can't split message: deserialization failure");
+        TofnFatal
+    })?;
+    let to = match wire_bytes.msg_type {
+        MsgType::Bcast | MsgType::TotalShareCount1P2pOnly => None,
+        MsgType::P2p { to } => Some(to),
+        MsgType::Chunk { .. } => {
+            error!("can't split message: already a chunk");
+            return Err(TofnFatal);
+        }
+    };
+
+    // `index`, `count` are at most `bytes.len()`, so this bounds the overhead of every chunk
+    let overhead = encode(&chunk(
+        &wire_bytes,
+        to,
+        bytes.len(),
+        bytes.len(),
+        &bytes[..mtu],
+    ))?
+    .len()
+        - mtu;
+    if overhead + MIN_CHUNK_LEN > mtu {
+        error!(
+            "can't split message: mtu {} is less than chunk overhead {} plus {}",
+            mtu, overhead, MIN_CHUNK_LEN
+        );
+        return Err(TofnFatal);
+    }
+    let chunk_len = mtu - overhead;
+    let count = (bytes.len() + chunk_len - 1) / chunk_len;
+
+    bytes
+        .chunks(chunk_len)
+        .enumerate()
+        .map(|(index, data)| encode(&chunk(&wire_bytes, to, index, count, data)))
+        .collect()
+}
+
+fn chunk<'a, K>(
+    wire_bytes: &WireBytesRef<K>,
+    to: Option<TypedUsize<K>>,
+    index: usize,
+    count: usize,
+    data: &'a [u8],
+) -> WireBytesRef<'a, K> {
+    WireBytesRef {
+        msg_type: MsgType::Chunk { to, index, count },
+        from: wire_bytes.from,
+        round: wire_bytes.round,
+        payload: data,
+        expected_msg_types: wire_bytes.expected_msg_types,
+    }
 }
 
 /// Prepare a `bincode` serde backend with our preferred config
@@ -116,18 +350,40 @@ fn bincoder() -> WithOtherTrailing<
 pub struct WireBytes<K> {
     pub msg_type: MsgType<K>,
     pub from: TypedUsize<K>,
+    pub round: usize, // round in which the message was sent, index starts at 0
     pub payload: BytesVec,
     pub expected_msg_types: ExpectedMsgTypes,
 }
 
+/// Borrowed version of `WireBytes` with the same encoding
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = ""))] // disable serde trait bounds on `K`: https://serde.rs/attr-bound.html
+pub struct WireBytesRef<'a, K> {
+    pub msg_type: MsgType<K>,
+    pub from: TypedUsize<K>,
+    pub round: usize, // round in which the message was sent, index starts at 0
+    #[serde(borrow)]
+    pub payload: &'a [u8],
+    pub expected_msg_types: ExpectedMsgTypes,
+}
+
 // TODO serde can derive Serialize for structs with a type parameter.
 // But I cannot derive Debug for these types unless `K: Debug`. How does serde do it?
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(bound(serialize = "", deserialize = ""))] // disable serde trait bounds on `K`: https://serde.rs/attr-bound.html
 pub enum MsgType<K> {
     Bcast,
-    P2p { to: TypedUsize<K> },
+    P2p {
+        to: TypedUsize<K>,
+    },
     TotalShareCount1P2pOnly, // special case: used only when total_share_count is 1
+    /// Piece `index` of `count` of a message split by [split_message].
+    /// `to` is `None` for a bcast and `Some` for a p2p.
+    Chunk {
+        to: Option<TypedUsize<K>>,
+        index: usize,
+        count: usize,
+    },
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
@@ -137,10 +393,23 @@ pub enum ExpectedMsgTypes {
     P2pOnly,
 }
 
+/// A `&[u8]` has the same encoding as a `BytesVec`
 #[derive(Serialize, Deserialize)]
-struct BytesVecVersioned {
+struct BytesVersioned<'a> {
     version: u16,
-    payload: BytesVec,
+    #[serde(borrow)]
+    payload: &'a [u8],
+}
+
+/// Trace the formats of the outer encodings of every message
+#[cfg(feature = "wire-spec")]
+pub(crate) fn trace_envelope(
+    tracer: &mut serde_reflection::Tracer,
+    samples: &serde_reflection::Samples,
+) -> serde_reflection::Result<()> {
+    tracer.trace_type::<BytesVersioned>(samples)?;
+    tracer.trace_type::<WireBytesRef<()>>(samples)?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -150,7 +419,15 @@ mod tests {
 
     use bincode::{DefaultOptions, Options};
 
-    use crate::sdk::wire_bytes::{decode, deserialize, encode, serialize, MAX_MSG_LEN};
+    use crate::{
+        collections::TypedUsize,
+        sdk::wire_bytes::{
+            decode, decode_canonical, decode_message, decode_message_canonical, decode_message_ref,
+            deserialize, deserialize_canonical, encode, encode_into, encode_message, peek_header,
+            serialize, split_message, BytesVersioned, ExpectedMsgTypes, MsgType, MAX_MSG_LEN,
+            TOFN_SERIALIZATION_VERSION,
+        },
+    };
 
     #[test]
     fn basic_correctness() {
@@ -167,6 +444,175 @@ mod tests {
         assert_eq!(msg, decode::<Vec<u64>>(&encoded_msg).unwrap());
     }
 
+    #[test]
+    fn versions() {
+        struct TestIndex;
+        let encoded_msg = encode(&42u64).unwrap();
+        let msg = encode_message::<TestIndex>(
+            vec![42u8; 10],
+            TypedUsize::from_usize(3),
+            2,
+            MsgType::Bcast,
+            ExpectedMsgTypes::BcastOnly,
+        )
+        .unwrap();
+        assert!(decode_message_ref::<TestIndex>(&msg).is_some());
+
+        // small versions occupy the first byte
+        let with_version = |bytes: &[u8], version: u16| {
+            let mut bytes = bytes.to_vec();
+            bytes[0] = version as u8;
+            bytes
+        };
+
+        // values of older versions still decode, messages don't
+        assert_eq!(decode::<u64>(&with_version(&encoded_msg, 0)), Some(42));
+        assert!(decode_message_ref::<TestIndex>(&with_version(&msg, 0)).is_none());
+
+        // later versions are unknown
+        let unknown = TOFN_SERIALIZATION_VERSION + 1;
+        assert!(decode::<u64>(&with_version(&encoded_msg, unknown)).is_none());
+        assert!(decode_message_ref::<TestIndex>(&with_version(&msg, unknown)).is_none());
+    }
+
+    #[test]
+    fn encode_into_reuses_buffer() {
+        let msg = vec![42u64; 10];
+        let expected = serialize(&BytesVersioned {
+            version: TOFN_SERIALIZATION_VERSION,
+            payload: &serialize(&msg).unwrap(),
+        })
+        .unwrap();
+
+        let mut bytes = vec![7u8; 100];
+        let capacity = bytes.capacity();
+        encode_into(&mut bytes, &msg).unwrap();
+        assert_eq!(bytes, expected);
+        assert_eq!(bytes.capacity(), capacity);
+    }
+
+    #[test]
+    fn canonical_encoding() {
+        // 5 as a 3-byte varint: marker 251 followed by a big-endian u16
+        let non_minimal = [251u8, 0, 5];
+        assert_eq!(deserialize::<u64>(&non_minimal), Some(5));
+        assert_eq!(deserialize_canonical::<u64>(&non_minimal), None);
+        assert_eq!(deserialize_canonical::<u64>(&[5]), Some(5));
+
+        // the same for the version of the outer encoding
+        let encoded_msg = encode(&42u64).unwrap();
+        assert_eq!(decode_canonical::<u64>(&encoded_msg), Some(42));
+        let mut non_minimal = vec![251u8, 0, TOFN_SERIALIZATION_VERSION as u8];
+        non_minimal.extend_from_slice(&encoded_msg[1..]);
+        assert_eq!(decode::<u64>(&non_minimal), Some(42));
+        assert_eq!(decode_canonical::<u64>(&non_minimal), None);
+
+        // and for the routing information of a message: round 2 as a 3-byte varint
+        struct TestIndex;
+        let msg = encode_message::<TestIndex>(
+            vec![42u8; 10],
+            TypedUsize::from_usize(3),
+            2,
+            MsgType::Bcast,
+            ExpectedMsgTypes::BcastOnly,
+        )
+        .unwrap();
+        assert!(decode_message_canonical::<TestIndex>(&msg).is_some());
+        // the round is followed by the payload length, the payload and the expected msg types
+        let round_index = msg.len() - 13;
+        assert_eq!(msg[round_index], 2);
+        let mut non_minimal = msg[..round_index].to_vec();
+        non_minimal.extend_from_slice(&[251, 0, 2]);
+        non_minimal.extend_from_slice(&msg[round_index + 1..]);
+        non_minimal[1] += 2; // length of the inner encoding, which follows the version
+        assert_eq!(
+            decode_message_ref::<TestIndex>(&non_minimal).unwrap().round,
+            2
+        );
+        assert!(decode_message_canonical::<TestIndex>(&non_minimal).is_none());
+    }
+
+    #[test]
+    fn split_message_chunks() {
+        struct TestIndex;
+        let msg = encode_message::<TestIndex>(
+            vec![42u8; 1000],
+            TypedUsize::from_usize(3),
+            2,
+            MsgType::P2p {
+                to: TypedUsize::from_usize(5),
+            },
+            ExpectedMsgTypes::BcastAndP2p,
+        )
+        .unwrap();
+
+        // small messages are not split
+        assert_eq!(split_message(&msg, msg.len()).unwrap(), vec![msg.clone()]);
+
+        let chunks = split_message(&msg, 100).unwrap();
+        assert!(chunks.len() > 1);
+        let mut reassembled = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert!(chunk.len() <= 100);
+            let chunk = decode_message::<TestIndex>(chunk).unwrap();
+            assert!(matches!(
+                chunk.msg_type,
+                MsgType::Chunk { to: Some(to), index, count }
+                    if to.as_usize() == 5 && index == i && count == chunks.len()
+            ));
+            assert_eq!(chunk.from.as_usize(), 3);
+            assert_eq!(chunk.round, 2);
+            reassembled.extend_from_slice(&chunk.payload);
+        }
+        assert_eq!(reassembled, msg);
+
+        // mtu too small for the chunk overhead
+        assert!(split_message(&msg, 5).is_err());
+    }
+
+    #[test]
+    fn peek_routing_header() {
+        struct TestIndex;
+        let msg = encode_message::<TestIndex>(
+            vec![42u8; 1000],
+            TypedUsize::from_usize(3),
+            2,
+            MsgType::P2p {
+                to: TypedUsize::from_usize(5),
+            },
+            ExpectedMsgTypes::BcastAndP2p,
+        )
+        .unwrap();
+        let header = peek_header::<TestIndex>(&msg).unwrap();
+        assert_eq!(header.from.as_usize(), 3);
+        assert_eq!(header.round, 2);
+        assert_eq!(header.to().unwrap().as_usize(), 5);
+
+        // chunks are routed like the message they belong to
+        for chunk in split_message(&msg, 100).unwrap() {
+            assert_eq!(
+                peek_header::<TestIndex>(&chunk)
+                    .unwrap()
+                    .to()
+                    .unwrap()
+                    .as_usize(),
+                5
+            );
+        }
+
+        let bcast = encode_message::<TestIndex>(
+            vec![],
+            TypedUsize::from_usize(0),
+            0,
+            MsgType::Bcast,
+            ExpectedMsgTypes::BcastOnly,
+        )
+        .unwrap();
+        assert!(peek_header::<TestIndex>(&bcast).unwrap().to().is_none());
+
+        assert!(peek_header::<TestIndex>(&msg[..10]).is_none());
+    }
+
     #[test]
     fn large_message() {
         // 5 bytes for length, and 1 byte for each int
@@ -223,6 +669,7 @@ pub mod malicious {
         encode_message(
             b"these bytes are corrupted 1234".to_vec(),
             wire_bytes.from,
+            wire_bytes.round,
             wire_bytes.msg_type,
             wire_bytes.expected_msg_types,
         )
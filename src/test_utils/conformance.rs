@@ -0,0 +1,232 @@
+//! Conformance harness for fault identification.
+//!
+//! For a given protocol and faulty share, [check_single_faults] enumerates every message the faulter sends in every round,
+//! injects each [FaultType] into that message, runs the protocol to completion
+//! and asserts that every honest share outputs exactly `{faulter's party: expected fault}`.
+//!
+//! Any protocol built on the tofn SDK should pass this harness for every choice of faulter.
+
+use alloc::vec::Vec;
+
+use tracing::info;
+
+use crate::{
+    collections::{FillVecMap, TypedUsize, VecMap},
+    sdk::{
+        api::{Fault, MsgType, Protocol, ProtocolFaulters, TofnFatal, TofnResult},
+        local::execute_round_with,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultType {
+    /// The message is never delivered
+    Withhold,
+    /// The message is replaced by bytes that do not decode
+    Garble,
+    /// The message is delivered twice
+    Duplicate,
+    /// The message keeps a valid header but carries a corrupted payload
+    #[cfg(feature = "malicious")]
+    CorruptPayload,
+}
+
+impl FaultType {
+    pub const ALL: &'static [FaultType] = &[
+        FaultType::Withhold,
+        FaultType::Garble,
+        FaultType::Duplicate,
+        #[cfg(feature = "malicious")]
+        FaultType::CorruptPayload,
+    ];
+
+    /// The fault honest parties should attribute to the faulter
+    pub fn expected_fault(&self) -> Fault {
+        match self {
+            FaultType::Withhold => Fault::MissingMessage,
+            _ => Fault::CorruptedMessage,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    Bcast,
+    /// `to` is a share index
+    P2p {
+        to: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConformanceCase {
+    /// Round in which the fault occurs, index starts at 1
+    pub round: usize,
+    /// Share index of the faulter
+    pub faulter: usize,
+    pub msg: Msg,
+    pub fault_type: FaultType,
+}
+
+/// Run every case in [conformance_cases] against fresh parties from `new_parties`.
+/// Panic if any honest share outputs anything other than the expected faulters.
+/// Return the number of cases checked.
+pub fn check_single_faults<F, K, P>(
+    mut new_parties: impl FnMut() -> VecMap<K, Protocol<F, K, P>>,
+    faulter: usize,
+) -> TofnResult<usize>
+where
+    P: core::fmt::Debug + PartialEq,
+{
+    let cases = conformance_cases(new_parties(), faulter)?;
+    for case in cases.iter() {
+        check_case(new_parties(), case)?;
+    }
+    Ok(cases.len())
+}
+
+/// Execute `parties` honestly and list a case for each fault type injected into each message sent by `faulter`.
+/// For p2ps only the message to the lowest-indexed peer is listed.
+pub fn conformance_cases<F, K, P>(
+    mut parties: VecMap<K, Protocol<F, K, P>>,
+    faulter: usize,
+) -> TofnResult<Vec<ConformanceCase>> {
+    let mut cases = Vec::new();
+    let mut round = 0;
+    while all_not_done(&parties) {
+        round += 1;
+        let faulter_round = match parties.get(TypedUsize::from_usize(faulter))? {
+            Protocol::NotDone(faulter_round) => faulter_round,
+            Protocol::Done(_) => return Err(TofnFatal),
+        };
+        let mut msgs = Vec::new();
+        if faulter_round.bcast_out().is_some() {
+            msgs.push(Msg::Bcast);
+        }
+        if let Some(p2ps) = faulter_round.p2ps_out() {
+            if let Some((to, _)) = p2ps.iter().next() {
+                msgs.push(Msg::P2p { to: to.as_usize() });
+            }
+        }
+        for msg in msgs {
+            cases.extend(FaultType::ALL.iter().map(|&fault_type| ConformanceCase {
+                round,
+                faulter,
+                msg,
+                fault_type,
+            }));
+        }
+        parties = next_round(parties, None, round)?;
+    }
+    Ok(cases)
+}
+
+/// Execute `parties` with `case` injected.
+/// Panic if any honest share outputs anything other than the expected faulters.
+pub fn check_case<F, K, P>(
+    mut parties: VecMap<K, Protocol<F, K, P>>,
+    case: &ConformanceCase,
+) -> TofnResult<()>
+where
+    P: core::fmt::Debug + PartialEq,
+{
+    info!("conformance: {:?}", case);
+    let faulter = TypedUsize::<K>::from_usize(case.faulter);
+
+    let expected_faulters: ProtocolFaulters<P> = match parties.get(faulter)? {
+        Protocol::NotDone(round) => {
+            let party_share_counts = round.info().party_share_counts();
+            let mut faulters = FillVecMap::with_size(party_share_counts.party_count());
+            faulters.set(
+                party_share_counts.share_to_party_id(faulter)?,
+                case.fault_type.expected_fault(),
+            )?;
+            faulters
+        }
+        Protocol::Done(_) => return Err(TofnFatal),
+    };
+
+    let mut round = 0;
+    while parties
+        .iter()
+        .any(|(i, party)| i != faulter && matches!(party, Protocol::NotDone(_)))
+    {
+        round += 1;
+        if round > case.round && !all_not_done(&parties) {
+            // some honest shares are done and won't send more messages
+            break;
+        }
+        parties = next_round(parties, Some(case), round)?;
+    }
+
+    for (i, party) in parties.iter() {
+        if i == faulter {
+            continue;
+        }
+        match party {
+            Protocol::Done(Err(faulters)) => assert!(
+                *faulters == expected_faulters,
+                "{:?}: share {} output faulters {:?}, expected {:?}",
+                case,
+                i,
+                faulters,
+                expected_faulters
+            ),
+            Protocol::Done(Ok(_)) => panic!("{:?}: share {} output success", case, i),
+            Protocol::NotDone(_) => panic!("{:?}: share {} did not finish", case, i),
+        }
+    }
+    Ok(())
+}
+
+fn all_not_done<F, K, P>(parties: &VecMap<K, Protocol<F, K, P>>) -> bool {
+    parties
+        .iter()
+        .all(|(_, party)| matches!(party, Protocol::NotDone(_)))
+}
+
+/// Deliver all messages for the current round, applying `case` if it applies to this round,
+/// then execute the next round for every share that is not done.
+fn next_round<F, K, P>(
+    parties: VecMap<K, Protocol<F, K, P>>,
+    case: Option<&ConformanceCase>,
+    round: usize,
+) -> TofnResult<VecMap<K, Protocol<F, K, P>>> {
+    let case = case.filter(|case| case.round == round);
+
+    #[cfg(feature = "malicious")]
+    let mut parties = parties;
+    #[cfg(feature = "malicious")]
+    if let Some(case) = case.filter(|case| case.fault_type == FaultType::CorruptPayload) {
+        if let Protocol::NotDone(faulter_round) =
+            parties.get_mut(TypedUsize::from_usize(case.faulter))?
+        {
+            faulter_round.corrupt_msg_payload(match case.msg {
+                Msg::Bcast => MsgType::Bcast,
+                Msg::P2p { to } => MsgType::P2p {
+                    to: TypedUsize::from_usize(to),
+                },
+            })?;
+        }
+    }
+
+    // deliver, applying the fault
+    execute_round_with(parties, |from, msg_type, bytes| {
+        let msg = match msg_type {
+            MsgType::P2p { to } => Msg::P2p { to: to.as_usize() },
+            _ => Msg::Bcast,
+        };
+        match case {
+            Some(case) if case.faulter == from.as_usize() && case.msg == msg => {
+                match case.fault_type {
+                    FaultType::Withhold => Vec::new(),
+                    FaultType::Garble => alloc::vec![b"these bytes are garbled".to_vec()],
+                    FaultType::Duplicate => alloc::vec![bytes.clone(), bytes],
+                    #[cfg(feature = "malicious")]
+                    FaultType::CorruptPayload => alloc::vec![bytes],
+                }
+            }
+            _ => alloc::vec![bytes],
+        }
+    })
+}
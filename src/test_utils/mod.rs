@@ -0,0 +1,4 @@
+//! Utilities for testing tofn integrations.
+//! Enabled by the `test-utils` crate feature.
+pub mod conformance;
+pub mod network_sim;
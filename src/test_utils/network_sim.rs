@@ -0,0 +1,396 @@
+//! Discrete-event network simulator for protocol execution.
+//!
+//! Each share runs independently: it executes its next round as soon as it has received all expected messages,
+//! or when its round timeout expires, whichever comes first.
+//! Messages travel over per-link channels with configurable latency, loss, duplication and reordering.
+//! Time is simulated in abstract ticks; nothing sleeps.
+//!
+//! Each simulated node behaves like a well-behaved tofn integrator:
+//! * messages that arrive ahead of the recipient's current round are buffered until that round starts
+//! * messages that arrive after the recipient has left their round are discarded
+//! * exact duplicates of an already delivered message are discarded
+
+use alloc::{
+    collections::{BTreeMap, BinaryHeap},
+    vec::Vec,
+};
+use core::cmp::Reverse;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use tracing::{debug, error, warn};
+
+use crate::{
+    collections::{TypedUsize, VecMap},
+    sdk::api::{BytesVec, Protocol, ProtocolOutput, TofnFatal, TofnResult},
+};
+
+/// Simulated time
+pub type Ticks = u64;
+
+/// Delivery characteristics of a one-way link between two shares
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkConfig {
+    /// Latency is sampled uniformly from `min_latency..=max_latency`
+    pub min_latency: Ticks,
+    pub max_latency: Ticks,
+    /// Probability in `[0, 1]` that a message is lost
+    pub drop_probability: f64,
+    /// Probability in `[0, 1]` that a message is delivered a second time
+    pub duplicate_probability: f64,
+    /// If `false` then messages on this link arrive in the order they were sent
+    pub reorder: bool,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            min_latency: 1,
+            max_latency: 1,
+            drop_probability: 0.0,
+            duplicate_probability: 0.0,
+            reorder: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkConfig {
+    /// Applies to every link not listed in `links`
+    pub default_link: LinkConfig,
+    /// Per-link overrides keyed by `(from, to)` share index
+    pub links: BTreeMap<(usize, usize), LinkConfig>,
+    /// A share executes its next round no later than this many ticks after the current round began
+    pub round_timeout: Ticks,
+    /// Seed for all random choices made by the simulator
+    pub seed: u64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            default_link: LinkConfig::default(),
+            links: BTreeMap::new(),
+            round_timeout: 1000,
+            seed: 0,
+        }
+    }
+}
+
+impl LinkConfig {
+    /// Fail unless probabilities are in `[0, 1]` and `min_latency <= max_latency`
+    fn check(&self) -> TofnResult<()> {
+        let probabilities = [self.drop_probability, self.duplicate_probability];
+        if probabilities.iter().any(|p| !(0.0..=1.0).contains(p)) {
+            error!("link probabilities {:?} are not in [0, 1]", probabilities);
+            return Err(TofnFatal);
+        }
+        if self.min_latency > self.max_latency {
+            error!(
+                "link min_latency {} exceeds max_latency {}",
+                self.min_latency, self.max_latency
+            );
+            return Err(TofnFatal);
+        }
+        Ok(())
+    }
+}
+
+impl NetworkConfig {
+    fn check(&self) -> TofnResult<()> {
+        self.default_link.check()?;
+        self.links.values().try_for_each(LinkConfig::check)
+    }
+
+    fn link(&self, from: usize, to: usize) -> &LinkConfig {
+        self.links.get(&(from, to)).unwrap_or(&self.default_link)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkStats {
+    pub sent: usize,
+    pub dropped: usize,
+    pub duplicated: usize,
+    /// Messages that arrived after the recipient had left the round
+    pub stale: usize,
+    /// Rounds executed because the round timeout expired
+    pub timeouts: usize,
+}
+
+pub struct SimulationOutput<F, K, P> {
+    pub outputs: VecMap<K, ProtocolOutput<F, P>>,
+    /// Time at which the last share finished
+    pub elapsed: Ticks,
+    pub stats: NetworkStats,
+}
+
+/// Run `parties` to completion over a simulated network.
+/// Fail if a [LinkConfig] in `config` is invalid.
+pub fn simulate<F, K, P>(
+    parties: VecMap<K, Protocol<F, K, P>>,
+    config: &NetworkConfig,
+) -> TofnResult<SimulationOutput<F, K, P>> {
+    config.check()?;
+    Simulation::new(parties, config).run()
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Event {
+    Deliver {
+        to: usize,
+        from: usize,
+        round: usize,
+        bytes: BytesVec,
+    },
+    Timeout {
+        share: usize,
+        round: usize,
+    },
+}
+
+struct Node<F, K, P> {
+    protocol: Option<Protocol<F, K, P>>,
+    round: usize, // 1-based
+    received: Vec<(usize, BytesVec)>,
+    future: Vec<(usize, usize, BytesVec)>,
+    done_at: Ticks,
+}
+
+struct Simulation<'a, F, K, P> {
+    nodes: Vec<Node<F, K, P>>,
+    config: &'a NetworkConfig,
+    rng: ChaCha20Rng,
+    now: Ticks,
+    seq: u64, // tie-breaker for events scheduled at the same time
+    events: BinaryHeap<Reverse<(Ticks, u64, Event)>>,
+    last_arrival: BTreeMap<(usize, usize), Ticks>,
+    stats: NetworkStats,
+}
+
+impl<'a, F, K, P> Simulation<'a, F, K, P> {
+    fn new(parties: VecMap<K, Protocol<F, K, P>>, config: &'a NetworkConfig) -> Self {
+        Self {
+            nodes: parties
+                .into_iter()
+                .map(|(_, protocol)| Node {
+                    protocol: Some(protocol),
+                    round: 1,
+                    received: Vec::new(),
+                    future: Vec::new(),
+                    done_at: 0,
+                })
+                .collect(),
+            config,
+            rng: ChaCha20Rng::seed_from_u64(config.seed),
+            now: 0,
+            seq: 0,
+            events: BinaryHeap::new(),
+            last_arrival: BTreeMap::new(),
+            stats: NetworkStats::default(),
+        }
+    }
+
+    fn run(mut self) -> TofnResult<SimulationOutput<F, K, P>> {
+        for share in 0..self.nodes.len() {
+            self.start_round(share)?;
+        }
+
+        while let Some(Reverse((time, _, event))) = self.events.pop() {
+            self.now = time;
+            match event {
+                Event::Deliver {
+                    to,
+                    from,
+                    round,
+                    bytes,
+                } => self.deliver(to, from, round, bytes)?,
+                Event::Timeout { share, round } => {
+                    if self.nodes[share].round == round && self.is_not_done(share) {
+                        debug!("t={}: share {} round {} timed out", self.now, share, round);
+                        self.stats.timeouts += 1;
+                        self.execute_next_round(share)?;
+                    }
+                }
+            }
+        }
+
+        let elapsed = self.nodes.iter().map(|n| n.done_at).max().unwrap_or(0);
+        let outputs = self
+            .nodes
+            .into_iter()
+            .map(|node| match node.protocol {
+                Some(Protocol::Done(output)) => Ok(output),
+                _ => {
+                    warn!("simulation ended but a share is not done");
+                    Err(TofnFatal)
+                }
+            })
+            .collect::<TofnResult<_>>()?;
+
+        Ok(SimulationOutput {
+            outputs,
+            elapsed,
+            stats: self.stats,
+        })
+    }
+
+    fn is_not_done(&self, share: usize) -> bool {
+        matches!(self.nodes[share].protocol, Some(Protocol::NotDone(_)))
+    }
+
+    fn schedule(&mut self, time: Ticks, event: Event) {
+        self.seq += 1;
+        self.events.push(Reverse((time, self.seq, event)));
+    }
+
+    /// Send this share's outgoing messages for its current round and arm its round timeout.
+    fn start_round(&mut self, share: usize) -> TofnResult<()> {
+        let round_num = self.nodes[share].round;
+        let round = match &self.nodes[share].protocol {
+            Some(Protocol::NotDone(round)) => round,
+            _ => return Ok(()),
+        };
+
+        // every message is sent to every share, including p2ps addressed to other shares
+        let mut outgoing: Vec<BytesVec> = Vec::new();
+        if let Some(bcast) = round.bcast_out() {
+            outgoing.push(bcast.clone());
+        }
+        if let Some(p2ps) = round.p2ps_out() {
+            outgoing.extend(p2ps.iter().map(|(_, bytes)| bytes.clone()));
+        }
+
+        for bytes in outgoing {
+            for to in 0..self.nodes.len() {
+                self.send(share, to, round_num, bytes.clone());
+            }
+        }
+        self.schedule(
+            self.now + self.config.round_timeout,
+            Event::Timeout {
+                share,
+                round: round_num,
+            },
+        );
+
+        // deliver messages that arrived early
+        let (ready, future) = core::mem::take(&mut self.nodes[share].future)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(_, round, _)| *round == round_num);
+        self.nodes[share].future = future;
+        for (from, _, bytes) in ready {
+            self.deliver(share, from, round_num, bytes)?;
+        }
+
+        Ok(())
+    }
+
+    fn send(&mut self, from: usize, to: usize, round: usize, bytes: BytesVec) {
+        self.stats.sent += 1;
+
+        // messages to self are delivered instantly and reliably
+        if from == to {
+            self.schedule(
+                self.now,
+                Event::Deliver {
+                    to,
+                    from,
+                    round,
+                    bytes,
+                },
+            );
+            return;
+        }
+
+        let link = self.config.link(from, to).clone();
+        if self.rng.gen_bool(link.drop_probability) {
+            debug!(
+                "t={}: drop msg {} -> {} round {}",
+                self.now, from, to, round
+            );
+            self.stats.dropped += 1;
+            return;
+        }
+
+        let copies = if self.rng.gen_bool(link.duplicate_probability) {
+            self.stats.duplicated += 1;
+            2
+        } else {
+            1
+        };
+        for _ in 0..copies {
+            let mut arrival = self.now + self.rng.gen_range(link.min_latency..=link.max_latency);
+            if !link.reorder {
+                let last_arrival = self.last_arrival.entry((from, to)).or_insert(0);
+                arrival = arrival.max(*last_arrival);
+                *last_arrival = arrival;
+            }
+            self.schedule(
+                arrival,
+                Event::Deliver {
+                    to,
+                    from,
+                    round,
+                    bytes: bytes.clone(),
+                },
+            );
+        }
+    }
+
+    fn deliver(&mut self, to: usize, from: usize, round: usize, bytes: BytesVec) -> TofnResult<()> {
+        if !self.is_not_done(to) {
+            return Ok(());
+        }
+        let node = &mut self.nodes[to];
+        if round > node.round {
+            node.future.push((from, round, bytes));
+            return Ok(());
+        }
+        if round < node.round {
+            self.stats.stale += 1;
+            return Ok(());
+        }
+        if node.received.iter().any(|(f, b)| *f == from && *b == bytes) {
+            return Ok(());
+        }
+
+        let round_ref = match &mut node.protocol {
+            Some(Protocol::NotDone(round)) => round,
+            _ => return Ok(()),
+        };
+        let from_party_id = round_ref
+            .info()
+            .party_share_counts()
+            .share_to_party_id(TypedUsize::<K>::from_usize(from))?;
+        round_ref.msg_in(from_party_id, &bytes)?;
+        let ready = !round_ref.expecting_more_msgs_this_round();
+        node.received.push((from, bytes));
+
+        if ready {
+            self.execute_next_round(to)?;
+        }
+        Ok(())
+    }
+
+    fn execute_next_round(&mut self, share: usize) -> TofnResult<()> {
+        let node = &mut self.nodes[share];
+        let round = match node.protocol.take() {
+            Some(Protocol::NotDone(round)) => round,
+            _ => return Err(TofnFatal),
+        };
+        let next = round.execute_next_round()?;
+        let done = matches!(next, Protocol::Done(_));
+        node.protocol = Some(next);
+        node.received.clear();
+
+        if done {
+            node.done_at = self.now;
+            debug!("t={}: share {} done", self.now, share);
+            Ok(())
+        } else {
+            node.round += 1;
+            self.start_round(share)
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use super::r1;
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    crypto_tools::paillier::{Ciphertext, Plaintext},
+    sdk::{
+        api::{PartyShareCounts, Protocol, TofnFatal, TofnResult},
+        implementer_api::{new_protocol, ProtocolBuilder},
+    },
+    threshold_paillier::keygen::{GroupPublicInfo, KeygenPartyId, KeygenShareId, ShareSecretInfo},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+/// Maximum byte length of messages exchanged during decrypt.
+/// The only decrypt message is a partial decryption whose proof grows with `log(share_count!)`,
+/// which fits for any share count up to [MAX_TOTAL_SHARE_COUNT](super::super::keygen::MAX_TOTAL_SHARE_COUNT).
+pub const MAX_MSG_LEN: usize = 5_000;
+
+pub type DecryptProtocol = Protocol<Plaintext, DecryptShareId, DecryptPartyId>;
+pub type DecryptProtocolBuilder = ProtocolBuilder<Plaintext, DecryptShareId>;
+
+// This includes all shares participating in the current decrypt protocol
+pub type KeygenShareIds = VecMap<DecryptShareId, TypedUsize<KeygenShareId>>;
+// This is the set of parties participating in the current decrypt protocol
+pub type DecryptParties = Subset<KeygenPartyId>;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DecryptShareId;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DecryptPartyId;
+
+/// Initialize a new decrypt protocol for `ciphertext`.
+/// Assume `group`, `share` are valid and check `decrypt_parties` and `ciphertext` against it.
+pub fn new_decrypt(
+    group: &GroupPublicInfo,
+    share: &ShareSecretInfo,
+    decrypt_parties: &DecryptParties,
+    ciphertext: &Ciphertext,
+) -> TofnResult<DecryptProtocol> {
+    if !group.encryption_key().validate_ciphertext(ciphertext) {
+        error!("invalid ciphertext");
+        return Err(TofnFatal);
+    }
+
+    let all_keygen_ids = VecMap::from_vec(
+        group
+            .party_share_counts()
+            .share_id_subset(decrypt_parties)?,
+    );
+
+    // participant share count must be at least threshold + 1
+    if all_keygen_ids.len() <= group.threshold() {
+        error!(
+            "not enough participant shares: threshold [{}], participants [{}]",
+            group.threshold(),
+            all_keygen_ids.len(),
+        );
+        return Err(TofnFatal);
+    }
+
+    // find my keygen share_id
+    let my_decrypt_id = all_keygen_ids
+        .iter()
+        .find(|(_, &k)| k == share.index())
+        .map(|(s, _)| s)
+        .ok_or_else(|| {
+            error!("my keygen share_id {} is not a participant", share.index());
+            TofnFatal
+        })?;
+
+    let decrypt_party_share_counts =
+        PartyShareCounts::from_vec(group.party_share_counts().subset(decrypt_parties)?)?;
+
+    let round2 = r1::start(group.clone(), share, ciphertext.clone(), all_keygen_ids)?;
+
+    new_protocol(
+        decrypt_party_share_counts,
+        my_decrypt_id,
+        MAX_MSG_LEN,
+        round2,
+    )
+}
@@ -0,0 +1,8 @@
+mod api;
+pub use api::*;
+
+mod r1;
+mod r2;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,43 @@
+use alloc::boxed::Box;
+
+use super::{r2, DecryptProtocolBuilder, KeygenShareIds};
+use crate::{
+    crypto_tools::paillier::{threshold::PartialDecryption, Ciphertext},
+    sdk::{
+        api::TofnResult,
+        implementer_api::{serialize, RoundBuilder},
+    },
+    threshold_paillier::keygen::{GroupPublicInfo, ShareSecretInfo},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Bcast {
+    pub(super) partial_decryption: PartialDecryption,
+}
+
+pub(super) fn start(
+    group: GroupPublicInfo,
+    share: &ShareSecretInfo,
+    ciphertext: Ciphertext,
+    all_keygen_ids: KeygenShareIds,
+) -> TofnResult<DecryptProtocolBuilder> {
+    let partial_decryption = group.params().partial_decrypt(
+        &mut rand::thread_rng(),
+        share.index().as_usize(),
+        share.dk_share(),
+        &ciphertext,
+    );
+
+    let bcast_out = Some(serialize(&Bcast { partial_decryption })?);
+
+    Ok(DecryptProtocolBuilder::NotDone(RoundBuilder::new(
+        Box::new(r2::R2 {
+            group,
+            ciphertext,
+            all_keygen_ids,
+        }),
+        bcast_out,
+        None,
+    )))
+}
@@ -0,0 +1,123 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use tracing::{error, warn};
+
+use super::{r1, DecryptShareId, KeygenShareIds};
+use crate::{
+    collections::{zip2, FillVecMap, P2ps},
+    crypto_tools::paillier::{Ciphertext, Plaintext},
+    sdk::{
+        api::{Fault::ProtocolFault, TofnFatal, TofnResult},
+        implementer_api::{Executer, ProtocolBuilder, ProtocolInfo},
+    },
+    threshold_paillier::keygen::GroupPublicInfo,
+};
+
+pub(super) struct R2 {
+    pub(super) group: GroupPublicInfo,
+    pub(super) ciphertext: Ciphertext,
+    pub(super) all_keygen_ids: KeygenShareIds,
+}
+
+impl Executer for R2 {
+    type FinalOutput = Plaintext;
+    type Index = DecryptShareId;
+    type Bcast = r1::Bcast;
+    type P2p = ();
+
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<ProtocolBuilder<Self::FinalOutput, Self::Index>> {
+        let my_decrypt_id = info.my_id();
+        let threshold = self.group.threshold();
+        let mut faulters = info.new_fillvecmap();
+        let mut valid_partial_decryptions = Vec::with_capacity(threshold + 1);
+
+        for (peer_decrypt_id, bcast_option, p2ps_option) in zip2(bcasts_in, p2ps_in) {
+            // anyone who did not send a bcast is a faulter
+            let partial_decryption = match bcast_option {
+                Some(bcast) => bcast.partial_decryption,
+                None => {
+                    warn!(
+                        "peer {} says: missing bcast from peer {} in round 2",
+                        my_decrypt_id, peer_decrypt_id
+                    );
+                    faulters.set(peer_decrypt_id, ProtocolFault)?;
+                    continue;
+                }
+            };
+
+            // anyone who sent p2ps is a faulter
+            if p2ps_option.is_some() {
+                warn!(
+                    "peer {} says: unexpected p2ps from peer {} in round 2",
+                    my_decrypt_id, peer_decrypt_id
+                );
+                faulters.set(peer_decrypt_id, ProtocolFault)?;
+                continue;
+            }
+
+            // verify partial decryption
+            let peer_keygen_id = *self.all_keygen_ids.get(peer_decrypt_id)?;
+            let verification_key = self.group.all_verification_keys().get(peer_keygen_id)?;
+
+            if !self.group.params().verify_partial_decryption(
+                peer_keygen_id.as_usize(),
+                verification_key,
+                &self.ciphertext,
+                &partial_decryption,
+            ) {
+                warn!(
+                    "peer {} says: fail partial decryption verify from peer {} in round 2",
+                    my_decrypt_id, peer_decrypt_id
+                );
+                faulters.set(peer_decrypt_id, ProtocolFault)?;
+                continue;
+            }
+
+            valid_partial_decryptions.push((peer_keygen_id.as_usize(), partial_decryption));
+
+            // have we got enough valid partial decryptions yet?
+            if valid_partial_decryptions.len() > threshold {
+                break;
+            }
+        }
+
+        // not enough valid partial decryptions => sad outcome
+        if valid_partial_decryptions.len() <= threshold {
+            warn!(
+                "peer {} says: insufficient valid partial decryptions {} to exceed threshold {}",
+                my_decrypt_id,
+                valid_partial_decryptions.len(),
+                threshold
+            );
+
+            // sanity check
+            if faulters.is_empty() {
+                error!(
+                    "peer {} says: insufficient valid partial decryptions but no faulters",
+                    my_decrypt_id
+                );
+                return Err(TofnFatal);
+            }
+
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        let partials: Vec<_> = valid_partial_decryptions
+            .iter()
+            .map(|(index, partial)| (*index, partial))
+            .collect();
+        let plaintext = self.group.params().combine(&partials)?;
+
+        Ok(ProtocolBuilder::Done(Ok(plaintext)))
+    }
+
+    #[cfg(test)]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
@@ -0,0 +1,62 @@
+use alloc::vec;
+
+use super::*;
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    crypto_tools::paillier::{keygen_unsafe, Plaintext},
+    sdk::local::execute_honest,
+    threshold_paillier::keygen::{deal, KeygenPartyShareCounts},
+};
+use tracing_test::traced_test;
+
+#[test]
+#[traced_test]
+fn basic_correctness() {
+    let mut rng = rand::thread_rng();
+    let (ek, dk) = keygen_unsafe(&mut rng).unwrap();
+    let party_share_counts = KeygenPartyShareCounts::from_vec(vec![1, 2, 1]).unwrap();
+    let threshold = 2;
+    let key_shares = deal(&mut rng, &dk, party_share_counts.clone(), threshold).unwrap();
+    let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+    assert_eq!(group.encryption_key(), &ek);
+
+    let plaintext = ek.random_plaintext();
+    let (ciphertext, _) = group.encryption_key().encrypt(&plaintext);
+
+    // parties 0 and 1 hold threshold + 1 shares
+    let mut decrypt_parties = Subset::with_max_size(party_share_counts.party_count());
+    decrypt_parties.add(TypedUsize::from_usize(0)).unwrap();
+    decrypt_parties.add(TypedUsize::from_usize(1)).unwrap();
+
+    let parties: VecMap<_, _> = party_share_counts
+        .share_id_subset(&decrypt_parties)
+        .unwrap()
+        .into_iter()
+        .map(|keygen_id| {
+            let key_share = key_shares.get(keygen_id).unwrap();
+            new_decrypt(
+                key_share.group(),
+                key_share.share(),
+                &decrypt_parties,
+                &ciphertext,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    for (_, output) in execute_honest(parties).unwrap() {
+        assert_eq!(output, plaintext);
+    }
+
+    // party 2 alone does not have enough shares
+    let mut decrypt_parties = Subset::with_max_size(party_share_counts.party_count());
+    decrypt_parties.add(TypedUsize::from_usize(2)).unwrap();
+    let key_share = key_shares.get(TypedUsize::from_usize(3)).unwrap();
+    assert!(new_decrypt(
+        key_share.group(),
+        key_share.share(),
+        &decrypt_parties,
+        &ciphertext
+    )
+    .is_err());
+}
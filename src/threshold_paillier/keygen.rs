@@ -0,0 +1,133 @@
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{
+    collections::{TypedUsize, VecMap},
+    crypto_tools::paillier::{
+        threshold::{self, DecryptionKeyShare, PublicParams, VerificationKey},
+        DecryptionKey, EncryptionKey,
+    },
+    sdk::api::{PartyShareCounts, TofnFatal, TofnResult},
+};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KeygenShareId;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KeygenPartyId;
+
+pub type KeygenPartyShareCounts = PartyShareCounts<KeygenPartyId>;
+
+pub const MAX_TOTAL_SHARE_COUNT: usize = 1000;
+
+/// output of [deal]: store this struct in tofnd kvstore
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecretKeyShare {
+    group: GroupPublicInfo,
+    share: ShareSecretInfo,
+}
+
+/// `GroupPublicInfo` is the same for all shares
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GroupPublicInfo {
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+    params: PublicParams,
+    all_verification_keys: VecMap<KeygenShareId, VerificationKey>,
+}
+
+/// `ShareSecretInfo` secret info unique to each share
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareSecretInfo {
+    index: TypedUsize<KeygenShareId>,
+    dk_share: DecryptionKeyShare,
+}
+
+/// Split `dk` into one [SecretKeyShare] for each share in `party_share_counts`,
+/// any `threshold + 1` of which can decrypt.
+/// The dealer must erase `dk` and the output once the shares are handed out.
+pub fn deal(
+    rng: &mut (impl CryptoRng + RngCore),
+    dk: &DecryptionKey,
+    party_share_counts: KeygenPartyShareCounts,
+    threshold: usize,
+) -> TofnResult<VecMap<KeygenShareId, SecretKeyShare>> {
+    let total_share_count = party_share_counts.total_share_count();
+    if total_share_count <= threshold || total_share_count > MAX_TOTAL_SHARE_COUNT {
+        error!(
+            "invalid (total_share_count, threshold, max_share_count): ({},{},{})",
+            total_share_count, threshold, MAX_TOTAL_SHARE_COUNT
+        );
+        return Err(TofnFatal);
+    }
+
+    let (params, verification_keys, dk_shares) =
+        threshold::deal(rng, dk, threshold, total_share_count)?;
+
+    let group = GroupPublicInfo {
+        party_share_counts,
+        threshold,
+        params,
+        all_verification_keys: VecMap::from_vec(verification_keys),
+    };
+
+    Ok(dk_shares
+        .into_iter()
+        .enumerate()
+        .map(|(index, dk_share)| SecretKeyShare {
+            group: group.clone(),
+            share: ShareSecretInfo {
+                index: TypedUsize::from_usize(index),
+                dk_share,
+            },
+        })
+        .collect())
+}
+
+impl GroupPublicInfo {
+    pub fn party_share_counts(&self) -> &KeygenPartyShareCounts {
+        &self.party_share_counts
+    }
+
+    pub fn share_count(&self) -> usize {
+        self.all_verification_keys.len()
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Encryption key for ciphertexts that the group can decrypt
+    pub fn encryption_key(&self) -> &EncryptionKey {
+        self.params.encryption_key()
+    }
+
+    pub fn all_verification_keys(&self) -> &VecMap<KeygenShareId, VerificationKey> {
+        &self.all_verification_keys
+    }
+
+    pub(super) fn params(&self) -> &PublicParams {
+        &self.params
+    }
+}
+
+impl ShareSecretInfo {
+    pub fn index(&self) -> TypedUsize<KeygenShareId> {
+        self.index
+    }
+
+    pub(super) fn dk_share(&self) -> &DecryptionKeyShare {
+        &self.dk_share
+    }
+}
+
+impl SecretKeyShare {
+    pub fn group(&self) -> &GroupPublicInfo {
+        &self.group
+    }
+
+    pub fn share(&self) -> &ShareSecretInfo {
+        &self.share
+    }
+}
@@ -0,0 +1,11 @@
+//! Threshold Paillier decryption.
+//!
+//! A trusted dealer splits a Paillier decryption key into shares with [keygen::deal].
+//! Anyone can encrypt under [keygen::GroupPublicInfo::encryption_key]
+//! and any `threshold + 1` shares decrypt with the [decrypt] protocol.
+//! Each share broadcasts a partial decryption with a proof of correctness,
+//! so that parties who send bad partial decryptions are identified as faulters.
+//!
+//! There is no distributed keygen: generation of a shared Paillier modulus without a dealer is out of scope.
+pub mod decrypt;
+pub mod keygen;
@@ -0,0 +1,132 @@
+//! Check tofn outputs without running protocols.
+//!
+//! This module is all that a light client or runtime needs to verify signatures and group keys,
+//! so it is compiled with the `verify-only` feature, which leaves out Paillier and the protocol rounds:
+//! ```toml
+//! tofn = { version = "0.1", default-features = false, features = ["verify-only"] }
+//! ```
+//! Groups and signatures are (de)serialized with [crate::sdk::api::deserialize] and [crate::sdk::api::serialize].
+//!
+//! MtA fault evidence contains Paillier ciphertexts and proofs,
+//! so it is verified with `gg20::verify_evidence` in the full build.
+use alloc::vec::Vec;
+
+use ecdsa::hazmat::VerifyPrimitive;
+use k256::PublicKey;
+
+use crate::crypto_tools::vss;
+pub use crate::{
+    crypto_tools::message_digest::MessageDigest,
+    group_metadata::{Backend, GroupMetadata},
+    sdk::api::{Signature, VerifyingKey},
+};
+
+/// Return `true` if `signature` is a valid ECDSA signature of `message_digest` under `verifying_key`
+pub fn verify_signature(
+    verifying_key: &VerifyingKey,
+    message_digest: &MessageDigest,
+    signature: &Signature,
+) -> bool {
+    PublicKey::from(verifying_key)
+        .as_affine()
+        .verify_prehashed(message_digest.into(), signature)
+        .is_ok()
+}
+
+/// Return `true` if every `threshold + 1` of the share pubkeys of a gg20 group
+/// interpolate to its verifying key.
+/// Multisig groups have no group key, so return `false`.
+pub fn verify_group_key(metadata: &GroupMetadata) -> bool {
+    let verifying_key = match (metadata.backend(), metadata.verifying_key()) {
+        (Backend::Gg20, Some(verifying_key)) => verifying_key,
+        _ => return false,
+    };
+    let share_pubkeys = metadata.share_pubkeys();
+    let share_count = match metadata.party_share_counts::<()>() {
+        Ok(party_share_counts) => party_share_counts.total_share_count(),
+        Err(_) => return false,
+    };
+    if share_count != share_pubkeys.len() || metadata.threshold() >= share_count {
+        return false;
+    }
+
+    let share_commits: Vec<_> = share_pubkeys
+        .iter()
+        .enumerate()
+        .map(|(index, pubkey)| vss::ShareCommit::from_point(index, pubkey.clone()))
+        .collect();
+    vss::share_commits_match(
+        &share_commits,
+        metadata.threshold(),
+        &PublicKey::from(&verifying_key).to_projective(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use super::*;
+    use crate::{crypto_tools::rng::dummy_secret_recovery_key, ecdsa};
+
+    #[test]
+    fn signature() {
+        let message_digest = MessageDigest::try_from(&[42; 32][..]).unwrap();
+        let key_pair = ecdsa::keygen(&dummy_secret_recovery_key(42), b"tofn nonce").unwrap();
+        let verifying_key =
+            VerifyingKey::from_sec1_bytes(key_pair.encoded_verifying_key()).unwrap();
+        let signature = ecdsa::sign(key_pair.signing_key(), &message_digest).unwrap();
+
+        assert!(verify_signature(
+            &verifying_key,
+            &message_digest,
+            &signature
+        ));
+        assert!(!verify_signature(
+            &verifying_key,
+            &MessageDigest::try_from(&[43; 32][..]).unwrap(),
+            &signature
+        ));
+    }
+
+    #[cfg(feature = "protocols")]
+    #[test]
+    fn group_key() {
+        use crate::{
+            collections::TypedUsize,
+            gg20, multisig,
+            sdk::{
+                api::{deserialize, serialize},
+                local::keygen_unsafe,
+            },
+        };
+
+        let party_share_counts =
+            gg20::keygen::KeygenPartyShareCounts::from_vec(alloc::vec![1, 2]).unwrap();
+        let key_shares = keygen_unsafe(party_share_counts, 1).unwrap();
+        let group = key_shares.get(TypedUsize::from_usize(0)).unwrap().group();
+        let metadata = GroupMetadata::from(group);
+        assert!(verify_group_key(&metadata));
+
+        // a light client only sees the serialized metadata
+        let bytes = serialize(&metadata).unwrap();
+        assert!(verify_group_key(&deserialize(&bytes).unwrap()));
+
+        // share pubkeys of another group key
+        let other = gg20::keygen::GroupPublicInfo::new(
+            group.party_share_counts().clone(),
+            group.threshold(),
+            (*group.y().as_ref() + k256::ProjectivePoint::GENERATOR).into(),
+            group.all_shares().clone(),
+        );
+        assert!(!verify_group_key(&GroupMetadata::from(&other)));
+
+        let multisig_shares = multisig::keygen::tests::execute_keygen(
+            &multisig::keygen::KeygenPartyShareCounts::from_vec(alloc::vec![2, 1]).unwrap(),
+            1,
+        );
+        assert!(!verify_group_key(&GroupMetadata::from(
+            multisig_shares.get(TypedUsize::from_usize(0)).unwrap()
+        )));
+    }
+}
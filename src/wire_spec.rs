@@ -0,0 +1,445 @@
+//! Machine-readable specification of tofn wire messages, for implementers of peers in other languages.
+//! Enabled by the `wire-spec` crate feature.
+//!
+//! [wire_spec] executes each protocol honestly in-process and traces the messages of every round
+//! with [serde_reflection](https://docs.rs/serde-reflection).
+//! The resulting [Registry]s can be fed to [serde-generate](https://docs.rs/serde-generate)
+//! to produce (de)serialization code.
+//! `cargo run --example wire_spec --features wire-spec` prints the spec as JSON.
+//!
+//! Encoding: every message is a `BytesVersioned` whose `payload` is a `WireBytesRef`
+//! (see [WireSpec::envelope]) whose `payload` is a bcast or p2p of [RoundSpec].
+//! Each layer is encoded with bincode using big-endian varint integers.
+//!
+//! Only messages sent during an honest execution are traced:
+//! enum variants that are sent only to accuse faulty peers are missing from the registries.
+
+use alloc::{string::String, vec::Vec};
+use core::convert::TryFrom;
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_reflection::{Format, Registry, Samples, Tracer, TracerConfig};
+use tracing::error;
+
+use crate::{
+    collections::{Subset, TypedUsize, VecMap},
+    crypto_tools::{paillier, rng::SecretRecoveryKey},
+    gg20, multisig,
+    sdk::{
+        api::{BytesVec, PartyShareCounts, Protocol, TofnFatal, TofnResult},
+        implementer_api::{deserialize, trace_envelope},
+    },
+    threshold_paillier,
+};
+
+const SESSION_NONCE: &[u8] = b"tofn-wire-spec";
+const PARTY_SHARE_COUNTS: [usize; 2] = [1, 2];
+const THRESHOLD: usize = 1;
+const MSG: [u8; 32] = [42; 32];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WireSpec {
+    /// Formats of `BytesVersioned` and `WireBytesRef`, the outer layers of every message
+    pub envelope: Registry,
+    pub protocols: Vec<ProtocolSpec>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolSpec {
+    pub protocol: &'static str,
+    /// Messages longer than this are rejected
+    pub max_msg_len: usize,
+    pub rounds: Vec<RoundSpec>,
+}
+
+/// Formats of the messages sent in a round
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundSpec {
+    /// The `round` field of the envelope, numbered from 0
+    pub round: usize,
+    /// `None` if the round has no bcast
+    pub bcast: Option<Format>,
+    /// `None` if the round has no p2ps
+    pub p2p: Option<Format>,
+    /// Named types used by `bcast` and `p2p`
+    pub registry: Registry,
+}
+
+impl WireSpec {
+    pub fn to_json(&self) -> TofnResult<String> {
+        serde_json::to_string_pretty(self).map_err(|err| {
+            error!("wire spec serialization failure: {}", err);
+            TofnFatal
+        })
+    }
+}
+
+/// Run every protocol and trace its messages. This takes a few seconds.
+pub fn wire_spec() -> TofnResult<WireSpec> {
+    let samples = Samples::new();
+    let mut tracer = Tracer::new(TracerConfig::default());
+    trace_envelope(&mut tracer, &samples).map_err(trace_err)?;
+    let envelope = tracer.registry().map_err(trace_err)?;
+
+    let mut protocols = Vec::new();
+
+    let (spec, multisig_key_shares) = trace_protocol("multisig::keygen", multisig_keygen()?)?;
+    protocols.push(spec);
+    protocols.push(trace_protocol("multisig::sign", multisig_sign(&multisig_key_shares)?)?.0);
+
+    let gg20_party_keygen_data: VecMap<gg20::keygen::KeygenPartyId, _> = party_share_counts()?
+        .iter()
+        .map(|(party_id, _)| {
+            gg20::keygen::create_party_keypair_and_zksetup_unsafe(
+                party_id,
+                &secret_recovery_key(party_id)?,
+                SESSION_NONCE,
+            )
+        })
+        .collect::<TofnResult<_>>()?;
+    let (spec, gg20_key_shares) =
+        trace_protocol("gg20::keygen", gg20_keygen(&gg20_party_keygen_data, false)?)?;
+    protocols.push(spec);
+    protocols.push(
+        trace_protocol(
+            "gg20::keygen::new_keygen_enrolled",
+            gg20_keygen(&gg20_party_keygen_data, true)?,
+        )?
+        .0,
+    );
+    protocols.push(trace_protocol("gg20::sign", gg20_sign(&gg20_key_shares)?)?.0);
+    protocols.push(trace_protocol("gg20::decrypt", gg20_decrypt(&gg20_key_shares)?)?.0);
+
+    protocols.push(trace_protocol("threshold_paillier::decrypt", threshold_paillier_decrypt()?)?.0);
+
+    Ok(WireSpec {
+        envelope,
+        protocols,
+    })
+}
+
+/// Trace the payloads of a bcast and a p2p of types `B`, `P`
+pub(crate) fn trace_round<B, P>(bcast: Option<&[u8]>, p2p: Option<&[u8]>) -> TofnResult<RoundSpec>
+where
+    B: Serialize + DeserializeOwned,
+    P: Serialize + DeserializeOwned,
+{
+    let mut samples = Samples::new();
+    let mut tracer = Tracer::new(TracerConfig::default());
+    let bcast = bcast
+        .map(|bytes| trace_payload::<B>(&mut tracer, &mut samples, bytes))
+        .transpose()?;
+    let p2p = p2p
+        .map(|bytes| trace_payload::<P>(&mut tracer, &mut samples, bytes))
+        .transpose()?;
+
+    Ok(RoundSpec {
+        round: 0,
+        bcast,
+        p2p,
+        // enums are traced only from values, so some of their variants may be missing
+        registry: tracer.registry_unchecked(),
+    })
+}
+
+fn trace_payload<T: Serialize + DeserializeOwned>(
+    tracer: &mut Tracer,
+    samples: &mut Samples,
+    bytes: &[u8],
+) -> TofnResult<Format> {
+    let value: T = deserialize(bytes).ok_or_else(|| {
+        error!("failed to deserialize {}", core::any::type_name::<T>());
+        TofnFatal
+    })?;
+    let (format, _) = tracer.trace_value(samples, &value).map_err(trace_err)?;
+    Ok(format)
+}
+
+fn trace_err(err: serde_reflection::Error) -> TofnFatal {
+    error!("wire spec tracing failure: {}", err);
+    TofnFatal
+}
+
+/// Execute an honest protocol to completion,
+/// tracing the messages of the first share in each round.
+fn trace_protocol<F, K, P>(
+    protocol: &'static str,
+    mut parties: VecMap<K, Protocol<F, K, P>>,
+) -> TofnResult<(ProtocolSpec, VecMap<K, F>)> {
+    let mut rounds = Vec::new();
+    let mut max_msg_len = 0;
+
+    while parties
+        .iter()
+        .all(|(_, party)| matches!(party, Protocol::NotDone(_)))
+    {
+        let mut current: VecMap<K, _> = parties
+            .into_iter()
+            .filter_map(|(_, party)| match party {
+                Protocol::NotDone(round) => Some(round),
+                Protocol::Done(_) => None,
+            })
+            .collect();
+
+        let (_, first) = current.iter().next().ok_or(TofnFatal)?;
+        rounds.push(first.trace_msgs_out()?);
+        max_msg_len = first.max_msg_in_len();
+
+        let msgs: Vec<(TypedUsize<K>, BytesVec)> = current
+            .iter()
+            .flat_map(|(from, round)| {
+                round
+                    .bcast_out()
+                    .cloned()
+                    .into_iter()
+                    .chain(
+                        round
+                            .p2ps_out()
+                            .into_iter()
+                            .flat_map(|p2ps| p2ps.iter().map(|(_, bytes)| bytes.clone())),
+                    )
+                    .map(move |bytes| (from, bytes))
+            })
+            .collect();
+        for (from, bytes) in msgs {
+            for (_, round) in current.iter_mut() {
+                let from = round.info().party_share_counts().share_to_party_id(from)?;
+                round.msg_in(from, &bytes)?;
+            }
+        }
+
+        parties = current
+            .into_iter()
+            .map(|(_, round)| round.execute_next_round())
+            .collect::<TofnResult<_>>()?;
+    }
+
+    let outputs = parties
+        .into_iter()
+        .map(|(_, party)| match party {
+            Protocol::Done(Ok(output)) => Ok(output),
+            _ => {
+                error!("honest {} execution did not succeed", protocol);
+                Err(TofnFatal)
+            }
+        })
+        .collect::<TofnResult<_>>()?;
+
+    Ok((
+        ProtocolSpec {
+            protocol,
+            max_msg_len,
+            rounds,
+        },
+        outputs,
+    ))
+}
+
+fn multisig_keygen(
+) -> TofnResult<VecMap<multisig::keygen::KeygenShareId, multisig::keygen::KeygenProtocol>> {
+    let party_share_counts = party_share_counts()?;
+    let mut parties = Vec::new();
+    for (party_id, &party_share_count) in party_share_counts.iter() {
+        for subshare_id in 0..party_share_count {
+            parties.push(multisig::keygen::new_keygen(
+                party_share_counts.clone(),
+                THRESHOLD,
+                party_id,
+                subshare_id,
+                &secret_recovery_key(party_id)?,
+                SESSION_NONCE,
+            )?);
+        }
+    }
+    Ok(VecMap::from_vec(parties))
+}
+
+fn multisig_sign(
+    key_shares: &VecMap<multisig::keygen::KeygenShareId, multisig::keygen::SecretKeyShare>,
+) -> TofnResult<VecMap<multisig::sign::SignShareId, multisig::sign::SignProtocol>> {
+    let sign_parties = all_parties()?;
+    let msg = multisig::sign::MessageDigest::try_from(&MSG[..]).map_err(|_| TofnFatal)?;
+    keygen_share_ids(&sign_parties)?
+        .into_iter()
+        .map(|(_, keygen_share_id)| {
+            let key_share = key_shares.get(keygen_share_id)?;
+            multisig::sign::new_sign(key_share.group(), key_share.share(), &sign_parties, &msg)
+        })
+        .collect()
+}
+
+fn gg20_keygen(
+    party_keygen_data: &VecMap<gg20::keygen::KeygenPartyId, gg20::keygen::PartyKeygenData>,
+    enrolled: bool,
+) -> TofnResult<VecMap<gg20::keygen::KeygenShareId, gg20::keygen::KeygenProtocol>> {
+    let party_share_counts = party_share_counts()?;
+    let enrollments: gg20::keygen::Enrollments = party_keygen_data
+        .iter()
+        .map(|(_, data)| data.enrollment())
+        .collect();
+    let mut parties = Vec::new();
+    for (party_id, &party_share_count) in party_share_counts.iter() {
+        let party_keygen_data = party_keygen_data.get(party_id)?;
+        for subshare_id in 0..party_share_count {
+            parties.push(if enrolled {
+                gg20::keygen::new_keygen_enrolled(
+                    party_share_counts.clone(),
+                    THRESHOLD,
+                    party_id,
+                    subshare_id,
+                    party_keygen_data,
+                    enrollments.clone(),
+                    #[cfg(feature = "malicious")]
+                    gg20::keygen::malicious::Behaviour::Honest,
+                )?
+            } else {
+                gg20::keygen::new_keygen(
+                    party_share_counts.clone(),
+                    THRESHOLD,
+                    party_id,
+                    subshare_id,
+                    party_keygen_data,
+                    #[cfg(feature = "malicious")]
+                    gg20::keygen::malicious::Behaviour::Honest,
+                )?
+            });
+        }
+    }
+    Ok(VecMap::from_vec(parties))
+}
+
+fn gg20_sign(
+    key_shares: &VecMap<gg20::keygen::KeygenShareId, gg20::keygen::SecretKeyShare>,
+) -> TofnResult<VecMap<gg20::sign::SignShareId, gg20::sign::SignProtocol>> {
+    let sign_parties = all_parties()?;
+    let msg = gg20::sign::MessageDigest::try_from(&MSG[..]).map_err(|_| TofnFatal)?;
+    keygen_share_ids(&sign_parties)?
+        .into_iter()
+        .map(|(_, keygen_share_id)| {
+            let key_share = key_shares.get(keygen_share_id)?;
+            gg20::sign::new_sign(
+                key_share.group(),
+                key_share.share(),
+                &sign_parties,
+                &msg,
+                #[cfg(feature = "malicious")]
+                gg20::sign::malicious::Behaviour::Honest,
+            )
+        })
+        .collect()
+}
+
+fn gg20_decrypt(
+    key_shares: &VecMap<gg20::keygen::KeygenShareId, gg20::keygen::SecretKeyShare>,
+) -> TofnResult<VecMap<gg20::decrypt::DecryptShareId, gg20::decrypt::DecryptProtocol>> {
+    let decrypt_parties = all_parties()?;
+    let group = key_shares.iter().next().ok_or(TofnFatal)?.1.group().clone();
+    let ciphertext = gg20::decrypt::encrypt(&group, &MSG)?;
+    keygen_share_ids(&decrypt_parties)?
+        .into_iter()
+        .map(|(_, keygen_share_id)| {
+            let key_share = key_shares.get(keygen_share_id)?;
+            gg20::decrypt::new_decrypt(
+                key_share.group(),
+                key_share.share(),
+                &decrypt_parties,
+                &ciphertext,
+            )
+        })
+        .collect()
+}
+
+fn threshold_paillier_decrypt() -> TofnResult<
+    VecMap<
+        threshold_paillier::decrypt::DecryptShareId,
+        threshold_paillier::decrypt::DecryptProtocol,
+    >,
+> {
+    let mut rng = rand::thread_rng();
+    let (_, dk) = paillier::keygen_unsafe(&mut rng)?;
+    let key_shares =
+        threshold_paillier::keygen::deal(&mut rng, &dk, party_share_counts()?, THRESHOLD)?;
+
+    let decrypt_parties = all_parties()?;
+    let ek = key_shares
+        .iter()
+        .next()
+        .ok_or(TofnFatal)?
+        .1
+        .group()
+        .encryption_key()
+        .clone();
+    let (ciphertext, _) = ek.encrypt(&ek.random_plaintext());
+    keygen_share_ids(&decrypt_parties)?
+        .into_iter()
+        .map(|(_, keygen_share_id)| {
+            let key_share = key_shares.get(keygen_share_id)?;
+            threshold_paillier::decrypt::new_decrypt(
+                key_share.group(),
+                key_share.share(),
+                &decrypt_parties,
+                &ciphertext,
+            )
+        })
+        .collect()
+}
+
+fn party_share_counts<P>() -> TofnResult<PartyShareCounts<P>> {
+    PartyShareCounts::from_vec(PARTY_SHARE_COUNTS.to_vec())
+}
+
+fn all_parties<P>() -> TofnResult<Subset<P>> {
+    let mut parties = Subset::with_max_size(PARTY_SHARE_COUNTS.len());
+    for i in 0..PARTY_SHARE_COUNTS.len() {
+        parties.add(TypedUsize::from_usize(i))?;
+    }
+    Ok(parties)
+}
+
+fn keygen_share_ids<S, K, P>(parties: &Subset<P>) -> TofnResult<VecMap<S, TypedUsize<K>>> {
+    Ok(VecMap::from_vec(
+        party_share_counts::<P>()?.share_id_subset(parties)?,
+    ))
+}
+
+/// The all-zero array with the first bytes set to the bytes of `index`
+fn secret_recovery_key<K>(index: TypedUsize<K>) -> TofnResult<SecretRecoveryKey> {
+    let index_bytes = index.as_usize().to_be_bytes();
+    let mut bytes = [0; 64];
+    bytes[..index_bytes.len()].copy_from_slice(&index_bytes);
+    SecretRecoveryKey::try_from(&bytes[..]).map_err(|_| TofnFatal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wire_spec;
+
+    #[test]
+    fn all_protocols() {
+        let spec = wire_spec().unwrap();
+        assert!(spec.envelope.contains_key("WireBytesRef"));
+        assert!(spec.envelope.contains_key("MsgType"));
+
+        let multisig_keygen = &spec.protocols[0];
+        assert_eq!(multisig_keygen.protocol, "multisig::keygen");
+        assert_eq!(
+            multisig_keygen.max_msg_len,
+            crate::multisig::keygen::MAX_MSG_LEN
+        );
+        assert_eq!(multisig_keygen.rounds.len(), 1);
+        assert!(multisig_keygen.rounds[0].bcast.is_some());
+        assert!(multisig_keygen.rounds[0].p2p.is_none());
+
+        let gg20_sign = spec
+            .protocols
+            .iter()
+            .find(|protocol| protocol.protocol == "gg20::sign")
+            .unwrap();
+        assert_eq!(gg20_sign.rounds.len(), 7);
+        for (i, round) in gg20_sign.rounds.iter().enumerate() {
+            assert_eq!(round.round, i);
+        }
+
+        assert!(spec.to_json().unwrap().contains("\"envelope\""));
+    }
+}
@@ -0,0 +1,234 @@
+//! A complete protocol built on [tofn::sdk::implementer_api]: a commit-reveal coin flip.
+//!
+//! * Round 1: each share broadcasts a commitment to a random value.
+//! * Round 2: each share reveals its value.
+//! * Output: the XOR of all values, or the shares whose reveal does not match their commitment.
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tofn::{
+    collections::{FillVecMap, P2ps, TypedUsize, VecMap},
+    sdk::{
+        api::{Fault, PartyShareCounts, Protocol, TofnResult},
+        implementer_api::{
+            new_protocol, serialize, Executer, ProtocolBuilder, ProtocolInfo, RoundBuilder,
+        },
+        local::{execute_honest, execute_protocol},
+    },
+};
+
+pub struct CoinShareId;
+pub struct CoinPartyId;
+
+pub type Coin = [u8; 32];
+pub type CoinProtocol = Protocol<Coin, CoinShareId, CoinPartyId>;
+type CoinProtocolBuilder = ProtocolBuilder<Coin, CoinShareId>;
+
+const MAX_MSG_LEN: usize = 100;
+
+#[derive(Serialize, Deserialize)]
+struct Commit {
+    digest: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize)]
+struct Reveal {
+    value: Coin,
+}
+
+/// Flip a coin with a fresh random value
+pub fn new_coin_flip(
+    party_share_counts: PartyShareCounts<CoinPartyId>,
+    share_id: TypedUsize<CoinShareId>,
+) -> TofnResult<CoinProtocol> {
+    let mut value = [0; 32];
+    rand::thread_rng().fill_bytes(&mut value);
+    start(party_share_counts, share_id, value, value)
+}
+
+fn start(
+    party_share_counts: PartyShareCounts<CoinPartyId>,
+    share_id: TypedUsize<CoinShareId>,
+    committed: Coin,
+    revealed: Coin,
+) -> TofnResult<CoinProtocol> {
+    new_protocol(
+        party_share_counts,
+        share_id,
+        MAX_MSG_LEN,
+        first_round(share_id, committed, revealed)?,
+    )
+}
+
+fn first_round(
+    share_id: TypedUsize<CoinShareId>,
+    committed: Coin,
+    revealed: Coin,
+) -> TofnResult<CoinProtocolBuilder> {
+    let bcast_out = Some(serialize(&Commit {
+        digest: commitment(share_id, &committed),
+    })?);
+    Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
+        Box::new(R2 { value: revealed }),
+        bcast_out,
+        None,
+    )))
+}
+
+fn commitment(share_id: TypedUsize<CoinShareId>, value: &Coin) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(share_id.to_bytes())
+        .chain_update(value)
+        .finalize()
+        .into()
+}
+
+/// Collect the commitments and reveal my value
+struct R2 {
+    value: Coin,
+}
+
+impl Executer for R2 {
+    type FinalOutput = Coin;
+    type Index = CoinShareId;
+    type Bcast = Commit;
+    type P2p = ();
+
+    fn execute(
+        self: Box<Self>,
+        _info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        _p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<CoinProtocolBuilder> {
+        // `Executer` has already flagged missing or corrupted messages,
+        // so every bcast is present
+        let commits = bcasts_in.to_vecmap()?;
+        let bcast_out = Some(serialize(&Reveal { value: self.value })?);
+        Ok(ProtocolBuilder::NotDone(RoundBuilder::new(
+            Box::new(R3 { commits }),
+            bcast_out,
+            None,
+        )))
+    }
+}
+
+/// Check the reveals against the commitments and combine them
+struct R3 {
+    commits: VecMap<CoinShareId, Commit>,
+}
+
+impl Executer for R3 {
+    type FinalOutput = Coin;
+    type Index = CoinShareId;
+    type Bcast = Reveal;
+    type P2p = ();
+
+    fn execute(
+        self: Box<Self>,
+        info: &ProtocolInfo<Self::Index>,
+        bcasts_in: FillVecMap<Self::Index, Self::Bcast>,
+        _p2ps_in: P2ps<Self::Index, Self::P2p>,
+    ) -> TofnResult<CoinProtocolBuilder> {
+        let reveals = bcasts_in.to_vecmap()?;
+
+        // a bad reveal is malicious behaviour, not a fatal error
+        let mut faulters = info.new_fillvecmap();
+        for (share_id, reveal) in &reveals {
+            if commitment(share_id, &reveal.value) != self.commits.get(share_id)?.digest {
+                faulters.set(share_id, Fault::ProtocolFault)?;
+            }
+        }
+        if !faulters.is_empty() {
+            return Ok(ProtocolBuilder::Done(Err(faulters)));
+        }
+
+        let mut coin = [0; 32];
+        for (_, reveal) in reveals {
+            coin.iter_mut()
+                .zip(reveal.value.iter())
+                .for_each(|(c, v)| *c ^= v);
+        }
+        Ok(ProtocolBuilder::Done(Ok(coin)))
+    }
+}
+
+fn party_share_counts() -> PartyShareCounts<CoinPartyId> {
+    PartyShareCounts::from_vec(vec![1, 2, 1]).unwrap()
+}
+
+#[test]
+fn honest() {
+    let party_share_counts = party_share_counts();
+    let parties: VecMap<CoinShareId, _> = (0..party_share_counts.total_share_count())
+        .map(|i| new_coin_flip(party_share_counts.clone(), TypedUsize::from_usize(i)).unwrap())
+        .collect();
+
+    let coins = execute_honest(parties).unwrap();
+    let (_, coin) = coins.iter().next().unwrap();
+    assert!(coins.iter().all(|(_, other)| other == coin));
+}
+
+#[test]
+fn chained() {
+    let party_share_counts = party_share_counts();
+    let share_count = party_share_counts.total_share_count();
+
+    // each share flips twice in one session, committing to its first value xor the first coin
+    let values: Vec<Coin> = (0..share_count).map(|i| [i as u8; 32]).collect();
+    let parties: VecMap<CoinShareId, _> = values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let share_id = TypedUsize::from_usize(i);
+            let builder = first_round(share_id, value, value)
+                .unwrap()
+                .and_then(move |coin: Coin| {
+                    let mut value = value;
+                    value.iter_mut().zip(coin.iter()).for_each(|(v, c)| *v ^= c);
+                    first_round(share_id, value, value)
+                })
+                .unwrap();
+            new_protocol(party_share_counts.clone(), share_id, MAX_MSG_LEN, builder).unwrap()
+        })
+        .collect();
+
+    // an even number of shares: the second coin is the first one
+    let first_coin = values.iter().fold([0; 32], |mut coin, value| {
+        coin.iter_mut().zip(value.iter()).for_each(|(c, v)| *c ^= v);
+        coin
+    });
+    for (_, coin) in execute_honest(parties).unwrap() {
+        assert_eq!(coin, first_coin);
+    }
+}
+
+#[test]
+fn bad_reveal() {
+    let party_share_counts = party_share_counts();
+    let cheater = TypedUsize::<CoinShareId>::from_usize(2);
+    let parties: VecMap<CoinShareId, _> = (0..party_share_counts.total_share_count())
+        .map(|i| {
+            let share_id = TypedUsize::from_usize(i);
+            let revealed = if share_id == cheater {
+                [1; 32]
+            } else {
+                [0; 32]
+            };
+            start(party_share_counts.clone(), share_id, [0; 32], revealed).unwrap()
+        })
+        .collect();
+
+    // share 2 is the second share of party 1
+    for (_, party) in execute_protocol(parties).unwrap() {
+        match party {
+            Protocol::Done(Err(faulters)) => {
+                assert_eq!(faulters.iter_some().count(), 1);
+                assert!(matches!(
+                    faulters.get(TypedUsize::from_usize(1)).unwrap(),
+                    Some(Fault::ProtocolFault)
+                ));
+            }
+            _ => panic!("expect faulters"),
+        }
+    }
+}
@@ -1,7 +1,54 @@
 use core::convert::TryInto;
 
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use tofn::{collections::TypedUsize, gg20::keygen::SecretRecoveryKey};
 
+/// Environment variable read by [TestSeed::from_env]
+pub const SEED_ENV_VAR: &str = "TOFN_TEST_SEED";
+
+/// Seed of the secret recovery keys used by a test,
+/// and so of each party's Paillier key and zk setup.
+/// Protocol randomness (eg. keygen polynomials, sign nonces, proof masks) still comes from
+/// the thread RNG, so re-running with the same seed reproduces the parties' keys but not
+/// every message: a failure that depends on protocol randomness may not recur.
+/// The seed is read from [SEED_ENV_VAR] if present, otherwise it is chosen at random.
+/// If the test panics then the seed is printed: `TOFN_TEST_SEED=<seed> cargo test ...`
+pub struct TestSeed(u64);
+
+impl TestSeed {
+    pub fn from_env() -> Self {
+        let seed = match std::env::var(SEED_ENV_VAR) {
+            Ok(seed) => seed
+                .parse()
+                .unwrap_or_else(|_| panic!("{} must be a u64, got {}", SEED_ENV_VAR, seed)),
+            Err(_) => rand::random(),
+        };
+        Self(seed)
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// A distinct secret recovery key for each `index`
+    pub fn secret_recovery_key<K>(&self, index: TypedUsize<K>) -> SecretRecoveryKey {
+        let mut rng = ChaCha20Rng::seed_from_u64(self.0);
+        rng.set_stream(index.as_usize() as u64);
+        let mut bytes = [0; 64];
+        rng.fill_bytes(&mut bytes);
+        bytes[..].try_into().unwrap()
+    }
+}
+
+impl Drop for TestSeed {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            eprintln!("test failed with {}={}", SEED_ENV_VAR, self.0);
+        }
+    }
+}
+
 pub mod keygen {
     use tofn::{
         collections::VecMap,
@@ -15,7 +62,10 @@ pub mod keygen {
     #[cfg(feature = "malicious")]
     use tofn::gg20::keygen::malicious::Behaviour;
 
+    use super::TestSeed;
+
     pub fn initialize_honest_parties(
+        seed: &TestSeed,
         party_share_counts: &PartyShareCounts<KeygenPartyId>,
         threshold: usize,
     ) -> VecMap<KeygenShareId, KeygenProtocol> {
@@ -25,7 +75,7 @@ pub mod keygen {
             .iter()
             .flat_map(|(party_id, &party_share_count)| {
                 // each party use the same secret recovery key for all its subshares
-                let secret_recovery_key = super::dummy_secret_recovery_key(party_id);
+                let secret_recovery_key = seed.secret_recovery_key(party_id);
 
                 let party_keygen_data = create_party_keypair_and_zksetup_unsafe(
                     party_id,
@@ -50,13 +100,3 @@ pub mod keygen {
             .collect()
     }
 }
-
-/// return the all-zero array with the first bytes set to the bytes of `index`
-pub fn dummy_secret_recovery_key<K>(index: TypedUsize<K>) -> SecretRecoveryKey {
-    let index_bytes = index.as_usize().to_be_bytes();
-    let mut result = [0; 64];
-    for (i, &b) in index_bytes.iter().enumerate() {
-        result[i] = b;
-    }
-    result[..].try_into().unwrap()
-}
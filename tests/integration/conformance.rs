@@ -0,0 +1,139 @@
+//! Run the fault identification conformance harness against every protocol
+
+use std::convert::TryFrom;
+
+use tofn::{
+    collections::{TypedUsize, VecMap},
+    gg20, multisig,
+    sdk::api::{PartyShareCounts, Protocol},
+    test_utils::conformance::check_single_faults,
+};
+
+use crate::{
+    common::{keygen, TestSeed},
+    single_thread::execute::execute_protocol,
+};
+
+// 2 parties with 1 and 2 shares, every party signs
+// faulter is the last share (party 1)
+const PARTY_SHARE_COUNTS: [usize; 2] = [1, 2];
+const THRESHOLD: usize = 1;
+const FAULTER: usize = 2;
+
+#[test]
+fn gg20_keygen() {
+    let seed = TestSeed::from_env();
+    let party_share_counts = PartyShareCounts::from_vec(PARTY_SHARE_COUNTS.to_vec()).unwrap();
+    let case_count = check_single_faults(
+        || keygen::initialize_honest_parties(&seed, &party_share_counts, THRESHOLD),
+        FAULTER,
+    )
+    .unwrap();
+    assert!(case_count > 0);
+}
+
+#[test]
+fn gg20_sign() {
+    let seed = TestSeed::from_env();
+    let party_share_counts = PartyShareCounts::from_vec(PARTY_SHARE_COUNTS.to_vec()).unwrap();
+    let key_shares = done(
+        execute_protocol(keygen::initialize_honest_parties(
+            &seed,
+            &party_share_counts,
+            THRESHOLD,
+        ))
+        .unwrap(),
+    );
+    let sign_parties = all_parties();
+    let msg_to_sign = gg20::sign::MessageDigest::try_from(&[42; 32][..]).unwrap();
+
+    let case_count = check_single_faults(
+        || {
+            key_shares.ref_map(|key_share| {
+                gg20::sign::new_sign(
+                    key_share.group(),
+                    key_share.share(),
+                    &sign_parties,
+                    &msg_to_sign,
+                    #[cfg(feature = "malicious")]
+                    gg20::sign::malicious::Behaviour::Honest,
+                )
+                .unwrap()
+            })
+        },
+        FAULTER,
+    )
+    .unwrap();
+    assert!(case_count > 0);
+}
+
+#[test]
+fn multisig_keygen() {
+    let seed = TestSeed::from_env();
+    let case_count = check_single_faults(|| multisig_keygen_parties(&seed), FAULTER).unwrap();
+    assert!(case_count > 0);
+}
+
+#[test]
+fn multisig_sign() {
+    let seed = TestSeed::from_env();
+    let key_shares = done(execute_protocol(multisig_keygen_parties(&seed)).unwrap());
+    let sign_parties = all_parties();
+    let msg_to_sign = multisig::sign::MessageDigest::try_from(&[42; 32][..]).unwrap();
+
+    let case_count = check_single_faults(
+        || {
+            key_shares.ref_map(|key_share| {
+                multisig::sign::new_sign(
+                    key_share.group(),
+                    key_share.share(),
+                    &sign_parties,
+                    &msg_to_sign,
+                )
+                .unwrap()
+            })
+        },
+        FAULTER,
+    )
+    .unwrap();
+    assert!(case_count > 0);
+}
+
+fn multisig_keygen_parties(
+    seed: &TestSeed,
+) -> VecMap<multisig::keygen::KeygenShareId, multisig::keygen::KeygenProtocol> {
+    let party_share_counts = PartyShareCounts::from_vec(PARTY_SHARE_COUNTS.to_vec()).unwrap();
+    party_share_counts
+        .iter()
+        .flat_map(|(party_id, &party_share_count)| {
+            let party_share_counts = party_share_counts.clone();
+            (0..party_share_count).map(move |subshare_id| {
+                multisig::keygen::new_keygen(
+                    party_share_counts.clone(),
+                    THRESHOLD,
+                    party_id,
+                    subshare_id,
+                    &seed.secret_recovery_key(party_id),
+                    b"conformance",
+                )
+                .unwrap()
+            })
+        })
+        .collect()
+}
+
+/// all parties sign, so sign share ids coincide with keygen share ids
+fn all_parties<P>() -> tofn::collections::Subset<P> {
+    let mut sign_parties = tofn::collections::Subset::with_max_size(PARTY_SHARE_COUNTS.len());
+    for party_id in 0..PARTY_SHARE_COUNTS.len() {
+        sign_parties.add(TypedUsize::from_usize(party_id)).unwrap();
+    }
+    sign_parties
+}
+
+fn done<F, K, P>(parties: VecMap<K, Protocol<F, K, P>>) -> VecMap<K, F> {
+    parties.map(|party| match party {
+        Protocol::NotDone(_) => panic!("share not done yet"),
+        Protocol::Done(result) => result.expect("share finished with error"),
+    })
+}
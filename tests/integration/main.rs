@@ -1,3 +1,8 @@
+mod coin_flip;
 mod common;
+#[cfg(feature = "test-utils")]
+mod conformance;
 mod multi_thread;
+#[cfg(feature = "test-utils")]
+mod network_sim;
 mod single_thread;
@@ -1,4 +1,4 @@
-use crate::common;
+use crate::common::{self, TestSeed};
 use broadcaster::Broadcaster;
 use ecdsa::hazmat::VerifyPrimitive;
 use k256::PublicKey;
@@ -19,13 +19,15 @@ use tofn::gg20::sign;
 #[test]
 fn basic_correctness() {
     set_up_logs();
+    let seed = TestSeed::from_env();
 
     let party_share_counts = PartyShareCounts::from_vec(vec![1, 2, 3, 4]).unwrap(); // 10 total shares
     let threshold = 5;
 
     // keygen
     debug!("start keygen");
-    let keygen_shares = common::keygen::initialize_honest_parties(&party_share_counts, threshold);
+    let keygen_shares =
+        common::keygen::initialize_honest_parties(&seed, &party_share_counts, threshold);
     let (keygen_broadcaster, keygen_receivers) =
         Broadcaster::new(party_share_counts.total_share_count());
     let (keygen_result_sender, keygen_result_receiver) = mpsc::channel();
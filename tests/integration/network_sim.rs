@@ -0,0 +1,127 @@
+//! Protocol execution over a simulated unreliable network
+
+use tofn::{
+    collections::{TypedUsize, VecMap},
+    multisig::keygen::{new_keygen, KeygenPartyId, KeygenProtocol, KeygenShareId},
+    sdk::api::{Fault, PartyShareCounts},
+    test_utils::network_sim::{simulate, LinkConfig, NetworkConfig},
+};
+
+use crate::common::{self, TestSeed};
+
+const ROUND_TIMEOUT: u64 = 100;
+
+fn multisig_keygen_parties(seed: &TestSeed) -> VecMap<KeygenShareId, KeygenProtocol> {
+    let party_share_counts = PartyShareCounts::<KeygenPartyId>::from_vec(vec![1, 2, 1]).unwrap();
+    let threshold = 2;
+    party_share_counts
+        .iter()
+        .flat_map(|(party_id, &party_share_count)| {
+            let party_share_counts = party_share_counts.clone();
+            (0..party_share_count).map(move |subshare_id| {
+                new_keygen(
+                    party_share_counts.clone(),
+                    threshold,
+                    party_id,
+                    subshare_id,
+                    &seed.secret_recovery_key(party_id),
+                    b"network_sim",
+                )
+                .unwrap()
+            })
+        })
+        .collect()
+}
+
+/// gg20 keygen has several rounds, so shares drift apart and receive messages ahead of time
+#[test]
+fn lossless_network_succeeds() {
+    let seed = TestSeed::from_env();
+    let config = NetworkConfig {
+        default_link: LinkConfig {
+            min_latency: 1,
+            max_latency: 30,
+            duplicate_probability: 0.3,
+            reorder: true,
+            ..LinkConfig::default()
+        },
+        round_timeout: ROUND_TIMEOUT,
+        ..NetworkConfig::default()
+    };
+
+    let party_share_counts = PartyShareCounts::from_vec(vec![1, 2, 1]).unwrap();
+    for i in 0..3 {
+        let output = simulate(
+            common::keygen::initialize_honest_parties(&seed, &party_share_counts, 2),
+            &NetworkConfig {
+                seed: seed.value().wrapping_add(i),
+                ..config.clone()
+            },
+        )
+        .unwrap();
+        assert_eq!(output.stats.dropped, 0);
+        assert_eq!(output.stats.timeouts, 0);
+        for (share_id, result) in output.outputs.iter() {
+            assert!(result.is_ok(), "share {} failed", share_id);
+        }
+    }
+}
+
+#[test]
+fn dead_link_times_out() {
+    let seed = TestSeed::from_env();
+    // every message from share 0 to share 3 is lost
+    let mut config = NetworkConfig {
+        round_timeout: ROUND_TIMEOUT,
+        seed: seed.value(),
+        ..NetworkConfig::default()
+    };
+    config.links.insert(
+        (0, 3),
+        LinkConfig {
+            drop_probability: 1.0,
+            ..LinkConfig::default()
+        },
+    );
+
+    let output = simulate(multisig_keygen_parties(&seed), &config).unwrap();
+    assert!(output.stats.timeouts > 0);
+    assert!(output.elapsed >= ROUND_TIMEOUT);
+
+    // share 3 belongs to party 2: it alone accuses party 0
+    for (share_id, result) in output.outputs.iter() {
+        if share_id.as_usize() == 3 {
+            let faulters = result.as_ref().unwrap_err();
+            assert_eq!(
+                faulters.get(TypedUsize::from_usize(0)).unwrap(),
+                Some(&Fault::MissingMessage)
+            );
+        } else {
+            assert!(result.is_ok(), "share {} failed", share_id);
+        }
+    }
+}
+
+#[test]
+fn invalid_link_config_rejected() {
+    let seed = TestSeed::from_env();
+    for link in [
+        LinkConfig {
+            drop_probability: 1.5,
+            ..LinkConfig::default()
+        },
+        LinkConfig {
+            duplicate_probability: -0.1,
+            ..LinkConfig::default()
+        },
+        LinkConfig {
+            min_latency: 2,
+            max_latency: 1,
+            ..LinkConfig::default()
+        },
+    ] {
+        let mut config = NetworkConfig::default();
+        config.links.insert((1, 0), link);
+        assert!(simulate(multisig_keygen_parties(&seed), &config).is_err());
+    }
+}
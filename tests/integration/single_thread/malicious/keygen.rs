@@ -10,14 +10,15 @@ use tofn::{
 use tracing::info;
 
 use crate::{
-    common::dummy_secret_recovery_key,
+    common::TestSeed,
     single_thread::{execute::execute_protocol, set_up_logs},
 };
 
 #[test]
 fn single_faults() {
     set_up_logs();
-    execute_test_case_list(&single_fault_test_case_list())
+    let seed = TestSeed::from_env();
+    execute_test_case_list(&seed, &single_fault_test_case_list())
 }
 
 pub fn single_fault_test_case_list() -> Vec<TestCase> {
@@ -26,6 +27,9 @@ pub fn single_fault_test_case_list() -> Vec<TestCase> {
         single_fault_test_case(R1BadCommit),
         single_fault_test_case(R1BadEncryptionKeyProof),
         single_fault_test_case(R1BadZkSetupProof),
+        single_fault_test_case(R1BadEncryptionKey),
+        single_fault_test_case(R1BadZkSetup),
+        single_fault_test_case(R1StaleCommit),
         single_fault_test_case(R2BadShare { victim: zero }),
         single_fault_test_case(R2BadEncryption { victim: zero }),
         single_fault_test_case(R3FalseAccusation { victim: zero }),
@@ -71,7 +75,10 @@ impl TestCase {
             }
         }
     }
-    pub fn initialize_malicious_parties(&self) -> VecMap<KeygenShareId, KeygenProtocol> {
+    pub fn initialize_malicious_parties(
+        &self,
+        seed: &TestSeed,
+    ) -> VecMap<KeygenShareId, KeygenProtocol> {
         let session_nonce = b"foobar";
         self.share_behaviours
             .iter()
@@ -83,7 +90,7 @@ impl TestCase {
 
                 let party_keygen_data = create_party_keypair_and_zksetup_unsafe(
                     party_id,
-                    &dummy_secret_recovery_key(share_id),
+                    &seed.secret_recovery_key(share_id),
                     session_nonce,
                 )
                 .unwrap();
@@ -102,7 +109,7 @@ impl TestCase {
     }
 }
 
-fn execute_test_case_list(test_cases: &[TestCase]) {
+fn execute_test_case_list(seed: &TestSeed, test_cases: &[TestCase]) {
     for test_case in test_cases {
         info!(
             "test: party_share_counts [{:?}] threshold [{}]",
@@ -121,12 +128,12 @@ fn execute_test_case_list(test_cases: &[TestCase]) {
             })
             .collect();
         info!("malicious participants {:?}", malicious_parties);
-        execute_test_case(test_case);
+        execute_test_case(seed, test_case);
     }
 }
 
-fn execute_test_case(test_case: &TestCase) {
-    let mut parties = test_case.initialize_malicious_parties();
+fn execute_test_case(seed: &TestSeed, test_case: &TestCase) {
+    let mut parties = test_case.initialize_malicious_parties(seed);
 
     parties = execute_protocol(parties).expect("internal tofn error");
 
@@ -1,4 +1,5 @@
 pub mod keygen;
+pub mod msg_fault;
 pub mod sign;
 pub mod sign_delta_inv;
 pub mod timeout_corrupt;
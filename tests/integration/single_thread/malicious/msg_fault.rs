@@ -0,0 +1,185 @@
+//! Single-threaded execution with a malicious share that misbehaves
+//! in sending its messages rather than in their contents,
+//! as committed by the message-delivery variants of `Behaviour`
+
+use core::convert::TryFrom;
+
+use tofn::{
+    collections::{FillVecMap, TypedUsize, VecMap},
+    gg20::{
+        keygen::{
+            self, create_party_keypair_and_zksetup_unsafe, new_keygen, KeygenPartyId, KeygenShareId,
+        },
+        sign::{self, new_sign, MessageDigest, SignParties, SignShareId},
+    },
+    sdk::api::{Fault, PartyShareCounts, Protocol, ProtocolFaulters},
+};
+use tracing::info;
+
+use crate::{
+    common::{keygen as common_keygen, TestSeed},
+    single_thread::{execute::execute_protocol, set_up_logs},
+};
+
+// 2 parties, 2 shares per party
+// share 3 (party 1) is malicious, share 0 (party 0) is its victim
+const PARTY_SHARE_COUNTS: [usize; 2] = [2, 2];
+const THRESHOLD: usize = 2;
+const MALICIOUS_SHARE_ID: usize = 3;
+const MALICIOUS_PARTY_ID: usize = 1;
+const VICTIM_SHARE_ID: usize = 0;
+
+#[test]
+fn msg_faults_keygen() {
+    use keygen::malicious::Behaviour::*;
+    set_up_logs();
+    let seed = TestSeed::from_env();
+    let party_share_counts = PartyShareCounts::from_vec(PARTY_SHARE_COUNTS.to_vec()).unwrap();
+    let victim = TypedUsize::from_usize(VICTIM_SHARE_ID);
+
+    // keygen round 2 sends both bcasts and p2ps
+    let cases = vec![
+        (WithholdBcast { round: 2 }, Fault::MissingMessage),
+        (WithholdP2p { round: 2, victim }, Fault::MissingMessage),
+        (HalfOfPeers { round: 2 }, Fault::MissingMessage),
+        (Replay { round: 2 }, Fault::CorruptedMessage),
+        (FutureRound { round: 2 }, Fault::CorruptedMessage),
+        (Impersonate { round: 2, victim }, Fault::CorruptedMessage),
+    ];
+
+    for (behaviour, fault) in cases {
+        info!("keygen with malicious behaviour {:?}", behaviour);
+        let session_nonce = b"foobar";
+        let parties: VecMap<KeygenShareId, _> = (0..party_share_counts.total_share_count())
+            .map(|i| {
+                let share_id = TypedUsize::from_usize(i);
+                let (party_id, subshare_id) = party_share_counts
+                    .share_to_party_subshare_ids(share_id)
+                    .unwrap();
+                let party_keygen_data = create_party_keypair_and_zksetup_unsafe(
+                    party_id,
+                    &seed.secret_recovery_key(party_id),
+                    session_nonce,
+                )
+                .unwrap();
+                new_keygen(
+                    party_share_counts.clone(),
+                    THRESHOLD,
+                    party_id,
+                    subshare_id,
+                    &party_keygen_data,
+                    if i == MALICIOUS_SHARE_ID {
+                        behaviour.clone()
+                    } else {
+                        Honest
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+        assert_expected_output::<_, _, KeygenPartyId>(
+            execute_protocol(parties).expect("internal tofn error"),
+            fault,
+            matches!(behaviour, HalfOfPeers { .. }),
+        );
+    }
+}
+
+#[test]
+fn msg_faults_sign() {
+    use sign::malicious::Behaviour::*;
+    set_up_logs();
+    let seed = TestSeed::from_env();
+    let party_share_counts = PartyShareCounts::from_vec(PARTY_SHARE_COUNTS.to_vec()).unwrap();
+    let secret_key_shares = execute_protocol(common_keygen::initialize_honest_parties(
+        &seed,
+        &party_share_counts,
+        THRESHOLD,
+    ))
+    .unwrap()
+    .map(|output| match output {
+        Protocol::NotDone(_) => panic!("share not done yet"),
+        Protocol::Done(result) => result.expect("share finished with error"),
+    });
+
+    // every party participates in sign so that sign ids coincide with keygen ids
+    let mut sign_parties = SignParties::with_max_size(party_share_counts.party_count());
+    for (party_id, _) in party_share_counts.iter() {
+        sign_parties.add(party_id).unwrap();
+    }
+    let keygen_share_ids = VecMap::<SignShareId, _>::from_vec(
+        party_share_counts.share_id_subset(&sign_parties).unwrap(),
+    );
+    let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+    let victim = TypedUsize::from_usize(VICTIM_SHARE_ID);
+
+    // sign round 1 sends both bcasts and p2ps; round 2 is the first that can replay
+    let cases = vec![
+        (WithholdBcast { round: 1 }, Fault::MissingMessage),
+        (WithholdP2p { round: 1, victim }, Fault::MissingMessage),
+        (HalfOfPeers { round: 1 }, Fault::MissingMessage),
+        (Replay { round: 2 }, Fault::CorruptedMessage),
+        (FutureRound { round: 1 }, Fault::CorruptedMessage),
+        (Impersonate { round: 1, victim }, Fault::CorruptedMessage),
+    ];
+
+    for (behaviour, fault) in cases {
+        info!("sign with malicious behaviour {:?}", behaviour);
+        let parties = keygen_share_ids
+            .clone()
+            .map2(|(sign_share_id, keygen_share_id)| {
+                let secret_key_share = secret_key_shares.get(keygen_share_id).unwrap();
+                new_sign(
+                    secret_key_share.group(),
+                    secret_key_share.share(),
+                    &sign_parties,
+                    &msg_to_sign,
+                    if sign_share_id.as_usize() == MALICIOUS_SHARE_ID {
+                        behaviour.clone()
+                    } else {
+                        Honest
+                    },
+                )
+                .unwrap()
+            });
+        assert_expected_output(
+            execute_protocol(parties).expect("internal tofn error"),
+            fault,
+            matches!(behaviour, HalfOfPeers { .. }),
+        );
+    }
+}
+
+/// Honest shares accuse the malicious party of `fault`.
+/// If the malicious share sent to `half_of_peers` only then its recipients
+/// may have moved on to the next round without noticing anything wrong.
+fn assert_expected_output<F, K, P>(
+    shares: VecMap<K, Protocol<F, K, P>>,
+    fault: Fault,
+    half_of_peers: bool,
+) where
+    P: PartialEq + core::fmt::Debug,
+{
+    let total_share_count = shares.len();
+    let mut want_faulters: ProtocolFaulters<P> = FillVecMap::with_size(PARTY_SHARE_COUNTS.len());
+    want_faulters
+        .set(TypedUsize::from_usize(MALICIOUS_PARTY_ID), fault)
+        .unwrap();
+
+    let mut accusers = 0;
+    for (i, share) in shares.iter() {
+        if i.as_usize() == MALICIOUS_SHARE_ID {
+            continue;
+        }
+        match share {
+            Protocol::NotDone(_) if half_of_peers && i.as_usize() < total_share_count / 2 => {
+                continue
+            }
+            Protocol::NotDone(_) => panic!("honest share {} not done yet", i),
+            Protocol::Done(Ok(_)) => panic!("expect failure, got success"),
+            Protocol::Done(Err(got_faulters)) => assert_eq!(*got_faulters, want_faulters),
+        }
+        accusers += 1;
+    }
+    assert!(accusers > 0, "no honest share detected the faulter");
+}
@@ -1,24 +1,26 @@
 use crate::{
-    common::keygen,
+    common::{keygen, TestSeed},
     single_thread::{execute::execute_protocol, set_up_logs},
 };
 use core::convert::TryFrom;
 use tofn::{
     collections::{FillVecMap, TypedUsize, VecMap},
     gg20::{
-        keygen::KeygenPartyId,
+        keygen::{GroupPublicInfo, KeygenPartyId},
         sign::{
             malicious::Behaviour::{self, *},
             new_sign, MessageDigest, SignParties, SignPartyId, SignShareId,
         },
+        verify_evidence::{verify_mta_failure, Culprit},
     },
-    sdk::api::{Fault, PartyShareCounts, Protocol::*, ProtocolOutput, Signature},
+    sdk::api::{Fault, MtaProof, PartyShareCounts, Protocol::*, ProtocolOutput, Signature},
 };
 use tracing::info;
 
 #[test]
 fn single_faults() {
     set_up_logs();
+    let seed = TestSeed::from_env();
 
     let test_cases = single_fault_test_cases();
 
@@ -26,6 +28,7 @@ fn single_faults() {
 
     // generate secret key shares by doing a keygen
     let secret_key_shares = execute_protocol(keygen::initialize_honest_parties(
+        &seed,
         &test_cases.party_share_counts,
         test_cases.threshold,
     ))
@@ -42,6 +45,10 @@ fn single_faults() {
             .unwrap(),
     );
     let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+    let group = secret_key_shares
+        .get(TypedUsize::from_usize(0))
+        .unwrap()
+        .group();
 
     for case in test_cases.cases.iter() {
         info!("sign with malicious behaviour {:?}", case);
@@ -70,7 +77,16 @@ fn single_faults() {
             if sign_share_id != test_cases.malicious_sign_share_id {
                 match result {
                     NotDone(_) => panic!("honest sign share_id {} not done yet", sign_share_id),
-                    Done(output) => test_cases.assert_expected_output(output),
+                    Done(output) => match case {
+                        R2BadMta { victim } => {
+                            test_cases.assert_mta_failure(output, *victim, MtaProof::MtA, group)
+                        }
+                        R2BadMtaWc { victim } => {
+                            test_cases.assert_mta_failure(output, *victim, MtaProof::MtAwc, group)
+                        }
+                        R6BadTranscriptHash => test_cases.assert_transcript_mismatch(output),
+                        _ => test_cases.assert_expected_output(output),
+                    },
                 }
             }
         }
@@ -113,6 +129,7 @@ fn single_fault_test_cases() -> SingleFaultTestCaseList {
         R6FalseAccusation { victim: zero },
         R6BadProof,
         R6FalseType5Claim,
+        R6BadTranscriptHash,
         R7BadSI,
         R7FalseType7Claim,
     ];
@@ -153,4 +170,41 @@ impl SingleFaultTestCaseList {
             }
         }
     }
+
+    /// The malicious share's transcript hash differs from everyone else's:
+    /// its party is reported, but not for a protocol fault
+    pub fn assert_transcript_mismatch(&self, output: &ProtocolOutput<Signature, SignPartyId>) {
+        let faulters = output.as_ref().expect_err("expect failure, got success");
+        let mut want_faulters = FillVecMap::with_size(faulters.size());
+        want_faulters
+            .set(TypedUsize::from_usize(1), Fault::TranscriptMismatch)
+            .unwrap();
+        assert_eq!(faulters, &want_faulters);
+    }
+
+    /// The malicious share's `proof` to `victim` failed and its evidence verifies against `group`
+    pub fn assert_mta_failure(
+        &self,
+        output: &ProtocolOutput<Signature, SignPartyId>,
+        victim: TypedUsize<SignShareId>,
+        proof: MtaProof,
+        group: &GroupPublicInfo,
+    ) {
+        let faulters = output.as_ref().expect_err("expect failure, got success");
+        assert_eq!(faulters.iter_some().count(), 1);
+        match faulters.get(TypedUsize::from_usize(1)).unwrap() {
+            Some(Fault::BadMtaProof(failure)) => {
+                assert_eq!(failure.prover, self.malicious_sign_share_id.as_usize());
+                assert_eq!(failure.verifier, victim.as_usize());
+                assert_eq!(failure.proof, proof);
+                assert_eq!(verify_mta_failure(group, failure).unwrap(), Culprit::Prover);
+
+                // evidence that does not match the failure is rejected
+                let mut bad_failure = failure.clone();
+                bad_failure.prover_ciphertext_hash[0] ^= 1;
+                assert!(verify_mta_failure(group, &bad_failure).is_err());
+            }
+            fault => panic!("expect BadMtaProof, got {:?}", fault),
+        }
+    }
 }
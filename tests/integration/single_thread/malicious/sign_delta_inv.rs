@@ -16,7 +16,7 @@ use tofn::{
 use tracing::{info, warn};
 
 use crate::{
-    common::keygen,
+    common::{keygen, TestSeed},
     single_thread::{
         execute::{execute_protocol, nobody_done},
         set_up_logs,
@@ -26,6 +26,7 @@ use crate::{
 #[test]
 fn delta_inverse() {
     set_up_logs();
+    let seed = TestSeed::from_env();
 
     // 3 keygen parties: 1,2,3 shares per party
     // 2 sign participants: keygen parties 0,2
@@ -67,6 +68,7 @@ fn delta_inverse() {
 
     // generate secret key shares by doing a keygen
     let secret_key_shares = execute_protocol(keygen::initialize_honest_parties(
+        &seed,
         &test_case.party_share_counts,
         test_case.threshold,
     ))
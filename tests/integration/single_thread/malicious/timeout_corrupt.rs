@@ -1,9 +1,12 @@
 //! Single-threaded generic protocol execution
-//! with a missing or corrupted message
+//! with a missing, corrupted or selectively delivered message
+
+use core::convert::TryFrom;
 
 use self::{FaultType::*, MsgType::*};
 use tofn::{
     collections::{FillVecMap, HoleVecMap, TypedUsize, VecMap},
+    gg20::sign::{new_sign, MessageDigest, SignParties, SignShareId},
     sdk::api::{
         BytesVec, Fault, MsgType, PartyShareCounts, Protocol, ProtocolFaulters, TofnResult,
     },
@@ -12,15 +15,23 @@ use tracing::{info, warn};
 // use tracing_test::traced_test; // enable logs in tests
 
 use crate::{
-    common::keygen,
-    single_thread::{execute::nobody_done, set_up_logs},
+    common::{keygen, TestSeed},
+    single_thread::{
+        execute::{self, nobody_done},
+        set_up_logs,
+    },
 };
 
 #[test]
 // #[traced_test]
 fn single_faults_keygen() {
     set_up_logs();
-    for test_case in single_fault_test_case_list() {
+    let seed = TestSeed::from_env();
+    // keygen round 2 sends both bcasts and p2ps
+    for test_case in single_fault_test_case_list(2)
+        .into_iter()
+        .chain(out_of_place_test_case_list())
+    {
         info!(
             "test: party_share_counts [{:?}] threshold [{}]",
             test_case.party_share_counts, test_case.threshold
@@ -29,36 +40,100 @@ fn single_faults_keygen() {
             "test: target_msg [{:?}], fault_type [{:?}]",
             test_case.msg, test_case.fault_type
         );
-        let shares =
-            keygen::initialize_honest_parties(&test_case.party_share_counts, test_case.threshold);
+        let shares = keygen::initialize_honest_parties(
+            &seed,
+            &test_case.party_share_counts,
+            test_case.threshold,
+        );
         execute_test_case(shares, test_case);
     }
 }
 
-pub fn single_fault_test_case_list<K, P>() -> Vec<SingleFaulterTestCase<K, P>> {
+#[test]
+fn single_faults_sign() {
+    set_up_logs();
+    let seed = TestSeed::from_env();
+
+    // all test cases share the same keygen: 2 parties, 2 shares per party
+    let party_share_counts = PartyShareCounts::from_vec(vec![2, 2]).unwrap();
+    let threshold = 2;
+    let secret_key_shares = execute::execute_protocol(keygen::initialize_honest_parties(
+        &seed,
+        &party_share_counts,
+        threshold,
+    ))
+    .unwrap()
+    .map(|output| match output {
+        Protocol::NotDone(_) => panic!("share not done yet"),
+        Protocol::Done(result) => result.expect("share finished with error"),
+    });
+
+    // every party participates in sign so that sign ids coincide with keygen ids
+    let mut sign_parties = SignParties::with_max_size(party_share_counts.party_count());
+    for (party_id, _) in party_share_counts.iter() {
+        sign_parties.add(party_id).unwrap();
+    }
+    let keygen_share_ids = VecMap::<SignShareId, _>::from_vec(
+        party_share_counts.share_id_subset(&sign_parties).unwrap(),
+    );
+    let msg_to_sign = MessageDigest::try_from(&[42; 32][..]).unwrap();
+
+    // sign round 1 sends both bcasts and p2ps
+    for test_case in single_fault_test_case_list(1) {
+        info!(
+            "test: target_msg [{:?}], fault_type [{:?}]",
+            test_case.msg, test_case.fault_type
+        );
+        let shares = keygen_share_ids.clone().map(|keygen_share_id| {
+            let secret_key_share = secret_key_shares.get(keygen_share_id).unwrap();
+            new_sign(
+                secret_key_share.group(),
+                secret_key_share.share(),
+                &sign_parties,
+                &msg_to_sign,
+                tofn::gg20::sign::malicious::Behaviour::Honest,
+            )
+            .unwrap()
+        });
+        execute_test_case(shares, test_case);
+    }
+}
+
+pub fn single_fault_test_case_list<K, P>(round: usize) -> Vec<SingleFaulterTestCase<K, P>> {
     let zero = TypedUsize::from_usize(0);
     vec![
-        single_fault_test_case(Bcast, Timeout),
-        single_fault_test_case(P2p { to: zero }, Timeout),
-        single_fault_test_case(Bcast, Corruption),
-        single_fault_test_case(P2p { to: zero }, Corruption),
-        single_fault_test_case(Bcast, Duplicate),
-        single_fault_test_case(P2p { to: zero }, Duplicate),
+        single_fault_test_case(round, Bcast, Timeout),
+        single_fault_test_case(round, P2p { to: zero }, Timeout),
+        single_fault_test_case(round, Bcast, Corruption),
+        single_fault_test_case(round, P2p { to: zero }, Corruption),
+        single_fault_test_case(round, Bcast, Duplicate),
+        single_fault_test_case(round, P2p { to: zero }, Duplicate),
+        single_fault_test_case(round, Bcast, PartialDelivery),
+        single_fault_test_case(round, P2p { to: zero }, PartialDelivery),
+    ]
+}
+
+/// Messages that are well-formed but sent in the wrong round or on behalf of the wrong party
+pub fn out_of_place_test_case_list<K, P>() -> Vec<SingleFaulterTestCase<K, P>> {
+    vec![
+        single_fault_test_case(2, Bcast, Replay),
+        single_fault_test_case(3, Bcast, FutureRound),
+        single_fault_test_case(2, Bcast, Impersonate),
     ]
 }
 
 fn single_fault_test_case<K, P>(
+    round: usize,
     msg: MsgType<K>,
     fault_type: FaultType,
 ) -> SingleFaulterTestCase<K, P> {
     // 2 parties, 2 shares per party
     // share 3 (party 1) is malicious
-    // fault in round 2
     let faulter_share_id = TypedUsize::from_usize(3);
     let faulter_party_id = TypedUsize::from_usize(1);
     let mut faulters = FillVecMap::with_size(2);
     let fault = match fault_type {
-        FaultType::Timeout => Fault::MissingMessage,
+        FaultType::Timeout | FaultType::PartialDelivery => Fault::MissingMessage,
         _ => Fault::CorruptedMessage,
     };
     faulters.set(faulter_party_id, fault).unwrap();
@@ -67,7 +142,7 @@ fn single_fault_test_case<K, P>(
         threshold: 2,
         faulter_share_id,
         faulter_party_id,
-        round: 2,
+        round,
         msg,
         fault_type,
         expected_honest_output: faulters,
@@ -91,10 +166,49 @@ pub enum FaultType {
     Timeout,
     Corruption,
     Duplicate,
+    /// Deliver the message only to the first half of all shares
+    PartialDelivery,
+    /// Resend the faulter's message from the previous round
+    Replay,
+    /// Run ahead and send this round's message during the previous round
+    FutureRound,
+    /// Resend share 0's message verbatim as if it were the faulter's own
+    Impersonate,
+}
+
+impl<K, P> SingleFaulterTestCase<K, P> {
+    /// Does share `to` receive message `msg` sent by `from` in round `round`?
+    fn delivers(
+        &self,
+        round: usize,
+        from: TypedUsize<K>,
+        msg: MsgType<K>,
+        to: TypedUsize<K>,
+    ) -> bool
+    where
+        K: Copy,
+    {
+        if round != self.round || from != self.faulter_share_id || !same_msg(msg, self.msg) {
+            return true;
+        }
+        match self.fault_type {
+            Timeout => false,
+            PartialDelivery => to.as_usize() < self.party_share_counts.total_share_count() / 2,
+            _ => true,
+        }
+    }
+}
+
+fn same_msg<K>(a: MsgType<K>, b: MsgType<K>) -> bool {
+    match (a, b) {
+        (Bcast, Bcast) => true,
+        (P2p { to: a }, P2p { to: b }) => a == b,
+        _ => false,
+    }
 }
 
-fn execute_test_case<F, K, P, const MAX_MSG_IN_LEN: usize>(
-    shares: VecMap<K, Protocol<F, K, P, MAX_MSG_IN_LEN>>,
+fn execute_test_case<F, K, P>(
+    shares: VecMap<K, Protocol<F, K, P>>,
     test_case: SingleFaulterTestCase<K, P>,
 ) where
     K: PartialEq + core::fmt::Debug + Clone + Copy, // TODO can't quite escape ugly trait bounds :(
@@ -103,12 +217,26 @@ fn execute_test_case<F, K, P, const MAX_MSG_IN_LEN: usize>(
     let shares = execute_protocol(shares, &test_case).expect("internal tofn error");
 
     // TEST: honest parties finished and produced the expected output
+    // exception: under partial delivery, recipients of the faulty message
+    // may have moved on to the next round without noticing anything wrong
+    let mut accusers = 0;
     for (i, party) in shares.iter() {
         if i != test_case.faulter_share_id {
             let result = match party {
+                Protocol::NotDone(_)
+                    if test_case.delivers(
+                        test_case.round,
+                        test_case.faulter_share_id,
+                        test_case.msg,
+                        i,
+                    ) && matches!(test_case.fault_type, PartialDelivery) =>
+                {
+                    continue;
+                }
                 Protocol::NotDone(_) => panic!("honest party {} not done yet", i),
                 Protocol::Done(result) => result,
             };
+            accusers += 1;
             match result {
                 Ok(_) => panic!("expect failure, got success"),
                 Err(got_faulters) => {
@@ -117,28 +245,31 @@ fn execute_test_case<F, K, P, const MAX_MSG_IN_LEN: usize>(
             }
         }
     }
+    assert!(accusers > 0, "no honest party detected the faulter");
 }
 
-pub fn execute_protocol<F, K, P, const MAX_MSG_IN_LEN: usize>(
-    mut parties: VecMap<K, Protocol<F, K, P, MAX_MSG_IN_LEN>>,
+pub fn execute_protocol<F, K, P>(
+    mut parties: VecMap<K, Protocol<F, K, P>>,
     test_case: &SingleFaulterTestCase<K, P>,
-) -> TofnResult<VecMap<K, Protocol<F, K, P, MAX_MSG_IN_LEN>>>
+) -> TofnResult<VecMap<K, Protocol<F, K, P>>>
 where
     K: Clone + Copy,
 {
     let mut current_round = 0;
+    let mut faulter_prev_bcast = None;
     while nobody_done(&parties) {
         current_round += 1;
-        parties = next_round(parties, test_case, current_round)?;
+        parties = next_round(parties, test_case, current_round, &mut faulter_prev_bcast)?;
     }
     Ok(parties)
 }
 
-fn next_round<F, K, P, const MAX_MSG_IN_LEN: usize>(
-    parties: VecMap<K, Protocol<F, K, P, MAX_MSG_IN_LEN>>,
+fn next_round<F, K, P>(
+    parties: VecMap<K, Protocol<F, K, P>>,
     test_case: &SingleFaulterTestCase<K, P>,
     current_round: usize,
-) -> TofnResult<VecMap<K, Protocol<F, K, P, MAX_MSG_IN_LEN>>>
+    faulter_prev_bcast: &mut Option<BytesVec>,
+) -> TofnResult<VecMap<K, Protocol<F, K, P>>>
 where
     K: Clone + Copy,
 {
@@ -171,42 +302,68 @@ where
 
     // deliver bcasts if present
     if let Some(bcasts) = bcasts {
+        let victim_bcast = bcasts.get(TypedUsize::from_usize(0))?.clone();
+        let prev_bcast =
+            faulter_prev_bcast.replace(bcasts.get(test_case.faulter_share_id)?.clone());
+
         for (from, bytes) in bcasts.into_iter() {
             let from_party_id = test_case.party_share_counts.share_to_party_id(from)?;
 
-            // inject timeout or duplicate fault
+            // inject replay or impersonation fault
             if current_round == test_case.round
                 && test_case.faulter_share_id == from
                 && matches!(test_case.msg, Bcast)
             {
-                match test_case.fault_type {
-                    Timeout => {
-                        info!(
-                            "drop bcast from share_id {} in round {}",
-                            test_case.faulter_share_id, test_case.round
-                        );
-                        continue;
-                    }
-                    Duplicate => {
-                        info!(
-                            "duplicate bcast from share_id {} in round {}",
-                            test_case.faulter_share_id, test_case.round
-                        );
-                        for (_, round) in rounds.iter_mut() {
-                            round.msg_in(from_party_id, &bytes)?;
-                        }
+                let injected = match test_case.fault_type {
+                    Replay => Some(
+                        prev_bcast
+                            .as_ref()
+                            .expect("no bcast from the previous round to replay"),
+                    ),
+                    Impersonate => Some(&victim_bcast),
+                    _ => None,
+                };
+                if let Some(injected) = injected {
+                    info!(
+                        "inject {:?} bcast from share_id {} in round {}",
+                        test_case.fault_type, test_case.faulter_share_id, test_case.round
+                    );
+                    for (_, round) in rounds.iter_mut() {
+                        round.msg_in(from_party_id, injected)?;
                     }
-                    _ => (),
                 }
             }
 
-            for (_, round) in rounds.iter_mut() {
-                round.msg_in(from_party_id, &bytes)?;
+            // inject duplicate fault
+            if current_round == test_case.round
+                && test_case.faulter_share_id == from
+                && matches!(test_case.msg, Bcast)
+                && matches!(test_case.fault_type, Duplicate)
+            {
+                info!(
+                    "duplicate bcast from share_id {} in round {}",
+                    test_case.faulter_share_id, test_case.round
+                );
+                for (_, round) in rounds.iter_mut() {
+                    round.msg_in(from_party_id, &bytes)?;
+                }
+            }
+
+            // inject timeout or partial delivery fault
+            for (to, round) in rounds.iter_mut() {
+                if test_case.delivers(current_round, from, Bcast, to) {
+                    round.msg_in(from_party_id, &bytes)?;
+                } else {
+                    info!(
+                        "drop bcast from share_id {} to {} in round {}",
+                        from, to, current_round
+                    );
+                }
             }
         }
     } else if current_round == test_case.round
         && matches!(test_case.msg, Bcast)
-        && matches!(test_case.fault_type, Timeout)
+        && matches!(test_case.fault_type, Timeout | PartialDelivery)
     {
         panic!("round {} has no bcasts to drop", test_case.round);
     }
@@ -222,47 +379,70 @@ where
         for (from, p2ps) in all_p2ps.into_iter() {
             let from_party_id = test_case.party_share_counts.share_to_party_id(from)?;
             for (to, bytes) in p2ps {
-                // inject timeout or duplicate fault
-                if current_round == test_case.round && test_case.faulter_share_id == from {
-                    if let P2p { to: victim } = test_case.msg {
-                        if victim == to {
-                            match test_case.fault_type {
-                                Timeout => {
-                                    info!(
-                                        "drop p2p from share_id {} to {} in round {}",
-                                        test_case.faulter_share_id, victim, test_case.round
-                                    );
-                                    continue;
-                                }
-                                Duplicate => {
-                                    info!(
-                                        "duplicate p2p from share_id {} to {} in round {}",
-                                        test_case.faulter_share_id, victim, test_case.round
-                                    );
-                                    for (_, round) in rounds.iter_mut() {
-                                        round.msg_in(from_party_id, &bytes)?;
-                                    }
-                                }
-                                _ => (),
-                            }
-                        }
+                // inject duplicate fault
+                if current_round == test_case.round
+                    && test_case.faulter_share_id == from
+                    && same_msg(P2p { to }, test_case.msg)
+                    && matches!(test_case.fault_type, Duplicate)
+                {
+                    info!(
+                        "duplicate p2p from share_id {} to {} in round {}",
+                        test_case.faulter_share_id, to, test_case.round
+                    );
+                    for (_, round) in rounds.iter_mut() {
+                        round.msg_in(from_party_id, &bytes)?;
                     }
                 }
 
-                for (_, round) in rounds.iter_mut() {
-                    round.msg_in(from_party_id, &bytes)?;
+                // inject timeout or partial delivery fault
+                for (recipient, round) in rounds.iter_mut() {
+                    if test_case.delivers(current_round, from, P2p { to }, recipient) {
+                        round.msg_in(from_party_id, &bytes)?;
+                    } else {
+                        info!(
+                            "drop p2p from share_id {} to {} at share_id {} in round {}",
+                            from, to, recipient, current_round
+                        );
+                    }
                 }
             }
         }
     } else if current_round == test_case.round
         && matches!(test_case.msg, P2p { to: _ })
-        && matches!(test_case.fault_type, Timeout)
+        && matches!(test_case.fault_type, Timeout | PartialDelivery)
     {
         panic!("round {} has no p2ps to drop", test_case.round);
     }
 
+    // inject future-round fault: let the faulter run ahead into the next round
+    // and deliver its next-round bcast to everyone still in this round
+    let mut rounds: Vec<_> = rounds.into_iter().collect();
+    let faulter_next =
+        if current_round + 1 == test_case.round && matches!(test_case.fault_type, FutureRound) {
+            let index = test_case.faulter_share_id.as_usize();
+            let (_, faulter_round) = rounds.remove(index);
+            let faulter_next = faulter_round.execute_next_round()?;
+            let bytes = match &faulter_next {
+                Protocol::NotDone(round) => round
+                    .bcast_out()
+                    .expect("faulter has no bcast to send ahead")
+                    .clone(),
+                Protocol::Done(_) => panic!("faulter done before round {}", test_case.round),
+            };
+            info!(
+                "send round {} bcast from share_id {} during round {}",
+                test_case.round, test_case.faulter_share_id, current_round
+            );
+            for (_, round) in rounds.iter_mut() {
+                round.msg_in(test_case.faulter_party_id, &bytes)?;
+            }
+            Some((index, faulter_next))
+        } else {
+            None
+        };
+
     // compute next round's parties
-    rounds
+    let mut next_parties = rounds
         .into_iter()
         .map(|(i, round)| {
             if round.expecting_more_msgs_this_round() {
@@ -273,5 +453,9 @@ where
             }
             round.execute_next_round()
         })
-        .collect::<TofnResult<_>>()
+        .collect::<TofnResult<Vec<_>>>()?;
+    if let Some((index, faulter_next)) = faulter_next {
+        next_parties.insert(index, faulter_next);
+    }
+    Ok(VecMap::from_vec(next_parties))
 }
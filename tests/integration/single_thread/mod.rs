@@ -1,6 +1,6 @@
 use core::convert::TryFrom;
 
-use crate::common;
+use crate::common::{self, TestSeed};
 use ecdsa::hazmat::VerifyPrimitive;
 use execute::*;
 use k256::PublicKey;
@@ -38,6 +38,7 @@ fn set_up_logs() {
 // #[traced_test]
 fn basic_correctness() {
     set_up_logs();
+    let seed = TestSeed::from_env();
 
     // keygen
     let party_share_counts = PartyShareCounts::from_vec(vec![1, 2, 3, 4]).unwrap(); // 10 total shares
@@ -56,7 +57,8 @@ fn basic_correctness() {
     );
 
     debug!("keygen...");
-    let keygen_shares = common::keygen::initialize_honest_parties(&party_share_counts, threshold);
+    let keygen_shares =
+        common::keygen::initialize_honest_parties(&seed, &party_share_counts, threshold);
     let keygen_share_outputs = execute_protocol(keygen_shares).expect("internal tofn error");
     let secret_key_shares: VecMap<keygen::KeygenShareId, keygen::SecretKeyShare> =
         keygen_share_outputs.map2(|(keygen_share_id, keygen_share)| match keygen_share {
@@ -105,7 +107,7 @@ fn basic_correctness() {
         .is_ok());
 }
 
-mod execute;
+pub(crate) mod execute;
 
 #[cfg(feature = "malicious")]
 mod malicious;